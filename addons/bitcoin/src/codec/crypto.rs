@@ -0,0 +1,230 @@
+use std::str::FromStr;
+
+use bitcoin::bip32::{DerivationPath, Xpriv};
+use bitcoin::key::{CompressedPublicKey, Keypair, PrivateKey, Secp256k1};
+use bitcoin::secp256k1::SecretKey;
+use bitcoin::{Address, Network, NetworkKind};
+use txtx_addon_kit::hex;
+
+/// Kind of address controlled by a `btc::secret_key` signer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AddressType {
+    /// Native segwit v0 address, derived following BIP-84
+    P2wpkh,
+    /// Taproot key path address, derived following BIP-86
+    P2tr,
+}
+
+impl AddressType {
+    pub fn from_some_str(value: Option<&str>) -> Result<Self, String> {
+        match value.map(|v| v.to_ascii_lowercase()).as_deref() {
+            None | Some("p2wpkh") | Some("bip84") => Ok(AddressType::P2wpkh),
+            Some("p2tr") | Some("bip86") => Ok(AddressType::P2tr),
+            Some(other) => {
+                Err(format!("unsupported address type '{other}', expected 'p2wpkh' or 'p2tr'"))
+            }
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        match self {
+            AddressType::P2wpkh => "p2wpkh".into(),
+            AddressType::P2tr => "p2tr".into(),
+        }
+    }
+
+    /// The derivation path of the first receiving address of the first account,
+    /// `m/84'/coin'/0'/0/0` for p2wpkh and `m/86'/coin'/0'/0/0` for p2tr.
+    pub fn default_derivation_path(&self, network: Network) -> String {
+        let purpose = match self {
+            AddressType::P2wpkh => 84,
+            AddressType::P2tr => 86,
+        };
+        let coin_type = match NetworkKind::from(network) {
+            NetworkKind::Main => 0,
+            NetworkKind::Test => 1,
+        };
+        format!("m/{purpose}'/{coin_type}'/0'/0/0")
+    }
+
+    pub fn address(&self, secret_key: &SecretKey, network: Network) -> Address {
+        let secp = Secp256k1::new();
+        match self {
+            AddressType::P2wpkh => {
+                let public_key = CompressedPublicKey(secret_key.public_key(&secp));
+                Address::p2wpkh(&public_key, network)
+            }
+            AddressType::P2tr => {
+                let (internal_key, _) =
+                    Keypair::from_secret_key(&secp, secret_key).x_only_public_key();
+                Address::p2tr(&secp, internal_key, None, network)
+            }
+        }
+    }
+}
+
+pub fn parse_network(value: Option<&str>) -> Result<Network, String> {
+    match value.map(|v| v.to_ascii_lowercase()).as_deref() {
+        None | Some("mainnet") | Some("bitcoin") => Ok(Network::Bitcoin),
+        Some("testnet") => Ok(Network::Testnet),
+        Some("signet") => Ok(Network::Signet),
+        Some("regtest") => Ok(Network::Regtest),
+        Some(other) => Err(format!(
+            "unsupported network '{other}', expected 'mainnet', 'testnet', 'signet' or 'regtest'"
+        )),
+    }
+}
+
+pub fn parse_address(value: &str, network: Network) -> Result<Address, String> {
+    Address::from_str(value)
+        .map_err(|e| format!("invalid address '{value}': {e}"))?
+        .require_network(network)
+        .map_err(|e| format!("invalid address '{value}': {e}"))
+}
+
+/// Parses a secret key, provided either in WIF or as hex-encoded bytes.
+pub fn secret_key_from_str(value: &str, network: Network) -> Result<SecretKey, String> {
+    if let Ok(private_key) = PrivateKey::from_wif(value) {
+        if private_key.network != NetworkKind::from(network) {
+            return Err(format!("the WIF secret key was not encoded for the network {network}"));
+        }
+        if !private_key.compressed {
+            return Err("uncompressed WIF secret keys are not supported".into());
+        }
+        return Ok(private_key.inner);
+    }
+    let stripped = value.strip_prefix("0x").unwrap_or(value);
+    let bytes = hex::decode(stripped)
+        .map_err(|_| "secret key must be encoded in WIF or hex".to_string())?;
+    SecretKey::from_slice(&bytes).map_err(|e| format!("invalid secret key: {e}"))
+}
+
+pub fn secret_key_from_mnemonic(
+    mnemonic: &str,
+    derivation_path: Option<&str>,
+    password: Option<&str>,
+    address_type: &AddressType,
+    network: Network,
+) -> Result<SecretKey, String> {
+    let mnemonic =
+        bip39::Mnemonic::parse(mnemonic).map_err(|e| format!("invalid mnemonic: {e}"))?;
+    let seed = mnemonic.to_seed(password.unwrap_or(""));
+    let derivation_path = match derivation_path {
+        Some(path) => path.to_string(),
+        None => address_type.default_derivation_path(network),
+    };
+    let derivation_path = DerivationPath::from_str(&derivation_path)
+        .map_err(|e| format!("invalid derivation path '{derivation_path}': {e}"))?;
+
+    let secp = Secp256k1::new();
+    let master =
+        Xpriv::new_master(network, &seed).map_err(|e| format!("failed to derive key: {e}"))?;
+    let child = master
+        .derive_priv(&secp, &derivation_path)
+        .map_err(|e| format!("failed to derive key: {e}"))?;
+    Ok(child.private_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn it_derives_bip84_and_bip86_addresses() {
+        // Test vectors of the first receiving address of BIP-84 and BIP-86
+        let cases = vec![
+            (
+                AddressType::P2wpkh,
+                "m/84'/0'/0'/0/0",
+                "KyZpNDKnfs94vbrwhJneDi77V6jF64PWPF8x5cdJb8ifgg2DUc9d",
+                "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            ),
+            (
+                AddressType::P2tr,
+                "m/86'/0'/0'/0/0",
+                "KyRv5iFPHG7iB5E4CqvMzH3WFJVhbfYK4VY7XAedd9Ys69mEsPLQ",
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            ),
+        ];
+        for (address_type, derivation_path, wif, address) in cases {
+            assert_eq!(address_type.default_derivation_path(Network::Bitcoin), derivation_path);
+            let secret_key =
+                secret_key_from_mnemonic(MNEMONIC, None, None, &address_type, Network::Bitcoin)
+                    .unwrap();
+            assert_eq!(secret_key, secret_key_from_str(wif, Network::Bitcoin).unwrap());
+            assert_eq!(
+                address_type.address(&secret_key, Network::Bitcoin).to_string(),
+                address,
+                "address of {}",
+                derivation_path
+            );
+        }
+    }
+
+    #[test]
+    fn it_derives_keys_from_explicit_paths_and_passwords() {
+        let default_key =
+            secret_key_from_mnemonic(MNEMONIC, None, None, &AddressType::P2wpkh, Network::Bitcoin)
+                .unwrap();
+        let explicit_key = secret_key_from_mnemonic(
+            MNEMONIC,
+            Some("m/84'/0'/0'/0/0"),
+            None,
+            &AddressType::P2tr,
+            Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(default_key, explicit_key);
+
+        let protected_key = secret_key_from_mnemonic(
+            MNEMONIC,
+            None,
+            Some("password"),
+            &AddressType::P2wpkh,
+            Network::Bitcoin,
+        )
+        .unwrap();
+        assert_ne!(default_key, protected_key);
+
+        assert_eq!(AddressType::P2tr.default_derivation_path(Network::Testnet), "m/86'/1'/0'/0/0");
+        assert!(secret_key_from_mnemonic(
+            "abandon abandon",
+            None,
+            None,
+            &AddressType::P2wpkh,
+            Network::Bitcoin
+        )
+        .is_err());
+        assert!(secret_key_from_mnemonic(
+            MNEMONIC,
+            Some("m/84'/zero'"),
+            None,
+            &AddressType::P2wpkh,
+            Network::Bitcoin
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn it_parses_wif_and_hex_secret_keys() {
+        let wif = "KyZpNDKnfs94vbrwhJneDi77V6jF64PWPF8x5cdJb8ifgg2DUc9d";
+        let secret_key = secret_key_from_str(wif, Network::Bitcoin).unwrap();
+        let hex_key = hex::encode(secret_key.secret_bytes());
+        assert_eq!(secret_key_from_str(&hex_key, Network::Bitcoin).unwrap(), secret_key);
+        assert_eq!(
+            secret_key_from_str(&format!("0x{hex_key}"), Network::Bitcoin).unwrap(),
+            secret_key
+        );
+
+        // The network of the WIF encoding must match
+        let testnet_wif = PrivateKey::new(secret_key, Network::Testnet).to_wif();
+        assert!(secret_key_from_str(&testnet_wif, Network::Bitcoin).is_err());
+        assert_eq!(secret_key_from_str(&testnet_wif, Network::Regtest).unwrap(), secret_key);
+
+        let uncompressed_wif = PrivateKey::new_uncompressed(secret_key, Network::Bitcoin).to_wif();
+        assert!(secret_key_from_str(&uncompressed_wif, Network::Bitcoin).is_err());
+        assert!(secret_key_from_str("not a key", Network::Bitcoin).is_err());
+    }
+}
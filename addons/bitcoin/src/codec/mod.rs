@@ -1,28 +1,312 @@
+pub mod crypto;
+pub mod psbt;
+
 pub enum BitcoinOpcode {
     // Constants
     Op0,
     OpPushData,
     OpPushData1,
+    OpPushData2,
+    OpPushData4,
+    Op1Negate,
+    /// `OP_1` to `OP_16`
+    OpN(u8),
+    // Control Flow Opcodes
+    OpNop,
+    OpIf,
+    OpNotIf,
+    OpElse,
+    OpEndIf,
+    OpVerify,
+    OpReturn,
     // Stack Opcodes
+    OpToAltStack,
+    OpFromAltStack,
+    Op2Drop,
+    Op2Dup,
+    Op3Dup,
+    Op2Over,
+    Op2Rot,
+    Op2Swap,
+    OpIfDup,
+    OpDepth,
+    OpDrop,
     OpDup,
+    OpNip,
+    OpOver,
+    OpPick,
+    OpRoll,
+    OpRot,
+    OpSwap,
+    OpTuck,
+    // Splice Opcodes
+    OpSize,
     // Bitwise Logic Opcodes
+    OpEqual,
     OpEqualVerify,
+    // Arithmetic Opcodes
+    Op1Add,
+    Op1Sub,
+    OpNegate,
+    OpAbs,
+    OpNot,
+    Op0NotEqual,
+    OpAdd,
+    OpSub,
+    OpBoolAnd,
+    OpBoolOr,
+    OpNumEqual,
+    OpNumEqualVerify,
+    OpNumNotEqual,
+    OpLessThan,
+    OpGreaterThan,
+    OpLessThanOrEqual,
+    OpGreaterThanOrEqual,
+    OpMin,
+    OpMax,
+    OpWithin,
     // Crypto Opcodes
+    OpRipemd160,
+    OpSha1,
+    OpSha256,
     OpHash160,
+    OpHash256,
+    OpCodeSeparator,
     OpCheckSig,
-    // Control Flow Opcodes
+    OpCheckSigVerify,
+    OpCheckMultiSig,
+    OpCheckMultiSigVerify,
+    OpCheckSigAdd,
+    // Locktime Opcodes
+    OpCheckLockTimeVerify,
+    OpCheckSequenceVerify,
+    // Reserved Opcodes
+    OpNop1,
+    /// `OP_NOP4` to `OP_NOP10`
+    OpNopN(u8),
 }
 
 impl BitcoinOpcode {
+    /// Returns the encoding of the opcode.
+    ///
+    /// Panics if the parameter of `OpN` or `OpNopN` is out of range: use `try_get_code`
+    /// when the parameter was not checked beforehand.
     pub fn get_code(&self) -> Vec<u8> {
-        match self {
-            BitcoinOpcode::Op0 => vec![0],
+        self.try_get_code().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns the encoding of the opcode, or an error if the parameter of `OpN` or `OpNopN`
+    /// is out of range.
+    pub fn try_get_code(&self) -> Result<Vec<u8>, String> {
+        let code = match self {
+            BitcoinOpcode::Op0 => vec![0x00],
             BitcoinOpcode::OpPushData => vec![],
-            BitcoinOpcode::OpPushData1 => vec![196],
-            BitcoinOpcode::OpDup => vec![118],
-            BitcoinOpcode::OpEqualVerify => vec![136],
-            BitcoinOpcode::OpHash160 => vec![169],
-            BitcoinOpcode::OpCheckSig => vec![172],
+            BitcoinOpcode::OpPushData1 => vec![0x4c],
+            BitcoinOpcode::OpPushData2 => vec![0x4d],
+            BitcoinOpcode::OpPushData4 => vec![0x4e],
+            BitcoinOpcode::Op1Negate => vec![0x4f],
+            BitcoinOpcode::OpN(n) => {
+                if !(1..=16).contains(n) {
+                    return Err(format!("OP_{n} is not defined: OP_N is only defined for 1 to 16"));
+                }
+                vec![0x50 + n]
+            }
+            BitcoinOpcode::OpNop => vec![0x61],
+            BitcoinOpcode::OpIf => vec![0x63],
+            BitcoinOpcode::OpNotIf => vec![0x64],
+            BitcoinOpcode::OpElse => vec![0x67],
+            BitcoinOpcode::OpEndIf => vec![0x68],
+            BitcoinOpcode::OpVerify => vec![0x69],
+            BitcoinOpcode::OpReturn => vec![0x6a],
+            BitcoinOpcode::OpToAltStack => vec![0x6b],
+            BitcoinOpcode::OpFromAltStack => vec![0x6c],
+            BitcoinOpcode::Op2Drop => vec![0x6d],
+            BitcoinOpcode::Op2Dup => vec![0x6e],
+            BitcoinOpcode::Op3Dup => vec![0x6f],
+            BitcoinOpcode::Op2Over => vec![0x70],
+            BitcoinOpcode::Op2Rot => vec![0x71],
+            BitcoinOpcode::Op2Swap => vec![0x72],
+            BitcoinOpcode::OpIfDup => vec![0x73],
+            BitcoinOpcode::OpDepth => vec![0x74],
+            BitcoinOpcode::OpDrop => vec![0x75],
+            BitcoinOpcode::OpDup => vec![0x76],
+            BitcoinOpcode::OpNip => vec![0x77],
+            BitcoinOpcode::OpOver => vec![0x78],
+            BitcoinOpcode::OpPick => vec![0x79],
+            BitcoinOpcode::OpRoll => vec![0x7a],
+            BitcoinOpcode::OpRot => vec![0x7b],
+            BitcoinOpcode::OpSwap => vec![0x7c],
+            BitcoinOpcode::OpTuck => vec![0x7d],
+            BitcoinOpcode::OpSize => vec![0x82],
+            BitcoinOpcode::OpEqual => vec![0x87],
+            BitcoinOpcode::OpEqualVerify => vec![0x88],
+            BitcoinOpcode::Op1Add => vec![0x8b],
+            BitcoinOpcode::Op1Sub => vec![0x8c],
+            BitcoinOpcode::OpNegate => vec![0x8f],
+            BitcoinOpcode::OpAbs => vec![0x90],
+            BitcoinOpcode::OpNot => vec![0x91],
+            BitcoinOpcode::Op0NotEqual => vec![0x92],
+            BitcoinOpcode::OpAdd => vec![0x93],
+            BitcoinOpcode::OpSub => vec![0x94],
+            BitcoinOpcode::OpBoolAnd => vec![0x9a],
+            BitcoinOpcode::OpBoolOr => vec![0x9b],
+            BitcoinOpcode::OpNumEqual => vec![0x9c],
+            BitcoinOpcode::OpNumEqualVerify => vec![0x9d],
+            BitcoinOpcode::OpNumNotEqual => vec![0x9e],
+            BitcoinOpcode::OpLessThan => vec![0x9f],
+            BitcoinOpcode::OpGreaterThan => vec![0xa0],
+            BitcoinOpcode::OpLessThanOrEqual => vec![0xa1],
+            BitcoinOpcode::OpGreaterThanOrEqual => vec![0xa2],
+            BitcoinOpcode::OpMin => vec![0xa3],
+            BitcoinOpcode::OpMax => vec![0xa4],
+            BitcoinOpcode::OpWithin => vec![0xa5],
+            BitcoinOpcode::OpRipemd160 => vec![0xa6],
+            BitcoinOpcode::OpSha1 => vec![0xa7],
+            BitcoinOpcode::OpSha256 => vec![0xa8],
+            BitcoinOpcode::OpHash160 => vec![0xa9],
+            BitcoinOpcode::OpHash256 => vec![0xaa],
+            BitcoinOpcode::OpCodeSeparator => vec![0xab],
+            BitcoinOpcode::OpCheckSig => vec![0xac],
+            BitcoinOpcode::OpCheckSigVerify => vec![0xad],
+            BitcoinOpcode::OpCheckMultiSig => vec![0xae],
+            BitcoinOpcode::OpCheckMultiSigVerify => vec![0xaf],
+            BitcoinOpcode::OpNop1 => vec![0xb0],
+            BitcoinOpcode::OpCheckLockTimeVerify => vec![0xb1],
+            BitcoinOpcode::OpCheckSequenceVerify => vec![0xb2],
+            BitcoinOpcode::OpNopN(n) => {
+                if !(4..=10).contains(n) {
+                    return Err(format!(
+                        "OP_NOP{n} is not defined: OP_NOPN is only defined for 4 to 10"
+                    ));
+                }
+                vec![0xb3 + (n - 4)]
+            }
+            BitcoinOpcode::OpCheckSigAdd => vec![0xba],
+        };
+        Ok(code)
+    }
+}
+
+/// Encodes the instructions pushing `data` onto the stack, using the smallest push opcode.
+pub fn encode_push_data(data: &[u8]) -> Vec<u8> {
+    let mut bytes = match data.len() {
+        len @ 0..=75 => vec![len as u8],
+        len @ 76..=0xff => {
+            let mut bytes = BitcoinOpcode::OpPushData1.get_code();
+            bytes.push(len as u8);
+            bytes
+        }
+        len @ 0x100..=0xffff => {
+            let mut bytes = BitcoinOpcode::OpPushData2.get_code();
+            bytes.extend((len as u16).to_le_bytes());
+            bytes
+        }
+        len => {
+            let mut bytes = BitcoinOpcode::OpPushData4.get_code();
+            bytes.extend((len as u32).to_le_bytes());
+            bytes
+        }
+    };
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+/// Encodes the instructions pushing the number `n` onto the stack, as a minimally encoded
+/// script number (`OP_0`, `OP_1NEGATE` and `OP_1` to `OP_16` are used when possible).
+pub fn encode_push_number(n: i64) -> Vec<u8> {
+    match n {
+        0 => BitcoinOpcode::Op0.get_code(),
+        -1 => BitcoinOpcode::Op1Negate.get_code(),
+        1..=16 => BitcoinOpcode::OpN(n as u8).get_code(),
+        _ => encode_push_data(&encode_script_number(n)),
+    }
+}
+
+/// Encodes a number as a little-endian, sign-magnitude script number.
+pub fn encode_script_number(n: i64) -> Vec<u8> {
+    if n == 0 {
+        return vec![];
+    }
+    let negative = n < 0;
+    let mut abs = n.unsigned_abs();
+    let mut bytes = vec![];
+    while abs > 0 {
+        bytes.push((abs & 0xff) as u8);
+        abs >>= 8;
+    }
+    // The most significant bit carries the sign: add a byte if it's already in use
+    if bytes.last().unwrap() & 0x80 != 0 {
+        bytes.push(if negative { 0x80 } else { 0x00 });
+    } else if negative {
+        *bytes.last_mut().unwrap() |= 0x80;
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_encodes_push_numbers() {
+        let cases: Vec<(i64, Vec<u8>)> = vec![
+            (0, vec![0x00]),
+            (-1, vec![0x4f]),
+            (17, vec![0x01, 0x11]),
+            (-2, vec![0x01, 0x82]),
+            (128, vec![0x02, 0x80, 0x00]),
+            (500_000, vec![0x03, 0x20, 0xa1, 0x07]),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(encode_push_number(n), expected, "push of {}", n);
+        }
+        for n in 1..=16 {
+            assert_eq!(encode_push_number(n), vec![0x50 + n as u8], "push of {}", n);
+        }
+    }
+
+    #[test]
+    fn it_encodes_script_numbers() {
+        let cases: Vec<(i64, Vec<u8>)> = vec![
+            (0, vec![]),
+            (1, vec![0x01]),
+            (127, vec![0x7f]),
+            // The sign bit of the most significant byte is in use: a byte is added
+            (128, vec![0x80, 0x00]),
+            (255, vec![0xff, 0x00]),
+            (256, vec![0x00, 0x01]),
+            (-1, vec![0x81]),
+            (-127, vec![0xff]),
+            (-128, vec![0x80, 0x80]),
+            (-255, vec![0xff, 0x80]),
+            (i64::MAX, vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]),
+            (i64::MIN + 1, vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            (i64::MIN, vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x80]),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(encode_script_number(n), expected, "script number {}", n);
+        }
+    }
+
+    #[test]
+    fn it_encodes_parameterized_opcodes() {
+        assert_eq!(BitcoinOpcode::OpN(1).get_code(), vec![0x51]);
+        assert_eq!(BitcoinOpcode::OpN(16).get_code(), vec![0x60]);
+        assert_eq!(BitcoinOpcode::OpNopN(4).get_code(), vec![0xb3]);
+        assert_eq!(BitcoinOpcode::OpNopN(10).get_code(), vec![0xb9]);
+
+        for (opcode, name) in [
+            (BitcoinOpcode::OpN(0), "OP_0"),
+            (BitcoinOpcode::OpN(17), "OP_17"),
+            (BitcoinOpcode::OpN(255), "OP_255"),
+            (BitcoinOpcode::OpNopN(3), "OP_NOP3"),
+            (BitcoinOpcode::OpNopN(11), "OP_NOP11"),
+        ] {
+            assert!(opcode.try_get_code().is_err(), "{} is out of range", name);
         }
+        assert_eq!(
+            BitcoinOpcode::OpN(17).try_get_code().unwrap_err(),
+            "OP_17 is not defined: OP_N is only defined for 1 to 16"
+        );
     }
 }
@@ -0,0 +1,419 @@
+use bitcoin::hashes::Hash;
+use bitcoin::key::{Keypair, Secp256k1, TapTweak};
+use bitcoin::psbt::Psbt;
+use bitcoin::secp256k1::{Message, SecretKey};
+use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::{
+    absolute, transaction, Address, Amount, Network, OutPoint, Script, ScriptBuf, Sequence,
+    Transaction, TxIn, TxOut, Txid, Witness,
+};
+use txtx_addon_kit::types::types::{ObjectType, Value};
+
+/// Virtual size of the version, locktime, segwit marker and in/out counts of a transaction.
+const TX_OVERHEAD_VSIZE: u64 = 11;
+
+#[derive(Debug, Clone)]
+pub struct Utxo {
+    pub txid: Txid,
+    pub vout: u32,
+    pub amount: Amount,
+    pub script_pubkey: ScriptBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnsignedTransfer {
+    pub psbt: Psbt,
+    pub fee: Amount,
+}
+
+fn input_vsize(script_pubkey: &Script) -> u64 {
+    // outpoint, sequence and empty script_sig, plus the discounted witness
+    if script_pubkey.is_p2tr() {
+        58
+    } else {
+        68
+    }
+}
+
+fn output_vsize(script_pubkey: &Script) -> u64 {
+    8 + 1 + script_pubkey.len() as u64
+}
+
+/// Builds a PSBT sending `amount` to `recipient`, funded by the largest `utxos` first.
+/// Change is sent back to `sender`, unless it would be dust, in which case it is added to the fee.
+pub fn build_transfer_psbt(
+    mut utxos: Vec<Utxo>,
+    sender: &Address,
+    recipient: &Address,
+    amount: Amount,
+    fee_rate: u64,
+) -> Result<UnsignedTransfer, String> {
+    let recipient_script = recipient.script_pubkey();
+    let change_script = sender.script_pubkey();
+    if amount < recipient_script.minimal_non_dust() {
+        return Err(format!(
+            "amount ({} sats) is below the dust limit ({} sats)",
+            amount.to_sat(),
+            recipient_script.minimal_non_dust().to_sat()
+        ));
+    }
+
+    utxos.sort_by(|a, b| b.amount.cmp(&a.amount));
+
+    let outputs_vsize = TX_OVERHEAD_VSIZE + output_vsize(&recipient_script);
+    let mut selected = vec![];
+    let mut inputs_vsize = 0;
+    let mut total = Amount::ZERO;
+    let mut change = None;
+    let mut fee = None;
+    for utxo in utxos.into_iter() {
+        inputs_vsize += input_vsize(&utxo.script_pubkey);
+        total += utxo.amount;
+        selected.push(utxo);
+
+        let fee_without_change = Amount::from_sat(fee_rate * (outputs_vsize + inputs_vsize));
+        let fee_with_change =
+            fee_without_change + Amount::from_sat(fee_rate * output_vsize(&change_script));
+        if total >= amount + fee_with_change
+            && total - amount - fee_with_change >= change_script.minimal_non_dust()
+        {
+            change = Some(total - amount - fee_with_change);
+            fee = Some(fee_with_change);
+            break;
+        }
+        if total >= amount + fee_without_change {
+            fee = Some(total - amount);
+            break;
+        }
+    }
+    let Some(fee) = fee else {
+        return Err(format!(
+            "insufficient funds: {} sats available in {} confirmed utxos, at least {} sats needed",
+            total.to_sat(),
+            selected.len(),
+            (amount + Amount::from_sat(fee_rate * (outputs_vsize + inputs_vsize))).to_sat()
+        ));
+    };
+
+    let mut output = vec![TxOut { value: amount, script_pubkey: recipient_script }];
+    if let Some(change) = change {
+        output.push(TxOut { value: change, script_pubkey: change_script });
+    }
+    let unsigned_tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: selected
+            .iter()
+            .map(|utxo| TxIn {
+                previous_output: OutPoint { txid: utxo.txid, vout: utxo.vout },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            })
+            .collect(),
+        output,
+    };
+
+    let mut psbt =
+        Psbt::from_unsigned_tx(unsigned_tx).map_err(|e| format!("failed to build psbt: {e}"))?;
+    for (input, utxo) in psbt.inputs.iter_mut().zip(selected.into_iter()) {
+        input.witness_utxo = Some(TxOut { value: utxo.amount, script_pubkey: utxo.script_pubkey });
+    }
+    Ok(UnsignedTransfer { psbt, fee })
+}
+
+/// Signs and finalizes every input of the PSBT spending an output locked to `address`,
+/// which must have been derived from `secret_key`. Returns the number of signed inputs.
+pub fn sign_psbt(
+    psbt: &mut Psbt,
+    secret_key: &SecretKey,
+    address: &Address,
+) -> Result<usize, String> {
+    let secp = Secp256k1::new();
+    let script_pubkey = address.script_pubkey();
+    let prevouts =
+        psbt.inputs.iter().map(|input| input.witness_utxo.clone()).collect::<Option<Vec<TxOut>>>();
+
+    let mut cache = SighashCache::new(&psbt.unsigned_tx);
+    let mut signed = 0;
+    for (index, input) in psbt.inputs.iter_mut().enumerate() {
+        let Some(utxo) = input.witness_utxo.as_ref() else {
+            continue;
+        };
+        if utxo.script_pubkey != script_pubkey || input.final_script_witness.is_some() {
+            continue;
+        }
+
+        if script_pubkey.is_p2wpkh() {
+            let sighash = cache
+                .p2wpkh_signature_hash(index, &script_pubkey, utxo.value, EcdsaSighashType::All)
+                .map_err(|e| format!("failed to compute sighash of input {index}: {e}"))?;
+            let message = Message::from_digest(sighash.to_byte_array());
+            let signature = bitcoin::ecdsa::Signature {
+                signature: secp.sign_ecdsa(&message, secret_key),
+                sighash_type: EcdsaSighashType::All,
+            };
+            let public_key = secret_key.public_key(&secp);
+            input.partial_sigs.insert(bitcoin::PublicKey::new(public_key), signature);
+            input.final_script_witness = Some(Witness::p2wpkh(&signature, &public_key));
+        } else if script_pubkey.is_p2tr() {
+            let Some(ref prevouts) = prevouts else {
+                return Err(format!(
+                    "signing taproot input {index} requires the witness utxo of every input"
+                ));
+            };
+            let sighash = cache
+                .taproot_key_spend_signature_hash(
+                    index,
+                    &Prevouts::All(prevouts),
+                    TapSighashType::Default,
+                )
+                .map_err(|e| format!("failed to compute sighash of input {index}: {e}"))?;
+            let message = Message::from_digest(sighash.to_byte_array());
+            let keypair = Keypair::from_secret_key(&secp, secret_key).tap_tweak(&secp, None);
+            let signature = bitcoin::taproot::Signature {
+                signature: secp.sign_schnorr_no_aux_rand(&message, &keypair.to_inner()),
+                sighash_type: TapSighashType::Default,
+            };
+            input.tap_key_sig = Some(signature);
+            input.final_script_witness = Some(Witness::p2tr_key_spend(&signature));
+        } else {
+            return Err(format!("unsupported script type for input {index}"));
+        }
+        signed += 1;
+    }
+    Ok(signed)
+}
+
+/// Extracts the network serialization of a fully signed PSBT.
+pub fn extract_signed_transaction(psbt: Psbt) -> Result<Transaction, String> {
+    if let Some(index) = psbt
+        .inputs
+        .iter()
+        .position(|input| input.final_script_witness.is_none() && input.final_script_sig.is_none())
+    {
+        return Err(format!("input {index} of the psbt is not signed"));
+    }
+    psbt.extract_tx().map_err(|e| format!("failed to extract transaction from psbt: {e}"))
+}
+
+/// Parses a PSBT provided either as a base64 string or as raw bytes.
+pub fn parse_psbt(value: &Value) -> Result<Psbt, String> {
+    if let Some(encoded) = value.as_string() {
+        if let Ok(psbt) = encoded.parse::<Psbt>() {
+            return Ok(psbt);
+        }
+    }
+    let bytes = value
+        .get_buffer_bytes_result()
+        .map_err(|e| format!("psbt must be encoded in base64 or hex: {e}"))?;
+    Psbt::deserialize(&bytes).map_err(|e| format!("invalid psbt: {e}"))
+}
+
+pub fn format_psbt_for_display(psbt: &Psbt, network: Network) -> Value {
+    let inputs = psbt
+        .unsigned_tx
+        .input
+        .iter()
+        .zip(psbt.inputs.iter())
+        .map(|(txin, input)| {
+            let mut res = ObjectType::from(vec![(
+                "outpoint",
+                Value::string(txin.previous_output.to_string()),
+            )]);
+            if let Some(utxo) = &input.witness_utxo {
+                res.insert("amount", Value::integer(utxo.value.to_sat() as i128));
+            }
+            res.to_value()
+        })
+        .collect::<Vec<_>>();
+    let outputs = psbt
+        .unsigned_tx
+        .output
+        .iter()
+        .map(|txout| {
+            let recipient = match Address::from_script(&txout.script_pubkey, network) {
+                Ok(address) => address.to_string(),
+                Err(_) => txout.script_pubkey.to_hex_string(),
+            };
+            ObjectType::from(vec![
+                ("recipient", Value::string(recipient)),
+                ("amount", Value::integer(txout.value.to_sat() as i128)),
+            ])
+            .to_value()
+        })
+        .collect::<Vec<_>>();
+
+    let mut res = ObjectType::from(vec![
+        ("inputs", Value::array(inputs)),
+        ("outputs", Value::array(outputs)),
+    ]);
+    if let Ok(fee) = psbt.fee() {
+        res.insert("fee", Value::integer(fee.to_sat() as i128));
+    }
+    res.to_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::key::XOnlyPublicKey;
+
+    use super::*;
+    use crate::codec::crypto::AddressType;
+
+    fn secret_key(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn utxo(index: u8, amount: u64, address: &Address) -> Utxo {
+        Utxo {
+            txid: Txid::from_byte_array([index; 32]),
+            vout: index as u32,
+            amount: Amount::from_sat(amount),
+            script_pubkey: address.script_pubkey(),
+        }
+    }
+
+    fn p2wpkh_address(byte: u8) -> Address {
+        AddressType::P2wpkh.address(&secret_key(byte), Network::Regtest)
+    }
+
+    #[test]
+    fn it_sends_change_back_to_the_sender() {
+        let sender = p2wpkh_address(1);
+        let recipient = p2wpkh_address(2);
+        let utxos = vec![utxo(1, 50_000, &sender), utxo(2, 100_000, &sender)];
+
+        // The largest utxo is enough: 11 + 31 bytes of overhead and recipient output,
+        // 68 bytes of input, 31 bytes of change output
+        let transfer =
+            build_transfer_psbt(utxos, &sender, &recipient, Amount::from_sat(60_000), 2).unwrap();
+        let tx = &transfer.psbt.unsigned_tx;
+        assert_eq!(transfer.fee, Amount::from_sat(2 * (11 + 31 + 68 + 31)));
+        assert_eq!(tx.input.len(), 1);
+        assert_eq!(tx.input[0].previous_output.vout, 2);
+        assert_eq!(tx.output.len(), 2);
+        assert_eq!(tx.output[0].value, Amount::from_sat(60_000));
+        assert_eq!(tx.output[0].script_pubkey, recipient.script_pubkey());
+        assert_eq!(tx.output[1].value, Amount::from_sat(100_000 - 60_000 - 282));
+        assert_eq!(tx.output[1].script_pubkey, sender.script_pubkey());
+        assert_eq!(transfer.psbt.fee().unwrap(), transfer.fee);
+    }
+
+    #[test]
+    fn it_adds_dust_change_to_the_fee() {
+        let sender = p2wpkh_address(1);
+        let recipient = p2wpkh_address(2);
+
+        // 218 sats would be left as change, below the 294 sats dust limit of p2wpkh outputs
+        let utxos = vec![utxo(1, 60_500, &sender)];
+        let transfer =
+            build_transfer_psbt(utxos, &sender, &recipient, Amount::from_sat(60_000), 2).unwrap();
+        assert_eq!(transfer.fee, Amount::from_sat(500));
+        assert_eq!(transfer.psbt.unsigned_tx.output.len(), 1);
+        assert_eq!(transfer.psbt.fee().unwrap(), transfer.fee);
+    }
+
+    #[test]
+    fn it_selects_utxos_until_the_transfer_is_funded() {
+        let sender = p2wpkh_address(1);
+        let recipient = p2wpkh_address(2);
+        let utxos = vec![utxo(1, 50_000, &sender), utxo(2, 100_000, &sender)];
+        let transfer =
+            build_transfer_psbt(utxos, &sender, &recipient, Amount::from_sat(120_000), 1).unwrap();
+        assert_eq!(transfer.psbt.unsigned_tx.input.len(), 2);
+        assert_eq!(transfer.psbt.inputs.len(), 2);
+        assert!(transfer.psbt.inputs.iter().all(|input| input.witness_utxo.is_some()));
+    }
+
+    #[test]
+    fn it_rejects_unfunded_transfers() {
+        let sender = p2wpkh_address(1);
+        let recipient = p2wpkh_address(2);
+        let utxos = vec![utxo(1, 10_000, &sender), utxo(2, 5_000, &sender)];
+
+        let err =
+            build_transfer_psbt(utxos.clone(), &sender, &recipient, Amount::from_sat(15_000), 1)
+                .unwrap_err();
+        assert!(err.starts_with("insufficient funds: 15000 sats available in 2 confirmed utxos"));
+
+        let err = build_transfer_psbt(vec![], &sender, &recipient, Amount::from_sat(1_000), 1)
+            .unwrap_err();
+        assert!(err.starts_with("insufficient funds: 0 sats available in 0 confirmed utxos"));
+
+        let err =
+            build_transfer_psbt(utxos, &sender, &recipient, Amount::from_sat(100), 1).unwrap_err();
+        assert!(err.contains("below the dust limit"));
+    }
+
+    #[test]
+    fn it_signs_transfers() {
+        let secp = Secp256k1::new();
+        for address_type in [AddressType::P2wpkh, AddressType::P2tr] {
+            let signer_key = secret_key(1);
+            let sender = address_type.address(&signer_key, Network::Regtest);
+            let recipient = p2wpkh_address(2);
+            let utxos = vec![utxo(1, 50_000, &sender), utxo(2, 100_000, &sender)];
+            let mut psbt =
+                build_transfer_psbt(utxos, &sender, &recipient, Amount::from_sat(120_000), 2)
+                    .unwrap()
+                    .psbt;
+            let prevouts = psbt
+                .inputs
+                .iter()
+                .map(|input| input.witness_utxo.clone().unwrap())
+                .collect::<Vec<_>>();
+
+            assert!(extract_signed_transaction(psbt.clone()).is_err());
+            // Inputs locked to other addresses are left untouched
+            let other_sender = address_type.address(&secret_key(3), Network::Regtest);
+            assert_eq!(sign_psbt(&mut psbt, &secret_key(3), &other_sender).unwrap(), 0);
+            assert_eq!(sign_psbt(&mut psbt, &signer_key, &sender).unwrap(), 2);
+            // Signed inputs are not signed twice
+            assert_eq!(sign_psbt(&mut psbt, &signer_key, &sender).unwrap(), 0);
+
+            let tx = extract_signed_transaction(psbt).unwrap();
+            let mut cache = SighashCache::new(&tx);
+            for (index, input) in tx.input.iter().enumerate() {
+                match address_type {
+                    AddressType::P2wpkh => {
+                        assert_eq!(input.witness.len(), 2);
+                        let signature =
+                            bitcoin::ecdsa::Signature::from_slice(&input.witness[0]).unwrap();
+                        let public_key =
+                            bitcoin::secp256k1::PublicKey::from_slice(&input.witness[1]).unwrap();
+                        assert_eq!(public_key, signer_key.public_key(&secp));
+                        let sighash = cache
+                            .p2wpkh_signature_hash(
+                                index,
+                                &sender.script_pubkey(),
+                                prevouts[index].value,
+                                EcdsaSighashType::All,
+                            )
+                            .unwrap();
+                        let message = Message::from_digest(sighash.to_byte_array());
+                        secp.verify_ecdsa(&message, &signature.signature, &public_key).unwrap();
+                    }
+                    AddressType::P2tr => {
+                        assert_eq!(input.witness.len(), 1);
+                        let signature =
+                            bitcoin::taproot::Signature::from_slice(&input.witness[0]).unwrap();
+                        // The key is spent through the output key of the taproot address
+                        let output_key =
+                            XOnlyPublicKey::from_slice(&sender.script_pubkey().as_bytes()[2..])
+                                .unwrap();
+                        let sighash = cache
+                            .taproot_key_spend_signature_hash(
+                                index,
+                                &Prevouts::All(&prevouts),
+                                TapSighashType::Default,
+                            )
+                            .unwrap();
+                        let message = Message::from_digest(sighash.to_byte_array());
+                        secp.verify_schnorr(&signature.signature, &message, &output_key).unwrap();
+                    }
+                }
+            }
+        }
+    }
+}
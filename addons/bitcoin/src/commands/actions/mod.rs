@@ -1,8 +1,31 @@
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::{
+    commands::PreCommandSpecification, diagnostics::Diagnostic, ConstructDid, Did,
+};
+
+pub mod broadcast_transaction;
 pub mod encode;
+pub mod send_btc;
+pub mod sign_psbt;
 
+use broadcast_transaction::BROADCAST_TRANSACTION;
 use encode::ENCODE_SCRIPT;
-use txtx_addon_kit::types::commands::PreCommandSpecification;
+use send_btc::SEND_BTC;
+use sign_psbt::SIGN_PSBT;
+
+use crate::constants::SIGNER;
 
 lazy_static! {
-    pub static ref ACTIONS: Vec<PreCommandSpecification> = vec![ENCODE_SCRIPT.clone()];
+    pub static ref ACTIONS: Vec<PreCommandSpecification> = vec![
+        ENCODE_SCRIPT.clone(),
+        SIGN_PSBT.clone(),
+        SEND_BTC.clone(),
+        BROADCAST_TRANSACTION.clone(),
+    ];
+}
+
+fn get_signer_did(args: &ValueStore) -> Result<ConstructDid, Diagnostic> {
+    let signer = args.get_expected_string(SIGNER)?;
+    let signer_did = ConstructDid(Did::from_hex_string(signer));
+    Ok(signer_did)
 }
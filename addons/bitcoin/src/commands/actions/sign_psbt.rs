@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use txtx_addon_kit::types::commands::{CommandImplementation, PreCommandSpecification};
+use txtx_addon_kit::types::frontend::{
+    ActionItemRequest, ActionItemStatus, Actions, BlockEvent, ReviewInputRequest,
+};
+use txtx_addon_kit::types::signers::{
+    SignerActionsFutureResult, SignerInstance, SignerSignFutureResult,
+};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::{
+    commands::CommandSpecification,
+    diagnostics::Diagnostic,
+    types::{Type, Value},
+};
+use txtx_addon_kit::types::{
+    signers::SignersState, types::RunbookSupervisionContext, ConstructDid,
+};
+
+use crate::typing::{BITCOIN_PSBT, BITCOIN_TRANSACTION};
+
+use super::get_signer_did;
+
+lazy_static! {
+    pub static ref SIGN_PSBT: PreCommandSpecification = define_command! {
+      SignBitcoinPsbt => {
+          name: "Sign Bitcoin PSBT",
+          matcher: "sign_psbt",
+          documentation: "The `btc::sign_psbt` action signs the inputs of a Partially Signed Bitcoin Transaction (BIP-174) spending outputs controlled by the signer. Once every input is signed, the finalized transaction is available in the `signed_transaction` output and can be broadcasted with `btc::broadcast_transaction`.",
+          implements_signing_capability: true,
+          implements_background_task_capability: false,
+          inputs: [
+            description: {
+                documentation: "A description of the transaction.",
+                typing: Type::string(),
+                optional: true,
+                tainting: false,
+                internal: false
+            },
+            psbt: {
+                documentation: "The PSBT to sign, encoded in base64 or hex.",
+                typing: Type::addon(BITCOIN_PSBT),
+                optional: false,
+                tainting: true,
+                internal: false
+            },
+            signer: {
+                documentation: "A reference to a signer construct, which will be used to sign the PSBT.",
+                typing: Type::string(),
+                optional: false,
+                tainting: true,
+                internal: false
+            }
+          ],
+          outputs: [
+              signed_psbt: {
+                  documentation: "The PSBT, including the signatures of the signer.",
+                  typing: Type::addon(BITCOIN_PSBT)
+              },
+              signed_transaction: {
+                  documentation: "The finalized transaction, if every input of the PSBT is signed.",
+                  typing: Type::addon(BITCOIN_TRANSACTION)
+              }
+          ],
+          example: txtx_addon_kit::indoc! {r#"
+          action "signed_psbt" "btc::sign_psbt" {
+              description = "Spend the multisig output"
+              psbt = "cHNidP8BAHECAAAAAf..."
+              signer = signer.alice
+          }
+      "#},
+      }
+    };
+}
+
+pub struct SignBitcoinPsbt;
+impl CommandImplementation for SignBitcoinPsbt {
+    fn check_instantiability(
+        _ctx: &CommandSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    fn check_signed_executability(
+        construct_did: &ConstructDid,
+        instance_name: &str,
+        _spec: &CommandSpecification,
+        values: &ValueStore,
+        supervision_context: &RunbookSupervisionContext,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        mut signers: SignersState,
+    ) -> SignerActionsFutureResult {
+        use crate::codec::crypto::parse_network;
+        use crate::codec::psbt::{format_psbt_for_display, parse_psbt};
+        use crate::constants::{
+            ACTION_ITEM_CHECK_FEE, FORMATTED_TRANSACTION, NETWORK, PSBT, UNSIGNED_PSBT_BYTES,
+        };
+
+        let signer_did = get_signer_did(values).unwrap();
+
+        let signer = signers_instances.get(&signer_did).unwrap().clone();
+        let construct_did = construct_did.clone();
+        let instance_name = instance_name.to_string();
+        let values = values.clone();
+        let supervision_context = supervision_context.clone();
+        let signers_instances = signers_instances.clone();
+
+        let future = async move {
+            let mut actions = Actions::none();
+            let mut signer_state = signers.pop_signer_state(&signer_did).unwrap();
+
+            let description =
+                values.get_expected_string("description").ok().and_then(|d| Some(d.to_string()));
+
+            let network = parse_network(signer_state.get_string(NETWORK))
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+            let psbt = values
+                .get_expected_value(PSBT)
+                .map_err(|diag| (signers.clone(), signer_state.clone(), diag))
+                .and_then(|psbt| {
+                    parse_psbt(psbt).map_err(|e| {
+                        (signers.clone(), signer_state.clone(), diagnosed_error!("{e}"))
+                    })
+                })?;
+
+            signer_state.insert_scoped_value(
+                &construct_did.to_string(),
+                UNSIGNED_PSBT_BYTES,
+                Value::buffer(psbt.serialize()),
+            );
+            signer_state.insert_scoped_value(
+                &construct_did.to_string(),
+                FORMATTED_TRANSACTION,
+                format_psbt_for_display(&psbt, network),
+            );
+
+            if supervision_context.review_input_values {
+                let mut action_items = vec![];
+                if let Ok(fee) = psbt.fee() {
+                    action_items.push(ActionItemRequest::new(
+                        &Some(construct_did.clone()),
+                        "Check transaction fee (sats)",
+                        None,
+                        ActionItemStatus::Todo,
+                        ReviewInputRequest::new("", &Value::integer(fee.to_sat() as i128))
+                            .to_action_type(),
+                        ACTION_ITEM_CHECK_FEE,
+                    ));
+                }
+                actions.push_panel("Transaction Execution", "");
+                actions.push_sub_group(description.clone(), action_items);
+            }
+
+            let (signers, signer_state, mut signer_actions) =
+                (signer.specification.check_signability)(
+                    &construct_did,
+                    &instance_name,
+                    &description,
+                    &Value::buffer(psbt.serialize()),
+                    &signer.specification,
+                    &values,
+                    signer_state,
+                    signers,
+                    &signers_instances,
+                    &supervision_context,
+                )?;
+            actions.append(&mut signer_actions);
+            Ok((signers, signer_state, actions))
+        };
+        Ok(Box::pin(future))
+    }
+
+    fn run_signed_execution(
+        construct_did: &ConstructDid,
+        _spec: &CommandSpecification,
+        values: &ValueStore,
+        _progress_tx: &txtx_addon_kit::channel::Sender<BlockEvent>,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        mut signers: SignersState,
+    ) -> SignerSignFutureResult {
+        let signer_did = get_signer_did(values).unwrap();
+        let signer_state = signers.pop_signer_state(&signer_did).unwrap();
+        let signer = signers_instances.get(&signer_did).unwrap();
+
+        let title = values.get_expected_string("description").unwrap_or("New Transaction".into());
+
+        (signer.specification.sign)(
+            construct_did,
+            title,
+            &Value::null(),
+            &signer.specification,
+            &values,
+            signer_state,
+            signers,
+            signers_instances,
+        )
+    }
+}
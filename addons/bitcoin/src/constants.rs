@@ -1,4 +1,38 @@
-pub const NAMESPACE: &str = "bitcoin";
+pub const NAMESPACE: &str = "btc";
 
 // Default keys
 pub const INSTRUCTIONS: &str = "instructions";
+pub const RPC_API_URL: &str = "rpc_api_url";
+pub const NETWORK: &str = "network";
+pub const SIGNER: &str = "signer";
+pub const SECRET_KEY: &str = "secret_key";
+pub const MNEMONIC: &str = "mnemonic";
+pub const DERIVATION_PATH: &str = "derivation_path";
+pub const ADDRESS_TYPE: &str = "address_type";
+pub const RECIPIENT_ADDRESS: &str = "recipient_address";
+pub const AMOUNT: &str = "amount";
+pub const FEE_RATE: &str = "fee_rate";
+pub const CONFIRMATIONS: &str = "confirmations";
+pub const PSBT: &str = "psbt";
+pub const SIGNED_PSBT: &str = "signed_psbt";
+pub const SIGNED_TRANSACTION: &str = "signed_transaction";
+pub const TRANSACTION_FEE: &str = "fee";
+
+// Signer state keys
+pub const SIGNER_SECRET_KEY_BYTES: &str = "signer_secret_key_bytes";
+pub const SIGNER_ADDRESS: &str = "signer_address";
+pub const SIGNER_PUBLIC_KEY: &str = "signer_public_key";
+pub const UNSIGNED_PSBT_BYTES: &str = "unsigned_psbt_bytes";
+pub const FORMATTED_TRANSACTION: &str = "formatted_transaction";
+pub const CHECKED_ADDRESS: &str = "checked_address";
+
+pub const DEFAULT_NETWORK: &str = "mainnet";
+pub const DEFAULT_ADDRESS_TYPE: &str = "p2wpkh";
+pub const DEFAULT_CONFIRMATIONS_NUMBER: u64 = 1;
+/// Fee rate (sat/vB) used when the node is unable to estimate one, e.g. on a fresh regtest chain
+pub const DEFAULT_FEE_RATE: u64 = 1;
+
+// Action items
+pub const ACTION_ITEM_CHECK_ADDRESS: &str = "check_address";
+pub const ACTION_ITEM_CHECK_FEE: &str = "check_fee";
+pub const ACTION_ITEM_PROVIDE_SIGNED_TRANSACTION: &str = "provide_signed_transaction";
@@ -26,6 +26,7 @@ lazy_static! {
         functions.extend(opcodes::bitwise_logic::BITWISE_LOGIC_FUNCTIONS.clone());
         functions.extend(opcodes::crypto::CRYPTO_FUNCTIONS.clone());
         functions.extend(opcodes::arithmetic::ARITHMETIC_FUNCTIONS.clone());
+        functions.extend(opcodes::locktime::LOCKTIME_FUNCTIONS.clone());
         functions
     };
 }
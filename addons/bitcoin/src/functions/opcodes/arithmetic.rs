@@ -1,5 +1,189 @@
 use txtx_addon_kit::types::functions::FunctionSpecification;
 
+use crate::codec::BitcoinOpcode;
+
 lazy_static! {
-    pub static ref ARITHMETIC_FUNCTIONS: Vec<FunctionSpecification> = vec![];
+    pub static ref ARITHMETIC_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_opcode_function! {
+            OneAdd => {
+                name: "op_1add",
+                opcode: "OP_1ADD",
+                code: "0x8b",
+                documentation: "1 is added to the input."
+            }
+        },
+        define_opcode_function! {
+            OneSub => {
+                name: "op_1sub",
+                opcode: "OP_1SUB",
+                code: "0x8c",
+                documentation: "1 is subtracted from the input."
+            }
+        },
+        define_opcode_function! {
+            Negate => {
+                name: "op_negate",
+                opcode: "OP_NEGATE",
+                code: "0x8f",
+                documentation: "The sign of the input is flipped."
+            }
+        },
+        define_opcode_function! {
+            Abs => {
+                name: "op_abs",
+                opcode: "OP_ABS",
+                code: "0x90",
+                documentation: "The input is made positive."
+            }
+        },
+        define_opcode_function! {
+            Not => {
+                name: "op_not",
+                opcode: "OP_NOT",
+                code: "0x91",
+                documentation: "If the input is 0 or 1, it is flipped. Otherwise the output will be 0."
+            }
+        },
+        define_opcode_function! {
+            ZeroNotEqual => {
+                name: "op_0notequal",
+                opcode: "OP_0NOTEQUAL",
+                code: "0x92",
+                documentation: "Returns 0 if the input is 0, 1 otherwise."
+            }
+        },
+        define_opcode_function! {
+            Add => {
+                name: "op_add",
+                opcode: "OP_ADD",
+                code: "0x93",
+                documentation: "a is added to b."
+            }
+        },
+        define_opcode_function! {
+            Sub => {
+                name: "op_sub",
+                opcode: "OP_SUB",
+                code: "0x94",
+                documentation: "b is subtracted from a."
+            }
+        },
+        define_opcode_function! {
+            BoolAnd => {
+                name: "op_booland",
+                opcode: "OP_BOOLAND",
+                code: "0x9a",
+                documentation: "If both a and b are not 0, the output is 1. Otherwise 0."
+            }
+        },
+        define_opcode_function! {
+            BoolOr => {
+                name: "op_boolor",
+                opcode: "OP_BOOLOR",
+                code: "0x9b",
+                documentation: "If a or b is not 0, the output is 1. Otherwise 0."
+            }
+        },
+        define_opcode_function! {
+            NumEqual => {
+                name: "op_numequal",
+                opcode: "OP_NUMEQUAL",
+                code: "0x9c",
+                documentation: "Returns 1 if the numbers are equal, 0 otherwise."
+            }
+        },
+        define_opcode_function! {
+            NumEqualVerify => {
+                name: "op_numequalverify",
+                opcode: "OP_NUMEQUALVERIFY",
+                code: "0x9d",
+                documentation: "Same as `OP_NUMEQUAL`, but runs `OP_VERIFY` afterward."
+            }
+        },
+        define_opcode_function! {
+            NumNotEqual => {
+                name: "op_numnotequal",
+                opcode: "OP_NUMNOTEQUAL",
+                code: "0x9e",
+                documentation: "Returns 1 if the numbers are not equal, 0 otherwise."
+            }
+        },
+        define_opcode_function! {
+            LessThan => {
+                name: "op_lessthan",
+                opcode: "OP_LESSTHAN",
+                code: "0x9f",
+                documentation: "Returns 1 if a is less than b, 0 otherwise."
+            }
+        },
+        define_opcode_function! {
+            GreaterThan => {
+                name: "op_greaterthan",
+                opcode: "OP_GREATERTHAN",
+                code: "0xa0",
+                documentation: "Returns 1 if a is greater than b, 0 otherwise."
+            }
+        },
+        define_opcode_function! {
+            LessThanOrEqual => {
+                name: "op_lessthanorequal",
+                opcode: "OP_LESSTHANOREQUAL",
+                code: "0xa1",
+                documentation: "Returns 1 if a is less than or equal to b, 0 otherwise."
+            }
+        },
+        define_opcode_function! {
+            GreaterThanOrEqual => {
+                name: "op_greaterthanorequal",
+                opcode: "OP_GREATERTHANOREQUAL",
+                code: "0xa2",
+                documentation: "Returns 1 if a is greater than or equal to b, 0 otherwise."
+            }
+        },
+        define_opcode_function! {
+            Min => {
+                name: "op_min",
+                opcode: "OP_MIN",
+                code: "0xa3",
+                documentation: "Returns the smaller of a and b."
+            }
+        },
+        define_opcode_function! {
+            Max => {
+                name: "op_max",
+                opcode: "OP_MAX",
+                code: "0xa4",
+                documentation: "Returns the larger of a and b."
+            }
+        },
+        define_opcode_function! {
+            Within => {
+                name: "op_within",
+                opcode: "OP_WITHIN",
+                code: "0xa5",
+                documentation: "Returns 1 if x is within the specified range (left-inclusive), 0 otherwise."
+            }
+        },
+    ];
 }
+
+impl_opcode_function!(OneAdd, BitcoinOpcode::Op1Add);
+impl_opcode_function!(OneSub, BitcoinOpcode::Op1Sub);
+impl_opcode_function!(Negate, BitcoinOpcode::OpNegate);
+impl_opcode_function!(Abs, BitcoinOpcode::OpAbs);
+impl_opcode_function!(Not, BitcoinOpcode::OpNot);
+impl_opcode_function!(ZeroNotEqual, BitcoinOpcode::Op0NotEqual);
+impl_opcode_function!(Add, BitcoinOpcode::OpAdd);
+impl_opcode_function!(Sub, BitcoinOpcode::OpSub);
+impl_opcode_function!(BoolAnd, BitcoinOpcode::OpBoolAnd);
+impl_opcode_function!(BoolOr, BitcoinOpcode::OpBoolOr);
+impl_opcode_function!(NumEqual, BitcoinOpcode::OpNumEqual);
+impl_opcode_function!(NumEqualVerify, BitcoinOpcode::OpNumEqualVerify);
+impl_opcode_function!(NumNotEqual, BitcoinOpcode::OpNumNotEqual);
+impl_opcode_function!(LessThan, BitcoinOpcode::OpLessThan);
+impl_opcode_function!(GreaterThan, BitcoinOpcode::OpGreaterThan);
+impl_opcode_function!(LessThanOrEqual, BitcoinOpcode::OpLessThanOrEqual);
+impl_opcode_function!(GreaterThanOrEqual, BitcoinOpcode::OpGreaterThanOrEqual);
+impl_opcode_function!(Min, BitcoinOpcode::OpMin);
+impl_opcode_function!(Max, BitcoinOpcode::OpMax);
+impl_opcode_function!(Within, BitcoinOpcode::OpWithin);
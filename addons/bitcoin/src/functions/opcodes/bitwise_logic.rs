@@ -1,50 +1,27 @@
-use txtx_addon_kit::types::{
-    diagnostics::Diagnostic,
-    functions::{FunctionImplementation, FunctionSpecification},
-    types::{Type, Value},
-    AuthorizationContext,
-};
+use txtx_addon_kit::types::functions::FunctionSpecification;
+
+use crate::codec::BitcoinOpcode;
 
-use crate::{
-    codec::BitcoinOpcode,
-    typing::{BitcoinValue, BITCOIN_OPCODE},
-};
 lazy_static! {
-    pub static ref BITWISE_LOGIC_FUNCTIONS: Vec<FunctionSpecification> = vec![define_function! {
-        EqualVerify => {
-            name: "op_equalverify",
-            documentation: "`btc::op_equalverify` pushes the `OP_EQUALVERIFY` opcode onto the stack.",
-            example: indoc! {r#"
-                output "opcode" {
-                    value = btc::op_equalverify()
-                }                
-                // > opcode: 0x88
-            "#},
-            inputs: [],
-            output: {
-                documentation: "A hex representation of the `OP_EQUALVERIFY` opcode.",
-                typing: Type::addon(BITCOIN_OPCODE)
-            },
-        }
-    },];
+    pub static ref BITWISE_LOGIC_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_opcode_function! {
+            Equal => {
+                name: "op_equal",
+                opcode: "OP_EQUAL",
+                code: "0x87",
+                documentation: "Returns 1 if the inputs are exactly equal, 0 otherwise."
+            }
+        },
+        define_opcode_function! {
+            EqualVerify => {
+                name: "op_equalverify",
+                opcode: "OP_EQUALVERIFY",
+                code: "0x88",
+                documentation: "Same as `OP_EQUAL`, but runs `OP_VERIFY` afterward."
+            }
+        },
+    ];
 }
 
-#[derive(Clone)]
-pub struct EqualVerify;
-impl FunctionImplementation for EqualVerify {
-    fn check_instantiability(
-        _fn_spec: &FunctionSpecification,
-        _auth_ctx: &AuthorizationContext,
-        _args: &Vec<Type>,
-    ) -> Result<Type, Diagnostic> {
-        unimplemented!()
-    }
-
-    fn run(
-        _fn_spec: &FunctionSpecification,
-        _auth_ctx: &AuthorizationContext,
-        _args: &Vec<Value>,
-    ) -> Result<Value, Diagnostic> {
-        Ok(BitcoinValue::opcode(BitcoinOpcode::OpEqualVerify.get_code()))
-    }
-}
+impl_opcode_function!(Equal, BitcoinOpcode::OpEqual);
+impl_opcode_function!(EqualVerify, BitcoinOpcode::OpEqualVerify);
@@ -6,7 +6,7 @@ use txtx_addon_kit::types::{
 };
 
 use crate::{
-    codec::BitcoinOpcode,
+    codec::{encode_push_number, BitcoinOpcode},
     functions::{arg_checker, to_diag},
     typing::{BitcoinValue, BITCOIN_OPCODE},
 };
@@ -66,7 +66,7 @@ lazy_static! {
                     output "opcode" {
                         value = btc::op_pushdata1(1, "ff")
                     }                
-                    // > opcode: 0xc401ff
+                    // > opcode: 0x4c01ff
                 "#},
                 inputs: [
                     length: {
@@ -86,6 +86,221 @@ lazy_static! {
                 },
             }
         },
+        define_function! {
+            PushData2 => {
+                name: "op_pushdata2",
+                documentation: "`btc::op_pushdata2` pushes the `OP_PUSHDATA2` opcode, the provided length encoded as two little-endian bytes, and the data bytes onto the stack, ensuring that the data's length matches the provided length.",
+                example: indoc! {r#"
+                    output "opcode" {
+                        value = btc::op_pushdata2(1, "ff")
+                    }
+                    // > opcode: 0x4d0100ff
+                "#},
+                inputs: [
+                    length: {
+                        documentation: "The number of bytes, up to 65535, that will be pushed to the stack.",
+                        typing: vec![Type::integer()],
+                        optional: false
+                    },
+                    bytes: {
+                        documentation: "The hex-encoded bytes that will be pushed to the stack, which should have length equal to the first argument.",
+                        typing: vec![Type::string(), Type::buffer()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The `OP_PUSHDATA2` opcode, the number of bytes, and the data, all encoded in hex.",
+                    typing: Type::addon(BITCOIN_OPCODE)
+                },
+            }
+        },
+        define_function! {
+            PushData4 => {
+                name: "op_pushdata4",
+                documentation: "`btc::op_pushdata4` pushes the `OP_PUSHDATA4` opcode, the provided length encoded as four little-endian bytes, and the data bytes onto the stack, ensuring that the data's length matches the provided length.",
+                example: indoc! {r#"
+                    output "opcode" {
+                        value = btc::op_pushdata4(1, "ff")
+                    }
+                    // > opcode: 0x4e01000000ff
+                "#},
+                inputs: [
+                    length: {
+                        documentation: "The number of bytes, up to 4294967295, that will be pushed to the stack.",
+                        typing: vec![Type::integer()],
+                        optional: false
+                    },
+                    bytes: {
+                        documentation: "The hex-encoded bytes that will be pushed to the stack, which should have length equal to the first argument.",
+                        typing: vec![Type::string(), Type::buffer()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The `OP_PUSHDATA4` opcode, the number of bytes, and the data, all encoded in hex.",
+                    typing: Type::addon(BITCOIN_OPCODE)
+                },
+            }
+        },
+        define_function! {
+            PushNumber => {
+                name: "op_push_number",
+                documentation: "`btc::op_push_number` pushes the provided number onto the stack, using the shortest encoding: `OP_0`, `OP_1NEGATE` and `OP_1` to `OP_16` for small numbers, and a minimally encoded script number otherwise.",
+                example: indoc! {r#"
+                    output "opcode" {
+                        value = btc::op_push_number(144)
+                    }
+                    // > opcode: 0x029000
+                "#},
+                inputs: [
+                    number: {
+                        documentation: "The number that will be pushed to the stack.",
+                        typing: vec![Type::integer()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The instructions pushing the number, encoded in hex.",
+                    typing: Type::addon(BITCOIN_OPCODE)
+                },
+            }
+        },
+        define_opcode_function! {
+            OneNegate => {
+                name: "op_1negate",
+                opcode: "OP_1NEGATE",
+                code: "0x4f",
+                documentation: "The number -1 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op1 => {
+                name: "op_1",
+                opcode: "OP_1",
+                code: "0x51",
+                documentation: "The number 1 is pushed onto the stack. Also known as `OP_TRUE`."
+            }
+        },
+        define_opcode_function! {
+            Op2 => {
+                name: "op_2",
+                opcode: "OP_2",
+                code: "0x52",
+                documentation: "The number 2 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op3 => {
+                name: "op_3",
+                opcode: "OP_3",
+                code: "0x53",
+                documentation: "The number 3 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op4 => {
+                name: "op_4",
+                opcode: "OP_4",
+                code: "0x54",
+                documentation: "The number 4 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op5 => {
+                name: "op_5",
+                opcode: "OP_5",
+                code: "0x55",
+                documentation: "The number 5 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op6 => {
+                name: "op_6",
+                opcode: "OP_6",
+                code: "0x56",
+                documentation: "The number 6 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op7 => {
+                name: "op_7",
+                opcode: "OP_7",
+                code: "0x57",
+                documentation: "The number 7 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op8 => {
+                name: "op_8",
+                opcode: "OP_8",
+                code: "0x58",
+                documentation: "The number 8 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op9 => {
+                name: "op_9",
+                opcode: "OP_9",
+                code: "0x59",
+                documentation: "The number 9 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op10 => {
+                name: "op_10",
+                opcode: "OP_10",
+                code: "0x5a",
+                documentation: "The number 10 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op11 => {
+                name: "op_11",
+                opcode: "OP_11",
+                code: "0x5b",
+                documentation: "The number 11 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op12 => {
+                name: "op_12",
+                opcode: "OP_12",
+                code: "0x5c",
+                documentation: "The number 12 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op13 => {
+                name: "op_13",
+                opcode: "OP_13",
+                code: "0x5d",
+                documentation: "The number 13 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op14 => {
+                name: "op_14",
+                opcode: "OP_14",
+                code: "0x5e",
+                documentation: "The number 14 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op15 => {
+                name: "op_15",
+                opcode: "OP_15",
+                code: "0x5f",
+                documentation: "The number 15 is pushed onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Op16 => {
+                name: "op_16",
+                opcode: "OP_16",
+                code: "0x60",
+                documentation: "The number 16 is pushed onto the stack."
+            }
+        },
     ];
 }
 
@@ -207,3 +422,144 @@ impl FunctionImplementation for PushData1 {
         Ok(BitcoinValue::opcode(bytes))
     }
 }
+
+#[derive(Clone)]
+pub struct PushData2;
+impl FunctionImplementation for PushData2 {
+    fn check_instantiability(
+        _fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        _args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let expected_bytes_length = args.get(0).unwrap().as_integer().unwrap();
+        let mut data = args
+            .get(1)
+            .unwrap()
+            .try_get_buffer_bytes_result()
+            .map_err(|e| to_diag(fn_spec, format!("argument must be decodable to hex: {e}")))?
+            .unwrap();
+
+        let expected_bytes_length: u16 = expected_bytes_length.try_into().map_err(|e| {
+            to_diag(fn_spec, format!("byte length must be between 0 and 65535: {e}"))
+        })?;
+
+        if data.len() != expected_bytes_length as usize {
+            return Err(to_diag(
+                fn_spec,
+                format!(
+                    "provided length ({}) does not equal data byte length ({})",
+                    expected_bytes_length,
+                    data.len()
+                ),
+            ));
+        }
+
+        let mut bytes: Vec<u8> = BitcoinOpcode::OpPushData2.get_code();
+        bytes.extend(expected_bytes_length.to_le_bytes());
+
+        bytes.append(&mut data);
+
+        Ok(BitcoinValue::opcode(bytes))
+    }
+}
+
+#[derive(Clone)]
+pub struct PushData4;
+impl FunctionImplementation for PushData4 {
+    fn check_instantiability(
+        _fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        _args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let expected_bytes_length = args.get(0).unwrap().as_integer().unwrap();
+        let mut data = args
+            .get(1)
+            .unwrap()
+            .try_get_buffer_bytes_result()
+            .map_err(|e| to_diag(fn_spec, format!("argument must be decodable to hex: {e}")))?
+            .unwrap();
+
+        let expected_bytes_length: u32 = expected_bytes_length.try_into().map_err(|e| {
+            to_diag(fn_spec, format!("byte length must be between 0 and 4294967295: {e}"))
+        })?;
+
+        if data.len() != expected_bytes_length as usize {
+            return Err(to_diag(
+                fn_spec,
+                format!(
+                    "provided length ({}) does not equal data byte length ({})",
+                    expected_bytes_length,
+                    data.len()
+                ),
+            ));
+        }
+
+        let mut bytes: Vec<u8> = BitcoinOpcode::OpPushData4.get_code();
+        bytes.extend(expected_bytes_length.to_le_bytes());
+
+        bytes.append(&mut data);
+
+        Ok(BitcoinValue::opcode(bytes))
+    }
+}
+
+#[derive(Clone)]
+pub struct PushNumber;
+impl FunctionImplementation for PushNumber {
+    fn check_instantiability(
+        _fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        _args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let number = args.get(0).unwrap().as_integer().unwrap();
+        let number: i64 = number
+            .try_into()
+            .map_err(|e| to_diag(fn_spec, format!("number is out of range: {e}")))?;
+        Ok(BitcoinValue::opcode(encode_push_number(number)))
+    }
+}
+
+impl_opcode_function!(OneNegate, BitcoinOpcode::Op1Negate);
+impl_opcode_function!(Op1, BitcoinOpcode::OpN(1));
+impl_opcode_function!(Op2, BitcoinOpcode::OpN(2));
+impl_opcode_function!(Op3, BitcoinOpcode::OpN(3));
+impl_opcode_function!(Op4, BitcoinOpcode::OpN(4));
+impl_opcode_function!(Op5, BitcoinOpcode::OpN(5));
+impl_opcode_function!(Op6, BitcoinOpcode::OpN(6));
+impl_opcode_function!(Op7, BitcoinOpcode::OpN(7));
+impl_opcode_function!(Op8, BitcoinOpcode::OpN(8));
+impl_opcode_function!(Op9, BitcoinOpcode::OpN(9));
+impl_opcode_function!(Op10, BitcoinOpcode::OpN(10));
+impl_opcode_function!(Op11, BitcoinOpcode::OpN(11));
+impl_opcode_function!(Op12, BitcoinOpcode::OpN(12));
+impl_opcode_function!(Op13, BitcoinOpcode::OpN(13));
+impl_opcode_function!(Op14, BitcoinOpcode::OpN(14));
+impl_opcode_function!(Op15, BitcoinOpcode::OpN(15));
+impl_opcode_function!(Op16, BitcoinOpcode::OpN(16));
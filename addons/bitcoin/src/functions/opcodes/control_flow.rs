@@ -1,5 +1,72 @@
 use txtx_addon_kit::types::functions::FunctionSpecification;
 
+use crate::codec::BitcoinOpcode;
+
 lazy_static! {
-    pub static ref CONTROL_FLOW_FUNCTIONS: Vec<FunctionSpecification> = vec![];
+    pub static ref CONTROL_FLOW_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_opcode_function! {
+            Nop => {
+                name: "op_nop",
+                opcode: "OP_NOP",
+                code: "0x61",
+                documentation: "Does nothing."
+            }
+        },
+        define_opcode_function! {
+            If => {
+                name: "op_if",
+                opcode: "OP_IF",
+                code: "0x63",
+                documentation: "If the top stack value is not false, the statements up to the matching `OP_ELSE` or `OP_ENDIF` are executed. The top stack value is removed."
+            }
+        },
+        define_opcode_function! {
+            NotIf => {
+                name: "op_notif",
+                opcode: "OP_NOTIF",
+                code: "0x64",
+                documentation: "If the top stack value is false, the statements up to the matching `OP_ELSE` or `OP_ENDIF` are executed. The top stack value is removed."
+            }
+        },
+        define_opcode_function! {
+            Else => {
+                name: "op_else",
+                opcode: "OP_ELSE",
+                code: "0x67",
+                documentation: "If the statements of the preceding `OP_IF`, `OP_NOTIF` or `OP_ELSE` were not executed, these statements are; otherwise they are skipped."
+            }
+        },
+        define_opcode_function! {
+            EndIf => {
+                name: "op_endif",
+                opcode: "OP_ENDIF",
+                code: "0x68",
+                documentation: "Ends an if/else block."
+            }
+        },
+        define_opcode_function! {
+            Verify => {
+                name: "op_verify",
+                opcode: "OP_VERIFY",
+                code: "0x69",
+                documentation: "Marks the transaction as invalid if the top stack value is not true. The top stack value is removed."
+            }
+        },
+        define_opcode_function! {
+            Return => {
+                name: "op_return",
+                opcode: "OP_RETURN",
+                code: "0x6a",
+                documentation: "Marks the output as provably unspendable, typically to embed data in a transaction."
+            }
+        },
+    ];
 }
+
+impl_opcode_function!(Nop, BitcoinOpcode::OpNop);
+impl_opcode_function!(If, BitcoinOpcode::OpIf);
+impl_opcode_function!(NotIf, BitcoinOpcode::OpNotIf);
+impl_opcode_function!(Else, BitcoinOpcode::OpElse);
+impl_opcode_function!(EndIf, BitcoinOpcode::OpEndIf);
+impl_opcode_function!(Verify, BitcoinOpcode::OpVerify);
+impl_opcode_function!(Return, BitcoinOpcode::OpReturn);
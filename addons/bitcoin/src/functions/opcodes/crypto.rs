@@ -1,90 +1,108 @@
-use txtx_addon_kit::types::{
-    diagnostics::Diagnostic,
-    functions::{FunctionImplementation, FunctionSpecification},
-    types::{Type, Value},
-    AuthorizationContext,
-};
+use txtx_addon_kit::types::functions::FunctionSpecification;
 
-use crate::{
-    codec::BitcoinOpcode,
-    typing::{BitcoinValue, BITCOIN_OPCODE},
-};
+use crate::codec::BitcoinOpcode;
 
 lazy_static! {
     pub static ref CRYPTO_FUNCTIONS: Vec<FunctionSpecification> = vec![
-        define_function! {
+        define_opcode_function! {
+            Ripemd160 => {
+                name: "op_ripemd160",
+                opcode: "OP_RIPEMD160",
+                code: "0xa6",
+                documentation: "The input is hashed using RIPEMD-160."
+            }
+        },
+        define_opcode_function! {
+            Sha1 => {
+                name: "op_sha1",
+                opcode: "OP_SHA1",
+                code: "0xa7",
+                documentation: "The input is hashed using SHA-1."
+            }
+        },
+        define_opcode_function! {
+            Sha256 => {
+                name: "op_sha256",
+                opcode: "OP_SHA256",
+                code: "0xa8",
+                documentation: "The input is hashed using SHA-256."
+            }
+        },
+        define_opcode_function! {
             Hash160 => {
                 name: "op_hash160",
-                documentation: "`btc::op_hash160` pushes the `OP_HASH160` opcode onto the stack.",
-                example: indoc! {r#"
-                    output "opcode" {
-                        value = btc::op_hash160()
-                    }                
-                    // > opcode: 0xa9
-                "#},
-                inputs: [],
-                output: {
-                    documentation: "A hex representation of the `OP_HASH160` opcode.",
-                    typing: Type::addon(BITCOIN_OPCODE)
-                },
+                opcode: "OP_HASH160",
+                code: "0xa9",
+                documentation: "The input is hashed twice: first with SHA-256 and then with RIPEMD-160."
             }
         },
-        define_function! {
+        define_opcode_function! {
+            Hash256 => {
+                name: "op_hash256",
+                opcode: "OP_HASH256",
+                code: "0xaa",
+                documentation: "The input is hashed two times with SHA-256."
+            }
+        },
+        define_opcode_function! {
+            CodeSeparator => {
+                name: "op_codeseparator",
+                opcode: "OP_CODESEPARATOR",
+                code: "0xab",
+                documentation: "Signature checking opcodes will only match signatures to the data after the most recently-executed `OP_CODESEPARATOR`."
+            }
+        },
+        define_opcode_function! {
             CheckSig => {
                 name: "op_checksig",
-                documentation: "`btc::op_checksig` pushes the `OP_CHECKSIG` opcode onto the stack.",
-                example: indoc! {r#"
-                    output "opcode" {
-                        value = btc::op_hash160()
-                    }                
-                    // > opcode: 0xac
-                "#},
-                inputs: [],
-                output: {
-                    documentation: "A hex representation of the `OP_CHECKSIG` opcode.",
-                    typing: Type::addon(BITCOIN_OPCODE)
-                },
+                opcode: "OP_CHECKSIG",
+                code: "0xac",
+                documentation: "Returns 1 if the signature is valid for the transaction and the public key, 0 otherwise."
+            }
+        },
+        define_opcode_function! {
+            CheckSigVerify => {
+                name: "op_checksigverify",
+                opcode: "OP_CHECKSIGVERIFY",
+                code: "0xad",
+                documentation: "Same as `OP_CHECKSIG`, but `OP_VERIFY` is executed afterward."
             }
-        }
+        },
+        define_opcode_function! {
+            CheckMultiSig => {
+                name: "op_checkmultisig",
+                opcode: "OP_CHECKMULTISIG",
+                code: "0xae",
+                documentation: "Compares the m signatures against the n public keys, in order, and returns 1 if all the signatures are valid, 0 otherwise. Disabled in tapscript."
+            }
+        },
+        define_opcode_function! {
+            CheckMultiSigVerify => {
+                name: "op_checkmultisigverify",
+                opcode: "OP_CHECKMULTISIGVERIFY",
+                code: "0xaf",
+                documentation: "Same as `OP_CHECKMULTISIG`, but `OP_VERIFY` is executed afterward. Disabled in tapscript."
+            }
+        },
+        define_opcode_function! {
+            CheckSigAdd => {
+                name: "op_checksigadd",
+                opcode: "OP_CHECKSIGADD",
+                code: "0xba",
+                documentation: "Tapscript only: increments n if the signature is valid for the public key, leaving n unchanged if the signature is empty. Used to express multisig policies in tapscript."
+            }
+        },
     ];
 }
 
-#[derive(Clone)]
-pub struct Hash160;
-impl FunctionImplementation for Hash160 {
-    fn check_instantiability(
-        _fn_spec: &FunctionSpecification,
-        _auth_ctx: &AuthorizationContext,
-        _args: &Vec<Type>,
-    ) -> Result<Type, Diagnostic> {
-        unimplemented!()
-    }
-
-    fn run(
-        _fn_spec: &FunctionSpecification,
-        _auth_ctx: &AuthorizationContext,
-        _args: &Vec<Value>,
-    ) -> Result<Value, Diagnostic> {
-        Ok(BitcoinValue::opcode(BitcoinOpcode::OpHash160.get_code()))
-    }
-}
-
-#[derive(Clone)]
-pub struct CheckSig;
-impl FunctionImplementation for CheckSig {
-    fn check_instantiability(
-        _fn_spec: &FunctionSpecification,
-        _auth_ctx: &AuthorizationContext,
-        _args: &Vec<Type>,
-    ) -> Result<Type, Diagnostic> {
-        unimplemented!()
-    }
-
-    fn run(
-        _fn_spec: &FunctionSpecification,
-        _auth_ctx: &AuthorizationContext,
-        _args: &Vec<Value>,
-    ) -> Result<Value, Diagnostic> {
-        Ok(BitcoinValue::opcode(BitcoinOpcode::OpCheckSig.get_code()))
-    }
-}
+impl_opcode_function!(Ripemd160, BitcoinOpcode::OpRipemd160);
+impl_opcode_function!(Sha1, BitcoinOpcode::OpSha1);
+impl_opcode_function!(Sha256, BitcoinOpcode::OpSha256);
+impl_opcode_function!(Hash160, BitcoinOpcode::OpHash160);
+impl_opcode_function!(Hash256, BitcoinOpcode::OpHash256);
+impl_opcode_function!(CodeSeparator, BitcoinOpcode::OpCodeSeparator);
+impl_opcode_function!(CheckSig, BitcoinOpcode::OpCheckSig);
+impl_opcode_function!(CheckSigVerify, BitcoinOpcode::OpCheckSigVerify);
+impl_opcode_function!(CheckMultiSig, BitcoinOpcode::OpCheckMultiSig);
+impl_opcode_function!(CheckMultiSigVerify, BitcoinOpcode::OpCheckMultiSigVerify);
+impl_opcode_function!(CheckSigAdd, BitcoinOpcode::OpCheckSigAdd);
@@ -0,0 +1,99 @@
+use txtx_addon_kit::types::functions::FunctionSpecification;
+
+use crate::codec::BitcoinOpcode;
+
+lazy_static! {
+    pub static ref LOCKTIME_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_opcode_function! {
+            CheckLockTimeVerify => {
+                name: "op_checklocktimeverify",
+                opcode: "OP_CHECKLOCKTIMEVERIFY",
+                code: "0xb1",
+                documentation: "Marks the transaction as invalid if the top stack item is greater than the transaction's `nLockTime` field (BIP-65). The top stack item is not removed."
+            }
+        },
+        define_opcode_function! {
+            CheckSequenceVerify => {
+                name: "op_checksequenceverify",
+                opcode: "OP_CHECKSEQUENCEVERIFY",
+                code: "0xb2",
+                documentation: "Marks the transaction as invalid if the relative lock time of the input, enforced by its `nSequence` field, is not at least the value of the top stack item (BIP-112). The top stack item is not removed."
+            }
+        },
+        define_opcode_function! {
+            Nop1 => {
+                name: "op_nop1",
+                opcode: "OP_NOP1",
+                code: "0xb0",
+                documentation: "Reserved for soft-fork upgrades, does nothing."
+            }
+        },
+        define_opcode_function! {
+            Nop4 => {
+                name: "op_nop4",
+                opcode: "OP_NOP4",
+                code: "0xb3",
+                documentation: "Reserved for soft-fork upgrades, does nothing."
+            }
+        },
+        define_opcode_function! {
+            Nop5 => {
+                name: "op_nop5",
+                opcode: "OP_NOP5",
+                code: "0xb4",
+                documentation: "Reserved for soft-fork upgrades, does nothing."
+            }
+        },
+        define_opcode_function! {
+            Nop6 => {
+                name: "op_nop6",
+                opcode: "OP_NOP6",
+                code: "0xb5",
+                documentation: "Reserved for soft-fork upgrades, does nothing."
+            }
+        },
+        define_opcode_function! {
+            Nop7 => {
+                name: "op_nop7",
+                opcode: "OP_NOP7",
+                code: "0xb6",
+                documentation: "Reserved for soft-fork upgrades, does nothing."
+            }
+        },
+        define_opcode_function! {
+            Nop8 => {
+                name: "op_nop8",
+                opcode: "OP_NOP8",
+                code: "0xb7",
+                documentation: "Reserved for soft-fork upgrades, does nothing."
+            }
+        },
+        define_opcode_function! {
+            Nop9 => {
+                name: "op_nop9",
+                opcode: "OP_NOP9",
+                code: "0xb8",
+                documentation: "Reserved for soft-fork upgrades, does nothing."
+            }
+        },
+        define_opcode_function! {
+            Nop10 => {
+                name: "op_nop10",
+                opcode: "OP_NOP10",
+                code: "0xb9",
+                documentation: "Reserved for soft-fork upgrades, does nothing."
+            }
+        },
+    ];
+}
+
+impl_opcode_function!(CheckLockTimeVerify, BitcoinOpcode::OpCheckLockTimeVerify);
+impl_opcode_function!(CheckSequenceVerify, BitcoinOpcode::OpCheckSequenceVerify);
+impl_opcode_function!(Nop1, BitcoinOpcode::OpNop1);
+impl_opcode_function!(Nop4, BitcoinOpcode::OpNopN(4));
+impl_opcode_function!(Nop5, BitcoinOpcode::OpNopN(5));
+impl_opcode_function!(Nop6, BitcoinOpcode::OpNopN(6));
+impl_opcode_function!(Nop7, BitcoinOpcode::OpNopN(7));
+impl_opcode_function!(Nop8, BitcoinOpcode::OpNopN(8));
+impl_opcode_function!(Nop9, BitcoinOpcode::OpNopN(9));
+impl_opcode_function!(Nop10, BitcoinOpcode::OpNopN(10));
@@ -1,6 +1,63 @@
+/// Defines the specification of a function encoding a single opcode that takes no argument.
+macro_rules! define_opcode_function {
+    ($func_key:ident => {
+        name: $fn_name:literal,
+        opcode: $opcode_name:literal,
+        code: $code:literal,
+        documentation: $doc:literal
+    }) => {
+        define_function! {
+            $func_key => {
+                name: $fn_name,
+                documentation: concat!("`btc::", $fn_name, "` pushes the `", $opcode_name, "` opcode onto the stack. ", $doc),
+                example: concat!("output \"opcode\" {\n    value = btc::", $fn_name, "()\n}\n// > opcode: ", $code, "\n"),
+                inputs: [],
+                output: {
+                    documentation: concat!("A hex representation of the `", $opcode_name, "` opcode."),
+                    typing: txtx_addon_kit::types::types::Type::addon(crate::typing::BITCOIN_OPCODE)
+                },
+            }
+        }
+    };
+}
+
+/// Implements a function encoding a single opcode that takes no argument.
+macro_rules! impl_opcode_function {
+    ($func_key:ident, $opcode:expr) => {
+        #[derive(Clone)]
+        pub struct $func_key;
+        impl txtx_addon_kit::types::functions::FunctionImplementation for $func_key {
+            fn check_instantiability(
+                _fn_spec: &txtx_addon_kit::types::functions::FunctionSpecification,
+                _auth_ctx: &txtx_addon_kit::types::AuthorizationContext,
+                _args: &Vec<txtx_addon_kit::types::types::Type>,
+            ) -> Result<
+                txtx_addon_kit::types::types::Type,
+                txtx_addon_kit::types::diagnostics::Diagnostic,
+            > {
+                unimplemented!()
+            }
+
+            fn run(
+                fn_spec: &txtx_addon_kit::types::functions::FunctionSpecification,
+                _auth_ctx: &txtx_addon_kit::types::AuthorizationContext,
+                _args: &Vec<txtx_addon_kit::types::types::Value>,
+            ) -> Result<
+                txtx_addon_kit::types::types::Value,
+                txtx_addon_kit::types::diagnostics::Diagnostic,
+            > {
+                let code =
+                    $opcode.try_get_code().map_err(|e| crate::functions::to_diag(fn_spec, e))?;
+                Ok(crate::typing::BitcoinValue::opcode(code))
+            }
+        }
+    };
+}
+
 pub mod arithmetic;
 pub mod bitwise_logic;
 pub mod constants;
 pub mod control_flow;
 pub mod crypto;
+pub mod locktime;
 pub mod stack;
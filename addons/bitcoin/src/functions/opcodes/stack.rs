@@ -1,48 +1,189 @@
-use txtx_addon_kit::types::{
-    diagnostics::Diagnostic,
-    functions::{FunctionImplementation, FunctionSpecification},
-    types::{Type, Value},
-    AuthorizationContext,
-};
+use txtx_addon_kit::types::functions::FunctionSpecification;
 
-use crate::{codec::BitcoinOpcode, typing::BitcoinValue};
+use crate::codec::BitcoinOpcode;
 
 lazy_static! {
-    pub static ref STACK_FUNCTIONS: Vec<FunctionSpecification> = vec![define_function! {
-        Dup => {
-            name: "op_dup",
-            documentation: "`btc::op_dup` pushes the `OP_DUP` opcode onto the stack.",
-            example: indoc! {r#"
-                output "opcode" {
-                    value = btc::op_dup()
-                }                
-                // > opcode: 0x76
-            "#},
-            inputs: [],
-            output: {
-                documentation: "A hex representation of the `OP_DUP` opcode.",
-                typing: Type::string()
-            },
-        }
-    },];
+    pub static ref STACK_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_opcode_function! {
+            ToAltStack => {
+                name: "op_toaltstack",
+                opcode: "OP_TOALTSTACK",
+                code: "0x6b",
+                documentation: "Moves the top item of the main stack to the top of the alt stack."
+            }
+        },
+        define_opcode_function! {
+            FromAltStack => {
+                name: "op_fromaltstack",
+                opcode: "OP_FROMALTSTACK",
+                code: "0x6c",
+                documentation: "Moves the top item of the alt stack to the top of the main stack."
+            }
+        },
+        define_opcode_function! {
+            TwoDrop => {
+                name: "op_2drop",
+                opcode: "OP_2DROP",
+                code: "0x6d",
+                documentation: "Removes the top two stack items."
+            }
+        },
+        define_opcode_function! {
+            TwoDup => {
+                name: "op_2dup",
+                opcode: "OP_2DUP",
+                code: "0x6e",
+                documentation: "Duplicates the top two stack items."
+            }
+        },
+        define_opcode_function! {
+            ThreeDup => {
+                name: "op_3dup",
+                opcode: "OP_3DUP",
+                code: "0x6f",
+                documentation: "Duplicates the top three stack items."
+            }
+        },
+        define_opcode_function! {
+            TwoOver => {
+                name: "op_2over",
+                opcode: "OP_2OVER",
+                code: "0x70",
+                documentation: "Copies the pair of items two spaces back in the stack to the front."
+            }
+        },
+        define_opcode_function! {
+            TwoRot => {
+                name: "op_2rot",
+                opcode: "OP_2ROT",
+                code: "0x71",
+                documentation: "Moves the fifth and sixth items to the top of the stack."
+            }
+        },
+        define_opcode_function! {
+            TwoSwap => {
+                name: "op_2swap",
+                opcode: "OP_2SWAP",
+                code: "0x72",
+                documentation: "Swaps the top two pairs of items."
+            }
+        },
+        define_opcode_function! {
+            IfDup => {
+                name: "op_ifdup",
+                opcode: "OP_IFDUP",
+                code: "0x73",
+                documentation: "Duplicates the top stack item if it is not 0."
+            }
+        },
+        define_opcode_function! {
+            Depth => {
+                name: "op_depth",
+                opcode: "OP_DEPTH",
+                code: "0x74",
+                documentation: "Puts the number of stack items onto the stack."
+            }
+        },
+        define_opcode_function! {
+            Drop => {
+                name: "op_drop",
+                opcode: "OP_DROP",
+                code: "0x75",
+                documentation: "Removes the top stack item."
+            }
+        },
+        define_opcode_function! {
+            Dup => {
+                name: "op_dup",
+                opcode: "OP_DUP",
+                code: "0x76",
+                documentation: "Duplicates the top stack item."
+            }
+        },
+        define_opcode_function! {
+            Nip => {
+                name: "op_nip",
+                opcode: "OP_NIP",
+                code: "0x77",
+                documentation: "Removes the second-to-top stack item."
+            }
+        },
+        define_opcode_function! {
+            Over => {
+                name: "op_over",
+                opcode: "OP_OVER",
+                code: "0x78",
+                documentation: "Copies the second-to-top stack item to the top."
+            }
+        },
+        define_opcode_function! {
+            Pick => {
+                name: "op_pick",
+                opcode: "OP_PICK",
+                code: "0x79",
+                documentation: "The item n back in the stack is copied to the top."
+            }
+        },
+        define_opcode_function! {
+            Roll => {
+                name: "op_roll",
+                opcode: "OP_ROLL",
+                code: "0x7a",
+                documentation: "The item n back in the stack is moved to the top."
+            }
+        },
+        define_opcode_function! {
+            Rot => {
+                name: "op_rot",
+                opcode: "OP_ROT",
+                code: "0x7b",
+                documentation: "The top three items on the stack are rotated to the left."
+            }
+        },
+        define_opcode_function! {
+            Swap => {
+                name: "op_swap",
+                opcode: "OP_SWAP",
+                code: "0x7c",
+                documentation: "The top two items on the stack are swapped."
+            }
+        },
+        define_opcode_function! {
+            Tuck => {
+                name: "op_tuck",
+                opcode: "OP_TUCK",
+                code: "0x7d",
+                documentation: "The item at the top of the stack is copied and inserted before the second-to-top item."
+            }
+        },
+        define_opcode_function! {
+            Size => {
+                name: "op_size",
+                opcode: "OP_SIZE",
+                code: "0x82",
+                documentation: "Pushes the string length of the top element of the stack, without removing it."
+            }
+        },
+    ];
 }
 
-#[derive(Clone)]
-pub struct Dup;
-impl FunctionImplementation for Dup {
-    fn check_instantiability(
-        _fn_spec: &FunctionSpecification,
-        _auth_ctx: &AuthorizationContext,
-        _args: &Vec<Type>,
-    ) -> Result<Type, Diagnostic> {
-        unimplemented!()
-    }
-
-    fn run(
-        _fn_spec: &FunctionSpecification,
-        _auth_ctx: &AuthorizationContext,
-        _args: &Vec<Value>,
-    ) -> Result<Value, Diagnostic> {
-        Ok(BitcoinValue::opcode(BitcoinOpcode::OpDup.get_code()))
-    }
-}
+impl_opcode_function!(ToAltStack, BitcoinOpcode::OpToAltStack);
+impl_opcode_function!(FromAltStack, BitcoinOpcode::OpFromAltStack);
+impl_opcode_function!(TwoDrop, BitcoinOpcode::Op2Drop);
+impl_opcode_function!(TwoDup, BitcoinOpcode::Op2Dup);
+impl_opcode_function!(ThreeDup, BitcoinOpcode::Op3Dup);
+impl_opcode_function!(TwoOver, BitcoinOpcode::Op2Over);
+impl_opcode_function!(TwoRot, BitcoinOpcode::Op2Rot);
+impl_opcode_function!(TwoSwap, BitcoinOpcode::Op2Swap);
+impl_opcode_function!(IfDup, BitcoinOpcode::OpIfDup);
+impl_opcode_function!(Depth, BitcoinOpcode::OpDepth);
+impl_opcode_function!(Drop, BitcoinOpcode::OpDrop);
+impl_opcode_function!(Dup, BitcoinOpcode::OpDup);
+impl_opcode_function!(Nip, BitcoinOpcode::OpNip);
+impl_opcode_function!(Over, BitcoinOpcode::OpOver);
+impl_opcode_function!(Pick, BitcoinOpcode::OpPick);
+impl_opcode_function!(Roll, BitcoinOpcode::OpRoll);
+impl_opcode_function!(Rot, BitcoinOpcode::OpRot);
+impl_opcode_function!(Swap, BitcoinOpcode::OpSwap);
+impl_opcode_function!(Tuck, BitcoinOpcode::OpTuck);
+impl_opcode_function!(Size, BitcoinOpcode::OpSize);
@@ -8,6 +8,8 @@ pub mod codec;
 mod commands;
 pub mod constants;
 mod functions;
+pub mod rpc;
+mod signers;
 mod typing;
 
 use constants::NAMESPACE;
@@ -36,7 +38,8 @@ impl Addon for BitcoinNetworkAddon {
     fn get_description(&self) -> &str {
         txtx_addon_kit::indoc! {r#"
             The Bitcoin `txtx` addon enables building Runbooks that interact with the Bitcoin blockchain.
-            Currently the Bitcoin addon can be used to encode Bitcoin Script.
+            The Bitcoin addon can be used to encode Bitcoin Script, and to sign and broadcast transactions
+            through a Bitcoin Core-compatible RPC.
         "#}
     }
 
@@ -53,6 +56,6 @@ impl Addon for BitcoinNetworkAddon {
     }
 
     fn get_signers(&self) -> Vec<SignerSpecification> {
-        vec![]
+        signers::SIGNERS.clone()
     }
 }
@@ -0,0 +1,9 @@
+use txtx_addon_kit::types::signers::SignerSpecification;
+
+mod secret_key;
+
+use secret_key::BITCOIN_SECRET_KEY_SIGNER;
+
+lazy_static! {
+    pub static ref SIGNERS: Vec<SignerSpecification> = vec![BITCOIN_SECRET_KEY_SIGNER.clone()];
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use txtx_addon_kit::channel;
+use txtx_addon_kit::constants::SIGNATURE_APPROVED;
+use txtx_addon_kit::types::commands::CommandExecutionResult;
+use txtx_addon_kit::types::frontend::{
+    ActionItemRequest, ActionItemStatus, ProvideSignedTransactionRequest, ReviewInputRequest,
+};
+use txtx_addon_kit::types::frontend::{Actions, BlockEvent};
+use txtx_addon_kit::types::signers::{
+    return_synchronous_actions, return_synchronous_result, CheckSignabilityOk, SignerActionErr,
+    SignerActionsFutureResult, SignerActivateFutureResult, SignerInstance, SignerSignFutureResult,
+};
+use txtx_addon_kit::types::signers::{SignerImplementation, SignerSpecification};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::{
+    commands::CommandSpecification,
+    diagnostics::Diagnostic,
+    types::{Type, Value},
+};
+use txtx_addon_kit::types::{
+    signers::SignersState, types::RunbookSupervisionContext, ConstructDid,
+};
+
+use crate::codec::crypto::{
+    parse_network, secret_key_from_mnemonic, secret_key_from_str, AddressType,
+};
+use crate::constants::{
+    ACTION_ITEM_CHECK_ADDRESS, ACTION_ITEM_PROVIDE_SIGNED_TRANSACTION, ADDRESS_TYPE,
+    CHECKED_ADDRESS, DEFAULT_NETWORK, DERIVATION_PATH, FORMATTED_TRANSACTION, MNEMONIC, NAMESPACE,
+    NETWORK, SECRET_KEY, SIGNED_PSBT, SIGNED_TRANSACTION, SIGNER_ADDRESS, SIGNER_PUBLIC_KEY,
+    SIGNER_SECRET_KEY_BYTES, UNSIGNED_PSBT_BYTES,
+};
+use crate::typing::BitcoinValue;
+
+lazy_static! {
+    pub static ref BITCOIN_SECRET_KEY_SIGNER: SignerSpecification = define_signer! {
+        BitcoinSecretKeySigner => {
+          name: "Bitcoin Secret Key Signer",
+          matcher: "secret_key",
+          documentation:txtx_addon_kit::indoc! {r#"The `btc::secret_key` signer can be used to synchronously sign a PSBT. The signer controls a native segwit (BIP-84) or taproot (BIP-86) address."#},
+          inputs: [
+            secret_key: {
+                documentation: "The secret key used to sign transactions, encoded in WIF or hex.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                sensitive: true
+            },
+            mnemonic: {
+                documentation: "The mnemonic phrase used to generate the secret key. This input will not be used if the `secret_key` input is provided.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                sensitive: true
+            },
+            derivation_path: {
+                documentation: "The derivation path used to generate the secret key. This input will not be used if the `secret_key` input is provided. The default is `m/84'/0'/0'/0/0` for p2wpkh addresses and `m/86'/0'/0'/0/0` for p2tr addresses, with a coin type of `1'` outside of mainnet.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                sensitive: true
+            },
+            password: {
+                documentation: "The BIP-39 passphrase used along with the mnemonic.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                sensitive: true
+            },
+            address_type: {
+                documentation: "The type of address controlled by the signer. Options are 'p2wpkh' (BIP-84) and 'p2tr' (BIP-86). The default is 'p2wpkh'.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                sensitive: false
+            },
+            network: {
+                documentation: "The Bitcoin network. Options are 'mainnet', 'testnet', 'signet' and 'regtest'. The default is 'mainnet'.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                sensitive: false
+            }
+          ],
+          outputs: [
+              public_key: {
+                documentation: "The public key of the account generated from the secret key.",
+                typing: Type::buffer()
+              },
+              address: {
+                documentation: "The address generated from the secret key.",
+                typing: Type::string()
+              }
+          ],
+          example: txtx_addon_kit::indoc! {r#"
+        // we can create a secret key signer by providing a mnemonic and computing the secret key
+        signer "alice" "btc::secret_key" {
+            mnemonic = "board list obtain sugar hour worth raven scout denial thunder horse logic fury scorpion fold genuine phrase wealth news aim below celery when cabin"
+            address_type = "p2tr"
+            network = "regtest"
+        }
+        // or we can create one by providing the secret key directly
+        signer "bob" "btc::secret_key" {
+            secret_key = "cVt4o7BGAig1UXywgGSmARhxMdzP5qvQsxKkSsc1XEkw3tDTQFpy"
+            network = "regtest"
+        }
+    "#},
+      }
+    };
+}
+
+pub struct BitcoinSecretKeySigner;
+impl SignerImplementation for BitcoinSecretKeySigner {
+    fn check_instantiability(
+        _ctx: &SignerSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn check_activability(
+        construct_did: &ConstructDid,
+        instance_name: &str,
+        _spec: &SignerSpecification,
+        values: &ValueStore,
+        mut signer_state: ValueStore,
+        signers: SignersState,
+        _signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        supervision_context: &RunbookSupervisionContext,
+        _auth_ctx: &txtx_addon_kit::types::AuthorizationContext,
+        _is_balance_check_required: bool,
+        _is_public_key_required: bool,
+    ) -> SignerActionsFutureResult {
+        let mut actions = Actions::none();
+
+        if signer_state.get_value(SIGNER_ADDRESS).is_some() {
+            return return_synchronous_actions(Ok((signers, signer_state, actions)));
+        }
+
+        let network = parse_network(values.get_string(NETWORK))
+            .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+        let address_type = AddressType::from_some_str(values.get_string(ADDRESS_TYPE))
+            .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+
+        let secret_key = if let Some(secret_key) = values.get_string(SECRET_KEY) {
+            secret_key_from_str(secret_key, network)
+        } else {
+            let mnemonic = values
+                .get_expected_string(MNEMONIC)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+            secret_key_from_mnemonic(
+                mnemonic,
+                values.get_string(DERIVATION_PATH),
+                values.get_string("password"),
+                &address_type,
+                network,
+            )
+        }
+        .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+
+        let secp = bitcoin::key::Secp256k1::new();
+        let public_key = secret_key.public_key(&secp);
+        let expected_address = address_type.address(&secret_key, network).to_string();
+
+        let network_name = values.get_string(NETWORK).unwrap_or(DEFAULT_NETWORK).to_string();
+        let activate = |signer_state: &mut ValueStore| {
+            signer_state
+                .insert(SIGNER_SECRET_KEY_BYTES, Value::buffer(secret_key.secret_bytes().to_vec()));
+            signer_state.insert(SIGNER_PUBLIC_KEY, Value::buffer(public_key.serialize().to_vec()));
+            signer_state.insert(SIGNER_ADDRESS, Value::string(expected_address.clone()));
+            signer_state.insert(NETWORK, Value::string(network_name.clone()));
+            signer_state.insert(ADDRESS_TYPE, Value::string(address_type.to_string()));
+            signer_state.insert(CHECKED_ADDRESS, Value::string(expected_address.clone()));
+        };
+
+        if supervision_context.review_input_values {
+            if let Ok(_) = values.get_expected_string(CHECKED_ADDRESS) {
+                activate(&mut signer_state);
+            } else {
+                actions.push_sub_group(
+                    None,
+                    vec![ActionItemRequest::new(
+                        &Some(construct_did.clone()),
+                        &format!("Check {} expected address", instance_name),
+                        None,
+                        ActionItemStatus::Todo,
+                        ReviewInputRequest::new("", &Value::string(expected_address.clone()))
+                            .to_action_type(),
+                        ACTION_ITEM_CHECK_ADDRESS,
+                    )],
+                );
+            }
+        } else {
+            activate(&mut signer_state);
+        }
+        return_synchronous_actions(Ok((signers, signer_state, actions)))
+    }
+
+    fn activate(
+        _construct_id: &ConstructDid,
+        _spec: &SignerSpecification,
+        _values: &ValueStore,
+        signer_state: ValueStore,
+        signers: SignersState,
+        _signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        _progress_tx: &channel::Sender<BlockEvent>,
+    ) -> SignerActivateFutureResult {
+        let mut result = CommandExecutionResult::new();
+        let address = signer_state
+            .get_expected_value(SIGNER_ADDRESS)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let public_key = signer_state
+            .get_expected_value(SIGNER_PUBLIC_KEY)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        result.outputs.insert("address".into(), address.clone());
+        result.outputs.insert("public_key".into(), public_key.clone());
+        return_synchronous_result(Ok((signers, signer_state, result)))
+    }
+
+    fn check_signability(
+        construct_did: &ConstructDid,
+        title: &str,
+        description: &Option<String>,
+        payload: &Value,
+        _spec: &SignerSpecification,
+        _values: &ValueStore,
+        signer_state: ValueStore,
+        signers: SignersState,
+        _signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        supervision_context: &RunbookSupervisionContext,
+    ) -> Result<CheckSignabilityOk, SignerActionErr> {
+        let actions = if supervision_context.review_input_values {
+            let construct_did_str = &construct_did.to_string();
+            if let Some(_) = signer_state.get_scoped_value(&construct_did_str, SIGNATURE_APPROVED) {
+                return Ok((signers, signer_state, Actions::none()));
+            }
+
+            let network = signer_state
+                .get_expected_string(NETWORK)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+            let formatted_payload =
+                signer_state.get_scoped_value(&construct_did_str, FORMATTED_TRANSACTION);
+
+            let request = ActionItemRequest::new(
+                &Some(construct_did.clone()),
+                title,
+                description.clone(),
+                ActionItemStatus::Todo,
+                ProvideSignedTransactionRequest::new(
+                    &signer_state.uuid,
+                    &payload,
+                    NAMESPACE,
+                    network,
+                )
+                .check_expectation_action_uuid(construct_did)
+                .only_approval_needed()
+                .formatted_payload(formatted_payload)
+                .to_action_type(),
+                ACTION_ITEM_PROVIDE_SIGNED_TRANSACTION,
+            );
+            Actions::append_item(
+                request,
+                Some("Review and sign the transactions from the list below"),
+                Some("Transaction Signing"),
+            )
+        } else {
+            Actions::none()
+        };
+        Ok((signers, signer_state, actions))
+    }
+
+    fn sign(
+        caller_uuid: &ConstructDid,
+        _title: &str,
+        _payload: &Value,
+        _spec: &SignerSpecification,
+        _values: &ValueStore,
+        signer_state: ValueStore,
+        signers: SignersState,
+        _signers_instances: &HashMap<ConstructDid, SignerInstance>,
+    ) -> SignerSignFutureResult {
+        use crate::codec::crypto::parse_address;
+        use crate::codec::psbt::{extract_signed_transaction, sign_psbt};
+        use bitcoin::psbt::Psbt;
+        use bitcoin::secp256k1::SecretKey;
+
+        let mut result = CommandExecutionResult::new();
+
+        let secret_key_bytes = signer_state
+            .get_expected_buffer_bytes(SIGNER_SECRET_KEY_BYTES)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let secret_key = SecretKey::from_slice(&secret_key_bytes).map_err(|e| {
+            (signers.clone(), signer_state.clone(), diagnosed_error!("invalid secret key: {e}"))
+        })?;
+        let network = parse_network(signer_state.get_string(NETWORK))
+            .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+        let address = signer_state
+            .get_expected_string(SIGNER_ADDRESS)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let address = parse_address(address, network)
+            .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+
+        let psbt_bytes = signer_state
+            .get_expected_scoped_buffer_bytes(&caller_uuid.to_string(), UNSIGNED_PSBT_BYTES)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let mut psbt = Psbt::deserialize(&psbt_bytes).map_err(|e| {
+            (signers.clone(), signer_state.clone(), diagnosed_error!("invalid psbt: {e}"))
+        })?;
+
+        let signed_inputs = sign_psbt(&mut psbt, &secret_key, &address)
+            .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+        if signed_inputs == 0 {
+            return Err((
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!("the psbt does not spend any output locked to {address}"),
+            ));
+        }
+
+        result.outputs.insert(SIGNED_PSBT.into(), BitcoinValue::psbt(psbt.serialize()));
+        // other signers may still need to sign some inputs, in which case the transaction can't be extracted yet
+        if let Ok(transaction) = extract_signed_transaction(psbt) {
+            result.outputs.insert(
+                SIGNED_TRANSACTION.into(),
+                BitcoinValue::transaction(bitcoin::consensus::encode::serialize(&transaction)),
+            );
+        }
+        return_synchronous_result(Ok((signers, signer_state, result)))
+    }
+}
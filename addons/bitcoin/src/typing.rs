@@ -2,6 +2,8 @@ use txtx_addon_kit::types::types::Value;
 
 pub const BITCOIN_OPCODE: &str = "btc::opcode";
 pub const BITCOIN_SCRIPT: &str = "btc::script";
+pub const BITCOIN_PSBT: &str = "btc::psbt";
+pub const BITCOIN_TRANSACTION: &str = "btc::transaction";
 
 pub struct BitcoinValue {}
 
@@ -12,4 +14,10 @@ impl BitcoinValue {
     pub fn script(bytes: Vec<u8>) -> Value {
         Value::addon(bytes, BITCOIN_SCRIPT)
     }
+    pub fn psbt(bytes: Vec<u8>) -> Value {
+        Value::addon(bytes, BITCOIN_PSBT)
+    }
+    pub fn transaction(bytes: Vec<u8>) -> Value {
+        Value::addon(bytes, BITCOIN_TRANSACTION)
+    }
 }
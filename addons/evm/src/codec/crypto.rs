@@ -1,6 +1,8 @@
+use std::path::PathBuf;
+
 use alloy::{
     hex::FromHex,
-    primitives::{keccak256, Address},
+    primitives::{keccak256, Address, B256},
     signers::k256::ecdsa::{SigningKey, VerifyingKey},
 };
 use alloy_signer_local::{coins_bip39::English, LocalSigner, MnemonicBuilder};
@@ -43,6 +45,14 @@ pub fn secret_key_to_secret_key_signer(secret_key: &Vec<u8>) -> Result<SecretKey
     Ok(signer)
 }
 
+pub fn keystore_to_secret_key_signer(
+    keystore_path: &PathBuf,
+    password: &str,
+) -> Result<SecretKeySigner, String> {
+    SecretKeySigner::decrypt_keystore(keystore_path, password)
+        .map_err(|e| format!("failed to decrypt keystore {}: {e}", keystore_path.display()))
+}
+
 pub fn field_bytes_to_secret_key_signer(field_bytes: &Vec<u8>) -> Result<SecretKeySigner, String> {
     let bytes = GenericArray::from_slice(field_bytes);
     SecretKeySigner::from_field_bytes(bytes)
@@ -93,3 +103,72 @@ pub fn public_key_from_signed_message(
     let public_key_bytes = public_key.serialize().to_vec();
     Ok(public_key_bytes)
 }
+
+pub fn address_from_signed_hash(hash: &B256, signature_bytes: &[u8]) -> Result<Address, String> {
+    if signature_bytes.len() != 65 {
+        return Err("failed to recover signer: invalid signature length".to_string());
+    }
+    let signature_array: &[u8; 64] = signature_bytes[0..64]
+        .try_into()
+        .map_err(|_| "failed to recover signer: invalid signature length")?;
+    let signature = Signature::parse_standard(signature_array)
+        .map_err(|e| format!("failed to recover signer: invalid signature: {e}"))?;
+
+    let recovery_id = RecoveryId::parse(signature_bytes[64].saturating_sub(27))
+        .map_err(|e| format!("failed to recover signer: invalid recovery id: {e}"))?;
+    let message = Message::parse_slice(hash.as_slice())
+        .map_err(|e| format!("failed to recover signer: invalid message hash: {e}"))?;
+    let public_key = recover(&message, &signature, &recovery_id)
+        .map_err(|e| format!("failed to recover signer: failed to recover public key: {e}"))?;
+    public_key_to_address(&public_key.serialize().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    /// The PBKDF2 test vector of the Web3 Secret Storage Definition.
+    const KEYSTORE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+
+    #[test]
+    fn it_decrypts_keystores() {
+        let dir = std::env::temp_dir().join(format!("txtx-evm-crypto-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let keystore_path = dir.join("keystore.json");
+        std::fs::write(&keystore_path, KEYSTORE).unwrap();
+
+        let signer = keystore_to_secret_key_signer(&keystore_path, "testpassword").unwrap();
+        assert_eq!(
+            hex::encode(signer.credential().to_bytes()),
+            "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d"
+        );
+        assert_eq!(
+            signer.address(),
+            Address::from_str("0x008aeeda4d805471df9b2a5b0f38a0c3bcba786b").unwrap()
+        );
+
+        let err = keystore_to_secret_key_signer(&keystore_path, "wrongpassword").unwrap_err();
+        assert!(err.starts_with(&format!("failed to decrypt keystore {}", keystore_path.display())));
+        assert!(keystore_to_secret_key_signer(&dir.join("missing.json"), "testpassword").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,13 +2,17 @@ pub mod contract_deployment;
 pub mod crypto;
 pub mod foundry;
 pub mod hardhat;
+pub mod safe;
 pub mod verify;
 
 use std::collections::VecDeque;
 use std::num::NonZeroUsize;
 
 use crate::commands::actions::get_expected_address;
-use crate::constants::{GAS_PRICE, MAX_FEE_PER_GAS, MAX_PRIORITY_FEE_PER_GAS};
+use crate::constants::{
+    ACCESS_LIST, AUTHORIZATION_LIST, BLOB_DATA, GAS_PRICE, MAX_FEE_PER_BLOB_GAS, MAX_FEE_PER_GAS,
+    MAX_PRIORITY_FEE_PER_GAS,
+};
 use crate::rpc::EvmRpc;
 use crate::typing::{
     DecodedLog, EvmValue, EVM_ADDRESS, EVM_BYTES, EVM_BYTES32, EVM_FUNCTION_CALL, EVM_INIT_CODE,
@@ -17,13 +21,17 @@ use crate::typing::{
 use alloy::consensus::{SignableTransaction, Transaction, TypedTransaction};
 use alloy::dyn_abi::parser::TypeSpecifier;
 use alloy::dyn_abi::{DynSolValue, EventExt, FunctionExt, Word};
+use alloy::eips::eip4844::builder::{SidecarBuilder, SimpleCoder};
+use alloy::eips::eip4844::BlobTransactionSidecar;
+use alloy::eips::eip7702::{Authorization, SignedAuthorization};
 use alloy::hex::{self, FromHex};
 use alloy::json_abi::{Constructor, JsonAbi, Param};
-use alloy::network::TransactionBuilder;
+use alloy::network::{TransactionBuilder, TransactionBuilder4844, TransactionBuilder7702};
 use alloy::primitives::utils::format_units;
-use alloy::primitives::{Address, TxKind, U256};
+use alloy::primitives::{Address, TxKind, B256, U256};
+use alloy::rlp::Decodable;
 use alloy::rpc::types::TransactionRequest;
-use alloy_rpc_types::{AccessList, Log};
+use alloy_rpc_types::{AccessList, AccessListItem, Log};
 use contract_deployment::AddressAbiMap;
 use txtx_addon_kit::types::diagnostics::Diagnostic;
 use txtx_addon_kit::types::stores::ValueStore;
@@ -35,6 +43,7 @@ pub enum TransactionType {
     EIP2930,
     EIP1559,
     EIP4844,
+    EIP7702,
 }
 
 impl TransactionType {
@@ -49,6 +58,7 @@ impl TransactionType {
             "eip2930" => Ok(TransactionType::EIP2930),
             "eip1559" => Ok(TransactionType::EIP1559),
             "eip4844" => Ok(TransactionType::EIP4844),
+            "eip7702" => Ok(TransactionType::EIP7702),
             other => Err(diagnosed_error!("invalid Ethereum Transaction type: {}", other)),
         }
     }
@@ -111,15 +121,16 @@ pub async fn build_unsigned_transaction(
             build_unsigned_legacy_transaction(&rpc, args, &filled_fields).await?
         }
         TransactionType::EIP2930 => {
-            println!("Unsupported tx type EIP2930 was used. Defaulting to EIP1559 tx");
-            build_unsigned_eip1559_transaction(&rpc, args, &filled_fields).await?
+            build_unsigned_eip2930_transaction(&rpc, args, &filled_fields).await?
         }
         TransactionType::EIP1559 => {
             build_unsigned_eip1559_transaction(&rpc, args, &filled_fields).await?
         }
         TransactionType::EIP4844 => {
-            println!("Unsupported tx type EIP4844 was used. Defaulting to EIP1559 tx");
-            build_unsigned_eip1559_transaction(&rpc, args, &filled_fields).await?
+            build_unsigned_eip4844_transaction(&rpc, args, &filled_fields).await?
+        }
+        TransactionType::EIP7702 => {
+            build_unsigned_eip7702_transaction(&rpc, args, &filled_fields).await?
         }
     };
 
@@ -207,10 +218,188 @@ async fn build_unsigned_eip1559_transaction(
     if let Some(code) = &fields.deploy_code {
         tx = tx.with_deploy_code(code.clone()).with_kind(TxKind::Create);
     }
+    if let Some(access_list) = get_access_list(args)? {
+        tx = tx.with_access_list(access_list);
+    }
+
+    Ok(tx)
+}
+
+async fn build_unsigned_eip2930_transaction(
+    rpc: &EvmRpc,
+    args: &ValueStore,
+    fields: &FilledCommonTransactionFields,
+) -> Result<TransactionRequest, String> {
+    let tx = build_unsigned_legacy_transaction(rpc, args, fields).await?;
+
+    // if no access list is provided, have the node generate one for this transaction
+    let access_list = match get_access_list(args)? {
+        Some(access_list) => access_list,
+        None => rpc.create_access_list(&tx).await.map_err(|e| e.to_string())?,
+    };
+    Ok(tx.with_access_list(access_list))
+}
+
+async fn build_unsigned_eip4844_transaction(
+    rpc: &EvmRpc,
+    args: &ValueStore,
+    fields: &FilledCommonTransactionFields,
+) -> Result<TransactionRequest, String> {
+    if fields.to.is_none() {
+        return Err("EIP-4844 transactions cannot be used to deploy contracts".into());
+    }
+    let sidecar = get_blob_sidecar(args)?
+        .ok_or(format!("EIP-4844 transactions require the '{BLOB_DATA}' field"))?;
+
+    let max_fee_per_blob_gas =
+        args.get_value(MAX_FEE_PER_BLOB_GAS).map(|v| v.expect_uint()).transpose()?;
+    let max_fee_per_blob_gas = match max_fee_per_blob_gas {
+        Some(max_fee_per_blob_gas) => max_fee_per_blob_gas as u128,
+        None => rpc.estimate_max_fee_per_blob_gas().await.map_err(|e| e.to_string())?,
+    };
+
+    let tx = build_unsigned_eip1559_transaction(rpc, args, fields)
+        .await?
+        .with_max_fee_per_blob_gas(max_fee_per_blob_gas)
+        .with_blob_sidecar(sidecar);
+    Ok(tx)
+}
+
+async fn build_unsigned_eip7702_transaction(
+    rpc: &EvmRpc,
+    args: &ValueStore,
+    fields: &FilledCommonTransactionFields,
+) -> Result<TransactionRequest, String> {
+    if fields.to.is_none() {
+        return Err("EIP-7702 transactions cannot be used to deploy contracts".into());
+    }
+    let authorization_list = get_authorization_list(args)?.unwrap_or_default();
+    if authorization_list.is_empty() {
+        return Err(format!("EIP-7702 transactions require a non-empty '{AUTHORIZATION_LIST}'"));
+    }
 
+    let tx = build_unsigned_eip1559_transaction(rpc, args, fields)
+        .await?
+        .with_authorization_list(authorization_list);
     Ok(tx)
 }
 
+/// Parses the `access_list` argument, expected to be an array of objects with an `address`
+/// and an optional array of `storage_keys`.
+fn get_access_list(args: &ValueStore) -> Result<Option<AccessList>, String> {
+    let Some(value) = args.get_value(ACCESS_LIST) else {
+        return Ok(None);
+    };
+    let entries = value.as_array().ok_or(format!("'{ACCESS_LIST}' must be an array"))?;
+
+    let mut items = vec![];
+    for (i, entry) in entries.iter().enumerate() {
+        let msg = format!("invalid access list entry #{}", i + 1);
+        let entry = entry.as_object().ok_or(format!("{msg}: expected object"))?;
+        let address = entry.get("address").ok_or(format!("{msg}: missing field 'address'"))?;
+        let address = get_expected_address(address).map_err(|e| format!("{msg}: {e}"))?;
+
+        let storage_keys = match entry.get("storage_keys") {
+            Some(keys) => keys
+                .as_array()
+                .ok_or(format!("{msg}: 'storage_keys' must be an array"))?
+                .iter()
+                .map(|key| {
+                    let bytes = key.get_buffer_bytes_result()?;
+                    if bytes.len() > 32 {
+                        return Err(format!("storage key is {} bytes long", bytes.len()));
+                    }
+                    Ok(B256::left_padding_from(&bytes))
+                })
+                .collect::<Result<Vec<_>, String>>()
+                .map_err(|e| format!("{msg}: invalid storage key: {e}"))?,
+            None => vec![],
+        };
+        items.push(AccessListItem { address, storage_keys });
+    }
+    Ok(Some(AccessList(items)))
+}
+
+/// Builds a blob sidecar from the `blob_data` argument, which can be a single buffer or an
+/// array of buffers. The data is encoded into as many blobs as needed.
+fn get_blob_sidecar(args: &ValueStore) -> Result<Option<BlobTransactionSidecar>, String> {
+    let Some(value) = args.get_value(BLOB_DATA) else {
+        return Ok(None);
+    };
+    let buffers = match value.as_array() {
+        Some(values) => values.iter().collect::<Vec<_>>(),
+        None => vec![value],
+    };
+
+    let data = buffers
+        .iter()
+        .enumerate()
+        .map(|(i, buffer)| {
+            buffer
+                .get_buffer_bytes_result()
+                .map_err(|e| format!("invalid blob data #{}: {e}", i + 1))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if data.iter().all(|bytes| bytes.is_empty()) {
+        return Err(format!("'{BLOB_DATA}' must not be empty"));
+    }
+
+    let mut builder = SidecarBuilder::<SimpleCoder>::new();
+    for bytes in data.iter() {
+        builder.ingest(bytes);
+    }
+    let sidecar = builder.build().map_err(|e| format!("failed to build blob sidecar: {e}"))?;
+    Ok(Some(sidecar))
+}
+
+/// Parses the `authorization_list` argument. Each entry is either an object with the
+/// `chain_id`, `address`, `nonce`, `y_parity`, `r` and `s` fields, or the RLP encoded
+/// signed authorization (as produced by `cast wallet sign-auth`).
+fn get_authorization_list(args: &ValueStore) -> Result<Option<Vec<SignedAuthorization>>, String> {
+    let Some(value) = args.get_value(AUTHORIZATION_LIST) else {
+        return Ok(None);
+    };
+    let entries = value.as_array().ok_or(format!("'{AUTHORIZATION_LIST}' must be an array"))?;
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            value_to_signed_authorization(entry)
+                .map_err(|e| format!("invalid authorization #{}: {e}", i + 1))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+fn value_to_signed_authorization(value: &Value) -> Result<SignedAuthorization, String> {
+    let Some(entry) = value.as_object() else {
+        let bytes = value.get_buffer_bytes_result()?;
+        return SignedAuthorization::decode(&mut bytes.as_slice())
+            .map_err(|e| format!("failed to decode authorization: {e}"));
+    };
+
+    let get_field = |key: &str| entry.get(key).ok_or(format!("missing field '{key}'"));
+    let get_uint = |key: &str| -> Result<u64, String> {
+        get_field(key)?.as_uint().ok_or(format!("field '{key}' must be an integer"))?
+    };
+    let get_u256 = |key: &str| -> Result<U256, String> {
+        let bytes = get_field(key)?.get_buffer_bytes_result()?;
+        U256::try_from_be_slice(&bytes).ok_or(format!("field '{key}' must be at most 32 bytes"))
+    };
+
+    let address = get_expected_address(get_field("address")?)?;
+    let chain_id = get_uint("chain_id")?;
+    let nonce = get_uint("nonce")?;
+    let y_parity = u8::try_from(get_uint("y_parity")?)
+        .map_err(|_| "field 'y_parity' must be 0 or 1".to_string())?;
+    let r = get_u256("r")?;
+    let s = get_u256("s")?;
+
+    let authorization = Authorization { chain_id: U256::from(chain_id), address, nonce };
+    Ok(SignedAuthorization::new_unchecked(authorization, y_parity, r, s))
+}
+
 async fn set_gas_limit(
     rpc: &EvmRpc,
     mut tx: TransactionRequest,
@@ -641,16 +830,64 @@ pub fn format_transaction_for_display(typed_transaction: &TypedTransaction) -> V
                 Value::integer(tx.max_priority_fee_per_gas as i128),
             );
         }
-        TypedTransaction::Eip4844(_tx) => {
-            unimplemented!("EIP-4844 is not supported");
+        TypedTransaction::Eip4844(tx) => {
+            let tx = tx.tx();
+            res.insert(
+                "access_list",
+                Value::array(format_access_list_for_display(&tx.access_list)),
+            );
+            res.insert("max_fee_per_gas", Value::integer(tx.max_fee_per_gas as i128));
+            res.insert(
+                "max_priority_fee_per_gas",
+                Value::integer(tx.max_priority_fee_per_gas as i128),
+            );
+            res.insert("max_fee_per_blob_gas", Value::integer(tx.max_fee_per_blob_gas as i128));
+            res.insert(
+                "blob_versioned_hashes",
+                Value::array(
+                    tx.blob_versioned_hashes
+                        .iter()
+                        .map(|hash| Value::string(hash.to_string()))
+                        .collect::<Vec<Value>>(),
+                ),
+            );
         }
-        TypedTransaction::Eip7702(_tx) => {
-            unimplemented!("EIP-7702 is not supported");
+        TypedTransaction::Eip7702(tx) => {
+            res.insert(
+                "access_list",
+                Value::array(format_access_list_for_display(&tx.access_list)),
+            );
+            res.insert("max_fee_per_gas", Value::integer(tx.max_fee_per_gas as i128));
+            res.insert(
+                "max_priority_fee_per_gas",
+                Value::integer(tx.max_priority_fee_per_gas as i128),
+            );
+            res.insert(
+                "authorization_list",
+                Value::array(format_authorization_list_for_display(&tx.authorization_list)),
+            );
         }
     }
     res.to_value()
 }
 
+pub fn format_authorization_list_for_display(
+    authorization_list: &[SignedAuthorization],
+) -> Vec<Value> {
+    authorization_list
+        .iter()
+        .map(|authorization| {
+            let inner = authorization.inner();
+            ObjectType::from(vec![
+                ("chain_id", Value::string(inner.chain_id.to_string())),
+                ("address", Value::string(inner.address.to_string())),
+                ("nonce", Value::integer(inner.nonce as i128)),
+            ])
+            .to_value()
+        })
+        .collect::<Vec<Value>>()
+}
+
 pub fn format_access_list_for_display(access_list: &AccessList) -> Vec<Value> {
     access_list
         .0
@@ -684,11 +921,25 @@ pub async fn get_transaction_cost(
             let base_fee = rpc.get_base_fee_per_gas().await.map_err(|e| e.to_string())?;
             tx.effective_gas_price(Some(base_fee as u64))
         }
-        TypedTransaction::Eip4844(_tx) => unimplemented!("EIP-4844 is not supported"),
-        TypedTransaction::Eip7702(_tx) => unimplemented!("EIP-7702 is not supported"),
+        TypedTransaction::Eip4844(tx) => {
+            let base_fee = rpc.get_base_fee_per_gas().await.map_err(|e| e.to_string())?;
+            tx.effective_gas_price(Some(base_fee as u64))
+        }
+        TypedTransaction::Eip7702(tx) => {
+            let base_fee = rpc.get_base_fee_per_gas().await.map_err(|e| e.to_string())?;
+            tx.effective_gas_price(Some(base_fee as u64))
+        }
     };
     let gas_limit = transaction.gas_limit();
-    let cost: i128 = effective_gas_price as i128 * gas_limit as i128;
+    let mut cost: i128 = effective_gas_price as i128 * gas_limit as i128;
+
+    // blob gas is paid on top of the execution gas
+    if let TypedTransaction::Eip4844(tx) = &transaction {
+        let tx = tx.tx();
+        let blob_base_fee = rpc.get_blob_base_fee().await.map_err(|e| e.to_string())?;
+        let blob_gas_price = blob_base_fee.min(tx.max_fee_per_blob_gas);
+        cost += blob_gas_price as i128 * tx.blob_gas() as i128;
+    }
     Ok(cost)
 }
 
@@ -744,3 +995,182 @@ pub fn abi_decode_logs(abi_map: &Value, logs: &[Log]) -> Result<Vec<Value>, Stri
         .collect::<Result<Vec<Value>, String>>()?;
     Ok(logs)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use alloy::eips::eip4844::builder::SidecarCoder;
+    use txtx_addon_kit::types::Did;
+
+    use super::*;
+
+    fn args(inputs: Vec<(&str, Value)>) -> ValueStore {
+        let mut args = ValueStore::new("args", &Did::zero());
+        for (key, value) in inputs {
+            args.insert(key, value);
+        }
+        args
+    }
+
+    fn object(fields: Vec<(&str, Value)>) -> Value {
+        ObjectType::from(fields).to_value()
+    }
+
+    const ADDRESS: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+
+    #[test]
+    fn it_parses_access_lists() {
+        assert_eq!(get_access_list(&args(vec![])).unwrap(), None);
+
+        let access_list = get_access_list(&args(vec![(
+            ACCESS_LIST,
+            Value::array(vec![
+                object(vec![
+                    ("address", Value::string(ADDRESS.into())),
+                    (
+                        "storage_keys",
+                        Value::array(vec![
+                            Value::string("0x01".into()),
+                            Value::buffer(vec![0xff; 32]),
+                        ]),
+                    ),
+                ]),
+                object(vec![("address", EvmValue::address(&Address::ZERO))]),
+            ]),
+        )]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            access_list,
+            AccessList(vec![
+                AccessListItem {
+                    address: Address::from_str(ADDRESS).unwrap(),
+                    storage_keys: vec![B256::with_last_byte(1), B256::repeat_byte(0xff)],
+                },
+                AccessListItem { address: Address::ZERO, storage_keys: vec![] },
+            ])
+        );
+
+        let get_error = |access_list: Value| {
+            get_access_list(&args(vec![(ACCESS_LIST, access_list)])).unwrap_err()
+        };
+        assert_eq!(get_error(Value::string(ADDRESS.into())), "'access_list' must be an array");
+        let err = get_error(Value::array(vec![
+            object(vec![("address", Value::string(ADDRESS.into()))]),
+            object(vec![("storage_keys", Value::array(vec![]))]),
+        ]));
+        assert_eq!(err, "invalid access list entry #2: missing field 'address'");
+        let err = get_error(Value::array(vec![object(vec![
+            ("address", Value::string(ADDRESS.into())),
+            ("storage_keys", Value::array(vec![Value::buffer(vec![1; 33])])),
+        ])]));
+        assert_eq!(
+            err,
+            "invalid access list entry #1: invalid storage key: storage key is 33 bytes long"
+        );
+    }
+
+    #[test]
+    fn it_builds_blob_sidecars() {
+        assert!(get_blob_sidecar(&args(vec![])).unwrap().is_none());
+
+        // a single buffer is accepted as well as an array of buffers
+        let sidecar = get_blob_sidecar(&args(vec![(BLOB_DATA, Value::buffer(b"hello".to_vec()))]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(sidecar.blobs.len(), 1);
+        assert_eq!(
+            SimpleCoder::default().decode_all(&sidecar.blobs).unwrap(),
+            vec![b"hello".to_vec()]
+        );
+
+        // data is spread over as many blobs as needed
+        let large_data = vec![0xab; 200_000];
+        let sidecar = get_blob_sidecar(&args(vec![(
+            BLOB_DATA,
+            Value::array(vec![Value::buffer(b"hello".to_vec()), Value::buffer(large_data.clone())]),
+        )]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(sidecar.blobs.len(), 2);
+        assert_eq!(sidecar.commitments.len(), 2);
+        assert_eq!(sidecar.proofs.len(), 2);
+        assert_eq!(
+            SimpleCoder::default().decode_all(&sidecar.blobs).unwrap(),
+            vec![b"hello".to_vec(), large_data]
+        );
+
+        for empty_data in [Value::array(vec![]), Value::buffer(vec![])] {
+            let err = get_blob_sidecar(&args(vec![(BLOB_DATA, empty_data)])).unwrap_err();
+            assert_eq!(err, "'blob_data' must not be empty");
+        }
+        let err = get_blob_sidecar(&args(vec![(
+            BLOB_DATA,
+            Value::array(vec![Value::buffer(vec![1]), Value::bool(true)]),
+        )]))
+        .unwrap_err();
+        assert!(err.starts_with("invalid blob data #2"), "{}", err);
+    }
+
+    #[test]
+    fn it_parses_signed_authorizations() {
+        let address = Address::from_str(ADDRESS).unwrap();
+        let expected = SignedAuthorization::new_unchecked(
+            Authorization { chain_id: U256::from(11155111), address, nonce: 3 },
+            1,
+            U256::from(0x1234),
+            U256::MAX,
+        );
+        let fields = vec![
+            ("chain_id", Value::integer(11155111)),
+            ("address", Value::string(ADDRESS.into())),
+            ("nonce", Value::integer(3)),
+            ("y_parity", Value::integer(1)),
+            ("r", Value::string("0x1234".into())),
+            ("s", Value::buffer(vec![0xff; 32])),
+        ];
+        assert_eq!(value_to_signed_authorization(&object(fields.clone())).unwrap(), expected);
+
+        // RLP encoded authorizations, as signed by `cast wallet sign-auth`
+        let encoded = alloy::rlp::encode(&expected);
+        assert_eq!(
+            value_to_signed_authorization(&Value::string(hex::encode_prefixed(&encoded))).unwrap(),
+            expected
+        );
+        assert!(value_to_signed_authorization(&Value::buffer(encoded[..10].to_vec())).is_err());
+
+        let get_error = |key: &'static str, value: Option<Value>| {
+            let mut fields = fields.clone();
+            fields.retain(|(k, _)| *k != key);
+            if let Some(value) = value {
+                fields.push((key, value));
+            }
+            value_to_signed_authorization(&object(fields)).unwrap_err()
+        };
+        assert_eq!(get_error("nonce", None), "missing field 'nonce'");
+        assert_eq!(
+            get_error("chain_id", Some(Value::string("1".into()))),
+            "field 'chain_id' must be an integer"
+        );
+        assert_eq!(
+            get_error("y_parity", Some(Value::integer(256))),
+            "field 'y_parity' must be 0 or 1"
+        );
+        assert_eq!(
+            get_error("r", Some(Value::buffer(vec![1; 33]))),
+            "field 'r' must be at most 32 bytes"
+        );
+    }
+
+    #[test]
+    fn it_parses_authorization_lists() {
+        assert_eq!(get_authorization_list(&args(vec![])).unwrap(), None);
+        let err = get_authorization_list(&args(vec![(
+            AUTHORIZATION_LIST,
+            Value::array(vec![object(vec![("address", Value::string(ADDRESS.into()))])]),
+        )]))
+        .unwrap_err();
+        assert_eq!(err, "invalid authorization #1: missing field 'chain_id'");
+    }
+}
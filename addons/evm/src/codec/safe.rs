@@ -0,0 +1,302 @@
+use alloy::dyn_abi::{DynSolValue, FunctionExt};
+use alloy::hex;
+use alloy::json_abi::Function;
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{keccak256, Address, TxKind, B256, U256};
+use alloy::rpc::types::TransactionRequest;
+
+use crate::rpc::EvmRpc;
+
+use super::crypto::address_from_signed_hash;
+
+const SAFE_TX_TYPEHASH_DEFINITION: &str = "SafeTx(address to,uint256 value,bytes data,uint8 operation,uint256 safeTxGas,uint256 baseGas,uint256 gasPrice,address gasToken,address refundReceiver,uint256 nonce)";
+const EXEC_TRANSACTION_SIGNATURE: &str =
+    "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)";
+
+/// The on-chain configuration of a Safe, fetched when the signer is activated.
+#[derive(Clone, Debug)]
+pub struct SafeInfo {
+    pub owners: Vec<Address>,
+    pub threshold: u64,
+    pub nonce: u64,
+    pub domain_separator: B256,
+}
+
+impl SafeInfo {
+    pub async fn fetch(rpc: &EvmRpc, safe_address: &Address) -> Result<Self, String> {
+        let owners = call_safe_view_function(rpc, safe_address, "getOwners() returns (address[])")
+            .await?
+            .as_array()
+            .ok_or("invalid response for 'getOwners'")?
+            .iter()
+            .map(|owner| owner.as_address().ok_or("invalid owner address returned by 'getOwners'"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let threshold =
+            call_safe_view_function(rpc, safe_address, "getThreshold() returns (uint256)").await?;
+        let threshold: u64 = threshold
+            .as_uint()
+            .and_then(|(threshold, _)| threshold.try_into().ok())
+            .ok_or("invalid response for 'getThreshold'")?;
+
+        let nonce = call_safe_view_function(rpc, safe_address, "nonce() returns (uint256)").await?;
+        let nonce: u64 = nonce
+            .as_uint()
+            .and_then(|(nonce, _)| nonce.try_into().ok())
+            .ok_or("invalid response for 'nonce'")?;
+
+        let domain_separator =
+            call_safe_view_function(rpc, safe_address, "domainSeparator() returns (bytes32)")
+                .await?;
+        let domain_separator = domain_separator
+            .as_fixed_bytes()
+            .map(|(bytes, _)| B256::from_slice(bytes))
+            .ok_or("invalid response for 'domainSeparator'")?;
+
+        Ok(SafeInfo { owners, threshold, nonce, domain_separator })
+    }
+}
+
+async fn call_safe_view_function(
+    rpc: &EvmRpc,
+    safe_address: &Address,
+    signature: &str,
+) -> Result<DynSolValue, String> {
+    let function = Function::parse(signature)
+        .map_err(|e| format!("invalid Safe function signature '{signature}': {e}"))?;
+    let input = function
+        .abi_encode_input(&[])
+        .map_err(|e| format!("failed to encode '{}' call: {e}", function.name))?;
+
+    let tx = TransactionRequest::default().with_to(*safe_address).with_input(input);
+    let result = rpc.call(&tx).await.map_err(|e| {
+        format!("failed to call '{}' on Safe {}: {}", function.name, safe_address, e.to_string())
+    })?;
+    let result = hex::decode(result)
+        .map_err(|e| format!("invalid response for '{}': {e}", function.name))?;
+
+    function
+        .abi_decode_output(&result)
+        .map_err(|e| format!("failed to decode '{}' response: {e}", function.name))?
+        .into_iter()
+        .next()
+        .ok_or(format!("empty response for '{}'", function.name))
+}
+
+/// A call executed by a Safe through `execTransaction`.
+/// Refunds are not supported, so the gas related fields of the Safe transaction are always zero.
+#[derive(Clone, Debug)]
+pub struct SafeTransaction {
+    pub to: Address,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub nonce: u64,
+}
+
+impl SafeTransaction {
+    /// Extracts the call to be executed by the Safe from the transaction built by an action.
+    pub fn from_transaction_request(tx: &TransactionRequest, nonce: u64) -> Result<Self, String> {
+        let to = match tx.to {
+            Some(TxKind::Call(to)) => to,
+            _ => {
+                return Err("Safe transactions cannot deploy contracts directly: use a factory contract instead".into())
+            }
+        };
+        let value = tx.value.unwrap_or_default();
+        let data = tx.input.input().map(|data| data.to_vec()).unwrap_or_default();
+        Ok(SafeTransaction { to, value, data, nonce })
+    }
+
+    /// Computes the EIP-712 hash of the Safe transaction, which is what owners sign.
+    pub fn hash(&self, domain_separator: &B256) -> B256 {
+        let struct_hash = keccak256(
+            DynSolValue::Tuple(vec![
+                DynSolValue::FixedBytes(keccak256(SAFE_TX_TYPEHASH_DEFINITION), 32),
+                DynSolValue::Address(self.to),
+                DynSolValue::Uint(self.value, 256),
+                DynSolValue::FixedBytes(keccak256(&self.data), 32),
+                // operation (call), safeTxGas, baseGas, gasPrice, gasToken and refundReceiver
+                DynSolValue::Uint(U256::ZERO, 8),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Address(Address::ZERO),
+                DynSolValue::Address(Address::ZERO),
+                DynSolValue::Uint(U256::from(self.nonce), 256),
+            ])
+            .abi_encode_params(),
+        );
+
+        let mut bytes = vec![0x19, 0x01];
+        bytes.extend_from_slice(domain_separator.as_slice());
+        bytes.extend_from_slice(struct_hash.as_slice());
+        keccak256(bytes)
+    }
+
+    /// Encodes the `execTransaction` call for this transaction, with the owners' packed signatures.
+    pub fn encode_exec_transaction(&self, signatures: &[u8]) -> Result<Vec<u8>, String> {
+        let function = Function::parse(EXEC_TRANSACTION_SIGNATURE)
+            .map_err(|e| format!("invalid execTransaction signature: {e}"))?;
+        function
+            .abi_encode_input(&[
+                DynSolValue::Address(self.to),
+                DynSolValue::Uint(self.value, 256),
+                DynSolValue::Bytes(self.data.clone()),
+                DynSolValue::Uint(U256::ZERO, 8),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Address(Address::ZERO),
+                DynSolValue::Address(Address::ZERO),
+                DynSolValue::Bytes(signatures.to_vec()),
+            ])
+            .map_err(|e| format!("failed to encode execTransaction call: {e}"))
+    }
+}
+
+/// Converts a signature of the Safe transaction hash, provided by a wallet through `personal_sign`,
+/// into a Safe `eth_sign` signature, after checking that it was produced by `owner`.
+pub fn eth_sign_signature_for_owner(
+    safe_tx_hash: &B256,
+    signature: &[u8],
+    owner: &Address,
+) -> Result<Vec<u8>, String> {
+    if signature.len() != 65 {
+        return Err(format!("expected a 65 bytes signature, got {} bytes", signature.len()));
+    }
+    let mut signature = signature.to_vec();
+    if signature[64] < 27 {
+        signature[64] += 27;
+    }
+
+    let mut message = b"\x19Ethereum Signed Message:\n32".to_vec();
+    message.extend_from_slice(safe_tx_hash.as_slice());
+    let signer = address_from_signed_hash(&keccak256(message), &signature)?;
+    if &signer != owner {
+        return Err(format!(
+            "signature of Safe transaction {} was produced by {}, expected {}",
+            safe_tx_hash, signer, owner
+        ));
+    }
+
+    // the Safe contract expects eth_sign signatures to have their `v` value increased by 4
+    signature[64] += 4;
+    Ok(signature)
+}
+
+/// Packs owner signatures in the format expected by `execTransaction`: the Safe contract
+/// requires the signatures to be sorted by owner address.
+pub fn pack_signatures(mut signatures: Vec<(Address, Vec<u8>)>) -> Vec<u8> {
+    signatures.sort_by(|(a, _), (b, _)| a.cmp(b));
+    signatures.into_iter().flat_map(|(_, signature)| signature).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use alloy::signers::SignerSync;
+
+    use super::*;
+    use crate::codec::crypto::secret_key_to_secret_key_signer;
+
+    fn b256(value: &str) -> B256 {
+        B256::from_str(value).unwrap()
+    }
+
+    fn address(byte: u8) -> Address {
+        Address::from([byte; 20])
+    }
+
+    #[test]
+    fn it_hashes_safe_transactions() {
+        // SAFE_TX_TYPEHASH of the Safe contracts
+        assert_eq!(
+            keccak256(SAFE_TX_TYPEHASH_DEFINITION),
+            b256("0xbb8310d486368db6bd6f849402fdd73ad53d316b5a4b2644ad6efe0f941286d8")
+        );
+
+        // domain separator of the Safe 0x1111…1111 on sepolia
+        let domain_separator =
+            b256("0xc6733c0928edbae256c68c60f094464cc27845147092bc929b1d868a03d5e3dc");
+        // transfer(0x2222…2222, 1000) of the token 0x3333…3333, with 1 ether of value
+        let tx = SafeTransaction {
+            to: address(0x33),
+            value: U256::from(1_000_000_000_000_000_000u128),
+            data: hex::decode(
+                "a9059cbb0000000000000000000000002222222222222222222222222222222222222222\
+                00000000000000000000000000000000000000000000000000000000000003e8",
+            )
+            .unwrap(),
+            nonce: 7,
+        };
+        assert_eq!(
+            tx.hash(&domain_separator),
+            b256("0x1e57386d628242a8ad7625587abc0f0cb54f449a125dfc79ba57125543e9e7ce")
+        );
+
+        let mut next_tx = tx.clone();
+        next_tx.nonce = 8;
+        assert_ne!(next_tx.hash(&domain_separator), tx.hash(&domain_separator));
+    }
+
+    #[test]
+    fn it_converts_personal_signatures_to_eth_sign_signatures() {
+        let owner = secret_key_to_secret_key_signer(&vec![1; 32]).unwrap();
+        let safe_tx_hash =
+            b256("0x1e57386d628242a8ad7625587abc0f0cb54f449a125dfc79ba57125543e9e7ce");
+        let personal_signature =
+            owner.sign_message_sync(safe_tx_hash.as_slice()).unwrap().as_bytes().to_vec();
+
+        let signature =
+            eth_sign_signature_for_owner(&safe_tx_hash, &personal_signature, &owner.address())
+                .unwrap();
+        assert_eq!(signature[..64], personal_signature[..64]);
+        assert_eq!(signature[64], personal_signature[64] + 4);
+        assert!(signature[64] == 31 || signature[64] == 32);
+
+        // the Safe contract recovers the owner from the prefixed hash, with `v - 4`
+        let mut message = b"\x19Ethereum Signed Message:\n32".to_vec();
+        message.extend_from_slice(safe_tx_hash.as_slice());
+        let mut recoverable_signature = signature.clone();
+        recoverable_signature[64] -= 4;
+        assert_eq!(
+            address_from_signed_hash(&keccak256(message), &recoverable_signature).unwrap(),
+            owner.address()
+        );
+
+        // `v` values of 0 and 1 are accepted as well
+        let mut signature_with_parity = personal_signature.clone();
+        signature_with_parity[64] -= 27;
+        assert_eq!(
+            eth_sign_signature_for_owner(&safe_tx_hash, &signature_with_parity, &owner.address())
+                .unwrap(),
+            signature
+        );
+
+        let err = eth_sign_signature_for_owner(&safe_tx_hash, &personal_signature, &address(1))
+            .unwrap_err();
+        assert!(err.contains(&format!("was produced by {}", owner.address())), "{}", err);
+        assert!(eth_sign_signature_for_owner(
+            &safe_tx_hash,
+            &personal_signature[..64],
+            &owner.address()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn it_packs_signatures_by_owner_address() {
+        let signatures = vec![
+            (address(0xcc), vec![0xcc; 65]),
+            (address(0x0a), vec![0x0a; 65]),
+            (address(0xb0), vec![0xb0; 65]),
+        ];
+        let packed = pack_signatures(signatures);
+        assert_eq!(packed.len(), 3 * 65);
+        assert_eq!(packed[..65], [0x0a; 65]);
+        assert_eq!(packed[65..130], [0xb0; 65]);
+        assert_eq!(packed[130..], [0xcc; 65]);
+        assert!(pack_signatures(vec![]).is_empty());
+    }
+}
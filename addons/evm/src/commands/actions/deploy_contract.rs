@@ -158,7 +158,7 @@ lazy_static! {
                         internal: false
                     },
                     type: {
-                        documentation: "The transaction type. Options are 'Legacy', 'EIP2930', 'EIP1559', 'EIP4844'. The default is 'EIP1559'.",
+                        documentation: "The transaction type. Options are 'Legacy', 'EIP2930', 'EIP1559', 'EIP4844', 'EIP7702'. The default is 'EIP1559'.",
                         typing: Type::string(),
                         optional: true,
                         tainting: false,
@@ -178,6 +178,34 @@ lazy_static! {
                         tainting: false,
                         internal: false
                     },
+                    max_fee_per_blob_gas: {
+                        documentation: "Sets the max fee per blob gas of an EIP4844 transaction. If omitted, this value will be estimated from the blob base fee of the network.",
+                        typing: Type::integer(),
+                        optional: true,
+                        tainting: false,
+                        internal: false
+                    },
+                    access_list: {
+                        documentation: "The access list of the transaction, as an array of objects with an 'address' and an array of 'storage_keys'. Used by EIP2930, EIP1559, EIP4844 and EIP7702 transactions. For EIP2930 transactions, the access list will be generated by the network if omitted.",
+                        typing: Type::array(Type::arbitrary_object()),
+                        optional: true,
+                        tainting: false,
+                        internal: false
+                    },
+                    blob_data: {
+                        documentation: "The data to be included as blobs in an EIP4844 transaction, as an array of buffers. Use `evm::get_blob_data_from_file` to load the data from a file.",
+                        typing: Type::array(Type::buffer()),
+                        optional: true,
+                        tainting: false,
+                        internal: false
+                    },
+                    authorization_list: {
+                        documentation: "The authorization list of an EIP7702 transaction. Each entry is either an object with the 'chain_id', 'address', 'nonce', 'y_parity', 'r' and 's' fields, or an RLP encoded signed authorization, such as the output of `cast wallet sign-auth`.",
+                        typing: Type::array(Type::arbitrary_object()),
+                        optional: true,
+                        tainting: false,
+                        internal: false
+                    },
                     nonce: {
                         documentation: "The account nonce of the signer. This value will be retrieved from the network if omitted.",
                         typing: Type::integer(),
@@ -83,7 +83,7 @@ lazy_static! {
                 internal: false
             },
             type: {
-                documentation: "The transaction type. Options are 'Legacy', 'EIP2930', 'EIP1559', 'EIP4844'. The default is 'EIP1559'. This value will be retrieved from the network if omitted.",
+                documentation: "The transaction type. Options are 'Legacy', 'EIP2930', 'EIP1559', 'EIP4844', 'EIP7702'. The default is 'EIP1559'. This value will be retrieved from the network if omitted.",
                 typing: Type::string(),
                 optional: true,
                 tainting: false,
@@ -10,6 +10,7 @@ pub const OUT_DIR: &str = env!("OUT_DIR");
 pub const NAMESPACE: &str = "evm";
 
 pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
+pub const DEFAULT_FOUNDRY_KEYSTORES_DIR: &str = "~/.foundry/keystores";
 
 // Signer attached storage keys
 pub const CHECKED_PUBLIC_KEY: &str = "checked_public_key";
@@ -29,6 +30,15 @@ pub const TRANSACTION_PAYLOAD_BYTES: &str = "transaction_payload_bytes";
 pub const SIGNED_MESSAGE_BYTES: &str = "signed_message_bytes";
 pub const MESSAGE_BYTES: &str = "message_bytes";
 pub const FORMATTED_TRANSACTION: &str = "formatted_transaction";
+pub const SAFE_ADDRESS: &str = "safe_address";
+pub const SAFE_THRESHOLD: &str = "safe_threshold";
+pub const SAFE_NONCE: &str = "safe_nonce";
+pub const SAFE_DOMAIN_SEPARATOR: &str = "safe_domain_separator";
+pub const SAFE_SIGNATURES: &str = "safe_signatures";
+pub const KEYSTORE_ACCOUNT: &str = "keystore_account";
+pub const KEYSTORE_PATH: &str = "keystore_path";
+pub const KEYSTORE_PASSWORD: &str = "keystore_password";
+pub const KEYSTORE_PASSWORD_FILE: &str = "keystore_password_file";
 
 // Defaults keys
 pub const CHAIN_ID: &str = "chain_id";
@@ -44,6 +54,10 @@ pub const GAS_LIMIT: &str = "gas_limit";
 pub const GAS_PRICE: &str = "gas_price";
 pub const MAX_FEE_PER_GAS: &str = "max_fee_per_gas";
 pub const MAX_PRIORITY_FEE_PER_GAS: &str = "max_priority_fee_per_gas";
+pub const MAX_FEE_PER_BLOB_GAS: &str = "max_fee_per_blob_gas";
+pub const ACCESS_LIST: &str = "access_list";
+pub const BLOB_DATA: &str = "blob_data";
+pub const AUTHORIZATION_LIST: &str = "authorization_list";
 pub const CONTRACT_ADDRESS: &str = "contract_address";
 pub const IMPL_CONTRACT_ADDRESS: &str = "impl_contract_address";
 pub const PROXY_CONTRACT_ADDRESS: &str = "proxy_contract_address";
@@ -93,6 +107,8 @@ pub const ACTION_ITEM_CHECK_NONCE: &str = "check_nonce";
 pub const ACTION_ITEM_CHECK_FEE: &str = "check_fee";
 pub const ACTION_ITEM_PROVIDE_PUBLIC_KEY: &str = "provide_public_key";
 pub const ACTION_ITEM_PROVIDE_SIGNED_TRANSACTION: &str = "provide_signed_transaction";
+pub const ACTION_ITEM_PROVIDE_SIGNED_MESSAGE: &str = "provide_signed_message";
+pub const ACTION_ITEM_PROVIDE_KEYSTORE_PASSWORD: &str = "provide_keystore_password";
 pub const ACTION_ITEM_SEND_TRANSACTION: &str = "send_transaction";
 pub const ACTION_OPEN_MODAL: &str = "open_modal";
 
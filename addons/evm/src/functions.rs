@@ -417,6 +417,32 @@ lazy_static! {
                     typing: DEPLOYMENT_ARTIFACTS_TYPE.clone()
                 },
             }
+        },
+        define_function! {
+            GetBlobDataFromFile => {
+                name: "get_blob_data_from_file",
+                documentation: "`evm::get_blob_data_from_file` reads the content of a file, to be included as blob data in an EIP-4844 transaction.",
+                example: indoc! {r#"
+                action "blob_tx" "evm::send_eth" {
+                    recipient_address = input.recipient
+                    amount = 0
+                    type = "EIP4844"
+                    blob_data = [evm::get_blob_data_from_file("./data/blob.bin")]
+                    signer = signer.deployer
+                }
+                "#},
+                inputs: [
+                    path: {
+                        documentation: "The path of the file, relative to the txtx manifest.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The content of the file.",
+                    typing: Type::buffer()
+                },
+            }
         }
     ];
 }
@@ -1123,3 +1149,44 @@ fn format_fn_error(ctx: &str, position: u64, expected: &str, actual: Option<&Val
         actual.and_then(|v| Some(v.get_type()))
     );
 }
+
+#[derive(Clone)]
+pub struct GetBlobDataFromFile;
+impl FunctionImplementation for GetBlobDataFromFile {
+    fn check_instantiability(
+        _fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        _args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let path_str = args.get(0).unwrap().as_string().unwrap();
+
+        let path = Path::new(path_str);
+        let location = if path.is_absolute() {
+            FileLocation::from_path(path.to_path_buf())
+        } else {
+            let mut workspace_loc = auth_ctx
+                .workspace_location
+                .get_parent_location()
+                .map_err(|e| to_diag(fn_spec, format!("unable to read workspace location: {e}")))?;
+
+            workspace_loc
+                .append_path(&path_str.to_string())
+                .map_err(|e| to_diag(fn_spec, format!("invalid blob data path: {}", e)))?;
+            workspace_loc
+        };
+
+        let bytes = location
+            .read_content()
+            .map_err(|e| to_diag(fn_spec, format!("failed to read blob data: {}", e)))?;
+        Ok(Value::buffer(bytes))
+    }
+}
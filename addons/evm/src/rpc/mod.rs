@@ -15,11 +15,12 @@ use alloy_provider::fillers::{
     BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller,
 };
 use alloy_provider::utils::{
-    EIP1559_FEE_ESTIMATION_PAST_BLOCKS, EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE,
+    EIP1559_BASE_FEE_MULTIPLIER, EIP1559_FEE_ESTIMATION_PAST_BLOCKS,
+    EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE,
 };
 use alloy_provider::Identity;
 use alloy_rpc_types::trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions};
-use alloy_rpc_types::{Block, BlockId, BlockNumberOrTag, FeeHistory};
+use alloy_rpc_types::{AccessList, Block, BlockId, BlockNumberOrTag, FeeHistory};
 use txtx_addon_kit::reqwest::Url;
 
 #[derive(Debug)]
@@ -157,6 +158,39 @@ impl EvmRpc {
         .await
     }
 
+    pub async fn create_access_list(
+        &self,
+        tx: &TransactionRequest,
+    ) -> Result<AccessList, RpcError> {
+        let result = EvmRpc::retry_async(|| async {
+            self.provider.create_access_list(tx).await.map_err(|e| {
+                RpcError::Message(format!("error creating access list: {}", e.to_string()))
+            })
+        })
+        .await?;
+
+        if let Some(error) = result.error {
+            return Err(RpcError::Message(format!("error creating access list: {}", error)));
+        }
+        Ok(result.access_list)
+    }
+
+    pub async fn get_blob_base_fee(&self) -> Result<u128, RpcError> {
+        EvmRpc::retry_async(|| async {
+            self.provider.get_blob_base_fee().await.map_err(|e| {
+                RpcError::Message(format!("error getting blob base fee: {}", e.to_string()))
+            })
+        })
+        .await
+    }
+
+    /// Estimates the max fee per blob gas of a transaction, leaving room for the blob base fee
+    /// to rise before the transaction is included, as the EIP-1559 fee estimation does.
+    pub async fn estimate_max_fee_per_blob_gas(&self) -> Result<u128, RpcError> {
+        let blob_base_fee = self.get_blob_base_fee().await?;
+        Ok(blob_base_fee.saturating_mul(EIP1559_BASE_FEE_MULTIPLIER))
+    }
+
     pub async fn estimate_eip1559_fees(&self) -> Result<Eip1559Estimation, RpcError> {
         EvmRpc::retry_async(|| async {
             self.provider.estimate_eip1559_fees().await.map_err(|e| {
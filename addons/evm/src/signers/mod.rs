@@ -1,13 +1,15 @@
 use txtx_addon_kit::types::signers::SignerSpecification;
 
 pub mod common;
+mod safe;
 mod secret_key;
 mod web_wallet;
 
+use safe::EVM_SAFE_SIGNER;
 use secret_key::EVM_SECRET_KEY_SIGNER;
 use web_wallet::EVM_WEB_WALLET;
 
 lazy_static! {
     pub static ref WALLETS: Vec<SignerSpecification> =
-        vec![EVM_SECRET_KEY_SIGNER.clone(), EVM_WEB_WALLET.clone()];
+        vec![EVM_SECRET_KEY_SIGNER.clone(), EVM_WEB_WALLET.clone(), EVM_SAFE_SIGNER.clone()];
 }
@@ -0,0 +1,601 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, B256};
+use alloy::rpc::types::TransactionRequest;
+use alloy::signers::SignerSync;
+use txtx_addon_kit::channel;
+use txtx_addon_kit::constants::{SIGNED_MESSAGE_BYTES, TX_HASH};
+use txtx_addon_kit::types::commands::CommandExecutionResult;
+use txtx_addon_kit::types::frontend::{
+    ActionItemRequest, ActionItemRequestType, ActionItemStatus, Actions, BlockEvent,
+    ProvideSignedMessageRequest,
+};
+use txtx_addon_kit::types::signers::{
+    return_synchronous_result, CheckSignabilityOk, SignerActionErr, SignerActionsFutureResult,
+    SignerActivateFutureResult, SignerImplementation, SignerInstance, SignerSignFutureResult,
+    SignerSpecification, SignersState,
+};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::RunbookSupervisionContext;
+use txtx_addon_kit::types::{
+    diagnostics::Diagnostic,
+    types::{Type, Value},
+};
+use txtx_addon_kit::types::{ConstructDid, Did};
+
+use crate::codec::crypto::field_bytes_to_secret_key_signer;
+use crate::codec::safe::{eth_sign_signature_for_owner, pack_signatures, SafeTransaction};
+use crate::constants::{
+    ACTION_ITEM_PROVIDE_SIGNED_MESSAGE, ALREADY_DEPLOYED, CHAIN_ID, CHECKED_ADDRESS, NAMESPACE,
+    SAFE_ADDRESS, SAFE_DOMAIN_SEPARATOR, SAFE_NONCE, SAFE_SIGNATURES, SAFE_THRESHOLD,
+    SECRET_KEY_WALLET_UNSIGNED_TRANSACTION_BYTES,
+};
+use crate::typing::EvmValue;
+
+lazy_static! {
+    pub static ref EVM_SAFE_SIGNER: SignerSpecification = define_signer! {
+        EvmSafeSigner => {
+            name: "EVM Safe Signer",
+            matcher: "safe",
+            documentation: txtx_addon_kit::indoc! {r#"The `evm::safe` signer executes transactions through a [Safe](https://safe.global) multisig account.
+            For each transaction, the signers listed as owners are asked to sign the Safe transaction hash until the Safe threshold is met.
+            The transaction is then submitted with `execTransaction` by the first `evm::secret_key` signer of the list."#},
+            inputs: [
+                safe_address: {
+                    documentation: "The address of the Safe account.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    sensitive: false
+                },
+                signers: {
+                    documentation: "A list of signers that are owners of the Safe. At least one of them must be an `evm::secret_key` signer, which is used to submit the transactions.",
+                    typing: Type::array(Type::string()),
+                    optional: false,
+                    tainting: true,
+                    sensitive: false
+                }
+            ],
+            outputs: [
+                address: {
+                    documentation: "The address of the Safe account.",
+                    typing: Type::string()
+                },
+                signers: {
+                    documentation: "The list of signers that are owners of the Safe.",
+                    typing: Type::array(Type::string())
+                },
+                threshold: {
+                    documentation: "The number of owner signatures required by the Safe.",
+                    typing: Type::integer()
+                }
+            ],
+            example: txtx_addon_kit::indoc! {r#"
+                signer "alice" "evm::secret_key" {
+                    secret_key = input.alice_secret_key
+                }
+
+                signer "bob" "evm::web_wallet" {
+                    expected_address = "0xCe246168E59dd8e28e367BB49b38Dc621768F425"
+                }
+
+                signer "protocol_safe" "evm::safe" {
+                    safe_address = "0x2A9E1A45F53D6bD3D8F3C2D1b9c1E17d0C8d5E21"
+                    signers = [signer.alice, signer.bob]
+                }
+            "#},
+        }
+    };
+}
+
+pub struct EvmSafeSigner;
+impl SignerImplementation for EvmSafeSigner {
+    fn check_instantiability(
+        _ctx: &SignerSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    // The owner signers are activated before the Safe signer, so their addresses are
+    // available in their signer states. Once checked against the Safe's owners, the Safe
+    // configuration is stored in the Safe signer state.
+    #[cfg(not(feature = "wasm"))]
+    fn check_activability(
+        construct_did: &ConstructDid,
+        instance_name: &str,
+        _spec: &SignerSpecification,
+        values: &ValueStore,
+        mut signer_state: ValueStore,
+        signers: SignersState,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        supervision_context: &RunbookSupervisionContext,
+        _auth_ctx: &txtx_addon_kit::types::AuthorizationContext,
+        is_balance_check_required: bool,
+        _is_public_key_required: bool,
+    ) -> SignerActionsFutureResult {
+        use txtx_addon_kit::types::signers::return_synchronous_actions;
+
+        use crate::{
+            codec::safe::SafeInfo, constants::RPC_API_URL, rpc::EvmRpc,
+            signers::common::get_additional_actions_for_address,
+        };
+
+        if signer_state.get_value(CHECKED_ADDRESS).is_some() {
+            return return_synchronous_actions(Ok((signers, signer_state, Actions::none())));
+        }
+
+        let safe_address = values
+            .get_expected_value(SAFE_ADDRESS)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let safe_address = EvmValue::to_address(safe_address)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let chain_id = values
+            .get_expected_uint(CHAIN_ID)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let rpc_api_url = values
+            .get_expected_string(RPC_API_URL)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .to_string();
+
+        let owner_signer_instances = get_owner_signer_instances(values, signers_instances)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let owner_addresses = get_owner_addresses(&owner_signer_instances, &signers)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+        let construct_did = construct_did.clone();
+        let instance_name = instance_name.to_string();
+        let supervision_context = supervision_context.clone();
+
+        let future = async move {
+            let rpc = EvmRpc::new(&rpc_api_url)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+            let safe_info = SafeInfo::fetch(&rpc, &safe_address).await.map_err(|e| {
+                (
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!("Safe {safe_address}: {e}"),
+                )
+            })?;
+
+            for ((_, owner_instance), owner_address) in
+                owner_signer_instances.iter().zip(owner_addresses.iter())
+            {
+                if !safe_info.owners.contains(owner_address) {
+                    return Err((
+                        signers.clone(),
+                        signer_state.clone(),
+                        diagnosed_error!(
+                            "signer '{}' ({}) is not an owner of Safe {}",
+                            owner_instance.name,
+                            owner_address,
+                            safe_address
+                        ),
+                    ));
+                }
+            }
+            if (owner_signer_instances.len() as u64) < safe_info.threshold {
+                return Err((
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!(
+                        "Safe {} requires {} signatures, but only {} owner signers were provided",
+                        safe_address,
+                        safe_info.threshold,
+                        owner_signer_instances.len()
+                    ),
+                ));
+            }
+            if get_executor_signer_did(&owner_signer_instances).is_none() {
+                return Err((
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!(
+                        "at least one of the Safe signers must be an 'evm::secret_key' signer"
+                    ),
+                ));
+            }
+
+            let mut actions = Actions::none();
+            if supervision_context.review_input_values {
+                let action_items = get_additional_actions_for_address(
+                    &Some(safe_address),
+                    &construct_did,
+                    &instance_name,
+                    &rpc_api_url,
+                    chain_id,
+                    false,
+                    is_balance_check_required,
+                    true,
+                )
+                .await
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+                if !action_items.is_empty() {
+                    actions.push_group(
+                        "Review and check the following signer related action items",
+                        action_items,
+                    );
+                }
+            }
+
+            signer_state.insert(CHECKED_ADDRESS, Value::string(safe_address.to_string()));
+            signer_state.insert("signer_address", Value::string(safe_address.to_string()));
+            signer_state.insert(SAFE_THRESHOLD, Value::integer(safe_info.threshold.into()));
+            signer_state.insert(SAFE_NONCE, Value::integer(safe_info.nonce.into()));
+            signer_state.insert(
+                SAFE_DOMAIN_SEPARATOR,
+                EvmValue::bytes32(safe_info.domain_separator.to_vec()),
+            );
+            Ok((signers, signer_state, actions))
+        };
+        Ok(Box::pin(future))
+    }
+
+    fn activate(
+        _construct_id: &ConstructDid,
+        _spec: &SignerSpecification,
+        values: &ValueStore,
+        mut signer_state: ValueStore,
+        signers: SignersState,
+        _signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        _progress_tx: &channel::Sender<BlockEvent>,
+    ) -> SignerActivateFutureResult {
+        let mut result = CommandExecutionResult::new();
+        let address = signer_state
+            .get_expected_value(CHECKED_ADDRESS)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .clone();
+        let threshold = signer_state
+            .get_expected_value(SAFE_THRESHOLD)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .clone();
+        let owner_signers = values
+            .get_expected_value("signers")
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .clone();
+
+        // signing happens with the action's values, so the owners are kept in the signer state
+        signer_state.insert("signers", owner_signers.clone());
+
+        result.outputs.insert("address".into(), address);
+        result.outputs.insert("signers".into(), owner_signers);
+        result.outputs.insert("threshold".into(), threshold);
+        return_synchronous_result(Ok((signers, signer_state, result)))
+    }
+
+    // Collects the owners' signatures of the Safe transaction hash: `evm::secret_key` owners
+    // sign directly, other owners are prompted with a `ProvideSignedMessage` action item.
+    // Once the threshold is met, the packed signatures are stored in the Safe signer state.
+    fn check_signability(
+        construct_did: &ConstructDid,
+        title: &str,
+        description: &Option<String>,
+        _payload: &Value,
+        _spec: &SignerSpecification,
+        values: &ValueStore,
+        mut signer_state: ValueStore,
+        signers: SignersState,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        supervision_context: &RunbookSupervisionContext,
+    ) -> Result<CheckSignabilityOk, SignerActionErr> {
+        let construct_did_str = construct_did.to_string();
+        if signer_state.get_scoped_value(&construct_did_str, TX_HASH).is_some()
+            || signer_state.get_scoped_value(&construct_did_str, SAFE_SIGNATURES).is_some()
+        {
+            return Ok((signers, signer_state, Actions::none()));
+        }
+
+        let already_deployed =
+            signer_state.get_scoped_bool(&construct_did_str, ALREADY_DEPLOYED).unwrap_or(false);
+        if already_deployed {
+            // the contract is already deployed, so no transaction will be sent by this signer
+            signer_state.insert_scoped_value(&construct_did_str, TX_HASH, Value::null());
+            return Ok((signers, signer_state, Actions::none()));
+        }
+
+        let chain_id = values
+            .get_expected_uint(CHAIN_ID)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let (_, safe_tx_hash) = get_safe_transaction(&construct_did_str, &mut signer_state)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let threshold = signer_state
+            .get_expected_uint(SAFE_THRESHOLD)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+        let owner_signer_instances = get_owner_signer_instances(&signer_state, signers_instances)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let owner_addresses = get_owner_addresses(&owner_signer_instances, &signers)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+        let mut signatures = vec![];
+        let mut pending_owners = vec![];
+        for ((owner_did, owner_instance), owner_address) in
+            owner_signer_instances.iter().zip(owner_addresses.into_iter())
+        {
+            let owner_state = signers.get_signer_state(owner_did).unwrap();
+            let signature = if let Ok(field_bytes) =
+                owner_state.get_expected_buffer_bytes("signer_field_bytes")
+            {
+                let secret_key_signer =
+                    field_bytes_to_secret_key_signer(&field_bytes).map_err(|e| {
+                        (signers.clone(), signer_state.clone(), diagnosed_error!("{e}"))
+                    })?;
+                let signature = secret_key_signer.sign_hash_sync(&safe_tx_hash).map_err(|e| {
+                    (
+                        signers.clone(),
+                        signer_state.clone(),
+                        diagnosed_error!("signer '{}': {e}", owner_instance.name),
+                    )
+                })?;
+                signature.as_bytes().to_vec()
+            } else if let Some(signed_message) =
+                owner_state.get_scoped_value(&construct_did_str, SIGNED_MESSAGE_BYTES)
+            {
+                let signed_message = signed_message.get_buffer_bytes_result().map_err(|e| {
+                    (signers.clone(), signer_state.clone(), diagnosed_error!("{e}"))
+                })?;
+                eth_sign_signature_for_owner(&safe_tx_hash, &signed_message, &owner_address)
+                    .map_err(|e| {
+                        (
+                            signers.clone(),
+                            signer_state.clone(),
+                            diagnosed_error!("signer '{}': {e}", owner_instance.name),
+                        )
+                    })?
+            } else {
+                pending_owners.push((owner_did, owner_instance));
+                continue;
+            };
+            signatures.push((owner_address, signature));
+        }
+
+        if signatures.len() as u64 >= threshold {
+            signatures.truncate(threshold as usize);
+            signer_state.insert_scoped_value(
+                &construct_did_str,
+                SAFE_SIGNATURES,
+                Value::buffer(pack_signatures(signatures)),
+            );
+            return Ok((signers, signer_state, Actions::none()));
+        }
+
+        if !supervision_context.review_input_values {
+            return Err((
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!(
+                    "Safe transaction {} requires {} signatures, only {} could be collected without supervision",
+                    safe_tx_hash,
+                    threshold,
+                    signatures.len()
+                ),
+            ));
+        }
+
+        let mut actions = Actions::none();
+        for (owner_did, owner_instance) in pending_owners.into_iter() {
+            let request = ActionItemRequest::new(
+                &Some(construct_did.clone()),
+                &format!("{} - {}", title, owner_instance.name),
+                description.clone(),
+                ActionItemStatus::Todo,
+                ActionItemRequestType::ProvideSignedMessage(ProvideSignedMessageRequest {
+                    check_expectation_action_uuid: Some(construct_did.clone()),
+                    signer_uuid: owner_did.clone(),
+                    message: Value::string(safe_tx_hash.to_string()),
+                    namespace: NAMESPACE.to_string(),
+                    network_id: chain_id.to_string(),
+                }),
+                ACTION_ITEM_PROVIDE_SIGNED_MESSAGE,
+            );
+            actions.append(&mut Actions::append_item(
+                request,
+                Some(&format!(
+                    "Sign the Safe transaction hash ({} of {} signatures collected)",
+                    signatures.len(),
+                    threshold
+                )),
+                Some("Transaction Signing"),
+            ));
+        }
+        Ok((signers, signer_state, actions))
+    }
+
+    // Builds the `execTransaction` call with the collected signatures and has the executor
+    // signer sign and broadcast it.
+    fn sign(
+        construct_did: &ConstructDid,
+        title: &str,
+        _payload: &Value,
+        _spec: &SignerSpecification,
+        values: &ValueStore,
+        mut signer_state: ValueStore,
+        mut signers: SignersState,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+    ) -> SignerSignFutureResult {
+        use crate::{
+            codec::CommonTransactionFields,
+            codec::{build_unsigned_transaction, get_typed_transaction_bytes, TransactionType},
+            constants::{RPC_API_URL, TRANSACTION_TYPE},
+            rpc::EvmRpc,
+            signers::common::get_signer_nonce,
+        };
+
+        let construct_did_str = construct_did.to_string();
+        if let Some(tx_hash) = signer_state.get_scoped_value(&construct_did_str, TX_HASH) {
+            let mut result = CommandExecutionResult::new();
+            result.outputs.insert(TX_HASH.into(), tx_hash.clone());
+            return return_synchronous_result(Ok((signers, signer_state, result)));
+        }
+
+        let signatures = signer_state
+            .get_expected_scoped_buffer_bytes(&construct_did_str, SAFE_SIGNATURES)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let (safe_tx, _) = get_safe_transaction(&construct_did_str, &mut signer_state)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let exec_transaction_input = safe_tx
+            .encode_exec_transaction(&signatures)
+            .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+        let safe_address = signer_state
+            .get_expected_value(CHECKED_ADDRESS)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .clone();
+
+        let owner_signer_instances = get_owner_signer_instances(&signer_state, signers_instances)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+        let executor_did = get_executor_signer_did(&owner_signer_instances).unwrap();
+        let executor_instance = signers_instances.get(&executor_did).unwrap().clone();
+
+        let construct_did = construct_did.clone();
+        let title = title.to_string();
+        let values = values.clone();
+        let signers_instances = signers_instances.clone();
+
+        let future = async move {
+            let mut executor_state = signers.pop_signer_state(&executor_did).unwrap();
+
+            let rpc_api_url = values
+                .get_expected_string(RPC_API_URL)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+            let chain_id = values
+                .get_expected_uint(CHAIN_ID)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+            let from = executor_state
+                .get_expected_value("signer_address")
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+                .clone();
+            let nonce = get_signer_nonce(&executor_state, chain_id)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?
+                .map(|nonce| nonce + 1);
+            let tx_type = TransactionType::from_some_value(values.get_string(TRANSACTION_TYPE))
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+            let rpc = EvmRpc::new(&rpc_api_url)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+            let common = CommonTransactionFields {
+                to: Some(safe_address),
+                from,
+                nonce,
+                chain_id,
+                amount: 0,
+                gas_limit: None,
+                input: Some(exec_transaction_input),
+                tx_type,
+                deploy_code: None,
+            };
+            let (transaction, _, _) =
+                build_unsigned_transaction(rpc, &values, common).await.map_err(|e| {
+                    (
+                        signers.clone(),
+                        signer_state.clone(),
+                        diagnosed_error!("failed to build Safe execTransaction call: {e}"),
+                    )
+                })?;
+            let transaction_bytes = get_typed_transaction_bytes(&transaction)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+            executor_state.insert_scoped_value(
+                &construct_did_str,
+                SECRET_KEY_WALLET_UNSIGNED_TRANSACTION_BYTES,
+                Value::buffer(transaction_bytes),
+            );
+
+            let (mut signers, executor_state, result) = (executor_instance.specification.sign)(
+                &construct_did,
+                &title,
+                &Value::null(),
+                &executor_instance.specification,
+                &values,
+                executor_state,
+                signers,
+                &signers_instances,
+            )?
+            .await?;
+            signers.push_signer_state(executor_state);
+
+            Ok((signers, signer_state, result))
+        };
+        Ok(Box::pin(future))
+    }
+}
+
+fn get_owner_signer_instances(
+    args: &ValueStore,
+    signers_instances: &HashMap<ConstructDid, SignerInstance>,
+) -> Result<Vec<(ConstructDid, SignerInstance)>, Diagnostic> {
+    let signers_uuid = args.get_expected_array("signers")?;
+    let mut owner_signers = Vec::new();
+    for signer_uuid in signers_uuid.iter() {
+        let uuid = signer_uuid.as_string().ok_or(diagnosed_error!("invalid signer reference"))?;
+        let uuid = ConstructDid(Did::from_hex_string(uuid));
+        let signer_instance = signers_instances
+            .get(&uuid)
+            .ok_or(diagnosed_error!("unknown signer reference"))?
+            .clone();
+        owner_signers.push((uuid, signer_instance));
+    }
+    Ok(owner_signers)
+}
+
+fn get_owner_addresses(
+    owner_signer_instances: &[(ConstructDid, SignerInstance)],
+    signers: &SignersState,
+) -> Result<Vec<Address>, Diagnostic> {
+    let mut owner_addresses = vec![];
+    for (owner_did, owner_instance) in owner_signer_instances.iter() {
+        let address = signers
+            .get_signer_state(owner_did)
+            .and_then(|state| state.get_value("signer_address"))
+            .ok_or(diagnosed_error!(
+                "signer '{}' must be activated before the Safe signer",
+                owner_instance.name
+            ))?;
+        owner_addresses.push(EvmValue::to_address(address)?);
+    }
+    Ok(owner_addresses)
+}
+
+/// The `execTransaction` call is submitted by the first `evm::secret_key` owner signer.
+fn get_executor_signer_did(
+    owner_signer_instances: &[(ConstructDid, SignerInstance)],
+) -> Option<ConstructDid> {
+    owner_signer_instances
+        .iter()
+        .find(|(_, instance)| {
+            instance.namespace == NAMESPACE && instance.specification.matcher == "secret_key"
+        })
+        .map(|(did, _)| did.clone())
+}
+
+/// Retrieves the Safe transaction wrapping the transaction built for the action `construct_did`,
+/// along with its hash. The Safe nonce is assigned the first time a transaction is seen for the action.
+fn get_safe_transaction(
+    construct_did: &str,
+    signer_state: &mut ValueStore,
+) -> Result<(SafeTransaction, B256), Diagnostic> {
+    let payload_bytes = signer_state.get_expected_scoped_buffer_bytes(
+        construct_did,
+        SECRET_KEY_WALLET_UNSIGNED_TRANSACTION_BYTES,
+    )?;
+    let transaction: TransactionRequest = serde_json::from_slice(&payload_bytes)
+        .map_err(|e| diagnosed_error!("error deserializing transaction: {e}"))?;
+
+    let nonce = match signer_state.get_scoped_value(construct_did, SAFE_NONCE) {
+        Some(nonce) => nonce.expect_uint().map_err(|e| diagnosed_error!("{e}"))?,
+        None => {
+            let nonce = signer_state.get_expected_uint(SAFE_NONCE)?;
+            signer_state.insert(SAFE_NONCE, Value::integer((nonce + 1).into()));
+            signer_state.insert_scoped_value(
+                construct_did,
+                SAFE_NONCE,
+                Value::integer(nonce.into()),
+            );
+            nonce
+        }
+    };
+
+    let safe_tx = SafeTransaction::from_transaction_request(&transaction, nonce)
+        .map_err(|e| diagnosed_error!("{e}"))?;
+    let domain_separator = signer_state.get_expected_buffer_bytes(SAFE_DOMAIN_SEPARATOR)?;
+    let safe_tx_hash = safe_tx.hash(&B256::from_slice(&domain_separator));
+    Ok((safe_tx, safe_tx_hash))
+}
@@ -44,7 +44,7 @@ lazy_static! {
         EvmSecretKeySigner => {
           name: "EVM Secret Key Signer",
           matcher: "secret_key",
-          documentation:txtx_addon_kit::indoc! {r#"The `evm::secret_key` signer can be used to synchronously sign a transaction."#},
+          documentation:txtx_addon_kit::indoc! {r#"The `evm::secret_key` signer can be used to synchronously sign a transaction. The secret key can be provided directly, derived from a mnemonic, or decrypted from a Web3 Secret Storage keystore."#},
           inputs: [
             secret_key: {
                 documentation: "The secret key used to sign messages and transactions.",
@@ -80,6 +80,34 @@ lazy_static! {
                 optional: true,
                 tainting: true,
                 sensitive: true
+            },
+            keystore_account: {
+                documentation: "The name of an account stored in the keystore directory, or the path to a Web3 Secret Storage keystore file (as created by `cast wallet import` or geth). This input will not be used if the `secret_key` input is provided.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                sensitive: false
+            },
+            keystore_path: {
+                documentation: "The directory containing the keystore files. Defaults to `~/.foundry/keystores`.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                sensitive: false
+            },
+            keystore_password: {
+                documentation: "The password used to decrypt the keystore. If omitted, the password is read from `keystore_password_file`, or requested when the runbook is supervised.",
+                typing: Type::string(),
+                optional: true,
+                tainting: false,
+                sensitive: true
+            },
+            keystore_password_file: {
+                documentation: "The path to a file containing the password used to decrypt the keystore.",
+                typing: Type::string(),
+                optional: true,
+                tainting: false,
+                sensitive: false
             }
           ],
           outputs: [
@@ -102,6 +130,12 @@ lazy_static! {
         signer "bob_again" "evm::secret_key" {
             secret_key = "03b3e0a76b292b2c83fc0ac14ae6160d0438ebe94e14bbb5b7755153628886e08e"
         }
+        // or we can load it from an encrypted keystore; when the runbook is supervised,
+        // the password is requested if not provided
+        signer "bob_keystore" "evm::secret_key" {
+            keystore_account = "bob"
+            keystore_password = env.BOB_KEYSTORE_PASSWORD
+        }
     "#},
       }
     };
@@ -126,14 +160,21 @@ impl SignerImplementation for EvmSecretKeySigner {
         signers: SignersState,
         _signers_instances: &HashMap<ConstructDid, SignerInstance>,
         supervision_context: &RunbookSupervisionContext,
-        _auth_ctx: &txtx_addon_kit::types::AuthorizationContext,
+        auth_ctx: &txtx_addon_kit::types::AuthorizationContext,
         _is_balance_check_required: bool,
         _is_public_key_required: bool,
     ) -> SignerActionsFutureResult {
         use crate::{
-            codec::crypto::{mnemonic_to_secret_key_signer, secret_key_to_secret_key_signer},
-            constants::CHECKED_ADDRESS,
+            codec::crypto::{
+                keystore_to_secret_key_signer, mnemonic_to_secret_key_signer,
+                secret_key_to_secret_key_signer,
+            },
+            constants::{
+                ACTION_ITEM_PROVIDE_KEYSTORE_PASSWORD, CHECKED_ADDRESS, KEYSTORE_ACCOUNT,
+                KEYSTORE_PASSWORD, KEYSTORE_PATH,
+            },
         };
+        use txtx_addon_kit::types::frontend::{ActionItemRequestType, ProvideInputRequest};
 
         let mut actions = Actions::none();
 
@@ -141,20 +182,56 @@ impl SignerImplementation for EvmSecretKeySigner {
             return return_synchronous_actions(Ok((signers, signer_state, actions)));
         }
 
-        let expected_signer =
-            if let Ok(secret_key_bytes) = values.get_expected_buffer_bytes("secret_key") {
-                secret_key_to_secret_key_signer(&secret_key_bytes)
-                    .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?
-            } else {
-                let mnemonic = values
-                    .get_expected_string("mnemonic")
-                    .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
-                let derivation_path = values.get_string("derivation_path");
-                let is_encrypted = values.get_bool("is_encrypted");
-                let password = values.get_string("password");
-                mnemonic_to_secret_key_signer(mnemonic, derivation_path, is_encrypted, password)
-                    .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?
+        let expected_signer = if let Ok(secret_key_bytes) =
+            values.get_expected_buffer_bytes("secret_key")
+        {
+            secret_key_to_secret_key_signer(&secret_key_bytes)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?
+        } else if let Some(keystore_account) = values.get_string(KEYSTORE_ACCOUNT) {
+            let keystore_location =
+                get_keystore_location(keystore_account, values.get_string(KEYSTORE_PATH), auth_ctx)
+                    .map_err(|e| {
+                        (signers.clone(), signer_state.clone(), diagnosed_error!("{e}"))
+                    })?;
+            let password = get_keystore_password(
+                values,
+                &keystore_location,
+                supervision_context.review_input_values,
+                auth_ctx,
+            )
+            .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+
+            let Some(password) = password else {
+                // the password is provided through an action item, and only kept in memory
+                actions.push_sub_group(
+                    None,
+                    vec![ActionItemRequest::new(
+                        &Some(construct_did.clone()),
+                        &format!("Provide {} keystore password", instance_name),
+                        Some(format!("Password decrypting keystore {}", keystore_location)),
+                        ActionItemStatus::Todo,
+                        ActionItemRequestType::ProvideInput(ProvideInputRequest {
+                            default_value: None,
+                            input_name: KEYSTORE_PASSWORD.into(),
+                            typing: Type::string(),
+                        }),
+                        ACTION_ITEM_PROVIDE_KEYSTORE_PASSWORD,
+                    )],
+                );
+                return return_synchronous_actions(Ok((signers, signer_state, actions)));
             };
+            keystore_to_secret_key_signer(&keystore_location.expect_path_buf(), &password)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?
+        } else {
+            let mnemonic = values
+                .get_expected_string("mnemonic")
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+            let derivation_path = values.get_string("derivation_path");
+            let is_encrypted = values.get_bool("is_encrypted");
+            let password = values.get_string("password");
+            mnemonic_to_secret_key_signer(mnemonic, derivation_path, is_encrypted, password)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?
+        };
 
         let expected_address: Address = expected_signer.address();
 
@@ -329,3 +406,171 @@ impl SignerImplementation for EvmSecretKeySigner {
         Ok(Box::pin(future))
     }
 }
+
+/// Resolves the keystore file of `keystore_account`, which is either a path to a keystore file
+/// or the name of an account stored in the keystore directory.
+#[cfg(not(feature = "wasm"))]
+fn get_keystore_location(
+    keystore_account: &str,
+    keystore_path: Option<&str>,
+    auth_ctx: &txtx_addon_kit::types::AuthorizationContext,
+) -> Result<txtx_addon_kit::helpers::fs::FileLocation, String> {
+    use crate::constants::DEFAULT_FOUNDRY_KEYSTORES_DIR;
+    use std::path::PathBuf;
+
+    let account_path = PathBuf::from(keystore_account);
+    let keystore_file = if account_path.components().count() > 1
+        || account_path.extension().map_or(false, |ext| ext == "json")
+    {
+        account_path
+    } else {
+        PathBuf::from(keystore_path.unwrap_or(DEFAULT_FOUNDRY_KEYSTORES_DIR)).join(account_path)
+    };
+    let location = auth_ctx.get_file_location_from_path_buf(&keystore_file)?;
+    if !location.exists() {
+        return Err(format!("keystore {} not found", location));
+    }
+    Ok(location)
+}
+
+/// Retrieves the keystore password from the `keystore_password` input, which is also where a
+/// password provided through an action item is set, or from the `keystore_password_file` input.
+/// Returns `None` when the password has to be requested, which is only possible when the runbook
+/// is supervised.
+#[cfg(not(feature = "wasm"))]
+fn get_keystore_password(
+    values: &ValueStore,
+    keystore_location: &txtx_addon_kit::helpers::fs::FileLocation,
+    is_supervised: bool,
+    auth_ctx: &txtx_addon_kit::types::AuthorizationContext,
+) -> Result<Option<String>, String> {
+    use crate::constants::{KEYSTORE_PASSWORD, KEYSTORE_PASSWORD_FILE};
+    use std::path::PathBuf;
+
+    if let Some(password) = values.get_string(KEYSTORE_PASSWORD) {
+        return Ok(Some(password.to_string()));
+    }
+    let Some(password_file) = values.get_string(KEYSTORE_PASSWORD_FILE) else {
+        if !is_supervised {
+            return Err(format!(
+                "missing password for keystore {}: set '{}' or '{}'",
+                keystore_location, KEYSTORE_PASSWORD, KEYSTORE_PASSWORD_FILE
+            ));
+        }
+        return Ok(None);
+    };
+    let location = auth_ctx.get_file_location_from_path_buf(&PathBuf::from(password_file))?;
+    let password = location
+        .read_content_as_utf8()
+        .map_err(|e| format!("failed to read keystore password file {}: {e}", location))?;
+    Ok(Some(password.trim_end_matches(&['\r', '\n']).to_string()))
+}
+
+#[cfg(all(test, not(feature = "wasm")))]
+mod tests {
+    use std::path::PathBuf;
+
+    use txtx_addon_kit::helpers::fs::FileLocation;
+    use txtx_addon_kit::types::{AuthorizationContext, Did};
+
+    use super::*;
+    use crate::constants::{KEYSTORE_PASSWORD, KEYSTORE_PASSWORD_FILE};
+
+    fn workspace(name: &str) -> (PathBuf, AuthorizationContext) {
+        let dir =
+            std::env::temp_dir().join(format!("txtx-evm-keystore-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(dir.join("keystores")).unwrap();
+        std::fs::write(dir.join("keystores").join("alice"), "{}").unwrap();
+        let auth_ctx = AuthorizationContext::new(FileLocation::from_path(dir.join("txtx.yml")));
+        (dir, auth_ctx)
+    }
+
+    #[test]
+    fn it_resolves_keystore_locations() {
+        let (dir, auth_ctx) = workspace("location");
+        let alice = dir.join("keystores").join("alice");
+        let keystores = dir.join("keystores").display().to_string();
+
+        // account names are looked up in the keystore directory
+        let location = get_keystore_location("alice", Some(&keystores), &auth_ctx).unwrap();
+        assert_eq!(location.expect_path_buf(), alice);
+        let location = get_keystore_location("alice", Some("keystores"), &auth_ctx).unwrap();
+        assert_eq!(location.expect_path_buf(), alice);
+        let err = get_keystore_location("bob", Some(&keystores), &auth_ctx).unwrap_err();
+        assert!(err.ends_with("bob not found"), "{}", err);
+
+        // paths are resolved from the workspace, regardless of the keystore directory
+        let location = get_keystore_location("keystores/alice", Some("~"), &auth_ctx).unwrap();
+        assert_eq!(location.expect_path_buf(), alice);
+        let location =
+            get_keystore_location(&alice.display().to_string(), Some("~"), &auth_ctx).unwrap();
+        assert_eq!(location.expect_path_buf(), alice);
+        std::fs::write(dir.join("bob.json"), "{}").unwrap();
+        let location = get_keystore_location("bob.json", None, &auth_ctx).unwrap();
+        assert_eq!(location.expect_path_buf(), dir.join("bob.json"));
+
+        // the default keystore directory is the one of foundry
+        let account = format!("txtx-missing-account-{}", std::process::id());
+        let err = get_keystore_location(&account, None, &auth_ctx).unwrap_err();
+        assert!(err.ends_with(&format!(".foundry/keystores/{} not found", account)), "{}", err);
+        assert!(!err.contains('~'), "{}", err);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_reads_keystore_passwords() {
+        let (dir, auth_ctx) = workspace("password");
+        let location = FileLocation::from_path(dir.join("keystores").join("alice"));
+        let get_password = |inputs: Vec<(&str, &str)>, is_supervised: bool| {
+            let mut values = ValueStore::new("signer", &Did::zero());
+            for (key, value) in inputs {
+                values.insert(key, Value::string(value.to_string()));
+            }
+            get_keystore_password(&values, &location, is_supervised, &auth_ctx)
+        };
+
+        assert_eq!(
+            get_password(vec![(KEYSTORE_PASSWORD, " secret\n")], false).unwrap(),
+            Some(" secret\n".to_string())
+        );
+
+        // the line ending of password files is trimmed, but not the password itself
+        for (content, expected) in [
+            ("secret", "secret"),
+            ("secret\n", "secret"),
+            ("secret\r\n", "secret"),
+            (" s e\n", " s e"),
+        ] {
+            std::fs::write(dir.join("password"), content).unwrap();
+            assert_eq!(
+                get_password(vec![(KEYSTORE_PASSWORD_FILE, "password")], false).unwrap(),
+                Some(expected.to_string())
+            );
+        }
+        // the password input takes precedence over the password file
+        assert_eq!(
+            get_password(
+                vec![(KEYSTORE_PASSWORD, "input"), (KEYSTORE_PASSWORD_FILE, "password")],
+                false
+            )
+            .unwrap(),
+            Some("input".to_string())
+        );
+        let err = get_password(vec![(KEYSTORE_PASSWORD_FILE, "missing")], true).unwrap_err();
+        assert!(err.starts_with("failed to read keystore password file"), "{}", err);
+
+        // a missing password is only requested when the runbook is supervised
+        assert_eq!(get_password(vec![], true).unwrap(), None);
+        let err = get_password(vec![], false).unwrap_err();
+        assert_eq!(
+            err,
+            format!(
+                "missing password for keystore {}: set 'keystore_password' or 'keystore_password_file'",
+                location
+            )
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
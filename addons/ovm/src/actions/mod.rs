@@ -1,12 +1,17 @@
 pub mod package_rollup;
-pub mod setup_rollup;
-
-use setup_rollup::SETUP_ROLLUP;
+pub mod start_rollup;
+pub mod stop_rollup;
 
 use package_rollup::PACKAGE_ROLLUP;
+use start_rollup::{SETUP_ROLLUP, START_ROLLUP};
+use stop_rollup::STOP_ROLLUP;
 use txtx_addon_kit::types::commands::PreCommandSpecification;
 
 lazy_static! {
-    pub static ref ACTIONS: Vec<PreCommandSpecification> =
-        vec![SETUP_ROLLUP.clone(), PACKAGE_ROLLUP.clone()];
+    pub static ref ACTIONS: Vec<PreCommandSpecification> = vec![
+        START_ROLLUP.clone(),
+        PACKAGE_ROLLUP.clone(),
+        STOP_ROLLUP.clone(),
+        SETUP_ROLLUP.clone(),
+    ];
 }
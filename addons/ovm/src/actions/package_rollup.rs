@@ -5,50 +5,75 @@ use txtx_addon_kit::types::commands::{
 };
 use txtx_addon_kit::types::frontend::{Actions, BlockEvent, StatusUpdater};
 use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::Value;
 use txtx_addon_kit::types::{commands::CommandSpecification, diagnostics::Diagnostic, types::Type};
 use txtx_addon_kit::types::{types::RunbookSupervisionContext, ConstructDid};
 use txtx_addon_kit::uuid::Uuid;
 
 use crate::codec::docker::RollupPackager;
-use crate::constants::{ROLLUP_CONTAINER_IDS, WORKING_DIR};
+use crate::constants::{
+    DOCKER_COMPOSE_FILE, GENESIS, IMAGE_TAG, ROLLUP, ROLLUP_CONTAINER_IDS, WORKING_DIR,
+};
 use crate::typing::ROLLUP_CONTAINER_IDS_TYPE;
 
 lazy_static! {
     pub static ref PACKAGE_ROLLUP: PreCommandSpecification = define_command! {
         PackageRollup => {
-            name: "Coming Soon",
+            name: "Package OP Stack Rollup",
             matcher: "package_rollup",
-            documentation: "The `ovm::package_rollup` action is coming soon.",
+            documentation: txtx_addon_kit::indoc! {r#"
+                The `ovm::package_rollup` action snapshots a rollup started with `ovm::start_rollup`, so that it can be restarted later in the same state.
+                The rollup containers are committed to the `txtx-op-geth`, `txtx-op-node`, `txtx-op-batcher` and `txtx-op-proposer` images, and the state of the op-geth data directory and of the configuration volume is archived in the working directory (`datadir.tar.gz`, `conf.tar.gz`), next to the `genesis.json` and `rollup.json` files.
+                A `docker-compose.yml` file restoring the archived state and starting the committed images is generated in the working directory, and the rollup containers are removed.
+                The rollup can then be restarted with `docker compose up`."#},
             implements_signing_capability: false,
             implements_background_task_capability: true,
             inputs: [
                 working_dir: {
-                    documentation: "Coming soon.",
+                    documentation: "The working directory of the rollup, as provided to `ovm::start_rollup`. The package is written to this directory.",
                     typing: Type::string(),
                     optional: false,
                     tainting: false,
                     internal: false
                 },
                 rollup_container_ids: {
-                    documentation: "Coming soon.",
+                    documentation: "The ids of the rollup containers, as returned by `ovm::start_rollup`.",
                     typing: ROLLUP_CONTAINER_IDS_TYPE.clone(),
                     optional: false,
-                    tainting: false,
+                    tainting: true,
+                    internal: false
+                },
+                image_tag: {
+                    documentation: "The tag of the images the rollup containers are committed to. The default is the L2 chain id, so that packaging the same rollup always produces the same images.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
                     internal: false
                 }
             ],
             outputs: [
                 genesis: {
-                    documentation: "The genesis.json settings.",
+                    documentation: "The content of the `genesis.json` file of the L2 chain.",
                     typing: Type::arbitrary_object()
                 },
                 rollup: {
-                    documentation: "The rollup.json settings.",
+                    documentation: "The content of the `rollup.json` file of the L2 chain.",
                     typing: Type::arbitrary_object()
+                },
+                docker_compose_file: {
+                    documentation: "The path of the generated `docker-compose.yml` file.",
+                    typing: Type::string()
                 }
             ],
             example: txtx_addon_kit::indoc! {r#"
-                // Coming soon
+                action "package" "ovm::package_rollup" {
+                    working_dir = "/tmp/ovm"
+                    rollup_container_ids = action.rollup.rollup_container_ids
+                }
+                output "docker_compose_file" {
+                    value = action.package.docker_compose_file
+                }
+                // > docker_compose_file: /tmp/ovm/docker-compose.yml
             "#},
         }
     };
@@ -110,6 +135,21 @@ impl CommandImplementation for PackageRollup {
             let rollup_packager = RollupPackager::new(working_dir, rollup_container_ids)
                 .map_err(|e| diagnosed_error!("Failed to package rollup: {e}"))?;
 
+            // fail before touching the containers if the L2 config files are missing
+            let (genesis, rollup) = rollup_packager
+                .get_l2_config_files()
+                .map_err(|e| diagnosed_error!("Failed to package rollup: {e}"))?;
+            let image_tag = match inputs.get_string(IMAGE_TAG) {
+                Some(image_tag) => image_tag.to_string(),
+                None => rollup
+                    .get("l2_chain_id")
+                    .and_then(|chain_id| chain_id.as_u64())
+                    .map(|chain_id| chain_id.to_string())
+                    .ok_or(diagnosed_error!(
+                        "Failed to package rollup: missing l2_chain_id in rollup.json"
+                    ))?,
+            };
+
             let mut status_updater =
                 StatusUpdater::new(&background_tasks_uuid, &construct_did, &progress_tx);
 
@@ -117,9 +157,9 @@ impl CommandImplementation for PackageRollup {
                 "Pausing, packaging, and removing rollup from Docker network",
             );
 
-            rollup_packager.package_rollup().await.map_err(|e| {
+            rollup_packager.package_rollup(&image_tag).await.map_err(|e| {
                 let diag = diagnosed_error!("Failed to package rollup: {e}");
-                status_updater.propagate_failed_status("Failed to initialize rollup", &diag);
+                status_updater.propagate_failed_status("Failed to package rollup", &diag);
                 diag
             })?;
 
@@ -127,7 +167,13 @@ impl CommandImplementation for PackageRollup {
                 "Complete",
                 &format!("Rollup packaged successfully - files are available at {}", working_dir),
             );
-            let result = CommandExecutionResult::new();
+            let mut result = CommandExecutionResult::new();
+            result.outputs.insert(GENESIS.to_string(), Value::from_json(&genesis));
+            result.outputs.insert(ROLLUP.to_string(), Value::from_json(&rollup));
+            result.outputs.insert(
+                DOCKER_COMPOSE_FILE.to_string(),
+                Value::string(rollup_packager.get_docker_compose_file()),
+            );
 
             Ok(result)
         };
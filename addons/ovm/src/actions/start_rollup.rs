@@ -1,12 +1,14 @@
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
 use txtx_addon_kit::types::commands::{
     CommandExecutionFutureResult, CommandExecutionResult, CommandImplementation,
     PreCommandSpecification,
 };
 use txtx_addon_kit::types::frontend::{Actions, BlockEvent, StatusUpdater};
 use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::Value;
 use txtx_addon_kit::types::{commands::CommandSpecification, diagnostics::Diagnostic, types::Type};
 use txtx_addon_kit::types::{types::RunbookSupervisionContext, ConstructDid};
 use txtx_addon_kit::uuid::Uuid;
@@ -14,95 +16,148 @@ use txtx_addon_kit::uuid::Uuid;
 use crate::codec::docker::RollupDeployer;
 use crate::codec::rollup_config::RollupConfig;
 use crate::constants::{
-    BATCHER_SECRET_KEY, JWT, L1_DEPLOYMENT_ADDRESSES, L1_RPC_API_URL, L1_RPC_KIND,
-    PROPOSER_SECRET_KEY, ROLLUP_CONFIG, ROLLUP_CONTAINER_IDS, SEQUENCER_SECRET_KEY, WORKING_DIR,
+    BATCHER_SECRET_KEY, DEFAULT_NETWORK_NAME, DEFAULT_READINESS_TIMEOUT_SECONDS, JWT,
+    L1_DEPLOYMENT_ADDRESSES, L1_RPC_API_URL, L1_RPC_KIND, L2_CHAIN_ID, L2_RPC_API_URL,
+    PROPOSER_SECRET_KEY, READINESS_TIMEOUT_SECONDS, ROLLUP_CONFIG, ROLLUP_CONTAINER_IDS,
+    SEQUENCER_SECRET_KEY, WORKING_DIR,
 };
 use crate::typing::{ROLLUP_CONFIG_TYPE, ROLLUP_CONTAINER_IDS_TYPE};
 
 lazy_static! {
     pub static ref START_ROLLUP: PreCommandSpecification = define_command! {
         StartRollup => {
-            name: "Coming Soon",
+            name: "Start OP Stack Rollup",
             matcher: "start_rollup",
-            documentation: "The `ovm::start_rollup` action takes some L2 settings and deployment addresses of the L1 contracts and generates the `genesis.json` and `rollup.json` files needed to start a L2 OP node.",
+            documentation: txtx_addon_kit::indoc! {r#"
+                The `ovm::start_rollup` action takes some L2 settings and the deployment addresses of the L1 contracts, generates the `genesis.json` and `rollup.json` files of the L2 chain, and starts an OP stack devnet (op-geth, op-node, op-batcher and op-proposer) in local Docker containers.
+                The action completes once the L2 RPC API serves the configured chain id and the sequencer produced its first block."#},
             implements_signing_capability: false,
             implements_background_task_capability: true,
             inputs: [
                 l1_rpc_api_url: {
-                    documentation: "The URL of the L1 EVM API used to fetch data.",
+                    documentation: "The URL of the L1 EVM API used to fetch data. Local urls (`localhost`, `127.0.0.1`) are made reachable from the containers.",
                     typing: Type::string(),
                     optional: false,
                     tainting: false,
                     internal: false
                 },
                 l1_rpc_kind: {
-                    documentation: "Coming soon.",
+                    documentation: "The kind of the L1 RPC provider, used by op-node to optimize its requests (for example 'basic', 'alchemy', 'quicknode', 'infura' or 'erigon'). The default is 'basic'.",
                     typing: Type::string(),
                     optional: true,
                     tainting: false,
                     internal: false
                 },
                 working_dir: {
-                    documentation: "Coming soon.",
+                    documentation: "The absolute path of the directory where the `genesis.json`, `rollup.json` and `jwt.txt` files and the initial op-geth data directory are written.",
                     typing: Type::string(),
                     optional: false,
                     tainting: false,
                     internal: false
                 },
                 sequencer_secret_key: {
-                    documentation: "Coming soon.",
+                    documentation: "The secret key used by op-node to sign the blocks produced by the sequencer. It must match the `p2p_sequencer_address` of the rollup config.",
                     typing: Type::string(),
                     optional: false,
                     tainting: false,
                     internal: false
                 },
                 batcher_secret_key: {
-                    documentation: "Coming soon.",
+                    documentation: "The secret key used by op-batcher to submit the L2 transactions batches to the L1. It must match the `batch_sender_address` of the rollup config.",
                     typing: Type::string(),
                     optional: false,
                     tainting: false,
                     internal: false
                 },
                 proposer_secret_key: {
-                    documentation: "Coming soon.",
+                    documentation: "The secret key used by op-proposer to submit the L2 output roots to the L1. It must match the `l2_output_oracle_proposer` of the rollup config.",
                     typing: Type::string(),
                     optional: false,
                     tainting: false,
                     internal: false
                 },
                 jwt: {
-                    documentation: "Coming soon.",
+                    documentation: "The 32 bytes hex encoded secret used to authenticate the engine API calls from op-node to op-geth.",
                     typing: Type::string(),
                     optional: false,
                     tainting: false,
                     internal: false
                 },
                 rollup_config: {
-                    documentation: "Coming soon.",
+                    documentation: "The settings of the L2 chain, used to generate the `genesis.json` and `rollup.json` files.",
                     typing: ROLLUP_CONFIG_TYPE.clone(),
                     optional: false,
-                    tainting: false,
+                    tainting: true,
                     internal: false
                 },
                 l1_deployment_addresses: {
-                    documentation: "Coming soon.",
-                    typing: Type::object(vec![]),
+                    documentation: "The addresses of the L1 contracts of the rollup, indexed by contract name (`L2OutputOracleProxy`, `OptimismPortalProxy`, etc.).",
+                    typing: Type::arbitrary_object(),
                     optional: false,
+                    tainting: true,
+                    internal: false
+                },
+                readiness_timeout_seconds: {
+                    documentation: "The number of seconds to wait for the rollup to be ready to receive transactions before failing. The default is 120.",
+                    typing: Type::integer(),
+                    optional: true,
                     tainting: false,
                     internal: false
                 }
             ],
             outputs: [
                 rollup_container_ids: {
-                    documentation: "Coming soon.",
+                    documentation: "The ids of the op-geth, op-node, op-batcher and op-proposer containers.",
                     typing: ROLLUP_CONTAINER_IDS_TYPE.clone()
+                },
+                l2_rpc_api_url: {
+                    documentation: "The URL of the L2 RPC API exposed by op-geth.",
+                    typing: Type::string()
+                },
+                l2_chain_id: {
+                    documentation: "The chain id of the L2.",
+                    typing: Type::integer()
                 }
             ],
             example: txtx_addon_kit::indoc! {r#"
-                // Coming soon
+                action "rollup" "ovm::start_rollup" {
+                    l1_rpc_api_url = env.l1_rpc_api_url
+                    working_dir = "/tmp/ovm"
+                    l1_deployment_addresses = variable.l1_deployment_addresses
+                    rollup_config {
+                        l1_chain_id = 11155111
+                        l2_chain_id = 42069
+                        p2p_sequencer_address = "0x6bD1f2D7dC7D2825c1842742948833e9eBFAa53b"
+                        batch_sender_address = "0xBBFe9114E6159C89571f0c8d7D2C177fCEe51B4E"
+                        l2_output_oracle_proposer = "0x0246e8ACC16e29e8CC5E633815b504681371a177"
+                        proxy_admin_owner = "0xCe246168E59dd8e28e367BB49b38Dc621768F425"
+                    }
+                    sequencer_secret_key = env.sequencer_secret_key
+                    batcher_secret_key = env.batcher_secret_key
+                    proposer_secret_key = env.proposer_secret_key
+                    jwt = env.jwt
+                }
+                output "l2_rpc_api_url" {
+                    value = action.rollup.l2_rpc_api_url
+                }
+                // > l2_rpc_api_url: http://localhost:8545
             "#},
         }
     };
+    /// `ovm::setup_rollup` was completed as `ovm::start_rollup`: the former action id is kept as a
+    /// deprecated alias, so that existing runbooks keep working.
+    pub static ref SETUP_ROLLUP: PreCommandSpecification = {
+        let mut spec = START_ROLLUP.clone();
+        if let PreCommandSpecification::Atomic(ref mut spec) = spec {
+            spec.name = "Setup OP Stack Rollup (deprecated)".to_string();
+            spec.matcher = "setup_rollup".to_string();
+            spec.documentation = format!(
+                "The `ovm::setup_rollup` action is a deprecated alias of `ovm::start_rollup`.\n{}",
+                spec.documentation
+            );
+        }
+        spec
+    };
 }
 
 pub struct StartRollup;
@@ -147,7 +202,7 @@ impl CommandImplementation for StartRollup {
         progress_tx: &txtx_addon_kit::channel::Sender<BlockEvent>,
         background_tasks_uuid: &Uuid,
         _supervision_context: &RunbookSupervisionContext,
-        _cloud_service_context: &Option<CloudServiceContext>
+        _cloud_service_context: &Option<CloudServiceContext>,
     ) -> CommandExecutionFutureResult {
         let construct_did = construct_did.clone();
         let inputs = inputs.clone();
@@ -155,7 +210,6 @@ impl CommandImplementation for StartRollup {
         let background_tasks_uuid = background_tasks_uuid.clone();
 
         let future = async move {
-            let network_name = "ovm_network";
             let working_dir = inputs.get_expected_string(WORKING_DIR)?;
             let l1_rpc_api_url = inputs.get_expected_string(L1_RPC_API_URL)?;
             let l1_rpc_kind = inputs.get_string(L1_RPC_KIND);
@@ -163,6 +217,10 @@ impl CommandImplementation for StartRollup {
             let batcher_secret_key = inputs.get_expected_string(BATCHER_SECRET_KEY)?;
             let proposer_secret_key = inputs.get_expected_string(PROPOSER_SECRET_KEY)?;
             let jwt = inputs.get_expected_string(JWT)?;
+            let readiness_timeout = inputs
+                .get_uint(READINESS_TIMEOUT_SECONDS)
+                .map_err(|e| diagnosed_error!("{}", e))?
+                .unwrap_or(DEFAULT_READINESS_TIMEOUT_SECONDS);
 
             let rollup_config =
                 RollupConfig::new(inputs.get_expected_map(ROLLUP_CONFIG)?, l1_rpc_api_url).await?;
@@ -170,7 +228,7 @@ impl CommandImplementation for StartRollup {
             let l1_deployment_addresses = inputs.get_expected_object(L1_DEPLOYMENT_ADDRESSES)?;
 
             let mut rollup_deployer = RollupDeployer::new(
-                network_name,
+                DEFAULT_NETWORK_NAME,
                 working_dir,
                 l1_rpc_api_url,
                 l1_rpc_kind,
@@ -204,27 +262,43 @@ impl CommandImplementation for StartRollup {
 
             status_updater.propagate_success_status("Complete", "All rollup services online");
 
-            let max_attempts = 30;
-            let mut attempts = 0;
+            status_updater
+                .propagate_pending_status("Waiting for rollup to be ready to receive transactions");
+            let started_at = Instant::now();
             loop {
-                attempts += 1;
-                if attempts == max_attempts {
+                let ready = rollup_deployer.check_ready_state().await.map_err(|e| {
+                    let diag = diagnosed_error!("Rollup failed to start: {e}");
+                    status_updater.propagate_failed_status("Rollup failed to start", &diag);
+                    diag
+                })?;
+                if ready {
                     break;
                 }
-                status_updater.propagate_pending_status(
-                    "Waiting for rollup to be ready to receive transactions",
-                );
+                if started_at.elapsed() >= Duration::from_secs(readiness_timeout) {
+                    let diag = diagnosed_error!(
+                        "Rollup was not ready to receive transactions after {} seconds",
+                        readiness_timeout
+                    );
+                    status_updater.propagate_failed_status("Rollup not ready", &diag);
+                    return Err(diag);
+                }
                 sleep(Duration::from_secs(1));
-                // if rollup_deployer.check_ready_state().await {
-                //     break;
-                // }
             }
-            status_updater
-                .propagate_success_status("Ready", "Rollup is ready to receive transactions");
+
+            let l2_rpc_api_url = RollupDeployer::get_l2_rpc_api_url();
+            status_updater.propagate_success_status(
+                "Ready",
+                &format!("Rollup is ready to receive transactions at {}", l2_rpc_api_url),
+            );
 
             let mut result = CommandExecutionResult::new();
             let rollup_container_ids = rollup_deployer.get_container_ids();
             result.outputs.insert(ROLLUP_CONTAINER_IDS.to_string(), rollup_container_ids.clone());
+            result.outputs.insert(L2_RPC_API_URL.to_string(), Value::string(l2_rpc_api_url));
+            result.outputs.insert(
+                L2_CHAIN_ID.to_string(),
+                Value::integer(rollup_deployer.get_l2_chain_id().into()),
+            );
             Ok(result)
         };
         Ok(Box::pin(future))
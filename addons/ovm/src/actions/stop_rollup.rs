@@ -0,0 +1,127 @@
+use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
+use txtx_addon_kit::types::commands::{
+    CommandExecutionFutureResult, CommandExecutionResult, CommandImplementation,
+    PreCommandSpecification,
+};
+use txtx_addon_kit::types::frontend::{Actions, BlockEvent, StatusUpdater};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::{commands::CommandSpecification, diagnostics::Diagnostic, types::Type};
+use txtx_addon_kit::types::{types::RunbookSupervisionContext, ConstructDid};
+use txtx_addon_kit::uuid::Uuid;
+
+use crate::codec::docker::RollupStopper;
+use crate::constants::{REMOVE_VOLUMES, ROLLUP_CONTAINER_IDS};
+use crate::typing::ROLLUP_CONTAINER_IDS_TYPE;
+
+lazy_static! {
+    pub static ref STOP_ROLLUP: PreCommandSpecification = define_command! {
+        StopRollup => {
+            name: "Stop OP Stack Rollup",
+            matcher: "stop_rollup",
+            documentation: txtx_addon_kit::indoc! {r#"
+                The `ovm::stop_rollup` action stops and removes the containers and the Docker network of a rollup started with `ovm::start_rollup`.
+                Containers already removed by `ovm::package_rollup` are skipped, so the action can be used to clean up a devnet whether it was packaged or not."#},
+            implements_signing_capability: false,
+            implements_background_task_capability: true,
+            inputs: [
+                rollup_container_ids: {
+                    documentation: "The ids of the rollup containers, as returned by `ovm::start_rollup`.",
+                    typing: ROLLUP_CONTAINER_IDS_TYPE.clone(),
+                    optional: false,
+                    tainting: true,
+                    internal: false
+                },
+                remove_volumes: {
+                    documentation: "Whether the Docker volumes holding the state of the rollup (op-geth data directory and configuration) should be removed as well. The default is false.",
+                    typing: Type::bool(),
+                    optional: true,
+                    tainting: false,
+                    internal: false
+                }
+            ],
+            outputs: [],
+            example: txtx_addon_kit::indoc! {r#"
+                action "stop" "ovm::stop_rollup" {
+                    rollup_container_ids = action.rollup.rollup_container_ids
+                    remove_volumes = true
+                    depends_on = [action.package]
+                }
+            "#},
+        }
+    };
+}
+
+pub struct StopRollup;
+impl CommandImplementation for StopRollup {
+    fn check_instantiability(
+        _ctx: &CommandSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn check_executability(
+        _construct_id: &ConstructDid,
+        _instance_name: &str,
+        _spec: &CommandSpecification,
+        _values: &ValueStore,
+        _supervision_context: &RunbookSupervisionContext,
+    ) -> Result<Actions, Diagnostic> {
+        Ok(Actions::none())
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    fn run_execution(
+        _construct_id: &ConstructDid,
+        _spec: &CommandSpecification,
+        _values: &ValueStore,
+        _progress_tx: &txtx_addon_kit::channel::Sender<BlockEvent>,
+    ) -> CommandExecutionFutureResult {
+        let future = async move {
+            let result = CommandExecutionResult::new();
+            Ok(result)
+        };
+
+        Ok(Box::pin(future))
+    }
+
+    fn build_background_task(
+        construct_did: &ConstructDid,
+        _spec: &CommandSpecification,
+        inputs: &ValueStore,
+        _outputs: &ValueStore,
+        progress_tx: &txtx_addon_kit::channel::Sender<BlockEvent>,
+        background_tasks_uuid: &Uuid,
+        _supervision_context: &RunbookSupervisionContext,
+        _cloud_service_context: &Option<CloudServiceContext>,
+    ) -> CommandExecutionFutureResult {
+        let construct_did = construct_did.clone();
+        let inputs = inputs.clone();
+        let progress_tx = progress_tx.clone();
+        let background_tasks_uuid = background_tasks_uuid.clone();
+
+        let future = async move {
+            let rollup_container_ids = inputs.get_expected_object(ROLLUP_CONTAINER_IDS)?;
+            let remove_volumes = inputs.get_bool(REMOVE_VOLUMES).unwrap_or(false);
+
+            let rollup_stopper = RollupStopper::new(rollup_container_ids)
+                .map_err(|e| diagnosed_error!("Failed to stop rollup: {e}"))?;
+
+            let mut status_updater =
+                StatusUpdater::new(&background_tasks_uuid, &construct_did, &progress_tx);
+
+            status_updater.propagate_pending_status("Stopping rollup");
+
+            rollup_stopper.stop_rollup(remove_volumes).await.map_err(|e| {
+                let diag = diagnosed_error!("Failed to stop rollup: {e}");
+                status_updater.propagate_failed_status("Failed to stop rollup", &diag);
+                diag
+            })?;
+
+            status_updater.propagate_success_status("Complete", "Rollup stopped");
+
+            Ok(CommandExecutionResult::new())
+        };
+        Ok(Box::pin(future))
+    }
+}
@@ -2,6 +2,7 @@ use std::{collections::HashMap, fs::File, thread::sleep, time::Duration};
 
 use bollard::{
     container::{Config, CreateContainerOptions, RemoveContainerOptions},
+    errors::Error as DockerError,
     image::CommitContainerOptions,
     network::CreateNetworkOptions,
     secret::{
@@ -20,10 +21,11 @@ use txtx_addon_kit::{
         types::{ObjectType, Value},
     },
 };
+use txtx_addon_network_evm::rpc::EvmRpc;
 
 use crate::constants::{
-    DEFAULT_OP_NODE_RPC_KIND, DEFAULT_TAG, OP_BATCHER_IMAGE, OP_GETH_IMAGE, OP_NODE_IMAGE,
-    OP_PROPOSER_IMAGE,
+    DEFAULT_NETWORK_NAME, DEFAULT_OP_NODE_RPC_KIND, DEFAULT_TAG, OP_BATCHER_IMAGE, OP_GETH_IMAGE,
+    OP_NODE_IMAGE, OP_PROPOSER_IMAGE,
 };
 
 use super::rollup_config::RollupConfig;
@@ -38,6 +40,12 @@ pub const OP_GETH_RPC_PORT: &str = "8545";
 pub const OP_GETH_WS_PORT: &str = "8546";
 pub const OP_BATCHER_RPC_PORT: &str = "8548";
 pub const OP_PROPOSER_RPC_PORT: &str = "8560";
+pub const OP_GETH_COMMITTED_IMAGE: &str = "txtx-op-geth";
+pub const OP_NODE_COMMITTED_IMAGE: &str = "txtx-op-node";
+pub const OP_BATCHER_COMMITTED_IMAGE: &str = "txtx-op-batcher";
+pub const OP_PROPOSER_COMMITTED_IMAGE: &str = "txtx-op-proposer";
+pub const DATADIR_VOLUME_NAME: &str = "datadir";
+pub const CONF_VOLUME_NAME: &str = "conf";
 
 #[derive(Debug, Clone)]
 pub struct RollupDeployer {
@@ -153,8 +161,57 @@ impl RollupDeployer {
         Ok(())
     }
 
+    pub fn get_l2_chain_id(&self) -> u64 {
+        self.rollup_config.l2_chain_id
+    }
+
+    /// The url of the op-geth RPC API, as exposed on the host.
+    pub fn get_l2_rpc_api_url() -> String {
+        format!("http://localhost:{}", OP_GETH_RPC_PORT)
+    }
+
+    /// Checks whether the rollup is ready to receive transactions: all the containers must be running,
+    /// and the L2 RPC API must serve the expected chain id and have produced its first block.
+    /// Returns an error if one of the containers exited, since the rollup won't recover from it.
     pub async fn check_ready_state(&self) -> Result<bool, Diagnostic> {
-        todo!()
+        let containers = vec![
+            (OP_GETH_CONTAINER_NAME, &self.op_geth_container_id),
+            (OP_NODE_CONTAINER_NAME, &self.op_node_container_id),
+            (OP_BATCHER_CONTAINER_NAME, &self.op_batcher_container_id),
+            (OP_PROPOSER_CONTAINER_NAME, &self.op_proposer_container_id),
+        ];
+        for (container_name, container_id) in containers {
+            let Some(container_id) = container_id else {
+                return Err(diagnosed_error!("{} container not started", container_name));
+            };
+            let container_info =
+                self.docker.inspect_container(container_id, None).await.map_err(|e| {
+                    diagnosed_error!("Failed to inspect {} container: {}", container_name, e)
+                })?;
+            let running = container_info.state.and_then(|state| state.running).unwrap_or(false);
+            if !running {
+                return Err(diagnosed_error!(
+                    "{} container is not running (run `docker logs {}` for more details)",
+                    container_name,
+                    container_name
+                ));
+            }
+        }
+
+        let rpc = EvmRpc::new(&RollupDeployer::get_l2_rpc_api_url())
+            .map_err(|e| diagnosed_error!("invalid L2 RPC API URL: {}", e))?;
+        let Ok(chain_id) = rpc.get_chain_id().await else {
+            return Ok(false);
+        };
+        if chain_id != self.rollup_config.l2_chain_id {
+            return Err(diagnosed_error!(
+                "L2 RPC API returned chain id {}, expected {}",
+                chain_id,
+                self.rollup_config.l2_chain_id
+            ));
+        }
+        let block_number = rpc.get_block_number().await.unwrap_or(0);
+        Ok(block_number > 0)
     }
 
     async fn initialize_docker_network(&mut self) -> Result<(), Diagnostic> {
@@ -181,8 +238,8 @@ impl RollupDeployer {
 
         self.network_id = network_id;
 
-        self.datadir_mount = Some(self.create_volume("datadir").await?);
-        self.conf_mount = Some(self.create_volume("conf").await?);
+        self.datadir_mount = Some(self.create_volume(DATADIR_VOLUME_NAME).await?);
+        self.conf_mount = Some(self.create_volume(CONF_VOLUME_NAME).await?);
 
         Ok(())
     }
@@ -858,45 +915,55 @@ impl RollupPackager {
         working_dir: &str,
         rollup_container_ids: IndexMap<String, Value>,
     ) -> Result<Self, Diagnostic> {
-        let op_geth_container_id = rollup_container_ids
-            .get("op_geth_container_id")
-            .and_then(|v| v.as_string())
-            .ok_or(diagnosed_error!("op_geth_container_id not found in container ids"))?;
-        let op_node_container_id = rollup_container_ids
-            .get("op_node_container_id")
-            .and_then(|v| v.as_string())
-            .ok_or(diagnosed_error!("op_node_container_id not found in container ids"))?;
-        let op_batcher_container_id = rollup_container_ids
-            .get("op_batcher_container_id")
-            .and_then(|v| v.as_string())
-            .ok_or(diagnosed_error!("op_batcher_container_id not found in container ids"))?;
-        let op_proposer_container_id = rollup_container_ids
-            .get("op_proposer_container_id")
-            .and_then(|v| v.as_string())
-            .ok_or(diagnosed_error!("op_proposer_container_id not found in container ids"))?;
-
         Ok(Self {
             working_dir: working_dir.to_string(),
-            op_geth_container_id: op_geth_container_id.to_string(),
-            op_node_container_id: op_node_container_id.to_string(),
-            op_batcher_container_id: op_batcher_container_id.to_string(),
-            op_proposer_container_id: op_proposer_container_id.to_string(),
+            op_geth_container_id: get_container_id(&rollup_container_ids, "op_geth_container_id")?,
+            op_node_container_id: get_container_id(&rollup_container_ids, "op_node_container_id")?,
+            op_batcher_container_id: get_container_id(
+                &rollup_container_ids,
+                "op_batcher_container_id",
+            )?,
+            op_proposer_container_id: get_container_id(
+                &rollup_container_ids,
+                "op_proposer_container_id",
+            )?,
             docker: Docker::connect_with_socket_defaults()
                 .map_err(|e| diagnosed_error!("Failed to connect to Docker: {}", e))?,
         })
     }
 
-    pub async fn package_rollup(&self) -> Result<(), Diagnostic> {
+    /// Reads the `genesis.json` and `rollup.json` files generated in the working directory
+    /// when the rollup was started.
+    pub fn get_l2_config_files(&self) -> Result<(JsonValue, JsonValue), Diagnostic> {
+        let read_config_file = |file_name: &str| -> Result<JsonValue, Diagnostic> {
+            let path = format!("{}/{}", self.working_dir, file_name);
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| diagnosed_error!("Failed to read {}: {}", path, e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| diagnosed_error!("Failed to parse {}: {}", path, e))
+        };
+        Ok((read_config_file("genesis.json")?, read_config_file("rollup.json")?))
+    }
+
+    pub fn get_docker_compose_file(&self) -> String {
+        format!("{}/docker-compose.yml", self.working_dir)
+    }
+
+    /// Snapshots the rollup into the working directory: the containers are committed to images tagged
+    /// with `image_tag`, the state of the volumes is archived next to the `genesis.json` and `rollup.json`
+    /// files, and a `docker-compose.yml` file restarting the rollup from this state is generated.
+    /// The containers are removed once packaged.
+    pub async fn package_rollup(&self, image_tag: &str) -> Result<(), Diagnostic> {
         self.pause_containers().await?;
-        self.commit_containers().await?;
+        self.commit_containers(image_tag).await?;
         self.tar_volumes().await?;
         let docker_compose_builder = DockerComposeBuilder::new(
             &self.working_dir,
-            "ovm_network",
-            "latest",
-            "latest",
-            "latest",
-            "latest",
+            DEFAULT_NETWORK_NAME,
+            image_tag,
+            image_tag,
+            image_tag,
+            image_tag,
         );
         docker_compose_builder.build()?;
         self.remove_containers().await?;
@@ -968,14 +1035,14 @@ impl RollupPackager {
                 mounts: Some(vec![
                     Mount {
                         target: Some(format!("/datadir")),
-                        source: Some("datadir".into()),
+                        source: Some(DATADIR_VOLUME_NAME.into()),
                         typ: Some(MountTypeEnum::VOLUME),
                         read_only: Some(false),
                         ..Default::default()
                     },
                     Mount {
                         target: Some(format!("/conf")),
-                        source: Some("conf".into()),
+                        source: Some(CONF_VOLUME_NAME.into()),
                         typ: Some(MountTypeEnum::VOLUME),
                         read_only: Some(false),
                         ..Default::default()
@@ -1027,11 +1094,13 @@ impl RollupPackager {
         Ok(())
     }
 
-    pub async fn commit_containers(&self) -> Result<(), Diagnostic> {
-        self.commit_container(&self.op_proposer_container_id, "txtx-op-proposer", "latest").await?;
-        self.commit_container(&self.op_batcher_container_id, "txtx-op-batcher", "latest").await?;
-        self.commit_container(&self.op_node_container_id, "txtx-op-node", "latest").await?;
-        self.commit_container(&self.op_geth_container_id, "txtx-op-geth", "latest").await?;
+    pub async fn commit_containers(&self, tag: &str) -> Result<(), Diagnostic> {
+        self.commit_container(&self.op_proposer_container_id, OP_PROPOSER_COMMITTED_IMAGE, tag)
+            .await?;
+        self.commit_container(&self.op_batcher_container_id, OP_BATCHER_COMMITTED_IMAGE, tag)
+            .await?;
+        self.commit_container(&self.op_node_container_id, OP_NODE_COMMITTED_IMAGE, tag).await?;
+        self.commit_container(&self.op_geth_container_id, OP_GETH_COMMITTED_IMAGE, tag).await?;
 
         Ok(())
     }
@@ -1134,6 +1203,10 @@ impl DockerComposeBuilder {
             .insert_str("op_node_tag", &self.op_node_tag)
             .insert_str("op_batcher_tag", &self.op_batcher_tag)
             .insert_str("op_proposer_tag", &self.op_proposer_tag)
+            .insert_str("op_geth_image", OP_GETH_COMMITTED_IMAGE)
+            .insert_str("op_node_image", OP_NODE_COMMITTED_IMAGE)
+            .insert_str("op_batcher_image", OP_BATCHER_COMMITTED_IMAGE)
+            .insert_str("op_proposer_image", OP_PROPOSER_COMMITTED_IMAGE)
             .build();
 
         let mut output_file = File::create(format!("{}/docker-compose.yml", self.working_dir))
@@ -1147,6 +1220,105 @@ impl DockerComposeBuilder {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct RollupStopper {
+    network_name: String,
+    docker: Docker,
+    container_ids: Vec<String>,
+}
+
+impl RollupStopper {
+    pub fn new(rollup_container_ids: IndexMap<String, Value>) -> Result<Self, Diagnostic> {
+        let container_ids = vec![
+            get_container_id(&rollup_container_ids, "op_proposer_container_id")?,
+            get_container_id(&rollup_container_ids, "op_batcher_container_id")?,
+            get_container_id(&rollup_container_ids, "op_node_container_id")?,
+            get_container_id(&rollup_container_ids, "op_geth_container_id")?,
+        ];
+        Ok(Self {
+            network_name: DEFAULT_NETWORK_NAME.to_string(),
+            docker: Docker::connect_with_socket_defaults()
+                .map_err(|e| diagnosed_error!("Failed to connect to Docker: {}", e))?,
+            container_ids,
+        })
+    }
+
+    /// Stops and removes the rollup containers and network. Containers that were already removed
+    /// (for instance by `ovm::package_rollup`) are skipped.
+    pub async fn stop_rollup(&self, remove_volumes: bool) -> Result<(), Diagnostic> {
+        for container_id in self.container_ids.iter() {
+            match self.docker.stop_container(container_id, None).await {
+                // 304: the container is already stopped
+                Ok(_) | Err(DockerError::DockerResponseServerError { status_code: 304, .. }) => {}
+                Err(DockerError::DockerResponseServerError { status_code: 404, .. }) => continue,
+                Err(e) => {
+                    return Err(diagnosed_error!(
+                        "Failed to stop container {}: {}",
+                        container_id,
+                        e
+                    ))
+                }
+            }
+            match self
+                .docker
+                .remove_container(
+                    container_id,
+                    Some(RemoveContainerOptions { v: false, force: true, ..Default::default() }),
+                )
+                .await
+            {
+                Ok(_) | Err(DockerError::DockerResponseServerError { status_code: 404, .. }) => {}
+                Err(e) => {
+                    return Err(diagnosed_error!(
+                        "Failed to remove container {}: {}",
+                        container_id,
+                        e
+                    ))
+                }
+            }
+        }
+
+        match self.docker.remove_network(&self.network_name).await {
+            Ok(_) | Err(DockerError::DockerResponseServerError { status_code: 404, .. }) => {}
+            Err(e) => {
+                return Err(diagnosed_error!(
+                    "Failed to remove network {}: {}",
+                    self.network_name,
+                    e
+                ))
+            }
+        }
+
+        if remove_volumes {
+            for volume_name in [DATADIR_VOLUME_NAME, CONF_VOLUME_NAME] {
+                match self.docker.remove_volume(volume_name, None).await {
+                    Ok(_)
+                    | Err(DockerError::DockerResponseServerError { status_code: 404, .. }) => {}
+                    Err(e) => {
+                        return Err(diagnosed_error!(
+                            "Failed to remove volume {}: {}",
+                            volume_name,
+                            e
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn get_container_id(
+    rollup_container_ids: &IndexMap<String, Value>,
+    key: &str,
+) -> Result<String, Diagnostic> {
+    rollup_container_ids
+        .get(key)
+        .and_then(|v| v.as_string())
+        .map(|v| v.to_string())
+        .ok_or(diagnosed_error!("{} not found in container ids", key))
+}
+
 fn url_is_local(url: &Url) -> bool {
     url.host_str() == Some("localhost") || url.host_str() == Some("127.0.0.1")
 }
@@ -11,6 +11,16 @@ pub const DEPLOYMENT_CONFIG: &str = "deployment_config";
 pub const ROLLUP_CONFIG: &str = "rollup_config";
 pub const L1_DEPLOYMENT_ADDRESSES: &str = "l1_deployment_addresses";
 pub const ROLLUP_CONTAINER_IDS: &str = "rollup_container_ids";
+pub const READINESS_TIMEOUT_SECONDS: &str = "readiness_timeout_seconds";
+pub const IMAGE_TAG: &str = "image_tag";
+pub const REMOVE_VOLUMES: &str = "remove_volumes";
+
+// Outputs keys
+pub const L2_RPC_API_URL: &str = "l2_rpc_api_url";
+pub const L2_CHAIN_ID: &str = "l2_chain_id";
+pub const GENESIS: &str = "genesis";
+pub const ROLLUP: &str = "rollup";
+pub const DOCKER_COMPOSE_FILE: &str = "docker_compose_file";
 
 // Docker images
 pub const DEFAULT_TAG: &str = "latest";
@@ -20,3 +30,5 @@ pub const OP_BATCHER_IMAGE: &str = "us-docker.pkg.dev/oplabs-tools-artifacts/ima
 pub const OP_PROPOSER_IMAGE: &str = "us-docker.pkg.dev/oplabs-tools-artifacts/images/op-proposer";
 
 pub const DEFAULT_OP_NODE_RPC_KIND: &str = "basic";
+pub const DEFAULT_NETWORK_NAME: &str = "ovm_network";
+pub const DEFAULT_READINESS_TIMEOUT_SECONDS: u64 = 120;
@@ -35,13 +35,14 @@ impl OvmNetworkAddon {
 
 impl Addon for OvmNetworkAddon {
     fn get_name(&self) -> &str {
-        "Coming Soon"
+        "OP Stack Rollups (alpha)"
     }
 
     fn get_description(&self) -> &str {
         txtx_addon_kit::indoc! {r#"
-            Coming Soon
-        "#}
+            The OVM `txtx` plugin enables building Runbooks that spin up OP stack rollups.
+            The actions can be used to generate the genesis of a L2 chain from the deployment of its L1 contracts, start the L2 nodes in local Docker containers, package the state of the rollup so that it can be restarted with Docker Compose, and stop the rollup.
+            "#}
     }
 
     fn get_namespace(&self) -> &str {
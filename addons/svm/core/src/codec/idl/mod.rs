@@ -5,8 +5,8 @@ use std::str::FromStr;
 use crate::typing::anchor as anchor_lang_idl;
 use crate::typing::SvmValue;
 use anchor_lang_idl::types::{
-    Idl, IdlArrayLen, IdlDefinedFields, IdlGenericArg, IdlInstruction, IdlType, IdlTypeDef,
-    IdlTypeDefGeneric, IdlTypeDefTy,
+    Idl, IdlAccount, IdlArrayLen, IdlDefinedFields, IdlGenericArg, IdlInstruction, IdlType,
+    IdlTypeDef, IdlTypeDefGeneric, IdlTypeDefTy,
 };
 use convert_idl::classic_idl_to_anchor_idl;
 use solana_sdk::pubkey::Pubkey;
@@ -67,6 +67,55 @@ impl IdlRef {
         self.idl.types.clone()
     }
 
+    pub fn get_account(&self, account_name: &str) -> Result<&IdlAccount, Diagnostic> {
+        self.idl
+            .accounts
+            .iter()
+            .find(|a| a.name == account_name)
+            .ok_or_else(|| diagnosed_error!("account '{account_name}' not found in IDL"))
+    }
+
+    /// Finds the account whose discriminator prefixes the provided account data.
+    pub fn get_account_for_data(&self, data: &[u8]) -> Option<&IdlAccount> {
+        self.idl
+            .accounts
+            .iter()
+            .find(|a| !a.discriminator.is_empty() && data.starts_with(&a.discriminator))
+    }
+
+    /// Decodes the data of an account owned by the program into an object.
+    /// If `account_name` is not provided, the account type is inferred from the discriminator.
+    /// Returns the name of the account type along with the decoded value.
+    pub fn decode_account_data(
+        &self,
+        data: &[u8],
+        account_name: Option<&str>,
+    ) -> Result<(String, Value), Diagnostic> {
+        let account = match account_name {
+            Some(account_name) => self.get_account(account_name)?,
+            None => self.get_account_for_data(data).ok_or_else(|| {
+                diagnosed_error!("account data does not match any account discriminator in IDL")
+            })?,
+        };
+        if !data.starts_with(&account.discriminator) {
+            return Err(diagnosed_error!(
+                "account data does not match the discriminator of account '{}'",
+                account.name
+            ));
+        }
+
+        let idl_types = self.get_types();
+        let mut remaining = &data[account.discriminator.len()..];
+        let value = borsh_decode_idl_type(
+            &mut remaining,
+            &IdlType::Defined { name: account.name.clone(), generics: vec![] },
+            &idl_types,
+            None,
+        )
+        .map_err(|e| diagnosed_error!("unable to decode account '{}': {}", account.name, e))?;
+        Ok((account.name.clone(), value))
+    }
+
     /// Encodes the arguments for a given instruction into a map of argument names to byte arrays.
     pub fn get_encoded_args_map(
         &self,
@@ -808,6 +857,192 @@ pub fn parse_bytes_to_value_with_expected_idl_type(
     }
 }
 
+/// The generics of the defined type being decoded, along with the generic arguments it was
+/// instantiated with.
+type GenericsContext<'a> = (&'a Vec<IdlTypeDefGeneric>, &'a Vec<IdlGenericArg>);
+
+fn take_bytes<'a>(data: &mut &'a [u8], len: usize, expected: &str) -> Result<&'a [u8], String> {
+    if data.len() < len {
+        return Err(format!(
+            "unable to decode {}: expected {} bytes, found {}",
+            expected,
+            len,
+            data.len()
+        ));
+    }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+fn take_array<const N: usize>(data: &mut &[u8], expected: &str) -> Result<[u8; N], String> {
+    let bytes = take_bytes(data, N, expected)?;
+    Ok(<[u8; N]>::try_from(bytes).unwrap())
+}
+
+fn take_length(data: &mut &[u8], expected: &str) -> Result<usize, String> {
+    Ok(u32::from_le_bytes(take_array::<4>(data, &format!("{expected} length"))?) as usize)
+}
+
+/// Decodes the borsh encoded `data` into a value of the provided IDL type, advancing `data`
+/// past the decoded bytes.
+fn borsh_decode_idl_type(
+    data: &mut &[u8],
+    idl_type: &IdlType,
+    idl_types: &Vec<IdlTypeDef>,
+    generics_context: Option<GenericsContext>,
+) -> Result<Value, String> {
+    let value = match idl_type {
+        IdlType::Bool => Value::bool(take_array::<1>(data, "bool")?[0] != 0),
+        IdlType::U8 => Value::integer(take_array::<1>(data, "u8")?[0].into()),
+        IdlType::I8 => Value::integer(i8::from_le_bytes(take_array(data, "i8")?).into()),
+        IdlType::U16 => Value::integer(u16::from_le_bytes(take_array(data, "u16")?).into()),
+        IdlType::I16 => Value::integer(i16::from_le_bytes(take_array(data, "i16")?).into()),
+        IdlType::U32 => Value::integer(u32::from_le_bytes(take_array(data, "u32")?).into()),
+        IdlType::I32 => Value::integer(i32::from_le_bytes(take_array(data, "i32")?).into()),
+        IdlType::F32 => Value::float(f32::from_le_bytes(take_array(data, "f32")?).into()),
+        IdlType::U64 => Value::integer(u64::from_le_bytes(take_array(data, "u64")?).into()),
+        IdlType::I64 => Value::integer(i64::from_le_bytes(take_array(data, "i64")?).into()),
+        IdlType::F64 => Value::float(f64::from_le_bytes(take_array(data, "f64")?)),
+        IdlType::U128 => Value::integer(
+            u128::from_le_bytes(take_array(data, "u128")?)
+                .try_into()
+                .map_err(|e| format!("unable to convert u128 to i128: {e}"))?,
+        ),
+        IdlType::I128 => Value::integer(i128::from_le_bytes(take_array(data, "i128")?)),
+        IdlType::U256 => return Err("IDL type U256 is not yet supported".to_string()),
+        IdlType::I256 => return Err("IDL type I256 is not yet supported".to_string()),
+        IdlType::Bytes => {
+            let len = take_length(data, "bytes")?;
+            Value::buffer(take_bytes(data, len, "bytes")?.to_vec())
+        }
+        IdlType::String => {
+            let len = take_length(data, "string")?;
+            let bytes = take_bytes(data, len, "string")?;
+            Value::string(
+                String::from_utf8(bytes.to_vec())
+                    .map_err(|e| format!("unable to decode string: {e}"))?,
+            )
+        }
+        IdlType::Pubkey => SvmValue::pubkey(take_bytes(data, 32, "pubkey")?.to_vec()),
+        IdlType::Option(idl_type) => match take_array::<1>(data, "option")?[0] {
+            0 => Value::null(),
+            1 => borsh_decode_idl_type(data, idl_type, idl_types, generics_context)?,
+            tag => return Err(format!("unable to decode option: invalid tag {tag}")),
+        },
+        IdlType::Vec(idl_type) => {
+            let len = take_length(data, "vec")?;
+            let mut items = Vec::with_capacity(len.min(data.len()));
+            for _ in 0..len {
+                items.push(borsh_decode_idl_type(data, idl_type, idl_types, generics_context)?);
+            }
+            Value::array(items)
+        }
+        IdlType::Array(idl_type, idl_array_len) => {
+            let len = match idl_array_len {
+                IdlArrayLen::Value(len) => *len,
+                IdlArrayLen::Generic(generic_len) => {
+                    let Some((type_def_generics, generic_args)) = generics_context else {
+                        return Err(format!(
+                            "generic array length {generic_len} used outside of a generic type"
+                        ));
+                    };
+                    let IdlType::Defined { name, .. } = parse_generic_expected_type(
+                        &IdlType::Generic(generic_len.to_string()),
+                        type_def_generics,
+                        generic_args,
+                    )?
+                    else {
+                        return Err(format!("unable to parse generic array length"));
+                    };
+                    name.parse::<usize>()
+                        .map_err(|e| format!("unable to parse generic array length: {}", e))?
+                }
+            };
+            let mut items = Vec::with_capacity(len.min(data.len()));
+            for _ in 0..len {
+                items.push(borsh_decode_idl_type(data, idl_type, idl_types, generics_context)?);
+            }
+            Value::array(items)
+        }
+        IdlType::Defined { name, generics } => {
+            let typing = idl_types
+                .iter()
+                .find(|t| &t.name == name)
+                .ok_or_else(|| format!("unable to find type definition for {} in idl", name))?;
+            let generics_context = Some((&typing.generics, generics));
+            match &typing.ty {
+                IdlTypeDefTy::Struct { fields } => match fields {
+                    Some(fields) => {
+                        borsh_decode_idl_defined_fields(data, fields, idl_types, generics_context)?
+                    }
+                    None => ObjectType::new().to_value(),
+                },
+                IdlTypeDefTy::Enum { variants } => {
+                    let variant_index = take_array::<1>(data, "enum variant")?[0] as usize;
+                    let variant = variants.get(variant_index).ok_or_else(|| {
+                        format!("unable to decode enum {}: unknown variant {}", name, variant_index)
+                    })?;
+                    let mut enum_value =
+                        ObjectType::from(vec![("variant", Value::string(variant.name.clone()))]);
+                    if let Some(fields) = &variant.fields {
+                        enum_value.insert(
+                            "value",
+                            borsh_decode_idl_defined_fields(
+                                data,
+                                fields,
+                                idl_types,
+                                generics_context,
+                            )?,
+                        );
+                    }
+                    enum_value.to_value()
+                }
+                IdlTypeDefTy::Type { alias } => {
+                    borsh_decode_idl_type(data, alias, idl_types, generics_context)?
+                }
+            }
+        }
+        IdlType::Generic(_) => {
+            let Some((type_def_generics, generic_args)) = generics_context else {
+                return Err(format!("generic type {:?} used outside of a generic type", idl_type));
+            };
+            let ty = parse_generic_expected_type(idl_type, type_def_generics, generic_args)?;
+            borsh_decode_idl_type(data, &ty, idl_types, None)?
+        }
+        t => return Err(format!("IDL type {:?} is not yet supported", t)),
+    };
+    Ok(value)
+}
+
+fn borsh_decode_idl_defined_fields(
+    data: &mut &[u8],
+    idl_defined_fields: &IdlDefinedFields,
+    idl_types: &Vec<IdlTypeDef>,
+    generics_context: Option<GenericsContext>,
+) -> Result<Value, String> {
+    match idl_defined_fields {
+        IdlDefinedFields::Named(fields) => {
+            let mut map = IndexMap::new();
+            for field in fields {
+                let value = borsh_decode_idl_type(data, &field.ty, idl_types, generics_context)
+                    .map_err(|e| format!("failed to decode field '{}': {}", field.name, e))?;
+                map.insert(field.name.clone(), value);
+            }
+            Ok(ObjectType::from_map(map).to_value())
+        }
+        IdlDefinedFields::Tuple(types) => {
+            let mut items = vec![];
+            for (i, ty) in types.iter().enumerate() {
+                let value = borsh_decode_idl_type(data, ty, idl_types, generics_context)
+                    .map_err(|e| format!("failed to decode field #{}: {}", i + 1, e))?;
+                items.push(value);
+            }
+            Ok(Value::array(items))
+        }
+    }
+}
+
 fn parse_generic_expected_type(
     expected_type: &IdlType,
     type_def_generics: &Vec<IdlTypeDefGeneric>,
@@ -834,3 +1069,63 @@ fn parse_generic_expected_type(
     };
     Ok(ty.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDL: &str = r#"{
+        "address": "11111111111111111111111111111111",
+        "metadata": { "name": "config", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": [],
+        "accounts": [{ "name": "Config", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }],
+        "types": [
+            {
+                "name": "Config",
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "admin", "type": "pubkey" },
+                        { "name": "fee", "type": "u64" },
+                        { "name": "label", "type": { "option": "string" } },
+                        { "name": "status", "type": { "defined": { "name": "Status" } } }
+                    ]
+                }
+            },
+            {
+                "name": "Status",
+                "type": {
+                    "kind": "enum",
+                    "variants": [{ "name": "Paused" }, { "name": "Active", "fields": ["u8"] }]
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn it_decodes_account_data() {
+        let idl_ref = IdlRef::from_str(IDL).unwrap();
+        let admin = Pubkey::new_unique();
+
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend(admin.to_bytes());
+        data.extend(42u64.to_le_bytes());
+        data.extend(borsh::to_vec(&Some("main".to_string())).unwrap());
+        data.extend([1, 3]);
+
+        let (account_type, value) = idl_ref.decode_account_data(&data, None).unwrap();
+        assert_eq!(account_type, "Config");
+        let object = value.as_object().unwrap();
+        assert_eq!(SvmValue::to_pubkey(object.get("admin").unwrap()).unwrap(), admin);
+        assert_eq!(object.get("fee").unwrap().as_integer(), Some(42));
+        assert_eq!(object.get("label").unwrap().as_string(), Some("main"));
+        let status = object.get("status").unwrap().as_object().unwrap();
+        assert_eq!(status.get("variant").unwrap().as_string(), Some("Active"));
+        assert_eq!(status.get("value").unwrap().as_array().unwrap()[0].as_integer(), Some(3));
+
+        // truncated data is reported instead of panicking
+        assert!(idl_ref.decode_account_data(&data[..20], Some("Config")).is_err());
+        // a foreign discriminator is rejected
+        assert!(idl_ref.decode_account_data(&[0; 64], None).is_err());
+    }
+}
@@ -3,6 +3,9 @@ pub mod idl;
 pub mod instruction;
 pub mod native;
 pub mod send_transaction;
+pub mod squads;
+pub mod token;
+pub mod transaction;
 pub mod utils;
 
 use anchor::AnchorProgramArtifacts;
@@ -24,7 +27,6 @@ use solana_sdk::hash::Hash;
 use solana_sdk::packet::PACKET_DATA_SIZE;
 use solana_sdk::signature::keypair_from_seed;
 use solana_sdk::signature::Keypair;
-use solana_sdk::signature::Signature;
 use solana_sdk::signer::Signer;
 use solana_sdk::system_instruction;
 use solana_sdk::system_instruction::MAX_PERMITTED_DATA_LENGTH;
@@ -51,6 +53,8 @@ use crate::constants::PAYER;
 use crate::typing::SvmValue;
 use crate::typing::SVM_CLOSE_TEMP_AUTHORITY_TRANSACTION_PARTS;
 use crate::typing::SVM_DEPLOYMENT_TRANSACTION;
+use transaction::ResolvedTransactionOptions;
+use transaction::SvmTransaction;
 
 const LAMPORTS_PER_SIGNATURE: u64 = 5000;
 
@@ -77,7 +81,7 @@ impl TxtxDeploymentSigner {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DeploymentTransaction {
     pub signers: Option<Vec<TxtxDeploymentSigner>>,
-    pub transaction: Transaction,
+    pub transaction: SvmTransaction,
     pub keypairs_bytes: Vec<Vec<u8>>,
     pub transaction_type: DeploymentTransactionType,
     pub commitment_level: CommitmentLevel,
@@ -86,7 +90,7 @@ pub struct DeploymentTransaction {
 
 impl DeploymentTransaction {
     pub fn new(
-        transaction: &Transaction,
+        transaction: &SvmTransaction,
         keypairs: Vec<&Keypair>,
         signers: Option<Vec<TxtxDeploymentSigner>>,
         transaction_type: DeploymentTransactionType,
@@ -105,7 +109,7 @@ impl DeploymentTransaction {
     }
 
     pub fn create_temp_account(
-        transaction: &Transaction,
+        transaction: &SvmTransaction,
         keypairs: Vec<&Keypair>,
         temp_authority_keypair: &Keypair,
     ) -> Self {
@@ -121,7 +125,7 @@ impl DeploymentTransaction {
         )
     }
 
-    pub fn create_buffer(transaction: &Transaction, keypairs: Vec<&Keypair>) -> Self {
+    pub fn create_buffer(transaction: &SvmTransaction, keypairs: Vec<&Keypair>) -> Self {
         Self::new(
             transaction,
             keypairs,
@@ -133,7 +137,7 @@ impl DeploymentTransaction {
     }
 
     pub fn write_to_buffer(
-        transaction: &Transaction,
+        transaction: &SvmTransaction,
         keypairs: Vec<&Keypair>,
         commitment_level: CommitmentLevel,
         do_await_confirmation: bool,
@@ -148,7 +152,10 @@ impl DeploymentTransaction {
         )
     }
 
-    pub fn transfer_buffer_authority(transaction: &Transaction, keypairs: Vec<&Keypair>) -> Self {
+    pub fn transfer_buffer_authority(
+        transaction: &SvmTransaction,
+        keypairs: Vec<&Keypair>,
+    ) -> Self {
         Self::new(
             transaction,
             keypairs,
@@ -159,7 +166,10 @@ impl DeploymentTransaction {
         )
     }
 
-    pub fn transfer_program_authority(transaction: &Transaction, keypairs: Vec<&Keypair>) -> Self {
+    pub fn transfer_program_authority(
+        transaction: &SvmTransaction,
+        keypairs: Vec<&Keypair>,
+    ) -> Self {
         Self::new(
             transaction,
             keypairs,
@@ -170,7 +180,7 @@ impl DeploymentTransaction {
         )
     }
 
-    pub fn deploy_program(transaction: &Transaction, keypairs: Vec<&Keypair>) -> Self {
+    pub fn deploy_program(transaction: &SvmTransaction, keypairs: Vec<&Keypair>) -> Self {
         Self::new(
             transaction,
             keypairs,
@@ -181,7 +191,7 @@ impl DeploymentTransaction {
         )
     }
 
-    pub fn upgrade_program(transaction: &Transaction, keypairs: Vec<&Keypair>) -> Self {
+    pub fn upgrade_program(transaction: &SvmTransaction, keypairs: Vec<&Keypair>) -> Self {
         Self::new(
             transaction,
             keypairs,
@@ -192,7 +202,10 @@ impl DeploymentTransaction {
         )
     }
 
-    pub fn payer_close_temp_authority(transaction: &Transaction, keypairs: Vec<&Keypair>) -> Self {
+    pub fn payer_close_temp_authority(
+        transaction: &SvmTransaction,
+        keypairs: Vec<&Keypair>,
+    ) -> Self {
         Self::new(
             transaction,
             keypairs,
@@ -204,7 +217,7 @@ impl DeploymentTransaction {
     }
 
     pub fn temp_authority_close_temp_authority(
-        transaction: &Transaction,
+        transaction: &SvmTransaction,
         keypairs: Vec<&Keypair>,
     ) -> Self {
         Self::new(
@@ -220,7 +233,7 @@ impl DeploymentTransaction {
     pub fn skip_temp_authority_close() -> Self {
         Self {
             signers: None,
-            transaction: Transaction::new_unsigned(Message::new(&[], None)),
+            transaction: SvmTransaction::Legacy(Transaction::new_unsigned(Message::new(&[], None))),
             keypairs_bytes: vec![],
             transaction_type: DeploymentTransactionType::SkipCloseTempAuthority,
             commitment_level: CommitmentLevel::Confirmed,
@@ -332,8 +345,8 @@ impl DeploymentTransaction {
         );
 
         let mut instructions = vec![];
-        let message_account_keys = self.transaction.message.account_keys.clone();
-        for instruction in self.transaction.message.instructions.iter() {
+        let message_account_keys = self.transaction.static_account_keys();
+        for instruction in self.transaction.instructions().iter() {
             let Some(account) = message_account_keys.get(instruction.program_id_index as usize)
             else {
                 continue;
@@ -366,19 +379,15 @@ impl DeploymentTransaction {
             ("instructions", Value::array(instructions)),
             (
                 "num_required_signatures",
-                Value::integer(self.transaction.message.header.num_required_signatures as i128),
+                Value::integer(self.transaction.header().num_required_signatures as i128),
             ),
             (
                 "num_readonly_signed_accounts",
-                Value::integer(
-                    self.transaction.message.header.num_readonly_signed_accounts as i128,
-                ),
+                Value::integer(self.transaction.header().num_readonly_signed_accounts as i128),
             ),
             (
                 "num_readonly_unsigned_accounts",
-                Value::integer(
-                    self.transaction.message.header.num_readonly_unsigned_accounts as i128,
-                ),
+                Value::integer(self.transaction.header().num_readonly_unsigned_accounts as i128),
             ),
         ]);
 
@@ -396,7 +405,7 @@ impl DeploymentTransaction {
     pub fn sign_transaction_with_keypairs(
         &self,
         rpc_api_url: &str,
-    ) -> Result<Transaction, Diagnostic> {
+    ) -> Result<SvmTransaction, Diagnostic> {
         let rpc_client = RpcClient::new_with_commitment(
             rpc_api_url,
             CommitmentConfig { commitment: self.commitment_level },
@@ -406,15 +415,13 @@ impl DeploymentTransaction {
             .get_latest_blockhash()
             .map_err(|e| diagnosed_error!("failed to get latest blockhash: {e}"))?;
 
-        let mut transaction: Transaction = self.transaction.clone();
+        let mut transaction: SvmTransaction = self.transaction.clone();
 
-        transaction.message.recent_blockhash = blockhash;
+        transaction.set_recent_blockhash(blockhash);
         let keypairs =
             self.get_keypairs().map_err(|e| diagnosed_error!("failed to sign transaction: {e}"))?;
 
-        transaction
-            .try_partial_sign(&keypairs, transaction.message.recent_blockhash)
-            .map_err(|e| diagnosed_error!("failed to sign transaction: {e}"))?;
+        transaction.try_partial_sign(&keypairs)?;
 
         Ok(transaction)
     }
@@ -621,6 +628,8 @@ pub struct UpgradeableProgramDeployer {
     pub auto_extend: bool,
     /// Whether the program is being upgraded (true), or deployed for the first time (false).
     pub is_program_upgrade: bool,
+    /// The compute budget and address lookup tables applied to the deployment transactions.
+    pub transaction_options: ResolvedTransactionOptions,
 }
 
 pub enum KeypairOrTxSigner {
@@ -643,6 +652,8 @@ impl UpgradeableProgramDeployer {
     ///     * `Pubkey` - The public key of the existing program buffer.
     ///     * `Keypair` - The keypair associated with the existing program buffer.
     ///     * `Vec<u8>` - A vector of bytes representing the existing program buffer data. If `None`, a new program buffer will be created.
+    /// * `auto_extend` - Whether to extend the program data account on upgrades. Defaults to `true`.
+    /// * `transaction_options` - The compute budget and address lookup tables to apply to the deployment transactions.
     pub fn new(
         program_keypair: Keypair,
         final_upgrade_authority_pubkey: &Pubkey,
@@ -652,6 +663,7 @@ impl UpgradeableProgramDeployer {
         rpc_client: RpcClient,
         existing_program_buffer_opts: Option<(Pubkey, Keypair, Vec<u8>)>,
         auto_extend: Option<bool>,
+        transaction_options: ResolvedTransactionOptions,
     ) -> Result<Self, Diagnostic> {
         let (buffer_pubkey, buffer_keypair, buffer_data) = match existing_program_buffer_opts {
             Some((buffer_pubkey, buffer_keypair, buffer_data)) => {
@@ -683,9 +695,20 @@ impl UpgradeableProgramDeployer {
             buffer_data,
             auto_extend: auto_extend.unwrap_or(true),
             is_program_upgrade,
+            transaction_options,
         })
     }
 
+    /// Builds an unsigned deployment transaction, applying the compute budget and address lookup tables.
+    fn build_transaction(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        blockhash: &Hash,
+    ) -> Result<SvmTransaction, Diagnostic> {
+        self.transaction_options.build_transaction(instructions, Some(payer), blockhash)
+    }
+
     pub fn get_transactions(&mut self) -> Result<Vec<Value>, Diagnostic> {
         let recent_blockhash = self
             .rpc_client
@@ -799,6 +822,13 @@ impl UpgradeableProgramDeployer {
             //         .unwrap_or(LAMPORTS_PER_SIGNATURE)
             //         * (finalize_tx_count) as u64;
             lamports += LAMPORTS_PER_SIGNATURE * (finalize_tx_count) as u64;
+
+            // priority fees, if any: write transactions hold a single instruction, the other
+            // transactions hold up to 3 instructions
+            lamports += self.transaction_options.max_priority_fee(1) * write_tx_count as u64;
+            lamports += self.transaction_options.max_priority_fee(3)
+                * (buffer_create_tx_count + set_buffer_authority_tx_count + finalize_tx_count)
+                    as u64;
         }
 
         // calculate rent for all program data written
@@ -823,10 +853,7 @@ impl UpgradeableProgramDeployer {
             0,
             &solana_sdk::system_program::id(),
         );
-        let message =
-            Message::new_with_blockhash(&[instruction], Some(&self.payer_pubkey), &blockhash);
-
-        let transaction = Transaction::new_unsigned(message);
+        let transaction = self.build_transaction(&[instruction], &self.payer_pubkey, blockhash)?;
 
         DeploymentTransaction::create_temp_account(
             &transaction,
@@ -861,13 +888,11 @@ impl UpgradeableProgramDeployer {
     fn get_create_buffer_transaction(&self, blockhash: &Hash) -> Result<Value, Diagnostic> {
         let create_buffer_instruction = self.get_create_buffer_instruction()?;
 
-        let message = Message::new_with_blockhash(
+        let transaction = self.build_transaction(
             &create_buffer_instruction,
-            Some(&self.temp_upgrade_authority_pubkey), // todo: can this be none? isn't the payer already set in the instruction
-            &blockhash,
-        );
-
-        let transaction = Transaction::new_unsigned(message);
+            &self.temp_upgrade_authority_pubkey,
+            blockhash,
+        )?;
 
         DeploymentTransaction::create_buffer(
             &transaction,
@@ -935,13 +960,8 @@ impl UpgradeableProgramDeployer {
             };
         }
 
-        let message = Message::new_with_blockhash(
-            &instructions,
-            Some(&self.temp_upgrade_authority_pubkey), // todo: can this be none? isn't the payer already set in the instruction
-            &blockhash,
-        );
-
-        let transaction = Transaction::new_unsigned(message);
+        let transaction =
+            self.build_transaction(&instructions, &self.temp_upgrade_authority_pubkey, blockhash)?;
 
         DeploymentTransaction::create_buffer(
             &transaction,
@@ -952,7 +972,7 @@ impl UpgradeableProgramDeployer {
 
     // Mostly copied from solana cli: https://github.com/txtx/solana/blob/8116c10021f09c806159852f65d37ffe6d5a118e/cli/src/program.rs#L2455
     fn get_write_to_buffer_transactions(&self, blockhash: &Hash) -> Result<Vec<Value>, Diagnostic> {
-        let create_msg = |offset: u32, bytes: Vec<u8>| {
+        let create_tx = |offset: u32, bytes: Vec<u8>| {
             let instruction = bpf_loader_upgradeable::write(
                 &self.buffer_pubkey,
                 &self.temp_upgrade_authority_pubkey,
@@ -960,16 +980,11 @@ impl UpgradeableProgramDeployer {
                 bytes,
             );
 
-            let instructions = vec![instruction];
-            Message::new_with_blockhash(
-                &instructions,
-                Some(&self.temp_upgrade_authority_pubkey), // todo: can this be none? isn't the payer already set in the instruction
-                &blockhash,
-            )
+            self.build_transaction(&[instruction], &self.temp_upgrade_authority_pubkey, blockhash)
         };
 
         let mut write_transactions = vec![];
-        let chunk_size = calculate_max_chunk_size(&create_msg);
+        let chunk_size = calculate_max_chunk_size(&create_tx)?;
 
         let chunks = self.binary.chunks(chunk_size).collect::<Vec<_>>();
 
@@ -977,8 +992,7 @@ impl UpgradeableProgramDeployer {
             let offset = i.saturating_mul(chunk_size);
             // Only write the chunk if it differs from our initial buffer data
             if *chunk != &self.buffer_data[offset..offset.saturating_add(chunk.len())] {
-                let transaction =
-                    Transaction::new_unsigned(create_msg(offset as u32, chunk.to_vec()));
+                let transaction = create_tx(offset as u32, chunk.to_vec())?;
 
                 let (do_await_confirmation, commitment_level) = if i == chunks.len() - 1 {
                     (true, CommitmentLevel::Confirmed)
@@ -1010,13 +1024,8 @@ impl UpgradeableProgramDeployer {
             &self.final_upgrade_authority_pubkey,
         );
 
-        let message = Message::new_with_blockhash(
-            &[instruction],
-            Some(&self.temp_upgrade_authority_pubkey), // todo: can this be none? isn't the payer already set in the instruction
-            &blockhash,
-        );
-
-        let transaction = Transaction::new_unsigned(message);
+        let transaction =
+            self.build_transaction(&[instruction], &self.temp_upgrade_authority_pubkey, blockhash)?;
 
         DeploymentTransaction::transfer_buffer_authority(
             &transaction,
@@ -1035,13 +1044,8 @@ impl UpgradeableProgramDeployer {
             Some(&self.final_upgrade_authority_pubkey),
         );
 
-        let message = Message::new_with_blockhash(
-            &[instruction],
-            Some(&self.temp_upgrade_authority_pubkey), // todo: can this be none? isn't the payer already set in the instruction
-            &blockhash,
-        );
-
-        let transaction = Transaction::new_unsigned(message);
+        let transaction =
+            self.build_transaction(&[instruction], &self.temp_upgrade_authority_pubkey, blockhash)?;
 
         DeploymentTransaction::transfer_program_authority(
             &transaction,
@@ -1108,7 +1112,7 @@ impl UpgradeableProgramDeployer {
                 &blockhash,
             );
 
-            let transaction = Transaction::new_unsigned(message);
+            let transaction = SvmTransaction::Legacy(Transaction::new_unsigned(message));
 
             return Some(
                 DeploymentTransaction::temp_authority_close_temp_authority(
@@ -1143,12 +1147,8 @@ impl UpgradeableProgramDeployer {
             diagnosed_error!("failed to create deploy with max program len instruction: {e}")
         })?;
 
-        let message = Message::new_with_blockhash(
-            &instructions,
-            Some(&self.temp_upgrade_authority_pubkey),
-            &blockhash,
-        );
-        let transaction = Transaction::new_unsigned(message);
+        let transaction =
+            self.build_transaction(&instructions, &self.temp_upgrade_authority_pubkey, blockhash)?;
 
         DeploymentTransaction::deploy_program(
             &transaction,
@@ -1165,12 +1165,11 @@ impl UpgradeableProgramDeployer {
             &self.payer_pubkey,
         );
 
-        let message = Message::new_with_blockhash(
+        let transaction = self.build_transaction(
             &[upgrade_instruction],
-            Some(&self.final_upgrade_authority_pubkey),
-            &blockhash,
-        );
-        let transaction = Transaction::new_unsigned(message);
+            &self.final_upgrade_authority_pubkey,
+            blockhash,
+        )?;
 
         DeploymentTransaction::upgrade_program(&transaction, vec![]).to_value()
     }
@@ -1264,21 +1263,14 @@ fn create_ephemeral_keypair() -> (usize, Mnemonic, Keypair) {
 }
 
 /// Copied from solana cli: https://github.com/txtx/solana/blob/8116c10021f09c806159852f65d37ffe6d5a118e/cli/src/program.rs#L2386
-fn calculate_max_chunk_size<F>(create_msg: &F) -> usize
+fn calculate_max_chunk_size<F>(create_tx: &F) -> Result<usize, Diagnostic>
 where
-    F: Fn(u32, Vec<u8>) -> Message,
+    F: Fn(u32, Vec<u8>) -> Result<SvmTransaction, Diagnostic>,
 {
-    let baseline_msg = create_msg(0, Vec::new());
-    let tx_size = bincode::serialized_size(&Transaction {
-        signatures: vec![
-            Signature::default();
-            baseline_msg.header.num_required_signatures as usize
-        ],
-        message: baseline_msg,
-    })
-    .unwrap() as usize;
+    let baseline_tx = create_tx(0, Vec::new())?;
+    let tx_size = baseline_tx.serialized_size()?;
     // add 1 byte buffer to account for shortvec encoding
-    PACKET_DATA_SIZE.saturating_sub(tx_size).saturating_sub(1)
+    Ok(PACKET_DATA_SIZE.saturating_sub(tx_size).saturating_sub(1))
 }
 
 pub fn transaction_is_fully_signed(transaction: &Transaction) -> bool {
@@ -1,9 +1,8 @@
 use std::sync::Arc;
 
-use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_client::{RpcClient, SerializableTransaction};
 use solana_client::rpc_config::RpcSendTransactionConfig;
 use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
-use solana_sdk::transaction::Transaction;
 use txtx_addon_kit::channel;
 use txtx_addon_kit::constants::SIGNED_TRANSACTION_BYTES;
 use txtx_addon_kit::types::commands::CommandExecutionResult;
@@ -15,8 +14,10 @@ use txtx_addon_kit::types::types::{RunbookSupervisionContext, Value};
 use txtx_addon_kit::types::ConstructDid;
 use txtx_addon_kit::uuid::Uuid;
 
+use crate::codec::transaction::SvmTransaction;
 use crate::constants::{
-    COMMITMENT_LEVEL, DO_AWAIT_CONFIRMATION, IS_DEPLOYMENT, RPC_API_URL, SIGNATURE,
+    COMMITMENT_LEVEL, DO_AWAIT_CONFIRMATION, IS_DEPLOYMENT, IS_EXECUTED_BY_SIGNER, RPC_API_URL,
+    SIGNATURE,
 };
 
 pub fn send_transaction_background_task(
@@ -35,6 +36,11 @@ pub fn send_transaction_background_task(
     let background_tasks_uuid = background_tasks_uuid.clone();
 
     let future = async move {
+        // signers such as `svm::squads` broadcast the transaction themselves
+        if outputs.get_bool(IS_EXECUTED_BY_SIGNER).unwrap_or(false) {
+            return Ok(CommandExecutionResult::from_value_store(&outputs));
+        }
+
         let rpc_api_url = inputs.get_expected_string(RPC_API_URL).unwrap().to_string();
         let commitment_level = inputs.get_expected_string(COMMITMENT_LEVEL).unwrap_or("confirmed");
         let do_await_confirmation = inputs.get_bool(DO_AWAIT_CONFIRMATION).unwrap_or(true);
@@ -89,18 +95,33 @@ pub fn send_transaction(
     do_await_confirmation: bool,
     transaction_bytes: &Vec<u8>,
 ) -> Result<String, Diagnostic> {
-    let transaction: Transaction = serde_json::from_slice(&transaction_bytes).map_err(|e| {
-        diagnosed_error!("unable to deserialize transaction from bytes ({})", e.to_string())
+    let transaction = SvmTransaction::from_bytes(&transaction_bytes).map_err(|e| {
+        diagnosed_error!("unable to deserialize transaction from bytes ({})", e.message)
     })?;
 
+    match transaction {
+        SvmTransaction::Legacy(transaction) => {
+            send_serializable_transaction(&rpc_client, do_await_confirmation, &transaction)
+        }
+        SvmTransaction::Versioned(transaction) => {
+            send_serializable_transaction(&rpc_client, do_await_confirmation, &transaction)
+        }
+    }
+}
+
+fn send_serializable_transaction(
+    rpc_client: &RpcClient,
+    do_await_confirmation: bool,
+    transaction: &impl SerializableTransaction,
+) -> Result<String, Diagnostic> {
     let signature = if do_await_confirmation {
-        rpc_client.send_and_confirm_transaction(&transaction).map_err(|e| {
+        rpc_client.send_and_confirm_transaction(transaction).map_err(|e| {
             diagnosed_error!("unable to send and confirm transaction ({})", e.to_string())
         })?
     } else {
         rpc_client
             .send_transaction_with_config(
-                &transaction,
+                transaction,
                 RpcSendTransactionConfig {
                     skip_preflight: true,
                     preflight_commitment: None,
@@ -0,0 +1,512 @@
+use std::str::FromStr;
+
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::{Message, MessageHeader};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+use txtx_addon_kit::types::diagnostics::Diagnostic;
+
+/// The address of the Squads v4 multisig program, deployed at the same address on all clusters.
+pub const SQUADS_V4_PROGRAM_ID: &str = "SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf";
+
+const SEED_PREFIX: &[u8] = b"multisig";
+const SEED_VAULT: &[u8] = b"vault";
+const SEED_TRANSACTION: &[u8] = b"transaction";
+const SEED_PROPOSAL: &[u8] = b"proposal";
+
+const PERMISSION_INITIATE: u8 = 1 << 0;
+const PERMISSION_VOTE: u8 = 1 << 1;
+const PERMISSION_EXECUTE: u8 = 1 << 2;
+
+pub fn squads_program_id(program_id: Option<&str>) -> Result<Pubkey, Diagnostic> {
+    let program_id = program_id.unwrap_or(SQUADS_V4_PROGRAM_ID);
+    Pubkey::from_str(program_id)
+        .map_err(|e| diagnosed_error!("invalid squads program id '{}': {e}", program_id))
+}
+
+pub fn get_vault_pda(multisig: &Pubkey, vault_index: u8, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[SEED_PREFIX, multisig.as_ref(), SEED_VAULT, &[vault_index]],
+        program_id,
+    )
+    .0
+}
+
+pub fn get_transaction_pda(
+    multisig: &Pubkey,
+    transaction_index: u64,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[SEED_PREFIX, multisig.as_ref(), SEED_TRANSACTION, &transaction_index.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+pub fn get_proposal_pda(multisig: &Pubkey, transaction_index: u64, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            SEED_PREFIX,
+            multisig.as_ref(),
+            SEED_TRANSACTION,
+            &transaction_index.to_le_bytes(),
+            SEED_PROPOSAL,
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// Computes the 8 bytes discriminator Anchor prepends to instructions data (`global` namespace)
+/// and accounts data (`account` namespace).
+fn anchor_discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator
+        .copy_from_slice(&hash(format!("{}:{}", namespace, name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Minimal cursor used for decoding the borsh-encoded Squads accounts.
+struct AccountReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> AccountReader<'a> {
+    fn new(data: &'a [u8], account_name: &str) -> Result<Self, Diagnostic> {
+        if data.len() < 8 || data[..8] != anchor_discriminator("account", account_name) {
+            return Err(diagnosed_error!("account data is not a squads {} account", account_name));
+        }
+        Ok(Self { data, offset: 8 })
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Diagnostic> {
+        let end = self.offset + len;
+        let Some(bytes) = self.data.get(self.offset..end) else {
+            return Err(diagnosed_error!("unexpected end of squads account data"));
+        };
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, Diagnostic> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, Diagnostic> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, Diagnostic> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, Diagnostic> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_pubkey(&mut self) -> Result<Pubkey, Diagnostic> {
+        Ok(Pubkey::new_from_array(self.take(32)?.try_into().unwrap()))
+    }
+
+    fn read_pubkeys(&mut self) -> Result<Vec<Pubkey>, Diagnostic> {
+        let len = self.read_u32()?;
+        (0..len).map(|_| self.read_pubkey()).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SquadsMember {
+    pub key: Pubkey,
+    pub permissions: u8,
+}
+
+impl SquadsMember {
+    pub fn can_initiate(&self) -> bool {
+        self.permissions & PERMISSION_INITIATE != 0
+    }
+    pub fn can_vote(&self) -> bool {
+        self.permissions & PERMISSION_VOTE != 0
+    }
+    pub fn can_execute(&self) -> bool {
+        self.permissions & PERMISSION_EXECUTE != 0
+    }
+}
+
+/// The subset of the Squads v4 `Multisig` account needed to create and track proposals.
+#[derive(Debug, Clone)]
+pub struct SquadsMultisig {
+    pub threshold: u16,
+    pub time_lock: u32,
+    pub transaction_index: u64,
+    pub stale_transaction_index: u64,
+    pub members: Vec<SquadsMember>,
+}
+
+impl SquadsMultisig {
+    pub fn from_account_data(data: &[u8]) -> Result<Self, Diagnostic> {
+        let mut reader = AccountReader::new(data, "Multisig")?;
+        let _create_key = reader.read_pubkey()?;
+        let _config_authority = reader.read_pubkey()?;
+        let threshold = reader.read_u16()?;
+        let time_lock = reader.read_u32()?;
+        let transaction_index = reader.read_u64()?;
+        let stale_transaction_index = reader.read_u64()?;
+        if reader.read_u8()? == 1 {
+            let _rent_collector = reader.read_pubkey()?;
+        }
+        let _bump = reader.read_u8()?;
+        let members_count = reader.read_u32()?;
+        let mut members = vec![];
+        for _ in 0..members_count {
+            let key = reader.read_pubkey()?;
+            let permissions = reader.read_u8()?;
+            members.push(SquadsMember { key, permissions });
+        }
+        Ok(Self { threshold, time_lock, transaction_index, stale_transaction_index, members })
+    }
+
+    pub fn get_member(&self, key: &Pubkey) -> Option<&SquadsMember> {
+        self.members.iter().find(|m| m.key.eq(key))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SquadsProposalStatus {
+    Draft,
+    Active,
+    Rejected,
+    Approved,
+    Executing,
+    Executed,
+    Cancelled,
+}
+
+impl SquadsProposalStatus {
+    pub fn to_string(&self) -> String {
+        match self {
+            Self::Draft => "draft",
+            Self::Active => "active",
+            Self::Rejected => "rejected",
+            Self::Approved => "approved",
+            Self::Executing => "executing",
+            Self::Executed => "executed",
+            Self::Cancelled => "cancelled",
+        }
+        .to_string()
+    }
+}
+
+/// The subset of the Squads v4 `Proposal` account needed to track the votes on a proposal.
+#[derive(Debug, Clone)]
+pub struct SquadsProposal {
+    pub transaction_index: u64,
+    pub status: SquadsProposalStatus,
+    pub approved: Vec<Pubkey>,
+    pub rejected: Vec<Pubkey>,
+    pub cancelled: Vec<Pubkey>,
+}
+
+impl SquadsProposal {
+    pub fn from_account_data(data: &[u8]) -> Result<Self, Diagnostic> {
+        let mut reader = AccountReader::new(data, "Proposal")?;
+        let _multisig = reader.read_pubkey()?;
+        let transaction_index = reader.read_u64()?;
+        let status = match reader.read_u8()? {
+            0 => SquadsProposalStatus::Draft,
+            1 => SquadsProposalStatus::Active,
+            2 => SquadsProposalStatus::Rejected,
+            3 => SquadsProposalStatus::Approved,
+            4 => SquadsProposalStatus::Executing,
+            5 => SquadsProposalStatus::Executed,
+            6 => SquadsProposalStatus::Cancelled,
+            other => return Err(diagnosed_error!("unknown squads proposal status: {}", other)),
+        };
+        // every status but `Executing` carries a timestamp
+        if status != SquadsProposalStatus::Executing {
+            let _timestamp = reader.read_u64()?;
+        }
+        let _bump = reader.read_u8()?;
+        let approved = reader.read_pubkeys()?;
+        let rejected = reader.read_pubkeys()?;
+        let cancelled = reader.read_pubkeys()?;
+        Ok(Self { transaction_index, status, approved, rejected, cancelled })
+    }
+}
+
+/// The message executed by a vault transaction, compiled the same way a legacy message is
+/// (writable signers, readonly signers, writable non signers, readonly non signers).
+#[derive(Debug, Clone)]
+pub struct VaultTransactionMessage {
+    pub message: Message,
+}
+
+impl VaultTransactionMessage {
+    /// Compiles the instructions of `transaction` into a message that can be executed by the vault.
+    /// The vault must be the only signer required by the instructions.
+    pub fn from_transaction(transaction: &Transaction, vault: &Pubkey) -> Result<Self, Diagnostic> {
+        let message = &transaction.message;
+        let mut instructions = vec![];
+        for compiled_instruction in message.instructions.iter() {
+            let Some(program_id) =
+                message.account_keys.get(compiled_instruction.program_id_index as usize)
+            else {
+                return Err(diagnosed_error!("invalid program id index in transaction"));
+            };
+            let mut accounts = vec![];
+            for account_index in compiled_instruction.accounts.iter() {
+                let index = *account_index as usize;
+                let Some(pubkey) = message.account_keys.get(index) else {
+                    return Err(diagnosed_error!("invalid account index in transaction"));
+                };
+                let is_signer = index < message.header.num_required_signatures as usize;
+                if is_signer && !pubkey.eq(vault) {
+                    return Err(diagnosed_error!(
+                        "account {} is required to sign the transaction, but squads vault transactions can only be signed by the vault ({})",
+                        pubkey,
+                        vault
+                    ));
+                }
+                accounts.push(AccountMeta {
+                    pubkey: *pubkey,
+                    is_signer,
+                    is_writable: is_writable_index(
+                        &message.header,
+                        message.account_keys.len(),
+                        index,
+                    ),
+                });
+            }
+            instructions.push(Instruction {
+                program_id: *program_id,
+                accounts,
+                data: compiled_instruction.data.clone(),
+            });
+        }
+
+        Ok(Self { message: Message::new(&instructions, Some(vault)) })
+    }
+
+    /// Serializes the message using the compact encoding expected by `vault_transaction_create`.
+    pub fn serialize(&self) -> Vec<u8> {
+        let header = &self.message.header;
+        let num_signers = header.num_required_signatures;
+        let num_writable_signers = num_signers - header.num_readonly_signed_accounts;
+        let num_writable_non_signers = (self.message.account_keys.len() as u8 - num_signers)
+            - header.num_readonly_unsigned_accounts;
+
+        let mut bytes = vec![num_signers, num_writable_signers, num_writable_non_signers];
+
+        bytes.push(self.message.account_keys.len() as u8);
+        for key in self.message.account_keys.iter() {
+            bytes.extend_from_slice(key.as_ref());
+        }
+
+        bytes.push(self.message.instructions.len() as u8);
+        for instruction in self.message.instructions.iter() {
+            bytes.push(instruction.program_id_index);
+            bytes.push(instruction.accounts.len() as u8);
+            bytes.extend_from_slice(&instruction.accounts);
+            bytes.extend_from_slice(&(instruction.data.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(&instruction.data);
+        }
+
+        // address table lookups
+        bytes.push(0);
+        bytes
+    }
+
+    /// The accounts that must be appended to `vault_transaction_execute`, in the order of the
+    /// message account keys. The vault is signing through the program, so no account is a signer.
+    pub fn get_execute_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let keys_count = self.message.account_keys.len();
+        self.message
+            .account_keys
+            .iter()
+            .enumerate()
+            .map(|(i, pubkey)| AccountMeta {
+                pubkey: *pubkey,
+                is_signer: false,
+                is_writable: is_writable_index(&self.message.header, keys_count, i),
+            })
+            .collect()
+    }
+}
+
+fn is_writable_index(header: &MessageHeader, keys_count: usize, index: usize) -> bool {
+    let num_signers = header.num_required_signatures as usize;
+    if index < num_signers {
+        index < num_signers - header.num_readonly_signed_accounts as usize
+    } else {
+        index - num_signers
+            < keys_count - num_signers - header.num_readonly_unsigned_accounts as usize
+    }
+}
+
+pub fn vault_transaction_create(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    transaction: &Pubkey,
+    creator: &Pubkey,
+    rent_payer: &Pubkey,
+    vault_index: u8,
+    message: &VaultTransactionMessage,
+    memo: Option<String>,
+) -> Result<Instruction, Diagnostic> {
+    let mut data = anchor_discriminator("global", "vault_transaction_create").to_vec();
+    let args = (vault_index, 0u8, message.serialize(), memo);
+    data.append(&mut borsh::to_vec(&args).map_err(|e| {
+        diagnosed_error!("failed to encode squads vault_transaction_create args: {e}")
+    })?);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig, false),
+            AccountMeta::new(*transaction, false),
+            AccountMeta::new_readonly(*creator, true),
+            AccountMeta::new(*rent_payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    })
+}
+
+pub fn proposal_create(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    proposal: &Pubkey,
+    creator: &Pubkey,
+    rent_payer: &Pubkey,
+    transaction_index: u64,
+) -> Result<Instruction, Diagnostic> {
+    let mut data = anchor_discriminator("global", "proposal_create").to_vec();
+    let args = (transaction_index, false);
+    data.append(
+        &mut borsh::to_vec(&args)
+            .map_err(|e| diagnosed_error!("failed to encode squads proposal_create args: {e}"))?,
+    );
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*multisig, false),
+            AccountMeta::new(*proposal, false),
+            AccountMeta::new_readonly(*creator, true),
+            AccountMeta::new(*rent_payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    })
+}
+
+pub fn proposal_approve(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    proposal: &Pubkey,
+    member: &Pubkey,
+) -> Result<Instruction, Diagnostic> {
+    let mut data = anchor_discriminator("global", "proposal_approve").to_vec();
+    let memo: Option<String> = None;
+    data.append(
+        &mut borsh::to_vec(&memo)
+            .map_err(|e| diagnosed_error!("failed to encode squads proposal_approve args: {e}"))?,
+    );
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*multisig, false),
+            AccountMeta::new(*member, true),
+            AccountMeta::new(*proposal, false),
+        ],
+        data,
+    })
+}
+
+pub fn vault_transaction_execute(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    proposal: &Pubkey,
+    transaction: &Pubkey,
+    member: &Pubkey,
+    message: &VaultTransactionMessage,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*multisig, false),
+        AccountMeta::new(*proposal, false),
+        AccountMeta::new_readonly(*transaction, false),
+        AccountMeta::new_readonly(*member, true),
+    ];
+    accounts.append(&mut message.get_execute_remaining_accounts());
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: anchor_discriminator("global", "vault_transaction_execute").to_vec(),
+    }
+}
+
+/// Builds the transaction wrapping `transaction` into a new vault transaction, creating its proposal
+/// and casting the first approval. The `member` pays for the rent and the fees of the transaction.
+pub fn get_create_proposal_transaction(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    vault_index: u8,
+    transaction_index: u64,
+    member: &Pubkey,
+    transaction: &Transaction,
+    memo: Option<String>,
+) -> Result<Transaction, Diagnostic> {
+    let vault = get_vault_pda(multisig, vault_index, program_id);
+    let vault_message = VaultTransactionMessage::from_transaction(transaction, &vault)?;
+    let transaction_pda = get_transaction_pda(multisig, transaction_index, program_id);
+    let proposal_pda = get_proposal_pda(multisig, transaction_index, program_id);
+
+    let instructions = vec![
+        vault_transaction_create(
+            program_id,
+            multisig,
+            &transaction_pda,
+            member,
+            member,
+            vault_index,
+            &vault_message,
+            memo,
+        )?,
+        proposal_create(program_id, multisig, &proposal_pda, member, member, transaction_index)?,
+        proposal_approve(program_id, multisig, &proposal_pda, member)?,
+    ];
+
+    let message = Message::new(&instructions, Some(member));
+    Ok(Transaction::new_unsigned(message))
+}
+
+/// Builds the transaction executing the vault transaction created by [get_create_proposal_transaction].
+pub fn get_execute_proposal_transaction(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    vault_index: u8,
+    transaction_index: u64,
+    member: &Pubkey,
+    transaction: &Transaction,
+) -> Result<Transaction, Diagnostic> {
+    let vault = get_vault_pda(multisig, vault_index, program_id);
+    let vault_message = VaultTransactionMessage::from_transaction(transaction, &vault)?;
+    let transaction_pda = get_transaction_pda(multisig, transaction_index, program_id);
+    let proposal_pda = get_proposal_pda(multisig, transaction_index, program_id);
+
+    let instruction = vault_transaction_execute(
+        program_id,
+        multisig,
+        &proposal_pda,
+        &transaction_pda,
+        member,
+        &vault_message,
+    );
+    let message = Message::new(&[instruction], Some(member));
+    Ok(Transaction::new_unsigned(message))
+}
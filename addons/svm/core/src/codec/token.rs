@@ -0,0 +1,276 @@
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+use spl_token_2022::extension::{
+    metadata_pointer, transfer_fee, ExtensionType, StateWithExtensions,
+};
+use spl_token_2022::instruction::AuthorityType;
+use spl_token_2022::state::Mint;
+use txtx_addon_kit::types::diagnostics::Diagnostic;
+
+/// The maximum transfer fee, in basis points, accepted by the Token-2022 program.
+pub const MAX_TRANSFER_FEE_BASIS_POINTS: u16 = 10_000;
+
+/// The configuration of the Token-2022 transfer fee extension of a mint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferFeeConfig {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+    pub config_authority: Option<Pubkey>,
+    pub withdraw_authority: Option<Pubkey>,
+}
+
+/// The configuration of the Token-2022 metadata pointer extension of a mint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataPointerConfig {
+    pub authority: Option<Pubkey>,
+    pub metadata_address: Option<Pubkey>,
+}
+
+/// The parameters of a new token mint.
+#[derive(Debug, Clone)]
+pub struct MintConfig {
+    pub token_program_id: Pubkey,
+    pub decimals: u8,
+    pub mint_authority: Pubkey,
+    pub freeze_authority: Option<Pubkey>,
+    pub transfer_fee: Option<TransferFeeConfig>,
+    pub metadata_pointer: Option<MetadataPointerConfig>,
+}
+
+impl MintConfig {
+    pub fn extension_types(&self) -> Vec<ExtensionType> {
+        let mut extension_types = vec![];
+        if self.transfer_fee.is_some() {
+            extension_types.push(ExtensionType::TransferFeeConfig);
+        }
+        if self.metadata_pointer.is_some() {
+            extension_types.push(ExtensionType::MetadataPointer);
+        }
+        extension_types
+    }
+
+    /// The size of the mint account, including the space used by its extensions.
+    pub fn account_len(&self) -> Result<usize, Diagnostic> {
+        ExtensionType::try_calculate_account_len::<Mint>(&self.extension_types())
+            .map_err(|e| diagnosed_error!("failed to compute mint account size: {e}"))
+    }
+
+    /// Builds the instructions creating the `mint` account, funded with `lamports` by `payer`,
+    /// and initializing it. Extensions must be initialized before the mint itself.
+    pub fn create_mint_instructions(
+        &self,
+        payer: &Pubkey,
+        mint: &Pubkey,
+        lamports: u64,
+    ) -> Result<Vec<Instruction>, Diagnostic> {
+        let extension_types = self.extension_types();
+        if !extension_types.is_empty() && self.token_program_id != spl_token_2022::id() {
+            return Err(diagnosed_error!(
+                "mint extensions are only supported by the Token-2022 program"
+            ));
+        }
+
+        let mut instructions = vec![system_instruction::create_account(
+            payer,
+            mint,
+            lamports,
+            self.account_len()? as u64,
+            &self.token_program_id,
+        )];
+
+        if let Some(transfer_fee) = &self.transfer_fee {
+            if transfer_fee.basis_points > MAX_TRANSFER_FEE_BASIS_POINTS {
+                return Err(diagnosed_error!(
+                    "transfer fee of {} basis points exceeds the maximum of {}",
+                    transfer_fee.basis_points,
+                    MAX_TRANSFER_FEE_BASIS_POINTS
+                ));
+            }
+            instructions.push(
+                transfer_fee::instruction::initialize_transfer_fee_config(
+                    &self.token_program_id,
+                    mint,
+                    transfer_fee.config_authority.as_ref(),
+                    transfer_fee.withdraw_authority.as_ref(),
+                    transfer_fee.basis_points,
+                    transfer_fee.maximum_fee,
+                )
+                .map_err(|e| diagnosed_error!("failed to create transfer fee instruction: {e}"))?,
+            );
+        }
+
+        if let Some(metadata_pointer) = &self.metadata_pointer {
+            instructions.push(
+                metadata_pointer::instruction::initialize(
+                    &self.token_program_id,
+                    mint,
+                    metadata_pointer.authority,
+                    metadata_pointer.metadata_address,
+                )
+                .map_err(|e| {
+                    diagnosed_error!("failed to create metadata pointer instruction: {e}")
+                })?,
+            );
+        }
+
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(
+                &self.token_program_id,
+                mint,
+                &self.mint_authority,
+                self.freeze_authority.as_ref(),
+                self.decimals,
+            )
+            .map_err(|e| diagnosed_error!("failed to create initialize mint instruction: {e}"))?,
+        );
+
+        Ok(instructions)
+    }
+}
+
+/// Parses an authority type, as named in runbooks, checking it is supported by the token program.
+pub fn parse_authority_type(
+    authority_type: &str,
+    token_program_id: &Pubkey,
+) -> Result<AuthorityType, Diagnostic> {
+    let (authority_type, is_token_2022_only) = match authority_type {
+        "mint_tokens" => (AuthorityType::MintTokens, false),
+        "freeze_account" => (AuthorityType::FreezeAccount, false),
+        "account_owner" => (AuthorityType::AccountOwner, false),
+        "close_account" => (AuthorityType::CloseAccount, false),
+        "transfer_fee_config" => (AuthorityType::TransferFeeConfig, true),
+        "withheld_withdraw" => (AuthorityType::WithheldWithdraw, true),
+        "close_mint" => (AuthorityType::CloseMint, true),
+        "permanent_delegate" => (AuthorityType::PermanentDelegate, true),
+        "metadata_pointer" => (AuthorityType::MetadataPointer, true),
+        other => return Err(diagnosed_error!("unsupported authority type '{}'", other)),
+    };
+    if is_token_2022_only && token_program_id != &spl_token_2022::id() {
+        return Err(diagnosed_error!(
+            "authority type {:?} is only supported by the Token-2022 program",
+            authority_type
+        ));
+    }
+    Ok(authority_type)
+}
+
+/// Fetches an account owned by a token program, failing if the account does not exist
+/// or is not owned by a token program.
+pub fn get_token_program_account(
+    rpc_client: &RpcClient,
+    address: &Pubkey,
+) -> Result<Account, Diagnostic> {
+    let account = rpc_client
+        .get_account(address)
+        .map_err(|e| diagnosed_error!("failed to fetch account {}: {}", address, e))?;
+    if account.owner != spl_token::id() && account.owner != spl_token_2022::id() {
+        return Err(diagnosed_error!(
+            "account {} is owned by {}, which is not a token program",
+            address,
+            account.owner
+        ));
+    }
+    Ok(account)
+}
+
+/// Fetches a mint, returning the id of its token program and its decimals.
+pub fn get_mint_program_and_decimals(
+    rpc_client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<(Pubkey, u8), Diagnostic> {
+    let account = get_token_program_account(rpc_client, mint)?;
+    let decimals = StateWithExtensions::<Mint>::unpack(&account.data)
+        .map_err(|e| diagnosed_error!("account {} is not a token mint: {}", mint, e))?
+        .base
+        .decimals;
+    Ok((account.owner, decimals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::program_pack::Pack;
+    use solana_sdk::system_program;
+
+    fn mint_config(token_program_id: Pubkey) -> MintConfig {
+        MintConfig {
+            token_program_id,
+            decimals: 6,
+            mint_authority: Pubkey::new_unique(),
+            freeze_authority: None,
+            transfer_fee: None,
+            metadata_pointer: None,
+        }
+    }
+
+    #[test]
+    fn it_builds_spl_token_mint_instructions() {
+        let config = mint_config(spl_token::id());
+        assert_eq!(config.account_len().unwrap(), spl_token::state::Mint::LEN);
+
+        let instructions = config
+            .create_mint_instructions(&Pubkey::new_unique(), &Pubkey::new_unique(), 1)
+            .unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].program_id, system_program::id());
+        assert_eq!(instructions[1].program_id, spl_token::id());
+    }
+
+    #[test]
+    fn it_initializes_extensions_before_the_mint() {
+        let mut config = mint_config(spl_token_2022::id());
+        config.transfer_fee = Some(TransferFeeConfig {
+            basis_points: 50,
+            maximum_fee: 5_000,
+            config_authority: Some(config.mint_authority),
+            withdraw_authority: None,
+        });
+        config.metadata_pointer =
+            Some(MetadataPointerConfig { authority: None, metadata_address: None });
+        assert!(config.account_len().unwrap() > spl_token::state::Mint::LEN);
+
+        let mint = Pubkey::new_unique();
+        let instructions =
+            config.create_mint_instructions(&Pubkey::new_unique(), &mint, 1).unwrap();
+        assert_eq!(instructions.len(), 4);
+        assert!(instructions[1..].iter().all(|ix| ix.program_id == spl_token_2022::id()));
+        assert_eq!(
+            instructions[3],
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::id(),
+                &mint,
+                &config.mint_authority,
+                None,
+                6
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_rejects_extensions_on_spl_token_mints() {
+        let mut config = mint_config(spl_token::id());
+        config.metadata_pointer =
+            Some(MetadataPointerConfig { authority: None, metadata_address: None });
+        assert!(config
+            .create_mint_instructions(&Pubkey::new_unique(), &Pubkey::new_unique(), 1)
+            .is_err());
+    }
+
+    #[test]
+    fn it_parses_authority_types() {
+        assert_eq!(
+            parse_authority_type("mint_tokens", &spl_token::id()).unwrap(),
+            AuthorityType::MintTokens
+        );
+        assert_eq!(
+            parse_authority_type("transfer_fee_config", &spl_token_2022::id()).unwrap(),
+            AuthorityType::TransferFeeConfig
+        );
+        assert!(parse_authority_type("transfer_fee_config", &spl_token::id()).is_err());
+        assert!(parse_authority_type("unknown", &spl_token_2022::id()).is_err());
+    }
+}
@@ -0,0 +1,509 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::address_lookup_table::state::AddressLookupTable;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{CompiledInstruction, Instruction};
+use solana_sdk::message::{
+    v0, AddressLookupTableAccount, Message, MessageHeader, VersionedMessage,
+};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+use txtx_addon_kit::types::diagnostics::Diagnostic;
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::Value;
+
+use crate::constants::{ADDRESS_LOOKUP_TABLES, COMPUTE_UNIT_LIMIT, COMPUTE_UNIT_PRICE};
+use crate::typing::SvmValue;
+use crate::utils::build_svm_transaction_from_svm_value;
+
+use super::transaction_is_fully_signed;
+
+/// The maximum number of compute units a transaction can request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// The number of compute units allotted to each instruction when no limit is requested.
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+/// The percentile of the recent prioritization fees used when the compute unit price is "auto".
+const AUTO_COMPUTE_UNIT_PRICE_PERCENTILE: usize = 75;
+
+/// A transaction, either a legacy transaction, or a versioned transaction compiled against
+/// address lookup tables.
+///
+/// The enum is untagged so that legacy transactions keep their historical serialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SvmTransaction {
+    Legacy(Transaction),
+    Versioned(VersionedTransaction),
+}
+
+impl SvmTransaction {
+    pub fn from_value(value: &Value) -> Result<Self, Diagnostic> {
+        build_svm_transaction_from_svm_value(value)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Diagnostic> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| diagnosed_error!("could not deserialize transaction: {e}"))
+    }
+
+    pub fn to_value(&self) -> Result<Value, Diagnostic> {
+        match self {
+            SvmTransaction::Legacy(transaction) => SvmValue::transaction(transaction),
+            SvmTransaction::Versioned(_) => {
+                let bytes = serde_json::to_vec(self)
+                    .map_err(|e| diagnosed_error!("failed to serialize transaction: {e}"))?;
+                Ok(SvmValue::transaction_from_bytes(bytes))
+            }
+        }
+    }
+
+    /// Returns the legacy transaction, for the code paths that don't support versioned transactions.
+    pub fn into_legacy(self) -> Result<Transaction, Diagnostic> {
+        match self {
+            SvmTransaction::Legacy(transaction) => Ok(transaction),
+            SvmTransaction::Versioned(_) => Err(diagnosed_error!(
+                "versioned transactions (using address lookup tables) are not supported here"
+            )),
+        }
+    }
+
+    pub fn signatures(&self) -> &Vec<Signature> {
+        match self {
+            SvmTransaction::Legacy(transaction) => &transaction.signatures,
+            SvmTransaction::Versioned(transaction) => &transaction.signatures,
+        }
+    }
+
+    pub fn signatures_mut(&mut self) -> &mut Vec<Signature> {
+        match self {
+            SvmTransaction::Legacy(transaction) => &mut transaction.signatures,
+            SvmTransaction::Versioned(transaction) => &mut transaction.signatures,
+        }
+    }
+
+    pub fn recent_blockhash(&self) -> &Hash {
+        match self {
+            SvmTransaction::Legacy(transaction) => &transaction.message.recent_blockhash,
+            SvmTransaction::Versioned(transaction) => transaction.message.recent_blockhash(),
+        }
+    }
+
+    pub fn set_recent_blockhash(&mut self, blockhash: Hash) {
+        match self {
+            SvmTransaction::Legacy(transaction) => transaction.message.recent_blockhash = blockhash,
+            SvmTransaction::Versioned(transaction) => {
+                transaction.message.set_recent_blockhash(blockhash)
+            }
+        }
+    }
+
+    pub fn header(&self) -> &MessageHeader {
+        match self {
+            SvmTransaction::Legacy(transaction) => &transaction.message.header,
+            SvmTransaction::Versioned(transaction) => transaction.message.header(),
+        }
+    }
+
+    /// The account keys stored in the message, excluding the ones loaded from lookup tables.
+    pub fn static_account_keys(&self) -> &[Pubkey] {
+        match self {
+            SvmTransaction::Legacy(transaction) => &transaction.message.account_keys,
+            SvmTransaction::Versioned(transaction) => transaction.message.static_account_keys(),
+        }
+    }
+
+    pub fn instructions(&self) -> &[CompiledInstruction] {
+        match self {
+            SvmTransaction::Legacy(transaction) => &transaction.message.instructions,
+            SvmTransaction::Versioned(transaction) => transaction.message.instructions(),
+        }
+    }
+
+    /// Signs the transaction with the provided keypairs, leaving the other signatures untouched.
+    pub fn try_partial_sign(&mut self, keypairs: &[Keypair]) -> Result<(), Diagnostic> {
+        match self {
+            SvmTransaction::Legacy(transaction) => {
+                let recent_blockhash = transaction.message.recent_blockhash;
+                transaction
+                    .try_partial_sign(keypairs, recent_blockhash)
+                    .map_err(|e| diagnosed_error!("failed to sign transaction: {e}"))
+            }
+            SvmTransaction::Versioned(transaction) => {
+                let num_required_signatures =
+                    transaction.message.header().num_required_signatures as usize;
+                transaction.signatures.resize(num_required_signatures, Signature::default());
+                let message_data = transaction.message.serialize();
+                for keypair in keypairs.iter() {
+                    let pubkey = keypair.pubkey();
+                    let Some(position) = transaction.message.static_account_keys()
+                        [..num_required_signatures]
+                        .iter()
+                        .position(|key| key.eq(&pubkey))
+                    else {
+                        return Err(diagnosed_error!(
+                            "failed to sign transaction: {} is not a signer of the transaction",
+                            pubkey
+                        ));
+                    };
+                    transaction.signatures[position] = keypair.sign_message(&message_data);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn is_fully_signed(&self) -> bool {
+        match self {
+            SvmTransaction::Legacy(transaction) => transaction_is_fully_signed(transaction),
+            SvmTransaction::Versioned(transaction) => {
+                let expected_signature_count =
+                    transaction.message.header().num_required_signatures as usize;
+                expected_signature_count == transaction.signatures.len()
+                    && transaction.signatures.iter().all(|sig| sig != &Signature::default())
+            }
+        }
+    }
+
+    pub fn verify_and_hash_message(&self) -> Result<Hash, Diagnostic> {
+        match self {
+            SvmTransaction::Legacy(transaction) => transaction.verify_and_hash_message(),
+            SvmTransaction::Versioned(transaction) => transaction.verify_and_hash_message(),
+        }
+        .map_err(|e| diagnosed_error!("failed to verify transaction: {e}"))
+    }
+
+    /// The size of the transaction once serialized for the wire, signatures included.
+    pub fn serialized_size(&self) -> Result<usize, Diagnostic> {
+        match self {
+            SvmTransaction::Legacy(transaction) => bincode::serialized_size(transaction),
+            SvmTransaction::Versioned(transaction) => bincode::serialized_size(transaction),
+        }
+        .map(|size| size as usize)
+        .map_err(|e| diagnosed_error!("failed to compute transaction size: {e}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComputeUnitPrice {
+    /// A price, in micro-lamports per compute unit
+    MicroLamports(u64),
+    /// A price estimated from the recent prioritization fees of the accounts written by the transaction
+    Auto,
+}
+
+/// The compute budget and address lookup tables inputs of the actions building transactions.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionOptions {
+    pub compute_unit_limit: Option<u32>,
+    pub compute_unit_price: Option<ComputeUnitPrice>,
+    pub address_lookup_tables: Vec<Pubkey>,
+}
+
+impl TransactionOptions {
+    pub fn from_args(args: &ValueStore) -> Result<Self, Diagnostic> {
+        let compute_unit_limit = match args.get_value(COMPUTE_UNIT_LIMIT) {
+            None => None,
+            Some(value) => {
+                let limit = value
+                    .as_uint()
+                    .and_then(|limit| limit.ok())
+                    .and_then(|limit| u32::try_from(limit).ok())
+                    .filter(|limit| *limit <= MAX_COMPUTE_UNIT_LIMIT)
+                    .ok_or(diagnosed_error!(
+                        "'{}' must be a positive integer lower or equal to {}",
+                        COMPUTE_UNIT_LIMIT,
+                        MAX_COMPUTE_UNIT_LIMIT
+                    ))?;
+                Some(limit)
+            }
+        };
+
+        let compute_unit_price = match args.get_value(COMPUTE_UNIT_PRICE) {
+            None => None,
+            Some(Value::String(price)) if price.eq("auto") => Some(ComputeUnitPrice::Auto),
+            Some(Value::String(price)) => {
+                Some(ComputeUnitPrice::MicroLamports(price.parse::<u64>().map_err(|_| {
+                    diagnosed_error!(
+                        "'{}' must be a number of micro-lamports, or 'auto'; found '{}'",
+                        COMPUTE_UNIT_PRICE,
+                        price
+                    )
+                })?))
+            }
+            Some(value) => Some(ComputeUnitPrice::MicroLamports(
+                value.as_uint().and_then(|price| price.ok()).ok_or(diagnosed_error!(
+                    "'{}' must be a number of micro-lamports, or 'auto'",
+                    COMPUTE_UNIT_PRICE
+                ))?,
+            )),
+        };
+
+        let address_lookup_tables = match args.get_array(ADDRESS_LOOKUP_TABLES) {
+            None => vec![],
+            Some(addresses) => addresses
+                .iter()
+                .map(|address| match address {
+                    Value::String(address) => Pubkey::from_str(address).map_err(|e| {
+                        diagnosed_error!("invalid address lookup table '{}': {}", address, e)
+                    }),
+                    other => SvmValue::to_pubkey(other)
+                        .map_err(|e| diagnosed_error!("invalid address lookup table: {}", e)),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+
+        Ok(Self { compute_unit_limit, compute_unit_price, address_lookup_tables })
+    }
+
+    /// Fetches the address lookup tables and estimates the compute unit price, so that the
+    /// options can be applied to the transactions writing to `writable_accounts`.
+    pub fn resolve(
+        &self,
+        rpc_client: &RpcClient,
+        writable_accounts: &[Pubkey],
+    ) -> Result<ResolvedTransactionOptions, Diagnostic> {
+        let compute_unit_price = match &self.compute_unit_price {
+            None => None,
+            Some(ComputeUnitPrice::MicroLamports(price)) => Some(*price),
+            Some(ComputeUnitPrice::Auto) => {
+                Some(estimate_compute_unit_price(rpc_client, writable_accounts)?)
+            }
+        };
+
+        let mut lookup_table_accounts = vec![];
+        for key in self.address_lookup_tables.iter() {
+            let account = rpc_client.get_account(key).map_err(|e| {
+                diagnosed_error!("failed to fetch address lookup table {}: {}", key, e)
+            })?;
+            let lookup_table = AddressLookupTable::deserialize(&account.data).map_err(|e| {
+                diagnosed_error!("account {} is not an address lookup table: {}", key, e)
+            })?;
+            lookup_table_accounts.push(AddressLookupTableAccount {
+                key: *key,
+                addresses: lookup_table.addresses.to_vec(),
+            });
+        }
+
+        Ok(ResolvedTransactionOptions {
+            compute_unit_limit: self.compute_unit_limit,
+            compute_unit_price,
+            lookup_table_accounts,
+        })
+    }
+}
+
+/// Estimates a compute unit price from the recent prioritization fees paid by the transactions
+/// writing to the same accounts.
+fn estimate_compute_unit_price(
+    rpc_client: &RpcClient,
+    writable_accounts: &[Pubkey],
+) -> Result<u64, Diagnostic> {
+    let mut fees = rpc_client
+        .get_recent_prioritization_fees(writable_accounts)
+        .map_err(|e| diagnosed_error!("failed to fetch recent prioritization fees: {e}"))?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect::<Vec<_>>();
+    if fees.is_empty() {
+        return Ok(0);
+    }
+    fees.sort_unstable();
+    let index = (fees.len() - 1) * AUTO_COMPUTE_UNIT_PRICE_PERCENTILE / 100;
+    Ok(fees[index])
+}
+
+/// Transaction options ready to be applied to the instructions of a transaction.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedTransactionOptions {
+    pub compute_unit_limit: Option<u32>,
+    /// Compute unit price, in micro-lamports
+    pub compute_unit_price: Option<u64>,
+    pub lookup_table_accounts: Vec<AddressLookupTableAccount>,
+}
+
+impl ResolvedTransactionOptions {
+    pub fn compute_budget_instructions(&self) -> Vec<Instruction> {
+        let mut instructions = vec![];
+        if let Some(limit) = self.compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+        }
+        if let Some(price) = self.compute_unit_price {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        instructions
+    }
+
+    /// The maximum priority fee, in lamports, paid by a transaction with `instructions_count`
+    /// instructions (compute budget instructions excluded).
+    pub fn max_priority_fee(&self, instructions_count: usize) -> u64 {
+        let Some(price) = self.compute_unit_price else {
+            return 0;
+        };
+        let limit = self.compute_unit_limit.unwrap_or(
+            (DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT * instructions_count as u32)
+                .min(MAX_COMPUTE_UNIT_LIMIT),
+        );
+        (price as u128 * limit as u128).div_ceil(1_000_000) as u64
+    }
+
+    /// Builds an unsigned transaction from the instructions, prepending the compute budget
+    /// instructions. A versioned transaction is built when lookup tables are provided, and a
+    /// legacy one otherwise. If `payer` is `None`, the first signer of the instructions pays.
+    pub fn build_transaction(
+        &self,
+        instructions: &[Instruction],
+        payer: Option<&Pubkey>,
+        blockhash: &Hash,
+    ) -> Result<SvmTransaction, Diagnostic> {
+        let mut all_instructions = self.compute_budget_instructions();
+        all_instructions.extend_from_slice(instructions);
+
+        let message = Message::new_with_blockhash(&all_instructions, payer, blockhash);
+        if self.lookup_table_accounts.is_empty() {
+            return Ok(SvmTransaction::Legacy(Transaction::new_unsigned(message)));
+        }
+
+        let payer = message
+            .account_keys
+            .first()
+            .ok_or(diagnosed_error!("unable to build a transaction without fee payer"))?;
+        let message = v0::Message::try_compile(
+            payer,
+            &all_instructions,
+            &self.lookup_table_accounts,
+            *blockhash,
+        )
+        .map_err(|e| diagnosed_error!("failed to compile versioned transaction: {e}"))?;
+        Ok(SvmTransaction::Versioned(VersionedTransaction {
+            signatures: vec![Signature::default(); message.header.num_required_signatures as usize],
+            message: VersionedMessage::V0(message),
+        }))
+    }
+}
+
+/// Builds the unsigned transaction of an action from its instructions, applying the
+/// `compute_unit_limit`, `compute_unit_price` and `address_lookup_tables` inputs of the action.
+pub fn build_action_transaction(
+    args: &ValueStore,
+    rpc_client: &RpcClient,
+    instructions: &[Instruction],
+    payer: Option<&Pubkey>,
+) -> Result<SvmTransaction, Diagnostic> {
+    let options = TransactionOptions::from_args(args)?
+        .resolve(rpc_client, &get_writable_accounts(instructions))?;
+    let blockhash = rpc_client
+        .get_latest_blockhash()
+        .map_err(|e| diagnosed_error!("failed to get latest blockhash: {e}"))?;
+    options.build_transaction(instructions, payer, &blockhash)
+}
+
+/// Lists the accounts written by the instructions, used to estimate the compute unit price.
+pub fn get_writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = vec![];
+    for meta in instructions.iter().flat_map(|ix| ix.accounts.iter()) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_instruction;
+
+    fn transfer_instruction(from: &Pubkey, to: &Pubkey) -> Instruction {
+        system_instruction::transfer(from, to, 1_000)
+    }
+
+    #[test]
+    fn it_builds_legacy_transactions_with_compute_budget() {
+        let payer = Keypair::new();
+        let options = ResolvedTransactionOptions {
+            compute_unit_limit: Some(50_000),
+            compute_unit_price: Some(1_000),
+            lookup_table_accounts: vec![],
+        };
+        let instruction = transfer_instruction(&payer.pubkey(), &Pubkey::new_unique());
+        let mut transaction =
+            options.build_transaction(&[instruction], None, &Hash::new_unique()).unwrap();
+
+        let SvmTransaction::Legacy(ref legacy) = transaction else {
+            panic!("expected a legacy transaction");
+        };
+        assert_eq!(legacy.message.instructions.len(), 3);
+        assert_eq!(options.max_priority_fee(1), 50);
+
+        transaction.try_partial_sign(&[payer]).unwrap();
+        assert!(transaction.is_fully_signed());
+        transaction.verify_and_hash_message().unwrap();
+    }
+
+    #[test]
+    fn it_builds_versioned_transactions_with_lookup_tables() {
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let options = ResolvedTransactionOptions {
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            lookup_table_accounts: vec![AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![recipient],
+            }],
+        };
+        let instruction = transfer_instruction(&payer.pubkey(), &recipient);
+        let mut transaction =
+            options.build_transaction(&[instruction], None, &Hash::new_unique()).unwrap();
+
+        let SvmTransaction::Versioned(ref versioned) = transaction else {
+            panic!("expected a versioned transaction");
+        };
+        assert_eq!(versioned.message.address_table_lookups().map(|l| l.len()), Some(1));
+        assert!(!transaction.static_account_keys().contains(&recipient));
+        assert!(!transaction.is_fully_signed());
+
+        transaction.try_partial_sign(&[payer]).unwrap();
+        assert!(transaction.is_fully_signed());
+        transaction.verify_and_hash_message().unwrap();
+
+        // round trip through the addon value
+        let value = transaction.to_value().unwrap();
+        let decoded = SvmTransaction::from_value(&value).unwrap();
+        assert!(matches!(decoded, SvmTransaction::Versioned(_)));
+        decoded.verify_and_hash_message().unwrap();
+    }
+
+    #[test]
+    fn it_keeps_the_legacy_serialization() {
+        let payer = Keypair::new();
+        let instruction = transfer_instruction(&payer.pubkey(), &Pubkey::new_unique());
+        let transaction =
+            Transaction::new_unsigned(Message::new(&[instruction], Some(&payer.pubkey())));
+        let value = SvmValue::transaction(&transaction).unwrap();
+        let decoded = SvmTransaction::from_value(&value).unwrap();
+        assert!(matches!(decoded, SvmTransaction::Legacy(_)));
+        assert_eq!(decoded.to_value().unwrap().to_bytes(), value.to_bytes());
+    }
+
+    #[test]
+    fn it_rejects_signers_foreign_to_the_transaction() {
+        let payer = Keypair::new();
+        let options = ResolvedTransactionOptions {
+            lookup_table_accounts: vec![AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![],
+            }],
+            ..Default::default()
+        };
+        let instruction = transfer_instruction(&payer.pubkey(), &Pubkey::new_unique());
+        let mut transaction =
+            options.build_transaction(&[instruction], None, &Hash::new_unique()).unwrap();
+        assert!(transaction.try_partial_sign(&[Keypair::new()]).is_err());
+    }
+}
@@ -0,0 +1,527 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use txtx_addon_kit::channel;
+use txtx_addon_kit::constants::SIGNED_TRANSACTION_BYTES;
+use txtx_addon_kit::indexmap::IndexMap;
+use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
+use txtx_addon_kit::types::commands::{
+    CommandExecutionFutureResult, CommandImplementation, CommandSpecification,
+    PreCommandSpecification,
+};
+use txtx_addon_kit::types::diagnostics::Diagnostic;
+use txtx_addon_kit::types::frontend::{BlockEvent, StatusUpdater};
+use txtx_addon_kit::types::signers::{
+    SignerActionsFutureResult, SignerInstance, SignerSignFutureResult, SignersState,
+};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::{RunbookSupervisionContext, Type, Value};
+use txtx_addon_kit::types::ConstructDid;
+use txtx_addon_kit::uuid::Uuid;
+
+use crate::codec::send_transaction::send_transaction_background_task;
+use crate::codec::token::{MetadataPointerConfig, MintConfig, TransferFeeConfig};
+use crate::codec::transaction::{build_action_transaction, SvmTransaction};
+use crate::constants::{
+    AUTHORITY, BASIS_POINTS, CHECKED_PUBLIC_KEY, CONFIG_AUTHORITY, DECIMALS, FREEZE_AUTHORITY,
+    MAXIMUM_FEE, METADATA_ADDRESS, METADATA_POINTER, MINT, MINT_AUTHORITY, RPC_API_URL,
+    TOKEN_MINT_ADDRESS, TOKEN_PROGRAM, TOKEN_PROGRAM_ID, TRANSACTION_BYTES, TRANSFER_FEE,
+    WITHDRAW_AUTHORITY,
+};
+use crate::typing::{SvmValue, METADATA_POINTER_MAP, SVM_PUBKEY, TRANSFER_FEE_MAP};
+
+use super::get_signers_did;
+use super::setup_surfnet::TokenProgram;
+use super::sign_transaction::SignTransaction;
+
+/// The number of decimals of a mint when the `decimals` input is omitted.
+const DEFAULT_MINT_DECIMALS: u8 = 9;
+
+lazy_static! {
+    pub static ref CREATE_TOKEN_MINT: PreCommandSpecification = define_command! {
+        CreateTokenMint => {
+            name: "Create Token Mint",
+            matcher: "create_token_mint",
+            documentation: "The `svm::create_token_mint` action encodes a transaction which creates and initializes a token mint account, signs it, and broadcasts it to the network. Mints owned by the Token-2022 program can be configured with the transfer fee and metadata pointer extensions.",
+            implements_signing_capability: true,
+            implements_background_task_capability: true,
+            inputs: [
+                description: {
+                    documentation: "A description of the transaction.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                mint: {
+                    documentation: "The address of the mint account to create. The signer for this address must be listed in `signers`.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                decimals: {
+                    documentation: "The number of decimals of the token. The default is 9.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                token_program: {
+                    documentation: "The token program owning the mint. Valid values are `token2020` and `token2022`. The default is `token2020`.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                mint_authority: {
+                    documentation: "The pubkey of the account allowed to mint tokens. If omitted, the first signer will be used.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                freeze_authority: {
+                    documentation: "The pubkey of the account allowed to freeze token accounts. If omitted, token accounts cannot be frozen.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                transfer_fee: {
+                    documentation: "The configuration of the transfer fee extension. Only supported by the `token2022` program.",
+                    typing: TRANSFER_FEE_MAP.clone(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                metadata_pointer: {
+                    documentation: "The configuration of the metadata pointer extension. Only supported by the `token2022` program.",
+                    typing: METADATA_POINTER_MAP.clone(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                signers: {
+                    documentation: "A set of references to signer constructs, which will be used to sign the transaction. The first signer pays for the mint account.",
+                    typing: Type::array(Type::string()),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                commitment_level: {
+                    documentation: "The commitment level expected for considering this action as done ('processed', 'confirmed', 'finalized'). The default is 'confirmed'.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                compute_unit_limit: {
+                    documentation: "The maximum number of compute units the transaction can consume. When omitted, the cluster default applies.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                compute_unit_price: {
+                    documentation: "The priority fee of the transaction, in micro-lamports per compute unit. Set to 'auto' to estimate the price from the recent prioritization fees of the accounts written by the transaction.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                address_lookup_tables: {
+                    documentation: "The addresses of the address lookup tables to use. When provided, a versioned (v0) transaction is built.",
+                    typing: Type::array(Type::string()),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_url: {
+                    documentation: "The URL to use when making API requests.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_auth_token: {
+                    documentation: "The HTTP authentication token to include in the headers when making API requests.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: true
+                }
+            ],
+            outputs: [
+                signature: {
+                    documentation: "The transaction computed signature.",
+                    typing: Type::string()
+                },
+                token_mint_address: {
+                    documentation: "The token mint address.",
+                    typing: Type::addon(SVM_PUBKEY)
+                },
+                token_program_id: {
+                    documentation: "The address of the token program owning the mint.",
+                    typing: Type::addon(SVM_PUBKEY)
+                }
+            ],
+            example: txtx_addon_kit::indoc! {
+                r#"action "usdx" "svm::create_token_mint" {
+                    description = "Create the USDX mint"
+                    mint = signer.usdx_mint.address
+                    decimals = 6
+                    token_program = "token2022"
+                    transfer_fee {
+                        basis_points = 50
+                        maximum_fee = 5000000
+                        config_authority = signer.admin.address
+                    }
+                    metadata_pointer {
+                        authority = signer.admin.address
+                    }
+                    signers = [signer.admin, signer.usdx_mint]
+                }"#
+            },
+      }
+    };
+}
+
+pub struct CreateTokenMint;
+impl CommandImplementation for CreateTokenMint {
+    fn check_instantiability(
+        _ctx: &CommandSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn check_signed_executability(
+        construct_did: &ConstructDid,
+        instance_name: &str,
+        spec: &CommandSpecification,
+        args: &ValueStore,
+        supervision_context: &RunbookSupervisionContext,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        mut signers: SignersState,
+    ) -> SignerActionsFutureResult {
+        let signers_did = get_signers_did(args).unwrap();
+        let signers_states = signers_did
+            .iter()
+            .map(|did| signers.get_signer_state(did).unwrap().clone())
+            .collect::<Vec<_>>();
+        let mut signer_state = signers.pop_signer_state(signers_did.first().unwrap()).unwrap();
+
+        let mint = Pubkey::from_str(
+            args.get_expected_string(MINT)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?,
+        )
+        .map_err(|e| {
+            (
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!("invalid mint pubkey: {}", e.to_string()),
+            )
+        })?;
+
+        let rpc_api_url = args
+            .get_expected_string(RPC_API_URL)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .to_string();
+
+        let mut signer_pubkeys = vec![];
+        for signer_state in signers_states.iter() {
+            let signer_pubkey = signer_state
+                .get_expected_string(CHECKED_PUBLIC_KEY)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+            let signer_pubkey = Pubkey::from_str(signer_pubkey).map_err(|e| {
+                (
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!("invalid signer pubkey: {}", e.to_string()),
+                )
+            })?;
+            signer_pubkeys.push(signer_pubkey);
+        }
+
+        // the mint account is created by the transaction, so it has to sign it
+        if !signer_pubkeys.contains(&mint) {
+            return Err((
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!(
+                    "the signer for mint {} must be listed in the 'signers' of the action",
+                    mint
+                ),
+            ));
+        }
+        let payer_pubkey = signer_pubkeys[0];
+        if payer_pubkey == mint {
+            return Err((
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!(
+                    "the first signer pays for the mint account, and cannot be the mint itself"
+                ),
+            ));
+        }
+
+        let mint_config = get_mint_config(args, &mint, &payer_pubkey)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+        let client = RpcClient::new(rpc_api_url);
+
+        let instructions = mint_config
+            .account_len()
+            .and_then(|account_len| {
+                client
+                    .get_minimum_balance_for_rent_exemption(account_len)
+                    .map_err(|e| diagnosed_error!("failed to get mint rent exemption: {e}"))
+            })
+            .and_then(|lamports| {
+                mint_config.create_mint_instructions(&payer_pubkey, &mint, lamports)
+            })
+            .map_err(|diag| (signers.clone(), signer_state.clone(), diag))?;
+
+        let transaction =
+            build_action_transaction(args, &client, &instructions, Some(&payer_pubkey))
+                .and_then(|transaction| transaction.to_value())
+                .map_err(|diag| (signers.clone(), signer_state.clone(), diag))?;
+
+        let mut args = args.clone();
+        args.insert(TRANSACTION_BYTES, transaction);
+
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            TOKEN_MINT_ADDRESS,
+            SvmValue::pubkey(mint.to_bytes().to_vec()),
+        );
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            TOKEN_PROGRAM_ID,
+            SvmValue::pubkey(mint_config.token_program_id.to_bytes().to_vec()),
+        );
+
+        signers.push_signer_state(signer_state);
+        SignTransaction::check_signed_executability(
+            construct_did,
+            instance_name,
+            spec,
+            &args,
+            supervision_context,
+            signers_instances,
+            signers,
+        )
+    }
+
+    fn run_signed_execution(
+        construct_did: &ConstructDid,
+        spec: &CommandSpecification,
+        args: &ValueStore,
+        progress_tx: &channel::Sender<BlockEvent>,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        signers: SignersState,
+    ) -> SignerSignFutureResult {
+        let progress_tx = progress_tx.clone();
+        let signers_instances = signers_instances.clone();
+        let construct_did = construct_did.clone();
+        let spec = spec.clone();
+
+        let mut args = args.clone();
+        let future = async move {
+            let run_signing_future = SignTransaction::run_signed_execution(
+                &construct_did,
+                &spec,
+                &args,
+                &progress_tx,
+                &signers_instances,
+                signers,
+            );
+            let (signers, signer_state, mut res_signing) = match run_signing_future {
+                Ok(future) => match future.await {
+                    Ok(res) => res,
+                    Err(err) => return Err(err),
+                },
+                Err(err) => return Err(err),
+            };
+
+            for key in [TOKEN_MINT_ADDRESS, TOKEN_PROGRAM_ID] {
+                let value = signer_state.get_scoped_value(&construct_did.to_string(), key).unwrap();
+                res_signing.outputs.insert(key.into(), value.clone());
+            }
+
+            // signers executing the transaction themselves don't return the signed transaction
+            let Some(transaction_bytes_value) = res_signing.outputs.get(SIGNED_TRANSACTION_BYTES)
+            else {
+                return Ok((signers, signer_state, res_signing));
+            };
+            args.insert(SIGNED_TRANSACTION_BYTES, transaction_bytes_value.clone());
+            let transaction = SvmTransaction::from_value(transaction_bytes_value)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+            let _ = transaction
+                .verify_and_hash_message()
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+            Ok((signers, signer_state, res_signing))
+        };
+        Ok(Box::pin(future))
+    }
+
+    fn build_background_task(
+        construct_did: &ConstructDid,
+        spec: &CommandSpecification,
+        values: &ValueStore,
+        outputs: &ValueStore,
+        progress_tx: &channel::Sender<BlockEvent>,
+        background_tasks_uuid: &Uuid,
+        supervision_context: &RunbookSupervisionContext,
+        _cloud_service_context: &Option<CloudServiceContext>,
+    ) -> CommandExecutionFutureResult {
+        let mut status_updater =
+            StatusUpdater::new(&background_tasks_uuid, &construct_did, &progress_tx);
+        let token_mint_address =
+            SvmValue::to_pubkey(outputs.get_expected_value(TOKEN_MINT_ADDRESS).unwrap()).unwrap();
+        let token_program_id =
+            SvmValue::to_pubkey(outputs.get_expected_value(TOKEN_PROGRAM_ID).unwrap()).unwrap();
+
+        status_updater.propagate_info(&format!(
+            "Creating token mint {} owned by program {}",
+            token_mint_address, token_program_id
+        ));
+
+        send_transaction_background_task(
+            &construct_did,
+            &spec,
+            &values,
+            &outputs,
+            &progress_tx,
+            &background_tasks_uuid,
+            &supervision_context,
+        )
+    }
+}
+
+fn get_mint_config(
+    args: &ValueStore,
+    mint: &Pubkey,
+    payer_pubkey: &Pubkey,
+) -> Result<MintConfig, Diagnostic> {
+    let token_program_id = match args.get_value(TOKEN_PROGRAM) {
+        Some(value) => match TokenProgram::from_value(value.clone())? {
+            TokenProgram::Custom(pubkey) => {
+                return Err(diagnosed_error!(
+                    "invalid token program {}: expected 'token2020' or 'token2022'",
+                    pubkey
+                ))
+            }
+            token_program => token_program.pubkey(),
+        },
+        None => spl_token::id(),
+    };
+
+    let decimals = match args.get_uint(DECIMALS).map_err(|e| diagnosed_error!("{e}"))? {
+        Some(decimals) => u8::try_from(decimals)
+            .map_err(|_| diagnosed_error!("invalid decimals {}: expected at most 255", decimals))?,
+        None => DEFAULT_MINT_DECIMALS,
+    };
+
+    // if the user has specified the mint authority pubkey, use it, otherwise use the first signer
+    let mint_authority = match args.get_value(MINT_AUTHORITY) {
+        Some(value) => parse_pubkey(value, MINT_AUTHORITY)?,
+        None => *payer_pubkey,
+    };
+    let freeze_authority =
+        args.get_value(FREEZE_AUTHORITY).map(|v| parse_pubkey(v, FREEZE_AUTHORITY)).transpose()?;
+
+    let transfer_fee = get_block(args, TRANSFER_FEE)?
+        .map(|block| {
+            let basis_points = get_block_uint(block, TRANSFER_FEE, BASIS_POINTS)?;
+            let basis_points = u16::try_from(basis_points).map_err(|_| {
+                diagnosed_error!("invalid '{}.{}': {}", TRANSFER_FEE, BASIS_POINTS, basis_points)
+            })?;
+            Ok::<_, Diagnostic>(TransferFeeConfig {
+                basis_points,
+                maximum_fee: get_block_uint(block, TRANSFER_FEE, MAXIMUM_FEE)?,
+                config_authority: get_block_pubkey(block, TRANSFER_FEE, CONFIG_AUTHORITY)?,
+                withdraw_authority: get_block_pubkey(block, TRANSFER_FEE, WITHDRAW_AUTHORITY)?,
+            })
+        })
+        .transpose()?;
+
+    let metadata_pointer = get_block(args, METADATA_POINTER)?
+        .map(|block| {
+            Ok::<_, Diagnostic>(MetadataPointerConfig {
+                authority: get_block_pubkey(block, METADATA_POINTER, AUTHORITY)?,
+                // the metadata is stored in the mint itself unless specified otherwise
+                metadata_address: get_block_pubkey(block, METADATA_POINTER, METADATA_ADDRESS)?
+                    .or(Some(*mint)),
+            })
+        })
+        .transpose()?;
+
+    Ok(MintConfig {
+        token_program_id,
+        decimals,
+        mint_authority,
+        freeze_authority,
+        transfer_fee,
+        metadata_pointer,
+    })
+}
+
+fn parse_pubkey(value: &Value, name: &str) -> Result<Pubkey, Diagnostic> {
+    SvmValue::to_pubkey(value).map_err(|e| diagnosed_error!("invalid '{name}': {e}"))
+}
+
+fn get_block<'a>(
+    args: &'a ValueStore,
+    key: &str,
+) -> Result<Option<&'a IndexMap<String, Value>>, Diagnostic> {
+    let Some(blocks) = args.get_map(key) else {
+        return Ok(None);
+    };
+    match blocks.as_slice() {
+        [] => Ok(None),
+        [block] => {
+            block.as_object().map(Some).ok_or(diagnosed_error!("'{}' must be a map type", key))
+        }
+        _ => Err(diagnosed_error!("only one '{}' block can be provided", key)),
+    }
+}
+
+fn get_block_uint(
+    block: &IndexMap<String, Value>,
+    block_key: &str,
+    key: &str,
+) -> Result<u64, Diagnostic> {
+    block.get(key).and_then(|v| v.as_uint()).and_then(|v| v.ok()).ok_or(diagnosed_error!(
+        "expected a positive integer '{}.{}'",
+        block_key,
+        key
+    ))
+}
+
+fn get_block_pubkey(
+    block: &IndexMap<String, Value>,
+    block_key: &str,
+    key: &str,
+) -> Result<Option<Pubkey>, Diagnostic> {
+    block.get(key).map(|v| parse_pubkey(v, &format!("{block_key}.{key}"))).transpose()
+}
@@ -3,8 +3,10 @@ use std::str::FromStr;
 use std::vec;
 
 use solana_client::rpc_client::RpcClient;
+use solana_sdk::bpf_loader_upgradeable::get_program_data_address;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
 use txtx_addon_kit::channel;
 use txtx_addon_kit::constants::{
     DESCRIPTION, NESTED_CONSTRUCT_COUNT, NESTED_CONSTRUCT_DID, NESTED_CONSTRUCT_INDEX,
@@ -28,6 +30,7 @@ use txtx_addon_kit::types::{ConstructDid, Did};
 use txtx_addon_kit::uuid::Uuid;
 
 use crate::codec::send_transaction::send_transaction_background_task;
+use crate::codec::transaction::TransactionOptions;
 use crate::codec::{DeploymentTransaction, ProgramArtifacts, UpgradeableProgramDeployer};
 use crate::constants::{
     AUTHORITY, AUTO_EXTEND, CHECKED_PUBLIC_KEY, COMMITMENT_LEVEL, DO_AWAIT_CONFIRMATION,
@@ -99,6 +102,30 @@ lazy_static! {
                         tainting: false,
                         internal: false,
                         sensitive: false
+                    },
+                    compute_unit_limit: {
+                        documentation: "The maximum number of compute units each deployment transaction can consume. When omitted, the cluster default applies.",
+                        typing: Type::integer(),
+                        optional: true,
+                        tainting: false,
+                        internal: false,
+                        sensitive: false
+                    },
+                    compute_unit_price: {
+                        documentation: "The priority fee of each deployment transaction, in micro-lamports per compute unit. Set to 'auto' to estimate the price from the recent prioritization fees of the program accounts.",
+                        typing: Type::integer(),
+                        optional: true,
+                        tainting: false,
+                        internal: false,
+                        sensitive: false
+                    },
+                    address_lookup_tables: {
+                        documentation: "The addresses of the address lookup tables to use. When provided, the deployment transactions are built as versioned (v0) transactions.",
+                        typing: Type::array(Type::string()),
+                        optional: true,
+                        tainting: false,
+                        internal: false,
+                        sensitive: false
                     }
                 ],
                 outputs: [
@@ -262,14 +289,31 @@ impl CommandImplementation for DeployProgram {
                     }
                 };
 
-                let mut deployer = UpgradeableProgramDeployer::new(
-                    program_artifacts.keypair().map_err(|e| {
-                        (
-                            signers.clone(),
-                            authority_signer_state.clone(),
-                            diagnosed_error!("failed to get program keypair: {}", e),
+                let program_keypair = program_artifacts.keypair().map_err(|e| {
+                    (
+                        signers.clone(),
+                        authority_signer_state.clone(),
+                        diagnosed_error!("failed to get program keypair: {}", e),
+                    )
+                })?;
+
+                // priority fees are estimated from the activity on the program accounts
+                let program_pubkey = program_keypair.pubkey();
+                let transaction_options = TransactionOptions::from_args(values)
+                    .and_then(|options| {
+                        options.resolve(
+                            &rpc_client,
+                            &[
+                                program_pubkey,
+                                get_program_data_address(&program_pubkey),
+                                payer_pubkey,
+                            ],
                         )
-                    })?,
+                    })
+                    .map_err(|e| (signers.clone(), authority_signer_state.clone(), e))?;
+
+                let mut deployer = UpgradeableProgramDeployer::new(
+                    program_keypair,
                     &authority_pubkey,
                     temp_authority_keypair,
                     &program_artifacts.bin(),
@@ -277,6 +321,7 @@ impl CommandImplementation for DeployProgram {
                     rpc_client,
                     None,
                     auto_extend,
+                    transaction_options,
                 )
                 .map_err(|e| {
                     (
@@ -425,14 +470,13 @@ impl CommandImplementation for DeployProgram {
         // we only need to check signability if there are signers for this transaction
         if let Some(signers_dids) = signers_dids {
             let mut values = values.clone();
-            let transaction_value = SvmValue::transaction(&deployment_transaction.transaction)
-                .map_err(|e| {
-                    (
-                        signers.clone(),
-                        authority_signer_state.clone(),
-                        diagnosed_error!("failed to serialize deployment transaction: {}", e),
-                    )
-                })?;
+            let transaction_value = deployment_transaction.transaction.to_value().map_err(|e| {
+                (
+                    signers.clone(),
+                    authority_signer_state.clone(),
+                    diagnosed_error!("failed to serialize deployment transaction: {}", e),
+                )
+            })?;
             values.insert(TRANSACTION_BYTES, transaction_value);
             values.insert(IS_DEPLOYMENT, Value::bool(true));
             values.insert(
@@ -541,7 +585,7 @@ impl CommandImplementation for DeployProgram {
                             diagnosed_error!("failed to sign transaction: {}", e),
                         )
                     })?;
-                let transaction_value = SvmValue::transaction(&transaction).map_err(|e| {
+                let transaction_value = transaction.to_value().map_err(|e| {
                     (
                         signers.clone(),
                         authority_signer_state.clone(),
@@ -575,14 +619,17 @@ impl CommandImplementation for DeployProgram {
                 Err(err) => return Err(err),
             };
 
-            let signed_transaction_value =
-                signin_res.outputs.remove(SIGNED_TRANSACTION_BYTES).unwrap();
+            // signers executing the transaction themselves (e.g. `svm::squads`)
+            // don't return the signed transaction
+            let signed_transaction_value = signin_res.outputs.remove(SIGNED_TRANSACTION_BYTES);
             result.append(&mut signin_res);
 
-            result.outputs.insert(
-                format!("{}:{}", &nested_construct_did.to_string(), SIGNED_TRANSACTION_BYTES),
-                signed_transaction_value,
-            );
+            if let Some(signed_transaction_value) = signed_transaction_value {
+                result.outputs.insert(
+                    format!("{}:{}", &nested_construct_did.to_string(), SIGNED_TRANSACTION_BYTES),
+                    signed_transaction_value,
+                );
+            }
 
             return Ok((signers, signer_state, result));
         };
@@ -647,13 +694,14 @@ impl CommandImplementation for DeployProgram {
                 _ => {}
             }
 
-            let signed_transaction_value = inputs
+            if let Some(signed_transaction_value) = inputs
                 .get_scoped_value(&nested_construct_did.to_string(), SIGNED_TRANSACTION_BYTES)
-                .unwrap()
-                .clone();
+                .cloned()
+            {
+                inputs.insert(SIGNED_TRANSACTION_BYTES, signed_transaction_value);
+            }
 
             inputs.insert(IS_DEPLOYMENT, Value::bool(true));
-            inputs.insert(SIGNED_TRANSACTION_BYTES, signed_transaction_value.clone());
             inputs.insert(
                 COMMITMENT_LEVEL,
                 Value::string(deployment_transaction.commitment_level.to_string()),
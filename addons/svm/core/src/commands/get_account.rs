@@ -0,0 +1,262 @@
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::pubkey::Pubkey;
+use txtx_addon_kit::types::commands::{
+    CommandExecutionFutureResult, CommandExecutionResult, CommandImplementation,
+    PreCommandSpecification,
+};
+use txtx_addon_kit::types::frontend::{Actions, BlockEvent};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::{ObjectType, RunbookSupervisionContext};
+use txtx_addon_kit::types::ConstructDid;
+use txtx_addon_kit::types::{
+    commands::CommandSpecification,
+    diagnostics::Diagnostic,
+    types::{Type, Value},
+};
+
+use crate::codec::idl::IdlRef;
+use crate::constants::{
+    ACCOUNT_NAME, ACCOUNT_TYPE, ADDRESS, COMMITMENT_LEVEL, DATA, DECODED, EXECUTABLE, EXISTS,
+    LAMPORTS, OWNER, PROGRAM_IDL, RPC_API_URL,
+};
+use crate::typing::SvmValue;
+
+lazy_static! {
+    pub static ref GET_ACCOUNT: PreCommandSpecification = define_command! {
+        GetAccount => {
+            name: "Get Account",
+            matcher: "get_account",
+            documentation: "The `svm::get_account` action fetches an account from the network. When a program IDL is provided, the account data is decoded into an object whose fields can be used by other constructs.",
+            implements_signing_capability: false,
+            implements_background_task_capability: false,
+            inputs: [
+                description: {
+                    documentation: "A description of the account read.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                address: {
+                    documentation: "The address of the account to fetch.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                program_idl: {
+                    documentation: "The IDL of the program owning the account, used to decode the account data.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                account_name: {
+                    documentation: "The name of the account type in the IDL. When omitted, the account type is inferred from the account discriminator.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                commitment_level: {
+                    documentation: "The commitment level used when fetching the account ('processed', 'confirmed', 'finalized'). The default is 'confirmed'.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_url: {
+                    documentation: "The URL to use when making API requests.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_auth_token: {
+                    documentation: "The HTTP authentication token to include in the headers when making API requests.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: true
+                }
+            ],
+            outputs: [
+                exists: {
+                    documentation: "Whether the account exists.",
+                    typing: Type::bool()
+                },
+                lamports: {
+                    documentation: "The balance of the account, in lamports.",
+                    typing: Type::integer()
+                },
+                owner: {
+                    documentation: "The address of the program owning the account.",
+                    typing: Type::string()
+                },
+                executable: {
+                    documentation: "Whether the account is an executable program.",
+                    typing: Type::bool()
+                },
+                data: {
+                    documentation: "The raw account data.",
+                    typing: Type::buffer()
+                },
+                account_type: {
+                    documentation: "The name of the IDL account type used to decode the account data.",
+                    typing: Type::string()
+                },
+                decoded: {
+                    documentation: "The account data decoded with the program IDL.",
+                    typing: Type::arbitrary_object()
+                }
+            ],
+            example: txtx_addon_kit::indoc! {r#"
+                action "config" "svm::get_account" {
+                    description = "Read the program configuration"
+                    address = variable.config_pda
+                    program_idl = variable.program.idl
+                    account_name = "Config"
+                }
+                output "admin" {
+                    value = action.config.decoded.admin
+                }
+            "#},
+        }
+    };
+}
+
+pub struct GetAccount;
+impl CommandImplementation for GetAccount {
+    fn check_instantiability(
+        _ctx: &CommandSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn check_executability(
+        _construct_id: &ConstructDid,
+        _instance_name: &str,
+        _spec: &CommandSpecification,
+        _values: &ValueStore,
+        _supervision_context: &RunbookSupervisionContext,
+    ) -> Result<Actions, Diagnostic> {
+        Ok(Actions::none())
+    }
+
+    fn run_execution(
+        _construct_id: &ConstructDid,
+        _spec: &CommandSpecification,
+        values: &ValueStore,
+        _progress_tx: &txtx_addon_kit::channel::Sender<BlockEvent>,
+    ) -> CommandExecutionFutureResult {
+        let values = values.clone();
+
+        let future = async move {
+            let address = parse_address(values.get_expected_value(ADDRESS)?, "account address")?;
+            let idl_ref = get_idl_ref(&values)?;
+            let account_name = values.get_string(ACCOUNT_NAME);
+            let rpc_client = get_rpc_client(&values)?;
+
+            let account = rpc_client
+                .get_account_with_commitment(&address, rpc_client.commitment())
+                .map_err(|e| diagnosed_error!("failed to fetch account {}: {}", address, e))?
+                .value;
+
+            let mut result = CommandExecutionResult::new();
+            match account {
+                Some(account) => {
+                    let account = account_to_object(&account, idl_ref.as_ref(), account_name)?;
+                    result.outputs.insert(EXISTS.into(), Value::bool(true));
+                    for (key, value) in account.inner().into_iter() {
+                        result.outputs.insert(key, value);
+                    }
+                }
+                None => {
+                    result.outputs.insert(EXISTS.into(), Value::bool(false));
+                    result.outputs.insert(LAMPORTS.into(), Value::integer(0));
+                    result.outputs.insert(DATA.into(), Value::buffer(vec![]));
+                    result.outputs.insert(DECODED.into(), Value::null());
+                }
+            }
+            Ok(result)
+        };
+
+        Ok(Box::pin(future))
+    }
+}
+
+pub fn get_rpc_client(values: &ValueStore) -> Result<RpcClient, Diagnostic> {
+    let rpc_api_url = values.get_expected_string(RPC_API_URL)?.to_string();
+    let commitment = match values.get_string(COMMITMENT_LEVEL).unwrap_or("confirmed") {
+        "processed" => CommitmentLevel::Processed,
+        "confirmed" => CommitmentLevel::Confirmed,
+        "finalized" => CommitmentLevel::Finalized,
+        other => return Err(diagnosed_error!("invalid commitment level '{}'", other)),
+    };
+    Ok(RpcClient::new_with_commitment(rpc_api_url, CommitmentConfig { commitment }))
+}
+
+pub fn get_idl_ref(values: &ValueStore) -> Result<Option<IdlRef>, Diagnostic> {
+    values
+        .get_string(PROGRAM_IDL)
+        .map(|idl_str| {
+            IdlRef::from_str(idl_str).map_err(|e| diagnosed_error!("invalid program idl: {e}"))
+        })
+        .transpose()
+}
+
+/// Describes an account as an object, decoding its data with the program IDL when provided.
+pub fn account_to_object(
+    account: &Account,
+    idl_ref: Option<&IdlRef>,
+    account_name: Option<&str>,
+) -> Result<ObjectType, Diagnostic> {
+    let mut object = ObjectType::from(vec![
+        (LAMPORTS, Value::integer(account.lamports.into())),
+        (OWNER, Value::string(account.owner.to_string())),
+        (EXECUTABLE, Value::bool(account.executable)),
+        (DATA, Value::buffer(account.data.clone())),
+    ]);
+    match idl_ref {
+        Some(idl_ref) => {
+            let (account_type, decoded) = decode_account(idl_ref, account, account_name)?;
+            object.insert(ACCOUNT_TYPE, Value::string(account_type));
+            object.insert(DECODED, decoded);
+        }
+        None => {
+            object.insert(DECODED, Value::null());
+        }
+    }
+    Ok(object)
+}
+
+fn decode_account(
+    idl_ref: &IdlRef,
+    account: &Account,
+    account_name: Option<&str>,
+) -> Result<(String, Value), Diagnostic> {
+    let program_id = idl_ref.get_program_pubkey()?;
+    if account.owner != program_id {
+        return Err(diagnosed_error!(
+            "unable to decode account: account is owned by {}, not by the IDL program {}",
+            account.owner,
+            program_id
+        ));
+    }
+    idl_ref.decode_account_data(&account.data, account_name)
+}
+
+/// Parses an account address, accepting the same encodings as the other SVM inputs.
+pub fn parse_address(value: &Value, name: &str) -> Result<Pubkey, Diagnostic> {
+    SvmValue::to_pubkey(value).map_err(|e| diagnosed_error!("invalid {name}: {e}"))
+}
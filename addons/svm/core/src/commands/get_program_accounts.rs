@@ -0,0 +1,240 @@
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use txtx_addon_kit::types::commands::{
+    CommandExecutionFutureResult, CommandExecutionResult, CommandImplementation,
+    PreCommandSpecification,
+};
+use txtx_addon_kit::types::frontend::{Actions, BlockEvent};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::RunbookSupervisionContext;
+use txtx_addon_kit::types::ConstructDid;
+use txtx_addon_kit::types::{
+    commands::CommandSpecification,
+    diagnostics::Diagnostic,
+    types::{Type, Value},
+};
+
+use crate::codec::idl::IdlRef;
+use crate::constants::{
+    ACCOUNTS, ACCOUNT_NAME, ADDRESS, BYTES, DATA_SIZE, MEMCMP, OFFSET, PROGRAM_ID,
+};
+use crate::typing::MEMCMP_FILTER_MAP;
+use crate::utils::decode_hex;
+
+use super::get_account::{account_to_object, get_idl_ref, get_rpc_client, parse_address};
+
+lazy_static! {
+    pub static ref GET_PROGRAM_ACCOUNTS: PreCommandSpecification = define_command! {
+        GetProgramAccounts => {
+            name: "Get Program Accounts",
+            matcher: "get_program_accounts",
+            documentation: "The `svm::get_program_accounts` action fetches the accounts owned by a program, optionally filtered by data size and by the content of their data. When a program IDL is provided, the data of each account is decoded into an object.",
+            implements_signing_capability: false,
+            implements_background_task_capability: false,
+            inputs: [
+                description: {
+                    documentation: "A description of the accounts read.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                program_id: {
+                    documentation: "The address of the program owning the accounts.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                program_idl: {
+                    documentation: "The IDL of the program, used to decode the data of the accounts.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                account_name: {
+                    documentation: "The name of an account type in the IDL. When provided, only the accounts of this type are fetched.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                memcmp: {
+                    documentation: "A filter on the account data, keeping the accounts whose data at `offset` matches `bytes`. Offsets are counted from the start of the account data, discriminator included. This block can be repeated.",
+                    typing: MEMCMP_FILTER_MAP.clone(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                data_size: {
+                    documentation: "When provided, only the accounts whose data has this exact size are fetched.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                commitment_level: {
+                    documentation: "The commitment level used when fetching the accounts ('processed', 'confirmed', 'finalized'). The default is 'confirmed'.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_url: {
+                    documentation: "The URL to use when making API requests.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_auth_token: {
+                    documentation: "The HTTP authentication token to include in the headers when making API requests.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: true
+                }
+            ],
+            outputs: [
+                accounts: {
+                    documentation: "The accounts fetched. Each account has the `address`, `lamports`, `owner`, `executable`, `data`, `account_type` and `decoded` fields.",
+                    typing: Type::array(Type::arbitrary_object())
+                }
+            ],
+            example: txtx_addon_kit::indoc! {r#"
+                action "vaults" "svm::get_program_accounts" {
+                    description = "List the vaults of the admin"
+                    program_id = variable.program.program_id
+                    program_idl = variable.program.idl
+                    account_name = "Vault"
+                    memcmp {
+                        offset = 8
+                        bytes = signer.admin.public_key
+                    }
+                }
+            "#},
+        }
+    };
+}
+
+pub struct GetProgramAccounts;
+impl CommandImplementation for GetProgramAccounts {
+    fn check_instantiability(
+        _ctx: &CommandSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn check_executability(
+        _construct_id: &ConstructDid,
+        _instance_name: &str,
+        _spec: &CommandSpecification,
+        _values: &ValueStore,
+        _supervision_context: &RunbookSupervisionContext,
+    ) -> Result<Actions, Diagnostic> {
+        Ok(Actions::none())
+    }
+
+    fn run_execution(
+        _construct_id: &ConstructDid,
+        _spec: &CommandSpecification,
+        values: &ValueStore,
+        _progress_tx: &txtx_addon_kit::channel::Sender<BlockEvent>,
+    ) -> CommandExecutionFutureResult {
+        let values = values.clone();
+
+        let future = async move {
+            let program_id = parse_address(values.get_expected_value(PROGRAM_ID)?, "program id")?;
+            let idl_ref = get_idl_ref(&values)?;
+            let account_name = values.get_string(ACCOUNT_NAME);
+            let filters = get_filters(&values, idl_ref.as_ref(), account_name)?;
+            let rpc_client = get_rpc_client(&values)?;
+
+            let config = RpcProgramAccountsConfig {
+                filters: if filters.is_empty() { None } else { Some(filters) },
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(rpc_client.commitment()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let program_accounts =
+                rpc_client.get_program_accounts_with_config(&program_id, config).map_err(|e| {
+                    diagnosed_error!("failed to fetch accounts of program {}: {}", program_id, e)
+                })?;
+
+            let mut accounts = vec![];
+            for (address, account) in program_accounts.iter() {
+                // without an account name, accounts of types unknown to the IDL are not decoded
+                let idl_ref = idl_ref.as_ref().filter(|idl_ref| {
+                    account_name.is_some() || idl_ref.get_account_for_data(&account.data).is_some()
+                });
+                let mut object = account_to_object(account, idl_ref, account_name)
+                    .map_err(|e| diagnosed_error!("account {}: {}", address, e.message))?;
+                object.insert(ADDRESS, Value::string(address.to_string()));
+                accounts.push(object.to_value());
+            }
+
+            let mut result = CommandExecutionResult::new();
+            result.outputs.insert(ACCOUNTS.into(), Value::array(accounts));
+            Ok(result)
+        };
+
+        Ok(Box::pin(future))
+    }
+}
+
+fn get_filters(
+    values: &ValueStore,
+    idl_ref: Option<&IdlRef>,
+    account_name: Option<&str>,
+) -> Result<Vec<RpcFilterType>, Diagnostic> {
+    let mut filters = vec![];
+
+    if let Some(account_name) = account_name {
+        let Some(idl_ref) = idl_ref else {
+            return Err(diagnosed_error!("'{}' requires a 'program_idl'", ACCOUNT_NAME));
+        };
+        let account = idl_ref.get_account(account_name)?;
+        filters
+            .push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, account.discriminator.clone())));
+    }
+
+    if let Some(data_size) = values.get_uint(DATA_SIZE).map_err(|e| diagnosed_error!("{e}"))? {
+        filters.push(RpcFilterType::DataSize(data_size));
+    }
+
+    let memcmp_filters = values.get_map(MEMCMP).map(|m| m.to_vec()).unwrap_or_default();
+    for (i, filter) in memcmp_filters.iter().enumerate() {
+        let filter = filter
+            .as_object()
+            .ok_or(diagnosed_error!("memcmp filter #{}: must be a map type", i + 1))?;
+        let offset = filter.get(OFFSET).and_then(|o| o.as_uint()).and_then(|o| o.ok()).ok_or(
+            diagnosed_error!("memcmp filter #{}: expected a positive integer 'offset'", i + 1),
+        )?;
+        let bytes = match filter.get(BYTES) {
+            Some(Value::String(s)) if s.starts_with("0x") => decode_hex(s)?,
+            Some(Value::String(s)) => solana_sdk::bs58::decode(s).into_vec().map_err(|e| {
+                diagnosed_error!("memcmp filter #{}: invalid base58 'bytes': {}", i + 1, e)
+            })?,
+            Some(value) => value.to_bytes(),
+            None => {
+                return Err(diagnosed_error!("memcmp filter #{}: missing 'bytes'", i + 1));
+            }
+        };
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset as usize, bytes)));
+    }
+
+    Ok(filters)
+}
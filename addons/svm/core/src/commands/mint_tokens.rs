@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use txtx_addon_kit::channel;
+use txtx_addon_kit::constants::SIGNED_TRANSACTION_BYTES;
+use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
+use txtx_addon_kit::types::commands::{
+    CommandExecutionFutureResult, CommandImplementation, CommandSpecification,
+    PreCommandSpecification,
+};
+use txtx_addon_kit::types::diagnostics::Diagnostic;
+use txtx_addon_kit::types::frontend::{BlockEvent, StatusUpdater};
+use txtx_addon_kit::types::signers::{
+    SignerActionsFutureResult, SignerInstance, SignerSignFutureResult, SignersState,
+};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::{RunbookSupervisionContext, Type, Value};
+use txtx_addon_kit::types::ConstructDid;
+use txtx_addon_kit::uuid::Uuid;
+
+use crate::codec::send_transaction::send_transaction_background_task;
+use crate::codec::token::get_mint_program_and_decimals;
+use crate::codec::transaction::{build_action_transaction, SvmTransaction};
+use crate::constants::{
+    AMOUNT, AUTHORITY_ADDRESS, CHECKED_PUBLIC_KEY, FUND_RECIPIENT, IS_FUNDING_RECIPIENT, MINT,
+    MINT_AUTHORITY, RECIPIENT, RECIPIENT_ADDRESS, RECIPIENT_TOKEN_ADDRESS, RPC_API_URL,
+    TOKEN_MINT_ADDRESS, TRANSACTION_BYTES,
+};
+use crate::typing::{SvmValue, SVM_PUBKEY};
+
+use super::get_signers_did;
+use super::sign_transaction::SignTransaction;
+
+lazy_static! {
+    pub static ref MINT_TOKENS: PreCommandSpecification = define_command! {
+        MintTokens => {
+            name: "Mint Tokens",
+            matcher: "mint_tokens",
+            documentation: "The `svm::mint_tokens` action encodes a transaction which mints new tokens to the associated token account of the recipient, signs it, and broadcasts it to the network. Mints owned by the SPL Token and Token-2022 programs are supported.",
+            implements_signing_capability: true,
+            implements_background_task_capability: true,
+            inputs: [
+                description: {
+                    documentation: "A description of the transaction.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                mint: {
+                    documentation: "The address of the token mint account.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                amount: {
+                    documentation: "The amount of tokens to mint, in base unit.",
+                    typing: Type::integer(),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                recipient: {
+                    documentation: "The SVM address of the recipient. The associated token account will be computed from this address and the mint address.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                fund_recipient: {
+                    documentation: "If set to `true` and the recipient token account does not exist, the action will create the account and fund it, using the first signer to fund the account. The default is `false`.",
+                    typing: Type::bool(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                mint_authority: {
+                    documentation: "The pubkey of the mint authority of the token. If omitted, the first signer will be used.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                signers: {
+                    documentation: "A set of references to signer constructs, which will be used to sign the transaction. The first signer pays for the transaction.",
+                    typing: Type::array(Type::string()),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                commitment_level: {
+                    documentation: "The commitment level expected for considering this action as done ('processed', 'confirmed', 'finalized'). The default is 'confirmed'.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                compute_unit_limit: {
+                    documentation: "The maximum number of compute units the transaction can consume. When omitted, the cluster default applies.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                compute_unit_price: {
+                    documentation: "The priority fee of the transaction, in micro-lamports per compute unit. Set to 'auto' to estimate the price from the recent prioritization fees of the accounts written by the transaction.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                address_lookup_tables: {
+                    documentation: "The addresses of the address lookup tables to use. When provided, a versioned (v0) transaction is built.",
+                    typing: Type::array(Type::string()),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_url: {
+                    documentation: "The URL to use when making API requests.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_auth_token: {
+                    documentation: "The HTTP authentication token to include in the headers when making API requests.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: true
+                }
+            ],
+            outputs: [
+                signature: {
+                    documentation: "The transaction computed signature.",
+                    typing: Type::string()
+                },
+                recipient_token_address: {
+                    documentation: "The recipient token account address.",
+                    typing: Type::addon(SVM_PUBKEY)
+                },
+                token_mint_address: {
+                    documentation: "The token mint address.",
+                    typing: Type::addon(SVM_PUBKEY)
+                }
+            ],
+            example: txtx_addon_kit::indoc! {
+                r#"action "mint_usdx" "svm::mint_tokens" {
+                    description = "Mint 1000 USDX to the treasury"
+                    mint = action.usdx.token_mint_address
+                    amount = 1000000000
+                    recipient = signer.treasury.address
+                    fund_recipient = true
+                    signers = [signer.admin]
+                }"#
+            },
+      }
+    };
+}
+
+pub struct MintTokens;
+impl CommandImplementation for MintTokens {
+    fn check_instantiability(
+        _ctx: &CommandSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn check_signed_executability(
+        construct_did: &ConstructDid,
+        instance_name: &str,
+        spec: &CommandSpecification,
+        args: &ValueStore,
+        supervision_context: &RunbookSupervisionContext,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        mut signers: SignersState,
+    ) -> SignerActionsFutureResult {
+        let signers_did = get_signers_did(args).unwrap();
+        let signers_states = signers_did
+            .iter()
+            .map(|did| signers.get_signer_state(did).unwrap().clone())
+            .collect::<Vec<_>>();
+        let mut signer_state = signers.pop_signer_state(signers_did.first().unwrap()).unwrap();
+
+        let amount = args
+            .get_expected_uint(AMOUNT)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+        let token_mint_address = Pubkey::from_str(
+            args.get_expected_string(MINT)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?,
+        )
+        .map_err(|e| {
+            (
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!("invalid mint pubkey: {}", e.to_string()),
+            )
+        })?;
+
+        let recipient = Pubkey::from_str(
+            args.get_expected_string(RECIPIENT)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?,
+        )
+        .map_err(|e| {
+            (
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!("invalid recipient: {}", e.to_string()),
+            )
+        })?;
+
+        let rpc_api_url = args
+            .get_expected_string(RPC_API_URL)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .to_string();
+
+        let mut signer_pubkeys = vec![];
+        for signer_state in signers_states.iter() {
+            let signer_pubkey = signer_state
+                .get_expected_string(CHECKED_PUBLIC_KEY)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+            let signer_pubkey = Pubkey::from_str(signer_pubkey).map_err(|e| {
+                (
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!("invalid signer pubkey: {}", e.to_string()),
+                )
+            })?;
+            signer_pubkeys.push(signer_pubkey);
+        }
+        let payer_pubkey = signer_pubkeys[0];
+
+        // if the user has specified the mint authority pubkey, use it, otherwise use the first signer
+        let mint_authority_pubkey = if let Some(mint_authority) = args.get_string(MINT_AUTHORITY) {
+            Pubkey::from_str(mint_authority).map_err(|e| {
+                (
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!("invalid mint authority pubkey: {}", e.to_string()),
+                )
+            })?
+        } else {
+            payer_pubkey
+        };
+
+        let client = RpcClient::new(rpc_api_url);
+
+        let (token_program_id, decimals) =
+            get_mint_program_and_decimals(&client, &token_mint_address)
+                .map_err(|diag| (signers.clone(), signer_state.clone(), diag))?;
+
+        let recipient_token_address =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                &recipient,
+                &token_mint_address,
+                &token_program_id,
+            );
+
+        let mut instructions = vec![spl_token_2022::instruction::mint_to_checked(
+            &token_program_id,
+            &token_mint_address,
+            &recipient_token_address,
+            &mint_authority_pubkey,
+            &[],
+            amount,
+            decimals,
+        )
+        .map_err(|e| {
+            (
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!("failed to create mint instruction: {}", e.to_string()),
+            )
+        })?];
+
+        let do_create_account = match client.get_account(&recipient_token_address) {
+            Ok(recipient_account) => recipient_account.lamports == 0,
+            Err(e) => {
+                if e.to_string().contains("AccountNotFound") {
+                    true
+                } else {
+                    return Err((
+                        signers.clone(),
+                        signer_state.clone(),
+                        diagnosed_error!(
+                            "failed to get token recipient account: {}",
+                            e.to_string()
+                        ),
+                    ));
+                }
+            }
+        };
+
+        let mut is_funding_recipient = false;
+        if do_create_account {
+            if args.get_bool(FUND_RECIPIENT).unwrap_or(false) {
+                is_funding_recipient = true;
+                instructions.insert(
+                    0,
+                    spl_associated_token_account::instruction::create_associated_token_account(
+                        &payer_pubkey,
+                        &recipient,
+                        &token_mint_address,
+                        &token_program_id,
+                    ),
+                );
+            } else {
+                return Err(
+                    (
+                        signers.clone(),
+                        signer_state.clone(),
+                        diagnosed_error!("cannot mint tokens because recipient token account does not exist; create the account or use the `fund_recipient = true` option")
+                    )
+                );
+            }
+        }
+
+        let transaction =
+            build_action_transaction(args, &client, &instructions, Some(&payer_pubkey))
+                .and_then(|transaction| transaction.to_value())
+                .map_err(|diag| (signers.clone(), signer_state.clone(), diag))?;
+
+        let mut args = args.clone();
+        args.insert(TRANSACTION_BYTES, transaction);
+
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            RECIPIENT_TOKEN_ADDRESS,
+            SvmValue::pubkey(recipient_token_address.to_bytes().to_vec()),
+        );
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            RECIPIENT_ADDRESS,
+            SvmValue::pubkey(recipient.to_bytes().to_vec()),
+        );
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            AUTHORITY_ADDRESS,
+            SvmValue::pubkey(mint_authority_pubkey.to_bytes().to_vec()),
+        );
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            TOKEN_MINT_ADDRESS,
+            SvmValue::pubkey(token_mint_address.to_bytes().to_vec()),
+        );
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            IS_FUNDING_RECIPIENT,
+            Value::bool(is_funding_recipient),
+        );
+
+        signers.push_signer_state(signer_state);
+        SignTransaction::check_signed_executability(
+            construct_did,
+            instance_name,
+            spec,
+            &args,
+            supervision_context,
+            signers_instances,
+            signers,
+        )
+    }
+
+    fn run_signed_execution(
+        construct_did: &ConstructDid,
+        spec: &CommandSpecification,
+        args: &ValueStore,
+        progress_tx: &channel::Sender<BlockEvent>,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        signers: SignersState,
+    ) -> SignerSignFutureResult {
+        let progress_tx = progress_tx.clone();
+        let signers_instances = signers_instances.clone();
+        let construct_did = construct_did.clone();
+        let spec = spec.clone();
+
+        let mut args = args.clone();
+        let future = async move {
+            let run_signing_future = SignTransaction::run_signed_execution(
+                &construct_did,
+                &spec,
+                &args,
+                &progress_tx,
+                &signers_instances,
+                signers,
+            );
+            let (signers, signer_state, mut res_signing) = match run_signing_future {
+                Ok(future) => match future.await {
+                    Ok(res) => res,
+                    Err(err) => return Err(err),
+                },
+                Err(err) => return Err(err),
+            };
+
+            for key in [
+                RECIPIENT_TOKEN_ADDRESS,
+                RECIPIENT_ADDRESS,
+                AUTHORITY_ADDRESS,
+                TOKEN_MINT_ADDRESS,
+                IS_FUNDING_RECIPIENT,
+            ] {
+                let value = signer_state.get_scoped_value(&construct_did.to_string(), key).unwrap();
+                res_signing.outputs.insert(key.into(), value.clone());
+            }
+
+            // signers executing the transaction themselves don't return the signed transaction
+            let Some(transaction_bytes_value) = res_signing.outputs.get(SIGNED_TRANSACTION_BYTES)
+            else {
+                return Ok((signers, signer_state, res_signing));
+            };
+            args.insert(SIGNED_TRANSACTION_BYTES, transaction_bytes_value.clone());
+            let transaction = SvmTransaction::from_value(transaction_bytes_value)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+            let _ = transaction
+                .verify_and_hash_message()
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+            Ok((signers, signer_state, res_signing))
+        };
+        Ok(Box::pin(future))
+    }
+
+    fn build_background_task(
+        construct_did: &ConstructDid,
+        spec: &CommandSpecification,
+        values: &ValueStore,
+        outputs: &ValueStore,
+        progress_tx: &channel::Sender<BlockEvent>,
+        background_tasks_uuid: &Uuid,
+        supervision_context: &RunbookSupervisionContext,
+        _cloud_service_context: &Option<CloudServiceContext>,
+    ) -> CommandExecutionFutureResult {
+        let mut status_updater =
+            StatusUpdater::new(&background_tasks_uuid, &construct_did, &progress_tx);
+        let recipient_token_address =
+            SvmValue::to_pubkey(outputs.get_expected_value(RECIPIENT_TOKEN_ADDRESS).unwrap())
+                .unwrap();
+        let recipient_address =
+            SvmValue::to_pubkey(outputs.get_expected_value(RECIPIENT_ADDRESS).unwrap()).unwrap();
+        let authority_address =
+            SvmValue::to_pubkey(outputs.get_expected_value(AUTHORITY_ADDRESS).unwrap()).unwrap();
+        let token_mint_address =
+            SvmValue::to_pubkey(outputs.get_expected_value(TOKEN_MINT_ADDRESS).unwrap()).unwrap();
+        let is_funding_recipient = outputs.get_bool(IS_FUNDING_RECIPIENT).unwrap_or(false);
+
+        status_updater.propagate_info(&format!(
+            "Minting token {} with mint authority {}",
+            token_mint_address, authority_address
+        ));
+        if is_funding_recipient {
+            status_updater.propagate_info(&format!(
+                "Recipient token account {} will be created for {}",
+                recipient_token_address, recipient_address
+            ));
+        }
+
+        send_transaction_background_task(
+            &construct_did,
+            &spec,
+            &values,
+            &outputs,
+            &progress_tx,
+            &background_tasks_uuid,
+            &supervision_context,
+        )
+    }
+}
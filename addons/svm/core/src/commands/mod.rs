@@ -1,21 +1,31 @@
 use crate::constants::{SIGNER, SIGNERS};
 // use encode_instruction::ENCODE_INSTRUCTION;
+use create_token_mint::CREATE_TOKEN_MINT;
 use deploy_program::DEPLOY_PROGRAM;
 use deploy_subraph::DEPLOY_SUBGRAPH;
+use get_account::GET_ACCOUNT;
+use get_program_accounts::GET_PROGRAM_ACCOUNTS;
+use mint_tokens::MINT_TOKENS;
 use process_instructions::PROCESS_INSTRUCTIONS;
 use send_sol::SEND_SOL;
 use send_token::SEND_TOKEN;
+use set_token_authority::SET_TOKEN_AUTHORITY;
 use setup_surfnet::SETUP_SURFNET;
 use sign_transaction::SIGN_TRANSACTION;
 use txtx_addon_kit::types::commands::PreCommandSpecification;
 use txtx_addon_kit::types::stores::ValueStore;
 use txtx_addon_kit::types::{diagnostics::Diagnostic, ConstructDid, Did};
 
+pub mod create_token_mint;
 pub mod deploy_program;
 pub mod deploy_subraph;
+pub mod get_account;
+pub mod get_program_accounts;
+pub mod mint_tokens;
 pub mod process_instructions;
 pub mod send_sol;
 pub mod send_token;
+pub mod set_token_authority;
 mod setup_surfnet;
 pub mod sign_transaction;
 
@@ -48,6 +58,11 @@ lazy_static! {
         DEPLOY_PROGRAM.clone(),
         SEND_SOL.clone(),
         SEND_TOKEN.clone(),
+        CREATE_TOKEN_MINT.clone(),
+        MINT_TOKENS.clone(),
+        SET_TOKEN_AUTHORITY.clone(),
+        GET_ACCOUNT.clone(),
+        GET_PROGRAM_ACCOUNTS.clone(),
         DEPLOY_SUBGRAPH.clone(),
         SETUP_SURFNET.clone()
     ];
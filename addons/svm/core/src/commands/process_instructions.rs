@@ -1,8 +1,6 @@
 use std::collections::HashMap;
 
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::message::Message;
-use solana_sdk::transaction::Transaction;
 use txtx_addon_kit::channel;
 use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
 use txtx_addon_kit::types::commands::{
@@ -21,8 +19,9 @@ use txtx_addon_kit::uuid::Uuid;
 
 use crate::codec::instruction::parse_instructions_map;
 use crate::codec::send_transaction::send_transaction_background_task;
+use crate::codec::transaction::build_action_transaction;
 use crate::constants::{RPC_API_URL, TRANSACTION_BYTES};
-use crate::typing::{SvmValue, INSTRUCTION_TYPE};
+use crate::typing::INSTRUCTION_TYPE;
 
 use super::get_signers_did;
 use super::sign_transaction::SignTransaction;
@@ -69,6 +68,30 @@ lazy_static! {
                         internal: false,
                         sensitive: false
                     },
+                    compute_unit_limit: {
+                        documentation: "The maximum number of compute units the transaction can consume. When omitted, the cluster default applies.",
+                        typing: Type::integer(),
+                        optional: true,
+                        tainting: false,
+                        internal: false,
+                        sensitive: false
+                    },
+                    compute_unit_price: {
+                        documentation: "The priority fee of the transaction, in micro-lamports per compute unit. Set to 'auto' to estimate the price from the recent prioritization fees of the accounts written by the transaction.",
+                        typing: Type::integer(),
+                        optional: true,
+                        tainting: false,
+                        internal: false,
+                        sensitive: false
+                    },
+                    address_lookup_tables: {
+                        documentation: "The addresses of the address lookup tables to use. When provided, a versioned (v0) transaction is built.",
+                        typing: Type::array(Type::string()),
+                        optional: true,
+                        tainting: false,
+                        internal: false,
+                        sensitive: false
+                    },
                     rpc_api_url: {
                         documentation: "The URL to use when making API requests.",
                         typing: Type::string(),
@@ -149,16 +172,9 @@ impl CommandImplementation for ProcessInstructions {
             )
         })?;
 
-        let mut message = Message::new(&instructions, None);
         let client = RpcClient::new(rpc_api_url);
-        message.recent_blockhash = client.get_latest_blockhash().map_err(|e| {
-            (
-                signers.clone(),
-                first_signer_state.clone(),
-                diagnosed_error!("failed to get latest blockhash: {e}"),
-            )
-        })?;
-        let transaction = SvmValue::transaction(&Transaction::new_unsigned(message))
+        let transaction = build_action_transaction(args, &client, &instructions, None)
+            .and_then(|transaction| transaction.to_value())
             .map_err(|e| (signers.clone(), first_signer_state.clone(), e))?;
 
         let mut args = args.clone();
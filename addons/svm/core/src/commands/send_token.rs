@@ -2,9 +2,7 @@ use std::collections::{HashMap, VecDeque};
 use std::str::FromStr;
 
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::message::Message;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::transaction::Transaction;
 use txtx_addon_kit::channel;
 use txtx_addon_kit::constants::SIGNED_TRANSACTION_BYTES;
 use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
@@ -23,13 +21,13 @@ use txtx_addon_kit::types::ConstructDid;
 use txtx_addon_kit::uuid::Uuid;
 
 use crate::codec::send_transaction::send_transaction_background_task;
+use crate::codec::transaction::{build_action_transaction, SvmTransaction};
 use crate::constants::{
     AMOUNT, AUTHORITY, AUTHORITY_ADDRESS, CHECKED_PUBLIC_KEY, FUND_RECIPIENT, IS_FUNDING_RECIPIENT,
     RECIPIENT, RECIPIENT_ADDRESS, RECIPIENT_TOKEN_ADDRESS, RPC_API_URL, SOURCE_TOKEN_ADDRESS,
     TOKEN, TOKEN_MINT_ADDRESS, TRANSACTION_BYTES,
 };
 use crate::typing::{SvmValue, SVM_PUBKEY};
-use crate::utils::build_transaction_from_svm_value;
 
 use super::get_signers_did;
 use super::sign_transaction::SignTransaction;
@@ -107,6 +105,30 @@ lazy_static! {
                     internal: false,
                     sensitive: false
                 },
+                compute_unit_limit: {
+                    documentation: "The maximum number of compute units the transaction can consume. When omitted, the cluster default applies.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                compute_unit_price: {
+                    documentation: "The priority fee of the transaction, in micro-lamports per compute unit. Set to 'auto' to estimate the price from the recent prioritization fees of the accounts written by the transaction.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                address_lookup_tables: {
+                    documentation: "The addresses of the address lookup tables to use. When provided, a versioned (v0) transaction is built.",
+                    typing: Type::array(Type::string()),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
                 rpc_api_url: {
                     documentation: "The URL to use when making API requests.",
                     typing: Type::string(),
@@ -310,18 +332,14 @@ impl CommandImplementation for SendToken {
             }
         }
 
-        let mut message =
-            Message::new(&instructions.into_iter().collect::<Vec<_>>(), Some(&authority_pubkey));
-
-        message.recent_blockhash = client.get_latest_blockhash().map_err(|e| {
-            (
-                signers.clone(),
-                signer_state.clone(),
-                diagnosed_error!("failed to retrieve latest blockhash: {}", e.to_string()),
-            )
-        })?;
-        let transaction = SvmValue::transaction(&Transaction::new_unsigned(message))
-            .map_err(|diag| (signers.clone(), signer_state.clone(), diag))?;
+        let transaction = build_action_transaction(
+            args,
+            &client,
+            &instructions.into_iter().collect::<Vec<_>>(),
+            Some(&authority_pubkey),
+        )
+        .and_then(|transaction| transaction.to_value())
+        .map_err(|diag| (signers.clone(), signer_state.clone(), diag))?;
 
         let mut args = args.clone();
         args.insert(TRANSACTION_BYTES, transaction);
@@ -430,19 +448,18 @@ impl CommandImplementation for SendToken {
             res_signing.outputs.insert(TOKEN_MINT_ADDRESS.into(), token_mint_address.clone());
             res_signing.outputs.insert(IS_FUNDING_RECIPIENT.into(), is_funding_recipient.clone());
 
-            let transaction_bytes_value =
-                res_signing.outputs.get(SIGNED_TRANSACTION_BYTES).unwrap();
+            // signers executing the transaction themselves don't return the signed transaction
+            let Some(transaction_bytes_value) = res_signing.outputs.get(SIGNED_TRANSACTION_BYTES)
+            else {
+                return Ok((signers, signer_state, res_signing));
+            };
             args.insert(SIGNED_TRANSACTION_BYTES, transaction_bytes_value.clone());
-            let transaction = build_transaction_from_svm_value(transaction_bytes_value)
+            let transaction = SvmTransaction::from_value(transaction_bytes_value)
                 .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
 
-            let _ = transaction.verify_and_hash_message().map_err(|e| {
-                (
-                    signers.clone(),
-                    signer_state.clone(),
-                    diagnosed_error!("failed to verify transaction message: {}", e),
-                )
-            })?;
+            let _ = transaction
+                .verify_and_hash_message()
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
             Ok((signers, signer_state, res_signing))
         };
         Ok(Box::pin(future))
@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use txtx_addon_kit::channel;
+use txtx_addon_kit::constants::SIGNED_TRANSACTION_BYTES;
+use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
+use txtx_addon_kit::types::commands::{
+    CommandExecutionFutureResult, CommandImplementation, CommandSpecification,
+    PreCommandSpecification,
+};
+use txtx_addon_kit::types::diagnostics::Diagnostic;
+use txtx_addon_kit::types::frontend::{BlockEvent, StatusUpdater};
+use txtx_addon_kit::types::signers::{
+    SignerActionsFutureResult, SignerInstance, SignerSignFutureResult, SignersState,
+};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::{RunbookSupervisionContext, Type, Value};
+use txtx_addon_kit::types::ConstructDid;
+use txtx_addon_kit::uuid::Uuid;
+
+use crate::codec::send_transaction::send_transaction_background_task;
+use crate::codec::token::{get_token_program_account, parse_authority_type};
+use crate::codec::transaction::{build_action_transaction, SvmTransaction};
+use crate::constants::{
+    ACCOUNT, AUTHORITY, AUTHORITY_ADDRESS, AUTHORITY_TYPE, CHECKED_PUBLIC_KEY, NEW_AUTHORITY,
+    REVOKE, RPC_API_URL, TRANSACTION_BYTES,
+};
+use crate::typing::SvmValue;
+
+use super::get_signers_did;
+use super::sign_transaction::SignTransaction;
+
+lazy_static! {
+    pub static ref SET_TOKEN_AUTHORITY: PreCommandSpecification = define_command! {
+        SetTokenAuthority => {
+            name: "Set Token Authority",
+            matcher: "set_token_authority",
+            documentation: "The `svm::set_token_authority` action encodes a transaction which sets or revokes an authority of a token mint or token account, signs it, and broadcasts it to the network. Mints and accounts owned by the SPL Token and Token-2022 programs are supported.",
+            implements_signing_capability: true,
+            implements_background_task_capability: true,
+            inputs: [
+                description: {
+                    documentation: "A description of the transaction.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                account: {
+                    documentation: "The address of the token mint or token account to update.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                authority_type: {
+                    documentation: "The authority to set. Valid values are `mint_tokens`, `freeze_account`, `account_owner` and `close_account`, as well as `transfer_fee_config`, `withheld_withdraw`, `close_mint`, `permanent_delegate` and `metadata_pointer` for the Token-2022 program.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                new_authority: {
+                    documentation: "The pubkey of the new authority. Either `new_authority` or `revoke` must be provided.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                revoke: {
+                    documentation: "If set to `true`, the authority is revoked, and can never be set again. The default is `false`.",
+                    typing: Type::bool(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                authority: {
+                    documentation: "The pubkey of the current authority. If omitted, the first signer will be used.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                signers: {
+                    documentation: "A set of references to signer constructs, which will be used to sign the transaction. The first signer pays for the transaction.",
+                    typing: Type::array(Type::string()),
+                    optional: false,
+                    tainting: true,
+                    internal: false,
+                    sensitive: false
+                },
+                commitment_level: {
+                    documentation: "The commitment level expected for considering this action as done ('processed', 'confirmed', 'finalized'). The default is 'confirmed'.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                compute_unit_limit: {
+                    documentation: "The maximum number of compute units the transaction can consume. When omitted, the cluster default applies.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                compute_unit_price: {
+                    documentation: "The priority fee of the transaction, in micro-lamports per compute unit. Set to 'auto' to estimate the price from the recent prioritization fees of the accounts written by the transaction.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                address_lookup_tables: {
+                    documentation: "The addresses of the address lookup tables to use. When provided, a versioned (v0) transaction is built.",
+                    typing: Type::array(Type::string()),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_url: {
+                    documentation: "The URL to use when making API requests.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: false,
+                    internal: false,
+                    sensitive: false
+                },
+                rpc_api_auth_token: {
+                    documentation: "The HTTP authentication token to include in the headers when making API requests.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: false,
+                    internal: false,
+                    sensitive: true
+                }
+            ],
+            outputs: [
+                signature: {
+                    documentation: "The transaction computed signature.",
+                    typing: Type::string()
+                }
+            ],
+            example: txtx_addon_kit::indoc! {
+                r#"action "revoke_mint_authority" "svm::set_token_authority" {
+                    description = "Cap the USDX supply"
+                    account = action.usdx.token_mint_address
+                    authority_type = "mint_tokens"
+                    revoke = true
+                    signers = [signer.admin]
+                }"#
+            },
+      }
+    };
+}
+
+pub struct SetTokenAuthority;
+impl CommandImplementation for SetTokenAuthority {
+    fn check_instantiability(
+        _ctx: &CommandSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        unimplemented!()
+    }
+
+    fn check_signed_executability(
+        construct_did: &ConstructDid,
+        instance_name: &str,
+        spec: &CommandSpecification,
+        args: &ValueStore,
+        supervision_context: &RunbookSupervisionContext,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        mut signers: SignersState,
+    ) -> SignerActionsFutureResult {
+        let signers_did = get_signers_did(args).unwrap();
+        let signers_states = signers_did
+            .iter()
+            .map(|did| signers.get_signer_state(did).unwrap().clone())
+            .collect::<Vec<_>>();
+        let mut signer_state = signers.pop_signer_state(signers_did.first().unwrap()).unwrap();
+
+        let account = Pubkey::from_str(
+            args.get_expected_string(ACCOUNT)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?,
+        )
+        .map_err(|e| {
+            (
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!("invalid account pubkey: {}", e.to_string()),
+            )
+        })?;
+
+        let authority_type = args
+            .get_expected_string(AUTHORITY_TYPE)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .to_string();
+
+        // revoking an authority is irreversible, so it has to be requested explicitly
+        let revoke = args.get_bool(REVOKE).unwrap_or(false);
+        let new_authority_pubkey = match (args.get_string(NEW_AUTHORITY), revoke) {
+            (Some(new_authority), false) => Some(Pubkey::from_str(new_authority).map_err(|e| {
+                (
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!("invalid new authority pubkey: {}", e.to_string()),
+                )
+            })?),
+            (None, true) => None,
+            (Some(_), true) => {
+                return Err((
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!(
+                        "'{}' and '{}' cannot both be provided",
+                        NEW_AUTHORITY,
+                        REVOKE
+                    ),
+                ))
+            }
+            (None, false) => {
+                return Err((
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!(
+                        "either '{}' or '{} = true' must be provided",
+                        NEW_AUTHORITY,
+                        REVOKE
+                    ),
+                ))
+            }
+        };
+
+        let rpc_api_url = args
+            .get_expected_string(RPC_API_URL)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .to_string();
+
+        let mut signer_pubkeys = vec![];
+        for signer_state in signers_states.iter() {
+            let signer_pubkey = signer_state
+                .get_expected_string(CHECKED_PUBLIC_KEY)
+                .map_err(|e| (signers.clone(), signer_state.clone(), diagnosed_error!("{e}")))?;
+            let signer_pubkey = Pubkey::from_str(signer_pubkey).map_err(|e| {
+                (
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!("invalid signer pubkey: {}", e.to_string()),
+                )
+            })?;
+            signer_pubkeys.push(signer_pubkey);
+        }
+        let payer_pubkey = signer_pubkeys[0];
+
+        // if the user has specified the authority pubkey, use it, otherwise use the first signer
+        let authority_pubkey = if let Some(authority_pubkey) = args.get_string(AUTHORITY) {
+            Pubkey::from_str(authority_pubkey).map_err(|e| {
+                (
+                    signers.clone(),
+                    signer_state.clone(),
+                    diagnosed_error!("invalid authority pubkey: {}", e.to_string()),
+                )
+            })?
+        } else {
+            payer_pubkey
+        };
+
+        let client = RpcClient::new(rpc_api_url);
+
+        let token_program_id = get_token_program_account(&client, &account)
+            .map(|account| account.owner)
+            .map_err(|diag| (signers.clone(), signer_state.clone(), diag))?;
+
+        let instruction = parse_authority_type(&authority_type, &token_program_id)
+            .and_then(|authority_type| {
+                spl_token_2022::instruction::set_authority(
+                    &token_program_id,
+                    &account,
+                    new_authority_pubkey.as_ref(),
+                    authority_type,
+                    &authority_pubkey,
+                    &[],
+                )
+                .map_err(|e| diagnosed_error!("failed to create set authority instruction: {e}"))
+            })
+            .map_err(|diag| (signers.clone(), signer_state.clone(), diag))?;
+
+        let transaction =
+            build_action_transaction(args, &client, &[instruction], Some(&payer_pubkey))
+                .and_then(|transaction| transaction.to_value())
+                .map_err(|diag| (signers.clone(), signer_state.clone(), diag))?;
+
+        let mut args = args.clone();
+        args.insert(TRANSACTION_BYTES, transaction);
+
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            ACCOUNT,
+            SvmValue::pubkey(account.to_bytes().to_vec()),
+        );
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            AUTHORITY_TYPE,
+            Value::string(authority_type),
+        );
+        signer_state.insert_scoped_value(
+            &construct_did.to_string(),
+            AUTHORITY_ADDRESS,
+            new_authority_pubkey
+                .map(|pubkey| SvmValue::pubkey(pubkey.to_bytes().to_vec()))
+                .unwrap_or(Value::null()),
+        );
+
+        signers.push_signer_state(signer_state);
+        SignTransaction::check_signed_executability(
+            construct_did,
+            instance_name,
+            spec,
+            &args,
+            supervision_context,
+            signers_instances,
+            signers,
+        )
+    }
+
+    fn run_signed_execution(
+        construct_did: &ConstructDid,
+        spec: &CommandSpecification,
+        args: &ValueStore,
+        progress_tx: &channel::Sender<BlockEvent>,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        signers: SignersState,
+    ) -> SignerSignFutureResult {
+        let progress_tx = progress_tx.clone();
+        let signers_instances = signers_instances.clone();
+        let construct_did = construct_did.clone();
+        let spec = spec.clone();
+
+        let mut args = args.clone();
+        let future = async move {
+            let run_signing_future = SignTransaction::run_signed_execution(
+                &construct_did,
+                &spec,
+                &args,
+                &progress_tx,
+                &signers_instances,
+                signers,
+            );
+            let (signers, signer_state, mut res_signing) = match run_signing_future {
+                Ok(future) => match future.await {
+                    Ok(res) => res,
+                    Err(err) => return Err(err),
+                },
+                Err(err) => return Err(err),
+            };
+
+            for key in [ACCOUNT, AUTHORITY_TYPE, AUTHORITY_ADDRESS] {
+                let value = signer_state.get_scoped_value(&construct_did.to_string(), key).unwrap();
+                res_signing.outputs.insert(key.into(), value.clone());
+            }
+
+            // signers executing the transaction themselves don't return the signed transaction
+            let Some(transaction_bytes_value) = res_signing.outputs.get(SIGNED_TRANSACTION_BYTES)
+            else {
+                return Ok((signers, signer_state, res_signing));
+            };
+            args.insert(SIGNED_TRANSACTION_BYTES, transaction_bytes_value.clone());
+            let transaction = SvmTransaction::from_value(transaction_bytes_value)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+            let _ = transaction
+                .verify_and_hash_message()
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+            Ok((signers, signer_state, res_signing))
+        };
+        Ok(Box::pin(future))
+    }
+
+    fn build_background_task(
+        construct_did: &ConstructDid,
+        spec: &CommandSpecification,
+        values: &ValueStore,
+        outputs: &ValueStore,
+        progress_tx: &channel::Sender<BlockEvent>,
+        background_tasks_uuid: &Uuid,
+        supervision_context: &RunbookSupervisionContext,
+        _cloud_service_context: &Option<CloudServiceContext>,
+    ) -> CommandExecutionFutureResult {
+        let mut status_updater =
+            StatusUpdater::new(&background_tasks_uuid, &construct_did, &progress_tx);
+        let account = SvmValue::to_pubkey(outputs.get_expected_value(ACCOUNT).unwrap()).unwrap();
+        let authority_type = outputs.get_string(AUTHORITY_TYPE).unwrap_or_default();
+
+        match outputs.get_value(AUTHORITY_ADDRESS) {
+            Some(new_authority) if !matches!(new_authority, Value::Null) => {
+                let new_authority = SvmValue::to_pubkey(new_authority).unwrap();
+                status_updater.propagate_info(&format!(
+                    "Setting '{}' authority of {} to {}",
+                    authority_type, account, new_authority
+                ));
+            }
+            _ => {
+                status_updater.propagate_info(&format!(
+                    "Revoking '{}' authority of {}",
+                    authority_type, account
+                ));
+            }
+        }
+
+        send_transaction_background_task(
+            &construct_did,
+            &spec,
+            &values,
+            &outputs,
+            &progress_tx,
+            &background_tasks_uuid,
+            &supervision_context,
+        )
+    }
+}
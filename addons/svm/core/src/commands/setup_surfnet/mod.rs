@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use set_account::SurfpoolAccountUpdate;
 use set_token_account::SurfpoolTokenAccountUpdate;
+pub use set_token_account::TokenProgram;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_client::rpc_request::RpcRequest;
 use txtx_addon_kit::channel;
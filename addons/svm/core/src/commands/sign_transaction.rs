@@ -1,7 +1,6 @@
-use crate::codec::transaction_is_fully_signed;
+use crate::codec::transaction::SvmTransaction;
 use crate::commands::get_signers_did;
-use crate::typing::{SvmValue, SVM_TRANSACTION};
-use crate::utils::build_transaction_from_svm_value;
+use crate::typing::SVM_TRANSACTION;
 use solana_sdk::signature::Signature;
 use std::collections::HashMap;
 use txtx_addon_kit::constants::SIGNED_TRANSACTION_BYTES;
@@ -18,7 +17,7 @@ use txtx_addon_kit::types::ConstructDid;
 use txtx_addon_kit::types::{commands::CommandSpecification, diagnostics::Diagnostic, types::Type};
 
 use crate::constants::{
-    IS_DEPLOYMENT, PARTIALLY_SIGNED_TRANSACTION_BYTES, TRANSACTION_BYTES,
+    IS_DEPLOYMENT, IS_EXECUTED_BY_SIGNER, PARTIALLY_SIGNED_TRANSACTION_BYTES, TRANSACTION_BYTES,
     UPDATED_PARTIALLY_SIGNED_TRANSACTION,
 };
 
@@ -202,7 +201,7 @@ impl CommandImplementation for SignTransaction {
                     .get_scoped_value(&construct_did.to_string(), TRANSACTION_BYTES)
                     .unwrap()
             };
-            let mut combined_transaction = build_transaction_from_svm_value(&payload).unwrap();
+            let mut combined_transaction = SvmTransaction::from_value(&payload).unwrap();
             let mut cursor = 0;
 
             for (signer_did, signer_instance) in signers_dids_with_instances {
@@ -220,14 +219,23 @@ impl CommandImplementation for SignTransaction {
                 ) {
                     Ok(res) => match res.await {
                         Ok((new_signers, new_signer_state, results)) => {
+                            // the signer already executed the transaction on our behalf
+                            if results
+                                .outputs
+                                .get(IS_EXECUTED_BY_SIGNER)
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(false)
+                            {
+                                return Ok((new_signers, new_signer_state, results));
+                            }
+
                             let partial_signed_tx = if let Some(partial_signed_tx_value) =
                                 results.outputs.get(PARTIALLY_SIGNED_TRANSACTION_BYTES)
                             {
-                                let partial_signed_tx =
-                                    build_transaction_from_svm_value(partial_signed_tx_value)
-                                        .map_err(|e| {
-                                            (new_signers.clone(), new_signer_state.clone(), e)
-                                        })?;
+                                let partial_signed_tx = SvmTransaction::from_value(
+                                    partial_signed_tx_value,
+                                )
+                                .map_err(|e| (new_signers.clone(), new_signer_state.clone(), e))?;
                                 partial_signed_tx
                             } else {
                                 // if the transaction was "updated" by the downstream signer,
@@ -237,17 +245,16 @@ impl CommandImplementation for SignTransaction {
                                     .get(UPDATED_PARTIALLY_SIGNED_TRANSACTION)
                                     .expect("Signed transaction bytes not found");
 
-                                let partial_signed_tx =
-                                    build_transaction_from_svm_value(partial_signed_tx_value)
-                                        .map_err(|e| {
-                                            (new_signers.clone(), new_signer_state.clone(), e)
-                                        })?;
+                                let partial_signed_tx = SvmTransaction::from_value(
+                                    partial_signed_tx_value,
+                                )
+                                .map_err(|e| (new_signers.clone(), new_signer_state.clone(), e))?;
                                 combined_transaction = partial_signed_tx.clone();
                                 partial_signed_tx
                             };
 
                             let participant_has_signed = combined_transaction
-                                .signatures
+                                .signatures()
                                 .iter()
                                 .any(|sig| sig != &Signature::default());
 
@@ -255,8 +262,8 @@ impl CommandImplementation for SignTransaction {
                             // and the new signatures were signed with a different blockhash,
                             // we'll return an error
                             if participant_has_signed
-                                && partial_signed_tx.message.recent_blockhash
-                                    != combined_transaction.message.recent_blockhash
+                                && partial_signed_tx.recent_blockhash()
+                                    != combined_transaction.recent_blockhash()
                             {
                                 return Err((
                                     new_signers.clone(),
@@ -270,35 +277,29 @@ impl CommandImplementation for SignTransaction {
                             // if this is the first time we're adding signatures to the combined transaction,
                             // and the new signatures were signed with a different blockhash,
                             // we'll update the combined transaction to match the updated blockhash
-                            if partial_signed_tx.message.recent_blockhash
-                                != combined_transaction.message.recent_blockhash
+                            if partial_signed_tx.recent_blockhash()
+                                != combined_transaction.recent_blockhash()
                             {
-                                combined_transaction.message.recent_blockhash =
-                                    partial_signed_tx.message.recent_blockhash;
+                                combined_transaction
+                                    .set_recent_blockhash(*partial_signed_tx.recent_blockhash());
                             }
 
                             // add the new signatures to the combined transaction
-                            for (i, sig) in partial_signed_tx.signatures.iter().enumerate() {
+                            for (i, sig) in partial_signed_tx.signatures().iter().enumerate() {
                                 if sig != &Signature::default() {
-                                    combined_transaction.signatures[i] = sig.clone();
+                                    combined_transaction.signatures_mut()[i] = sig.clone();
                                 }
                             }
 
-                            if transaction_is_fully_signed(&combined_transaction) {
+                            if combined_transaction.is_fully_signed() {
                                 let mut result = CommandExecutionResult::new();
 
                                 combined_transaction.verify_and_hash_message().map_err(|e| {
-                                    (
-                                        new_signers.clone(),
-                                        new_signer_state.clone(),
-                                        diagnosed_error!(
-                                            "failed to verify signed transaction: {e}"
-                                        ),
-                                    )
+                                    (new_signers.clone(), new_signer_state.clone(), e)
                                 })?;
                                 result.outputs.insert(
                                     SIGNED_TRANSACTION_BYTES.into(),
-                                    SvmValue::transaction(&combined_transaction).map_err(|e| {
+                                    combined_transaction.to_value().map_err(|e| {
                                         (new_signers.clone(), new_signer_state.clone(), e)
                                     })?,
                                 );
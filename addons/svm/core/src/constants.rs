@@ -25,6 +25,20 @@ pub const DERIVATION_PATH: &str = "derivation_path";
 pub const IS_ENCRYPTED: &str = "is_encrypted";
 pub const PASSWORD: &str = "password";
 pub const KEYPAIR_JSON: &str = "keypair_json";
+pub const IS_EXECUTED_BY_SIGNER: &str = "is_executed_by_signer";
+
+// Squads
+pub const MULTISIG_ADDRESS: &str = "multisig_address";
+pub const VAULT_INDEX: &str = "vault_index";
+pub const INITIATOR: &str = "initiator";
+pub const APPROVAL_THRESHOLD: &str = "approval_threshold";
+pub const PROPOSAL_ADDRESS: &str = "proposal_address";
+pub const PROPOSAL_STATUS: &str = "proposal_status";
+pub const TRANSACTION_INDEX: &str = "transaction_index";
+pub const NEXT_TRANSACTION_INDEX: &str = "next_transaction_index";
+pub const SQUADS_PROGRAM_ID: &str = "squads_program_id";
+pub const SQUADS_PROPOSAL_TRANSACTION: &str = "squads_proposal_transaction";
+pub const PROPOSAL_TIMEOUT: &str = "proposal_timeout";
 
 // Defaults keys
 pub const RPC_API_URL: &str = "rpc_api_url";
@@ -40,6 +54,9 @@ pub const UPDATED_PARTIALLY_SIGNED_TRANSACTION: &str = "updated_partially_signed
 pub const NETWORK_ID: &str = "network_id";
 pub const AUTO_EXTEND: &str = "auto_extend";
 pub const COMMITMENT_LEVEL: &str = "commitment_level";
+pub const COMPUTE_UNIT_LIMIT: &str = "compute_unit_limit";
+pub const COMPUTE_UNIT_PRICE: &str = "compute_unit_price";
+pub const ADDRESS_LOOKUP_TABLES: &str = "address_lookup_tables";
 pub const DO_AWAIT_CONFIRMATION: &str = "do_await_confirmation";
 pub const SIGNATURE: &str = "signature";
 pub const SIGNATURES: &str = "signatures";
@@ -57,6 +74,40 @@ pub const IS_FUNDING_RECIPIENT: &str = "is_funding_recipient";
 pub const SET_ACCOUNT: &str = "set_account";
 pub const SET_TOKEN_ACCOUNT: &str = "set_token_account";
 
+// Account reads keys
+pub const ACCOUNT_NAME: &str = "account_name";
+pub const ACCOUNT_TYPE: &str = "account_type";
+pub const ACCOUNTS: &str = "accounts";
+pub const EXISTS: &str = "exists";
+pub const LAMPORTS: &str = "lamports";
+pub const OWNER: &str = "owner";
+pub const EXECUTABLE: &str = "executable";
+pub const DATA: &str = "data";
+pub const DECODED: &str = "decoded";
+pub const MEMCMP: &str = "memcmp";
+pub const DATA_SIZE: &str = "data_size";
+pub const OFFSET: &str = "offset";
+pub const BYTES: &str = "bytes";
+
+// Token mint keys
+pub const MINT: &str = "mint";
+pub const DECIMALS: &str = "decimals";
+pub const TOKEN_PROGRAM: &str = "token_program";
+pub const TOKEN_PROGRAM_ID: &str = "token_program_id";
+pub const MINT_AUTHORITY: &str = "mint_authority";
+pub const FREEZE_AUTHORITY: &str = "freeze_authority";
+pub const TRANSFER_FEE: &str = "transfer_fee";
+pub const BASIS_POINTS: &str = "basis_points";
+pub const MAXIMUM_FEE: &str = "maximum_fee";
+pub const CONFIG_AUTHORITY: &str = "config_authority";
+pub const WITHDRAW_AUTHORITY: &str = "withdraw_authority";
+pub const METADATA_POINTER: &str = "metadata_pointer";
+pub const METADATA_ADDRESS: &str = "metadata_address";
+pub const ACCOUNT: &str = "account";
+pub const AUTHORITY_TYPE: &str = "authority_type";
+pub const NEW_AUTHORITY: &str = "new_authority";
+pub const REVOKE: &str = "revoke";
+
 // Subgraph keys
 pub const BLOCK_HEIGHT: &str = "block_height";
 pub const EVENT: &str = "event";
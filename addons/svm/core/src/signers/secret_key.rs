@@ -4,7 +4,6 @@ use std::sync::Arc;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
 use solana_sdk::signature::Keypair;
-use solana_sdk::transaction::Transaction;
 use txtx_addon_kit::channel;
 use txtx_addon_kit::constants::{SIGNATURE_APPROVED, SIGNATURE_SKIPPABLE};
 use txtx_addon_kit::types::commands::CommandExecutionResult;
@@ -25,8 +24,8 @@ use txtx_addon_kit::types::{
     diagnostics::Diagnostic,
     types::{Type, Value},
 };
-use txtx_addon_network_svm_types::SvmValue;
 
+use crate::codec::transaction::SvmTransaction;
 use crate::codec::DeploymentTransaction;
 use crate::constants::{
     ACTION_ITEM_CHECK_ADDRESS, ACTION_ITEM_PROVIDE_SIGNED_TRANSACTION, ADDRESS, CHECKED_ADDRESS,
@@ -34,7 +33,6 @@ use crate::constants::{
     NAMESPACE, NETWORK_ID, PARTIALLY_SIGNED_TRANSACTION_BYTES, PUBLIC_KEY, RPC_API_URL, SECRET_KEY,
     TRANSACTION_BYTES,
 };
-use crate::utils::build_transaction_from_svm_value;
 use txtx_addon_kit::types::signers::return_synchronous_actions;
 use txtx_addon_kit::types::types::RunbookSupervisionContext;
 
@@ -420,9 +418,9 @@ impl SignerImplementation for SvmSecretKey {
                         )
                     })?;
 
-                let mut transaction: Transaction = deployment_transaction.transaction.clone();
+                let mut transaction: SvmTransaction = deployment_transaction.transaction.clone();
 
-                transaction.message.recent_blockhash = blockhash;
+                transaction.set_recent_blockhash(blockhash);
 
                 let keypairs = deployment_transaction.get_keypairs().map_err(|e| {
                     (
@@ -433,41 +431,28 @@ impl SignerImplementation for SvmSecretKey {
                 })?;
 
                 transaction
-                    .try_partial_sign(&keypairs, transaction.message.recent_blockhash)
-                    .map_err(|e| {
-                        (
-                            signers.clone(),
-                            signer_state.clone(),
-                            diagnosed_error!("failed to sign transaction: {e}"),
-                        )
-                    })?;
+                    .try_partial_sign(&keypairs)
+                    .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
 
                 (transaction, deployment_transaction.signers.is_some())
             } else {
-                let mut transaction: Transaction = build_transaction_from_svm_value(&payload)
+                let mut transaction = SvmTransaction::from_value(&payload)
                     .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
 
-                transaction.message.recent_blockhash = blockhash;
+                transaction.set_recent_blockhash(blockhash);
 
                 (transaction, true)
             };
 
         if do_sign_with_txtx_signer {
             transaction
-                .try_partial_sign(&[keypair], transaction.message.recent_blockhash)
-                .map_err(|e| {
-                    (
-                        signers.clone(),
-                        signer_state.clone(),
-                        diagnosed_error!("failed to sign transaction: {e}"),
-                    )
-                })?;
+                .try_partial_sign(&[keypair])
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
         }
 
         result.outputs.insert(
             PARTIALLY_SIGNED_TRANSACTION_BYTES.into(),
-            SvmValue::transaction(&transaction)
-                .map_err(|e| (signers.clone(), signer_state.clone(), e))?,
+            transaction.to_value().map_err(|e| (signers.clone(), signer_state.clone(), e))?,
         );
 
         return_synchronous_result(Ok((signers, signer_state, result)))
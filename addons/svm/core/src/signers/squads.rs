@@ -1,11 +1,15 @@
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
+use solana_client::nonblocking::rpc_client::RpcClient as AsyncRpcClient;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
 use txtx_addon_kit::channel;
-use txtx_addon_kit::constants::{SIGNATURE_APPROVED, SIGNATURE_SKIPPABLE};
 use txtx_addon_kit::types::commands::CommandExecutionResult;
-use txtx_addon_kit::types::frontend::{
-    ActionItemRequest, ActionItemStatus, ProvideSignedTransactionRequest, ReviewInputRequest,
-};
+use txtx_addon_kit::types::frontend::{ActionItemRequest, ActionItemStatus, ReviewInputRequest};
 use txtx_addon_kit::types::frontend::{Actions, BlockEvent};
 use txtx_addon_kit::types::signers::{
     return_synchronous_result, CheckSignabilityOk, SignerActionErr, SignerActionsFutureResult,
@@ -14,35 +18,85 @@ use txtx_addon_kit::types::signers::{
 use txtx_addon_kit::types::signers::{SignerImplementation, SignerSpecification};
 use txtx_addon_kit::types::stores::ValueStore;
 use txtx_addon_kit::types::AuthorizationContext;
-use txtx_addon_kit::types::ConstructDid;
 use txtx_addon_kit::types::{
     commands::CommandSpecification,
     diagnostics::Diagnostic,
     types::{Type, Value},
 };
+use txtx_addon_kit::types::{ConstructDid, Did};
 
+use crate::codec::squads::{
+    get_create_proposal_transaction, get_execute_proposal_transaction, get_proposal_pda,
+    get_vault_pda, squads_program_id, SquadsMultisig, SquadsProposal, SquadsProposalStatus,
+};
+use crate::codec::transaction_is_fully_signed;
 use crate::constants::{
-    ACTION_ITEM_CHECK_ADDRESS, ACTION_ITEM_PROVIDE_SIGNED_TRANSACTION, ADDRESS, CHECKED_ADDRESS,
-    CHECKED_PUBLIC_KEY, FORMATTED_TRANSACTION, IS_SIGNABLE, NAMESPACE, NETWORK_ID, PUBLIC_KEY,
-    TRANSACTION_BYTES,
+    ACTION_ITEM_CHECK_ADDRESS, ADDRESS, APPROVAL_THRESHOLD, CHECKED_ADDRESS, CHECKED_PUBLIC_KEY,
+    FORMATTED_TRANSACTION, INITIATOR, IS_DEPLOYMENT, IS_EXECUTED_BY_SIGNER, MULTISIG_ADDRESS,
+    NEXT_TRANSACTION_INDEX, PARTIALLY_SIGNED_TRANSACTION_BYTES, PROPOSAL_ADDRESS, PROPOSAL_STATUS,
+    PROPOSAL_TIMEOUT, PUBLIC_KEY, RPC_API_URL, SIGNATURE, SQUADS_PROGRAM_ID,
+    SQUADS_PROPOSAL_TRANSACTION, TRANSACTION_BYTES, TRANSACTION_INDEX,
+    UPDATED_PARTIALLY_SIGNED_TRANSACTION, VAULT_INDEX,
 };
 use crate::typing::SvmValue;
+use crate::utils::build_transaction_from_svm_value;
 use txtx_addon_kit::types::signers::return_synchronous_actions;
 use txtx_addon_kit::types::types::RunbookSupervisionContext;
 
+const PROPOSAL_POLLING_INTERVAL_MS: u64 = 5_000;
+const DEFAULT_PROPOSAL_TIMEOUT_SECS: u64 = 3_600;
+
 lazy_static! {
     pub static ref SVM_SQUADS: SignerSpecification = define_signer! {
-        SvmSecretKey => {
+        SvmSquads => {
             name: "Squads Signer",
             matcher: "squads",
-            documentation:txtx_addon_kit::indoc! {r#"The `svm::squads` signer can be used to synchronously sign a transaction."#},
+            documentation:txtx_addon_kit::indoc! {r#"The `svm::squads` signer can be used to route transactions through a [Squads v4](https://squads.so) multisig vault.
+            Each transaction is wrapped in a vault transaction, for which a proposal is created and approved by the `initiator` signer.
+            The runbook then waits for the other members of the multisig to approve the proposal. Once the approval threshold is met, the `initiator` attempts to execute the proposal; if it is not allowed to, the runbook waits for another member to execute it.
+            When used with `svm::deploy_program`, this signer should be used as the `authority`, while a regular signer is used as the `payer`."#},
             inputs: [
+                multisig_address: {
+                    documentation: "The address of the Squads multisig account.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    sensitive: false
+                },
+                initiator: {
+                    documentation: "A reference to a signer construct, which must be a member of the multisig with the permissions to initiate and vote. This signer creates and approves the proposals, and pays for their fees and rent.",
+                    typing: Type::string(),
+                    optional: false,
+                    tainting: true,
+                    sensitive: false
+                },
+                vault_index: {
+                    documentation: "The index of the multisig vault used to execute the transactions. The default is 0.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: true,
+                    sensitive: false
+                },
                 address: {
-                    documentation: "The squad vault address.",
+                    documentation: "The expected address of the squad vault. If provided, the vault address derived from the multisig must match this address.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    sensitive: false
+                },
+                squads_program_id: {
+                    documentation: "The address of the Squads v4 program. The default is the address of the program deployed by Squads on mainnet and devnet.",
                     typing: Type::string(),
                     optional: true,
                     tainting: true,
-                    sensitive: true
+                    sensitive: false
+                },
+                proposal_timeout: {
+                    documentation: "The number of seconds to wait for a proposal to be approved and executed before failing. The default is 3600 (one hour).",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: false,
+                    sensitive: false
                 }
             ],
             outputs: [
@@ -51,21 +105,34 @@ lazy_static! {
                     typing: Type::string()
                 },
                 address: {
-                    documentation: "The SVM address generated from the secret key, mnemonic, or keypair file. This is an alias for the `public_key` output.",
+                    documentation: "The address of the squad vault. This is an alias for the `public_key` output.",
+                    typing: Type::string()
+                },
+                multisig_address: {
+                    documentation: "The address of the Squads multisig account.",
                     typing: Type::string()
+                },
+                approval_threshold: {
+                    documentation: "The number of approvals required for a proposal to be executed.",
+                    typing: Type::integer()
                 }
             ],
             example: txtx_addon_kit::indoc! {r#"
-            signer "deployer" "svm::squads" {
-                address = input.address
+            signer "member" "svm::secret_key" {
+                keypair_json = "~/.config/solana/id.json"
+            }
+
+            signer "treasury" "svm::squads" {
+                multisig_address = input.multisig_address
+                initiator = signer.member
             }
         "#},
         }
     };
 }
 
-pub struct SvmSecretKey;
-impl SignerImplementation for SvmSecretKey {
+pub struct SvmSquads;
+impl SignerImplementation for SvmSquads {
     fn check_instantiability(
         _ctx: &SignerSpecification,
         _args: Vec<Type>,
@@ -87,29 +154,118 @@ impl SignerImplementation for SvmSecretKey {
         _is_balance_check_required: bool,
         _is_public_key_required: bool,
     ) -> SignerActionsFutureResult {
-        let mut actions = Actions::none();
-
         if signer_state.get_value(CHECKED_PUBLIC_KEY).is_some() {
-            return return_synchronous_actions(Ok((signers, signer_state, actions)));
+            return return_synchronous_actions(Ok((signers, signer_state, Actions::none())));
         }
 
-        let pubkey = SvmValue::to_pubkey(
+        let initiator_did =
+            get_initiator_did(values).map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+        let initiator_pubkey = signers
+            .get_signer_state(&initiator_did)
+            .and_then(|state| state.get_string(CHECKED_PUBLIC_KEY))
+            .ok_or(diagnosed_error!(
+                "the initiator of the squad '{}' must be activated before the squad",
+                instance_name
+            ))
+            .and_then(|pubkey| {
+                Pubkey::from_str(pubkey)
+                    .map_err(|e| diagnosed_error!("invalid initiator public key: {e}"))
+            })
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+        let multisig = Pubkey::from_str(
             values
-                .get_expected_value(ADDRESS)
+                .get_expected_string(MULTISIG_ADDRESS)
                 .map_err(|e| (signers.clone(), signer_state.clone(), e))?,
         )
         .map_err(|e| {
-            (signers.clone(), signer_state.clone(), diagnosed_error!("invalid pubkey: {e}"))
+            (
+                signers.clone(),
+                signer_state.clone(),
+                diagnosed_error!("invalid multisig address: {e}"),
+            )
         })?;
 
-        let pubkey_value = SvmValue::pubkey(pubkey.to_bytes().to_vec());
-        let pubkey_string_value = Value::string(pubkey.to_string());
+        let vault_index =
+            get_vault_index(values).map_err(|e| (signers.clone(), signer_state.clone(), e))?;
 
-        if supervision_context.review_input_values {
-            if let Ok(_) = values.get_expected_string(CHECKED_ADDRESS) {
-                signer_state.insert(CHECKED_PUBLIC_KEY, pubkey_value.clone());
-                signer_state.insert(CHECKED_ADDRESS, pubkey_string_value.clone());
-            } else {
+        let program_id = squads_program_id(values.get_string(SQUADS_PROGRAM_ID))
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+        let proposal_timeout =
+            get_proposal_timeout(values).map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+        let rpc_api_url = values
+            .get_expected_string(RPC_API_URL)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?
+            .to_string();
+
+        let expected_address = values.get_string(ADDRESS).map(|a| a.to_string());
+        let is_address_checked = values.get_expected_string(CHECKED_ADDRESS).is_ok();
+        let construct_did = construct_did.clone();
+        let instance_name = instance_name.to_string();
+        let supervision_context = supervision_context.clone();
+
+        let future = async move {
+            let mut actions = Actions::none();
+
+            let rpc_client = AsyncRpcClient::new(rpc_api_url);
+            let squads_multisig = match rpc_client.get_account_data(&multisig).await {
+                Ok(data) => SquadsMultisig::from_account_data(&data),
+                Err(e) => {
+                    Err(diagnosed_error!("unable to retrieve multisig account {}: {}", multisig, e))
+                }
+            }
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+            let Some(member) = squads_multisig.get_member(&initiator_pubkey) else {
+                return Err((
+                    signers,
+                    signer_state,
+                    diagnosed_error!(
+                        "the initiator {} is not a member of the multisig {}",
+                        initiator_pubkey,
+                        multisig
+                    ),
+                ));
+            };
+            if !member.can_initiate() || !member.can_vote() {
+                return Err((
+                    signers,
+                    signer_state,
+                    diagnosed_error!(
+                        "the initiator {} must have the permissions to initiate and vote on proposals",
+                        initiator_pubkey
+                    ),
+                ));
+            }
+
+            let vault = get_vault_pda(&multisig, vault_index, &program_id);
+            if let Some(expected_address) = expected_address {
+                if expected_address != vault.to_string() {
+                    return Err((
+                        signers,
+                        signer_state,
+                        diagnosed_error!(
+                            "the vault address {} derived from the multisig does not match the expected address {}",
+                            vault,
+                            expected_address
+                        ),
+                    ));
+                }
+            }
+
+            signer_state.insert(MULTISIG_ADDRESS, Value::string(multisig.to_string()));
+            signer_state.insert(VAULT_INDEX, Value::integer(vault_index.into()));
+            signer_state.insert(SQUADS_PROGRAM_ID, Value::string(program_id.to_string()));
+            signer_state.insert(PROPOSAL_TIMEOUT, Value::integer(proposal_timeout.into()));
+            signer_state.insert(INITIATOR, Value::string(initiator_did.to_string()));
+            signer_state
+                .insert(APPROVAL_THRESHOLD, Value::integer(squads_multisig.threshold.into()));
+
+            let vault_value = Value::string(vault.to_string());
+            if supervision_context.review_input_values && !is_address_checked {
                 actions.push_sub_group(
                     None,
                     vec![ActionItemRequest::new(
@@ -117,17 +273,17 @@ impl SignerImplementation for SvmSecretKey {
                         &format!("Check {} expected address", instance_name),
                         None,
                         ActionItemStatus::Todo,
-                        ReviewInputRequest::new("", &pubkey_string_value).to_action_type(),
+                        ReviewInputRequest::new("", &vault_value).to_action_type(),
                         ACTION_ITEM_CHECK_ADDRESS,
                     )],
                 );
+            } else {
+                signer_state.insert(CHECKED_PUBLIC_KEY, vault_value.clone());
+                signer_state.insert(CHECKED_ADDRESS, vault_value);
             }
-        } else {
-            signer_state.insert(CHECKED_PUBLIC_KEY, pubkey_value.clone());
-            signer_state.insert(CHECKED_ADDRESS, pubkey_string_value.clone());
-        }
 
-        let future = async move { Ok((signers, signer_state, actions)) };
+            Ok((signers, signer_state, actions))
+        };
         Ok(Box::pin(future))
     }
 
@@ -143,8 +299,12 @@ impl SignerImplementation for SvmSecretKey {
         let mut result = CommandExecutionResult::new();
         let public_key = signer_state.get_value(CHECKED_PUBLIC_KEY).unwrap();
         let address = signer_state.get_value(CHECKED_ADDRESS).unwrap();
+        let multisig_address = signer_state.get_value(MULTISIG_ADDRESS).unwrap();
+        let approval_threshold = signer_state.get_value(APPROVAL_THRESHOLD).unwrap();
         result.outputs.insert(ADDRESS.into(), address.clone());
         result.outputs.insert(PUBLIC_KEY.into(), public_key.clone());
+        result.outputs.insert(MULTISIG_ADDRESS.into(), multisig_address.clone());
+        result.outputs.insert(APPROVAL_THRESHOLD.into(), approval_threshold.clone());
         return_synchronous_result(Ok((signers, signer_state, result)))
     }
 
@@ -156,83 +316,671 @@ impl SignerImplementation for SvmSecretKey {
         _spec: &SignerSpecification,
         values: &ValueStore,
         mut signer_state: ValueStore,
-        signers: SignersState,
-        _signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        mut signers: SignersState,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
         supervision_context: &RunbookSupervisionContext,
     ) -> Result<CheckSignabilityOk, SignerActionErr> {
-        signer_state.insert_scoped_value(
-            &construct_did.to_string(),
-            TRANSACTION_BYTES,
-            payload.clone(),
-        );
+        let construct_did_str = &construct_did.to_string();
+        signer_state.insert_scoped_value(&construct_did_str, TRANSACTION_BYTES, payload.clone());
 
-        let actions = if supervision_context.review_input_values {
-            let construct_did_str = &construct_did.to_string();
-            if let Some(_) = signer_state.get_scoped_value(&construct_did_str, SIGNATURE_APPROVED) {
-                return Ok((signers, signer_state, Actions::none()));
-            }
+        let squad = SquadContext::from_signer_state(&signer_state, &signers)
+            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
 
-            let network_id = match values.get_expected_string(NETWORK_ID) {
-                Ok(value) => value,
-                Err(diag) => return Err((signers, signer_state, diag)),
-            };
-            let signable = signer_state
-                .get_scoped_value(&construct_did_str, IS_SIGNABLE)
-                .and_then(|v| v.as_bool())
-                .unwrap_or(true);
-
-            let status = match signable {
-                true => ActionItemStatus::Todo,
-                false => ActionItemStatus::Blocked,
+        let Some(initiator_instance) = signers_instances.get(&squad.initiator_did) else {
+            return Err((
+                signers,
+                signer_state,
+                diagnosed_error!("squad initiator signer instance not found"),
+            ));
+        };
+
+        let proposal_transaction =
+            match signer_state.get_scoped_value(&construct_did_str, SQUADS_PROPOSAL_TRANSACTION) {
+                Some(proposal_transaction) => proposal_transaction.clone(),
+                None => {
+                    let rpc_api_url = values
+                        .get_expected_string(RPC_API_URL)
+                        .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+                    let transaction_index =
+                        get_transaction_index(&construct_did_str, &mut signer_state, || {
+                            let rpc_client = RpcClient::new_with_commitment(
+                                rpc_api_url.to_string(),
+                                CommitmentConfig::confirmed(),
+                            );
+                            rpc_client
+                                .get_account_data(&squad.multisig)
+                                .map_err(|e| {
+                                    diagnosed_error!(
+                                        "unable to retrieve multisig account {}: {}",
+                                        squad.multisig,
+                                        e
+                                    )
+                                })
+                                .and_then(|data| SquadsMultisig::from_account_data(&data))
+                                .map(|squads_multisig| squads_multisig.transaction_index)
+                        })
+                        .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+                    let transaction = build_transaction_from_svm_value(payload)
+                        .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+                    let proposal_transaction = squad
+                        .get_create_proposal_transaction(transaction_index, &transaction, title)
+                        .and_then(|tx| SvmValue::transaction(&tx))
+                        .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+                    signer_state.insert_scoped_value(
+                        &construct_did_str,
+                        PROPOSAL_ADDRESS,
+                        Value::string(squad.get_proposal_address(transaction_index).to_string()),
+                    );
+                    signer_state.insert_scoped_value(
+                        &construct_did_str,
+                        SQUADS_PROPOSAL_TRANSACTION,
+                        proposal_transaction.clone(),
+                    );
+                    proposal_transaction
+                }
             };
-            let skippable = signer_state
-                .get_scoped_value(&construct_did_str, SIGNATURE_SKIPPABLE)
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false);
-            let formatted_payload =
-                signer_state.get_scoped_value(&construct_did_str, FORMATTED_TRANSACTION);
-
-            let request = ActionItemRequest::new(
-                &Some(construct_did.clone()),
-                title,
-                description.clone(),
-                status,
-                ProvideSignedTransactionRequest::new(
-                    &signer_state.uuid,
-                    &payload,
-                    NAMESPACE,
-                    &network_id,
-                )
-                .skippable(skippable)
-                .check_expectation_action_uuid(construct_did)
-                .formatted_payload(formatted_payload)
-                .only_approval_needed()
-                .to_action_type(),
-                ACTION_ITEM_PROVIDE_SIGNED_TRANSACTION,
-            );
-            Actions::append_item(
-                request,
-                Some("Review and sign the transactions from the list below"),
-                Some("Transaction Signing"),
-            )
-        } else {
-            Actions::none()
+
+        let Some(mut initiator_state) = signers.pop_signer_state(&squad.initiator_did) else {
+            return Err((
+                signers,
+                signer_state,
+                diagnosed_error!("squad initiator signer state not found"),
+            ));
         };
-        Ok((signers, signer_state, actions))
+        if let Some(formatted_transaction) =
+            signer_state.get_scoped_value(&construct_did_str, FORMATTED_TRANSACTION)
+        {
+            initiator_state.insert_scoped_value(
+                &construct_did_str,
+                FORMATTED_TRANSACTION,
+                formatted_transaction.clone(),
+            );
+        }
+
+        let description = Some(format!(
+            "{}This transaction creates and approves a proposal on the Squads multisig {}.",
+            description.as_ref().map(|d| format!("{} ", d)).unwrap_or_default(),
+            squad.multisig
+        ));
+        let mut values = values.clone();
+        values.insert(IS_DEPLOYMENT, Value::bool(false));
+
+        match (initiator_instance.specification.check_signability)(
+            construct_did,
+            title,
+            &description,
+            &proposal_transaction,
+            &initiator_instance.specification,
+            &values,
+            initiator_state,
+            signers,
+            signers_instances,
+            supervision_context,
+        ) {
+            Ok((mut signers, initiator_state, actions)) => {
+                signers.push_signer_state(initiator_state);
+                Ok((signers, signer_state, actions))
+            }
+            Err((mut signers, initiator_state, diag)) => {
+                signers.push_signer_state(initiator_state);
+                Err((signers, signer_state, diag))
+            }
+        }
     }
 
     fn sign(
-        _caller_uuid: &ConstructDid,
-        _title: &str,
-        _payload: &Value,
+        caller_uuid: &ConstructDid,
+        title: &str,
+        payload: &Value,
         _spec: &SignerSpecification,
-        _values: &ValueStore,
+        values: &ValueStore,
         signer_state: ValueStore,
         signers: SignersState,
-        _signers_instances: &HashMap<ConstructDid, SignerInstance>,
+        signers_instances: &HashMap<ConstructDid, SignerInstance>,
     ) -> SignerSignFutureResult {
-        let result = CommandExecutionResult::new();
+        let caller_uuid = caller_uuid.clone();
+        let title = title.to_string();
+        let payload = payload.clone();
+        let signers_instances = signers_instances.clone();
+        let mut values = values.clone();
+        // the initiator signs the transactions wrapping the payload, not the payload itself
+        values.insert(IS_DEPLOYMENT, Value::bool(false));
 
-        return_synchronous_result(Ok((signers, signer_state, result)))
+        let future = async move {
+            let mut signers = signers;
+            let construct_did_str = &caller_uuid.to_string();
+
+            let squad = SquadContext::from_signer_state(&signer_state, &signers)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+            let Some(initiator_instance) = signers_instances.get(&squad.initiator_did) else {
+                return Err((
+                    signers,
+                    signer_state,
+                    diagnosed_error!("squad initiator signer instance not found"),
+                ));
+            };
+
+            let (Some(proposal_transaction), Some(transaction_index)) = (
+                signer_state.get_scoped_value(&construct_did_str, SQUADS_PROPOSAL_TRANSACTION),
+                signer_state
+                    .get_scoped_integer(&construct_did_str, TRANSACTION_INDEX)
+                    .map(|i| i as u64),
+            ) else {
+                return Err((
+                    signers,
+                    signer_state,
+                    diagnosed_error!("squad proposal transaction was not prepared"),
+                ));
+            };
+
+            let rpc_api_url = values
+                .get_expected_string(RPC_API_URL)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+            let rpc_client = AsyncRpcClient::new_with_commitment(
+                rpc_api_url.to_string(),
+                CommitmentConfig::confirmed(),
+            );
+
+            let proposal_transaction = build_transaction_from_svm_value(proposal_transaction)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+            let (updated_signers, proposal_signature) = sign_and_send_with_initiator(
+                &caller_uuid,
+                &title,
+                &proposal_transaction,
+                &squad.initiator_did,
+                initiator_instance,
+                &values,
+                signers,
+                &signers_instances,
+                &rpc_client,
+            )
+            .await
+            .map_err(|(signers, e)| {
+                (
+                    signers,
+                    signer_state.clone(),
+                    diagnosed_error!("failed to create squad proposal: {}", e.message),
+                )
+            })?;
+            signers = updated_signers;
+
+            let transaction = build_transaction_from_svm_value(&payload)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+            let proposal_address = squad.get_proposal_address(transaction_index);
+
+            let mut execution_signature = None;
+            let mut did_attempt_execution = false;
+            let deadline = Instant::now() + Duration::from_secs(squad.proposal_timeout);
+            let proposal = loop {
+                let proposal = match rpc_client.get_account_data(&proposal_address).await {
+                    Ok(data) => SquadsProposal::from_account_data(&data),
+                    Err(e) => Err(diagnosed_error!(
+                        "unable to retrieve squad proposal {}: {}",
+                        proposal_address,
+                        e
+                    )),
+                }
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+
+                let step =
+                    get_next_proposal_step(&proposal_address, &proposal, did_attempt_execution)
+                        .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+                match step {
+                    ProposalStep::Done => break proposal,
+                    ProposalStep::Execute => {
+                        did_attempt_execution = true;
+                        let execute_transaction = squad
+                            .get_execute_proposal_transaction(transaction_index, &transaction)
+                            .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
+                        // the initiator might not be allowed (or able) to execute the proposal,
+                        // in which case we keep waiting for another member to execute it
+                        match sign_and_send_with_initiator(
+                            &caller_uuid,
+                            &title,
+                            &execute_transaction,
+                            &squad.initiator_did,
+                            initiator_instance,
+                            &values,
+                            signers,
+                            &signers_instances,
+                            &rpc_client,
+                        )
+                        .await
+                        {
+                            Ok((updated_signers, signature)) => {
+                                signers = updated_signers;
+                                execution_signature = Some(signature);
+                                continue;
+                            }
+                            Err((updated_signers, _)) => {
+                                signers = updated_signers;
+                            }
+                        }
+                    }
+                    ProposalStep::Wait => {}
+                }
+                if Instant::now() >= deadline {
+                    return Err((
+                        signers,
+                        signer_state,
+                        diagnosed_error!(
+                            "squad proposal {} was not executed within {} seconds (status: {})",
+                            proposal_address,
+                            squad.proposal_timeout,
+                            proposal.status.to_string()
+                        ),
+                    ));
+                }
+                tokio::time::sleep(Duration::from_millis(PROPOSAL_POLLING_INTERVAL_MS)).await;
+            };
+
+            let mut result = CommandExecutionResult::new();
+            result.outputs.insert(IS_EXECUTED_BY_SIGNER.into(), Value::bool(true));
+            result.outputs.insert(
+                SIGNATURE.into(),
+                Value::string(execution_signature.unwrap_or(proposal_signature)),
+            );
+            result
+                .outputs
+                .insert(PROPOSAL_ADDRESS.into(), Value::string(proposal_address.to_string()));
+            result
+                .outputs
+                .insert(PROPOSAL_STATUS.into(), Value::string(proposal.status.to_string()));
+            result.outputs.insert(
+                TRANSACTION_INDEX.into(),
+                Value::integer(proposal.transaction_index.into()),
+            );
+            result
+                .outputs
+                .insert(MULTISIG_ADDRESS.into(), Value::string(squad.multisig.to_string()));
+            result
+                .outputs
+                .insert(APPROVAL_THRESHOLD.into(), Value::integer(squad.approval_threshold.into()));
+
+            Ok((signers, signer_state, result))
+        };
+        Ok(Box::pin(future))
+    }
+}
+
+/// The squad configuration stored in the signer state during activation.
+struct SquadContext {
+    multisig: Pubkey,
+    vault_index: u8,
+    program_id: Pubkey,
+    initiator_did: ConstructDid,
+    initiator: Pubkey,
+    approval_threshold: u16,
+    proposal_timeout: u64,
+}
+
+impl SquadContext {
+    fn from_signer_state(
+        signer_state: &ValueStore,
+        signers: &SignersState,
+    ) -> Result<Self, Diagnostic> {
+        let multisig = Pubkey::from_str(signer_state.get_expected_string(MULTISIG_ADDRESS)?)
+            .map_err(|e| diagnosed_error!("invalid multisig address: {e}"))?;
+        let vault_index = signer_state.get_expected_uint(VAULT_INDEX)? as u8;
+        let program_id =
+            squads_program_id(Some(signer_state.get_expected_string(SQUADS_PROGRAM_ID)?))?;
+        let initiator_did = get_initiator_did(signer_state)?;
+        let initiator = signers
+            .get_signer_state(&initiator_did)
+            .and_then(|state| state.get_string(CHECKED_PUBLIC_KEY))
+            .ok_or(diagnosed_error!("squad initiator signer state not found"))
+            .and_then(|pubkey| {
+                Pubkey::from_str(pubkey)
+                    .map_err(|e| diagnosed_error!("invalid initiator public key: {e}"))
+            })?;
+        let approval_threshold = signer_state.get_expected_uint(APPROVAL_THRESHOLD)? as u16;
+        let proposal_timeout = signer_state.get_expected_uint(PROPOSAL_TIMEOUT)?;
+        Ok(Self {
+            multisig,
+            vault_index,
+            program_id,
+            initiator_did,
+            initiator,
+            approval_threshold,
+            proposal_timeout,
+        })
+    }
+
+    fn get_proposal_address(&self, transaction_index: u64) -> Pubkey {
+        get_proposal_pda(&self.multisig, transaction_index, &self.program_id)
+    }
+
+    fn get_create_proposal_transaction(
+        &self,
+        transaction_index: u64,
+        transaction: &Transaction,
+        memo: &str,
+    ) -> Result<Transaction, Diagnostic> {
+        get_create_proposal_transaction(
+            &self.program_id,
+            &self.multisig,
+            self.vault_index,
+            transaction_index,
+            &self.initiator,
+            transaction,
+            Some(memo.to_string()),
+        )
+    }
+
+    fn get_execute_proposal_transaction(
+        &self,
+        transaction_index: u64,
+        transaction: &Transaction,
+    ) -> Result<Transaction, Diagnostic> {
+        get_execute_proposal_transaction(
+            &self.program_id,
+            &self.multisig,
+            self.vault_index,
+            transaction_index,
+            &self.initiator,
+            transaction,
+        )
+    }
+}
+
+fn get_initiator_did(values: &ValueStore) -> Result<ConstructDid, Diagnostic> {
+    let initiator = values.get_expected_string(INITIATOR)?;
+    Ok(ConstructDid(Did::from_hex_string(initiator)))
+}
+
+fn get_vault_index(values: &ValueStore) -> Result<u8, Diagnostic> {
+    let vault_index = values
+        .get_uint(VAULT_INDEX)
+        .map_err(|e| diagnosed_error!("invalid vault index: {e}"))?
+        .unwrap_or(0);
+    u8::try_from(vault_index)
+        .map_err(|_| diagnosed_error!("invalid vault index: {} is greater than 255", vault_index))
+}
+
+fn get_proposal_timeout(values: &ValueStore) -> Result<u64, Diagnostic> {
+    let proposal_timeout = values
+        .get_uint(PROPOSAL_TIMEOUT)
+        .map_err(|e| diagnosed_error!("invalid proposal timeout: {e}"))?
+        .unwrap_or(DEFAULT_PROPOSAL_TIMEOUT_SECS);
+    if proposal_timeout == 0 {
+        return Err(diagnosed_error!("invalid proposal timeout: must be greater than 0"));
+    }
+    Ok(proposal_timeout)
+}
+
+/// What the signer should do next, given the latest state of the proposal it is tracking.
+#[derive(Debug, Clone, PartialEq)]
+enum ProposalStep {
+    /// The proposal was executed.
+    Done,
+    /// The proposal reached the approval threshold, the initiator should try to execute it.
+    Execute,
+    /// The proposal is waiting for votes, or for another member to execute it.
+    Wait,
+}
+
+fn get_next_proposal_step(
+    proposal_address: &Pubkey,
+    proposal: &SquadsProposal,
+    did_attempt_execution: bool,
+) -> Result<ProposalStep, Diagnostic> {
+    match proposal.status {
+        SquadsProposalStatus::Executed => Ok(ProposalStep::Done),
+        SquadsProposalStatus::Rejected | SquadsProposalStatus::Cancelled => Err(diagnosed_error!(
+            "squad proposal {} was {}",
+            proposal_address,
+            proposal.status.to_string()
+        )),
+        SquadsProposalStatus::Approved if !did_attempt_execution => Ok(ProposalStep::Execute),
+        _ => Ok(ProposalStep::Wait),
+    }
+}
+
+/// Has the initiator sign `transaction`, then broadcasts it and waits for its confirmation.
+/// The initiator signer state is pushed back to `signers`, whether signing succeeded or not.
+async fn sign_and_send_with_initiator(
+    caller_uuid: &ConstructDid,
+    title: &str,
+    transaction: &Transaction,
+    initiator_did: &ConstructDid,
+    initiator_instance: &SignerInstance,
+    values: &ValueStore,
+    mut signers: SignersState,
+    signers_instances: &HashMap<ConstructDid, SignerInstance>,
+    rpc_client: &AsyncRpcClient,
+) -> Result<(SignersState, String), (SignersState, Diagnostic)> {
+    let Some(initiator_state) = signers.pop_signer_state(initiator_did) else {
+        return Err((signers, diagnosed_error!("squad initiator signer state not found")));
+    };
+    let payload = match SvmValue::transaction(transaction) {
+        Ok(payload) => payload,
+        Err(e) => {
+            signers.push_signer_state(initiator_state);
+            return Err((signers, e));
+        }
+    };
+
+    let res = match (initiator_instance.specification.sign)(
+        caller_uuid,
+        title,
+        &payload,
+        &initiator_instance.specification,
+        values,
+        initiator_state,
+        signers,
+        signers_instances,
+    ) {
+        Ok(future) => future.await,
+        Err(e) => Err(e),
+    };
+    let (mut signers, initiator_state, result) = match res {
+        Ok(res) => res,
+        Err((mut signers, initiator_state, diag)) => {
+            signers.push_signer_state(initiator_state);
+            return Err((signers, diag));
+        }
+    };
+    signers.push_signer_state(initiator_state);
+
+    let Some(signed_transaction) = result
+        .outputs
+        .get(PARTIALLY_SIGNED_TRANSACTION_BYTES)
+        .or(result.outputs.get(UPDATED_PARTIALLY_SIGNED_TRANSACTION))
+    else {
+        return Err((signers, diagnosed_error!("squad initiator did not sign the transaction")));
+    };
+    let signed_transaction = match build_transaction_from_svm_value(signed_transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => return Err((signers, e)),
+    };
+    if !transaction_is_fully_signed(&signed_transaction) {
+        return Err((signers, diagnosed_error!("squad initiator did not sign the transaction")));
+    }
+
+    match rpc_client.send_and_confirm_transaction(&signed_transaction).await {
+        Ok(signature) => Ok((signers, signature.to_string())),
+        Err(e) => Err((
+            signers,
+            diagnosed_error!("unable to send and confirm transaction ({})", e.to_string()),
+        )),
+    }
+}
+
+/// Returns the index of the multisig transaction proposed for the action. The index of the first
+/// proposal is read from the multisig account with `get_multisig_transaction_index`, and the
+/// following proposals are numbered from the signer state: all the proposals of an execution are
+/// checked before any of them lands on chain.
+fn get_transaction_index(
+    construct_did: &str,
+    signer_state: &mut ValueStore,
+    get_multisig_transaction_index: impl FnOnce() -> Result<u64, Diagnostic>,
+) -> Result<u64, Diagnostic> {
+    if let Some(transaction_index) = signer_state.get_scoped_value(construct_did, TRANSACTION_INDEX)
+    {
+        return transaction_index.expect_uint().map_err(|e| diagnosed_error!("{e}"));
+    }
+    let transaction_index = match signer_state.get_value(NEXT_TRANSACTION_INDEX) {
+        Some(next_transaction_index) => {
+            next_transaction_index.expect_uint().map_err(|e| diagnosed_error!("{e}"))?
+        }
+        None => get_multisig_transaction_index()? + 1,
+    };
+    signer_state.insert(NEXT_TRANSACTION_INDEX, Value::integer((transaction_index + 1).into()));
+    signer_state.insert_scoped_value(
+        construct_did,
+        TRANSACTION_INDEX,
+        Value::integer(transaction_index.into()),
+    );
+    Ok(transaction_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_instruction;
+
+    fn squad_context() -> SquadContext {
+        SquadContext {
+            multisig: Pubkey::new_unique(),
+            vault_index: 0,
+            program_id: squads_program_id(None).unwrap(),
+            initiator_did: ConstructDid(Did::zero()),
+            initiator: Pubkey::new_unique(),
+            approval_threshold: 2,
+            proposal_timeout: DEFAULT_PROPOSAL_TIMEOUT_SECS,
+        }
+    }
+
+    fn proposal(transaction_index: u64, status: SquadsProposalStatus) -> SquadsProposal {
+        SquadsProposal {
+            transaction_index,
+            status,
+            approved: vec![],
+            rejected: vec![],
+            cancelled: vec![],
+        }
+    }
+
+    #[test]
+    fn it_numbers_the_proposals_of_an_execution() {
+        let mut signer_state = ValueStore::new("squad", &Did::zero());
+        let fetches = std::cell::Cell::new(0);
+        let get_multisig_transaction_index = || {
+            fetches.set(fetches.get() + 1);
+            Ok(6)
+        };
+
+        let first =
+            get_transaction_index("first", &mut signer_state, get_multisig_transaction_index)
+                .unwrap();
+        let second =
+            get_transaction_index("second", &mut signer_state, get_multisig_transaction_index)
+                .unwrap();
+        assert_eq!((first, second), (7, 8));
+        assert_eq!(fetches.get(), 1);
+
+        // Checking an action again keeps its index
+        let first =
+            get_transaction_index("first", &mut signer_state, get_multisig_transaction_index)
+                .unwrap();
+        assert_eq!(first, 7);
+        let squad = squad_context();
+        assert_ne!(squad.get_proposal_address(7), squad.get_proposal_address(8));
+    }
+
+    #[test]
+    fn it_creates_approves_and_executes_a_proposal() {
+        let squad = squad_context();
+        let transaction_index = 7;
+        let vault = get_vault_pda(&squad.multisig, squad.vault_index, &squad.program_id);
+        let recipient = Pubkey::new_unique();
+        let transfer = Transaction::new_with_payer(
+            &[system_instruction::transfer(&vault, &recipient, 1_000)],
+            Some(&vault),
+        );
+        let proposal_address = squad.get_proposal_address(transaction_index);
+
+        // create: the initiator pays for the vault transaction and proposal, and casts the first vote
+        let create_transaction = squad
+            .get_create_proposal_transaction(transaction_index, &transfer, "transfer")
+            .unwrap();
+        let create_message = &create_transaction.message;
+        assert_eq!(create_message.account_keys[0], squad.initiator);
+        assert_eq!(create_message.instructions.len(), 3);
+        assert!(create_message.instructions.iter().all(|ix| create_message.account_keys
+            [ix.program_id_index as usize]
+            == squad.program_id));
+        assert!(create_message.account_keys.contains(&proposal_address));
+
+        // approve: the proposal waits for the other members until the threshold is met
+        let mut did_attempt_execution = false;
+        for status in [SquadsProposalStatus::Draft, SquadsProposalStatus::Active] {
+            let step = get_next_proposal_step(
+                &proposal_address,
+                &proposal(transaction_index, status),
+                did_attempt_execution,
+            )
+            .unwrap();
+            assert_eq!(step, ProposalStep::Wait);
+        }
+        let step = get_next_proposal_step(
+            &proposal_address,
+            &proposal(transaction_index, SquadsProposalStatus::Approved),
+            did_attempt_execution,
+        )
+        .unwrap();
+        assert_eq!(step, ProposalStep::Execute);
+        did_attempt_execution = true;
+
+        // execute: the vault transaction's accounts are passed to the squads program
+        let execute_transaction =
+            squad.get_execute_proposal_transaction(transaction_index, &transfer).unwrap();
+        let execute_message = &execute_transaction.message;
+        assert_eq!(execute_message.account_keys[0], squad.initiator);
+        assert_eq!(execute_message.instructions.len(), 1);
+        assert!(execute_message.account_keys.contains(&proposal_address));
+        assert!(execute_message.account_keys.contains(&vault));
+        assert!(execute_message.account_keys.contains(&recipient));
+
+        // an approved proposal that failed to execute is not executed twice
+        let step = get_next_proposal_step(
+            &proposal_address,
+            &proposal(transaction_index, SquadsProposalStatus::Approved),
+            did_attempt_execution,
+        )
+        .unwrap();
+        assert_eq!(step, ProposalStep::Wait);
+        let step = get_next_proposal_step(
+            &proposal_address,
+            &proposal(transaction_index, SquadsProposalStatus::Executed),
+            did_attempt_execution,
+        )
+        .unwrap();
+        assert_eq!(step, ProposalStep::Done);
+    }
+
+    #[test]
+    fn it_fails_on_rejected_or_cancelled_proposals() {
+        let proposal_address = Pubkey::new_unique();
+        for status in [SquadsProposalStatus::Rejected, SquadsProposalStatus::Cancelled] {
+            let expected =
+                format!("squad proposal {} was {}", proposal_address, status.to_string());
+            let diag =
+                get_next_proposal_step(&proposal_address, &proposal(1, status), false).unwrap_err();
+            assert_eq!(diag.message, expected);
+        }
+    }
+
+    #[test]
+    fn it_validates_the_proposal_timeout() {
+        let mut values = ValueStore::tmp();
+        assert_eq!(get_proposal_timeout(&values).unwrap(), DEFAULT_PROPOSAL_TIMEOUT_SECS);
+        values.insert(PROPOSAL_TIMEOUT, Value::integer(60));
+        assert_eq!(get_proposal_timeout(&values).unwrap(), 60);
+        values.insert(PROPOSAL_TIMEOUT, Value::integer(0));
+        assert!(get_proposal_timeout(&values).is_err());
     }
 }
@@ -3,7 +3,6 @@ use std::collections::HashMap;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::signature::Signature;
-use solana_sdk::transaction::Transaction;
 use txtx_addon_kit::channel;
 use txtx_addon_kit::constants::{SIGNATURE_SKIPPABLE, SIGNED_TRANSACTION_BYTES};
 use txtx_addon_kit::types::commands::CommandExecutionResult;
@@ -25,7 +24,8 @@ use txtx_addon_kit::types::{
     types::{Type, Value},
 };
 
-use crate::codec::{transaction_is_fully_signed, DeploymentTransaction};
+use crate::codec::transaction::SvmTransaction;
+use crate::codec::DeploymentTransaction;
 use crate::constants::{
     ACTION_ITEM_CHECK_ADDRESS, ACTION_ITEM_PROVIDE_PUBLIC_KEY,
     ACTION_ITEM_PROVIDE_SIGNED_TRANSACTION, ADDRESS, CHECKED_ADDRESS, CHECKED_PUBLIC_KEY,
@@ -33,8 +33,6 @@ use crate::constants::{
     PARTIALLY_SIGNED_TRANSACTION_BYTES, PUBLIC_KEY, REQUESTED_STARTUP_DATA, RPC_API_URL,
     TRANSACTION_BYTES, UPDATED_PARTIALLY_SIGNED_TRANSACTION,
 };
-use crate::typing::SvmValue;
-use crate::utils::build_transaction_from_svm_value;
 
 use super::get_additional_actions_for_address;
 
@@ -336,7 +334,7 @@ impl SignerImplementation for SvmWebWallet {
         let supervisor_signed_tx = if let Some(signed_transaction_value) = signed_transaction_value
         {
             let transaction =
-                build_transaction_from_svm_value(&signed_transaction_value).map_err(|e| {
+                SvmTransaction::from_value(&signed_transaction_value).map_err(|e| {
                     (
                         signers.clone(),
                         signer_state.clone(),
@@ -344,7 +342,7 @@ impl SignerImplementation for SvmWebWallet {
                     )
                 })?;
 
-            let is_fully_signed = transaction_is_fully_signed(&transaction);
+            let is_fully_signed = transaction.is_fully_signed();
 
             if is_fully_signed {
                 // the supervisor has fully signed the transaction, and there's a chance the web wallet has
@@ -365,7 +363,7 @@ impl SignerImplementation for SvmWebWallet {
         // so the supervisor updates the blockhash before signing. we'll use this blockhash for the transaction
         // if it's available
         let blockhash = if let Some(transaction) = &supervisor_signed_tx {
-            transaction.message.recent_blockhash.clone()
+            transaction.recent_blockhash().clone()
         } else {
             let rpc_api_url = values
                 .get_expected_string(RPC_API_URL)
@@ -404,7 +402,7 @@ impl SignerImplementation for SvmWebWallet {
             } else {
                 deployment_transaction.transaction.clone()
             };
-            transaction.message.recent_blockhash = blockhash;
+            transaction.set_recent_blockhash(blockhash);
 
             let keypairs = deployment_transaction.get_keypairs().map_err(|e| {
                 (
@@ -414,30 +412,24 @@ impl SignerImplementation for SvmWebWallet {
                 )
             })?;
 
-            transaction.try_partial_sign(&keypairs, transaction.message.recent_blockhash).map_err(
-                |e| {
-                    (
-                        signers.clone(),
-                        signer_state.clone(),
-                        diagnosed_error!("failed to sign transaction: {e}"),
-                    )
-                },
-            )?;
+            transaction
+                .try_partial_sign(&keypairs)
+                .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
             (transaction, deployment_transaction.signers.is_some())
         } else {
-            let mut transaction: Transaction = build_transaction_from_svm_value(&payload)
+            let mut transaction = SvmTransaction::from_value(&payload)
                 .map_err(|e| (signers.clone(), signer_state.clone(), e))?;
 
-            transaction.message.recent_blockhash = blockhash;
+            transaction.set_recent_blockhash(blockhash);
 
             (transaction, true)
         };
 
         if do_sign_with_txtx_signer {
             if let Some(supervisor_signed_tx) = &supervisor_signed_tx {
-                for (i, sig) in supervisor_signed_tx.signatures.iter().enumerate() {
+                for (i, sig) in supervisor_signed_tx.signatures().iter().enumerate() {
                     if sig != &Signature::default() {
-                        transaction.signatures[i] = sig.clone();
+                        transaction.signatures_mut()[i] = sig.clone();
                     }
                 }
             } else {
@@ -452,14 +444,12 @@ impl SignerImplementation for SvmWebWallet {
         if did_update_transaction {
             result.outputs.insert(
                 UPDATED_PARTIALLY_SIGNED_TRANSACTION.into(),
-                SvmValue::transaction(&transaction)
-                    .map_err(|e| (signers.clone(), signer_state.clone(), e))?,
+                transaction.to_value().map_err(|e| (signers.clone(), signer_state.clone(), e))?,
             );
         } else {
             result.outputs.insert(
                 PARTIALLY_SIGNED_TRANSACTION_BYTES.into(),
-                SvmValue::transaction(&transaction)
-                    .map_err(|e| (signers.clone(), signer_state.clone(), e))?,
+                transaction.to_value().map_err(|e| (signers.clone(), signer_state.clone(), e))?,
             );
         }
 
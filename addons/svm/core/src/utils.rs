@@ -1,3 +1,4 @@
+use crate::codec::transaction::SvmTransaction;
 use crate::codec::DeploymentTransaction;
 use crate::typing::{
     SVM_CLOSE_TEMP_AUTHORITY_TRANSACTION_PARTS, SVM_DEPLOYMENT_TRANSACTION, SVM_TRANSACTION,
@@ -17,20 +18,21 @@ pub fn decode_hex(str: &str) -> Result<Vec<u8>, Diagnostic> {
 }
 
 pub fn build_transaction_from_svm_value(value: &Value) -> Result<Transaction, Diagnostic> {
+    build_svm_transaction_from_svm_value(value)?.into_legacy()
+}
+
+pub fn build_svm_transaction_from_svm_value(value: &Value) -> Result<SvmTransaction, Diagnostic> {
     match value {
         Value::String(s) => {
             if is_hex(s) {
                 let hex = decode_hex(s)?;
-                return serde_json::from_slice(&hex)
-                    .map_err(|e| diagnosed_error!("could not deserialize transaction: {e}"));
+                return SvmTransaction::from_bytes(&hex);
             }
-            return serde_json::from_str(s)
-                .map_err(|e| diagnosed_error!("could not deserialize transaction: {e}"));
+            return SvmTransaction::from_bytes(s.as_bytes());
         }
         Value::Addon(addon_data) => {
             if addon_data.id == SVM_TRANSACTION {
-                return serde_json::from_slice(&addon_data.bytes)
-                    .map_err(|e| diagnosed_error!("could not deserialize transaction: {e}"));
+                return SvmTransaction::from_bytes(&addon_data.bytes);
             } else if addon_data.id == SVM_DEPLOYMENT_TRANSACTION
                 || addon_data.id == SVM_CLOSE_TEMP_AUTHORITY_TRANSACTION_PARTS
             {
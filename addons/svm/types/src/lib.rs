@@ -391,6 +391,21 @@ lazy_static! {
         }
     };
 
+    pub static ref MEMCMP_FILTER_MAP: Type = define_strict_map_type! {
+        offset: {
+            documentation: "The offset, in bytes, of the data to compare in the account data.",
+            typing: Type::integer(),
+            optional: false,
+            tainting: true
+        },
+        bytes: {
+            documentation: "The bytes expected at the offset, as a hex string prefixed with '0x', a base58 string, or a buffer.",
+            typing: Type::string(),
+            optional: false,
+            tainting: true
+        }
+    };
+
     pub static ref SET_TOKEN_ACCOUNT_MAP: Type = define_strict_map_type! {
         public_key: {
             documentation: "The public key of the token owner account to update.",
@@ -441,6 +456,48 @@ lazy_static! {
             tainting: true
         }
     };
+
+    pub static ref TRANSFER_FEE_MAP: Type = define_strict_map_type! {
+        basis_points: {
+            documentation: "The fee charged on each transfer, in basis points of the transferred amount.",
+            typing: Type::integer(),
+            optional: false,
+            tainting: true
+        },
+        maximum_fee: {
+            documentation: "The maximum fee charged on a transfer, in base units of the token.",
+            typing: Type::integer(),
+            optional: false,
+            tainting: true
+        },
+        config_authority: {
+            documentation: "The public key of the account allowed to update the transfer fee. If omitted, the transfer fee cannot be updated.",
+            typing: Type::addon(SVM_PUBKEY),
+            optional: true,
+            tainting: true
+        },
+        withdraw_authority: {
+            documentation: "The public key of the account allowed to withdraw the withheld fees. If omitted, the withheld fees cannot be withdrawn.",
+            typing: Type::addon(SVM_PUBKEY),
+            optional: true,
+            tainting: true
+        }
+    };
+
+    pub static ref METADATA_POINTER_MAP: Type = define_strict_map_type! {
+        authority: {
+            documentation: "The public key of the account allowed to update the metadata pointer. If omitted, the pointer cannot be updated.",
+            typing: Type::addon(SVM_PUBKEY),
+            optional: true,
+            tainting: true
+        },
+        metadata_address: {
+            documentation: "The address of the account holding the token metadata. The default is the mint itself.",
+            typing: Type::addon(SVM_PUBKEY),
+            optional: true,
+            tainting: true
+        }
+    };
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -0,0 +1,74 @@
+use teloxide::prelude::*;
+use teloxide::types::{ParseMode, Recipient};
+use teloxide::RequestError;
+use txtx_addon_kit::reqwest::Url;
+use txtx_addon_kit::types::diagnostics::Diagnostic;
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::Value;
+
+use crate::constants::{
+    DEFAULT_TELEGRAM_API_URL, PARSE_MODE, TELEGRAM_API_URL, TELEGRAM_BOT_API_TOKEN,
+    TELEGRAM_CHAT_ID,
+};
+
+/// Builds a bot client from the `telegram_bot_api_token` and `telegram_api_url` inputs.
+/// Overriding the API url allows running against a self-hosted Bot API server, or a local stub.
+pub fn build_bot(values: &ValueStore) -> Result<Bot, Diagnostic> {
+    let token = values.get_expected_string(TELEGRAM_BOT_API_TOKEN)?;
+    let api_url = values.get_string(TELEGRAM_API_URL).unwrap_or(DEFAULT_TELEGRAM_API_URL);
+    let api_url = Url::parse(api_url)
+        .map_err(|e| diagnosed_error!("invalid Telegram API url '{}': {}", api_url, e))?;
+    Ok(Bot::new(token).set_api_url(api_url))
+}
+
+/// Retrieves the chat targeted by the `telegram_chat_id` input, which is either a chat id,
+/// or the username of a channel (`@channel_name`).
+pub fn get_chat_recipient(values: &ValueStore) -> Result<Recipient, Diagnostic> {
+    match values.get_expected_value(TELEGRAM_CHAT_ID)? {
+        Value::Integer(chat_id) => {
+            let chat_id: i64 = (*chat_id)
+                .try_into()
+                .map_err(|_| diagnosed_error!("invalid Telegram chat id {}", chat_id))?;
+            Ok(Recipient::Id(ChatId(chat_id)))
+        }
+        Value::String(chat_id) => {
+            if let Ok(chat_id) = chat_id.parse::<i64>() {
+                Ok(Recipient::Id(ChatId(chat_id)))
+            } else if chat_id.starts_with('@') {
+                Ok(Recipient::ChannelUsername(chat_id.clone()))
+            } else {
+                Err(diagnosed_error!(
+                    "invalid Telegram chat id '{}': expected a chat id or a channel username",
+                    chat_id
+                ))
+            }
+        }
+        other => Err(diagnosed_error!(
+            "invalid Telegram chat id: expected integer or string, got {}",
+            other.get_type().to_string()
+        )),
+    }
+}
+
+/// Retrieves the formatting applied to messages from the `parse_mode` input.
+pub fn get_parse_mode(values: &ValueStore) -> Result<Option<ParseMode>, Diagnostic> {
+    let Some(parse_mode) = values.get_string(PARSE_MODE) else {
+        return Ok(None);
+    };
+    match parse_mode.to_lowercase().as_str() {
+        "markdown" | "markdownv2" => Ok(Some(ParseMode::MarkdownV2)),
+        "html" => Ok(Some(ParseMode::Html)),
+        "text" => Ok(None),
+        _ => Err(diagnosed_error!(
+            "invalid parse mode '{}': expected 'markdown', 'html' or 'text'",
+            parse_mode
+        )),
+    }
+}
+
+pub fn diagnosed_request_error(method: &str, e: RequestError) -> Diagnostic {
+    match e {
+        RequestError::Api(e) => diagnosed_error!("Telegram API rejected '{}': {}", method, e),
+        e => diagnosed_error!("Telegram API request '{}' failed: {}", method, e),
+    }
+}
@@ -0,0 +1,23 @@
+pub const NAMESPACE: &str = "telegram";
+
+// Inputs keys
+pub const TELEGRAM_BOT_API_TOKEN: &str = "telegram_bot_api_token";
+pub const TELEGRAM_CHAT_ID: &str = "telegram_chat_id";
+pub const TELEGRAM_API_URL: &str = "telegram_api_url";
+pub const MESSAGE: &str = "message";
+pub const PARSE_MODE: &str = "parse_mode";
+pub const APPROVERS: &str = "approvers";
+pub const TIMEOUT_SECONDS: &str = "timeout_seconds";
+
+// Outputs keys
+pub const MESSAGE_ID: &str = "message_id";
+pub const APPROVED: &str = "approved";
+pub const APPROVED_BY: &str = "approved_by";
+
+// Default values
+pub const DEFAULT_TELEGRAM_API_URL: &str = "https://api.telegram.org";
+pub const DEFAULT_POLLING_TIMEOUT_SECONDS: u32 = 30;
+
+// Callback data
+pub const APPROVE_CALLBACK_PREFIX: &str = "approve";
+pub const REJECT_CALLBACK_PREFIX: &str = "reject";
@@ -0,0 +1,62 @@
+use txtx_addon_kit::types::{
+    diagnostics::Diagnostic,
+    functions::{FunctionImplementation, FunctionSpecification},
+    types::{Type, Value},
+    AuthorizationContext,
+};
+
+lazy_static! {
+    pub static ref FUNCTIONS: Vec<FunctionSpecification> = vec![define_function! {
+        EscapeMarkdown => {
+            name: "escape_markdown",
+            documentation: "`telegram::escape_markdown` escapes the characters of a string that are reserved by Telegram's MarkdownV2 formatting, so that it can be interpolated in a message sent with `parse_mode = \"markdown\"`.",
+            example: txtx_addon_kit::indoc! {r#"
+                action "notify_team" "telegram::send_message" {
+                    telegram_bot_api_token = env.telegram_bot_api_token
+                    telegram_chat_id = env.telegram_chat_id
+                    parse_mode = "markdown"
+                    message = "*Deployed* ${telegram::escape_markdown(action.deploy.contract_address)}"
+                }
+            "#},
+            inputs: [
+                text: {
+                    documentation: "The text to escape.",
+                    typing: vec![Type::string()]
+                }
+            ],
+            output: {
+                documentation: "The escaped text.",
+                typing: Type::string()
+            },
+        }
+    },];
+}
+
+#[derive(Clone)]
+pub struct EscapeMarkdown;
+impl FunctionImplementation for EscapeMarkdown {
+    fn check_instantiability(
+        _fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        _args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        Ok(Type::string())
+    }
+
+    fn run(
+        _fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        let text = match args.get(0) {
+            Some(Value::String(text)) => text.clone(),
+            Some(other) => other.to_string(),
+            None => {
+                return Err(diagnosed_error!(
+                    "function 'telegram::escape_markdown' expects a string argument"
+                ))
+            }
+        };
+        Ok(Value::string(teloxide::utils::markdown::escape(&text)))
+    }
+}
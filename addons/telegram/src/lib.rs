@@ -4,10 +4,21 @@ extern crate lazy_static;
 #[macro_use]
 extern crate txtx_addon_kit;
 
-use txtx_addon_kit::{types::commands::PreCommandSpecification, Addon};
-
+use txtx_addon_kit::{
+    types::{commands::PreCommandSpecification, functions::FunctionSpecification},
+    Addon,
+};
+
+mod bot;
+mod constants;
+mod functions;
+mod request_approval;
 mod send_message;
 mod setup_chat;
+mod typing;
+
+#[cfg(test)]
+mod tests;
 
 #[derive(Debug)]
 pub struct TelegramAddon;
@@ -25,15 +36,24 @@ impl Addon for TelegramAddon {
 
     fn get_description(&self) -> &str {
         txtx_addon_kit::indoc! {r#"
-            Lorem ipsum 
+            The Telegram addon sends notifications to Telegram chats, and requests approvals from
+            whitelisted users before continuing the execution of a runbook.
             "#}
     }
 
     fn get_namespace(&self) -> &str {
-        "telegram"
+        constants::NAMESPACE
+    }
+
+    fn get_functions(&self) -> Vec<FunctionSpecification> {
+        functions::FUNCTIONS.clone()
     }
 
     fn get_actions(&self) -> Vec<PreCommandSpecification> {
-        vec![send_message::TELEGRAM_SEND_MESSAGE.clone(), setup_chat::TELEGRAM_SETUP_CHAT.clone()]
+        vec![
+            send_message::TELEGRAM_SEND_MESSAGE.clone(),
+            request_approval::TELEGRAM_REQUEST_APPROVAL.clone(),
+            setup_chat::TELEGRAM_SETUP_CHAT.clone(),
+        ]
     }
 }
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use teloxide::prelude::*;
+use teloxide::types::{
+    AllowedUpdate, InlineKeyboardButton, InlineKeyboardMarkup, UpdateKind, User,
+};
+use txtx_addon_kit::types::commands::{
+    CommandExecutionFutureResult, CommandExecutionResult, CommandImplementation,
+    PreCommandSpecification,
+};
+use txtx_addon_kit::types::frontend::{Actions, BlockEvent};
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::{RunbookSupervisionContext, Value};
+use txtx_addon_kit::types::ConstructDid;
+use txtx_addon_kit::types::{commands::CommandSpecification, diagnostics::Diagnostic, types::Type};
+
+use crate::bot::{build_bot, diagnosed_request_error, get_chat_recipient, get_parse_mode};
+use crate::constants::{
+    APPROVED, APPROVED_BY, APPROVERS, APPROVE_CALLBACK_PREFIX, DEFAULT_POLLING_TIMEOUT_SECONDS,
+    MESSAGE, MESSAGE_ID, REJECT_CALLBACK_PREFIX, TELEGRAM_BOT_API_TOKEN, TIMEOUT_SECONDS,
+};
+use crate::typing::get_command_outputs_type;
+
+lazy_static! {
+    pub static ref TELEGRAM_REQUEST_APPROVAL: PreCommandSpecification = define_command! {
+      TelegramRequestApproval => {
+          name: "Request Telegram Approval",
+          matcher: "request_approval",
+          documentation: txtx_addon_kit::indoc! {r#"
+            The `telegram::request_approval` action posts a message with 'Approve' and 'Reject' buttons to a Telegram chat,
+            and blocks the execution of the runbook until one of the approvers answers.
+            The runbook execution fails if the request is rejected, or if no approver answered before the timeout.
+            The bot answers are retrieved by polling the Bot API (`getUpdates`), so the bot must not have a webhook configured.
+            The approval requests sent by a bot are posted one at a time: a request is only posted once the previous requests of the bot were answered."#},
+          implements_signing_capability: false,
+          implements_background_task_capability: false,
+          inputs: [
+            description: {
+                documentation: "A description of the call.",
+                typing: Type::string(),
+                optional: true,
+                tainting: false,
+                internal: false
+            },
+            telegram_bot_api_token: {
+              documentation: "The API token of the bot sending the request, as provided by @BotFather.",
+              typing: Type::string(),
+              optional: false,
+              tainting: false,
+                internal: false
+            },
+            telegram_chat_id: {
+                documentation: "The id of the chat to send the request to, as returned by `telegram::setup_chat`, or the username of a channel (`@channel_name`).",
+                typing: Type::string(),
+                optional: false,
+                tainting: true,
+                internal: false
+            },
+            telegram_api_url: {
+                documentation: "The url of the Telegram Bot API. Defaults to `https://api.telegram.org`.",
+                typing: Type::string(),
+                optional: true,
+                tainting: false,
+                internal: false
+            },
+            message: {
+                documentation: "The message describing what is being approved. Construct outputs can be interpolated in the message.",
+                typing: Type::string(),
+                optional: false,
+                tainting: true,
+                internal: false
+            },
+            parse_mode: {
+                documentation: "The formatting of the message: 'markdown' (Telegram's MarkdownV2), 'html' or 'text'. Defaults to 'text'.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                internal: false
+            },
+            approvers: {
+                documentation: "The Telegram users allowed to answer the request, identified by their user id or username. Answers from other users are ignored.",
+                typing: Type::array(Type::string()),
+                optional: false,
+                tainting: true,
+                internal: false
+            },
+            timeout_seconds: {
+                documentation: "The number of seconds to wait for an answer before failing, including the time spent waiting for the other requests of the bot to be answered. By default, the request waits indefinitely.",
+                typing: Type::integer(),
+                optional: true,
+                tainting: false,
+                internal: false
+            }
+          ],
+          outputs: [
+              message_id: {
+                  documentation: "The id of the message sent.",
+                  typing: Type::integer()
+              },
+              approved: {
+                  documentation: "Whether the request was approved. Always true, since a rejected request fails the runbook execution.",
+                  typing: Type::bool()
+              },
+              approved_by: {
+                  documentation: "The username (or user id) of the approver who approved the request.",
+                  typing: Type::string()
+              }
+          ],
+          example: txtx_addon_kit::indoc! {r#"
+            action "approve_upgrade" "telegram::request_approval" {
+                description = "Approve the protocol upgrade"
+                telegram_bot_api_token = env.telegram_bot_api_token
+                telegram_chat_id = env.telegram_chat_id
+                message = "Upgrade the protocol to ${action.deploy_v2.contract_address}?"
+                approvers = ["alice", "123456789"]
+                timeout_seconds = 3600
+            }
+      "#},
+      }
+    };
+}
+
+lazy_static! {
+    /// Answers are read with `getUpdates`, which acknowledges every update it returns, including
+    /// the answers to the other requests of the bot: the approval requests sent by a bot are
+    /// serialized, so that concurrent requests can't consume each other's answers.
+    static ref APPROVAL_REQUEST_LOCKS: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>> =
+        Mutex::new(HashMap::new());
+}
+
+fn get_approval_request_lock(bot_token: &str) -> Arc<tokio::sync::Mutex<()>> {
+    let mut locks = APPROVAL_REQUEST_LOCKS.lock().unwrap();
+    locks.entry(bot_token.to_string()).or_default().clone()
+}
+
+pub struct TelegramRequestApproval;
+impl CommandImplementation for TelegramRequestApproval {
+    fn check_instantiability(
+        spec: &CommandSpecification,
+        _args: Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        Ok(get_command_outputs_type(spec))
+    }
+
+    fn check_executability(
+        _construct_id: &ConstructDid,
+        _instance_name: &str,
+        _spec: &CommandSpecification,
+        values: &ValueStore,
+        _supervision_context: &RunbookSupervisionContext,
+    ) -> Result<Actions, Diagnostic> {
+        let _ = build_bot(values)?;
+        let _ = get_chat_recipient(values)?;
+        let _ = get_parse_mode(values)?;
+        let _ = get_approvers(values)?;
+        Ok(Actions::none())
+    }
+
+    fn run_execution(
+        construct_id: &ConstructDid,
+        _spec: &CommandSpecification,
+        values: &ValueStore,
+        _progress_tx: &txtx_addon_kit::channel::Sender<BlockEvent>,
+    ) -> CommandExecutionFutureResult {
+        let bot = build_bot(values)?;
+        let recipient = get_chat_recipient(values)?;
+        let parse_mode = get_parse_mode(values)?;
+        let message = values.get_expected_string(MESSAGE)?.to_string();
+        let approvers = get_approvers(values)?;
+        let timeout = values
+            .get_uint(TIMEOUT_SECONDS)
+            .map_err(|e| diagnosed_error!("{}", e))?
+            .map(Duration::from_secs);
+
+        // callback data is limited to 64 bytes, so only a prefix of the construct did is used
+        // to identify the answers to this request
+        let request_id = construct_id.to_string()[..32].to_string();
+        let approve_data = format!("{}:{}", APPROVE_CALLBACK_PREFIX, request_id);
+        let reject_data = format!("{}:{}", REJECT_CALLBACK_PREFIX, request_id);
+
+        let approval_request_lock =
+            get_approval_request_lock(values.get_expected_string(TELEGRAM_BOT_API_TOKEN)?);
+
+        let future = async move {
+            let mut result = CommandExecutionResult::new();
+
+            let started_at = Instant::now();
+            let _approval_request_guard = match timeout {
+                Some(timeout) => {
+                    tokio::time::timeout(timeout, approval_request_lock.lock_owned()).await.map_err(
+                        |_| {
+                            diagnosed_error!(
+                                "approval request timed out after {} seconds, waiting for the other requests of the bot to be answered",
+                                timeout.as_secs()
+                            )
+                        },
+                    )?
+                }
+                None => approval_request_lock.lock_owned().await,
+            };
+
+            let keyboard = InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback("Approve", approve_data.clone()),
+                InlineKeyboardButton::callback("Reject", reject_data.clone()),
+            ]]);
+            let mut request = bot.send_message(recipient, message).reply_markup(keyboard);
+            if let Some(parse_mode) = parse_mode {
+                request = request.parse_mode(parse_mode);
+            }
+            let sent_message =
+                request.await.map_err(|e| diagnosed_request_error("sendMessage", e))?;
+
+            let mut offset = 0;
+            let (approved, answered_by) = 'polling: loop {
+                let polling_timeout = match timeout {
+                    Some(timeout) => {
+                        let remaining = timeout.saturating_sub(started_at.elapsed());
+                        if remaining.is_zero() {
+                            return Err(diagnosed_error!(
+                                "approval request timed out after {} seconds",
+                                timeout.as_secs()
+                            ));
+                        }
+                        DEFAULT_POLLING_TIMEOUT_SECONDS.min(remaining.as_secs().max(1) as u32)
+                    }
+                    None => DEFAULT_POLLING_TIMEOUT_SECONDS,
+                };
+
+                let updates = bot
+                    .get_updates()
+                    .offset(offset)
+                    .timeout(polling_timeout)
+                    .allowed_updates(vec![AllowedUpdate::CallbackQuery])
+                    .await
+                    .map_err(|e| diagnosed_request_error("getUpdates", e))?;
+
+                for update in updates {
+                    offset = update.id.as_offset() + 1;
+                    let UpdateKind::CallbackQuery(query) = update.kind else {
+                        continue;
+                    };
+                    let approved = match query.data.as_deref() {
+                        Some(data) if data == approve_data => true,
+                        Some(data) if data == reject_data => false,
+                        // answers to former requests
+                        _ => continue,
+                    };
+
+                    if !is_approver(&query.from, &approvers) {
+                        bot.answer_callback_query(query.id.clone())
+                            .text("You are not allowed to answer this request")
+                            .show_alert(true)
+                            .await
+                            .map_err(|e| diagnosed_request_error("answerCallbackQuery", e))?;
+                        continue;
+                    }
+
+                    bot.answer_callback_query(query.id.clone())
+                        .await
+                        .map_err(|e| diagnosed_request_error("answerCallbackQuery", e))?;
+                    break 'polling (approved, display_user(&query.from));
+                }
+            };
+
+            // acknowledge the update consumed, and remove the buttons from the request
+            let _ = bot.get_updates().offset(offset).timeout(0).await;
+            bot.edit_message_reply_markup(sent_message.chat.id, sent_message.id)
+                .await
+                .map_err(|e| diagnosed_request_error("editMessageReplyMarkup", e))?;
+            let status = if approved { "Approved" } else { "Rejected" };
+            bot.send_message(sent_message.chat.id, format!("{} by {}", status, answered_by))
+                .await
+                .map_err(|e| diagnosed_request_error("sendMessage", e))?;
+
+            if !approved {
+                return Err(diagnosed_error!("approval request rejected by {}", answered_by));
+            }
+
+            result.outputs.insert(MESSAGE_ID.into(), Value::integer(sent_message.id.0.into()));
+            result.outputs.insert(APPROVED.into(), Value::bool(true));
+            result.outputs.insert(APPROVED_BY.into(), Value::string(answered_by));
+            Ok(result)
+        };
+        Ok(Box::pin(future))
+    }
+}
+
+fn get_approvers(values: &ValueStore) -> Result<Vec<String>, Diagnostic> {
+    let approvers = values
+        .get_expected_array(APPROVERS)?
+        .iter()
+        .map(|approver| match approver {
+            Value::String(approver) => Ok(approver.trim_start_matches('@').to_string()),
+            Value::Integer(user_id) => Ok(user_id.to_string()),
+            _ => Err(diagnosed_error!("invalid approver: expected a username or a user id")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if approvers.is_empty() {
+        return Err(diagnosed_error!("at least one approver is required"));
+    }
+    Ok(approvers)
+}
+
+fn is_approver(user: &User, approvers: &[String]) -> bool {
+    approvers.iter().any(|approver| {
+        approver.eq(&user.id.0.to_string())
+            || user.username.as_ref().map_or(false, |username| approver.eq(username))
+    })
+}
+
+fn display_user(user: &User) -> String {
+    match &user.username {
+        Some(username) => format!("@{}", username),
+        None => user.id.0.to_string(),
+    }
+}
@@ -5,16 +5,22 @@ use txtx_addon_kit::types::commands::{
 };
 use txtx_addon_kit::types::frontend::{Actions, BlockEvent};
 use txtx_addon_kit::types::stores::ValueStore;
-use txtx_addon_kit::types::types::RunbookSupervisionContext;
+use txtx_addon_kit::types::types::{RunbookSupervisionContext, Value};
 use txtx_addon_kit::types::ConstructDid;
 use txtx_addon_kit::types::{commands::CommandSpecification, diagnostics::Diagnostic, types::Type};
 
+use crate::bot::{build_bot, diagnosed_request_error, get_chat_recipient, get_parse_mode};
+use crate::constants::{MESSAGE, MESSAGE_ID};
+use crate::typing::get_command_outputs_type;
+
 lazy_static! {
     pub static ref TELEGRAM_SEND_MESSAGE: PreCommandSpecification = define_command! {
       TelegramSendMessage => {
           name: "Send Telegram Message",
           matcher: "send_message",
-          documentation: "The `telegram::send_message` ...",
+          documentation: txtx_addon_kit::indoc! {r#"
+            The `telegram::send_message` action sends a message to a Telegram chat through a bot.
+            The message can reference the outputs of other constructs, and be formatted with Markdown or HTML."#},
           implements_signing_capability: false,
           implements_background_task_capability: false,
           inputs: [
@@ -26,38 +32,54 @@ lazy_static! {
                 internal: false
             },
             telegram_bot_api_token: {
-              documentation: "Coming soon",
+              documentation: "The API token of the bot sending the message, as provided by @BotFather.",
               typing: Type::string(),
               optional: false,
               tainting: false,
                 internal: false
             },
             telegram_chat_id: {
-                documentation: "Coming soon",
+                documentation: "The id of the chat to send the message to, as returned by `telegram::setup_chat`, or the username of a channel (`@channel_name`).",
                 typing: Type::string(),
                 optional: false,
                 tainting: true,
                 internal: false
             },
+            telegram_api_url: {
+                documentation: "The url of the Telegram Bot API. Defaults to `https://api.telegram.org`.",
+                typing: Type::string(),
+                optional: true,
+                tainting: false,
+                internal: false
+            },
             message: {
-                documentation: "Message to send.",
+                documentation: "The message to send. Construct outputs can be interpolated in the message (`${action.deploy.contract_address}`).",
                 typing: Type::string(),
                 optional: false,
                 tainting: true,
                 internal: false
+            },
+            parse_mode: {
+                documentation: "The formatting of the message: 'markdown' (Telegram's MarkdownV2), 'html' or 'text'. Defaults to 'text'. Interpolated values can be escaped for Markdown with `telegram::escape_markdown`.",
+                typing: Type::string(),
+                optional: true,
+                tainting: true,
+                internal: false
             }
           ],
           outputs: [
-              result: {
-                  documentation: "The contract call result.",
-                  typing: Type::string()
+              message_id: {
+                  documentation: "The id of the message sent.",
+                  typing: Type::integer()
               }
           ],
           example: txtx_addon_kit::indoc! {r#"
             action "notify_team" "telegram::send_message" {
                 description = "Notify team"
-                message = "myFunction"
                 telegram_bot_api_token = env.telegram_bot_api_token
+                telegram_chat_id = env.telegram_chat_id
+                parse_mode = "markdown"
+                message = "Contract deployed at `${action.deploy.contract_address}`"
             }
       "#},
       }
@@ -67,20 +89,24 @@ lazy_static! {
 pub struct TelegramSendMessage;
 impl CommandImplementation for TelegramSendMessage {
     fn check_instantiability(
-        _ctx: &CommandSpecification,
+        spec: &CommandSpecification,
         _args: Vec<Type>,
     ) -> Result<Type, Diagnostic> {
-        unimplemented!()
+        Ok(get_command_outputs_type(spec))
     }
 
     fn check_executability(
         _construct_id: &ConstructDid,
         _instance_name: &str,
         _spec: &CommandSpecification,
-        _values: &ValueStore,
+        values: &ValueStore,
         _supervision_context: &RunbookSupervisionContext,
     ) -> Result<Actions, Diagnostic> {
-        Ok(Actions::none()) // todo
+        // fail early on invalid inputs, rather than when the message is sent
+        let _ = build_bot(values)?;
+        let _ = get_chat_recipient(values)?;
+        let _ = get_parse_mode(values)?;
+        Ok(Actions::none())
     }
 
     fn run_execution(
@@ -89,18 +115,22 @@ impl CommandImplementation for TelegramSendMessage {
         values: &ValueStore,
         _progress_tx: &txtx_addon_kit::channel::Sender<BlockEvent>,
     ) -> CommandExecutionFutureResult {
-        let args = values.clone();
-
-        let telegram_chat_id: i64 =
-            args.get_expected_integer("telegram_chat_id")?.try_into().unwrap();
-        let telegram_bot_api_token =
-            args.get_expected_string("telegram_bot_api_token")?.to_string();
-        let message = args.get_expected_string("message")?.to_string();
+        let bot = build_bot(values)?;
+        let recipient = get_chat_recipient(values)?;
+        let parse_mode = get_parse_mode(values)?;
+        let message = values.get_expected_string(MESSAGE)?.to_string();
 
         let future = async move {
-            let result = CommandExecutionResult::new();
-            let bot = Bot::new(telegram_bot_api_token);
-            let _ = bot.send_message(ChatId(telegram_chat_id), message).await;
+            let mut result = CommandExecutionResult::new();
+
+            let mut request = bot.send_message(recipient, message);
+            if let Some(parse_mode) = parse_mode {
+                request = request.parse_mode(parse_mode);
+            }
+            let sent_message =
+                request.await.map_err(|e| diagnosed_request_error("sendMessage", e))?;
+
+            result.outputs.insert(MESSAGE_ID.into(), Value::integer(sent_message.id.0.into()));
             Ok(result)
         };
         Ok(Box::pin(future))
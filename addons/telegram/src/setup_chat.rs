@@ -9,6 +9,8 @@ use txtx_addon_kit::types::types::RunbookSupervisionContext;
 use txtx_addon_kit::types::ConstructDid;
 use txtx_addon_kit::types::{commands::CommandSpecification, diagnostics::Diagnostic, types::Type};
 
+use crate::typing::get_command_outputs_type;
+
 lazy_static! {
     pub static ref TELEGRAM_SETUP_CHAT: PreCommandSpecification = define_command! {
       TelegramSetupChat => {
@@ -52,10 +54,10 @@ lazy_static! {
 pub struct TelegramSetupChat;
 impl CommandImplementation for TelegramSetupChat {
     fn check_instantiability(
-        _ctx: &CommandSpecification,
+        spec: &CommandSpecification,
         _args: Vec<Type>,
     ) -> Result<Type, Diagnostic> {
-        unimplemented!()
+        Ok(get_command_outputs_type(spec))
     }
 
     fn check_executability(
@@ -0,0 +1,304 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use txtx_addon_kit::serde_json::{self, json, Value as JsonValue};
+use txtx_addon_kit::types::commands::{
+    CommandExecutionResult, CommandImplementation, CommandSpecification, PreCommandSpecification,
+};
+use txtx_addon_kit::types::diagnostics::Diagnostic;
+use txtx_addon_kit::types::frontend::BlockEvent;
+use txtx_addon_kit::types::stores::ValueStore;
+use txtx_addon_kit::types::types::Value;
+use txtx_addon_kit::types::{ConstructDid, Did};
+
+use crate::constants::{
+    APPROVED, APPROVED_BY, APPROVERS, MESSAGE, MESSAGE_ID, PARSE_MODE, TELEGRAM_API_URL,
+    TELEGRAM_BOT_API_TOKEN, TELEGRAM_CHAT_ID, TIMEOUT_SECONDS,
+};
+use crate::request_approval::{TelegramRequestApproval, TELEGRAM_REQUEST_APPROVAL};
+use crate::send_message::{TelegramSendMessage, TELEGRAM_SEND_MESSAGE};
+
+type Handler = dyn Fn(&str, &JsonValue) -> JsonValue + Send + Sync;
+
+/// Local stub of the Telegram Bot API, answering each method call with `handler`, and recording
+/// the calls received.
+struct BotApiStub {
+    url: String,
+    calls: Arc<Mutex<Vec<(String, JsonValue)>>>,
+}
+
+impl BotApiStub {
+    fn start(handler: impl Fn(&str, &JsonValue) -> JsonValue + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let calls = Arc::new(Mutex::new(vec![]));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let moved_calls = calls.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let handler = handler.clone();
+                let calls = moved_calls.clone();
+                std::thread::spawn(move || serve_connection(stream, handler, calls));
+            }
+        });
+        Self { url, calls }
+    }
+
+    fn calls(&self, method: &str) -> Vec<JsonValue> {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(m, _)| m.eq(method))
+            .map(|(_, params)| params.clone())
+            .collect()
+    }
+}
+
+fn serve_connection(
+    stream: TcpStream,
+    handler: Arc<Handler>,
+    calls: Arc<Mutex<Vec<(String, JsonValue)>>>,
+) {
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+    loop {
+        // request line: `POST /bot<token>/<method> HTTP/1.1`
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+            return;
+        }
+        let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+        let method = path.rsplit('/').next().unwrap_or_default().to_string();
+
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).unwrap_or(0) == 0 {
+                return;
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+        let params = serde_json::from_slice(&body).unwrap_or(JsonValue::Null);
+
+        let response = handler(&method, &params);
+        calls.lock().unwrap().push((method, params));
+        let status = match response["ok"].as_bool() {
+            Some(true) => "200 OK".to_string(),
+            _ => format!("{} Error", response["error_code"].as_u64().unwrap_or(400)),
+        };
+        let response = response.to_string();
+        let http_response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            response.len(),
+            response
+        );
+        if writer.write_all(http_response.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+fn ok(result: JsonValue) -> JsonValue {
+    json!({ "ok": true, "result": result })
+}
+
+fn api_error(error_code: u16, description: &str) -> JsonValue {
+    json!({ "ok": false, "error_code": error_code, "description": description })
+}
+
+fn message(message_id: i32, text: &str) -> JsonValue {
+    json!({
+        "message_id": message_id,
+        "date": 1_700_000_000,
+        "chat": { "id": -100, "type": "private", "first_name": "Deployments" },
+        "from": { "id": 1, "is_bot": true, "first_name": "txtx" },
+        "text": text,
+    })
+}
+
+fn user(id: u64, username: &str) -> JsonValue {
+    json!({ "id": id, "is_bot": false, "first_name": username, "username": username })
+}
+
+fn callback_query(update_id: i32, from: JsonValue, data: &str) -> JsonValue {
+    json!({
+        "update_id": update_id,
+        "callback_query": {
+            "id": format!("query-{}", update_id),
+            "from": from,
+            "chat_instance": "chat-instance",
+            "data": data,
+        },
+    })
+}
+
+fn command_spec(spec: &PreCommandSpecification) -> CommandSpecification {
+    match spec {
+        PreCommandSpecification::Atomic(spec) => spec.clone(),
+        PreCommandSpecification::Composite(_) => unreachable!(),
+    }
+}
+
+fn values(stub: &BotApiStub, bot_token: &str, inputs: Vec<(&str, Value)>) -> ValueStore {
+    let mut values = ValueStore::new("telegram", &Did::zero());
+    values.insert(TELEGRAM_BOT_API_TOKEN, Value::string(bot_token.to_string()));
+    values.insert(TELEGRAM_API_URL, Value::string(stub.url.clone()));
+    values.insert(TELEGRAM_CHAT_ID, Value::string("-100".to_string()));
+    for (key, value) in inputs {
+        values.insert(key, value);
+    }
+    values
+}
+
+async fn send_message(values: &ValueStore) -> Result<CommandExecutionResult, Diagnostic> {
+    let (progress_tx, _progress_rx) = txtx_addon_kit::channel::unbounded::<BlockEvent>();
+    let construct_did = ConstructDid(Did::from_components(vec!["notify_team"]));
+    let spec = command_spec(&TELEGRAM_SEND_MESSAGE);
+    TelegramSendMessage::run_execution(&construct_did, &spec, values, &progress_tx)?.await
+}
+
+async fn request_approval(
+    construct_did: &ConstructDid,
+    values: &ValueStore,
+) -> Result<CommandExecutionResult, Diagnostic> {
+    let (progress_tx, _progress_rx) = txtx_addon_kit::channel::unbounded::<BlockEvent>();
+    let spec = command_spec(&TELEGRAM_REQUEST_APPROVAL);
+    TelegramRequestApproval::run_execution(construct_did, &spec, values, &progress_tx)?.await
+}
+
+/// Stub answering approval requests with `updates`, one update per `getUpdates` call.
+fn approval_stub(updates: Vec<JsonValue>) -> BotApiStub {
+    let updates = Mutex::new(VecDeque::from(updates));
+    BotApiStub::start(move |method, params| match method {
+        "sendMessage" => ok(message(42, params["text"].as_str().unwrap_or_default())),
+        "editMessageReplyMarkup" => ok(message(42, "Approve the protocol upgrade")),
+        "answerCallbackQuery" => ok(json!(true)),
+        "getUpdates" => {
+            // the last call only acknowledges the updates consumed
+            if params["timeout"].as_u64() == Some(0) {
+                return ok(json!([]));
+            }
+            let update = updates.lock().unwrap().pop_front();
+            ok(json!(update.into_iter().collect::<Vec<_>>()))
+        }
+        _ => api_error(404, "Not Found: method not found"),
+    })
+}
+
+fn approval_values(stub: &BotApiStub, bot_token: &str) -> ValueStore {
+    values(
+        stub,
+        bot_token,
+        vec![
+            (MESSAGE, Value::string("Approve the protocol upgrade".to_string())),
+            (APPROVERS, Value::array(vec![Value::string("@alice".to_string())])),
+            (TIMEOUT_SECONDS, Value::integer(10)),
+        ],
+    )
+}
+
+fn callback_data(prefix: &str, construct_did: &ConstructDid) -> String {
+    format!("{}:{}", prefix, &construct_did.to_string()[..32])
+}
+
+#[tokio::test]
+async fn it_sends_messages() {
+    let stub = BotApiStub::start(|method, params| match method {
+        "sendMessage" => ok(message(42, params["text"].as_str().unwrap_or_default())),
+        _ => api_error(404, "Not Found: method not found"),
+    });
+    let values = values(
+        &stub,
+        "1:send",
+        vec![
+            (MESSAGE, Value::string("*Deployed*".to_string())),
+            (PARSE_MODE, Value::string("markdown".to_string())),
+        ],
+    );
+
+    let result = send_message(&values).await.unwrap();
+    assert_eq!(result.outputs.get(MESSAGE_ID).and_then(|v| v.as_integer()), Some(42));
+
+    let calls = stub.calls("sendMessage");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0]["chat_id"], json!(-100));
+    assert_eq!(calls[0]["text"], json!("*Deployed*"));
+    assert_eq!(calls[0]["parse_mode"], json!("MarkdownV2"));
+}
+
+#[tokio::test]
+async fn it_reports_api_errors() {
+    let stub = BotApiStub::start(|_, _| api_error(400, "Bad Request: chat not found"));
+    let values = values(&stub, "1:error", vec![(MESSAGE, Value::string("hello".to_string()))]);
+
+    let Err(diagnostic) = send_message(&values).await else {
+        panic!("expected the message to be rejected");
+    };
+    assert!(diagnostic.message.contains("Telegram API rejected 'sendMessage'"));
+    assert!(diagnostic.message.contains("chat not found"));
+}
+
+#[tokio::test]
+async fn it_approves_requests_answered_by_approvers() {
+    let construct_did = ConstructDid(Did::from_components(vec!["approve_upgrade"]));
+    let approve_data = callback_data("approve", &construct_did);
+    let stub = approval_stub(vec![
+        // answer to another request
+        callback_query(1, user(100, "alice"), "approve:another-request"),
+        // answer from a user who is not an approver
+        callback_query(2, user(200, "mallory"), &approve_data),
+        callback_query(3, user(100, "alice"), &approve_data),
+    ]);
+    let values = approval_values(&stub, "1:approve");
+
+    let result = request_approval(&construct_did, &values).await.unwrap();
+    assert_eq!(result.outputs.get(MESSAGE_ID).and_then(|v| v.as_integer()), Some(42));
+    assert_eq!(result.outputs.get(APPROVED).and_then(|v| v.as_bool()), Some(true));
+    assert_eq!(result.outputs.get(APPROVED_BY).and_then(|v| v.as_string()), Some("@alice"));
+
+    let answers = stub.calls("answerCallbackQuery");
+    assert_eq!(answers.len(), 2);
+    assert_eq!(answers[0]["callback_query_id"], json!("query-2"));
+    assert_eq!(answers[0]["show_alert"], json!(true));
+    assert_eq!(answers[1]["callback_query_id"], json!("query-3"));
+    assert_eq!(stub.calls("editMessageReplyMarkup").len(), 1);
+    let messages = stub.calls("sendMessage");
+    assert_eq!(messages.last().unwrap()["text"], json!("Approved by @alice"));
+}
+
+#[tokio::test]
+async fn it_fails_rejected_requests() {
+    let construct_did = ConstructDid(Did::from_components(vec!["approve_upgrade"]));
+    let stub = approval_stub(vec![callback_query(
+        1,
+        user(100, "alice"),
+        &callback_data("reject", &construct_did),
+    )]);
+    let values = approval_values(&stub, "1:reject");
+
+    let Err(diagnostic) = request_approval(&construct_did, &values).await else {
+        panic!("expected the request to be rejected");
+    };
+    assert!(diagnostic.message.contains("rejected by @alice"));
+    let messages = stub.calls("sendMessage");
+    assert_eq!(messages.last().unwrap()["text"], json!("Rejected by @alice"));
+}
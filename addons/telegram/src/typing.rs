@@ -0,0 +1,19 @@
+use txtx_addon_kit::types::commands::CommandSpecification;
+use txtx_addon_kit::types::types::{ObjectProperty, Type};
+
+/// Type of the object an action evaluates to, exposing the outputs of its specification.
+pub fn get_command_outputs_type(spec: &CommandSpecification) -> Type {
+    let props = spec
+        .outputs
+        .iter()
+        .map(|output| ObjectProperty {
+            name: output.name.clone(),
+            documentation: output.documentation.clone(),
+            typing: output.typing.clone(),
+            optional: false,
+            tainting: false,
+            internal: false,
+        })
+        .collect();
+    Type::strict_object(props)
+}
@@ -236,7 +236,9 @@ impl RawHclContent {
 
     pub fn into_blocks(&self) -> Result<VecDeque<Block>, Diagnostic> {
         let content = crate::hcl::parser::parse_body(&self.0).map_err(|e| {
+            let offset = e.location().offset();
             Diagnostic::error_from_string(format!("parsing error: {}", e.to_string()))
+                .set_span_range(Some(offset..offset))
         })?;
         Ok(content.into_blocks().into_iter().collect::<VecDeque<Block>>())
     }
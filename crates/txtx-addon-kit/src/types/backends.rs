@@ -0,0 +1,630 @@
+use std::fmt::Debug;
+use std::io::{ErrorKind, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::fs::FileLocation;
+
+/// Storage of the runbooks execution states.
+/// Entries are addressed by keys such as `deploy.mainnet.tx-state.json`.
+pub trait StateBackend: Debug + Send + Sync {
+    /// Human readable location of an entry, used for display purposes
+    fn describe(&self, key: &str) -> String;
+    /// Returns the content of an entry, or `None` if the entry does not exist
+    fn read(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+    /// Creates or overwrites an entry
+    fn write(&self, key: &str, content: &[u8]) -> Result<(), String>;
+    /// Atomically creates an entry. Returns false if the entry already exists.
+    fn write_if_absent(&self, key: &str, content: &[u8]) -> Result<bool, String>;
+    /// Removes an entry. Removing an entry that does not exist is not an error.
+    fn delete(&self, key: &str) -> Result<(), String>;
+
+    fn exists(&self, key: &str) -> Result<bool, String> {
+        Ok(self.read(key)?.is_some())
+    }
+}
+
+/// Backend storing a state, as configured per environment in the `state_backends` section of
+/// the txtx.yml manifest:
+///
+/// ```yaml
+/// state_backends:
+///   mainnet:
+///     type: s3
+///     bucket: deployments
+///     region: us-east-1
+///     endpoint: http://localhost:9000
+///     prefix: txtx
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StateBackendConfig {
+    /// State files are stored on the local filesystem, in the location configured by the runbook
+    Local,
+    /// State files are stored in a bucket of an S3-compatible object storage
+    S3(S3StateBackendConfig),
+}
+
+impl StateBackendConfig {
+    pub fn build(&self, local_location: &FileLocation) -> Result<Box<dyn StateBackend>, String> {
+        match self {
+            StateBackendConfig::Local => Ok(Box::new(LocalStateBackend::new(local_location))),
+            #[cfg(not(feature = "wasm"))]
+            StateBackendConfig::S3(config) => Ok(Box::new(S3StateBackend::new(config)?)),
+            #[cfg(feature = "wasm")]
+            StateBackendConfig::S3(_) => Err(format!("s3 state backend not supported")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct S3StateBackendConfig {
+    /// Name of the bucket
+    pub bucket: String,
+    /// Region of the bucket (defaults to `us-east-1`)
+    pub region: Option<String>,
+    /// Endpoint of the object storage (defaults to `https://s3.<region>.amazonaws.com`)
+    pub endpoint: Option<String>,
+    /// Prefix of the keys of the state files
+    pub prefix: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalStateBackend {
+    location: FileLocation,
+}
+
+impl LocalStateBackend {
+    pub fn new(location: &FileLocation) -> Self {
+        Self { location: location.clone() }
+    }
+
+    pub fn get_location(&self, key: &str) -> Result<FileLocation, String> {
+        let mut location = self.location.clone();
+        location.append_path(key)?;
+        Ok(location)
+    }
+}
+
+impl StateBackend for LocalStateBackend {
+    fn describe(&self, key: &str) -> String {
+        match self.get_location(key) {
+            Ok(location) => location.to_string(),
+            Err(_) => format!("{}/{}", self.location, key),
+        }
+    }
+
+    fn read(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let location = self.get_location(key)?;
+        if !location.exists() {
+            return Ok(None);
+        }
+        location.read_content().map(Some)
+    }
+
+    fn write(&self, key: &str, content: &[u8]) -> Result<(), String> {
+        self.get_location(key)?.write_content(content)
+    }
+
+    fn write_if_absent(&self, key: &str, content: &[u8]) -> Result<bool, String> {
+        let location = self.get_location(key)?;
+        let path = location.expect_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("unable to create {}: {}", parent.display(), e))?;
+        }
+        let mut file = match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => return Ok(false),
+            Err(e) => return Err(format!("unable to create {}: {}", location, e)),
+        };
+        file.write_all(content).map_err(|e| format!("unable to write {}: {}", location, e))?;
+        Ok(true)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        let location = self.get_location(key)?;
+        match std::fs::remove_file(location.to_string()) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("unable to remove {}: {}", location, e)),
+        }
+    }
+
+    fn exists(&self, key: &str) -> Result<bool, String> {
+        Ok(self.get_location(key)?.exists())
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+pub use s3::{S3Credentials, S3StateBackend};
+
+#[cfg(not(feature = "wasm"))]
+mod s3 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use hmac::{Hmac, Mac};
+    use reqwest::blocking::Client;
+    use reqwest::{Method, StatusCode, Url};
+    use sha2::{Digest, Sha256};
+
+    use super::{S3StateBackendConfig, StateBackend};
+
+    pub const AWS_ACCESS_KEY_ID: &str = "AWS_ACCESS_KEY_ID";
+    pub const AWS_SECRET_ACCESS_KEY: &str = "AWS_SECRET_ACCESS_KEY";
+    pub const AWS_SESSION_TOKEN: &str = "AWS_SESSION_TOKEN";
+    const DEFAULT_REGION: &str = "us-east-1";
+
+    #[derive(Clone)]
+    pub struct S3Credentials {
+        pub access_key_id: String,
+        pub secret_access_key: String,
+        pub session_token: Option<String>,
+    }
+
+    impl S3Credentials {
+        pub fn from_env() -> Result<Self, String> {
+            let access_key_id = std::env::var(AWS_ACCESS_KEY_ID)
+                .map_err(|_| format!("s3 state backend: {} not set", AWS_ACCESS_KEY_ID))?;
+            let secret_access_key = std::env::var(AWS_SECRET_ACCESS_KEY)
+                .map_err(|_| format!("s3 state backend: {} not set", AWS_SECRET_ACCESS_KEY))?;
+            let session_token = std::env::var(AWS_SESSION_TOKEN).ok();
+            Ok(Self { access_key_id, secret_access_key, session_token })
+        }
+    }
+
+    impl std::fmt::Debug for S3Credentials {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("S3Credentials").field("access_key_id", &self.access_key_id).finish()
+        }
+    }
+
+    /// State backend for S3-compatible object storages (AWS S3, MinIO, R2, ...).
+    /// Objects are addressed path-style and requests are authenticated with AWS Signature V4.
+    #[derive(Clone, Debug)]
+    pub struct S3StateBackend {
+        bucket: String,
+        region: String,
+        endpoint: Url,
+        prefix: Option<String>,
+        credentials: S3Credentials,
+    }
+
+    struct SignedRequest {
+        method: Method,
+        url: Url,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    }
+
+    impl S3StateBackend {
+        pub fn new(config: &S3StateBackendConfig) -> Result<Self, String> {
+            Self::new_with_credentials(config, S3Credentials::from_env()?)
+        }
+
+        pub fn new_with_credentials(
+            config: &S3StateBackendConfig,
+            credentials: S3Credentials,
+        ) -> Result<Self, String> {
+            let region = config.region.clone().unwrap_or(DEFAULT_REGION.to_string());
+            let endpoint =
+                config.endpoint.clone().unwrap_or(format!("https://s3.{}.amazonaws.com", region));
+            let endpoint = Url::parse(&endpoint)
+                .map_err(|e| format!("s3 state backend: invalid endpoint {}: {}", endpoint, e))?;
+            let prefix = config
+                .prefix
+                .as_ref()
+                .map(|p| p.trim_matches('/').to_string())
+                .filter(|p| !p.is_empty());
+            Ok(Self { bucket: config.bucket.clone(), region, endpoint, prefix, credentials })
+        }
+
+        fn object_key(&self, key: &str) -> String {
+            match &self.prefix {
+                Some(prefix) => format!("{}/{}", prefix, key),
+                None => key.to_string(),
+            }
+        }
+
+        fn object_path(&self, key: &str) -> String {
+            let base_path = self.endpoint.path().trim_end_matches('/');
+            format!(
+                "{}/{}/{}",
+                base_path,
+                uri_encode(&self.bucket),
+                uri_encode(&self.object_key(key))
+            )
+        }
+
+        fn sign_request(
+            &self,
+            method: Method,
+            key: &str,
+            body: Vec<u8>,
+            extra_headers: Vec<(String, String)>,
+            now: SystemTime,
+        ) -> Result<SignedRequest, String> {
+            let path = self.object_path(key);
+            let mut url = self.endpoint.clone();
+            url.set_path(&path);
+
+            let host = match (self.endpoint.host_str(), self.endpoint.port()) {
+                (Some(host), Some(port)) => format!("{}:{}", host, port),
+                (Some(host), None) => host.to_string(),
+                (None, _) => return Err(format!("s3 state backend: invalid endpoint {}", url)),
+            };
+            let (date, amz_date) = format_amz_dates(now);
+            let payload_hash = hex::encode(Sha256::digest(&body));
+
+            let mut headers = vec![
+                ("host".to_string(), host),
+                ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+                ("x-amz-date".to_string(), amz_date.clone()),
+            ];
+            if let Some(session_token) = &self.credentials.session_token {
+                headers.push(("x-amz-security-token".to_string(), session_token.clone()));
+            }
+            headers.extend(extra_headers.into_iter().map(|(k, v)| (k.to_lowercase(), v)));
+            headers.sort_by(|a, b| a.0.cmp(&b.0));
+
+            let canonical_headers =
+                headers.iter().map(|(k, v)| format!("{}:{}\n", k, v.trim())).collect::<String>();
+            let signed_headers =
+                headers.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>().join(";");
+            let canonical_request = format!(
+                "{}\n{}\n\n{}\n{}\n{}",
+                method.as_str(),
+                path,
+                canonical_headers,
+                signed_headers,
+                payload_hash
+            );
+
+            let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+            let string_to_sign = format!(
+                "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+                amz_date,
+                scope,
+                hex::encode(Sha256::digest(canonical_request.as_bytes()))
+            );
+
+            let signing_key =
+                [date.as_str(), self.region.as_str(), "s3", "aws4_request"].iter().fold(
+                    format!("AWS4{}", self.credentials.secret_access_key).into_bytes(),
+                    |key, data| hmac_sha256(&key, data.as_bytes()),
+                );
+            let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+            headers.push((
+                "authorization".to_string(),
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.credentials.access_key_id, scope, signed_headers, signature
+                ),
+            ));
+            // The host header is set by the http client
+            headers.retain(|(k, _)| k != "host");
+
+            Ok(SignedRequest { method, url, headers, body })
+        }
+
+        fn send(
+            &self,
+            method: Method,
+            key: &str,
+            body: Vec<u8>,
+            extra_headers: Vec<(String, String)>,
+        ) -> Result<(StatusCode, Vec<u8>), String> {
+            let request = self.sign_request(method, key, body, extra_headers, SystemTime::now())?;
+            // The blocking client can't be used from within an async runtime: requests are
+            // performed from a dedicated thread.
+            std::thread::spawn(move || {
+                let client = Client::new();
+                let mut builder = client.request(request.method, request.url.clone());
+                for (k, v) in request.headers.iter() {
+                    builder = builder.header(k, v);
+                }
+                let response = builder.body(request.body).send().map_err(|e| {
+                    format!("s3 state backend: request to {} failed: {}", request.url, e)
+                })?;
+                let status = response.status();
+                let bytes = response
+                    .bytes()
+                    .map_err(|e| format!("s3 state backend: unable to read response: {}", e))?;
+                Ok((status, bytes.to_vec()))
+            })
+            .join()
+            .map_err(|_| format!("s3 state backend: request thread panicked"))?
+        }
+
+        fn unexpected_status(&self, key: &str, status: StatusCode, body: &[u8]) -> String {
+            format!(
+                "s3 state backend: unexpected status {} for {}: {}",
+                status,
+                self.describe(key),
+                String::from_utf8_lossy(body)
+            )
+        }
+    }
+
+    impl StateBackend for S3StateBackend {
+        fn describe(&self, key: &str) -> String {
+            format!("s3://{}/{}", self.bucket, self.object_key(key))
+        }
+
+        fn read(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+            let (status, body) = self.send(Method::GET, key, vec![], vec![])?;
+            match status {
+                StatusCode::OK => Ok(Some(body)),
+                StatusCode::NOT_FOUND => Ok(None),
+                _ => Err(self.unexpected_status(key, status, &body)),
+            }
+        }
+
+        fn write(&self, key: &str, content: &[u8]) -> Result<(), String> {
+            let (status, body) = self.send(Method::PUT, key, content.to_vec(), vec![])?;
+            match status {
+                StatusCode::OK => Ok(()),
+                _ => Err(self.unexpected_status(key, status, &body)),
+            }
+        }
+
+        fn write_if_absent(&self, key: &str, content: &[u8]) -> Result<bool, String> {
+            let (status, body) = self.send(
+                Method::PUT,
+                key,
+                content.to_vec(),
+                vec![("if-none-match".to_string(), "*".to_string())],
+            )?;
+            match status {
+                StatusCode::OK => Ok(true),
+                StatusCode::PRECONDITION_FAILED | StatusCode::CONFLICT => Ok(false),
+                _ => Err(self.unexpected_status(key, status, &body)),
+            }
+        }
+
+        fn delete(&self, key: &str) -> Result<(), String> {
+            let (status, body) = self.send(Method::DELETE, key, vec![], vec![])?;
+            match status {
+                StatusCode::OK | StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(()),
+                _ => Err(self.unexpected_status(key, status, &body)),
+            }
+        }
+
+        fn exists(&self, key: &str) -> Result<bool, String> {
+            let (status, body) = self.send(Method::HEAD, key, vec![], vec![])?;
+            match status {
+                StatusCode::OK => Ok(true),
+                StatusCode::NOT_FOUND => Ok(false),
+                _ => Err(self.unexpected_status(key, status, &body)),
+            }
+        }
+    }
+
+    fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any size");
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// Percent-encodes everything but the unreserved characters and the path separators.
+    fn uri_encode(input: &str) -> String {
+        let mut encoded = String::new();
+        for byte in input.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                    encoded.push(byte as char)
+                }
+                _ => encoded.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+        encoded
+    }
+
+    /// Returns the `YYYYMMDD` and `YYYYMMDDTHHMMSSZ` representations of a UTC time.
+    fn format_amz_dates(time: SystemTime) -> (String, String) {
+        let secs = time.duration_since(UNIX_EPOCH).expect("Time went backwards").as_secs();
+        let (days, secs_of_day) = (secs / 86400, secs % 86400);
+        // Civil date from days since epoch (H. Hinnant's algorithm)
+        let z = days as i64 + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        let date = format!("{:04}{:02}{:02}", year, month, day);
+        let amz_date = format!(
+            "{}T{:02}{:02}{:02}Z",
+            date,
+            secs_of_day / 3600,
+            (secs_of_day % 3600) / 60,
+            secs_of_day % 60
+        );
+        (date, amz_date)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::collections::HashMap;
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        use super::*;
+
+        #[test]
+        fn it_formats_amz_dates() {
+            let time = UNIX_EPOCH + Duration::from_secs(1440938160);
+            assert_eq!(
+                format_amz_dates(time),
+                ("20150830".to_string(), "20150830T123600Z".to_string())
+            );
+        }
+
+        #[test]
+        fn it_encodes_object_keys() {
+            assert_eq!(
+                uri_encode("txtx/deploy.main net.tx-state.json"),
+                "txtx/deploy.main%20net.tx-state.json"
+            );
+        }
+
+        /// Minimal in-memory stand-in for an S3-compatible object storage, serving GET, HEAD,
+        /// PUT (with `If-None-Match: *` support) and DELETE requests.
+        fn start_object_storage_stand_in() -> String {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = listener.local_addr().unwrap();
+            let objects: Arc<Mutex<HashMap<String, Vec<u8>>>> =
+                Arc::new(Mutex::new(HashMap::new()));
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else {
+                        continue;
+                    };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut parts = request_line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let path = parts.next().unwrap_or_default().to_string();
+
+                    let mut headers = HashMap::new();
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        let line = line.trim_end();
+                        if line.is_empty() {
+                            break;
+                        }
+                        if let Some((k, v)) = line.split_once(':') {
+                            headers.insert(k.trim().to_lowercase(), v.trim().to_string());
+                        }
+                    }
+                    let content_length =
+                        headers.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+                    let mut body = vec![0; content_length];
+                    reader.read_exact(&mut body).unwrap();
+
+                    let (status, response_body) = if !headers.contains_key("authorization") {
+                        ("403 Forbidden", vec![])
+                    } else {
+                        let mut objects = objects.lock().unwrap();
+                        match method.as_str() {
+                            "GET" | "HEAD" => match objects.get(&path) {
+                                Some(content) if method == "GET" => ("200 OK", content.clone()),
+                                Some(_) => ("200 OK", vec![]),
+                                None => ("404 Not Found", vec![]),
+                            },
+                            "PUT" => {
+                                if headers.get("if-none-match").map(|v| v == "*").unwrap_or(false)
+                                    && objects.contains_key(&path)
+                                {
+                                    ("412 Precondition Failed", vec![])
+                                } else {
+                                    objects.insert(path, body);
+                                    ("200 OK", vec![])
+                                }
+                            }
+                            "DELETE" => {
+                                objects.remove(&path);
+                                ("204 No Content", vec![])
+                            }
+                            _ => ("405 Method Not Allowed", vec![]),
+                        }
+                    };
+                    let content_length = if method == "HEAD" { 0 } else { response_body.len() };
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                        status, content_length
+                    );
+                    if method != "HEAD" {
+                        let _ = stream.write_all(&response_body);
+                    }
+                }
+            });
+            format!("http://{}", address)
+        }
+
+        #[test]
+        fn it_stores_states_in_an_s3_compatible_storage() {
+            let endpoint = start_object_storage_stand_in();
+            let backend = S3StateBackend::new_with_credentials(
+                &S3StateBackendConfig {
+                    bucket: "deployments".into(),
+                    region: None,
+                    endpoint: Some(endpoint),
+                    prefix: Some("/txtx/".into()),
+                },
+                S3Credentials {
+                    access_key_id: "minioadmin".into(),
+                    secret_access_key: "minioadmin".into(),
+                    session_token: None,
+                },
+            )
+            .unwrap();
+
+            let key = "deploy.mainnet.tx-state.json";
+            assert_eq!(backend.describe(key), "s3://deployments/txtx/deploy.mainnet.tx-state.json");
+            assert_eq!(backend.read(key).unwrap(), None);
+            assert!(!backend.exists(key).unwrap());
+
+            backend.write(key, b"{}").unwrap();
+            assert_eq!(backend.read(key).unwrap(), Some(b"{}".to_vec()));
+            assert!(backend.exists(key).unwrap());
+
+            assert!(!backend.write_if_absent(key, b"[]").unwrap());
+            assert_eq!(backend.read(key).unwrap(), Some(b"{}".to_vec()));
+
+            backend.delete(key).unwrap();
+            assert_eq!(backend.read(key).unwrap(), None);
+            assert!(backend.write_if_absent(key, b"[]").unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_backends_configs() {
+        let config: StateBackendConfig = serde_json::from_str(
+            r#"{"type": "s3", "bucket": "deployments", "endpoint": "http://localhost:9000"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            StateBackendConfig::S3(S3StateBackendConfig {
+                bucket: "deployments".into(),
+                region: None,
+                endpoint: Some("http://localhost:9000".into()),
+                prefix: None,
+            })
+        );
+        let config: StateBackendConfig = serde_json::from_str(r#"{"type": "local"}"#).unwrap();
+        assert_eq!(config, StateBackendConfig::Local);
+    }
+
+    #[test]
+    fn it_stores_states_on_the_filesystem() {
+        let dir = std::env::temp_dir().join(format!("txtx-state-backend-{}", std::process::id()));
+        let backend = LocalStateBackend::new(&FileLocation::from_path(dir.clone()));
+
+        let key = "deploy.devnet.tx-state.json";
+        assert_eq!(backend.read(key).unwrap(), None);
+        assert!(backend.write_if_absent(key, b"{}").unwrap());
+        assert!(!backend.write_if_absent(key, b"[]").unwrap());
+        assert_eq!(backend.read(key).unwrap(), Some(b"{}".to_vec()));
+        backend.delete(key).unwrap();
+        backend.delete(key).unwrap();
+        assert!(!backend.exists(key).unwrap());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -13,6 +13,7 @@ use types::{ObjectDefinition, ObjectProperty, Type};
 
 use crate::helpers::fs::FileLocation;
 
+pub mod backends;
 pub mod block_id;
 pub mod cloud_interface;
 pub mod commands;
@@ -384,6 +384,11 @@ impl SignerInstance {
                                 Value::string(update.public_key.clone()),
                             );
                         }
+                        ActionItemResponseType::ProvideInput(update) => {
+                            // provided inputs are only kept in memory, and are never added to
+                            // the evaluated inputs of the signer
+                            values.insert(&update.input_name, update.updated_value.clone());
+                        }
                         ActionItemResponseType::ReviewInput(response) => {
                             let request = action_item_requests
                                 .map(|requests| requests.iter().find(|r| r.id.eq(&action_item_id)));
@@ -51,6 +51,35 @@ fn it_deserializes_values(val: JsonValue) {
         .unwrap();
 }
 
+#[test]
+fn it_converts_json_documents() {
+    let json = json!({
+        "name": "rollup",
+        "chain_id": 42069,
+        "gas_limit": 18446744073709551615u64,
+        "fee_ratio": 0.5,
+        "enabled": true,
+        "genesis": null,
+        "addresses": ["0x01", "0x02"],
+    });
+    let value = Value::from_json(&json);
+    let object = value.as_object().unwrap();
+    assert_eq!(object.get("name"), Some(&Value::string("rollup".to_string())));
+    assert_eq!(object.get("chain_id"), Some(&Value::integer(42069)));
+    assert_eq!(object.get("gas_limit"), Some(&Value::integer(u64::MAX.into())));
+    assert_eq!(object.get("fee_ratio"), Some(&Value::float(0.5)));
+    assert_eq!(object.get("enabled"), Some(&Value::bool(true)));
+    assert_eq!(object.get("genesis"), Some(&Value::null()));
+    assert_eq!(
+        object.get("addresses"),
+        Some(&Value::array(vec![
+            Value::string("0x01".to_string()),
+            Value::string("0x02".to_string())
+        ]))
+    );
+    assert_eq!(Value::from_json(&json!("ok")), Value::string("ok".to_string()));
+}
+
 #[test]
 fn it_rejects_invalid_keys() {
     match serde_json::from_value::<Value>(json!({"type": "strin", "value": "my string"})) {
@@ -573,6 +573,25 @@ impl Value {
         };
         json
     }
+
+    /// Converts a JSON document, such as a response body or a generated file, to a value.
+    /// Integers are kept as integers, other numbers are converted to floats.
+    pub fn from_json(json: &JsonValue) -> Value {
+        match json {
+            JsonValue::Null => Value::null(),
+            JsonValue::Bool(b) => Value::bool(*b),
+            JsonValue::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(i), _) => Value::integer(i.into()),
+                (None, Some(u)) => Value::integer(u.into()),
+                _ => Value::float(n.as_f64().unwrap_or_default()),
+            },
+            JsonValue::String(s) => Value::string(s.clone()),
+            JsonValue::Array(items) => Value::array(items.iter().map(Value::from_json).collect()),
+            JsonValue::Object(props) => Value::object(
+                props.iter().map(|(key, value)| (key.clone(), Value::from_json(value))).collect(),
+            ),
+        }
+    }
 }
 
 fn i128_to_u64(i128: i128) -> Result<u64, String> {
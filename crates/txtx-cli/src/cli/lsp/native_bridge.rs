@@ -19,7 +19,8 @@ use txtx_lsp::backend::{
 };
 use txtx_lsp::lsp_types::{
     DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse,
-    SignatureHelp, SignatureHelpParams,
+    Location, PrepareRenameResponse, ReferenceParams, RenameParams, SignatureHelp,
+    SignatureHelpParams, TextDocumentPositionParams, WorkspaceEdit,
 };
 use txtx_lsp::state::EditorState;
 use txtx_lsp::utils;
@@ -45,10 +46,11 @@ pub async fn start_language_server(
         match oper.index() {
             i if i == notifications_oper => match oper.recv(&notification_rx) {
                 Ok(notification) => {
-                    let result = process_notification(notification, &mut editor_state, None).await;
-                    if let Ok(response) = result {
-                        let _ = response_tx.send(LspResponse::Notification(response));
-                    }
+                    // a response is always sent, the bridge is waiting for it
+                    let response = process_notification(notification, &mut editor_state, None)
+                        .await
+                        .unwrap_or_else(|e| LspNotificationResponse::error(&e));
+                    let _ = response_tx.send(LspResponse::Notification(response));
                 }
                 Err(_e) => {
                     continue;
@@ -177,6 +179,56 @@ impl LanguageServer for LspNativeBridge {
         Ok(None)
     }
 
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let _ = match self.request_tx.lock() {
+            Ok(tx) => tx.send(LspRequest::References(params)),
+            Err(_) => return Ok(None),
+        };
+
+        let response_rx = self.response_rx.lock().expect("failed to lock response_rx");
+        let response = &response_rx.recv().expect("failed to get value from recv");
+        if let LspResponse::Request(LspRequestResponse::References(locations)) = response {
+            return Ok(Some(locations.to_vec()));
+        }
+
+        Ok(None)
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let _ = match self.request_tx.lock() {
+            Ok(tx) => tx.send(LspRequest::PrepareRename(params)),
+            Err(_) => return Ok(None),
+        };
+
+        let response_rx = self.response_rx.lock().expect("failed to lock response_rx");
+        let response = &response_rx.recv().expect("failed to get value from recv");
+        if let LspResponse::Request(LspRequestResponse::PrepareRename(data)) = response {
+            return Ok(data.to_owned());
+        }
+
+        Ok(None)
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let _ = match self.request_tx.lock() {
+            Ok(tx) => tx.send(LspRequest::Rename(params)),
+            Err(_) => return Ok(None),
+        };
+
+        let response_rx = self.response_rx.lock().expect("failed to lock response_rx");
+        let response = &response_rx.recv().expect("failed to get value from recv");
+        match response {
+            LspResponse::Request(LspRequestResponse::Rename(Ok(edits))) => Ok(edits.to_owned()),
+            LspResponse::Request(LspRequestResponse::Rename(Err(message))) => {
+                Err(Error::invalid_params(message.to_owned()))
+            }
+            _ => Ok(None),
+        }
+    }
+
     async fn document_symbol(
         &self,
         params: DocumentSymbolParams,
@@ -330,21 +382,52 @@ impl LanguageServer for LspNativeBridge {
             .log_message(MessageType::INFO, "Txtx Language Server - Received change notification")
             .await;
 
-        if let Some(contract_location) = utils::get_runbook_location(&params.text_document.uri) {
-            if let Ok(tx) = self.notification_tx.lock() {
-                let _ = tx.send(LspNotification::RunbookChanged(
-                    contract_location,
-                    params.content_changes[0].text.to_string(),
-                ));
-            };
+        let Some(contract_location) = utils::get_runbook_location(&params.text_document.uri) else {
+            return;
+        };
+        let Some(content_change) = params.content_changes.into_iter().last() else {
+            return;
+        };
+        let _ = match self.notification_tx.lock() {
+            Ok(tx) => {
+                tx.send(LspNotification::RunbookChanged(contract_location, content_change.text))
+            }
+            Err(_) => return,
+        };
+
+        let mut aggregated_diagnostics = vec![];
+        if let Ok(response_rx) = self.response_rx.lock() {
+            if let Ok(LspResponse::Notification(ref mut notification_response)) = response_rx.recv()
+            {
+                aggregated_diagnostics.append(&mut notification_response.aggregated_diagnostics);
+            }
+        }
+
+        // diagnostics are refreshed as the runbook is edited, without notifying the user on every keystroke
+        for (location, mut diags) in aggregated_diagnostics.drain(..) {
+            if let Ok(url) = location.to_url_string() {
+                self.client
+                    .publish_diagnostics(
+                        Url::parse(&url).unwrap(),
+                        clarity_diagnostics_to_tower_lsp_type(&mut diags),
+                        None,
+                    )
+                    .await;
+            }
         }
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
-        if let Some(contract_location) = utils::get_runbook_location(&params.text_document.uri) {
-            if let Ok(tx) = self.notification_tx.lock() {
-                let _ = tx.send(LspNotification::RunbookClosed(contract_location));
-            };
+        let Some(contract_location) = utils::get_runbook_location(&params.text_document.uri) else {
+            return;
+        };
+        let _ = match self.notification_tx.lock() {
+            Ok(tx) => tx.send(LspNotification::RunbookClosed(contract_location)),
+            Err(_) => return,
+        };
+        // the response carries nothing, but has to be consumed to keep the channel in sync
+        if let Ok(response_rx) = self.response_rx.lock() {
+            let _ = response_rx.recv();
         }
     }
 }
@@ -12,6 +12,7 @@ mod env;
 mod lsp;
 mod runbooks;
 mod snapshots;
+mod state;
 
 pub const AUTH_SERVICE_URL_KEY: &str = "AUTH_SERVICE_URL";
 pub const AUTH_CALLBACK_PORT_KEY: &str = "AUTH_CALLBACK_PORT";
@@ -72,6 +73,9 @@ enum Command {
     /// Run, runbook, run!
     #[clap(name = "run", bin_name = "run")]
     Run(ExecuteRunbook),
+    /// Inspect the constructs of a runbook and their last execution
+    #[clap(name = "inspect", bin_name = "inspect")]
+    Inspect(InspectRunbook),
     /// Display Documentation
     #[clap(name = "docs", bin_name = "docs")]
     Docs(GetDocumentation),
@@ -85,6 +89,9 @@ enum Command {
     /// Snapshot management (work in progress)
     #[clap(subcommand)]
     Snapshots(SnapshotCommand),
+    /// Runbook state management
+    #[clap(subcommand, name = "state", bin_name = "state")]
+    State(StateCommand),
     /// Txtx cloud commands
     #[clap(subcommand, name = "cloud", bin_name = "cloud")]
     Cloud(CloudCommand),
@@ -96,8 +103,57 @@ enum SnapshotCommand {
     #[clap(name = "begin", bin_name = "begin")]
     Begin(BeginSnapshot),
     /// Finalize snapshot
-    #[clap(name = "end", bin_name = "end")]
+    #[clap(name = "commit", bin_name = "commit")]
     Commit(CommitSnapshot),
+    /// Write the runbook states recorded by a committed snapshot
+    #[clap(name = "replay", bin_name = "replay")]
+    Replay(ReplaySnapshot),
+    /// Restore the runbook states preceding a committed snapshot
+    #[clap(name = "rollback", bin_name = "rollback")]
+    Rollback(RollbackSnapshot),
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum StateCommand {
+    /// Record non-critical changes (e.g. an RPC URL update) in the state file, without executing the runbook
+    #[clap(name = "reconcile", bin_name = "reconcile")]
+    Reconcile(ReconcileState),
+    /// Remove the lock of a state file left behind by an interrupted execution
+    #[clap(name = "unlock", bin_name = "unlock")]
+    Unlock(UnlockState),
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+pub struct ReconcileState {
+    /// Path to the manifest
+    #[arg(long = "manifest-file-path", short = 'm', default_value = "./txtx.yml")]
+    pub manifest_path: String,
+    /// Name of the runbook as indexed in the txtx.yml
+    pub runbook: String,
+    /// Choose the environment variable to set from those configured in the txtx.yml
+    #[arg(long = "env")]
+    pub environment: Option<String>,
+    /// A set of inputs to use for batch processing
+    #[arg(long = "input")]
+    pub inputs: Vec<String>,
+    /// Number of seconds to wait for the state file lock to be released by a concurrent execution
+    #[arg(long = "lock-timeout", default_value = "0")]
+    pub lock_timeout: u64,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+pub struct UnlockState {
+    /// Path to the manifest
+    #[arg(long = "manifest-file-path", short = 'm', default_value = "./txtx.yml")]
+    pub manifest_path: String,
+    /// Name of the runbook as indexed in the txtx.yml
+    pub runbook: String,
+    /// Choose the environment variable to set from those configured in the txtx.yml
+    #[arg(long = "env")]
+    pub environment: Option<String>,
+    /// Remove the lock, even if an execution could still be in progress
+    #[arg(long = "force", action=ArgAction::SetTrue)]
+    pub force: bool,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -108,6 +164,12 @@ pub struct BeginSnapshot {
     /// Path to the snapshot
     #[arg(long = "snapshot-file-path", short = 's')]
     pub snapshot_path: String,
+    /// Name of the snapshot (defaults to the snapshot file name)
+    #[arg(long = "name", short = 'n')]
+    pub name: Option<String>,
+    /// Choose the environment variable to set from those configured in the txtx.yml
+    #[arg(long = "env")]
+    pub environment: Option<String>,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -120,6 +182,26 @@ pub struct CommitSnapshot {
     pub snapshot_path: String,
 }
 
+#[derive(Parser, PartialEq, Clone, Debug)]
+pub struct ReplaySnapshot {
+    /// Path to the manifest
+    #[arg(long = "manifest-file-path", short = 'm', default_value = "./txtx.yml")]
+    pub manifest_path: String,
+    /// Path to the snapshot
+    #[arg(long = "snapshot-file-path", short = 's')]
+    pub snapshot_path: String,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+pub struct RollbackSnapshot {
+    /// Path to the manifest
+    #[arg(long = "manifest-file-path", short = 'm', default_value = "./txtx.yml")]
+    pub manifest_path: String,
+    /// Path to the snapshot
+    #[arg(long = "snapshot-file-path", short = 's')]
+    pub snapshot_path: String,
+}
+
 #[derive(Parser, PartialEq, Clone, Debug)]
 pub struct CheckRunbook {
     /// Path to the manifest
@@ -133,6 +215,9 @@ pub struct CheckRunbook {
     /// A set of inputs to use for batch processing
     #[arg(long = "input")]
     pub inputs: Vec<String>,
+    /// Print the execution plan in JSON format
+    #[arg(long = "json", action=ArgAction::SetTrue)]
+    pub json: bool,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -143,15 +228,17 @@ pub struct InspectRunbook {
     /// Path to the manifest
     #[arg(long = "manifest-file-path", short = 'm', default_value = "./txtx.yml")]
     pub manifest_path: String,
-    /// Disable Terminal UI
+    /// Name of the runbook as indexed in the txtx.yml, or the path of the .tx file to inspect
+    pub runbook: String,
+    /// Choose the environment variable to set from those configured in the txtx.yml
+    #[arg(long = "env")]
+    pub environment: Option<String>,
+    /// A set of inputs to use for batch processing
+    #[arg(long = "input")]
+    pub inputs: Vec<String>,
+    /// Disable Terminal UI, and print the inspected constructs instead
     #[clap(long = "no-term-ui")]
     pub no_tui: bool,
-    /// Path to runbook root file
-    // #[clap(long = "runbook-path", conflicts_with = "runbook")]
-    // pub runbook_path: Option<String>,
-    /// Name of runbook as indexed in txtx.yml
-    #[clap(long = "runbook")]
-    pub runbook: Option<String>,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -169,7 +256,7 @@ pub struct ExecuteRunbook {
     /// Execute the runbook with supervision via the browser UI (this is the default execution mode)
     #[arg(long = "browser", short = 'b', action=ArgAction::SetTrue, group = "execution_mode")]
     pub web_console: bool,
-    /// Execute the runbook with supervision via the terminal console (coming soon)
+    /// Execute the runbook with supervision via the terminal console
     #[arg(long = "terminal", short = 't', action=ArgAction::SetTrue, group = "execution_mode")]
     pub term_console: bool,
     /// When running in unsupervised mode, print outputs in JSON format. If a directory is provided, the output will be written a file at the directory.
@@ -199,6 +286,17 @@ pub struct ExecuteRunbook {
     /// Execute the Runbook even if the cached state suggests this Runbook has already been executed
     #[arg(long = "force", short = 'f')]
     pub force_execution: bool,
+    /// Write the execution plan to the provided path in JSON format, without executing the runbook
+    #[arg(long = "plan")]
+    pub plan: Option<String>,
+    /// Execute the runbook only if it matches the execution plan saved at the provided path.
+    /// The runbook is checked against the plan once, before the execution starts: changes
+    /// made to the runbook sources or the manifest during the execution are not detected
+    #[arg(long = "from-plan", conflicts_with = "plan")]
+    pub from_plan: Option<String>,
+    /// Number of seconds to wait for the state file lock to be released by a concurrent execution
+    #[arg(long = "lock-timeout", default_value = "0")]
+    pub lock_timeout: u64,
 }
 
 impl ExecuteRunbook {
@@ -300,6 +398,9 @@ async fn handle_command(
         Command::Run(cmd) => {
             runbooks::handle_run_command(&cmd, buffer_stdin, ctx, &env).await?;
         }
+        Command::Inspect(cmd) => {
+            runbooks::handle_inspect_command(&cmd, buffer_stdin, ctx, &env).await?;
+        }
         Command::List(cmd) => {
             runbooks::handle_list_command(&cmd, ctx).await?;
         }
@@ -315,23 +416,37 @@ async fn handle_command(
         Command::Snapshots(SnapshotCommand::Commit(cmd)) => {
             snapshots::handle_commit_command(&cmd, ctx).await?;
         }
+        Command::Snapshots(SnapshotCommand::Replay(cmd)) => {
+            snapshots::handle_replay_command(&cmd, ctx).await?;
+        }
+        Command::Snapshots(SnapshotCommand::Rollback(cmd)) => {
+            snapshots::handle_rollback_command(&cmd, ctx).await?;
+        }
+        Command::State(StateCommand::Reconcile(cmd)) => {
+            state::handle_reconcile_command(&cmd, buffer_stdin, ctx, &env).await?;
+        }
+        Command::State(StateCommand::Unlock(cmd)) => {
+            state::handle_unlock_command(&cmd, ctx).await?;
+        }
         Command::Lsp => {
             lsp::run_lsp().await?;
         }
         #[cfg(feature = "txtx_serve")]
         Command::Serve(cmd) => {
-            warn!(
-                ctx.expect_logger(),
-                "The command `txtx serve` is experimental and will run for 30 minutes."
-            );
+            warn!(ctx.expect_logger(), "The command `txtx serve` is experimental.");
             let addr = format!("{}:{}", cmd.network_binding_ip_address, cmd.network_binding_port);
-            let _ = txtx_serve::start_server(&addr).await.unwrap();
+            let manifest_path = cmd.manifest_path.clone().unwrap_or("./txtx.yml".into());
+            let _ = txtx_serve::start_server(&addr, &manifest_path)
+                .await
+                .map_err(|e| format!("unable to start server: {}", e))?;
+            info!(ctx.expect_logger(), "Serving runbooks from {} on {}", manifest_path, addr);
             ctrlc::set_handler(move || {
                 std::process::exit(1);
             })
             .expect("Error setting Ctrl-C handler");
-            // Consider making the duration configurable or running indefinitely
-            thread::sleep(std::time::Duration::new(1800, 0));
+            loop {
+                thread::sleep(std::time::Duration::from_secs(60));
+            }
         }
         Command::Cloud(cmd) => handle_cloud_commands(&cmd, buffer_stdin, &env).await?,
     }
@@ -464,6 +579,40 @@ mod tests {
         assert_eq!(result.inputs, vec!["input1", "input2"]);
     }
 
+    #[test]
+    fn test_plan_setting() {
+        let args = vec!["txtx", "runbook", "--plan", "plan.json"];
+        let result = parse_args(args);
+        assert_eq!(result.plan, Some(String::from("plan.json")));
+    }
+
+    #[test]
+    fn test_lock_timeout_setting() {
+        let args = vec!["txtx", "runbook", "--lock-timeout", "30"];
+        let result = ExecuteRunbook::try_parse_from(args).unwrap();
+        assert_eq!(result.lock_timeout, 30);
+
+        let args = vec!["txtx", "runbook"];
+        let result = ExecuteRunbook::try_parse_from(args).unwrap();
+        assert_eq!(result.lock_timeout, 0);
+    }
+
+    #[test]
+    fn test_from_plan_conflicts_with_plan() {
+        let args = vec!["txtx", "runbook", "--plan", "plan.json", "--from-plan", "plan.json"];
+        let err = ExecuteRunbook::try_parse_from(args).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_inspect_runbook_settings() {
+        let args = vec!["txtx", "runbook", "--env", "testnet", "--no-term-ui"];
+        let result = InspectRunbook::try_parse_from(args).unwrap();
+        assert_eq!(result.runbook, "runbook");
+        assert_eq!(result.environment, Some(String::from("testnet")));
+        assert_eq!(result.no_tui, true);
+    }
+
     #[test_case("--unsupervised", "--browser")]
     #[test_case("--unsupervised", "--terminal")]
     #[test_case("--browser", "--terminal")]
@@ -1,5 +1,8 @@
-use super::{env::TxtxEnv, CheckRunbook, Context, CreateRunbook, ExecuteRunbook, ListRunbooks};
-use crate::{get_addon_by_namespace, get_available_addons};
+use super::{
+    env::TxtxEnv, CheckRunbook, Context, CreateRunbook, ExecuteRunbook, InspectRunbook,
+    ListRunbooks,
+};
+use crate::{get_addon_by_namespace, get_available_addons, term_ui::inspect::DetailKind};
 use ascii_table::AsciiTable;
 use console::Style;
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
@@ -10,7 +13,11 @@ use std::{
     fs::{self, File},
     io::Write,
     path::PathBuf,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 use tokio::sync::RwLock;
 use txtx_cloud::{auth::AuthConfig, router::TxtxAuthenticatedCloudServiceRouter};
@@ -41,7 +48,8 @@ use txtx_core::{
         RunbookMetadata, RunbookStateLocation, WorkspaceManifest,
     },
     runbook::{
-        AddonConstructFactory, ConsolidatedChanges, RunbookTopLevelInputsMap, SynthesizedChange,
+        AddonConstructFactory, ConsolidatedChanges, RunbookExecutionPlan, RunbookTopLevelInputsMap,
+        SynthesizedChange,
     },
     start_supervised_runbook_runloop, start_unsupervised_runbook_runloop,
     types::{ConstructDid, Runbook, RunbookSnapshotContext, RunbookSources},
@@ -130,18 +138,12 @@ pub fn display_snapshot_diffing(
     {
         println!("{} Latest snapshot in sync with latest runbook updates\n", green!("✓"));
 
-        // todo: if we had no critical changes, but there are some synthesized changes, this means the
-        // synthesized changes were non-critical. we should consider updating our runbook state file
-        // to match this new state, but not actually execute any actions.
-        // my concern here is that the state file could be used as a source of truth. For example, if I change something like
-        // the RPC API URL, that's non-tainting (because if I change the url, that doesn't mean I want to execute
-        // again, I just want a different RPC in the future) but it would be misleading to update the state file to reflect
-        // the new RPC - that's not what we executed in the past.
-        // issue: https://linear.app/txtx/issue/TXTX-265/update-statefile-on-non-critical-changes
-        // if we end up doing this:
-        // if !synthesized_changes.is_empty() {
-        // .. update state file
-        // }
+        if !synthesized_changes.is_empty() {
+            println!(
+                "{} Non-critical changes detected, run `txtx state reconcile` to record them in the state file\n",
+                yellow!("!")
+            );
+        }
 
         return None;
     }
@@ -165,6 +167,21 @@ pub async fn handle_check_command(
     )
     .await?;
 
+    if cmd.json {
+        let previous_state = runbook_state.as_ref().and_then(|state_file_location| {
+            state_file_location
+                .load_execution_snapshot(
+                    true,
+                    &runbook.runbook_id.name,
+                    &runbook.top_level_inputs_map.current_top_level_input_name(),
+                )
+                .ok()
+        });
+        let plan = runbook.compute_execution_plan(previous_state).await?;
+        println!("{}", serde_json::to_string_pretty(&plan).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+
     match &runbook_state {
         Some(state_file_location) => {
             let ctx = RunbookSnapshotContext::new();
@@ -210,6 +227,57 @@ pub async fn handle_check_command(
     Ok(())
 }
 
+pub async fn handle_inspect_command(
+    cmd: &InspectRunbook,
+    buffer_stdin: Option<String>,
+    _ctx: &Context,
+    env: &TxtxEnv,
+) -> Result<(), String> {
+    let (_manifest, runbook_name, mut runbook, runbook_state) = load_runbook_from_manifest(
+        &cmd.manifest_path,
+        &cmd.runbook,
+        &cmd.environment,
+        &cmd.inputs,
+        buffer_stdin,
+        env,
+    )
+    .await?;
+
+    let previous_state = runbook_state.as_ref().and_then(|state_file_location| {
+        state_file_location
+            .load_execution_snapshot(
+                true,
+                &runbook.runbook_id.name,
+                &runbook.top_level_inputs_map.current_top_level_input_name(),
+            )
+            .ok()
+    });
+    let plan = runbook.compute_execution_plan(previous_state.clone()).await?;
+    let constructs =
+        crate::term_ui::inspect::collect_constructs(&runbook, previous_state.as_ref(), &plan);
+
+    if !cmd.no_tui {
+        return crate::term_ui::inspect::main(&runbook_name, constructs)
+            .map_err(|e| format!("unable to start the inspector: {}", e));
+    }
+
+    if previous_state.is_none() {
+        println!("{} No execution state found for runbook '{}'\n", yellow!("!"), runbook_name);
+    }
+    for construct in constructs.iter() {
+        for (kind, line) in construct.details() {
+            match kind {
+                DetailKind::Title => println!("{}", purple!(line)),
+                DetailKind::Section => println!("  {}", yellow!(line)),
+                DetailKind::Changed => println!("    {}", red!(line)),
+                DetailKind::Field | DetailKind::Muted => println!("    {}", line),
+            }
+        }
+        println!();
+    }
+    Ok(())
+}
+
 pub async fn handle_new_command(cmd: &CreateRunbook, _ctx: &Context) -> Result<(), String> {
     let manifest_location = FileLocation::from_path_string(&cmd.manifest_path)?;
     let manifest_res = WorkspaceManifest::from_location(&manifest_location);
@@ -499,7 +567,8 @@ pub async fn handle_run_command(
     // Confirm that if the runbook is using cloud services, the user is authenticated
     check_cloud_service_eligibility(runbook.flow_contexts.first().expect("no flow found"))?;
 
-    let previous_state_opt = if let Some(state_file_location) = runbook_state_location.clone() {
+    let load_previous_state = |runbook: &Runbook| {
+        let state_file_location = runbook_state_location.as_ref()?;
         match state_file_location.load_execution_snapshot(
             true,
             &runbook.runbook_id.name,
@@ -511,10 +580,54 @@ pub async fn handle_run_command(
                 None
             }
         }
-    } else {
-        None
     };
 
+    if let Some(plan_path) = &cmd.plan {
+        let previous_state = if cmd.force_execution { None } else { load_previous_state(&runbook) };
+        let plan = runbook.compute_execution_plan(previous_state).await?;
+        let plan_location = FileLocation::from_path_string(plan_path)?;
+        let plan_json = serde_json::to_string_pretty(&plan).map_err(|e| e.to_string())?;
+        plan_location.write_content(plan_json.as_bytes())?;
+        println!("{} Execution plan written to {}", green!("✓"), plan_location);
+        return Ok(());
+    }
+
+    // Held until the end of the execution, so that concurrent executions can't clobber the state.
+    // Computing a plan doesn't write the state, so it doesn't take the lock.
+    // Early returns rely on the guard releasing the lock when dropped.
+    let state_lock = match &runbook_state_location {
+        Some(state_file_location) => Some(
+            state_file_location
+                .acquire_lock(
+                    &runbook.runbook_id.name,
+                    &runbook.top_level_inputs_map.current_top_level_input_name(),
+                    Duration::from_secs(cmd.lock_timeout),
+                )
+                .await?,
+        ),
+        None => None,
+    };
+    // The state is loaded once the lock is held, so that it can't be outdated by the time
+    // the execution writes it back.
+    let previous_state_opt = load_previous_state(&runbook);
+
+    if let Some(plan_path) = &cmd.from_plan {
+        let plan_location = FileLocation::from_path_string(plan_path)?;
+        let approved_plan: RunbookExecutionPlan =
+            serde_json::from_slice(&plan_location.read_content()?)
+                .map_err(|e| format!("unable to read plan {}: {}", plan_location, e))?;
+
+        let execution_context_backups = runbook.backup_execution_contexts();
+        let previous_state = if cmd.force_execution { None } else { previous_state_opt.clone() };
+        let current_plan = runbook.compute_execution_plan(previous_state).await?;
+        if let Err(diag) = approved_plan.check_drift(&current_plan) {
+            return Err(diag.message);
+        }
+        runbook.restore_execution_contexts(execution_context_backups);
+        println!("{} Runbook matches the approved plan {}", green!("✓"), plan_location);
+    }
+    let is_plan_approved = cmd.from_plan.is_some();
+
     runbook.enable_full_execution_mode();
 
     if !cmd.force_execution {
@@ -590,7 +703,7 @@ pub async fn handle_run_command(
                 println!("\n");
             }
 
-            if has_actions_to_execute_count || has_actions_to_re_execute {
+            if (has_actions_to_execute_count || has_actions_to_re_execute) && !is_plan_approved {
                 let theme = ColorfulTheme {
                     values_style: Style::new().green(),
                     ..ColorfulTheme::default()
@@ -699,7 +812,7 @@ pub async fn handle_run_command(
             &cmd.output,
         );
 
-        return Ok(());
+        return state_lock.map_or(Ok(()), |lock| lock.release());
     }
 
     let (block_tx, block_rx) = channel::unbounded::<BlockEvent>();
@@ -719,6 +832,37 @@ pub async fn handle_run_command(
         .map(|k| k.clone())
         .collect::<Vec<_>>();
 
+    // When supervised from the terminal, the supervisor owns the terminal until the operator exits it:
+    // the execution output is only printed once the terminal has been restored.
+    let runloop_completed = Arc::new(AtomicBool::new(false));
+    let term_ui_exit_rx = if cmd.term_console {
+        let (term_ui_exit_tx, term_ui_exit_rx) = channel::bounded(1);
+        let block_store = block_store.clone();
+        let block_events_rx = block_broadcaster.subscribe();
+        let action_item_events_tx = action_item_events_tx.clone();
+        let moved_kill_loops_tx = kill_loops_tx.clone();
+        let moved_runloop_completed = runloop_completed.clone();
+        let runbook_name = runbook_name.clone();
+        let _ = hiro_system_kit::thread_named("Terminal Supervisor").spawn(move || {
+            if let Err(e) = crate::term_ui::supervisor::main(
+                &runbook_name,
+                block_store,
+                block_events_rx,
+                action_item_events_tx,
+            ) {
+                println!("{} Terminal supervisor failed: {}", red!("x"), e);
+            }
+            let _ = term_ui_exit_tx.send(());
+            // the operator left the supervisor before the end of the execution
+            if !moved_runloop_completed.load(Ordering::SeqCst) {
+                let _ = moved_kill_loops_tx.send(true);
+            }
+        });
+        Some(term_ui_exit_rx)
+    } else {
+        None
+    };
+
     let moved_block_tx = block_tx.clone();
     let moved_kill_loops_tx = kill_loops_tx.clone();
     let moved_runbook_state = runbook_state_location.clone();
@@ -727,9 +871,15 @@ pub async fn handle_run_command(
     let _ = hiro_system_kit::thread_named("Runbook Runloop").spawn(move || {
         let runloop_future =
             start_supervised_runbook_runloop(&mut runbook, moved_block_tx, action_item_events_rx);
+        let res = hiro_system_kit::nestable_block_on(runloop_future);
+        runloop_completed.store(true, Ordering::SeqCst);
+
+        if let Some(term_ui_exit_rx) = term_ui_exit_rx {
+            let _ = term_ui_exit_rx.recv();
+        }
 
         process_runbook_execution_output(
-            hiro_system_kit::nestable_block_on(runloop_future),
+            res,
             &mut runbook,
             moved_runbook_state,
             &output_json,
@@ -788,6 +938,7 @@ pub async fn handle_run_command(
 
     #[cfg(feature = "supervisor_ui")]
     let moved_relayer_channel_tx = relayer_channel_tx.clone();
+    let is_term_console = cmd.term_console;
     let block_store_handle = tokio::spawn(async move {
         loop {
             if let Ok(mut block_event) = block_rx.try_recv() {
@@ -835,7 +986,9 @@ pub async fn handle_run_command(
                         .filter(|(_, b)| b.uuid == update.progress_bar_uuid)
                         .for_each(|(_, b)| b.visible = update.visible),
                     BlockEvent::RunbookCompleted => {
-                        println!("\n{}", green!("Runbook complete!"));
+                        if !is_term_console {
+                            println!("\n{}", green!("Runbook complete!"));
+                        }
                     }
                     BlockEvent::Error(new_block) => {
                         let len = block_store.len();
@@ -886,7 +1039,7 @@ pub async fn handle_run_command(
     })
     .expect("Error setting Ctrl-C handler");
     let _ = tokio::join!(block_store_handle);
-    Ok(())
+    state_lock.map_or(Ok(()), |lock| lock.release())
 }
 
 pub fn load_workspace_manifest_from_manifest_path(
@@ -1,13 +1,126 @@
-use super::{BeginSnapshot, CommitSnapshot, Context};
+use txtx_core::kit::helpers::fs::FileLocation;
+use txtx_core::manifest::WorkspaceManifest;
+use txtx_core::snapshot::{Snapshot, SnapshotLocation};
 
-pub async fn handle_begin_command(_cmd: &BeginSnapshot, _ctx: &Context) -> Result<(), String> {
-    // Create a .lock file on a DB file path specified
-    // Write state transitions to it
+use super::{BeginSnapshot, CommitSnapshot, Context, ReplaySnapshot, RollbackSnapshot};
+
+pub async fn handle_begin_command(cmd: &BeginSnapshot, _ctx: &Context) -> Result<(), String> {
+    let manifest_location = FileLocation::from_path_string(&cmd.manifest_path)?;
+    let manifest = WorkspaceManifest::from_location(&manifest_location)?;
+    // resolve the environment the same way runbook executions do, so that the snapshot
+    // matches the state files written by `txtx run`
+    let environment = manifest
+        .get_runbook_inputs(&cmd.environment, &vec![], None)?
+        .current_top_level_input_name();
+
+    let snapshot_location = SnapshotLocation(FileLocation::from_path_string(&cmd.snapshot_path)?);
+    let name = match &cmd.name {
+        Some(name) => name.clone(),
+        None => snapshot_location
+            .0
+            .get_file_name()
+            .map(|f| f.split('.').next().unwrap_or(&f).to_string())
+            .ok_or(format!("invalid snapshot path {}", cmd.snapshot_path))?,
+    };
+    // keep track of the current state of the runbooks, restored if the snapshot is rolled back
+    let mut snapshot = Snapshot::begin(&name, &environment);
+    for runbook in manifest.runbooks.iter() {
+        let Some(state_location) = &runbook.state else {
+            continue;
+        };
+        if let Ok(execution) =
+            state_location.load_execution_snapshot(false, &runbook.name, &environment)
+        {
+            snapshot.record_baseline(execution)?;
+        }
+    }
+
+    snapshot_location.begin(&snapshot)?;
+    println!(
+        "{} Snapshot '{}' begun on environment '{}'\nRun your runbooks, then run `txtx snapshots commit -s {}`",
+        green!("✓"),
+        snapshot.name,
+        snapshot.environment,
+        cmd.snapshot_path
+    );
     Ok(())
 }
 
-pub async fn handle_commit_command(_cmd: &CommitSnapshot, _ctx: &Context) -> Result<(), String> {
-    // Check for .lock file
-    // Write state transitions accumulated to db file specified
+pub async fn handle_commit_command(cmd: &CommitSnapshot, _ctx: &Context) -> Result<(), String> {
+    let manifest_location = FileLocation::from_path_string(&cmd.manifest_path)?;
+    let manifest = WorkspaceManifest::from_location(&manifest_location)?;
+
+    let snapshot_location = SnapshotLocation(FileLocation::from_path_string(&cmd.snapshot_path)?);
+    let mut snapshot = snapshot_location.load_pending()?;
+
+    // Collect the runbooks executed since the snapshot was begun, in execution order
+    let mut executions = vec![];
+    for runbook in manifest.runbooks.iter() {
+        let Some(state_location) = &runbook.state else {
+            continue;
+        };
+        let Ok(execution) =
+            state_location.load_execution_snapshot(false, &runbook.name, &snapshot.environment)
+        else {
+            continue;
+        };
+        if snapshot.includes_execution(&execution) {
+            executions.push(execution);
+        }
+    }
+    executions.sort_by(|a, b| a.ended_at().cmp(b.ended_at()));
+
+    for execution in executions.into_iter() {
+        println!("{} Recording runbook '{}'", purple!("→"), execution.name());
+        snapshot.record_runbook_execution(execution)?;
+    }
+
+    snapshot_location.commit(&mut snapshot)?;
+    println!(
+        "{} Snapshot '{}' committed to {} ({} runbooks, {} state transitions)",
+        green!("✓"),
+        snapshot.name,
+        cmd.snapshot_path,
+        snapshot.runbooks.len(),
+        snapshot.state_transitions().count()
+    );
+    Ok(())
+}
+
+pub async fn handle_replay_command(cmd: &ReplaySnapshot, _ctx: &Context) -> Result<(), String> {
+    let manifest_location = FileLocation::from_path_string(&cmd.manifest_path)?;
+    let manifest = WorkspaceManifest::from_location(&manifest_location)?;
+
+    let snapshot_location = SnapshotLocation(FileLocation::from_path_string(&cmd.snapshot_path)?);
+    let snapshot = snapshot_location.load()?;
+
+    for state_file_location in snapshot.replay(&manifest)?.iter() {
+        println!("{} State file {} written", purple!("→"), state_file_location);
+    }
+    println!(
+        "{} Snapshot '{}' replayed on environment '{}'",
+        green!("✓"),
+        snapshot.name,
+        snapshot.environment
+    );
+    Ok(())
+}
+
+pub async fn handle_rollback_command(cmd: &RollbackSnapshot, _ctx: &Context) -> Result<(), String> {
+    let manifest_location = FileLocation::from_path_string(&cmd.manifest_path)?;
+    let manifest = WorkspaceManifest::from_location(&manifest_location)?;
+
+    let snapshot_location = SnapshotLocation(FileLocation::from_path_string(&cmd.snapshot_path)?);
+    let snapshot = snapshot_location.load()?;
+
+    for state_file_location in snapshot.rollback(&manifest)?.iter() {
+        println!("{} State file {} restored", purple!("→"), state_file_location);
+    }
+    println!(
+        "{} Snapshot '{}' rolled back on environment '{}'",
+        green!("✓"),
+        snapshot.name,
+        snapshot.environment
+    );
     Ok(())
 }
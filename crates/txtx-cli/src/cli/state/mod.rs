@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use txtx_core::kit::helpers::fs::FileLocation;
+use txtx_core::manifest::WorkspaceManifest;
+
+use super::env::TxtxEnv;
+use super::runbooks::load_runbook_from_manifest;
+use super::{Context, ReconcileState, UnlockState};
+
+pub async fn handle_reconcile_command(
+    cmd: &ReconcileState,
+    buffer_stdin: Option<String>,
+    _ctx: &Context,
+    env: &TxtxEnv,
+) -> Result<(), String> {
+    let (_manifest, runbook_name, mut runbook, runbook_state) = load_runbook_from_manifest(
+        &cmd.manifest_path,
+        &cmd.runbook,
+        &cmd.environment,
+        &cmd.inputs,
+        buffer_stdin,
+        env,
+    )
+    .await?;
+
+    let Some(runbook_state_location) = runbook_state else {
+        return Err(format!("runbook '{}' is not configured with a state location", runbook_name));
+    };
+
+    let state_lock = runbook_state_location
+        .acquire_lock(
+            &runbook.runbook_id.name,
+            &runbook.top_level_inputs_map.current_top_level_input_name(),
+            Duration::from_secs(cmd.lock_timeout),
+        )
+        .await?;
+
+    match runbook.reconcile_state(&runbook_state_location).await? {
+        Some((state_file_location, reconciliation)) => {
+            println!("\n{}\n", yellow!("Changes recorded:"));
+            for change in reconciliation.changes.iter() {
+                let formatted_change = change
+                    .description
+                    .iter()
+                    .map(|c| if c.starts_with("-") { red!(c) } else { green!(c) })
+                    .collect::<Vec<_>>()
+                    .join("");
+                println!(
+                    "{}:\n-------------------------\n{}\n-------------------------",
+                    change.label, formatted_change
+                );
+            }
+            println!(
+                "{} State file {} reconciled with runbook '{}'",
+                green!("✓"),
+                state_file_location,
+                runbook_name
+            );
+        }
+        None => {
+            println!("{} State file already in sync with runbook '{}'", green!("✓"), runbook_name);
+        }
+    }
+    state_lock.release()
+}
+
+pub async fn handle_unlock_command(cmd: &UnlockState, _ctx: &Context) -> Result<(), String> {
+    let manifest_location = FileLocation::from_path_string(&cmd.manifest_path)?;
+    let manifest = WorkspaceManifest::from_location(&manifest_location)?;
+    // The environment is resolved the same way executions resolve it
+    let environment = manifest
+        .get_runbook_inputs(&cmd.environment, &vec![], None)?
+        .current_top_level_input_name();
+
+    let runbook = manifest
+        .runbooks
+        .iter()
+        .find(|r| r.name.eq(&cmd.runbook))
+        .ok_or(format!("runbook '{}' not found in manifest", cmd.runbook))?;
+    let Some(runbook_state_location) = &runbook.state else {
+        return Err(format!("runbook '{}' is not configured with a state location", runbook.name));
+    };
+    if !cmd.force {
+        return Err(format!(
+            "removing the lock of a state file while an execution is in progress could corrupt the state, use --force to proceed"
+        ));
+    }
+
+    match runbook_state_location.force_unlock(&runbook.name, &environment)? {
+        Some(owner) => {
+            println!(
+                "{} Lock held by {} removed from runbook '{}' state",
+                green!("✓"),
+                owner,
+                runbook.name
+            );
+        }
+        None => {
+            println!("{} Runbook '{}' state is not locked", green!("✓"), runbook.name);
+        }
+    }
+    Ok(())
+}
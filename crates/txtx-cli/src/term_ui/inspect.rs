@@ -1,125 +1,554 @@
 use std::{error::Error, io};
 
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{
-    prelude::*,
-    style::palette::{self, tailwind},
-    widgets::*,
+use ratatui::{prelude::*, style::palette::tailwind, widgets::*};
+use txtx_core::{
+    kit::{
+        indexmap::IndexMap,
+        types::{types::Value, ConstructDid},
+    },
+    runbook::{
+        CommandSnapshot, PlannedChangeKind, RunbookExecutionPlan, RunbookExecutionSnapshot,
+        ValuePostEvaluation,
+    },
+    types::Runbook,
 };
-use txtx_core::types::Runbook;
 
-const INFO_TEXT: &str = "(Esc) quit | (↑) move up | (↓) move down";
+const NAVIGATION_INFO_TEXT: &str =
+    "(↑/↓) select | (/) search | (pgup/pgdn) scroll details | (esc) clear search | (q) quit";
+const SEARCH_INFO_TEXT: &str = "(enter) apply | (esc) clear";
 
-const ITEM_HEIGHT: usize = 4;
+/// Status of a construct in the last execution recorded in the state file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LastExecutionStatus {
+    /// The construct was executed
+    Executed,
+    /// The construct was recorded, but its execution did not complete
+    NotExecuted,
+    /// The construct is not part of the state file
+    Unknown,
+}
+
+impl LastExecutionStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LastExecutionStatus::Executed => "executed",
+            LastExecutionStatus::NotExecuted => "not executed",
+            LastExecutionStatus::Unknown => "no state",
+        }
+    }
+}
+
+/// An input of a construct, with the value recorded in the state file and the value it
+/// evaluates to now.
+#[derive(Debug, Clone)]
+pub struct InspectedInput {
+    pub name: String,
+    pub recorded: Option<String>,
+    pub current: Option<String>,
+    pub critical: bool,
+}
+
+/// Everything known about a construct: its definition, its place in the graph, what was
+/// recorded during its last execution, and whether the next execution would run it again.
+#[derive(Debug, Clone)]
+pub struct InspectedConstruct {
+    pub flow: String,
+    pub construct_did: ConstructDid,
+    pub construct_type: String,
+    pub construct_name: String,
+    pub namespace: String,
+    pub command: String,
+    pub inputs: Vec<InspectedInput>,
+    pub outputs: Vec<(String, String)>,
+    pub upstream: Vec<String>,
+    pub downstream: Vec<String>,
+    pub status: LastExecutionStatus,
+    /// Whether the construct is part of the execution plan
+    pub planned: bool,
+    /// Changes from the last execution impacting the construct
+    pub changes: Vec<String>,
+}
+
+impl InspectedConstruct {
+    pub fn id(&self) -> String {
+        format!("{}.{}", self.construct_type, self.construct_name)
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        if query.is_empty() {
+            return true;
+        }
+        let query = query.to_lowercase();
+        [
+            self.id(),
+            self.namespace.clone(),
+            self.command.clone(),
+            self.flow.clone(),
+            self.status.label().to_string(),
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&query))
+    }
+
+    /// Renders the construct details as a list of lines, shared by the terminal UI and the
+    /// plain text output.
+    pub fn details(&self) -> Vec<(DetailKind, String)> {
+        let mut lines = vec![
+            (DetailKind::Title, self.id()),
+            (DetailKind::Field, format!("flow: {}", self.flow)),
+            (DetailKind::Field, format!("command: {}::{}", self.namespace, self.command)),
+            (DetailKind::Field, format!("did: {}", self.construct_did)),
+            (DetailKind::Field, format!("last execution: {}", self.status.label())),
+            (
+                DetailKind::Field,
+                format!(
+                    "next execution: {}",
+                    if self.planned { "will be executed" } else { "up to date" }
+                ),
+            ),
+        ];
+
+        if !self.changes.is_empty() {
+            lines.push((DetailKind::Section, "Changes since last execution".into()));
+            for change in self.changes.iter() {
+                lines.push((DetailKind::Changed, format!("- {}", change)));
+            }
+        }
+
+        lines.push((DetailKind::Section, "Inputs".into()));
+        if self.inputs.is_empty() {
+            lines.push((DetailKind::Muted, "none".into()));
+        }
+        for input in self.inputs.iter() {
+            let critical = if input.critical { " (critical)" } else { "" };
+            match (&input.recorded, &input.current) {
+                (Some(recorded), Some(current)) if recorded.ne(current) => {
+                    push_value(&mut lines, DetailKind::Changed, &input.name, critical, recorded);
+                    push_value(&mut lines, DetailKind::Changed, "  now", "", current);
+                }
+                (Some(value), _) => {
+                    push_value(&mut lines, DetailKind::Field, &input.name, critical, value)
+                }
+                (None, Some(value)) => {
+                    push_value(&mut lines, DetailKind::Field, &input.name, " (not recorded)", value)
+                }
+                (None, None) => {}
+            }
+        }
+
+        lines.push((DetailKind::Section, "Outputs".into()));
+        if self.outputs.is_empty() {
+            lines.push((DetailKind::Muted, "none recorded".into()));
+        }
+        for (name, value) in self.outputs.iter() {
+            push_value(&mut lines, DetailKind::Field, name, "", value);
+        }
+
+        lines.push((DetailKind::Section, "Upstream dependencies".into()));
+        if self.upstream.is_empty() {
+            lines.push((DetailKind::Muted, "none".into()));
+        }
+        for dependency in self.upstream.iter() {
+            lines.push((DetailKind::Field, format!("- {}", dependency)));
+        }
+
+        lines.push((DetailKind::Section, "Downstream dependencies".into()));
+        if self.downstream.is_empty() {
+            lines.push((DetailKind::Muted, "none".into()));
+        }
+        for dependency in self.downstream.iter() {
+            lines.push((DetailKind::Field, format!("- {}", dependency)));
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DetailKind {
+    Title,
+    Section,
+    Field,
+    Changed,
+    Muted,
+}
+
+fn push_value(
+    lines: &mut Vec<(DetailKind, String)>,
+    kind: DetailKind,
+    name: &str,
+    suffix: &str,
+    value: &str,
+) {
+    let mut value_lines = value.lines();
+    lines.push((kind, format!("{}{}: {}", name, suffix, value_lines.next().unwrap_or_default())));
+    for line in value_lines {
+        lines.push((kind, format!("    {}", line)));
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Array(_) | Value::Object(_) => {
+            serde_json::to_string_pretty(&value.to_json()).unwrap_or_else(|_| value.to_string())
+        }
+        _ => value.to_string(),
+    }
+}
+
+fn format_post_evaluation(value: &ValuePostEvaluation) -> String {
+    match value {
+        ValuePostEvaluation::Value(value) => format_value(value),
+        ValuePostEvaluation::ObjectValue(props) => format_value(&Value::object(
+            props.iter().map(|(k, (v, _))| (k.clone(), v.clone())).collect(),
+        )),
+        ValuePostEvaluation::MapValue(entries) => format_value(&Value::array(
+            entries
+                .iter()
+                .map(|entry| {
+                    Value::object(entry.iter().map(|(k, (v, _))| (k.clone(), v.clone())).collect())
+                })
+                .collect(),
+        )),
+    }
+}
+
+/// Gathers the constructs of every flow of the runbook, from the simulated execution contexts,
+/// the state file of the previous execution and the execution plan computed against it.
+pub fn collect_constructs(
+    runbook: &Runbook,
+    previous_state: Option<&RunbookExecutionSnapshot>,
+    plan: &RunbookExecutionPlan,
+) -> Vec<InspectedConstruct> {
+    let mut constructs = vec![];
+    for flow_context in runbook.flow_contexts.iter() {
+        let execution_context = &flow_context.execution_context;
+        let graph_context = &flow_context.graph_context;
+        let workspace_context = &flow_context.workspace_context;
+        let resolve_names = |construct_dids: Vec<ConstructDid>| -> Vec<String> {
+            construct_dids
+                .iter()
+                .filter_map(|did| workspace_context.constructs.get(did))
+                .map(|construct_id| {
+                    format!("{}.{}", construct_id.construct_type, construct_id.construct_name)
+                })
+                .collect()
+        };
+        let flow_snapshot = previous_state.and_then(|state| state.flows.get(&flow_context.name));
+
+        for construct_did in execution_context.order_for_commands_execution.iter() {
+            let Some(command_instance) = execution_context.commands_instances.get(construct_did)
+            else {
+                continue;
+            };
+            let Some(construct_id) = workspace_context.constructs.get(construct_did) else {
+                continue;
+            };
+            let command_snapshot: Option<&CommandSnapshot> =
+                flow_snapshot.and_then(|flow| flow.commands.get(construct_did));
+
+            let mut inputs: IndexMap<String, InspectedInput> = IndexMap::new();
+            if let Some(command_snapshot) = command_snapshot {
+                for (name, input) in command_snapshot.inputs.iter() {
+                    inputs.insert(
+                        name.clone(),
+                        InspectedInput {
+                            name: name.clone(),
+                            recorded: Some(format_post_evaluation(&input.value_post_evaluation)),
+                            current: None,
+                            critical: input.critical,
+                        },
+                    );
+                }
+            }
+            if let Some(inputs_evaluations) =
+                execution_context.commands_inputs_evaluation_results.get(construct_did)
+            {
+                for input in command_instance.specification.inputs.iter() {
+                    if input.sensitive {
+                        continue;
+                    }
+                    let Some(value) = inputs_evaluations.inputs.get_value(&input.name) else {
+                        continue;
+                    };
+                    inputs
+                        .entry(input.name.clone())
+                        .or_insert_with(|| InspectedInput {
+                            name: input.name.clone(),
+                            recorded: None,
+                            current: None,
+                            critical: false,
+                        })
+                        .current = Some(format_value(value));
+                }
+            }
 
-struct TableColors {
+            let outputs = command_snapshot
+                .map(|command| {
+                    command
+                        .outputs
+                        .iter()
+                        .map(|(name, output)| (name.clone(), format_value(&output.value)))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let (upstream, downstream) =
+                match graph_context.constructs_dag_node_lookup.contains_key(construct_did) {
+                    true => (
+                        resolve_names(
+                            graph_context
+                                .get_upstream_dependencies_for_construct_did(construct_did),
+                        ),
+                        resolve_names(
+                            graph_context
+                                .get_downstream_dependencies_for_construct_did(construct_did, true),
+                        ),
+                    ),
+                    false => (vec![], vec![]),
+                };
+
+            let status = match command_snapshot {
+                Some(command) if command.executed => LastExecutionStatus::Executed,
+                Some(_) => LastExecutionStatus::NotExecuted,
+                None => LastExecutionStatus::Unknown,
+            };
+
+            let planned = plan
+                .flows
+                .get(&flow_context.name)
+                .map(|flow| flow.constructs.iter().any(|c| c.construct_did.eq(construct_did)))
+                .unwrap_or(false);
+
+            let changes = plan
+                .changes
+                .iter()
+                .filter(|change| {
+                    change.impacted.iter().any(|(flow, did)| {
+                        flow.eq(&flow_context.name) && did.as_ref() == Some(construct_did)
+                    })
+                })
+                .map(|change| {
+                    let critical = if change.critical { " (critical)" } else { "" };
+                    match change.kind {
+                        PlannedChangeKind::Edition => {
+                            format!("edited{}: {}", critical, change.description.join(", "))
+                        }
+                        PlannedChangeKind::Addition => "added since last execution".to_string(),
+                        PlannedChangeKind::FormerFailure => {
+                            format!(
+                                "failed during last execution: {}",
+                                change.description.join(", ")
+                            )
+                        }
+                    }
+                })
+                .collect();
+
+            constructs.push(InspectedConstruct {
+                flow: flow_context.name.clone(),
+                construct_did: construct_did.clone(),
+                construct_type: construct_id.construct_type.clone(),
+                construct_name: construct_id.construct_name.clone(),
+                namespace: command_instance.namespace.clone(),
+                command: command_instance.specification.matcher.clone(),
+                inputs: inputs.into_values().collect(),
+                outputs,
+                upstream,
+                downstream,
+                status,
+                planned,
+                changes,
+            });
+        }
+    }
+    constructs
+}
+
+struct InspectColors {
     buffer_bg: Color,
     header_bg: Color,
     header_fg: Color,
-    row_fg: Color,
-    selected_style_fg: Color,
+    text_fg: Color,
+    muted_fg: Color,
+    border: Color,
+    selected_fg: Color,
     normal_row_color: Color,
     alt_row_color: Color,
-    footer_border_color: Color,
+    success: Color,
+    pending: Color,
+    changed: Color,
 }
 
-impl TableColors {
+impl InspectColors {
     fn new(color: &tailwind::Palette) -> Self {
         Self {
             buffer_bg: tailwind::SLATE.c950,
             header_bg: color.c900,
             header_fg: tailwind::SLATE.c200,
-            row_fg: tailwind::SLATE.c200,
-            selected_style_fg: color.c400,
+            text_fg: tailwind::SLATE.c200,
+            muted_fg: tailwind::SLATE.c500,
+            border: color.c700,
+            selected_fg: color.c400,
             normal_row_color: tailwind::SLATE.c950,
             alt_row_color: tailwind::SLATE.c900,
-            footer_border_color: color.c400,
+            success: tailwind::EMERALD.c400,
+            pending: tailwind::YELLOW.c400,
+            changed: tailwind::ORANGE.c400,
+        }
+    }
+
+    fn status_color(&self, construct: &InspectedConstruct) -> Color {
+        match (&construct.status, construct.planned) {
+            (_, true) => self.pending,
+            (LastExecutionStatus::Executed, false) => self.success,
+            _ => self.muted_fg,
+        }
+    }
+
+    fn detail_style(&self, kind: DetailKind) -> Style {
+        match kind {
+            DetailKind::Title => Style::new().fg(self.selected_fg).bold(),
+            DetailKind::Section => Style::new().fg(self.header_fg).bold().underlined(),
+            DetailKind::Field => Style::new().fg(self.text_fg),
+            DetailKind::Changed => Style::new().fg(self.changed),
+            DetailKind::Muted => Style::new().fg(self.muted_fg),
         }
     }
 }
 
 struct App {
+    runbook_name: String,
+    constructs: Vec<InspectedConstruct>,
+    /// Indexes of the constructs matching the search query
+    filtered: Vec<usize>,
+    query: String,
+    searching: bool,
     state: TableState,
-    runbook: Runbook,
-    scroll_state: ScrollbarState,
-    colors: TableColors,
+    details_scroll: u16,
+    colors: InspectColors,
 }
 
 impl App {
-    fn new(runbook: Runbook) -> App {
-        App {
-            state: TableState::default().with_selected(0),
-            scroll_state: ScrollbarState::new(
-                (runbook.execution_context.commands_instances.len() - 1) * ITEM_HEIGHT,
-            ),
-            colors: TableColors::new(&palette::tailwind::EMERALD),
-            runbook,
-        }
+    fn new(runbook_name: &str, constructs: Vec<InspectedConstruct>) -> App {
+        let mut app = App {
+            runbook_name: runbook_name.to_string(),
+            filtered: vec![],
+            constructs,
+            query: String::new(),
+            searching: false,
+            state: TableState::default(),
+            details_scroll: 0,
+            colors: InspectColors::new(&tailwind::EMERALD),
+        };
+        app.apply_filter();
+        app
+    }
+
+    fn apply_filter(&mut self) {
+        self.filtered = self
+            .constructs
+            .iter()
+            .enumerate()
+            .filter(|(_, construct)| construct.matches(&self.query))
+            .map(|(i, _)| i)
+            .collect();
+        self.state.select(if self.filtered.is_empty() { None } else { Some(0) });
+        self.details_scroll = 0;
+    }
+
+    fn selected(&self) -> Option<&InspectedConstruct> {
+        self.state
+            .selected()
+            .and_then(|i| self.filtered.get(i))
+            .and_then(|i| self.constructs.get(*i))
     }
+
     pub fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.runbook.execution_context.commands_instances.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(i));
-        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+        self.move_selection(1);
     }
 
     pub fn previous(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.runbook.execution_context.commands_instances.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
-        };
+        self.move_selection(self.filtered.len().saturating_sub(1));
+    }
+
+    fn move_selection(&mut self, step: usize) {
+        let len = self.filtered.len();
+        if len == 0 {
+            return;
+        }
+        let i = self.state.selected().map(|i| (i + step) % len).unwrap_or(0);
         self.state.select(Some(i));
-        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+        self.details_scroll = 0;
     }
 
-    pub fn set_colors(&mut self) {
-        self.colors = TableColors::new(&tailwind::EMERALD)
+    /// Returns `true` when the inspector should be closed.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return true;
+        }
+        if self.searching {
+            match key.code {
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.query.clear();
+                    self.apply_filter();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.apply_filter();
+                }
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.apply_filter();
+                }
+                KeyCode::Down => self.next(),
+                KeyCode::Up => self.previous(),
+                _ => {}
+            }
+            return false;
+        }
+        match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Esc if self.query.is_empty() => return true,
+            KeyCode::Esc => {
+                self.query.clear();
+                self.apply_filter();
+            }
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Char('j') | KeyCode::Down => self.next(),
+            KeyCode::Char('k') | KeyCode::Up => self.previous(),
+            KeyCode::PageDown => self.details_scroll = self.details_scroll.saturating_add(5),
+            KeyCode::PageUp => self.details_scroll = self.details_scroll.saturating_sub(5),
+            _ => {}
+        }
+        false
     }
 }
 
-pub fn main(runbook: Runbook) -> Result<(), Box<dyn Error>> {
+pub fn main(runbook_name: &str, constructs: Vec<InspectedConstruct>) -> Result<(), Box<dyn Error>> {
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // create app and run it
-    let app = App::new(runbook);
+    let app = App::new(runbook_name, constructs);
     let res = run_app(&mut terminal, app);
 
     // restore terminal
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
-    if let Err(err) = res {
-        println!("{err:?}");
-    }
-
+    res?;
     Ok(())
 }
 
@@ -128,105 +557,148 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
         terminal.draw(|f| ui(f, &mut app))?;
 
         if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                use KeyCode::*;
-                match key.code {
-                    Char('q') | Esc => return Ok(()),
-                    Char('j') | Down => app.next(),
-                    Char('k') | Up => app.previous(),
-                    _ => {}
-                }
+            if key.kind == KeyEventKind::Press && app.handle_key(key) {
+                return Ok(());
             }
         }
     }
 }
 
 fn ui(f: &mut Frame, app: &mut App) {
-    let rects = Layout::vertical([Constraint::Min(5), Constraint::Length(3)]).split(f.size());
+    let [search_area, body_area, footer_area] =
+        Layout::vertical([Constraint::Length(3), Constraint::Min(5), Constraint::Length(3)])
+            .areas(f.area());
+    let [table_area, details_area] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .areas(body_area);
 
-    app.set_colors();
+    f.render_widget(
+        ratatui::widgets::Block::new().style(Style::new().bg(app.colors.buffer_bg)),
+        f.area(),
+    );
 
-    render_table(f, app, rects[0]);
+    render_search(f, app, search_area);
+    render_table(f, app, table_area);
+    render_details(f, app, details_area);
+    render_footer(f, app, footer_area);
+}
 
-    render_scrollbar(f, app, rects[0]);
+fn bordered_block<'a>(title: String, colors: &InspectColors) -> ratatui::widgets::Block<'a> {
+    ratatui::widgets::Block::bordered()
+        .title(Span::styled(title, Style::new().fg(colors.selected_fg).bold()))
+        .border_style(Style::new().fg(colors.border))
+        .border_type(BorderType::Rounded)
+}
 
-    render_footer(f, app, rects[1]);
+fn render_search(f: &mut Frame, app: &App, area: Rect) {
+    let mut spans = vec![
+        Span::styled("/ ", Style::new().fg(app.colors.muted_fg)),
+        Span::styled(app.query.clone(), Style::new().fg(app.colors.text_fg)),
+    ];
+    if app.searching {
+        spans.push(Span::styled("█", Style::new().fg(app.colors.selected_fg)));
+    }
+    let title = format!(
+        " txtx · {} · {}/{} constructs ",
+        app.runbook_name,
+        app.filtered.len(),
+        app.constructs.len()
+    );
+    let search = Paragraph::new(Line::from(spans)).block(bordered_block(title, &app.colors));
+    f.render_widget(search, area);
 }
 
 fn render_table(f: &mut Frame, app: &mut App, area: Rect) {
-    let header_style = Style::default()
-        .fg(app.colors.header_fg)
-        .bg(app.colors.header_bg);
-    let selected_style = Style::default()
-        .add_modifier(Modifier::REVERSED)
-        .fg(app.colors.selected_style_fg);
-
-    let header = ["Construct Uuid"]
-        .iter()
-        .cloned()
+    let header = ["Construct", "Namespace", "Last execution", ""]
+        .into_iter()
         .map(Cell::from)
         .collect::<Row>()
-        .style(header_style)
+        .style(Style::new().fg(app.colors.header_fg).bg(app.colors.header_bg))
         .height(1);
-    let rows = app
-        .runbook
-        .execution_context
-        .commands_instances
-        .iter()
-        .enumerate()
-        .map(|(i, (construct_did, _construct_data))| {
-            let color = match i % 2 {
-                0 => app.colors.normal_row_color,
-                _ => app.colors.alt_row_color,
-            };
-            let row = vec![construct_did.value().to_string()];
-            Row::new(row)
-                .style(Style::new().fg(app.colors.row_fg).bg(color))
-                .height(4)
-        });
-    let bar = " █ ";
-    let t = Table::new(
+
+    let rows = app.filtered.iter().enumerate().map(|(i, construct_index)| {
+        let construct = &app.constructs[*construct_index];
+        let bg = match i % 2 {
+            0 => app.colors.normal_row_color,
+            _ => app.colors.alt_row_color,
+        };
+        let next = match (construct.planned, construct.changes.is_empty()) {
+            (true, false) => Span::styled("changed", Style::new().fg(app.colors.changed)),
+            (true, true) => Span::styled("pending", Style::new().fg(app.colors.pending)),
+            (false, _) => Span::raw(""),
+        };
+        Row::new(vec![
+            Cell::from(construct.id()),
+            Cell::from(construct.namespace.clone()),
+            Cell::from(Span::styled(
+                construct.status.label(),
+                Style::new().fg(app.colors.status_color(construct)),
+            )),
+            Cell::from(next),
+        ])
+        .style(Style::new().fg(app.colors.text_fg).bg(bg))
+    });
+
+    let table = Table::new(
         rows,
         [
-            // + 1 is for padding.
-            Constraint::Length(64),
+            Constraint::Percentage(45),
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
+            Constraint::Percentage(15),
         ],
     )
     .header(header)
-    .highlight_style(selected_style)
-    .highlight_symbol(Text::from(vec![
-        "".into(),
-        bar.into(),
-        bar.into(),
-        "".into(),
-    ]))
-    .bg(app.colors.buffer_bg)
+    .block(bordered_block(" Constructs ".into(), &app.colors))
+    .highlight_style(Style::new().fg(app.colors.selected_fg).add_modifier(Modifier::REVERSED))
+    .highlight_symbol(" █ ")
     .highlight_spacing(HighlightSpacing::Always);
-    f.render_stateful_widget(t, area, &mut app.state);
-}
+    f.render_stateful_widget(table, area, &mut app.state);
 
-fn render_scrollbar(f: &mut Frame, app: &mut App, area: Rect) {
+    let mut scroll_state =
+        ScrollbarState::new(app.filtered.len()).position(app.state.selected().unwrap_or(0));
     f.render_stateful_widget(
         Scrollbar::default()
             .orientation(ScrollbarOrientation::VerticalRight)
             .begin_symbol(None)
             .end_symbol(None),
-        area.inner(&Margin {
-            vertical: 1,
-            horizontal: 1,
-        }),
-        &mut app.scroll_state,
+        area.inner(Margin { vertical: 1, horizontal: 1 }),
+        &mut scroll_state,
     );
 }
 
-fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
-    let info_footer = Paragraph::new(Line::from(INFO_TEXT))
-        .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::new().fg(app.colors.footer_border_color))
+fn render_details(f: &mut Frame, app: &App, area: Rect) {
+    let lines = match app.selected() {
+        Some(construct) => construct
+            .details()
+            .into_iter()
+            .flat_map(|(kind, text)| {
+                let line = Line::from(Span::styled(text, app.colors.detail_style(kind)));
+                match kind {
+                    DetailKind::Section => vec![Line::default(), line],
+                    _ => vec![line],
+                }
+            })
+            .collect(),
+        None => vec![Line::from(Span::styled(
+            "No construct matching the search",
+            Style::new().fg(app.colors.muted_fg),
+        ))],
+    };
+    let details = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((app.details_scroll, 0))
+        .block(bordered_block(" Details ".into(), &app.colors));
+    f.render_widget(details, area);
+}
+
+fn render_footer(f: &mut Frame, app: &App, area: Rect) {
+    let info_text = if app.searching { SEARCH_INFO_TEXT } else { NAVIGATION_INFO_TEXT };
+    let footer =
+        Paragraph::new(Line::from(info_text)).style(Style::new().fg(app.colors.muted_fg)).block(
+            ratatui::widgets::Block::bordered()
+                .border_style(Style::new().fg(app.colors.border))
                 .border_type(BorderType::Double),
         );
-    f.render_widget(info_footer, area);
+    f.render_widget(footer, area);
 }
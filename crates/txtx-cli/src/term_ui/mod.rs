@@ -1 +1,2 @@
-// pub mod inspect;
+pub mod inspect;
+pub mod supervisor;
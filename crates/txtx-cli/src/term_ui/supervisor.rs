@@ -0,0 +1,869 @@
+use std::{collections::BTreeMap, error::Error, io, sync::Arc, time::Duration};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{prelude::*, style::palette::tailwind, widgets::*};
+use tokio::sync::{
+    broadcast::{self, error::TryRecvError},
+    RwLock,
+};
+use txtx_core::kit::{
+    types::{
+        frontend::{
+            ActionGroup, ActionItemRequest, ActionItemRequestType, ActionItemResponse,
+            ActionItemResponseType, ActionItemStatus, Block, BlockEvent, Panel,
+            PickInputOptionRequest, ProgressBarStatusColor, ProvideInputRequest,
+            ProvideSignedTransactionRequest, ProvideSignedTransactionResponse,
+            ProvidedInputResponse, ReviewedInputResponse,
+        },
+        types::{Type, Value},
+    },
+    uuid::Uuid,
+};
+
+const TICK_RATE: Duration = Duration::from_millis(50);
+
+const NAVIGATION_INFO_TEXT: &str =
+    "(↑/↓) select | (enter) review, edit or confirm | (v) validate | (esc) close modal | (q) quit";
+const EDIT_INFO_TEXT: &str = "(enter) submit | (esc) cancel";
+const PICK_INFO_TEXT: &str = "(↑/↓) select | (enter) pick | (esc) cancel";
+const CONFIRM_INFO_TEXT: &str = "(y) approve and sign | (n) cancel";
+const CONFIRM_SKIPPABLE_INFO_TEXT: &str = "(y) approve and sign | (s) skip | (n) cancel";
+
+struct SupervisorColors {
+    buffer_bg: Color,
+    header_fg: Color,
+    text_fg: Color,
+    muted_fg: Color,
+    border: Color,
+    selected_fg: Color,
+    success: Color,
+    pending: Color,
+    error: Color,
+    info: Color,
+}
+
+impl SupervisorColors {
+    fn new(color: &tailwind::Palette) -> Self {
+        Self {
+            buffer_bg: tailwind::SLATE.c950,
+            header_fg: color.c400,
+            text_fg: tailwind::SLATE.c200,
+            muted_fg: tailwind::SLATE.c500,
+            border: color.c700,
+            selected_fg: color.c300,
+            success: tailwind::EMERALD.c400,
+            pending: tailwind::YELLOW.c400,
+            error: tailwind::RED.c400,
+            info: tailwind::PURPLE.c400,
+        }
+    }
+
+    fn progress_color(&self, color: &ProgressBarStatusColor) -> Color {
+        match color {
+            ProgressBarStatusColor::Green => self.success,
+            ProgressBarStatusColor::Yellow => self.pending,
+            ProgressBarStatusColor::Red => self.error,
+            ProgressBarStatusColor::Purple => self.info,
+        }
+    }
+}
+
+/// A line of the action items list: either a group title, or an action item.
+enum Entry {
+    Title(String),
+    Item(ActionItemRequest),
+}
+
+enum Mode {
+    Navigate,
+    EditInput { item: ActionItemRequest, request: ProvideInputRequest, buffer: String },
+    PickOption { item: ActionItemRequest, request: PickInputOptionRequest, state: ListState },
+    ConfirmTransaction { item: ActionItemRequest, request: ProvideSignedTransactionRequest },
+}
+
+enum Control {
+    Continue,
+    Quit,
+}
+
+struct App {
+    runbook_name: String,
+    blocks: Vec<Block>,
+    entries: Vec<Entry>,
+    list_state: ListState,
+    open_modal: Option<Uuid>,
+    mode: Mode,
+    message: Option<(String, Color)>,
+    runbook_completed: bool,
+    colors: SupervisorColors,
+    action_item_events_tx: broadcast::Sender<ActionItemResponse>,
+}
+
+impl App {
+    fn new(
+        runbook_name: &str,
+        action_item_events_tx: broadcast::Sender<ActionItemResponse>,
+    ) -> App {
+        App {
+            runbook_name: runbook_name.to_string(),
+            blocks: vec![],
+            entries: vec![],
+            list_state: ListState::default(),
+            open_modal: None,
+            mode: Mode::Navigate,
+            message: None,
+            runbook_completed: false,
+            colors: SupervisorColors::new(&tailwind::EMERALD),
+            action_item_events_tx,
+        }
+    }
+
+    /// Replaces the blocks with the latest content of the block store, and rebuilds the list of
+    /// action items of the current panel while preserving the selection.
+    fn refresh(&mut self, blocks: Vec<Block>) {
+        let selected_id = self.selected_item().map(|item| item.id.clone());
+        self.blocks = blocks;
+        self.entries = match self.current_panel() {
+            Some((_, _, groups)) => build_entries(groups),
+            None => vec![],
+        };
+        let selected = self
+            .entries
+            .iter()
+            .position(|entry| match (entry, &selected_id) {
+                (Entry::Item(item), Some(id)) => item.id.eq(id),
+                _ => false,
+            })
+            .or_else(|| self.entries.iter().position(|entry| matches!(entry, Entry::Item(_))));
+        self.list_state.select(selected);
+    }
+
+    /// The panel being supervised: the open modal if any, otherwise the latest action panel.
+    fn current_panel(&self) -> Option<(&str, &str, &Vec<ActionGroup>)> {
+        if let Some(modal_uuid) = self.open_modal {
+            let modal = self.blocks.iter().find_map(|block| match &block.panel {
+                Panel::ModalPanel(panel) if block.uuid == modal_uuid => {
+                    Some((panel.title.as_str(), panel.description.as_str(), &panel.groups))
+                }
+                _ => None,
+            });
+            if modal.is_some() {
+                return modal;
+            }
+        }
+        self.blocks.iter().rev().filter(|block| block.visible).find_map(|block| {
+            match &block.panel {
+                Panel::ActionPanel(panel) => {
+                    Some((panel.title.as_str(), panel.description.as_str(), &panel.groups))
+                }
+                _ => None,
+            }
+        })
+    }
+
+    fn selected_item(&self) -> Option<&ActionItemRequest> {
+        match self.list_state.selected().and_then(|i| self.entries.get(i)) {
+            Some(Entry::Item(item)) => Some(item),
+            _ => None,
+        }
+    }
+
+    fn select_next(&mut self) {
+        self.select_item(1);
+    }
+
+    fn select_previous(&mut self) {
+        self.select_item(self.entries.len().saturating_sub(1));
+    }
+
+    /// Moves the selection by `step` entries (modulo the number of entries), skipping titles.
+    fn select_item(&mut self, step: usize) {
+        let len = self.entries.len();
+        if len == 0 {
+            return;
+        }
+        let mut i = self.list_state.selected().unwrap_or(0);
+        for _ in 0..len {
+            i = (i + step) % len;
+            if let Entry::Item(_) = self.entries[i] {
+                self.list_state.select(Some(i));
+                return;
+            }
+        }
+    }
+
+    fn set_message(&mut self, message: &str, color: Color) {
+        self.message = Some((message.to_string(), color));
+    }
+
+    fn send_response(&mut self, item: &ActionItemRequest, payload: ActionItemResponseType) {
+        let response = ActionItemResponse { action_item_id: item.id.clone(), payload };
+        if self.action_item_events_tx.send(response).is_err() {
+            self.set_message("the runbook is not running anymore", self.colors.error);
+        }
+    }
+
+    fn validate_current_panel(&mut self) {
+        let validate_item = self.entries.iter().find_map(|entry| match entry {
+            Entry::Item(item) => match item.action_type {
+                ActionItemRequestType::ValidateBlock(_) | ActionItemRequestType::ValidateModal => {
+                    Some(item.clone())
+                }
+                _ => None,
+            },
+            _ => None,
+        });
+        match validate_item {
+            Some(item) => self.activate_item(item),
+            None => self.set_message("nothing to validate in this panel", self.colors.pending),
+        }
+    }
+
+    fn activate_item(&mut self, item: ActionItemRequest) {
+        self.message = None;
+        if let ActionItemStatus::Blocked = item.action_status {
+            self.set_message("this action is waiting for other actions", self.colors.pending);
+            return;
+        }
+        match &item.action_type {
+            ActionItemRequestType::ReviewInput(request) => {
+                let value_checked = !matches!(item.action_status, ActionItemStatus::Success(_));
+                self.send_response(
+                    &item,
+                    ActionItemResponseType::ReviewInput(ReviewedInputResponse {
+                        input_name: request.input_name.clone(),
+                        value_checked,
+                        force_execution: request.force_execution,
+                    }),
+                );
+            }
+            ActionItemRequestType::ProvideInput(request) => {
+                let buffer = request.default_value.as_ref().map(format_value).unwrap_or_default();
+                self.mode = Mode::EditInput { request: request.clone(), item, buffer };
+            }
+            ActionItemRequestType::PickInputOption(request) => {
+                if request.options.is_empty() {
+                    return;
+                }
+                let selected = request.options.iter().position(|o| o.eq(&request.selected));
+                self.mode = Mode::PickOption {
+                    request: request.clone(),
+                    item,
+                    state: ListState::default().with_selected(selected.or(Some(0))),
+                };
+            }
+            ActionItemRequestType::ProvideSignedTransaction(request) => {
+                if let ActionItemStatus::Success(_) = item.action_status {
+                    return;
+                }
+                if !request.only_approval_needed {
+                    self.set_message(
+                        "this transaction must be signed with a wallet: use the browser supervisor",
+                        self.colors.pending,
+                    );
+                    return;
+                }
+                self.mode = Mode::ConfirmTransaction { request: request.clone(), item };
+            }
+            ActionItemRequestType::ProvidePublicKey(_)
+            | ActionItemRequestType::ProvideSignedMessage(_)
+            | ActionItemRequestType::SendTransaction(_) => {
+                self.set_message(
+                    "this action requires a wallet: use the browser supervisor",
+                    self.colors.pending,
+                );
+            }
+            ActionItemRequestType::OpenModal(data) => {
+                self.open_modal = Some(data.modal_uuid);
+                self.refresh(self.blocks.clone());
+            }
+            ActionItemRequestType::ValidateBlock(_) => {
+                self.send_response(&item, ActionItemResponseType::ValidateBlock);
+            }
+            ActionItemRequestType::ValidateModal => {
+                self.send_response(&item, ActionItemResponseType::ValidateModal);
+                self.open_modal = None;
+                self.refresh(self.blocks.clone());
+            }
+            ActionItemRequestType::DisplayOutput(_)
+            | ActionItemRequestType::DisplayErrorLog(_)
+            | ActionItemRequestType::BeginFlow(_) => {}
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Control {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Control::Quit;
+        }
+        match std::mem::replace(&mut self.mode, Mode::Navigate) {
+            Mode::Navigate => match key.code {
+                KeyCode::Char('q') => return Control::Quit,
+                KeyCode::Esc if self.open_modal.is_some() => {
+                    self.open_modal = None;
+                    self.refresh(self.blocks.clone());
+                }
+                KeyCode::Esc => return Control::Quit,
+                KeyCode::Char('j') | KeyCode::Down => self.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => self.select_previous(),
+                KeyCode::Char('v') => self.validate_current_panel(),
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    if let Some(item) = self.selected_item().cloned() {
+                        self.activate_item(item);
+                    }
+                }
+                _ => {}
+            },
+            Mode::EditInput { item, request, mut buffer } => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => match parse_input_value(&buffer, &request.typing) {
+                    Ok(updated_value) => {
+                        self.send_response(
+                            &item,
+                            ActionItemResponseType::ProvideInput(ProvidedInputResponse {
+                                input_name: request.input_name.clone(),
+                                updated_value,
+                            }),
+                        );
+                    }
+                    Err(e) => {
+                        self.set_message(&e, self.colors.error);
+                        self.mode = Mode::EditInput { item, request, buffer };
+                    }
+                },
+                KeyCode::Backspace => {
+                    buffer.pop();
+                    self.mode = Mode::EditInput { item, request, buffer };
+                }
+                KeyCode::Char(c) => {
+                    buffer.push(c);
+                    self.mode = Mode::EditInput { item, request, buffer };
+                }
+                _ => self.mode = Mode::EditInput { item, request, buffer },
+            },
+            Mode::PickOption { item, request, mut state } => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => {
+                    if let Some(option) = state.selected().and_then(|i| request.options.get(i)) {
+                        self.send_response(
+                            &item,
+                            ActionItemResponseType::PickInputOption(option.value.clone()),
+                        );
+                    }
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    let next = state.selected().map_or(0, |i| (i + 1) % request.options.len());
+                    state.select(Some(next));
+                    self.mode = Mode::PickOption { item, request, state };
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    let len = request.options.len();
+                    let previous = state.selected().map_or(0, |i| (i + len - 1) % len);
+                    state.select(Some(previous));
+                    self.mode = Mode::PickOption { item, request, state };
+                }
+                _ => self.mode = Mode::PickOption { item, request, state },
+            },
+            Mode::ConfirmTransaction { item, request } => match key.code {
+                KeyCode::Char('y') => {
+                    self.send_response(
+                        &item,
+                        ActionItemResponseType::ProvideSignedTransaction(
+                            ProvideSignedTransactionResponse {
+                                signed_transaction_bytes: None,
+                                signature_approved: Some(true),
+                                signer_uuid: request.signer_uuid.clone(),
+                            },
+                        ),
+                    );
+                }
+                KeyCode::Char('s') if request.skippable => {
+                    self.send_response(
+                        &item,
+                        ActionItemResponseType::ProvideSignedTransaction(
+                            ProvideSignedTransactionResponse {
+                                signed_transaction_bytes: None,
+                                signature_approved: None,
+                                signer_uuid: request.signer_uuid.clone(),
+                            },
+                        ),
+                    );
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {}
+                _ => self.mode = Mode::ConfirmTransaction { item, request },
+            },
+        }
+        Control::Continue
+    }
+}
+
+/// Starts the terminal supervisor, rendering the blocks of the `block_store` and sending the
+/// operator's responses to the runbook runloop through `action_item_events_tx`.
+/// Returns when the operator quits or when the block store stops processing events.
+pub fn main(
+    runbook_name: &str,
+    block_store: Arc<RwLock<BTreeMap<usize, Block>>>,
+    mut block_events_rx: broadcast::Receiver<BlockEvent>,
+    action_item_events_tx: broadcast::Sender<ActionItemResponse>,
+) -> Result<(), Box<dyn Error>> {
+    // setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // create app and run it
+    let app = App::new(runbook_name, action_item_events_tx);
+    let res = run_app(&mut terminal, app, &block_store, &mut block_events_rx);
+
+    // restore terminal
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    res?;
+    Ok(())
+}
+
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    mut app: App,
+    block_store: &Arc<RwLock<BTreeMap<usize, Block>>>,
+    block_events_rx: &mut broadcast::Receiver<BlockEvent>,
+) -> io::Result<()> {
+    loop {
+        loop {
+            match block_events_rx.try_recv() {
+                Ok(BlockEvent::RunbookCompleted) => {
+                    app.runbook_completed = true;
+                    app.set_message("Runbook complete! Press (q) to exit.", app.colors.success);
+                }
+                Ok(BlockEvent::Exit) | Err(TryRecvError::Closed) => return Ok(()),
+                Ok(_) | Err(TryRecvError::Lagged(_)) => {}
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+        let blocks = block_store.blocking_read().values().cloned().collect();
+        app.refresh(blocks);
+
+        terminal.draw(|f| ui(f, &mut app))?;
+
+        if event::poll(TICK_RATE)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    if let Control::Quit = app.handle_key(key) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn build_entries(groups: &Vec<ActionGroup>) -> Vec<Entry> {
+    let mut entries = vec![];
+    for group in groups.iter() {
+        if !group.title.is_empty() {
+            entries.push(Entry::Title(group.title.clone()));
+        }
+        for sub_group in group.sub_groups.iter() {
+            if let Some(title) = sub_group.title.as_ref().filter(|t| !t.is_empty()) {
+                entries.push(Entry::Title(title.clone()));
+            }
+            for item in sub_group.action_items.iter() {
+                entries.push(Entry::Item(item.clone()));
+            }
+        }
+    }
+    entries
+}
+
+/// Parses the value typed by the operator for an input of type `typing`.
+fn parse_input_value(input: &str, typing: &Type) -> Result<Value, String> {
+    match typing {
+        Type::String => Ok(Value::string(input.to_string())),
+        Type::Integer => input
+            .trim()
+            .parse::<i128>()
+            .map(Value::integer)
+            .map_err(|e| format!("invalid integer: {e}")),
+        Type::Float => {
+            input.trim().parse::<f64>().map(Value::float).map_err(|e| format!("invalid float: {e}"))
+        }
+        Type::Bool => match input.trim() {
+            "true" => Ok(Value::bool(true)),
+            "false" => Ok(Value::bool(false)),
+            _ => Err("invalid bool: expected 'true' or 'false'".into()),
+        },
+        _ => Err(format!(
+            "inputs of type {} can't be edited from the terminal: use the browser supervisor",
+            typing.to_string()
+        )),
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Array(_) | Value::Object(_) => {
+            serde_json::to_string_pretty(&value.to_json()).unwrap_or_else(|_| value.to_string())
+        }
+        _ => value.to_string(),
+    }
+}
+
+fn item_title(item: &ActionItemRequest) -> String {
+    if !item.title.is_empty() {
+        return item.title.clone();
+    }
+    match &item.action_type {
+        ActionItemRequestType::ReviewInput(request) => request.input_name.clone(),
+        ActionItemRequestType::ProvideInput(request) => request.input_name.clone(),
+        ActionItemRequestType::DisplayOutput(request) => request.name.clone(),
+        ActionItemRequestType::DisplayErrorLog(request) => request.diagnostic.message.clone(),
+        _ => String::new(),
+    }
+}
+
+fn status_span(status: &ActionItemStatus, colors: &SupervisorColors) -> Span<'static> {
+    match status {
+        ActionItemStatus::Todo => Span::styled("[ ]", Style::new().fg(colors.text_fg)),
+        ActionItemStatus::Success(_) => Span::styled("[✓]", Style::new().fg(colors.success)),
+        ActionItemStatus::InProgress(_) => Span::styled("[…]", Style::new().fg(colors.pending)),
+        ActionItemStatus::Error(_) => Span::styled("[x]", Style::new().fg(colors.error)),
+        ActionItemStatus::Warning(_) => Span::styled("[!]", Style::new().fg(colors.pending)),
+        ActionItemStatus::Blocked => Span::styled("[-]", Style::new().fg(colors.muted_fg)),
+    }
+}
+
+fn labeled_value(label: &str, value: String, colors: &SupervisorColors) -> Vec<Line<'static>> {
+    let mut lines =
+        vec![Line::from(Span::styled(format!("{label}:"), Style::new().fg(colors.header_fg)))];
+    lines.extend(value.lines().map(|line| Line::from(format!("  {line}"))));
+    lines
+}
+
+fn item_details(item: &ActionItemRequest, colors: &SupervisorColors) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(Span::styled(item_title(item), Style::new().bold()))];
+    if let Some(description) = item.description.as_ref().filter(|d| !d.is_empty()) {
+        lines.extend(description.lines().map(|line| Line::from(line.to_string())));
+    }
+    lines.push(Line::default());
+
+    match &item.action_type {
+        ActionItemRequestType::ReviewInput(request) => {
+            lines.extend(labeled_value(&request.input_name, format_value(&request.value), colors));
+        }
+        ActionItemRequestType::ProvideInput(request) => {
+            lines.extend(labeled_value("input", request.input_name.clone(), colors));
+            lines.extend(labeled_value("type", request.typing.to_string(), colors));
+            if let Some(default_value) = &request.default_value {
+                lines.extend(labeled_value("default value", format_value(default_value), colors));
+            }
+        }
+        ActionItemRequestType::PickInputOption(request) => {
+            lines.extend(labeled_value(
+                "selected",
+                request.selected.displayed_value.clone(),
+                colors,
+            ));
+            let options = request
+                .options
+                .iter()
+                .map(|o| o.displayed_value.clone())
+                .collect::<Vec<_>>()
+                .join("\n");
+            lines.extend(labeled_value("options", options, colors));
+        }
+        ActionItemRequestType::ProvideSignedTransaction(request) => {
+            lines.extend(labeled_value(
+                "network",
+                format!("{} ({})", request.network_id, request.namespace),
+                colors,
+            ));
+            if let Some(address) = &request.expected_signer_address {
+                lines.extend(labeled_value("signer", address.clone(), colors));
+            }
+            let payload = request.formatted_payload.as_ref().unwrap_or(&request.payload);
+            lines.extend(labeled_value("transaction", format_value(payload), colors));
+        }
+        ActionItemRequestType::SendTransaction(request) => {
+            lines.extend(labeled_value(
+                "network",
+                format!("{} ({})", request.network_id, request.namespace),
+                colors,
+            ));
+            let payload = request.formatted_payload.as_ref().unwrap_or(&request.payload);
+            lines.extend(labeled_value("transaction", format_value(payload), colors));
+        }
+        ActionItemRequestType::ProvideSignedMessage(request) => {
+            lines.extend(labeled_value("message", format_value(&request.message), colors));
+        }
+        ActionItemRequestType::ProvidePublicKey(request) => {
+            lines.extend(labeled_value("message", request.message.clone(), colors));
+        }
+        ActionItemRequestType::DisplayOutput(request) => {
+            lines.extend(labeled_value(&request.name, format_value(&request.value), colors));
+        }
+        ActionItemRequestType::DisplayErrorLog(request) => {
+            lines.extend(labeled_value("error", request.diagnostic.message.clone(), colors));
+        }
+        ActionItemRequestType::OpenModal(data) => {
+            lines.push(Line::from(format!("Press (enter) to open '{}'", data.title)));
+        }
+        ActionItemRequestType::ValidateBlock(_) | ActionItemRequestType::ValidateModal => {
+            lines.push(Line::from("Press (enter) to validate this panel"));
+        }
+        ActionItemRequestType::BeginFlow(_) => {}
+    }
+
+    let status = match &item.action_status {
+        ActionItemStatus::Success(Some(message)) => Some((message.clone(), colors.success)),
+        ActionItemStatus::InProgress(message) => Some((message.clone(), colors.pending)),
+        ActionItemStatus::Error(diag) => Some((diag.message.clone(), colors.error)),
+        ActionItemStatus::Warning(diag) => Some((diag.message.clone(), colors.pending)),
+        _ => None,
+    };
+    if let Some((message, color)) = status {
+        lines.push(Line::default());
+        lines.extend(
+            message
+                .lines()
+                .map(|line| Line::from(Span::styled(line.to_string(), Style::new().fg(color)))),
+        );
+    }
+    lines
+}
+
+/// The latest status of each construct of the visible progress bars, followed by the errors
+/// reported after the current panel.
+fn activity_lines(app: &App) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    for block in app.blocks.iter().filter(|b| b.visible) {
+        match &block.panel {
+            Panel::ProgressBar(constructs) => {
+                for construct in constructs.iter() {
+                    let Some(status) = construct.statuses.last() else {
+                        continue;
+                    };
+                    lines.push(Line::from(vec![
+                        Span::styled(
+                            format!("{:<16}", status.status),
+                            Style::new().fg(app.colors.progress_color(&status.status_color)),
+                        ),
+                        Span::raw(status.message.clone()),
+                    ]));
+                }
+            }
+            Panel::ErrorPanel(panel) => {
+                for group in panel.groups.iter() {
+                    for sub_group in group.sub_groups.iter() {
+                        for item in sub_group.action_items.iter() {
+                            if let ActionItemRequestType::DisplayErrorLog(log) = &item.action_type {
+                                lines.push(Line::from(Span::styled(
+                                    format!("error: {}", log.diagnostic.message),
+                                    Style::new().fg(app.colors.error),
+                                )));
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    let activity = activity_lines(app);
+    let activity_height = if activity.is_empty() { 0 } else { activity.len().min(8) as u16 + 2 };
+    let [header_area, body_area, activity_area, footer_area] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Min(6),
+        Constraint::Length(activity_height),
+        Constraint::Length(3),
+    ])
+    .areas(f.area());
+
+    f.render_widget(
+        ratatui::widgets::Block::new().style(Style::new().bg(app.colors.buffer_bg)),
+        f.area(),
+    );
+
+    render_header(f, app, header_area);
+    render_action_items(f, app, body_area);
+    if !activity.is_empty() {
+        render_activity(f, app, activity, activity_area);
+    }
+    render_footer(f, app, footer_area);
+    render_popup(f, app);
+}
+
+fn bordered_block<'a>(title: &'a str, colors: &SupervisorColors) -> ratatui::widgets::Block<'a> {
+    ratatui::widgets::Block::bordered()
+        .title(Span::styled(title, Style::new().fg(colors.header_fg).bold()))
+        .border_style(Style::new().fg(colors.border))
+        .border_type(BorderType::Rounded)
+}
+
+fn render_header(f: &mut Frame, app: &App, area: Rect) {
+    let (title, description) = match app.current_panel() {
+        Some((title, description, _)) => (title.to_string(), description.to_string()),
+        None => ("Waiting for the runbook".to_string(), String::new()),
+    };
+    let lines = vec![
+        Line::from(Span::styled(title.to_uppercase(), Style::new().fg(app.colors.text_fg).bold())),
+        Line::from(Span::styled(description, Style::new().fg(app.colors.muted_fg))),
+    ];
+    let runbook_title = match app.runbook_completed {
+        true => format!(" txtx · {} · completed ", app.runbook_name),
+        false => format!(" txtx · {} ", app.runbook_name),
+    };
+    let header = Paragraph::new(lines).block(bordered_block(&runbook_title, &app.colors));
+    f.render_widget(header, area);
+}
+
+fn render_action_items(f: &mut Frame, app: &mut App, area: Rect) {
+    let [list_area, details_area] =
+        Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(area);
+
+    let items = app
+        .entries
+        .iter()
+        .map(|entry| match entry {
+            Entry::Title(title) => ListItem::new(Line::from(Span::styled(
+                title.clone(),
+                Style::new().fg(app.colors.header_fg).bold(),
+            ))),
+            Entry::Item(item) => ListItem::new(Line::from(vec![
+                Span::raw("  "),
+                status_span(&item.action_status, &app.colors),
+                Span::raw(" "),
+                Span::styled(item_title(item), Style::new().fg(app.colors.text_fg)),
+            ])),
+        })
+        .collect::<Vec<_>>();
+    let list = List::new(items)
+        .block(bordered_block(" Actions ", &app.colors))
+        .highlight_style(Style::new().fg(app.colors.selected_fg).add_modifier(Modifier::REVERSED))
+        .highlight_spacing(HighlightSpacing::Always);
+    f.render_stateful_widget(list, list_area, &mut app.list_state);
+
+    let details = match app.selected_item() {
+        Some(item) => item_details(item, &app.colors),
+        None => vec![],
+    };
+    let details = Paragraph::new(details)
+        .style(Style::new().fg(app.colors.text_fg))
+        .wrap(Wrap { trim: false })
+        .block(bordered_block(" Details ", &app.colors));
+    f.render_widget(details, details_area);
+}
+
+fn render_activity(f: &mut Frame, app: &App, lines: Vec<Line<'static>>, area: Rect) {
+    // keep the latest lines visible
+    let scroll = lines.len().saturating_sub(area.height.saturating_sub(2) as usize) as u16;
+    let activity =
+        Paragraph::new(lines).scroll((scroll, 0)).block(bordered_block(" Activity ", &app.colors));
+    f.render_widget(activity, area);
+}
+
+fn render_footer(f: &mut Frame, app: &App, area: Rect) {
+    let info_text = match &app.mode {
+        Mode::Navigate => NAVIGATION_INFO_TEXT,
+        Mode::EditInput { .. } => EDIT_INFO_TEXT,
+        Mode::PickOption { .. } => PICK_INFO_TEXT,
+        Mode::ConfirmTransaction { request, .. } if request.skippable => {
+            CONFIRM_SKIPPABLE_INFO_TEXT
+        }
+        Mode::ConfirmTransaction { .. } => CONFIRM_INFO_TEXT,
+    };
+    let mut spans = vec![Span::styled(info_text, Style::new().fg(app.colors.muted_fg))];
+    if let Some((message, color)) = &app.message {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(message.clone(), Style::new().fg(*color)));
+    }
+    let footer = Paragraph::new(Line::from(spans)).block(
+        ratatui::widgets::Block::bordered()
+            .border_style(Style::new().fg(app.colors.border))
+            .border_type(BorderType::Double),
+    );
+    f.render_widget(footer, area);
+}
+
+fn render_popup(f: &mut Frame, app: &mut App) {
+    let area = centered_rect(f.area(), 60, 50);
+    match &mut app.mode {
+        Mode::Navigate => {}
+        Mode::EditInput { request, buffer, .. } => {
+            let lines = vec![
+                Line::from(Span::styled(
+                    format!("type: {}", request.typing.to_string()),
+                    Style::new().fg(app.colors.muted_fg),
+                )),
+                Line::default(),
+                Line::from(vec![
+                    Span::styled(buffer.clone(), Style::new().fg(app.colors.text_fg)),
+                    Span::styled("█", Style::new().fg(app.colors.selected_fg)),
+                ]),
+            ];
+            let title = format!(" Edit '{}' ", request.input_name);
+            let popup = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(bordered_block(&title, &app.colors))
+                .style(Style::new().bg(app.colors.buffer_bg));
+            f.render_widget(Clear, area);
+            f.render_widget(popup, area);
+        }
+        Mode::PickOption { item, request, state } => {
+            let options = request
+                .options
+                .iter()
+                .map(|o| ListItem::new(o.displayed_value.clone()))
+                .collect::<Vec<_>>();
+            let title = format!(" {} ", item_title(item));
+            let popup = List::new(options)
+                .block(bordered_block(&title, &app.colors))
+                .style(Style::new().fg(app.colors.text_fg).bg(app.colors.buffer_bg))
+                .highlight_style(
+                    Style::new().fg(app.colors.selected_fg).add_modifier(Modifier::REVERSED),
+                );
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(popup, area, state);
+        }
+        Mode::ConfirmTransaction { item, .. } => {
+            let mut lines = item_details(item, &app.colors);
+            lines.push(Line::default());
+            lines.push(Line::from(Span::styled(
+                "Approve this transaction to sign it with the local signer.",
+                Style::new().fg(app.colors.pending),
+            )));
+            let popup = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(bordered_block(" Confirm transaction ", &app.colors))
+                .style(Style::new().fg(app.colors.text_fg).bg(app.colors.buffer_bg));
+            f.render_widget(Clear, area);
+            f.render_widget(popup, area);
+        }
+    }
+}
+
+fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let [_, vertical, _] = Layout::vertical([
+        Constraint::Percentage((100 - percent_y) / 2),
+        Constraint::Percentage(percent_y),
+        Constraint::Percentage((100 - percent_y) / 2),
+    ])
+    .areas(area);
+    let [_, centered, _] = Layout::horizontal([
+        Constraint::Percentage((100 - percent_x) / 2),
+        Constraint::Percentage(percent_x),
+        Constraint::Percentage((100 - percent_x) / 2),
+    ])
+    .areas(vertical);
+    centered
+}
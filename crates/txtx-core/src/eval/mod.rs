@@ -969,6 +969,49 @@ pub enum ExpressionEvaluationStatus {
     DependencyNotComputed,
 }
 
+/// Evaluates the elements of a string or heredoc template, interpolating the result of each
+/// template expression in the resulting string.
+fn eval_template_elements<'a>(
+    elements: impl Iterator<Item = &'a Element>,
+    dependencies_execution_results: &DependencyExecutionResultCache,
+    package_id: &PackageId,
+    runbook_workspace_context: &RunbookWorkspaceContext,
+    runbook_execution_context: &RunbookExecutionContext,
+    runtime_context: &RuntimeContext,
+) -> Result<ExpressionEvaluationStatus, Diagnostic> {
+    let mut res = String::new();
+    for element in elements {
+        match element {
+            Element::Literal(literal) => {
+                res.push_str(literal.value());
+            }
+            Element::Interpolation(interpolation) => {
+                let value = match eval_expression(
+                    &interpolation.expr,
+                    dependencies_execution_results,
+                    package_id,
+                    runbook_workspace_context,
+                    runbook_execution_context,
+                    runtime_context,
+                )? {
+                    ExpressionEvaluationStatus::CompleteOk(result) => result.to_string(),
+                    ExpressionEvaluationStatus::CompleteErr(e) => {
+                        return Ok(ExpressionEvaluationStatus::CompleteErr(e))
+                    }
+                    ExpressionEvaluationStatus::DependencyNotComputed => {
+                        return Ok(ExpressionEvaluationStatus::DependencyNotComputed)
+                    }
+                };
+                res.push_str(&value);
+            }
+            Element::Directive(_) => {
+                return Err(diagnosed_error!("string templates with directives not yet supported"))
+            }
+        };
+    }
+    Ok(ExpressionEvaluationStatus::CompleteOk(Value::string(res)))
+}
+
 pub fn eval_expression(
     expr: &Expression,
     dependencies_execution_results: &DependencyExecutionResultCache,
@@ -1077,41 +1120,25 @@ pub fn eval_expression(
         }
         // Represents a string containing template interpolations and template directives.
         Expression::StringTemplate(string_template) => {
-            let mut res = String::new();
-            for element in string_template.into_iter() {
-                match element {
-                    Element::Literal(literal) => {
-                        res.push_str(literal.value());
-                    }
-                    Element::Interpolation(interpolation) => {
-                        let value = match eval_expression(
-                            &interpolation.expr,
-                            dependencies_execution_results,
-                            package_id,
-                            runbook_workspace_context,
-                            runbook_execution_context,
-                            runtime_context,
-                        )? {
-                            ExpressionEvaluationStatus::CompleteOk(result) => result.to_string(),
-                            ExpressionEvaluationStatus::CompleteErr(e) => {
-                                return Ok(ExpressionEvaluationStatus::CompleteErr(e))
-                            }
-                            ExpressionEvaluationStatus::DependencyNotComputed => {
-                                return Ok(ExpressionEvaluationStatus::DependencyNotComputed)
-                            }
-                        };
-                        res.push_str(&value);
-                    }
-                    Element::Directive(_) => {
-                        unimplemented!("string templates with directives not yet supported")
-                    }
-                };
-            }
-            Value::string(res)
+            return eval_template_elements(
+                string_template.iter(),
+                dependencies_execution_results,
+                package_id,
+                runbook_workspace_context,
+                runbook_execution_context,
+                runtime_context,
+            );
         }
         // Represents an HCL heredoc template.
-        Expression::HeredocTemplate(_heredoc_template) => {
-            unimplemented!()
+        Expression::HeredocTemplate(heredoc_template) => {
+            return eval_template_elements(
+                heredoc_template.template.iter(),
+                dependencies_execution_results,
+                package_id,
+                runbook_workspace_context,
+                runbook_execution_context,
+                runtime_context,
+            );
         }
         // Represents a sub-expression wrapped in parenthesis.
         Expression::Parenthesis(_sub_expr) => {
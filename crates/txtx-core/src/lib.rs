@@ -10,8 +10,8 @@ mod constants;
 pub mod errors;
 pub mod eval;
 pub mod manifest;
-// pub mod snapshot;
 pub mod runbook;
+pub mod snapshot;
 pub mod std;
 pub mod templates;
 pub mod types;
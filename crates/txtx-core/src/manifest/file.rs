@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use txtx_addon_kit::{
     helpers::fs::{get_txtx_files_paths, FileLocation},
     indexmap::IndexMap,
-    types::RunbookId,
+    types::{backends::StateBackendConfig, RunbookId},
 };
 
 use crate::runbook::{Runbook, RunbookSources};
@@ -15,12 +15,20 @@ pub struct WorkspaceManifestFile {
     pub id: String,
     pub runbooks: Vec<RunbookMetadataFile>,
     pub environments: IndexMap<String, IndexMap<String, String>>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub state_backends: IndexMap<String, StateBackendConfig>,
 }
 
 impl WorkspaceManifestFile {
     pub fn new(name: String) -> Self {
         let id = normalize_user_input(&name);
-        WorkspaceManifestFile { name, id, runbooks: vec![], environments: IndexMap::new() }
+        WorkspaceManifestFile {
+            name,
+            id,
+            runbooks: vec![],
+            environments: IndexMap::new(),
+            state_backends: IndexMap::new(),
+        }
     }
 }
 
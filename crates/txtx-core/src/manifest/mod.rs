@@ -1,11 +1,15 @@
+use std::fmt::Display;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use crate::runbook::{
-    RunbookExecutionSnapshot, RunbookTopLevelInputsMap, DEFAULT_TOP_LEVEL_INPUTS_NAME,
+    now_as_string, RunbookExecutionSnapshot, RunbookTopLevelInputsMap,
+    DEFAULT_TOP_LEVEL_INPUTS_NAME,
 };
 use txtx_addon_kit::helpers::fs::{FileAccessor, FileLocation};
 use txtx_addon_kit::indexmap::IndexMap;
 use txtx_addon_kit::serde::{Deserialize, Serialize};
+use txtx_addon_kit::types::backends::{LocalStateBackend, StateBackend, StateBackendConfig};
 
 pub mod file;
 
@@ -17,6 +21,9 @@ pub struct WorkspaceManifest {
     pub id: String,
     pub runbooks: Vec<RunbookMetadata>,
     pub environments: IndexMap<String, IndexMap<String, String>>,
+    /// Backends storing the runbooks states, indexed by environment
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub state_backends: IndexMap<String, StateBackendConfig>,
     #[serde(skip_serializing, skip_deserializing)]
     pub location: Option<FileLocation>,
 }
@@ -29,6 +36,7 @@ impl WorkspaceManifest {
             id,
             runbooks: vec![],
             environments: IndexMap::new(),
+            state_backends: IndexMap::new(),
             location: None,
         }
     }
@@ -77,6 +85,14 @@ impl WorkspaceManifest {
         manifest_file: WorkspaceManifestFile,
         manifest_location: &FileLocation,
     ) -> Result<WorkspaceManifest, String> {
+        for environment in manifest_file.state_backends.keys() {
+            if manifest_file.environments.get(environment).is_none() {
+                return Err(format!(
+                    "state backend configured for environment '{}', unknown from manifest",
+                    environment
+                ));
+            }
+        }
         let manifest = WorkspaceManifest {
             name: manifest_file.name,
             id: manifest_file.id,
@@ -99,13 +115,21 @@ impl WorkspaceManifest {
                                 location
                                     .append_path(&f)
                                     .expect("unable to create state destination path");
-                                RunbookStateLocation::Location(location)
+                                if manifest_file.state_backends.is_empty() {
+                                    RunbookStateLocation::Location(location)
+                                } else {
+                                    RunbookStateLocation::Backends(
+                                        location,
+                                        manifest_file.state_backends.clone(),
+                                    )
+                                }
                             })
                         })
                         .unwrap_or(None),
                 })
                 .collect::<Vec<_>>(),
             environments: manifest_file.environments.clone(),
+            state_backends: manifest_file.state_backends.clone(),
             location: Some(manifest_location.clone()),
         };
         Ok(manifest)
@@ -141,77 +165,345 @@ fn normalize_user_input(input: &str) -> String {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum RunbookStateLocation {
     Location(FileLocation),
+    /// Local location, along with the backends storing the states of specific environments
+    Backends(FileLocation, IndexMap<String, StateBackendConfig>),
 }
 
 impl RunbookStateLocation {
+    pub fn get_state_file_name(runbook_id: &str, environment: Option<&str>) -> String {
+        format!(
+            "{}.{}.tx-state.json",
+            runbook_id,
+            environment.unwrap_or(&DEFAULT_TOP_LEVEL_INPUTS_NAME.to_ascii_lowercase())
+        )
+    }
+
+    /// Returns the location of the state file on the local filesystem.
     pub fn get_location_for_ctx(
         &self,
         runbook_id: &str,
         environment: Option<&str>,
     ) -> FileLocation {
+        let (RunbookStateLocation::Location(location)
+        | RunbookStateLocation::Backends(location, _)) = &self;
+        let mut location = location.clone();
+        location
+            .append_path(&Self::get_state_file_name(runbook_id, environment))
+            .expect("unable to create state destination path");
+        location
+    }
+
+    /// Returns the backend storing the state of the runbook for the given environment.
+    /// Environments without a configured backend store their state on the local filesystem.
+    pub fn get_backend(&self, environment_selector: &str) -> Result<Box<dyn StateBackend>, String> {
         match &self {
             RunbookStateLocation::Location(location) => {
-                let mut location = location.clone();
-                location
-                    .append_path(&format!(
-                        "{}.{}.tx-state.json",
-                        runbook_id,
-                        environment.unwrap_or(&DEFAULT_TOP_LEVEL_INPUTS_NAME.to_ascii_lowercase())
-                    ))
-                    .expect("unable to create state destination path");
-                location
+                Ok(Box::new(LocalStateBackend::new(location)))
+            }
+            RunbookStateLocation::Backends(location, backends) => {
+                match backends.get(environment_selector) {
+                    Some(backend) => backend.build(location),
+                    None => Ok(Box::new(LocalStateBackend::new(location))),
+                }
             }
         }
     }
 
     pub fn load_execution_snapshot(
         &self,
-        load_lock_file_if_exists: bool,
+        load_transient_state_if_exists: bool,
         runbook_id: &str,
         environment_selector: &str,
     ) -> Result<RunbookExecutionSnapshot, String> {
-        let state_file_location = self.get_location_for_ctx(runbook_id, Some(environment_selector));
-        let file_to_load = if load_lock_file_if_exists {
-            match RunbookTransientStateLocation::from_state_file_location(&state_file_location) {
-                Some(RunbookTransientStateLocation(lockfile_location)) => lockfile_location,
-                None => state_file_location.clone(),
-            }
-        } else {
-            state_file_location.clone()
+        let backend = self.get_backend(environment_selector)?;
+        let state_file_key = Self::get_state_file_name(runbook_id, Some(environment_selector));
+        let transient_state_file_key =
+            RunbookTransientStateLocation::get_key_from_state_file_key(&state_file_key);
+
+        let transient_snapshot_bytes = match load_transient_state_if_exists {
+            true => backend.read(&transient_state_file_key)?,
+            false => None,
+        };
+        let (key_loaded, snapshot_bytes) = match transient_snapshot_bytes {
+            Some(bytes) => (transient_state_file_key, bytes),
+            None => match backend.read(&state_file_key)? {
+                Some(bytes) => (state_file_key, bytes),
+                None => {
+                    return Err(format!(
+                        "unable to read {}: file not found",
+                        backend.describe(&state_file_key)
+                    ))
+                }
+            },
         };
 
-        let snapshot_bytes = file_to_load.read_content()?;
         if snapshot_bytes.is_empty() {
-            return Err(format!("unable to read {}: file empty", file_to_load));
+            return Err(format!("unable to read {}: file empty", backend.describe(&key_loaded)));
         }
-        let snapshot: RunbookExecutionSnapshot = serde_json::from_slice(&snapshot_bytes)
-            .map_err(|e| format!("unable to read {}: {}", file_to_load, e.to_string()))?;
+        let snapshot: RunbookExecutionSnapshot =
+            serde_json::from_slice(&snapshot_bytes).map_err(|e| {
+                format!("unable to read {}: {}", backend.describe(&key_loaded), e.to_string())
+            })?;
         Ok(snapshot)
     }
+
+    /// Writes the state of the runbook, and returns the description of its location.
+    pub fn write_execution_snapshot(
+        &self,
+        snapshot: &RunbookExecutionSnapshot,
+        runbook_id: &str,
+        environment_selector: &str,
+    ) -> Result<String, String> {
+        let state_file_key = Self::get_state_file_name(runbook_id, Some(environment_selector));
+        self.write_snapshot(snapshot, &state_file_key, environment_selector)
+    }
+
+    /// Removes the state of the runbook, and returns the description of its location.
+    pub fn remove_execution_snapshot(
+        &self,
+        runbook_id: &str,
+        environment_selector: &str,
+    ) -> Result<String, String> {
+        let backend = self.get_backend(environment_selector)?;
+        let state_file_key = Self::get_state_file_name(runbook_id, Some(environment_selector));
+        backend.delete(&state_file_key)?;
+        Ok(backend.describe(&state_file_key))
+    }
+
+    /// Writes the transient state of a runbook whose execution did not complete, and returns
+    /// the description of its location.
+    pub fn write_transient_execution_snapshot(
+        &self,
+        snapshot: &RunbookExecutionSnapshot,
+        runbook_id: &str,
+        environment_selector: &str,
+    ) -> Result<String, String> {
+        let state_file_key = Self::get_state_file_name(runbook_id, Some(environment_selector));
+        let transient_state_file_key =
+            RunbookTransientStateLocation::get_key_from_state_file_key(&state_file_key);
+        self.write_snapshot(snapshot, &transient_state_file_key, environment_selector)
+    }
+
+    pub fn has_transient_state(
+        &self,
+        runbook_id: &str,
+        environment_selector: &str,
+    ) -> Result<bool, String> {
+        let state_file_key = Self::get_state_file_name(runbook_id, Some(environment_selector));
+        self.get_backend(environment_selector)?
+            .exists(&RunbookTransientStateLocation::get_key_from_state_file_key(&state_file_key))
+    }
+
+    pub fn remove_transient_state(
+        &self,
+        runbook_id: &str,
+        environment_selector: &str,
+    ) -> Result<(), String> {
+        let state_file_key = Self::get_state_file_name(runbook_id, Some(environment_selector));
+        self.get_backend(environment_selector)?
+            .delete(&RunbookTransientStateLocation::get_key_from_state_file_key(&state_file_key))
+    }
+
+    /// Acquires the advisory lock of the state file, waiting up to `timeout` for the current
+    /// owner to release it. The lock should be released with [RunbookStateLock::release], the
+    /// returned guard releasing it on a best-effort basis when dropped.
+    pub async fn acquire_lock(
+        &self,
+        runbook_id: &str,
+        environment_selector: &str,
+        timeout: Duration,
+    ) -> Result<RunbookStateLock, String> {
+        let state_file_key = Self::get_state_file_name(runbook_id, Some(environment_selector));
+        RunbookStateLock::acquire(self.get_backend(environment_selector)?, &state_file_key, timeout)
+            .await
+    }
+
+    /// Removes the advisory lock of the state file, regardless of its owner.
+    /// Returns the owner of the removed lock, if any.
+    pub fn force_unlock(
+        &self,
+        runbook_id: &str,
+        environment_selector: &str,
+    ) -> Result<Option<RunbookStateLockOwner>, String> {
+        let state_file_key = Self::get_state_file_name(runbook_id, Some(environment_selector));
+        RunbookStateLock::force_unlock(self.get_backend(environment_selector)?, &state_file_key)
+    }
+
+    fn write_snapshot(
+        &self,
+        snapshot: &RunbookExecutionSnapshot,
+        key: &str,
+        environment_selector: &str,
+    ) -> Result<String, String> {
+        let backend = self.get_backend(environment_selector)?;
+        let content = serde_json::to_string_pretty(snapshot)
+            .map_err(|e| format!("unable to serialize state: {}", e))?;
+        backend.write(key, content.as_bytes())?;
+        Ok(backend.describe(key))
+    }
 }
 
+/// State of a runbook whose execution did not complete, stored next to its state file.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RunbookTransientStateLocation(pub FileLocation);
 
 impl RunbookTransientStateLocation {
-    pub fn get_location_from_state_file_location(
-        state_file_location: &FileLocation,
-    ) -> FileLocation {
-        let lock_file_name = format!("{}.lock", state_file_location.get_file_name().unwrap());
-        let mut lock_file_location = state_file_location.get_parent_location().unwrap();
-        lock_file_location.append_path(&lock_file_name).unwrap();
-        lock_file_location
-    }
-
-    pub fn from_state_file_location(state_file_location: &FileLocation) -> Option<Self> {
-        let lock_file_location =
-            RunbookTransientStateLocation::get_location_from_state_file_location(
-                state_file_location,
-            );
-        if lock_file_location.exists() {
-            Some(Self(lock_file_location))
-        } else {
-            None
+    pub fn get_key_from_state_file_key(state_file_key: &str) -> String {
+        format!("{}.lock", state_file_key)
+    }
+}
+
+const STATE_LOCK_POLLING_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunbookStateLockOwner {
+    /// User running the execution
+    pub owner: String,
+    /// Process running the execution
+    pub pid: u32,
+    /// Host running the execution
+    pub host: String,
+    /// Keep track of the lock acquisition date
+    pub acquired_at: String,
+}
+
+impl RunbookStateLockOwner {
+    pub fn current() -> Self {
+        let owner = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or("unknown".to_string());
+        let host = std::env::var("HOSTNAME")
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .or_else(|_| std::fs::read_to_string("/etc/hostname").map(|h| h.trim().to_string()))
+            .unwrap_or("unknown".to_string());
+        Self { owner, pid: std::process::id(), host, acquired_at: now_as_string() }
+    }
+}
+
+impl Display for RunbookStateLockOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{} (pid {}) since {}", self.owner, self.host, self.pid, self.acquired_at)
+    }
+}
+
+/// Advisory lock preventing concurrent executions of a runbook from writing the same state file.
+/// The lock is an entry stored next to the state file by its backend, describing its owner.
+#[derive(Debug)]
+pub struct RunbookStateLock {
+    backend: Box<dyn StateBackend>,
+    key: String,
+    pub owner: RunbookStateLockOwner,
+    released: bool,
+}
+
+impl RunbookStateLock {
+    pub fn get_key_from_state_file_key(state_file_key: &str) -> String {
+        format!("{}.tx-lock", state_file_key)
+    }
+
+    /// Acquires the lock if it is not held, without waiting for its current owner to release it.
+    pub fn try_acquire(
+        backend: Box<dyn StateBackend>,
+        state_file_key: &str,
+    ) -> Result<Option<Self>, String> {
+        let key = Self::get_key_from_state_file_key(state_file_key);
+        let owner = RunbookStateLockOwner::current();
+        if !Self::write_owner(backend.as_ref(), &key, &owner)? {
+            return Ok(None);
+        }
+        Ok(Some(Self { backend, key, owner, released: false }))
+    }
+
+    /// Acquires the lock, waiting up to `timeout` for its current owner to release it.
+    pub async fn acquire(
+        backend: Box<dyn StateBackend>,
+        state_file_key: &str,
+        timeout: Duration,
+    ) -> Result<Self, String> {
+        let key = Self::get_key_from_state_file_key(state_file_key);
+        let started_at = Instant::now();
+        loop {
+            let owner = RunbookStateLockOwner::current();
+            if Self::write_owner(backend.as_ref(), &key, &owner)? {
+                return Ok(Self { backend, key, owner, released: false });
+            }
+            let elapsed = started_at.elapsed();
+            if elapsed >= timeout {
+                let current_owner = match Self::read_owner(backend.as_ref(), state_file_key) {
+                    Ok(Some(current_owner)) => current_owner.to_string(),
+                    _ => "an unknown owner".to_string(),
+                };
+                return Err(format!(
+                    "state file {} is locked by {}. If no other execution is in progress, the lock can be removed with `txtx state unlock --force`",
+                    backend.describe(state_file_key),
+                    current_owner
+                ));
+            }
+            tokio::time::sleep(STATE_LOCK_POLLING_INTERVAL.min(timeout - elapsed)).await;
+        }
+    }
+
+    /// Releases the lock. The lock is left untouched if it is now owned by another execution,
+    /// after having been forcefully removed.
+    pub fn release(mut self) -> Result<(), String> {
+        self.released = true;
+        self.remove_if_owned()
+    }
+
+    fn write_owner(
+        backend: &dyn StateBackend,
+        key: &str,
+        owner: &RunbookStateLockOwner,
+    ) -> Result<bool, String> {
+        let content = serde_json::to_vec_pretty(owner)
+            .map_err(|e| format!("unable to serialize lock: {}", e))?;
+        backend.write_if_absent(key, &content)
+    }
+
+    fn remove_if_owned(&self) -> Result<(), String> {
+        let Some(bytes) = self.backend.read(&self.key)? else {
+            return Ok(());
+        };
+        let owner = serde_json::from_slice::<RunbookStateLockOwner>(&bytes)
+            .map_err(|e| format!("unable to read {}: {}", self.backend.describe(&self.key), e))?;
+        if owner != self.owner {
+            return Ok(());
+        }
+        self.backend.delete(&self.key)
+    }
+
+    pub fn read_owner(
+        backend: &dyn StateBackend,
+        state_file_key: &str,
+    ) -> Result<Option<RunbookStateLockOwner>, String> {
+        let key = Self::get_key_from_state_file_key(state_file_key);
+        let Some(bytes) = backend.read(&key)? else {
+            return Ok(None);
+        };
+        let owner = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("unable to read {}: {}", backend.describe(&key), e))?;
+        Ok(Some(owner))
+    }
+
+    pub fn force_unlock(
+        backend: Box<dyn StateBackend>,
+        state_file_key: &str,
+    ) -> Result<Option<RunbookStateLockOwner>, String> {
+        let key = Self::get_key_from_state_file_key(state_file_key);
+        if !backend.exists(&key)? {
+            return Ok(None);
+        }
+        let owner = Self::read_owner(backend.as_ref(), state_file_key).unwrap_or(None);
+        backend.delete(&key)?;
+        Ok(owner)
+    }
+}
+
+impl Drop for RunbookStateLock {
+    fn drop(&mut self) {
+        // Best-effort fallback for the guards that were not explicitly released
+        if !self.released {
+            let _ = self.remove_if_owned();
         }
     }
 }
@@ -234,3 +526,133 @@ impl RunbookMetadata {
         RunbookMetadata { location, description, name: name.to_string(), state: None }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_location(test_name: &str) -> (RunbookStateLocation, PathBuf) {
+        let state_dir = std::env::temp_dir().join(format!(
+            "txtx-state-lock-{}-{}",
+            test_name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&state_dir);
+        let location = RunbookStateLocation::Location(FileLocation::from_path(state_dir.clone()));
+        (location, state_dir)
+    }
+
+    fn lock_path(state_dir: &PathBuf) -> PathBuf {
+        let state_file_key = RunbookStateLocation::get_state_file_name("deploy", Some("mainnet"));
+        state_dir.join(RunbookStateLock::get_key_from_state_file_key(&state_file_key))
+    }
+
+    #[tokio::test]
+    async fn it_rejects_a_second_acquisition() {
+        let (location, state_dir) = state_location("second");
+        let lock = location.acquire_lock("deploy", "mainnet", Duration::ZERO).await.unwrap();
+
+        let state_file_key = RunbookStateLocation::get_state_file_name("deploy", Some("mainnet"));
+        let backend = location.get_backend("mainnet").unwrap();
+        assert!(RunbookStateLock::try_acquire(backend, &state_file_key).unwrap().is_none());
+        let err = location
+            .acquire_lock("deploy", "mainnet", Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert!(err.contains("txtx state unlock --force"));
+
+        // Other environments are locked independently
+        let other = location.acquire_lock("deploy", "testnet", Duration::ZERO).await.unwrap();
+        other.release().unwrap();
+
+        lock.release().unwrap();
+        let _ = std::fs::remove_dir_all(&state_dir);
+    }
+
+    #[tokio::test]
+    async fn it_releases_the_lock() {
+        let (location, state_dir) = state_location("release");
+        let lock = location.acquire_lock("deploy", "mainnet", Duration::ZERO).await.unwrap();
+        assert!(lock_path(&state_dir).exists());
+        lock.release().unwrap();
+        assert!(!lock_path(&state_dir).exists());
+
+        // Guards that are not explicitly released are released when dropped
+        let lock = location.acquire_lock("deploy", "mainnet", Duration::ZERO).await.unwrap();
+        drop(lock);
+        assert!(!lock_path(&state_dir).exists());
+        let _ = std::fs::remove_dir_all(&state_dir);
+    }
+
+    #[tokio::test]
+    async fn it_breaks_a_stale_lock() {
+        let (location, state_dir) = state_location("stale");
+        // A crashed execution never releases its lock
+        let stale_lock = location.acquire_lock("deploy", "mainnet", Duration::ZERO).await.unwrap();
+        let stale_owner = stale_lock.owner.clone();
+        std::mem::forget(stale_lock);
+        assert!(location.acquire_lock("deploy", "mainnet", Duration::ZERO).await.is_err());
+
+        let owner = location.force_unlock("deploy", "mainnet").unwrap();
+        assert_eq!(owner, Some(stale_owner));
+        assert!(!lock_path(&state_dir).exists());
+        assert_eq!(location.force_unlock("deploy", "mainnet").unwrap(), None);
+
+        let lock = location.acquire_lock("deploy", "mainnet", Duration::ZERO).await.unwrap();
+        lock.release().unwrap();
+        let _ = std::fs::remove_dir_all(&state_dir);
+    }
+
+    #[tokio::test]
+    async fn it_keeps_locks_acquired_by_other_executions() {
+        let (location, state_dir) = state_location("other");
+        let lock = location.acquire_lock("deploy", "mainnet", Duration::ZERO).await.unwrap();
+        location.force_unlock("deploy", "mainnet").unwrap();
+        let other = location.acquire_lock("deploy", "mainnet", Duration::ZERO).await.unwrap();
+
+        lock.release().unwrap();
+        assert!(lock_path(&state_dir).exists());
+        other.release().unwrap();
+        assert!(!lock_path(&state_dir).exists());
+        let _ = std::fs::remove_dir_all(&state_dir);
+    }
+
+    #[test]
+    fn it_reads_manifests_without_state_backends() {
+        let manifest: WorkspaceManifest = serde_json::from_str(
+            r#"{
+                "name": "test",
+                "id": "test",
+                "runbooks": [],
+                "environments": { "mainnet": { "rpc_url": "http://localhost:8545" } }
+            }"#,
+        )
+        .unwrap();
+        assert!(manifest.state_backends.is_empty());
+
+        let serialized = serde_json::to_string(&manifest).unwrap();
+        assert!(!serialized.contains("state_backends"));
+        let manifest: WorkspaceManifest = serde_json::from_str(&serialized).unwrap();
+        assert!(manifest.state_backends.is_empty());
+        assert!(manifest.environments.get("mainnet").is_some());
+
+        let manifest_file: WorkspaceManifestFile = serde_yml::from_str(
+            "name: test\nid: test\nrunbooks: []\nenvironments:\n  mainnet:\n    rpc_url: http://localhost:8545\n",
+        )
+        .unwrap();
+        assert!(manifest_file.state_backends.is_empty());
+        let serialized = serde_yml::to_string(&manifest_file).unwrap();
+        assert!(!serialized.contains("state_backends"));
+    }
+
+    #[test]
+    fn it_round_trips_state_backends() {
+        let mut manifest = WorkspaceManifest::new("test".to_string());
+        manifest.environments.insert("mainnet".to_string(), IndexMap::new());
+        manifest.state_backends.insert("mainnet".to_string(), StateBackendConfig::Local);
+
+        let serialized = serde_json::to_string(&manifest).unwrap();
+        let manifest: WorkspaceManifest = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(manifest.state_backends.get("mainnet"), Some(&StateBackendConfig::Local));
+    }
+}
@@ -32,6 +32,17 @@ pub struct RunbookExecutionSnapshot {
     pub flows: IndexMap<String, RunbookFlowSnapshot>,
     /// Snapshot of the inputs provided by the manifest and CLI
     top_level_inputs_fingerprints: IndexMap<String, Did>,
+    /// Audit trail of the non-critical changes recorded without executing the runbook
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reconciliations: Vec<StateReconciliation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateReconciliation {
+    /// Keep track of the reconciliation date
+    pub reconciled_at: String,
+    /// Non-critical changes recorded in the state
+    pub changes: Vec<Change>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,7 +74,109 @@ impl RunbookExecutionSnapshot {
                 .into_iter()
                 .map(|(k, v)| (k, v.compute_fingerprint()))
                 .collect(),
+            reconciliations: vec![],
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ended_at(&self) -> &str {
+        &self.ended_at
+    }
+
+    /// Records the non-critical changes between this snapshot and the snapshot of the current
+    /// state of the runbook, without requiring the runbook to be executed again. The changes
+    /// are appended to the reconciliations audit trail.
+    /// The snapshots of the reconciled constructs are updated with their current values, so that
+    /// the recorded changes are not reported again by the next diff.
+    /// Returns `None` when there is nothing to reconcile, and an error when some of the changes
+    /// can only be applied by executing the runbook.
+    pub fn reconcile(
+        &mut self,
+        new: &RunbookExecutionSnapshot,
+        consolidated_changes: &ConsolidatedChanges,
+    ) -> Result<Option<StateReconciliation>, String> {
+        if !consolidated_changes.new_plans_to_add.is_empty() {
+            return Err(format!(
+                "new chains to synchronize ({}), the runbook must be executed",
+                consolidated_changes.new_plans_to_add.join(", ")
+            ));
+        }
+        for (change, _) in consolidated_changes.get_synthesized_changes().iter() {
+            match change {
+                SynthesizedChange::Edition(_, true) => {
+                    return Err(format!("critical changes detected, the runbook must be executed"));
+                }
+                SynthesizedChange::FormerFailure(_, command_name) => {
+                    return Err(format!(
+                        "action '{}' was not executed, the runbook must be executed",
+                        command_name
+                    ));
+                }
+                SynthesizedChange::Addition(_) => {
+                    return Err(format!("new actions detected, the runbook must be executed"));
+                }
+                SynthesizedChange::Edition(_, false) => {}
+            }
         }
+
+        let changes = consolidated_changes
+            .plans_to_update
+            .values()
+            .flat_map(|plan_changes| plan_changes.constructs_to_update.iter())
+            .filter(|change| !change.description.is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return Ok(None);
+        }
+
+        let reconciled_constructs = changes
+            .iter()
+            .filter_map(|change| change.construct_did.clone())
+            .collect::<HashSet<_>>();
+        for (flow_id, flow_snapshot) in self.flows.iter_mut() {
+            let Some(new_flow_snapshot) = new.flows.get(flow_id) else {
+                continue;
+            };
+            for (signer_did, signer) in flow_snapshot.signers.iter_mut() {
+                let Some(new_signer) = new_flow_snapshot.signers.get(signer_did) else {
+                    continue;
+                };
+                if reconciled_constructs.contains(signer_did) {
+                    signer.construct_name = new_signer.construct_name.clone();
+                    signer.construct_location = new_signer.construct_location.clone();
+                }
+                for (key, value) in new_signer.outputs.iter() {
+                    signer.outputs.insert(key.clone(), value.clone());
+                }
+            }
+            for (construct_did, command) in flow_snapshot.commands.iter_mut() {
+                if !command.executed {
+                    continue;
+                }
+                let Some(new_command) = new_flow_snapshot.commands.get(construct_did) else {
+                    continue;
+                };
+                if reconciled_constructs.contains(construct_did) {
+                    command.construct_name = new_command.construct_name.clone();
+                    command.construct_location = new_command.construct_location.clone();
+                    command.construct_addon = new_command.construct_addon.clone();
+                    // Critical changes were rejected above: the critical values of both snapshots
+                    // are identical, and only the non-critical values are updated
+                    command.inputs = new_command.inputs.clone();
+                }
+                for (key, output) in new_command.outputs.iter() {
+                    command.outputs.insert(key.clone(), output.clone());
+                }
+            }
+        }
+
+        let reconciliation = StateReconciliation { reconciled_at: now_as_string(), changes };
+        self.reconciliations.push(reconciliation.clone());
+        Ok(Some(reconciliation))
     }
 }
 
@@ -88,14 +201,14 @@ pub struct SigningCommandSnapshot {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandSnapshot {
     package_did: PackageDid,
-    construct_type: String,
-    construct_name: String,
+    pub construct_type: String,
+    pub construct_name: String,
     construct_location: FileLocation,
-    construct_addon: Option<String>,
+    pub construct_addon: Option<String>,
     upstream_constructs_dids: Vec<ConstructDid>,
     pub inputs: IndexMap<String, CommandInputSnapshot>,
     pub outputs: IndexMap<String, CommandOutputSnapshot>,
-    executed: bool,
+    pub executed: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -160,6 +273,10 @@ impl RunbookSnapshotContext {
         // workspace_context: &RunbookWorkspaceContext,
 
         let mut snapshot = RunbookExecutionSnapshot::new(&runbook_id, top_level_inputs_map);
+        // Executions do not erase the audit trail of the former reconciliations
+        if let Some(previous_snapshot) = previous_snapshot.as_ref() {
+            snapshot.reconciliations = previous_snapshot.reconciliations.clone();
+        }
 
         let mut flow_contexts = flow_contexts.clone();
         flow_contexts.sort_by(|a, b| a.name.cmp(&b.name));
@@ -1155,7 +1272,84 @@ where
     result
 }
 
-fn now_as_string() -> String {
+#[cfg(test)]
+impl RunbookExecutionSnapshot {
+    /// Builds the snapshot of a runbook executing `commands` in a single `default` flow, all of
+    /// them being signed by the same signer.
+    pub(crate) fn for_testing(
+        name: &str,
+        ended_at: &str,
+        commands: Vec<(ConstructDid, CommandSnapshot)>,
+    ) -> Self {
+        let runbook_id = RunbookId { org: None, workspace: None, name: name.to_string() };
+        let mut snapshot =
+            RunbookExecutionSnapshot::new(&runbook_id, &RunbookTopLevelInputsMap::new());
+        snapshot.ended_at = ended_at.to_string();
+        let signer = SigningCommandSnapshot {
+            package_did: PackageDid(Did::zero()),
+            construct_type: "signer".to_string(),
+            construct_name: "deployer".to_string(),
+            construct_addon: None,
+            construct_location: FileLocation::working_dir(),
+            downstream_constructs_dids: commands.iter().map(|(did, _)| did.clone()).collect(),
+            inputs_fingerprint: Did::zero(),
+            outputs: IndexMap::new(),
+        };
+        snapshot.flows.insert(
+            "default".to_string(),
+            RunbookFlowSnapshot {
+                flow_inputs_fingerprints: IndexMap::new(),
+                addon_defaults_fingerprints: IndexMap::new(),
+                packages: IndexMap::new(),
+                signers: IndexMap::from([(
+                    ConstructDid(Did::from_components(vec!["deployer".as_bytes()])),
+                    signer,
+                )]),
+                commands: commands.into_iter().collect(),
+            },
+        );
+        snapshot
+    }
+}
+
+#[cfg(test)]
+impl CommandSnapshot {
+    /// Builds the snapshot of a `std::variable` command, with inputs given as
+    /// `(name, value, critical)`.
+    pub(crate) fn for_testing(
+        construct_name: &str,
+        inputs: Vec<(&str, Value, bool)>,
+        outputs: Vec<(&str, Value)>,
+        executed: bool,
+    ) -> Self {
+        CommandSnapshot {
+            package_did: PackageDid(Did::zero()),
+            construct_type: "variable".to_string(),
+            construct_name: construct_name.to_string(),
+            construct_location: FileLocation::working_dir(),
+            construct_addon: None,
+            upstream_constructs_dids: vec![],
+            inputs: inputs
+                .into_iter()
+                .map(|(k, v, critical)| {
+                    let input = CommandInputSnapshot {
+                        value_pre_evaluation: None,
+                        value_post_evaluation: ValuePostEvaluation::Value(v),
+                        critical,
+                    };
+                    (k.to_string(), input)
+                })
+                .collect(),
+            outputs: outputs
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), CommandOutputSnapshot { value: v, signed: false }))
+                .collect(),
+            executed,
+        }
+    }
+}
+
+pub(crate) fn now_as_string() -> String {
     // Get the current system time
     let now = SystemTime::now();
     // Calculate the duration since the Unix epoch
@@ -1169,3 +1363,76 @@ fn now_as_string() -> String {
 }
 // Shortcut:
 // Support for constructs being removed / added / replaced
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn construct_did(name: &str) -> ConstructDid {
+        ConstructDid(Did::from_components(vec![name.as_bytes()]))
+    }
+
+    fn execution(url: &str, critical: bool) -> RunbookExecutionSnapshot {
+        let command = CommandSnapshot::for_testing(
+            "fetch",
+            vec![("url", Value::string(url.to_string()), critical)],
+            vec![("response", Value::string("ok".to_string()))],
+            true,
+        );
+        RunbookExecutionSnapshot::for_testing(
+            "deploy",
+            &now_as_string(),
+            vec![(construct_did("fetch"), command)],
+        )
+    }
+
+    fn recorded_changes(changes: &ConsolidatedChanges) -> usize {
+        changes
+            .plans_to_update
+            .values()
+            .flat_map(|plan_changes| plan_changes.constructs_to_update.iter())
+            .filter(|change| !change.description.is_empty())
+            .count()
+    }
+
+    #[test]
+    fn it_reconciles_non_critical_changes() {
+        let ctx = RunbookSnapshotContext::new();
+        let mut previous = execution("https://a", false);
+        let new = execution("https://b", false);
+
+        let changes = ctx.diff(previous.clone(), new.clone()).unwrap();
+        assert!(recorded_changes(&changes) > 0);
+        let reconciliation = previous.reconcile(&new, &changes).unwrap().unwrap();
+        assert!(!reconciliation.changes.is_empty());
+        assert_eq!(previous.reconciliations.len(), 1);
+
+        // The reconciled state is in sync with the runbook
+        let changes = ctx.diff(previous.clone(), new.clone()).unwrap();
+        assert_eq!(recorded_changes(&changes), 0);
+        assert!(previous.reconcile(&new, &changes).unwrap().is_none());
+        assert_eq!(previous.reconciliations.len(), 1);
+    }
+
+    #[test]
+    fn it_rejects_critical_changes() {
+        let ctx = RunbookSnapshotContext::new();
+        let mut previous = execution("https://a", true);
+        let new = execution("https://b", true);
+
+        let changes = ctx.diff(previous.clone(), new.clone()).unwrap();
+        assert!(previous.reconcile(&new, &changes).is_err());
+        assert!(previous.reconciliations.is_empty());
+    }
+
+    #[test]
+    fn it_ignores_snapshots_without_changes() {
+        let ctx = RunbookSnapshotContext::new();
+        let mut previous = execution("https://a", false);
+        let new = execution("https://a", false);
+
+        let changes = ctx.diff(previous.clone(), new.clone()).unwrap();
+        assert!(previous.reconcile(&new, &changes).unwrap().is_none());
+        assert!(previous.reconciliations.is_empty());
+    }
+}
@@ -277,7 +277,29 @@ impl RunbookGraphContext {
             if let Err(_e) =
                 self.constructs_dag.add_edge(dst_node_index.clone(), src_node_index.clone(), 1)
             {
-                diags.push(diagnosed_error!("Cycling dependency"));
+                let (Some(src_id), Some(dst_id)) =
+                    (workspace_context.constructs.get(src), workspace_context.constructs.get(dst))
+                else {
+                    diags.push(diagnosed_error!("Cycling dependency"));
+                    continue;
+                };
+                let span = execution_context
+                    .commands_instances
+                    .get(src)
+                    .map(|instance| instance.block.span())
+                    .or(execution_context.signers_instances.get(src).map(|s| s.block.span()))
+                    .flatten();
+                diags.push(
+                    diagnosed_error!(
+                        "Cycling dependency: {}.{} depends on {}.{}, which depends on it",
+                        src_id.construct_type,
+                        src_id.construct_name,
+                        dst_id.construct_type,
+                        dst_id.construct_name,
+                    )
+                    .location(&src_id.construct_location)
+                    .set_span_range(span),
+                );
             }
         }
 
@@ -515,7 +537,10 @@ mod tests {
         else {
             panic!("Missing expected error on circular dependency");
         };
-        assert_eq!(e.get(0).unwrap().message, format!("Cycling dependency"));
+        let diag = e.get(0).unwrap();
+        assert!(diag.message.starts_with("Cycling dependency"));
+        assert!(diag.message.contains("variable.a") && diag.message.contains("variable.b"));
+        assert!(diag.location.is_some());
     }
 
     #[test_case(include_str!("../tests/fixtures/ab_c.tx"), vec!["a", "b", "c"])]
@@ -23,17 +23,25 @@ pub mod embedded_runbook;
 mod execution_context;
 pub mod flow_context;
 mod graph_context;
+mod plan;
 mod runtime_context;
 mod workspace_context;
 
+pub(crate) use diffing_context::now_as_string;
 pub use diffing_context::ConsolidatedChanges;
-pub use diffing_context::{RunbookExecutionSnapshot, RunbookSnapshotContext, SynthesizedChange};
+pub use diffing_context::{
+    CommandSnapshot, RunbookExecutionSnapshot, RunbookSnapshotContext, StateReconciliation,
+    SynthesizedChange, ValuePostEvaluation,
+};
 pub use execution_context::{RunbookExecutionContext, RunbookExecutionMode};
 pub use graph_context::RunbookGraphContext;
+pub use plan::{
+    FlowExecutionPlan, PlannedChange, PlannedChangeKind, PlannedConstruct, RunbookExecutionPlan,
+};
 pub use runtime_context::{AddonConstructFactory, RuntimeContext};
 pub use workspace_context::RunbookWorkspaceContext;
 
-use crate::manifest::{RunbookStateLocation, RunbookTransientStateLocation};
+use crate::manifest::RunbookStateLocation;
 
 #[derive(Debug)]
 pub struct Runbook {
@@ -335,6 +343,17 @@ impl Runbook {
         execution_context_backups
     }
 
+    pub fn restore_execution_contexts(
+        &mut self,
+        mut execution_context_backups: HashMap<String, RunbookExecutionContext>,
+    ) {
+        for flow_context in self.flow_contexts.iter_mut() {
+            if let Some(execution_context) = execution_context_backups.remove(&flow_context.name) {
+                flow_context.execution_context = execution_context;
+            }
+        }
+    }
+
     pub async fn simulate_and_snapshot_flows(
         &mut self,
         old_snapshot: &RunbookExecutionSnapshot,
@@ -504,29 +523,59 @@ impl Runbook {
         (actions_to_re_execute, actions_to_execute)
     }
 
+    /// Records the non-critical changes made to the runbook since its latest execution (e.g. an
+    /// RPC URL update) in its state file, without executing anything.
+    /// Returns `None` if the state file was already in sync with the runbook.
+    pub async fn reconcile_state(
+        &mut self,
+        runbook_state_location: &RunbookStateLocation,
+    ) -> Result<Option<(String, StateReconciliation)>, String> {
+        let environment = self.top_level_inputs_map.current_top_level_input_name();
+        if runbook_state_location.has_transient_state(&self.runbook_id.name, &environment)? {
+            return Err(format!(
+                "the latest execution of the runbook did not complete, the runbook must be executed"
+            ));
+        }
+        let mut previous_snapshot = runbook_state_location.load_execution_snapshot(
+            false,
+            &self.runbook_id.name,
+            &environment,
+        )?;
+
+        // simulate the runbook the same way executions do, applying the previous state on top of
+        // the gaps of the simulation
+        self.enable_full_execution_mode();
+        let ctx = RunbookSnapshotContext::new();
+        let new = self.simulate_and_snapshot_flows(&previous_snapshot).await?;
+        let consolidated_changes = ctx.diff(previous_snapshot.clone(), new.clone())?;
+
+        let Some(reconciliation) = previous_snapshot.reconcile(&new, &consolidated_changes)? else {
+            return Ok(None);
+        };
+        let state_file_location = runbook_state_location.write_execution_snapshot(
+            &previous_snapshot,
+            &self.runbook_id.name,
+            &environment,
+        )?;
+        Ok(Some((state_file_location, reconciliation)))
+    }
+
     pub fn write_runbook_state(
         &self,
         runbook_state_location: Option<RunbookStateLocation>,
-    ) -> Result<Option<FileLocation>, String> {
+    ) -> Result<Option<String>, String> {
         if let Some(state_file_location) = runbook_state_location {
+            let environment = self.top_level_inputs_map.current_top_level_input_name();
             let previous_snapshot = match state_file_location.load_execution_snapshot(
                 true,
                 &self.runbook_id.name,
-                &self.top_level_inputs_map.current_top_level_input_name(),
+                &environment,
             ) {
                 Ok(snapshot) => Some(snapshot),
                 Err(_e) => None,
             };
 
-            let state_file_location = state_file_location.get_location_for_ctx(
-                &self.runbook_id.name,
-                Some(&self.top_level_inputs_map.current_top_level_input_name()),
-            );
-            if let Some(RunbookTransientStateLocation(lock_file)) =
-                RunbookTransientStateLocation::from_state_file_location(&state_file_location)
-            {
-                let _ = std::fs::remove_file(&lock_file.to_string());
-            }
+            let _ = state_file_location.remove_transient_state(&self.runbook_id.name, &environment);
 
             let diff = RunbookSnapshotContext::new();
             let snapshot = diff
@@ -537,9 +586,11 @@ impl Runbook {
                     &self.top_level_inputs_map,
                 )
                 .map_err(|e| e.message)?;
-            state_file_location
-                .write_content(serde_json::to_string_pretty(&snapshot).unwrap().as_bytes())
-                .expect("unable to save state");
+            let state_file_location = state_file_location.write_execution_snapshot(
+                &snapshot,
+                &self.runbook_id.name,
+                &environment,
+            )?;
             Ok(Some(state_file_location))
         } else {
             Ok(None)
@@ -549,27 +600,22 @@ impl Runbook {
     pub fn mark_failed_and_write_transient_state(
         &mut self,
         runbook_state_location: Option<RunbookStateLocation>,
-    ) -> Result<Option<FileLocation>, String> {
+    ) -> Result<Option<String>, String> {
         for running_context in self.flow_contexts.iter_mut() {
             running_context.execution_context.execution_mode = RunbookExecutionMode::FullFailed;
         }
 
         if let Some(runbook_state_location) = runbook_state_location {
+            let environment = self.top_level_inputs_map.current_top_level_input_name();
             let previous_snapshot = match runbook_state_location.load_execution_snapshot(
                 false,
                 &self.runbook_id.name,
-                &self.top_level_inputs_map.current_top_level_input_name(),
+                &environment,
             ) {
                 Ok(snapshot) => Some(snapshot),
                 Err(_e) => None,
             };
 
-            let lock_file = RunbookTransientStateLocation::get_location_from_state_file_location(
-                &runbook_state_location.get_location_for_ctx(
-                    &self.runbook_id.name,
-                    Some(&self.top_level_inputs_map.current_top_level_input_name()),
-                ),
-            );
             let diff = RunbookSnapshotContext::new();
             let snapshot = diff
                 .snapshot_runbook_execution(
@@ -579,10 +625,10 @@ impl Runbook {
                     &self.top_level_inputs_map,
                 )
                 .map_err(|e| e.message)?;
-            lock_file
-                .write_content(serde_json::to_string_pretty(&snapshot).unwrap().as_bytes())
+            let transient_state_file_location = runbook_state_location
+                .write_transient_execution_snapshot(&snapshot, &self.runbook_id.name, &environment)
                 .map_err(|e| format!("unable to save state ({})", e.to_string()))?;
-            Ok(Some(lock_file))
+            Ok(Some(transient_state_file_location))
         } else {
             Ok(None)
         }
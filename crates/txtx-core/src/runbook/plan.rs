@@ -0,0 +1,451 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use txtx_addon_kit::indexmap::IndexMap;
+use txtx_addon_kit::types::diagnostics::Diagnostic;
+use txtx_addon_kit::types::types::Value;
+use txtx_addon_kit::types::{ConstructDid, Did, WithEvaluatableInputs};
+
+use super::diffing_context::now_as_string;
+use super::{
+    ConsolidatedChanges, Runbook, RunbookExecutionMode, RunbookExecutionSnapshot,
+    RunbookSnapshotContext, SynthesizedChange,
+};
+
+/// A machine-readable description of what a runbook execution would do, computed
+/// without executing anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunbookExecutionPlan {
+    /// Name of the runbook
+    pub runbook: String,
+    /// Environment the plan was computed against
+    pub environment: String,
+    /// Schema version
+    pub version: u32,
+    /// Keep track of the plan creation date
+    pub created_at: String,
+    /// Constructs that would be executed, indexed by flow
+    pub flows: IndexMap<String, FlowExecutionPlan>,
+    /// Changes synthesized from the diff with the previous execution state
+    pub changes: Vec<PlannedChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowExecutionPlan {
+    /// Constructs that would be executed, in execution order
+    pub constructs: Vec<PlannedConstruct>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedConstruct {
+    pub construct_did: ConstructDid,
+    pub construct_type: String,
+    pub construct_name: String,
+    /// Matcher of the command implementing the construct (e.g. `evm::deploy_contract`)
+    pub command: String,
+    /// Evaluated inputs, sensitive inputs excluded
+    pub inputs: IndexMap<String, Value>,
+    /// Fingerprint of the evaluated tainting inputs
+    pub inputs_fingerprint: Did,
+    /// Signers that would be asked to sign
+    pub signers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedChangeKind {
+    Edition,
+    Addition,
+    FormerFailure,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub kind: PlannedChangeKind,
+    pub critical: bool,
+    pub description: Vec<String>,
+    /// Flows and constructs impacted by the change
+    pub impacted: Vec<(String, Option<ConstructDid>)>,
+}
+
+impl RunbookExecutionPlan {
+    pub fn new(runbook: &Runbook, consolidated_changes: Option<&ConsolidatedChanges>) -> Self {
+        let mut flows = IndexMap::new();
+        for flow_context in runbook.flow_contexts.iter() {
+            let execution_context = &flow_context.execution_context;
+            let constructs_to_consider = match &execution_context.execution_mode {
+                RunbookExecutionMode::Ignored => {
+                    flows.insert(
+                        flow_context.name.clone(),
+                        FlowExecutionPlan { constructs: vec![] },
+                    );
+                    continue;
+                }
+                RunbookExecutionMode::Partial(constructs) => {
+                    Some(constructs.iter().collect::<HashSet<_>>())
+                }
+                RunbookExecutionMode::Full | RunbookExecutionMode::FullFailed => None,
+            };
+
+            let mut constructs = vec![];
+            for construct_did in execution_context.order_for_commands_execution.iter() {
+                if let Some(ref constructs_to_consider) = constructs_to_consider {
+                    if !constructs_to_consider.contains(construct_did) {
+                        continue;
+                    }
+                }
+                let Some(command_instance) =
+                    execution_context.commands_instances.get(construct_did)
+                else {
+                    continue;
+                };
+                let Some(construct_id) =
+                    flow_context.workspace_context.constructs.get(construct_did)
+                else {
+                    continue;
+                };
+
+                let inputs_evaluations =
+                    execution_context.commands_inputs_evaluation_results.get(construct_did);
+                let mut inputs = IndexMap::new();
+                let mut tainting_inputs = vec![];
+                for input in command_instance.specification.inputs.iter() {
+                    let value = inputs_evaluations
+                        .and_then(|evaluations| evaluations.inputs.get_value(&input.name));
+                    if input.tainting {
+                        let expression = command_instance
+                            .get_expression_from_input(&input.name)
+                            .map(|expr| expr.to_string().trim().to_string());
+                        tainting_inputs.push((input.name.clone(), value.cloned(), expression));
+                    }
+                    if input.sensitive {
+                        continue;
+                    }
+                    if let Some(value) = value {
+                        inputs.insert(input.name.clone(), value.clone());
+                    }
+                }
+                let inputs_fingerprint =
+                    compute_inputs_fingerprint(construct_did, &tainting_inputs);
+
+                let signers = execution_context
+                    .signers_downstream_dependencies
+                    .iter()
+                    .filter(|(_, downstream)| downstream.contains(construct_did))
+                    .filter_map(|(signer_did, _)| {
+                        flow_context
+                            .workspace_context
+                            .constructs
+                            .get(signer_did)
+                            .map(|s| s.construct_name.clone())
+                    })
+                    .collect();
+
+                constructs.push(PlannedConstruct {
+                    construct_did: construct_did.clone(),
+                    construct_type: construct_id.construct_type.clone(),
+                    construct_name: construct_id.construct_name.clone(),
+                    command: format!(
+                        "{}::{}",
+                        command_instance.namespace, command_instance.specification.matcher
+                    ),
+                    inputs,
+                    inputs_fingerprint,
+                    signers,
+                });
+            }
+            flows.insert(flow_context.name.clone(), FlowExecutionPlan { constructs });
+        }
+
+        let changes = consolidated_changes
+            .map(|changes| {
+                changes
+                    .get_synthesized_changes()
+                    .into_iter()
+                    .map(|(change, impacted)| match change {
+                        SynthesizedChange::Edition(description, critical) => PlannedChange {
+                            kind: PlannedChangeKind::Edition,
+                            critical,
+                            description,
+                            impacted,
+                        },
+                        SynthesizedChange::Addition(_) => PlannedChange {
+                            kind: PlannedChangeKind::Addition,
+                            critical: false,
+                            description: vec![],
+                            impacted,
+                        },
+                        SynthesizedChange::FormerFailure(_, command_name) => PlannedChange {
+                            kind: PlannedChangeKind::FormerFailure,
+                            critical: false,
+                            description: vec![command_name],
+                            impacted,
+                        },
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            runbook: runbook.runbook_id.name.clone(),
+            environment: runbook.top_level_inputs_map.current_top_level_input_name(),
+            version: 1,
+            created_at: now_as_string(),
+            flows,
+            changes,
+        }
+    }
+
+    /// Iterates over the constructs that would be executed, across flows.
+    pub fn constructs(&self) -> impl Iterator<Item = (&String, &PlannedConstruct)> {
+        self.flows.iter().flat_map(|(flow, plan)| plan.constructs.iter().map(move |c| (flow, c)))
+    }
+
+    /// Compares this approved plan with the plan computed for the current state of the runbook.
+    /// Returns a diagnostic listing every construct that drifted: constructs whose inputs
+    /// fingerprint changed, and constructs that were added to or removed from the plan.
+    pub fn check_drift(&self, current: &RunbookExecutionPlan) -> Result<(), Diagnostic> {
+        if self.runbook != current.runbook || self.environment != current.environment {
+            return Err(diagnosed_error!(
+                "the plan was computed for runbook '{}' in environment '{}', not runbook '{}' in environment '{}'",
+                self.runbook,
+                self.environment,
+                current.runbook,
+                current.environment
+            ));
+        }
+
+        let approved = self
+            .constructs()
+            .map(|(flow, c)| ((flow, &c.construct_did), c))
+            .collect::<IndexMap<_, _>>();
+        let planned = current
+            .constructs()
+            .map(|(flow, c)| ((flow, &c.construct_did), c))
+            .collect::<IndexMap<_, _>>();
+
+        let mut drifts = vec![];
+        for ((flow, construct_did), construct) in approved.iter() {
+            match planned.get(&(*flow, *construct_did)) {
+                None => drifts.push(format!(
+                    "- {}.{} (flow '{}'): approved, but would no longer be executed",
+                    construct.construct_type, construct.construct_name, flow
+                )),
+                Some(current) if current.inputs_fingerprint != construct.inputs_fingerprint => {
+                    drifts.push(format!(
+                        "- {}.{} (flow '{}'): inputs drifted from the approved plan",
+                        construct.construct_type, construct.construct_name, flow
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        for ((flow, construct_did), construct) in planned.iter() {
+            if !approved.contains_key(&(*flow, *construct_did)) {
+                drifts.push(format!(
+                    "- {}.{} (flow '{}'): would be executed, but is not part of the approved plan",
+                    construct.construct_type, construct.construct_name, flow
+                ));
+            }
+        }
+
+        if drifts.is_empty() {
+            return Ok(());
+        }
+        Err(diagnosed_error!(
+            "the runbook drifted from the approved plan created at {}:\n{}",
+            self.created_at,
+            drifts.join("\n")
+        ))
+    }
+}
+
+/// Fingerprints the tainting inputs of a construct, given as `(name, evaluated value, source
+/// expression)`. Inputs that could not be evaluated during the simulation (e.g. inputs depending
+/// on the outputs of constructs not executed yet) are fingerprinted with their source expression,
+/// so that editing them is still detected as a drift.
+fn compute_inputs_fingerprint(
+    construct_did: &ConstructDid,
+    tainting_inputs: &Vec<(String, Option<Value>, Option<String>)>,
+) -> Did {
+    let mut comps = vec![construct_did.as_bytes().to_vec()];
+    for (name, value, expression) in tainting_inputs.iter() {
+        let fingerprint = match (value, expression) {
+            (Some(value), _) => value.compute_fingerprint(),
+            (None, Some(expression)) => {
+                Did::from_components(vec!["unresolved", expression.as_str()])
+            }
+            (None, None) => continue,
+        };
+        comps.push(name.as_bytes().to_vec());
+        comps.push(fingerprint.as_bytes().to_vec());
+    }
+    Did::from_components(comps)
+}
+
+impl Runbook {
+    /// Simulates the execution of the runbook, diffing it against the previous execution state
+    /// when provided, and returns the resulting execution plan. Nothing gets executed.
+    pub async fn compute_execution_plan(
+        &mut self,
+        previous_snapshot: Option<RunbookExecutionSnapshot>,
+    ) -> Result<RunbookExecutionPlan, String> {
+        self.enable_full_execution_mode();
+
+        let consolidated_changes = match previous_snapshot {
+            Some(old) => {
+                let ctx = RunbookSnapshotContext::new();
+                let execution_context_backups = self.backup_execution_contexts();
+                let new = self.simulate_and_snapshot_flows(&old).await?;
+                let consolidated_changes = ctx.diff(old, new)?;
+                self.prepare_flows_for_new_plans(
+                    &consolidated_changes.new_plans_to_add,
+                    execution_context_backups,
+                );
+                let _ =
+                    self.prepared_flows_for_updated_plans(&consolidated_changes.plans_to_update);
+                // flows that were restored from their backup need to be simulated again
+                self.simulate_flows(Some(&consolidated_changes.new_plans_to_add)).await;
+                Some(consolidated_changes)
+            }
+            None => {
+                self.simulate_flows(None).await;
+                None
+            }
+        };
+
+        Ok(RunbookExecutionPlan::new(self, consolidated_changes.as_ref()))
+    }
+
+    pub(crate) async fn simulate_flows(&mut self, flows_filter: Option<&Vec<String>>) {
+        for flow_context in self.flow_contexts.iter_mut() {
+            if let Some(flows_filter) = flows_filter {
+                if !flows_filter.contains(&flow_context.name) {
+                    continue;
+                }
+            }
+            let frontier = HashSet::new();
+            let _res = flow_context
+                .execution_context
+                .simulate_execution(
+                    &self.runtime_context,
+                    &flow_context.workspace_context,
+                    &self.supervision_context,
+                    &frontier,
+                )
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use txtx_test_utils::test_harness::build_runbook_from_fixture;
+
+    use super::*;
+    use crate::tests::get_addon_by_namespace;
+
+    fn construct(name: &str, fingerprint: Did) -> PlannedConstruct {
+        PlannedConstruct {
+            construct_did: ConstructDid(Did::from_components(vec![name])),
+            construct_type: "action".to_string(),
+            construct_name: name.to_string(),
+            command: "std::send_http_request".to_string(),
+            inputs: IndexMap::new(),
+            inputs_fingerprint: fingerprint,
+            signers: vec![],
+        }
+    }
+
+    fn fingerprint(name: &str, value: Option<&str>, expression: Option<&str>) -> Did {
+        compute_inputs_fingerprint(
+            &ConstructDid(Did::from_components(vec![name])),
+            &vec![(
+                "url".to_string(),
+                value.map(|v| Value::string(v.to_string())),
+                expression.map(|e| e.to_string()),
+            )],
+        )
+    }
+
+    fn plan(constructs: Vec<PlannedConstruct>) -> RunbookExecutionPlan {
+        RunbookExecutionPlan {
+            runbook: "deploy".to_string(),
+            environment: "testnet".to_string(),
+            version: 1,
+            created_at: now_as_string(),
+            flows: IndexMap::from_iter([("default".to_string(), FlowExecutionPlan { constructs })]),
+            changes: vec![],
+        }
+    }
+
+    #[test]
+    fn it_accepts_an_unchanged_plan() {
+        let approved = plan(vec![
+            construct("a", fingerprint("a", Some("https://a"), None)),
+            construct("b", fingerprint("b", None, Some("action.a.response"))),
+        ]);
+        let current = plan(vec![
+            construct("a", fingerprint("a", Some("https://a"), None)),
+            construct("b", fingerprint("b", None, Some("action.a.response"))),
+        ]);
+        assert!(approved.check_drift(&current).is_ok());
+    }
+
+    #[test]
+    fn it_detects_changed_inputs() {
+        let approved = plan(vec![construct("a", fingerprint("a", Some("https://a"), None))]);
+        let current = plan(vec![construct("a", fingerprint("a", Some("https://b"), None))]);
+        let diag = approved.check_drift(&current).unwrap_err();
+        assert!(diag.message.contains("action.a (flow 'default'): inputs drifted"));
+    }
+
+    #[test]
+    fn it_detects_changed_unresolved_inputs() {
+        let unresolved = fingerprint("b", None, Some("action.a.response"));
+        assert_ne!(unresolved, fingerprint("b", None, None));
+        let approved = plan(vec![construct("b", unresolved)]);
+        let current = plan(vec![construct("b", fingerprint("b", None, Some("action.c.response")))]);
+        let diag = approved.check_drift(&current).unwrap_err();
+        assert!(diag.message.contains("action.b (flow 'default'): inputs drifted"));
+    }
+
+    #[test]
+    fn it_detects_added_and_removed_constructs() {
+        let approved = plan(vec![construct("a", fingerprint("a", Some("https://a"), None))]);
+        let current = plan(vec![construct("b", fingerprint("b", Some("https://a"), None))]);
+        let diag = approved.check_drift(&current).unwrap_err();
+        assert!(diag.message.contains("action.a (flow 'default'): approved, but would no longer"));
+        assert!(diag.message.contains("action.b (flow 'default'): would be executed, but is not"));
+    }
+
+    #[test]
+    fn it_rejects_plans_of_other_environments() {
+        let approved = plan(vec![]);
+        let mut current = plan(vec![]);
+        current.environment = "mainnet".to_string();
+        assert!(approved.check_drift(&current).is_err());
+    }
+
+    #[tokio::test]
+    async fn it_rejects_runbooks_modified_after_the_plan_was_approved() {
+        let fixture = include_str!("../tests/fixtures/ab_c.tx");
+        let mut runbook =
+            build_runbook_from_fixture("ab_c.tx", fixture, get_addon_by_namespace).await.unwrap();
+        let approved = runbook.compute_execution_plan(None).await.unwrap();
+
+        let mut runbook =
+            build_runbook_from_fixture("ab_c.tx", fixture, get_addon_by_namespace).await.unwrap();
+        let current = runbook.compute_execution_plan(None).await.unwrap();
+        assert!(approved.check_drift(&current).is_ok());
+
+        let modified = fixture
+            .replace("value = 1\n    description = \"a\"", "value = 2\n    description = \"a\"");
+        assert_ne!(modified, fixture);
+        let mut runbook =
+            build_runbook_from_fixture("ab_c.tx", &modified, get_addon_by_namespace).await.unwrap();
+        let current = runbook.compute_execution_plan(None).await.unwrap();
+        let diag = approved.check_drift(&current).unwrap_err();
+        assert!(diag.message.contains("variable.a (flow"));
+    }
+}
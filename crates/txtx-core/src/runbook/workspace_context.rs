@@ -257,7 +257,15 @@ impl RunbookWorkspaceContext {
 
                         let Some((namespace, command_id)) = namespaced_action.split_once("::")
                         else {
-                            todo!("return diagnostic")
+                            diagnostics.push(
+                                diagnosed_error!(
+                                    "invalid action syntax: expected `action \"{}\" \"namespace::action\"`",
+                                    command_name.as_str()
+                                )
+                                .location(&location)
+                                .set_span_range(block.span()),
+                            );
+                            continue;
                         };
 
                         match runtime_context.addons_context.create_action_instance(
@@ -408,7 +416,8 @@ impl RunbookWorkspaceContext {
                     unknown => {
                         diagnostics.push(
                             Diagnostic::error_from_string(format!("unknown construct {}", unknown))
-                                .location(&location),
+                                .location(&location)
+                                .set_span_range(block.ident.span()),
                         );
                     }
                 }
@@ -788,7 +797,12 @@ impl RunbookWorkspaceContext {
                     Expression::Bool(value) => {
                         subpath.push_back(Value::bool(**value));
                     }
-                    _ => unimplemented!(),
+                    _ => {
+                        return Err(format!(
+                            "unable to resolve '{}': only literal values can be used as indexes",
+                            expression.to_string().trim()
+                        ));
+                    }
                 }
             }
         }
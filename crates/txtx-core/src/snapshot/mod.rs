@@ -1,17 +1,448 @@
+use serde::{Deserialize, Serialize};
+use txtx_addon_kit::helpers::fs::FileLocation;
+use txtx_addon_kit::indexmap::IndexMap;
+use txtx_addon_kit::types::types::Value;
+use txtx_addon_kit::types::ConstructDid;
+
+use crate::manifest::{RunbookStateLocation, WorkspaceManifest};
+use crate::runbook::now_as_string;
+use crate::runbook::RunbookExecutionSnapshot;
+
+/// A named, reviewable artifact grouping the executions of several runbooks against the same
+/// environment. A snapshot is begun, accumulates runbook executions, and is then committed.
+/// A committed snapshot can be replayed, writing the recorded states back, or rolled back,
+/// restoring the states the runbooks had when the snapshot was begun.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snapshot {
+    /// Name of the snapshot
     pub name: String,
+    /// Schema version
+    pub version: u32,
+    /// Environment the runbooks were executed against
+    pub environment: String,
+    /// Keep track of the snapshot start date
+    pub begun_at: String,
+    /// Keep track of the snapshot commit date
+    pub committed_at: Option<String>,
+    /// Runbook executions recorded, indexed by runbook name
+    pub runbooks: IndexMap<String, RunbookSnapshot>,
+    /// States of the runbooks when the snapshot was begun, indexed by runbook name
+    #[serde(default)]
+    pub baselines: IndexMap<String, RunbookExecutionSnapshot>,
+    /// Id of the next state transition, never reused once assigned
+    #[serde(default)]
+    next_transition_id: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunbookSnapshot {
+    /// Serialized execution of the runbook
+    pub execution: RunbookExecutionSnapshot,
+    /// Ordered state transitions of the constructs executed by the runbook
     pub state_transitions: Vec<StateTransition>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StateTransition {
+    /// Unique, increasing id of the transition in the snapshot, across runbooks
     pub id: u32,
+    pub flow: String,
+    pub construct_did: ConstructDid,
+    pub construct_type: String,
+    pub construct_name: String,
+    pub construct_addon: Option<String>,
+    /// Outputs produced by the construct
+    pub outputs: IndexMap<String, Value>,
+}
+
+impl Snapshot {
+    pub fn begin(name: &str, environment: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: 1,
+            environment: environment.to_string(),
+            begun_at: now_as_string(),
+            committed_at: None,
+            runbooks: IndexMap::new(),
+            baselines: IndexMap::new(),
+            next_transition_id: 0,
+        }
+    }
+
+    pub fn is_committed(&self) -> bool {
+        self.committed_at.is_some()
+    }
+
+    /// Returns true if the execution ended after the snapshot was begun.
+    pub fn includes_execution(&self, execution: &RunbookExecutionSnapshot) -> bool {
+        let (Ok(begun_at), Ok(ended_at)) = (
+            chrono::DateTime::parse_from_rfc3339(&self.begun_at),
+            chrono::DateTime::parse_from_rfc3339(execution.ended_at()),
+        ) else {
+            return false;
+        };
+        ended_at >= begun_at
+    }
+
+    /// Records the state of a runbook before the snapshot, restored when the snapshot is
+    /// rolled back. Runbooks without a baseline had no state when the snapshot was begun.
+    pub fn record_baseline(&mut self, execution: RunbookExecutionSnapshot) -> Result<(), String> {
+        if self.is_committed() {
+            return Err(format!("snapshot '{}' is already committed", self.name));
+        }
+        self.baselines.insert(execution.name().to_string(), execution);
+        Ok(())
+    }
+
+    /// Records the execution of a runbook, along with the state transitions of the constructs
+    /// it executed. Recording a runbook a second time replaces its previous execution.
+    pub fn record_runbook_execution(
+        &mut self,
+        execution: RunbookExecutionSnapshot,
+    ) -> Result<(), String> {
+        if self.is_committed() {
+            return Err(format!("snapshot '{}' is already committed", self.name));
+        }
+        let runbook_name = execution.name().to_string();
+        // snapshots written before ids were tracked only carry the ids of their transitions
+        let mut id = self
+            .state_transitions()
+            .map(|t| t.id + 1)
+            .max()
+            .unwrap_or(0)
+            .max(self.next_transition_id);
+        self.runbooks.shift_remove(&runbook_name);
+
+        let mut state_transitions = vec![];
+        for (flow, flow_snapshot) in execution.flows.iter() {
+            for (construct_did, command) in flow_snapshot.commands.iter() {
+                if !command.executed {
+                    continue;
+                }
+                state_transitions.push(StateTransition {
+                    id,
+                    flow: flow.clone(),
+                    construct_did: construct_did.clone(),
+                    construct_type: command.construct_type.clone(),
+                    construct_name: command.construct_name.clone(),
+                    construct_addon: command.construct_addon.clone(),
+                    outputs: command
+                        .outputs
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.value.clone()))
+                        .collect(),
+                });
+                id += 1;
+            }
+        }
+        self.next_transition_id = id;
+        self.runbooks.insert(runbook_name, RunbookSnapshot { execution, state_transitions });
+        Ok(())
+    }
+
+    pub fn commit(&mut self) -> Result<(), String> {
+        if self.is_committed() {
+            return Err(format!("snapshot '{}' is already committed", self.name));
+        }
+        if self.runbooks.is_empty() {
+            return Err(format!("snapshot '{}' does not include any runbook execution", self.name));
+        }
+        self.committed_at = Some(now_as_string());
+        Ok(())
+    }
+
+    /// Iterates over the state transitions of all the runbooks, in execution order.
+    pub fn state_transitions(&self) -> impl Iterator<Item = &StateTransition> {
+        self.runbooks.values().flat_map(|r| r.state_transitions.iter())
+    }
+
+    /// Replays the state transitions, up to and including the transition `up_to` if provided,
+    /// and returns the resulting outputs of each construct.
+    pub fn replay_state_transitions(
+        &self,
+        up_to: Option<u32>,
+    ) -> IndexMap<ConstructDid, IndexMap<String, Value>> {
+        let mut outputs: IndexMap<ConstructDid, IndexMap<String, Value>> = IndexMap::new();
+        for transition in self.state_transitions() {
+            if up_to.map_or(false, |up_to| transition.id > up_to) {
+                continue;
+            }
+            let construct_outputs = outputs.entry(transition.construct_did.clone()).or_default();
+            for (key, value) in transition.outputs.iter() {
+                construct_outputs.insert(key.clone(), value.clone());
+            }
+        }
+        outputs
+    }
+
+    /// Writes the recorded runbook states to their state location, for the environment of the
+    /// snapshot. Returns the descriptions of the locations written.
+    pub fn replay(&self, manifest: &WorkspaceManifest) -> Result<Vec<String>, String> {
+        if !self.is_committed() {
+            return Err(format!("snapshot '{}' must be committed to be replayed", self.name));
+        }
+        let mut written = vec![];
+        for (runbook_name, runbook_snapshot) in self.runbooks.iter() {
+            let state_location = self.get_state_location(manifest, runbook_name)?;
+            written.push(state_location.write_execution_snapshot(
+                &runbook_snapshot.execution,
+                runbook_name,
+                &self.environment,
+            )?);
+        }
+        Ok(written)
+    }
+
+    /// Restores the states the runbooks had when the snapshot was begun, in reverse execution
+    /// order, removing the states of the runbooks first executed in the snapshot.
+    /// Fails if a runbook was executed again since the snapshot was committed.
+    /// Returns the descriptions of the locations restored.
+    pub fn rollback(&self, manifest: &WorkspaceManifest) -> Result<Vec<String>, String> {
+        if !self.is_committed() {
+            return Err(format!("snapshot '{}' must be committed to be rolled back", self.name));
+        }
+        let mut state_locations = vec![];
+        for (runbook_name, runbook_snapshot) in self.runbooks.iter() {
+            let state_location = self.get_state_location(manifest, runbook_name)?;
+            let current =
+                state_location.load_execution_snapshot(false, runbook_name, &self.environment)?;
+            if current.ended_at() != runbook_snapshot.execution.ended_at() {
+                return Err(format!(
+                    "runbook '{}' was executed after snapshot '{}' was committed",
+                    runbook_name, self.name
+                ));
+            }
+            state_locations.push((runbook_name, state_location));
+        }
+
+        let mut restored = vec![];
+        for (runbook_name, state_location) in state_locations.into_iter().rev() {
+            let description = match self.baselines.get(runbook_name) {
+                Some(baseline) => state_location.write_execution_snapshot(
+                    baseline,
+                    runbook_name,
+                    &self.environment,
+                )?,
+                None => {
+                    state_location.remove_execution_snapshot(runbook_name, &self.environment)?
+                }
+            };
+            restored.push(description);
+        }
+        Ok(restored)
+    }
+
+    fn get_state_location<'a>(
+        &self,
+        manifest: &'a WorkspaceManifest,
+        runbook_name: &str,
+    ) -> Result<&'a RunbookStateLocation, String> {
+        manifest
+            .runbooks
+            .iter()
+            .find(|r| r.name.eq(runbook_name))
+            .ok_or(format!("runbook '{}' not found in manifest", runbook_name))?
+            .state
+            .as_ref()
+            .ok_or(format!("runbook '{}' is not configured with a state location", runbook_name))
+    }
 }
 
-// Step 1: Serialize the state of a Runbook Execution
-// Step 2: Execute a runbook
+/// Location of a snapshot file. While a snapshot is in progress, it is kept in a `.lock` file
+/// next to the snapshot file, which is written once the snapshot is committed.
+#[derive(Debug, Clone)]
+pub struct SnapshotLocation(pub FileLocation);
+
+impl SnapshotLocation {
+    pub fn get_lock_file_location(&self) -> Result<FileLocation, String> {
+        let file_name =
+            self.0.get_file_name().ok_or(format!("invalid snapshot path {}", self.0))?;
+        let mut lock_file_location = self.0.get_parent_location()?;
+        lock_file_location.append_path(&format!("{}.lock", file_name))?;
+        Ok(lock_file_location)
+    }
+
+    pub fn is_pending(&self) -> Result<bool, String> {
+        Ok(self.get_lock_file_location()?.exists())
+    }
+
+    pub fn begin(&self, snapshot: &Snapshot) -> Result<(), String> {
+        if self.is_pending()? {
+            return Err(format!("a snapshot is already in progress at {}", self.0));
+        }
+        self.write_snapshot(&self.get_lock_file_location()?, snapshot)
+    }
+
+    pub fn load_pending(&self) -> Result<Snapshot, String> {
+        if !self.is_pending()? {
+            return Err(format!("no snapshot in progress at {}", self.0));
+        }
+        Self::read_snapshot(&self.get_lock_file_location()?)
+    }
+
+    pub fn commit(&self, snapshot: &mut Snapshot) -> Result<(), String> {
+        snapshot.commit()?;
+        self.write_snapshot(&self.0, snapshot)?;
+        let lock_file_location = self.get_lock_file_location()?;
+        std::fs::remove_file(lock_file_location.to_string())
+            .map_err(|e| format!("unable to remove {}: {}", lock_file_location, e))
+    }
+
+    pub fn load(&self) -> Result<Snapshot, String> {
+        Self::read_snapshot(&self.0)
+    }
 
-// Todo:
-// Serialize execution graph
-// Load graph
-// Introduce snapshot construct in .tx
-// Ability to diff 2 graphs, and be smart about it
+    fn write_snapshot(&self, location: &FileLocation, snapshot: &Snapshot) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(snapshot)
+            .map_err(|e| format!("unable to serialize snapshot: {}", e))?;
+        location.write_content(content.as_bytes())
+    }
+
+    fn read_snapshot(location: &FileLocation) -> Result<Snapshot, String> {
+        let bytes = location.read_content()?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| format!("unable to read {}: {}", location, e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use txtx_addon_kit::types::Did;
+
+    use super::*;
+    use crate::manifest::RunbookMetadata;
+    use crate::runbook::CommandSnapshot;
+
+    const ENVIRONMENT: &str = "testnet";
+
+    fn construct_did(name: &str) -> ConstructDid {
+        ConstructDid(Did::from_components(vec![name.as_bytes()]))
+    }
+
+    fn execution(
+        name: &str,
+        ended_at: &str,
+        commands: Vec<(&str, i128)>,
+    ) -> RunbookExecutionSnapshot {
+        RunbookExecutionSnapshot::for_testing(
+            name,
+            ended_at,
+            commands
+                .into_iter()
+                .map(|(construct_name, value)| {
+                    let command = CommandSnapshot::for_testing(
+                        construct_name,
+                        vec![("value", Value::integer(value), true)],
+                        vec![("value", Value::integer(value))],
+                        true,
+                    );
+                    (construct_did(construct_name), command)
+                })
+                .collect(),
+        )
+    }
+
+    fn manifest(test_name: &str, runbooks: Vec<&str>) -> (WorkspaceManifest, PathBuf) {
+        let state_dir = std::env::temp_dir().join(format!(
+            "txtx-snapshot-{}-{}",
+            test_name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&state_dir);
+        let mut manifest = WorkspaceManifest::new("test".to_string());
+        for runbook in runbooks {
+            let mut metadata = RunbookMetadata::new(runbook, runbook, None);
+            metadata.state =
+                Some(RunbookStateLocation::Location(FileLocation::from_path(state_dir.clone())));
+            manifest.runbooks.push(metadata);
+        }
+        (manifest, state_dir)
+    }
+
+    #[test]
+    fn it_never_reuses_state_transition_ids() {
+        let mut snapshot = Snapshot::begin("release", ENVIRONMENT);
+        snapshot
+            .record_runbook_execution(execution("a", &now_as_string(), vec![("a1", 1), ("a2", 2)]))
+            .unwrap();
+        snapshot
+            .record_runbook_execution(execution("b", &now_as_string(), vec![("b1", 3)]))
+            .unwrap();
+        // recording "a" again removes its transitions, ids must not collide with "b"'s
+        snapshot
+            .record_runbook_execution(execution("a", &now_as_string(), vec![("a1", 4)]))
+            .unwrap();
+
+        let ids = snapshot.state_transitions().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![2, 3]);
+        let names =
+            snapshot.state_transitions().map(|t| t.construct_name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["b1", "a1"]);
+
+        // the counter survives a serialization round trip
+        let mut snapshot: Snapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        snapshot
+            .record_runbook_execution(execution("c", &now_as_string(), vec![("c1", 5)]))
+            .unwrap();
+        assert_eq!(snapshot.state_transitions().last().unwrap().id, 4);
+    }
+
+    #[test]
+    fn it_replays_state_transitions() {
+        let mut snapshot = Snapshot::begin("release", ENVIRONMENT);
+        snapshot
+            .record_runbook_execution(execution("a", &now_as_string(), vec![("a1", 1), ("a2", 2)]))
+            .unwrap();
+        snapshot
+            .record_runbook_execution(execution("b", &now_as_string(), vec![("b1", 3)]))
+            .unwrap();
+
+        let outputs = snapshot.replay_state_transitions(Some(1));
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[&construct_did("a2")]["value"].as_integer(), Some(2));
+        assert!(outputs.get(&construct_did("b1")).is_none());
+
+        let outputs = snapshot.replay_state_transitions(None);
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs[&construct_did("b1")]["value"].as_integer(), Some(3));
+    }
+
+    #[test]
+    fn it_replays_and_rolls_back_runbook_states() {
+        let (manifest, state_dir) = manifest("rollback", vec!["a", "b"]);
+        let state_location = manifest.runbooks[0].state.clone().unwrap();
+
+        // "a" has a state before the snapshot is begun, "b" does not
+        let baseline = execution("a", &now_as_string(), vec![("a1", 1)]);
+        state_location.write_execution_snapshot(&baseline, "a", ENVIRONMENT).unwrap();
+        let mut snapshot = Snapshot::begin("release", ENVIRONMENT);
+        snapshot.record_baseline(baseline.clone()).unwrap();
+        assert!(snapshot.replay(&manifest).is_err());
+
+        let a = execution("a", &now_as_string(), vec![("a1", 2)]);
+        let b = execution("b", &now_as_string(), vec![("b1", 3)]);
+        snapshot.record_runbook_execution(a.clone()).unwrap();
+        snapshot.record_runbook_execution(b.clone()).unwrap();
+        snapshot.commit().unwrap();
+
+        assert_eq!(snapshot.replay(&manifest).unwrap().len(), 2);
+        let current = state_location.load_execution_snapshot(false, "b", ENVIRONMENT).unwrap();
+        assert_eq!(current.ended_at(), b.ended_at());
+
+        assert_eq!(snapshot.rollback(&manifest).unwrap().len(), 2);
+        let current = state_location.load_execution_snapshot(false, "a", ENVIRONMENT).unwrap();
+        assert_eq!(current.ended_at(), baseline.ended_at());
+        assert!(state_location.load_execution_snapshot(false, "b", ENVIRONMENT).is_err());
+
+        // a runbook executed after the snapshot was committed can't be rolled back
+        snapshot.replay(&manifest).unwrap();
+        let later = execution("b", "2100-01-01T00:00:00+00:00", vec![("b1", 4)]);
+        state_location.write_execution_snapshot(&later, "b", ENVIRONMENT).unwrap();
+        assert!(snapshot.rollback(&manifest).is_err());
+
+        let _ = std::fs::remove_dir_all(&state_dir);
+    }
+}
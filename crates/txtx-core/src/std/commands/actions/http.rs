@@ -1,3 +1,7 @@
+use std::time::Duration;
+
+use serde_json::Value as JsonValue;
+use txtx_addon_kit::indexmap::IndexMap;
 use txtx_addon_kit::reqwest::{self, Method};
 use txtx_addon_kit::types::commands::{CommandExecutionFutureResult, PreCommandSpecification};
 use txtx_addon_kit::types::frontend::{Actions, BlockEvent};
@@ -36,9 +40,8 @@ lazy_static! {
                 },
                 method: {
                   documentation: indoc!{r#"
-                  The HTTP Method for the request. 
-                  Allowed methods are a subset of methods defined in RFC7231: GET, HEAD, and POST. 
-                  POST support is only intended for read-only URLs, such as submitting a search."#},
+                  The HTTP Method for the request.
+                  Allowed methods are a subset of methods defined in RFC7231: GET, HEAD, POST, PUT, PATCH and DELETE. The default is GET."#},
                   typing: Type::string(),
                   optional: true,
                   tainting: true,
@@ -57,6 +60,58 @@ lazy_static! {
                     optional: true,
                     tainting: true,
                     internal: false
+                },
+                bearer_token: {
+                    documentation: "A token to send in the `Authorization` header, using the Bearer scheme.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: true
+                },
+                basic_auth_username: {
+                    documentation: "The username to send in the `Authorization` header, using the Basic scheme.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: true
+                },
+                basic_auth_password: {
+                    documentation: "The password to send in the `Authorization` header, using the Basic scheme. Requires `basic_auth_username`.",
+                    typing: Type::string(),
+                    optional: true,
+                    tainting: true,
+                    internal: false,
+                    sensitive: true
+                },
+                expected_status: {
+                    documentation: "The status code expected in the response. If provided, any other status code fails the action.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: true,
+                    internal: false
+                },
+                retry_status_codes: {
+                    documentation: "The response status codes that should trigger a retry of the request, such as `[429, 503]`. The default is to never retry.",
+                    typing: Type::array(Type::integer()),
+                    optional: true,
+                    tainting: true,
+                    internal: false
+                },
+                max_retries: {
+                    documentation: "The maximum number of retries when the response status code is one of `retry_status_codes`. The default is 3.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: true,
+                    internal: false
+                },
+                retry_interval_ms: {
+                    documentation: "The delay in milliseconds before the first retry. The delay is doubled after each retry. The default is 500.",
+                    typing: Type::integer(),
+                    optional: true,
+                    tainting: true,
+                    internal: false
                 }
             ],
             outputs: [
@@ -64,6 +119,14 @@ lazy_static! {
                     documentation: "The response body returned as a string.",
                     typing: Type::string()
                 },
+                response_json: {
+                    documentation: "The response body decoded as JSON. This output is only set when the response body is a JSON object or array.",
+                    typing: Type::arbitrary_object()
+                },
+                response_headers: {
+                    documentation: "A map of the response header field names and values. Values of repeated headers are separated by commas.",
+                    typing: Type::arbitrary_object()
+                },
                 status_code: {
                     documentation: "The HTTP response status code.",
                     typing: Type::integer()
@@ -73,15 +136,32 @@ lazy_static! {
             action "example" "std::send_http_request" {
               url = "https://example.com"
             }
-          
+
             output "status" {
               value = action.example.status_code
             }
             // > status: 200
+
+            action "register" "std::send_http_request" {
+              url = "https://registry.example.com/deployments"
+              method = "PUT"
+              body = { "name": "counter", "address": "0x1234" }
+              bearer_token = env.REGISTRY_TOKEN
+              expected_status = 200
+              retry_status_codes = [429, 503]
+            }
+
+            output "deployment" {
+              value = action.register.response_json
+            }
             "#},
         }
     };
 }
+const ALLOWED_METHODS: [&str; 6] = ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE"];
+const DEFAULT_MAX_RETRIES: u64 = 3;
+const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
 pub struct SendHttpRequest;
 
 impl CommandImplementation for SendHttpRequest {
@@ -113,10 +193,43 @@ impl CommandImplementation for SendHttpRequest {
         let url = values.get_expected_string("url")?.to_string();
         let request_body = values.get_value("body").cloned();
         let method = {
-            let value = values.get_string("method").unwrap_or("GET");
-            Method::try_from(value).unwrap()
+            let value = values.get_string("method").unwrap_or("GET").to_uppercase();
+            if !ALLOWED_METHODS.contains(&value.as_str()) {
+                return Err(diagnosed_error!(
+                    "unsupported http method '{}'; allowed methods are {}",
+                    value,
+                    ALLOWED_METHODS.join(", ")
+                ));
+            }
+            Method::try_from(value.as_str()).unwrap()
         };
         let request_headers = values.get_value("headers").cloned();
+        let timeout = values
+            .get_uint("timeout_ms")
+            .map_err(|e| diagnosed_error!("invalid 'timeout_ms': {e}"))?
+            .map(Duration::from_millis);
+        let bearer_token = values.get_string("bearer_token").map(|s| s.to_string());
+        let basic_auth_username = values.get_string("basic_auth_username").map(|s| s.to_string());
+        let basic_auth_password = values.get_string("basic_auth_password").map(|s| s.to_string());
+        if basic_auth_password.is_some() && basic_auth_username.is_none() {
+            return Err(diagnosed_error!("'basic_auth_password' requires 'basic_auth_username'"));
+        }
+        if bearer_token.is_some() && basic_auth_username.is_some() {
+            return Err(diagnosed_error!(
+                "'bearer_token' and 'basic_auth_username' cannot be used together"
+            ));
+        }
+        let expected_status = get_expected_status(&values)?;
+        let retry_status_codes = get_retry_status_codes(&values)?;
+        let max_retries = values
+            .get_uint("max_retries")
+            .map_err(|e| diagnosed_error!("invalid 'max_retries': {e}"))?
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+        let retry_interval = values
+            .get_uint("retry_interval_ms")
+            .map_err(|e| diagnosed_error!("invalid 'retry_interval_ms': {e}"))?
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_RETRY_INTERVAL);
 
         let future = async move {
             let request_headers = request_headers
@@ -142,6 +255,13 @@ impl CommandImplementation for SendHttpRequest {
                 }
             }
 
+            if let Some(bearer_token) = bearer_token {
+                req_builder = req_builder.bearer_auth(bearer_token);
+            }
+            if let Some(username) = basic_auth_username {
+                req_builder = req_builder.basic_auth(username, basic_auth_password);
+            }
+
             if let Some(request_body) = request_body {
                 if request_body.as_object().is_some() {
                     req_builder = req_builder.json(&request_body.to_json());
@@ -150,19 +270,62 @@ impl CommandImplementation for SendHttpRequest {
                 }
             }
 
-            let res = req_builder.send().await.map_err(|e| {
-                Diagnostic::error_from_string(format!("unable to send http request - {e}"))
-            })?;
+            let mut retries = 0;
+            let mut retry_interval = retry_interval;
+            let res = loop {
+                let request = req_builder
+                    .try_clone()
+                    .ok_or_else(|| diagnosed_error!("unable to build http request"))?
+                    .send();
+                let res = match timeout {
+                    Some(timeout) => {
+                        tokio::time::timeout(timeout, request).await.map_err(|_| {
+                            diagnosed_error!(
+                                "http request timed out after {}ms",
+                                timeout.as_millis()
+                            )
+                        })?
+                    }
+                    None => request.await,
+                }
+                .map_err(|e| {
+                    Diagnostic::error_from_string(format!("unable to send http request - {e}"))
+                })?;
+
+                if retries >= max_retries || !retry_status_codes.contains(&res.status().as_u16()) {
+                    break res;
+                }
+                tokio::time::sleep(retry_interval).await;
+                retries += 1;
+                retry_interval *= 2;
+            };
 
             let status_code = res.status();
+            let response_headers = fold_response_headers(res.headers());
+
             let response_body = res.text().await.map_err(|e| {
                 Diagnostic::error_from_string(format!("Failed to parse http request result: {e}"))
             })?;
 
+            if let Some(expected_status) = expected_status {
+                if status_code.as_u16() != expected_status {
+                    return Err(diagnosed_error!(
+                        "http request returned status code {}, expected {}: {}",
+                        status_code.as_u16(),
+                        expected_status,
+                        response_body
+                    ));
+                }
+            }
+
             result
                 .outputs
                 .insert(format!("status_code"), Value::integer(status_code.as_u16().into()));
 
+            if let Some(response_json) = decode_response_json(&response_body) {
+                result.outputs.insert(format!("response_json"), response_json);
+            }
+            result.outputs.insert(format!("response_headers"), Value::object(response_headers));
             result.outputs.insert(format!("response_body"), Value::string(response_body));
 
             Ok::<CommandExecutionResult, Diagnostic>(result)
@@ -173,3 +336,138 @@ impl CommandImplementation for SendHttpRequest {
         Ok(Box::pin(future))
     }
 }
+
+/// Parses the `expected_status` input, which must be a valid status code.
+fn get_expected_status(values: &ValueStore) -> Result<Option<u16>, Diagnostic> {
+    values
+        .get_value("expected_status")
+        .map(|value| {
+            parse_status_code(value).ok_or_else(|| {
+                diagnosed_error!(
+                    "'expected_status' must be a status code; found '{}'",
+                    value.to_string()
+                )
+            })
+        })
+        .transpose()
+}
+
+/// Parses the `retry_status_codes` input, which must be a list of valid status codes.
+fn get_retry_status_codes(values: &ValueStore) -> Result<Vec<u16>, Diagnostic> {
+    let Some(value) = values.get_value("retry_status_codes") else {
+        return Ok(vec![]);
+    };
+    value
+        .as_array()
+        .and_then(|codes| codes.iter().map(parse_status_code).collect::<Option<Vec<u16>>>())
+        .ok_or_else(|| diagnosed_error!("'retry_status_codes' must be a list of status codes"))
+}
+
+fn parse_status_code(value: &Value) -> Option<u16> {
+    let code = u16::try_from(value.as_integer()?).ok()?;
+    reqwest::StatusCode::from_u16(code).ok().map(|code| code.as_u16())
+}
+
+/// Collects the response headers by name. The values of repeated headers are folded into
+/// a single comma separated value.
+fn fold_response_headers(headers: &reqwest::header::HeaderMap) -> IndexMap<String, Value> {
+    let mut response_headers: IndexMap<String, Value> = IndexMap::new();
+    for (name, value) in headers.iter() {
+        let value = String::from_utf8_lossy(value.as_bytes()).to_string();
+        match response_headers.get_mut(name.as_str()) {
+            Some(Value::String(existing)) => {
+                existing.push_str(", ");
+                existing.push_str(&value);
+            }
+            _ => {
+                response_headers.insert(name.to_string(), Value::string(value));
+            }
+        }
+    }
+    response_headers
+}
+
+/// Decodes the response body when it is a JSON object or array. Scalar JSON documents,
+/// such as a bare string or number, are left to the `response_body` output.
+fn decode_response_json(response_body: &str) -> Option<Value> {
+    match serde_json::from_str::<JsonValue>(response_body).ok()? {
+        json @ (JsonValue::Object(_) | JsonValue::Array(_)) => Some(Value::from_json(&json)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use txtx_addon_kit::reqwest::header::{HeaderMap, HeaderValue};
+    use txtx_addon_kit::types::Did;
+
+    use super::*;
+
+    fn values(inputs: Vec<(&str, Value)>) -> ValueStore {
+        let mut values = ValueStore::new("send_http_request", &Did::zero());
+        for (key, value) in inputs {
+            values.insert(key, value);
+        }
+        values
+    }
+
+    #[test]
+    fn it_folds_repeated_response_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append("set-cookie", HeaderValue::from_static("a=1"));
+        headers.append("content-type", HeaderValue::from_static("application/json"));
+        headers.append("set-cookie", HeaderValue::from_static("b=2"));
+
+        let response_headers = fold_response_headers(&headers);
+        assert_eq!(response_headers.len(), 2);
+        assert_eq!(response_headers.get("set-cookie"), Some(&Value::string("a=1, b=2".into())));
+        assert_eq!(
+            response_headers.get("content-type"),
+            Some(&Value::string("application/json".into()))
+        );
+    }
+
+    #[test_case(r#"{"address": "0x1234", "nonce": 2}"#, Some(Value::object(IndexMap::from([("address".to_string(), Value::string("0x1234".into())), ("nonce".to_string(), Value::integer(2))]))); "object")]
+    #[test_case(r#"[1, "two", null]"#, Some(Value::array(vec![Value::integer(1), Value::string("two".into()), Value::null()])); "array")]
+    #[test_case(r#""ok""#, None; "string")]
+    #[test_case("42", None; "number")]
+    #[test_case("null", None; "null")]
+    #[test_case("<html></html>", None; "not json")]
+    #[test_case("", None; "empty")]
+    fn it_decodes_json_responses(response_body: &str, expected: Option<Value>) {
+        assert_eq!(decode_response_json(response_body), expected);
+    }
+
+    #[test_case(vec![], None; "missing")]
+    #[test_case(vec![("expected_status", Value::integer(201))], Some(201); "created")]
+    fn it_parses_expected_status(inputs: Vec<(&str, Value)>, expected: Option<u16>) {
+        assert_eq!(get_expected_status(&values(inputs)).unwrap(), expected);
+    }
+
+    #[test_case(Value::integer(-1); "negative")]
+    #[test_case(Value::integer(42); "too small")]
+    #[test_case(Value::integer(1000); "too large")]
+    #[test_case(Value::integer(65_736); "overflowing")]
+    #[test_case(Value::string("200".into()); "string")]
+    fn it_rejects_invalid_expected_status(expected_status: Value) {
+        let err = get_expected_status(&values(vec![("expected_status", expected_status)]));
+        assert!(err.unwrap_err().message.starts_with("'expected_status' must be a status code"));
+    }
+
+    #[test_case(vec![], vec![]; "missing")]
+    #[test_case(vec![("retry_status_codes", Value::array(vec![]))], vec![]; "empty")]
+    #[test_case(vec![("retry_status_codes", Value::array(vec![Value::integer(429), Value::integer(503)]))], vec![429, 503]; "codes")]
+    fn it_parses_retry_status_codes(inputs: Vec<(&str, Value)>, expected: Vec<u16>) {
+        assert_eq!(get_retry_status_codes(&values(inputs)).unwrap(), expected);
+    }
+
+    #[test_case(Value::integer(429); "not a list")]
+    #[test_case(Value::array(vec![Value::integer(429), Value::string("503".into())]); "string code")]
+    #[test_case(Value::array(vec![Value::integer(70_000)]); "overflowing code")]
+    #[test_case(Value::array(vec![Value::integer(5)]); "invalid code")]
+    fn it_rejects_invalid_retry_status_codes(retry_status_codes: Value) {
+        let err = get_retry_status_codes(&values(vec![("retry_status_codes", retry_status_codes)]));
+        assert_eq!(err.unwrap_err().message, "'retry_status_codes' must be a list of status codes");
+    }
+}
@@ -0,0 +1,131 @@
+use txtx_addon_kit::types::AuthorizationContext;
+use txtx_addon_kit::{
+    define_function, indoc,
+    types::{
+        diagnostics::Diagnostic,
+        functions::{FunctionImplementation, FunctionSpecification},
+        types::{Type, Value},
+    },
+};
+
+use super::{any_type, arg_checker, check_arg_types, to_diag, value_to_string};
+
+lazy_static! {
+    pub static ref CONVERSION_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_function! {
+            ConvertToInt => {
+                name: "to_int",
+                documentation: indoc!{r#"
+                `to_int` converts a value to an integer.
+                Strings can be decimal (`"42"`) or `0x` prefixed hexadecimal (`"0x2a"`) numbers, floats are truncated, and booleans are converted to 0 or 1.
+                "#},
+                example: indoc!{r#"
+                output "number" {
+                    value = to_int("0x2a")
+                }
+                // > number: 42
+                "#},
+                inputs: [
+                    value: {
+                        documentation: "The string, float, boolean or integer to convert.",
+                        typing: vec![Type::string(), Type::float(), Type::bool(), Type::integer()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The integer value.",
+                    typing: Type::integer()
+                },
+            }
+        },
+        define_function! {
+            ConvertToString => {
+                name: "to_string",
+                documentation: "`to_string` converts a value to a string. Lists and objects are converted to JSON, and buffers to `0x` prefixed hexadecimal strings.",
+                example: indoc!{r#"
+                output "text" {
+                    value = to_string(42)
+                }
+                // > text: 42
+                "#},
+                inputs: [
+                    value: {
+                        documentation: "The value to convert.",
+                        typing: any_type(),
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The string representation of the value.",
+                    typing: Type::string()
+                },
+            }
+        }
+    ];
+}
+
+pub struct ConvertToInt;
+impl FunctionImplementation for ConvertToInt {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let value = match args.get(0).unwrap() {
+            Value::Integer(value) => *value,
+            Value::Bool(value) => *value as i128,
+            Value::Float(value) => {
+                if !value.is_finite() {
+                    return Err(to_diag(fn_spec, format!("cannot convert {} to integer", value)));
+                }
+                value.trunc() as i128
+            }
+            Value::String(value) => {
+                let value = value.trim();
+                let parsed = match value.strip_prefix("0x") {
+                    Some(hex) => i128::from_str_radix(hex, 16),
+                    None => value.parse::<i128>(),
+                };
+                parsed.map_err(|e| {
+                    to_diag(fn_spec, format!("cannot convert '{}' to integer: {}", value, e))
+                })?
+            }
+            other => {
+                return Err(to_diag(
+                    fn_spec,
+                    format!("cannot convert {} to integer", other.get_type().to_string()),
+                ))
+            }
+        };
+        Ok(Value::integer(value))
+    }
+}
+
+pub struct ConvertToString;
+impl FunctionImplementation for ConvertToString {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        Ok(Value::string(value_to_string(args.get(0).unwrap())))
+    }
+}
@@ -8,8 +8,11 @@ use txtx_addon_kit::{
     },
 };
 
+use super::{any_type, arg_checker, check_arg_types, check_variadic_arg_types, to_diag};
+
 lazy_static! {
-    pub static ref LIST_FUNCTIONS: Vec<FunctionSpecification> = vec![define_function! {
+    pub static ref LIST_FUNCTIONS: Vec<FunctionSpecification> = vec![
+    define_function! {
         Index => {
             name: "index",
             documentation: "`index` gets the entry from a list at the specified index.",
@@ -34,17 +37,129 @@ lazy_static! {
                 typing: Type::string() // todo: the result can be any type, but our types don't have an any
             },
         }
-    },];
+    },
+    define_function! {
+        Concat => {
+            name: "concat",
+            documentation: "`concat` concatenates strings, lists or buffers. All the arguments must be of the same kind.",
+            example: indoc!{r#"
+            output "concatenated" {
+                value = concat(["a", "b"], ["c"])
+            }
+            // > concatenated: ["a", "b", "c"]
+          "#},
+            inputs: [
+                values: {
+                    documentation: "The strings, lists or buffers to concatenate, provided as separate arguments.",
+                    typing: vec![Type::string(), Type::array(Type::null()), Type::buffer()],
+                    optional: false
+                }
+            ],
+            output: {
+                documentation: "The concatenation of the arguments, of the same kind as the arguments.",
+                typing: Type::string()
+            },
+        }
+    },
+    define_function! {
+        Contains => {
+            name: "contains",
+            documentation: "`contains` checks whether a list contains a value, a string contains a substring, or an object contains a key.",
+            example: indoc!{r#"
+            output "has_b" {
+                value = contains(["a", "b", "c"], "b")
+            }
+            // > has_b: true
+          "#},
+            inputs: [
+                collection: {
+                    documentation: "The list, string or object to search in.",
+                    typing: vec![Type::array(Type::null()), Type::string(), Type::arbitrary_object()],
+                    optional: false
+                },
+                value: {
+                    documentation: "The value, substring or key to search for.",
+                    typing: any_type(),
+                    optional: false
+                }
+            ],
+            output: {
+                documentation: "`true` if the collection contains the value, `false` otherwise.",
+                typing: Type::bool()
+            },
+        }
+    },
+    define_function! {
+        Length => {
+            name: "length",
+            documentation: "`length` returns the number of elements of a list, the number of characters of a string, the number of keys of an object, or the number of bytes of a buffer.",
+            example: indoc!{r#"
+            output "count" {
+                value = length(["a", "b", "c"])
+            }
+            // > count: 3
+          "#},
+            inputs: [
+                collection: {
+                    documentation: "The list, string, object or buffer to measure.",
+                    typing: vec![Type::array(Type::null()), Type::string(), Type::arbitrary_object(), Type::buffer()],
+                    optional: false
+                }
+            ],
+            output: {
+                documentation: "The length of the collection.",
+                typing: Type::integer()
+            },
+        }
+    },
+    define_function! {
+        Range => {
+            name: "range",
+            documentation: "`range` generates the list of integers from `start` (included) to `end` (excluded), separated by `step`.",
+            example: indoc!{r#"
+            output "indexes" {
+                value = range(0, 10, 3)
+            }
+            // > indexes: [0, 3, 6, 9]
+          "#},
+            inputs: [
+                start: {
+                    documentation: "The first integer of the list.",
+                    typing: vec![Type::integer()],
+                    optional: false
+                },
+                end: {
+                    documentation: "The upper bound (excluded) of the list, or its lower bound if `step` is negative.",
+                    typing: vec![Type::integer()],
+                    optional: false
+                },
+                step: {
+                    documentation: "The difference between two consecutive integers. The default is 1.",
+                    typing: vec![Type::integer()],
+                    optional: true
+                }
+            ],
+            output: {
+                documentation: "The list of integers.",
+                typing: Type::array(Type::integer())
+            },
+        }
+    }
+    ];
 }
 
 pub struct Index;
 impl FunctionImplementation for Index {
     fn check_instantiability(
-        _fn_spec: &FunctionSpecification,
+        fn_spec: &FunctionSpecification,
         _auth_ctx: &AuthorizationContext,
-        _args: &Vec<Type>,
+        args: &Vec<Type>,
     ) -> Result<Type, Diagnostic> {
-        unimplemented!()
+        check_arg_types(fn_spec, args)?;
+        match args.get(0) {
+            Some(Type::Array(item_type)) => Ok(*item_type.clone()),
+            _ => Ok(fn_spec.output.typing.clone()),
+        }
     }
 
     fn run(
@@ -64,3 +179,167 @@ impl FunctionImplementation for Index {
         }
     }
 }
+
+pub struct Concat;
+impl FunctionImplementation for Concat {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_variadic_arg_types(fn_spec, args)?;
+        let first = args.get(0).unwrap();
+        for arg in args.iter().skip(1) {
+            if std::mem::discriminant(arg) != std::mem::discriminant(first) {
+                return Err(to_diag(
+                    fn_spec,
+                    format!("cannot concatenate {} and {}", first.to_string(), arg.to_string()),
+                ));
+            }
+        }
+        Ok(first.clone())
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let mut result = args.get(0).unwrap().clone();
+        for arg in args.iter().skip(1) {
+            match (&mut result, arg) {
+                (Value::String(result), Value::String(value)) => result.push_str(value),
+                (Value::Array(result), Value::Array(values)) => {
+                    result.extend(values.iter().cloned())
+                }
+                (Value::Buffer(result), Value::Buffer(bytes)) => result.extend(bytes),
+                (result, arg) => {
+                    return Err(to_diag(
+                        fn_spec,
+                        format!(
+                            "cannot concatenate {} and {}",
+                            result.get_type().to_string(),
+                            arg.get_type().to_string()
+                        ),
+                    ))
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+pub struct Contains;
+impl FunctionImplementation for Contains {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let value = args.get(1).unwrap();
+        let contains = match (args.get(0).unwrap(), value) {
+            (Value::Array(list), value) => list.contains(value),
+            (Value::String(string), Value::String(substring)) => {
+                string.contains(substring.as_str())
+            }
+            (Value::Object(object), Value::String(key)) => object.contains_key(key),
+            (collection, value) => {
+                return Err(to_diag(
+                    fn_spec,
+                    format!(
+                        "cannot search a {} in a {}",
+                        value.get_type().to_string(),
+                        collection.get_type().to_string()
+                    ),
+                ))
+            }
+        };
+        Ok(Value::bool(contains))
+    }
+}
+
+pub struct Length;
+impl FunctionImplementation for Length {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let length = match args.get(0).unwrap() {
+            Value::Array(list) => list.len(),
+            Value::String(string) => string.chars().count(),
+            Value::Object(object) => object.len(),
+            Value::Buffer(bytes) => bytes.len(),
+            other => {
+                return Err(to_diag(
+                    fn_spec,
+                    format!("cannot get the length of {}", other.get_type().to_string()),
+                ))
+            }
+        };
+        Ok(Value::integer(length as i128))
+    }
+}
+
+pub struct Range;
+impl FunctionImplementation for Range {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let start = args.get(0).unwrap().expect_integer();
+        let end = args.get(1).unwrap().expect_integer();
+        let step = match args.get(2) {
+            Some(Value::Integer(step)) => *step,
+            Some(other) => {
+                return Err(to_diag(
+                    fn_spec,
+                    format!("step should be an integer, found {}", other.get_type().to_string()),
+                ))
+            }
+            None => 1,
+        };
+        if step == 0 {
+            return Err(to_diag(fn_spec, "step cannot be 0".into()));
+        }
+        let mut values = vec![];
+        let mut current = start;
+        while (step > 0 && current < end) || (step < 0 && current > end) {
+            values.push(Value::integer(current));
+            let Some(next) = current.checked_add(step) else {
+                break;
+            };
+            current = next;
+        }
+        Ok(Value::array(values))
+    }
+}
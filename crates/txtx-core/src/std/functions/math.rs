@@ -0,0 +1,385 @@
+use std::cmp::Ordering;
+
+use txtx_addon_kit::types::AuthorizationContext;
+use txtx_addon_kit::{
+    define_function, indoc,
+    types::{
+        diagnostics::Diagnostic,
+        functions::{FunctionImplementation, FunctionSpecification},
+        types::{Type, Value},
+    },
+};
+
+use super::{arg_checker, check_arg_types, check_variadic_arg_types, to_diag};
+
+lazy_static! {
+    pub static ref MATH_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_function! {
+            Min => {
+                name: "min",
+                documentation: "`min` returns the smallest of a list of numbers. The numbers can be provided as a list, or as separate arguments.",
+                example: indoc!{r#"
+                output "smallest" {
+                    value = min(12, 4, 7)
+                }
+                // > smallest: 4
+                "#},
+                inputs: [
+                    values: {
+                        documentation: "The `integer` or `float` numbers to compare, or a list of numbers.",
+                        typing: vec![Type::integer(), Type::float(), Type::array(Type::integer()), Type::array(Type::float())],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The smallest number. The result is a `float` if any of the numbers is a `float`.",
+                    typing: Type::integer()
+                },
+            }
+        },
+        define_function! {
+            Max => {
+                name: "max",
+                documentation: "`max` returns the largest of a list of numbers. The numbers can be provided as a list, or as separate arguments.",
+                example: indoc!{r#"
+                output "largest" {
+                    value = max([12, 4, 7])
+                }
+                // > largest: 12
+                "#},
+                inputs: [
+                    values: {
+                        documentation: "The `integer` or `float` numbers to compare, or a list of numbers.",
+                        typing: vec![Type::integer(), Type::float(), Type::array(Type::integer()), Type::array(Type::float())],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The largest number. The result is a `float` if any of the numbers is a `float`.",
+                    typing: Type::integer()
+                },
+            }
+        },
+        define_function! {
+            Sum => {
+                name: "sum",
+                documentation: "`sum` returns the sum of a list of numbers. The numbers can be provided as a list, or as separate arguments.",
+                example: indoc!{r#"
+                output "total" {
+                    value = sum([12, 4, 7])
+                }
+                // > total: 23
+                "#},
+                inputs: [
+                    values: {
+                        documentation: "The `integer` or `float` numbers to add, or a list of numbers.",
+                        typing: vec![Type::integer(), Type::float(), Type::array(Type::integer()), Type::array(Type::float())],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The sum of the numbers. The result is a `float` if any of the numbers is a `float`.",
+                    typing: Type::integer()
+                },
+            }
+        },
+        define_function! {
+            ParseUnits => {
+                name: "parse_units",
+                documentation: indoc!{r#"
+                `parse_units` converts a decimal amount to an integer amount of base units, given the number of decimals of the unit.
+                For example, an amount of ETH can be converted to wei with 18 decimals, and an amount of STX to micro-STX with 6 decimals.
+                "#},
+                example: indoc!{r#"
+                output "amount_in_wei" {
+                    value = parse_units("1.5", 18)
+                }
+                // > amount_in_wei: 1500000000000000000
+                "#},
+                inputs: [
+                    amount: {
+                        documentation: "The decimal amount, as a string (recommended, to avoid rounding errors), an integer or a float.",
+                        typing: vec![Type::string(), Type::integer(), Type::float()],
+                        optional: false
+                    },
+                    decimals: {
+                        documentation: "The number of decimals of the unit.",
+                        typing: vec![Type::integer()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The amount in base units.",
+                    typing: Type::integer()
+                },
+            }
+        },
+        define_function! {
+            FormatUnits => {
+                name: "format_units",
+                documentation: "`format_units` converts an integer amount of base units to a decimal amount, given the number of decimals of the unit. It is the inverse of `parse_units`.",
+                example: indoc!{r#"
+                output "amount_in_eth" {
+                    value = format_units(1500000000000000000, 18)
+                }
+                // > amount_in_eth: 1.5
+                "#},
+                inputs: [
+                    amount: {
+                        documentation: "The amount in base units.",
+                        typing: vec![Type::integer()],
+                        optional: false
+                    },
+                    decimals: {
+                        documentation: "The number of decimals of the unit.",
+                        typing: vec![Type::integer()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The decimal amount, as a string.",
+                    typing: Type::string()
+                },
+            }
+        }
+    ];
+}
+
+/// Returns the type of the result of an aggregation of numbers: `float` if any of
+/// the numbers is a float, `integer` otherwise.
+fn check_numbers_types(
+    fn_spec: &FunctionSpecification,
+    args: &Vec<Type>,
+) -> Result<Type, Diagnostic> {
+    check_variadic_arg_types(fn_spec, args)?;
+    let has_float = args.iter().any(|arg| match arg {
+        Type::Float => true,
+        Type::Array(item_type) => **item_type == Type::Float,
+        _ => false,
+    });
+    Ok(if has_float { Type::float() } else { Type::integer() })
+}
+
+/// Collects the numbers provided either as separate arguments, or as a single list.
+fn get_numbers<'a>(
+    fn_spec: &FunctionSpecification,
+    args: &'a Vec<Value>,
+) -> Result<Vec<&'a Value>, Diagnostic> {
+    let numbers: Vec<&Value> = match args.as_slice() {
+        [Value::Array(list)] => list.iter().collect(),
+        args => args.iter().collect(),
+    };
+    if numbers.is_empty() {
+        return Err(to_diag(fn_spec, "expected at least one number".into()));
+    }
+    for number in numbers.iter() {
+        if !matches!(number, Value::Integer(_) | Value::Float(_)) {
+            return Err(to_diag(
+                fn_spec,
+                format!("expected a number, found {}", number.get_type().to_string()),
+            ));
+        }
+    }
+    Ok(numbers)
+}
+
+fn as_f64(value: &Value) -> f64 {
+    match value {
+        Value::Integer(value) => *value as f64,
+        Value::Float(value) => *value,
+        _ => unreachable!(),
+    }
+}
+
+fn compare_numbers(
+    fn_spec: &FunctionSpecification,
+    args: &Vec<Value>,
+    keep: Ordering,
+) -> Result<Value, Diagnostic> {
+    let numbers = get_numbers(fn_spec, args)?;
+    let mut result = numbers[0];
+    for number in numbers.iter().skip(1) {
+        let ordering = match (number, result) {
+            (Value::Integer(lhs), Value::Integer(rhs)) => lhs.cmp(rhs),
+            (lhs, rhs) => as_f64(lhs).total_cmp(&as_f64(rhs)),
+        };
+        if ordering == keep {
+            result = number;
+        }
+    }
+    if numbers.iter().any(|n| matches!(n, Value::Float(_))) {
+        Ok(Value::float(as_f64(result)))
+    } else {
+        Ok(result.clone())
+    }
+}
+
+fn get_decimals(fn_spec: &FunctionSpecification, value: &Value) -> Result<u32, Diagnostic> {
+    let decimals = value.expect_integer();
+    // 10^38 is the largest power of 10 fitting in an i128
+    if !(0..=38).contains(&decimals) {
+        return Err(to_diag(
+            fn_spec,
+            format!("decimals should be between 0 and 38, found {}", decimals),
+        ));
+    }
+    Ok(decimals as u32)
+}
+
+pub struct Min;
+impl FunctionImplementation for Min {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_numbers_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        compare_numbers(fn_spec, args, Ordering::Less)
+    }
+}
+
+pub struct Max;
+impl FunctionImplementation for Max {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_numbers_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        compare_numbers(fn_spec, args, Ordering::Greater)
+    }
+}
+
+pub struct Sum;
+impl FunctionImplementation for Sum {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_numbers_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        let numbers = get_numbers(fn_spec, args)?;
+        if numbers.iter().any(|n| matches!(n, Value::Float(_))) {
+            return Ok(Value::float(numbers.iter().map(|n| as_f64(n)).sum()));
+        }
+        let mut sum: i128 = 0;
+        for number in numbers {
+            sum = sum
+                .checked_add(number.expect_integer())
+                .ok_or_else(|| to_diag(fn_spec, "integer overflow".into()))?;
+        }
+        Ok(Value::integer(sum))
+    }
+}
+
+pub struct ParseUnits;
+impl FunctionImplementation for ParseUnits {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let amount = match args.get(0).unwrap() {
+            Value::String(amount) => amount.trim().to_string(),
+            Value::Integer(amount) => amount.to_string(),
+            Value::Float(amount) => amount.to_string(),
+            other => {
+                return Err(to_diag(
+                    fn_spec,
+                    format!("expected an amount, found {}", other.get_type().to_string()),
+                ))
+            }
+        };
+        let decimals = get_decimals(fn_spec, args.get(1).unwrap())?;
+        let invalid_amount = || to_diag(fn_spec, format!("invalid amount '{}'", amount));
+
+        let (negative, unsigned_amount) = match amount.strip_prefix('-') {
+            Some(unsigned_amount) => (true, unsigned_amount),
+            None => (false, amount.as_str()),
+        };
+        let (integer_part, fractional_part) =
+            unsigned_amount.split_once('.').unwrap_or((unsigned_amount, ""));
+        if integer_part.is_empty() && fractional_part.is_empty() {
+            return Err(invalid_amount());
+        }
+        if fractional_part.len() > decimals as usize {
+            return Err(to_diag(
+                fn_spec,
+                format!("amount '{}' has more than {} decimals", amount, decimals),
+            ));
+        }
+        let digits =
+            format!("{}{:0<width$}", integer_part, fractional_part, width = decimals as usize);
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid_amount());
+        }
+        let value = digits.parse::<i128>().map_err(|_| invalid_amount())?;
+        Ok(Value::integer(if negative { -value } else { value }))
+    }
+}
+
+pub struct FormatUnits;
+impl FunctionImplementation for FormatUnits {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let amount = args.get(0).unwrap().expect_integer();
+        let decimals = get_decimals(fn_spec, args.get(1).unwrap())?;
+
+        let unit = 10u128.pow(decimals);
+        let integer_part = amount.unsigned_abs() / unit;
+        let fractional_part = amount.unsigned_abs() % unit;
+        let sign = if amount < 0 { "-" } else { "" };
+        if fractional_part == 0 {
+            return Ok(Value::string(format!("{}{}", sign, integer_part)));
+        }
+        let fractional_part = format!("{:0>width$}", fractional_part, width = decimals as usize);
+        Ok(Value::string(format!(
+            "{}{}.{}",
+            sign,
+            integer_part,
+            fractional_part.trim_end_matches('0')
+        )))
+    }
+}
@@ -1,16 +1,21 @@
 pub mod base58;
 pub mod base64;
+pub mod conversion;
 pub mod crypto;
 pub mod hash;
 pub mod hex;
 pub mod json;
 pub mod list;
+pub mod math;
+pub mod object;
 pub mod operators;
+pub mod strings;
+pub mod time;
 use txtx_addon_kit::types::functions::FunctionSpecification;
 use txtx_addon_kit::types::{
     diagnostics::Diagnostic,
     functions::{arg_checker_with_ctx, fn_diag_with_ctx},
-    types::Value,
+    types::{Type, Value},
 };
 
 use crate::constants::NAMESPACE;
@@ -24,6 +29,102 @@ pub fn to_diag(fn_spec: &FunctionSpecification, e: String) -> Diagnostic {
     error_fn(fn_spec, e)
 }
 
+/// Checks the types of the arguments of a function call against the inputs of the function
+/// specification, and returns the type of the function output.
+pub fn check_arg_types(
+    fn_spec: &FunctionSpecification,
+    args: &Vec<Type>,
+) -> Result<Type, Diagnostic> {
+    check_args(fn_spec, args, false)
+}
+
+/// Same as [check_arg_types], for functions accepting any number of arguments: the extra
+/// arguments are checked against the last input of the function specification.
+pub fn check_variadic_arg_types(
+    fn_spec: &FunctionSpecification,
+    args: &Vec<Type>,
+) -> Result<Type, Diagnostic> {
+    check_args(fn_spec, args, true)
+}
+
+fn check_args(
+    fn_spec: &FunctionSpecification,
+    args: &Vec<Type>,
+    variadic: bool,
+) -> Result<Type, Diagnostic> {
+    if !variadic && args.len() > fn_spec.inputs.len() {
+        return Err(to_diag(
+            fn_spec,
+            format!("expected at most {} arguments, found {}", fn_spec.inputs.len(), args.len()),
+        ));
+    }
+    for (i, input) in fn_spec.inputs.iter().enumerate() {
+        if args.get(i).is_none() && !input.optional {
+            return Err(to_diag(
+                fn_spec,
+                format!("missing required argument #{} ({})", i + 1, input.name),
+            ));
+        }
+    }
+    for (i, arg) in args.iter().enumerate() {
+        let Some(input) = fn_spec.inputs.get(i).or(fn_spec.inputs.last()) else {
+            break;
+        };
+        if !input.typing.iter().any(|typing| type_matches(arg, typing)) {
+            let expected_types =
+                input.typing.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(",");
+            return Err(to_diag(
+                fn_spec,
+                format!(
+                    "argument #{} ({}) should be of type ({}), found {}",
+                    i + 1,
+                    input.name,
+                    expected_types,
+                    arg.to_string()
+                ),
+            ));
+        }
+    }
+    Ok(fn_spec.output.typing.clone())
+}
+
+fn type_matches(arg: &Type, expected: &Type) -> bool {
+    match (arg, expected) {
+        (Type::Addon(_), Type::Addon(_)) => true,
+        (Type::Object(_), Type::Object(_)) => true,
+        (Type::Map(_), Type::Map(_)) => true,
+        // we don't have an "any" type: arrays of type null (such as empty arrays) match any array,
+        // and any array matches the arrays of type null
+        (Type::Array(arg), Type::Array(_)) if **arg == Type::Null => true,
+        (Type::Array(_), Type::Array(expected)) if **expected == Type::Null => true,
+        (Type::Array(arg), Type::Array(expected)) => type_matches(arg, expected),
+        _ => arg.eq(expected),
+    }
+}
+
+/// Renders a value as a string: strings are not quoted, and lists and objects are rendered as JSON.
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Array(_) | Value::Object(_) => value.to_json().to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// The types accepted by the functions taking any value as input.
+pub fn any_type() -> Vec<Type> {
+    vec![
+        Type::null(),
+        Type::bool(),
+        Type::integer(),
+        Type::float(),
+        Type::string(),
+        Type::buffer(),
+        Type::addon(""),
+        Type::array(Type::null()),
+        Type::arbitrary_object(),
+    ]
+}
+
 lazy_static! {
     pub static ref FUNCTIONS: Vec<FunctionSpecification> = {
         let mut functions = vec![];
@@ -35,6 +136,46 @@ lazy_static! {
         functions.extend(hash::FUNCTIONS.clone());
         functions.extend(hex::FUNCTIONS.clone());
         functions.extend(base58::FUNCTIONS.clone());
+        functions.extend(strings::STRING_FUNCTIONS.clone());
+        functions.extend(conversion::CONVERSION_FUNCTIONS.clone());
+        functions.extend(math::MATH_FUNCTIONS.clone());
+        functions.extend(object::OBJECT_FUNCTIONS.clone());
+        functions.extend(time::TIME_FUNCTIONS.clone());
         functions
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use txtx_addon_kit::types::{types::Value, AuthorizationContext};
+
+    use super::FUNCTIONS;
+
+    fn run_function(name: &str, args: Vec<Value>) -> Result<Value, String> {
+        let fn_spec = FUNCTIONS.iter().find(|f| f.name == name).unwrap();
+        (fn_spec.runner)(fn_spec, &AuthorizationContext::empty(), &args).map_err(|e| e.message)
+    }
+
+    #[test_case("format", vec![Value::string("{} + {} = {{sum}}".into()), Value::integer(1), Value::integer(2)], Value::string("1 + 2 = {sum}".into()); "format")]
+    #[test_case("join", vec![Value::array(vec![Value::string("a".into()), Value::integer(1)]), Value::string("-".into())], Value::string("a-1".into()); "join")]
+    #[test_case("to_int", vec![Value::string("0x2a".into())], Value::integer(42); "to_int hex")]
+    #[test_case("to_int", vec![Value::float(-3.9)], Value::integer(-3); "to_int float")]
+    #[test_case("min", vec![Value::integer(3), Value::integer(-1), Value::integer(2)], Value::integer(-1); "min")]
+    #[test_case("max", vec![Value::array(vec![Value::integer(3), Value::float(4.5)])], Value::float(4.5); "max list")]
+    #[test_case("sum", vec![Value::integer(1), Value::integer(2), Value::integer(3)], Value::integer(6); "sum")]
+    #[test_case("range", vec![Value::integer(5), Value::integer(0), Value::integer(-2)], Value::array(vec![Value::integer(5), Value::integer(3), Value::integer(1)]); "range negative step")]
+    #[test_case("parse_units", vec![Value::string("1.5".into()), Value::integer(18)], Value::integer(1_500_000_000_000_000_000); "parse_units")]
+    #[test_case("format_units", vec![Value::integer(-1_500_000), Value::integer(6)], Value::string("-1.5".into()); "format_units")]
+    #[test_case("timestamp", vec![Value::integer(0)], Value::string("1970-01-01T00:00:00+00:00".into()); "timestamp")]
+    fn it_runs_std_functions(name: &str, args: Vec<Value>, expected: Value) {
+        assert_eq!(run_function(name, args).unwrap(), expected);
+    }
+
+    #[test_case("format", vec![Value::string("{} {}".into()), Value::integer(1)]; "format missing value")]
+    #[test_case("range", vec![Value::integer(0), Value::integer(1), Value::integer(0)]; "range zero step")]
+    #[test_case("parse_units", vec![Value::string("1.123".into()), Value::integer(2)]; "parse_units too many decimals")]
+    fn it_rejects_invalid_std_function_calls(name: &str, args: Vec<Value>) {
+        assert!(run_function(name, args).is_err());
+    }
+}
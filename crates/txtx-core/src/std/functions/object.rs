@@ -0,0 +1,162 @@
+use txtx_addon_kit::indexmap::IndexMap;
+use txtx_addon_kit::types::AuthorizationContext;
+use txtx_addon_kit::{
+    define_function, indoc,
+    types::{
+        diagnostics::Diagnostic,
+        functions::{FunctionImplementation, FunctionSpecification},
+        types::{Type, Value},
+    },
+};
+
+use super::{arg_checker, check_arg_types, check_variadic_arg_types, to_diag};
+
+lazy_static! {
+    pub static ref OBJECT_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_function! {
+            Keys => {
+                name: "keys",
+                documentation: "`keys` returns the list of the keys of an object, in their definition order.",
+                example: indoc!{r#"
+                output "names" {
+                    value = keys({ "alice": 1, "bob": 2 })
+                }
+                // > names: ["alice", "bob"]
+                "#},
+                inputs: [
+                    object: {
+                        documentation: "The object to get the keys from.",
+                        typing: vec![Type::arbitrary_object()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The keys of the object.",
+                    typing: Type::array(Type::string())
+                },
+            }
+        },
+        define_function! {
+            Values => {
+                name: "values",
+                documentation: "`values` returns the list of the values of an object, in the definition order of their keys.",
+                example: indoc!{r#"
+                output "scores" {
+                    value = values({ "alice": 1, "bob": 2 })
+                }
+                // > scores: [1, 2]
+                "#},
+                inputs: [
+                    object: {
+                        documentation: "The object to get the values from.",
+                        typing: vec![Type::arbitrary_object()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The values of the object.",
+                    typing: Type::array(Type::null())
+                },
+            }
+        },
+        define_function! {
+            Merge => {
+                name: "merge",
+                documentation: "`merge` combines several objects into a single one. When a key is defined in several objects, the value of the last object is kept.",
+                example: indoc!{r#"
+                output "settings" {
+                    value = merge({ "network": "devnet", "retries": 1 }, { "retries": 3 })
+                }
+                // > settings: { "network": "devnet", "retries": 3 }
+                "#},
+                inputs: [
+                    objects: {
+                        documentation: "The objects to merge, provided as separate arguments.",
+                        typing: vec![Type::arbitrary_object()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The merged object.",
+                    typing: Type::arbitrary_object()
+                },
+            }
+        }
+    ];
+}
+
+pub struct Keys;
+impl FunctionImplementation for Keys {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let object = args.get(0).unwrap().expect_object();
+        Ok(Value::array(object.keys().map(|key| Value::string(key.clone())).collect()))
+    }
+}
+
+pub struct Values;
+impl FunctionImplementation for Values {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let object = args.get(0).unwrap().expect_object();
+        Ok(Value::array(object.values().cloned().collect()))
+    }
+}
+
+pub struct Merge;
+impl FunctionImplementation for Merge {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_variadic_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let mut merged = IndexMap::new();
+        for (i, arg) in args.iter().enumerate() {
+            let Value::Object(object) = arg else {
+                return Err(to_diag(
+                    fn_spec,
+                    format!(
+                        "argument #{} should be of type (object), found {}",
+                        i + 1,
+                        arg.get_type().to_string()
+                    ),
+                ));
+            };
+            merged.extend(object.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Ok(Value::object(merged))
+    }
+}
@@ -0,0 +1,345 @@
+use txtx_addon_kit::types::AuthorizationContext;
+use txtx_addon_kit::{
+    define_function, indoc,
+    types::{
+        diagnostics::Diagnostic,
+        functions::{FunctionImplementation, FunctionSpecification},
+        types::{Type, Value},
+    },
+};
+
+use super::{
+    any_type, arg_checker, check_arg_types, check_variadic_arg_types, to_diag, value_to_string,
+};
+
+lazy_static! {
+    pub static ref STRING_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_function! {
+            Format => {
+                name: "format",
+                documentation: indoc!{r#"
+                `format` builds a string by replacing each `{}` placeholder of a template with the next argument.
+                Literal braces can be written by doubling them (`{{` and `}}`).
+                "#},
+                example: indoc!{r#"
+                output "greeting" {
+                    value = format("Hello, {}! You have {} new messages.", "Alice", 3)
+                }
+                // > greeting: Hello, Alice! You have 3 new messages.
+                "#},
+                inputs: [
+                    template: {
+                        documentation: "The template string, containing one `{}` placeholder per value.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    },
+                    values: {
+                        documentation: "The values to interpolate in the template, provided as additional arguments.",
+                        typing: any_type()
+                    }
+                ],
+                output: {
+                    documentation: "The formatted string.",
+                    typing: Type::string()
+                },
+            }
+        },
+        define_function! {
+            Join => {
+                name: "join",
+                documentation: "`join` concatenates the elements of a list into a string, inserting a separator between them.",
+                example: indoc!{r#"
+                output "joined" {
+                    value = join(["a", "b", "c"], ", ")
+                }
+                // > joined: a, b, c
+                "#},
+                inputs: [
+                    list: {
+                        documentation: "The list of values to join.",
+                        typing: vec![Type::array(Type::null())],
+                        optional: false
+                    },
+                    separator: {
+                        documentation: "The separator to insert between the elements.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The joined string.",
+                    typing: Type::string()
+                },
+            }
+        },
+        define_function! {
+            Split => {
+                name: "split",
+                documentation: "`split` divides a string into a list of substrings, using a separator.",
+                example: indoc!{r#"
+                output "parts" {
+                    value = split("a,b,c", ",")
+                }
+                // > parts: ["a", "b", "c"]
+                "#},
+                inputs: [
+                    value: {
+                        documentation: "The string to split.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    },
+                    separator: {
+                        documentation: "The separator to split the string on.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The list of substrings.",
+                    typing: Type::array(Type::string())
+                },
+            }
+        },
+        define_function! {
+            Replace => {
+                name: "replace",
+                documentation: "`replace` replaces all the occurrences of a substring in a string.",
+                example: indoc!{r#"
+                output "replaced" {
+                    value = replace("hello world", "world", "txtx")
+                }
+                // > replaced: hello txtx
+                "#},
+                inputs: [
+                    value: {
+                        documentation: "The string to search in.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    },
+                    pattern: {
+                        documentation: "The substring to replace.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    },
+                    replacement: {
+                        documentation: "The string to replace the substring with.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The string, with all the occurrences of the substring replaced.",
+                    typing: Type::string()
+                },
+            }
+        },
+        define_function! {
+            Lower => {
+                name: "lower",
+                documentation: "`lower` converts all the characters of a string to lowercase.",
+                example: indoc!{r#"
+                output "lowercase" {
+                    value = lower("Hello World")
+                }
+                // > lowercase: hello world
+                "#},
+                inputs: [
+                    value: {
+                        documentation: "The string to convert.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The lowercase string.",
+                    typing: Type::string()
+                },
+            }
+        },
+        define_function! {
+            Upper => {
+                name: "upper",
+                documentation: "`upper` converts all the characters of a string to uppercase.",
+                example: indoc!{r#"
+                output "uppercase" {
+                    value = upper("Hello World")
+                }
+                // > uppercase: HELLO WORLD
+                "#},
+                inputs: [
+                    value: {
+                        documentation: "The string to convert.",
+                        typing: vec![Type::string()],
+                        optional: false
+                    }
+                ],
+                output: {
+                    documentation: "The uppercase string.",
+                    typing: Type::string()
+                },
+            }
+        }
+    ];
+}
+
+pub struct Format;
+impl FunctionImplementation for Format {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_variadic_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let template = args.get(0).unwrap().expect_string();
+        let mut values = args.iter().skip(1);
+
+        let mut result = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    chars.next();
+                    result.push(c);
+                }
+                ('{', Some('}')) => {
+                    chars.next();
+                    let Some(value) = values.next() else {
+                        return Err(to_diag(
+                            fn_spec,
+                            "the template has more placeholders than values".into(),
+                        ));
+                    };
+                    result.push_str(&value_to_string(value));
+                }
+                ('{', _) | ('}', _) => {
+                    return Err(to_diag(
+                        fn_spec,
+                        format!("invalid template '{}': unmatched brace", template),
+                    ));
+                }
+                _ => result.push(c),
+            }
+        }
+        if values.next().is_some() {
+            return Err(to_diag(fn_spec, "the template has more values than placeholders".into()));
+        }
+        Ok(Value::string(result))
+    }
+}
+
+pub struct Join;
+impl FunctionImplementation for Join {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let list = args.get(0).unwrap().expect_array();
+        let separator = args.get(1).unwrap().expect_string();
+        let joined = list.iter().map(value_to_string).collect::<Vec<_>>().join(separator);
+        Ok(Value::string(joined))
+    }
+}
+
+pub struct Split;
+impl FunctionImplementation for Split {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let value = args.get(0).unwrap().expect_string();
+        let separator = args.get(1).unwrap().expect_string();
+        let parts = value.split(separator).map(|part| Value::string(part.to_string())).collect();
+        Ok(Value::array(parts))
+    }
+}
+
+pub struct Replace;
+impl FunctionImplementation for Replace {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let value = args.get(0).unwrap().expect_string();
+        let pattern = args.get(1).unwrap().expect_string();
+        let replacement = args.get(2).unwrap().expect_string();
+        Ok(Value::string(value.replace(pattern, replacement)))
+    }
+}
+
+pub struct Lower;
+impl FunctionImplementation for Lower {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        Ok(Value::string(args.get(0).unwrap().expect_string().to_lowercase()))
+    }
+}
+
+pub struct Upper;
+impl FunctionImplementation for Upper {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        Ok(Value::string(args.get(0).unwrap().expect_string().to_uppercase()))
+    }
+}
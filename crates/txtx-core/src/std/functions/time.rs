@@ -0,0 +1,111 @@
+use txtx_addon_kit::types::AuthorizationContext;
+use txtx_addon_kit::{
+    define_function, indoc,
+    types::{
+        diagnostics::Diagnostic,
+        functions::{FunctionImplementation, FunctionSpecification},
+        types::{Type, Value},
+    },
+};
+
+use super::{arg_checker, check_arg_types, to_diag};
+
+lazy_static! {
+    pub static ref TIME_FUNCTIONS: Vec<FunctionSpecification> = vec![
+        define_function! {
+            Now => {
+                name: "now",
+                documentation: "`now` returns the current time, as a number of seconds since the Unix epoch.",
+                example: indoc!{r#"
+                output "current_time" {
+                    value = now()
+                }
+                // > current_time: 1729000000
+                "#},
+                inputs: [],
+                output: {
+                    documentation: "The number of seconds elapsed since the Unix epoch.",
+                    typing: Type::integer()
+                },
+            }
+        },
+        define_function! {
+            Timestamp => {
+                name: "timestamp",
+                documentation: "`timestamp` formats a Unix time as an RFC 3339 date, or the current time if no argument is provided.",
+                example: indoc!{r#"
+                output "date" {
+                    value = timestamp(1729000000)
+                }
+                // > date: 2024-10-15T13:46:40+00:00
+                "#},
+                inputs: [
+                    seconds: {
+                        documentation: "The number of seconds since the Unix epoch. Defaults to the current time.",
+                        typing: vec![Type::integer()],
+                        optional: true
+                    }
+                ],
+                output: {
+                    documentation: "The RFC 3339 formatted date.",
+                    typing: Type::string()
+                },
+            }
+        }
+    ];
+}
+
+pub struct Now;
+impl FunctionImplementation for Now {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        _fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        _args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        Ok(Value::integer(chrono::Utc::now().timestamp() as i128))
+    }
+}
+
+pub struct Timestamp;
+impl FunctionImplementation for Timestamp {
+    fn check_instantiability(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Type>,
+    ) -> Result<Type, Diagnostic> {
+        check_arg_types(fn_spec, args)
+    }
+
+    fn run(
+        fn_spec: &FunctionSpecification,
+        _auth_ctx: &AuthorizationContext,
+        args: &Vec<Value>,
+    ) -> Result<Value, Diagnostic> {
+        arg_checker(fn_spec, args)?;
+        let datetime = match args.get(0) {
+            None | Some(Value::Null) => chrono::Utc::now(),
+            Some(Value::Integer(seconds)) => i64::try_from(*seconds)
+                .ok()
+                .and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0))
+                .ok_or_else(|| to_diag(fn_spec, format!("invalid timestamp {}", seconds)))?,
+            Some(other) => {
+                return Err(to_diag(
+                    fn_spec,
+                    format!(
+                        "argument #1 should be of type (integer), found {}",
+                        other.get_type().to_string()
+                    ),
+                ))
+            }
+        };
+        Ok(Value::string(datetime.to_rfc3339()))
+    }
+}
@@ -1,10 +1,11 @@
 use crate::lsp_types::MessageType;
-use crate::state::{build_state, EditorState, WorkspaceState};
+use crate::state::{build_state, EditorState};
 use crate::utils::get_runbook_location;
 use lsp_types::{
     CompletionItem, CompletionParams, DocumentSymbol, DocumentSymbolParams, GotoDefinitionParams,
-    Hover, HoverParams, InitializeParams, InitializeResult, Location, SignatureHelp,
-    SignatureHelpParams,
+    Hover, HoverParams, InitializeParams, InitializeResult, Location, PrepareRenameResponse,
+    ReferenceParams, RenameParams, SignatureHelp, SignatureHelpParams, TextDocumentPositionParams,
+    WorkspaceEdit,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
@@ -85,7 +86,7 @@ pub async fn process_notification(
             }
 
             // With this manifest_location, let's initialize our state.
-            let mut protocol_state = WorkspaceState::new();
+            let mut protocol_state = editor_state.try_read(|es| es.new_workspace_state())?;
             match build_state(&manifest_location, &mut protocol_state, file_accessor).await {
                 Ok(_) => {
                     editor_state
@@ -99,7 +100,7 @@ pub async fn process_notification(
         }
         LspNotification::ManifestSaved(manifest_location) => {
             // We will rebuild the entire state, without to try any optimizations for now
-            let mut workspace_state = WorkspaceState::new();
+            let mut workspace_state = editor_state.try_read(|es| es.new_workspace_state())?;
             match build_state(&manifest_location, &mut workspace_state, file_accessor).await {
                 Ok(_) => {
                     editor_state
@@ -161,7 +162,7 @@ pub async fn process_notification(
                 return Ok(LspNotificationResponse::default());
             }
 
-            let mut protocol_state = WorkspaceState::new();
+            let mut protocol_state = editor_state.try_read(|es| es.new_workspace_state())?;
             match build_state(&manifest_location, &mut protocol_state, file_accessor).await {
                 Ok(_) => {
                     editor_state
@@ -182,7 +183,7 @@ pub async fn process_notification(
             };
 
             // TODO(): introduce partial analysis #604
-            let mut workspace_state = WorkspaceState::new();
+            let mut workspace_state = editor_state.try_read(|es| es.new_workspace_state())?;
             match build_state(&manifest_location, &mut workspace_state, file_accessor).await {
                 Ok(_) => {
                     editor_state.try_write(|es| {
@@ -200,11 +201,32 @@ pub async fn process_notification(
             }
         }
         LspNotification::RunbookChanged(runbook_location, contract_source) => {
-            match editor_state.try_write(|es| {
+            if let Err(err) = editor_state.try_write(|es| {
                 es.update_active_contract(&runbook_location, &contract_source, false)
             })? {
-                Ok(_result) => Ok(LspNotificationResponse::default()),
-                Err(err) => Ok(LspNotificationResponse::error(&err)),
+                return Ok(LspNotificationResponse::error(&err));
+            }
+
+            let manifest_location = match editor_state.try_read(|es| {
+                es.runbooks_lookup.get(&runbook_location).map(|m| m.manifest_location.clone())
+            })? {
+                Some(manifest_location) => manifest_location,
+                None => runbook_location.get_workspace_manifest_location(file_accessor).await?,
+            };
+
+            // the runbook is checked with its unsaved changes on every edit
+            let mut workspace_state = editor_state.try_read(|es| es.new_workspace_state())?;
+            match build_state(&manifest_location, &mut workspace_state, file_accessor).await {
+                Ok(_) => {
+                    editor_state.try_write(|es| {
+                        es.clear_workspace(&manifest_location);
+                        es.index_workspace(manifest_location, workspace_state);
+                    })?;
+                    let (aggregated_diagnostics, notification) =
+                        editor_state.try_read(|es| es.get_aggregated_diagnostics())?;
+                    Ok(LspNotificationResponse { aggregated_diagnostics, notification })
+                }
+                Err(e) => Ok(LspNotificationResponse::error(&e)),
             }
         }
         LspNotification::RunbookClosed(runbook_location) => {
@@ -219,6 +241,9 @@ pub enum LspRequest {
     Completion(CompletionParams),
     SignatureHelp(SignatureHelpParams),
     Definition(GotoDefinitionParams),
+    References(ReferenceParams),
+    PrepareRename(TextDocumentPositionParams),
+    Rename(RenameParams),
     Hover(HoverParams),
     DocumentSymbol(DocumentSymbolParams),
     Initialize(InitializeParams),
@@ -229,6 +254,10 @@ pub enum LspRequestResponse {
     CompletionItems(Vec<CompletionItem>),
     SignatureHelp(Option<SignatureHelp>),
     Definition(Option<Location>),
+    References(Vec<Location>),
+    PrepareRename(Option<PrepareRenameResponse>),
+    /// Rejected renames (e.g. invalid or conflicting names) are reported to the user
+    Rename(Result<Option<WorkspaceEdit>, String>),
     DocumentSymbol(Vec<DocumentSymbol>),
     Hover(Option<Hover>),
     Initialize(InitializeResult),
@@ -271,6 +300,44 @@ pub fn process_request(
             Ok(LspRequestResponse::Definition(location))
         }
 
+        LspRequest::References(params) => {
+            let file_url = params.text_document_position.text_document.uri;
+            let runbook_location = match get_runbook_location(&file_url) {
+                Some(runbook_location) => runbook_location,
+                None => return Ok(LspRequestResponse::References(vec![])),
+            };
+            let position = params.text_document_position.position;
+            let include_declaration = params.context.include_declaration;
+            let locations = editor_state
+                .try_read(|es| es.get_references(&runbook_location, &position, include_declaration))
+                .unwrap_or_default();
+            Ok(LspRequestResponse::References(locations))
+        }
+
+        LspRequest::PrepareRename(params) => {
+            let runbook_location = match get_runbook_location(&params.text_document.uri) {
+                Some(runbook_location) => runbook_location,
+                None => return Ok(LspRequestResponse::PrepareRename(None)),
+            };
+            let response = editor_state
+                .try_read(|es| es.prepare_rename(&runbook_location, &params.position))
+                .unwrap_or_default();
+            Ok(LspRequestResponse::PrepareRename(response))
+        }
+
+        LspRequest::Rename(params) => {
+            let file_url = params.text_document_position.text_document.uri;
+            let runbook_location = match get_runbook_location(&file_url) {
+                Some(runbook_location) => runbook_location,
+                None => return Ok(LspRequestResponse::Rename(Ok(None))),
+            };
+            let position = params.text_document_position.position;
+            let edits = editor_state
+                .try_read(|es| es.get_rename_edits(&runbook_location, &position, &params.new_name))
+                .unwrap_or(Ok(None));
+            Ok(LspRequestResponse::Rename(edits))
+        }
+
         LspRequest::SignatureHelp(params) => {
             let file_url = params.text_document_position_params.text_document.uri;
             let runbook_location = match get_runbook_location(&file_url) {
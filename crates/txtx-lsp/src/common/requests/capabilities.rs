@@ -1,7 +1,7 @@
 use lsp_types::{
-    CompletionOptions, HoverProviderCapability, ServerCapabilities, SignatureHelpOptions,
-    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
-    TextDocumentSyncSaveOptions,
+    CompletionOptions, HoverProviderCapability, RenameOptions, ServerCapabilities,
+    SignatureHelpOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, TextDocumentSyncSaveOptions,
 };
 use serde::{Deserialize, Serialize};
 
@@ -13,8 +13,12 @@ pub struct InitializationOptions {
     pub completion_include_native_placeholders: bool,
     document_symbols: bool,
     go_to_definition: bool,
+    references: bool,
+    rename: bool,
     hover: bool,
     signature_help: bool,
+    /// Environment of the workspace manifest the runbooks are checked against
+    pub environment: Option<String>,
 }
 
 impl InitializationOptions {
@@ -25,8 +29,11 @@ impl InitializationOptions {
             completion_include_native_placeholders: true,
             document_symbols: false,
             go_to_definition: true,
+            references: true,
+            rename: true,
             hover: true,
             signature_help: true,
+            environment: None,
         }
     }
 }
@@ -56,6 +63,17 @@ pub fn get_capabilities(initialization_options: &InitializationOptions) -> Serve
             true => Some(lsp_types::OneOf::Left(true)),
             false => None,
         },
+        references_provider: match initialization_options.references {
+            true => Some(lsp_types::OneOf::Left(true)),
+            false => None,
+        },
+        rename_provider: match initialization_options.rename {
+            true => Some(lsp_types::OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: Default::default(),
+            })),
+            false => None,
+        },
         signature_help_provider: match initialization_options.signature_help {
             true => Some(SignatureHelpOptions {
                 trigger_characters: Some(vec![" ".to_string()]),
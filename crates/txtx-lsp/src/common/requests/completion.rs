@@ -1,935 +1,473 @@
-use std::{collections::HashMap, vec};
+use std::collections::{BTreeSet, HashMap};
 
 use lazy_static::lazy_static;
 use lsp_types::{
-    CompletionItem, CompletionItemKind, Documentation, InsertTextFormat, MarkupContent, MarkupKind,
-    Position,
+    Command, CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionTextEdit,
+    Documentation, Hover, HoverContents, MarkupContent, MarkupKind, Position, Range, TextEdit,
 };
 use regex::Regex;
+use txtx_addon_kit::types::commands::{CommandInput, CommandOutput, PreCommandSpecification};
+use txtx_addon_kit::Addon;
 
+use super::helpers::position_to_offset;
 
 lazy_static! {
-    static ref COMPLETION_ITEMS_CLARITY_1: Vec<CompletionItem> =
-        build_default_native_keywords_list(ClarityVersion::Clarity1);
-    static ref COMPLETION_ITEMS_CLARITY_2: Vec<CompletionItem> =
-        build_default_native_keywords_list(ClarityVersion::Clarity2);
-    static ref VAR_FUNCTIONS: Vec<String> = vec![
-        NativeFunctions::SetVar.to_string(),
-        NativeFunctions::FetchVar.to_string(),
-    ];
-    static ref MAP_FUNCTIONS: Vec<String> = vec![
-        NativeFunctions::InsertEntry.to_string(),
-        NativeFunctions::FetchEntry.to_string(),
-        NativeFunctions::SetEntry.to_string(),
-        NativeFunctions::DeleteEntry.to_string(),
-    ];
-    static ref FT_FUNCTIONS: Vec<String> = vec![
-        NativeFunctions::GetTokenBalance.to_string(),
-        NativeFunctions::GetTokenSupply.to_string(),
-        NativeFunctions::BurnToken.to_string(),
-        NativeFunctions::MintToken.to_string(),
-        NativeFunctions::TransferToken.to_string(),
-    ];
-    static ref NFT_FUNCTIONS: Vec<String> = vec![
-        NativeFunctions::GetAssetOwner.to_string(),
-        NativeFunctions::BurnAsset.to_string(),
-        NativeFunctions::MintAsset.to_string(),
-        NativeFunctions::TransferAsset.to_string(),
-    ];
-    static ref ITERATOR_FUNCTIONS: Vec<String> = vec![
-        NativeFunctions::Map.to_string(),
-        NativeFunctions::Filter.to_string(),
-        NativeFunctions::Fold.to_string(),
-    ];
-    static ref VALID_MAP_FUNCTIONS_CLARITY_1: Vec<CompletionItem> =
-        build_map_valid_cb_completion_items(ClarityVersion::Clarity1);
-    static ref VALID_MAP_FUNCTIONS_CLARITY_2: Vec<CompletionItem> =
-        build_map_valid_cb_completion_items(ClarityVersion::Clarity2);
-    static ref VALID_FILTER_FUNCTIONS_CLARITY_1: Vec<CompletionItem> =
-        build_filter_valid_cb_completion_items(ClarityVersion::Clarity1);
-    static ref VALID_FILTER_FUNCTIONS_CLARITY_2: Vec<CompletionItem> =
-        build_filter_valid_cb_completion_items(ClarityVersion::Clarity2);
-    static ref VALID_FOLD_FUNCTIONS_CLARITY_1: Vec<CompletionItem> =
-        build_fold_valid_cb_completion_items(ClarityVersion::Clarity1);
-    static ref VALID_FOLD_FUNCTIONS_CLARITY_2: Vec<CompletionItem> =
-        build_fold_valid_cb_completion_items(ClarityVersion::Clarity2);
+    static ref COMMAND_HEADER: Regex =
+        Regex::new(r#"^\s*(action|signer)\s+"([^"]*)"\s+"([^"]*)"\s*$"#).unwrap();
+    static ref PARTIAL_COMMAND_ID: Regex =
+        Regex::new(r#"^\s*(action|signer)\s+"[^"]*"\s+"([^"]*)$"#).unwrap();
+    static ref PARTIAL_ATTRIBUTE: Regex = Regex::new(r"^\s*([A-Za-z_][A-Za-z0-9_]*)?$").unwrap();
+    static ref ATTRIBUTE: Regex = Regex::new(r"^(\s*)([A-Za-z_][A-Za-z0-9_]*)\s*=").unwrap();
+    static ref PARTIAL_OUTPUT: Regex =
+        Regex::new(r"action\.([A-Za-z0-9_-]+)\.([A-Za-z0-9_]*)$").unwrap();
 }
 
-#[derive(Clone, Debug, Default)]
-pub struct ContractDefinedData {
-    position: Position,
-    consts: Vec<(String, String)>,
-    locals: Vec<(String, String)>,
-    pub vars: Vec<String>,
-    pub maps: Vec<String>,
-    pub fts: Vec<String>,
-    pub nfts: Vec<String>,
-    pub functions_completion_items: Vec<CompletionItem>,
+/// Specification of an addon command (action or signer), as documented in the editor.
+#[derive(Clone, Debug)]
+pub struct CommandDocumentation {
+    /// `action` or `signer`
+    pub construct_type: String,
+    pub namespace: String,
+    pub matcher: String,
+    pub documentation: String,
+    pub inputs: Vec<CommandInput>,
+    pub outputs: Vec<CommandOutput>,
 }
 
-impl<'a> ContractDefinedData {
-    pub fn new(expressions: &[SymbolicExpression], position: &Position) -> Self {
-        let mut defined_data = ContractDefinedData {
-            position: *position,
-            ..Default::default()
-        };
-        traverse(&mut defined_data, expressions);
-        defined_data
-    }
-
-    // this methods is in charge of:
-    // 1. set the function completion item with its arguments
-    // 2. set the local binding names if the position is within this function
-    fn set_function_completion_with_bindings(
-        &mut self,
-        expr: &SymbolicExpression,
-        name: &ClarityName,
-        parameters: &[TypedVar<'a>],
-    ) {
-        let mut completion_args: Vec<String> = vec![];
-        for (i, typed_var) in parameters.iter().enumerate() {
-            if let Ok(signature) =
-                TypeSignature::parse_type_repr(DEFAULT_EPOCH, typed_var.type_expr, &mut ())
-            {
-                completion_args.push(format!("${{{}:{}:{}}}", i + 1, typed_var.name, signature));
-
-                if is_position_within_span(&self.position, &expr.span, 0) {
-                    self.locals
-                        .push((typed_var.name.to_string(), signature.to_string()));
-                }
-            };
-        }
-
-        let insert_text = match completion_args.len() {
-            0 => Some(name.to_string()),
-            _ => Some(format!("{} {}", name, completion_args.join(" "))),
-        };
-
-        self.functions_completion_items.push(CompletionItem {
-            label: name.to_string(),
-            kind: Some(CompletionItemKind::MODULE),
-            insert_text,
-            insert_text_format: Some(InsertTextFormat::SNIPPET),
-            ..Default::default()
-        });
-    }
-
-    pub fn populate_snippet_with_options(
-        &self,
-        version: &ClarityVersion,
-        name: &String,
-        snippet: &str,
-    ) -> Option<String> {
-        if VAR_FUNCTIONS.contains(name) && !self.vars.is_empty() {
-            let choices = self.vars.join(",");
-            return Some(snippet.replace("${1:var}", &format!("${{1|{}|}}", choices)));
-        }
-        if MAP_FUNCTIONS.contains(name) && !self.maps.is_empty() {
-            let choices = self.maps.join(",");
-            return Some(snippet.replace("${1:map-name}", &format!("${{1|{}|}}", choices)));
-        }
-        if FT_FUNCTIONS.contains(name) && !self.fts.is_empty() {
-            let choices = self.fts.join(",");
-            return Some(snippet.replace("${1:token-name}", &format!("${{1|{}|}}", choices)));
-        }
-        if NFT_FUNCTIONS.contains(name) && !self.nfts.is_empty() {
-            let choices = self.nfts.join(",");
-            return Some(snippet.replace("${1:asset-name}", &format!("${{1|{}|}}", choices)));
-        }
-        if ITERATOR_FUNCTIONS.contains(name) && !self.functions_completion_items.is_empty() {
-            let mut choices = self
-                .functions_completion_items
-                .iter()
-                .map(|f| f.label.to_string())
-                .collect::<Vec<String>>()
-                .join(",");
-            choices.push(',');
-            choices.push_str(
-                &get_iterator_cb_completion_item(version, name)
-                    .iter()
-                    .map(|i| i.insert_text.clone().unwrap())
-                    .collect::<Vec<String>>()
-                    .join(","),
-            );
-            return Some(snippet.replace("${1:func}", &format!("${{1|{}|}}", choices)));
+impl CommandDocumentation {
+    pub fn from_addons(addons: &[Box<dyn Addon>]) -> Vec<CommandDocumentation> {
+        let mut commands = vec![];
+        for addon in addons.iter() {
+            let namespace = addon.get_namespace().to_string();
+            for action in addon.get_actions() {
+                // composite actions are routed to atomic actions, and have no inputs of their own
+                let PreCommandSpecification::Atomic(spec) = action else {
+                    continue;
+                };
+                commands.push(CommandDocumentation {
+                    construct_type: "action".into(),
+                    namespace: namespace.clone(),
+                    matcher: spec.matcher,
+                    documentation: spec.documentation,
+                    inputs: spec.inputs.into_iter().chain(spec.default_inputs).collect(),
+                    outputs: spec.outputs,
+                });
+            }
+            for spec in addon.get_signers() {
+                commands.push(CommandDocumentation {
+                    construct_type: "signer".into(),
+                    namespace: namespace.clone(),
+                    matcher: spec.matcher,
+                    documentation: spec.documentation,
+                    inputs: spec.inputs.into_iter().chain(spec.default_inputs).collect(),
+                    outputs: spec.outputs,
+                });
+            }
         }
-        None
+        commands
     }
 
-    pub fn get_contract_completion_items(&self) -> Vec<CompletionItem> {
-        [&self.consts[..], &self.locals[..]]
-            .concat()
-            .iter()
-            .map(|(name, definition)| {
-                CompletionItem::new_simple(name.to_string(), definition.to_string())
-            })
-            .collect()
+    /// Id of the command, as used in runbooks (e.g. `evm::deploy_contract`)
+    pub fn get_id(&self) -> String {
+        format!("{}::{}", self.namespace, self.matcher)
     }
-}
 
-impl<'a> ASTVisitor<'a> for ContractDefinedData {
-    fn visit_define_constant(
-        &mut self,
-        _expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        value: &'a SymbolicExpression,
-    ) -> bool {
-        self.consts.push((name.to_string(), value.to_string()));
-        true
+    fn find<'a>(
+        commands: &'a [CommandDocumentation],
+        construct_type: &str,
+        command_id: &str,
+    ) -> Option<&'a CommandDocumentation> {
+        commands.iter().find(|c| c.construct_type.eq(construct_type) && c.get_id().eq(command_id))
     }
 
-    fn visit_define_data_var(
-        &mut self,
-        _expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _data_type: &'a SymbolicExpression,
-        _initial: &'a SymbolicExpression,
-    ) -> bool {
-        self.vars.push(name.to_string());
-        true
-    }
-
-    fn visit_define_map(
-        &mut self,
-        _expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _key_type: &'a SymbolicExpression,
-        _value_type: &'a SymbolicExpression,
-    ) -> bool {
-        self.maps.push(name.to_string());
-        true
-    }
-
-    fn visit_define_ft(
-        &mut self,
-        _expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _supply: Option<&'a SymbolicExpression>,
-    ) -> bool {
-        self.fts.push(name.to_string());
-        true
+    fn find_input(&self, name: &str) -> Option<&CommandInput> {
+        self.inputs.iter().find(|i| !i.internal && i.name.eq(name))
     }
+}
 
-    fn visit_define_nft(
-        &mut self,
-        _expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _nft_type: &'a SymbolicExpression,
-    ) -> bool {
-        self.nfts.push(name.to_string());
-        true
-    }
+/// Returns the command ids of the actions defined in `source`, indexed by action name.
+pub fn get_actions_command_ids(source: &str) -> HashMap<String, String> {
+    source
+        .lines()
+        .filter_map(|line| line.split_once('{').map(|(header, _)| header))
+        .filter_map(|header| COMMAND_HEADER.captures(header))
+        .filter(|captures| &captures[1] == "action")
+        .map(|captures| (captures[2].to_string(), captures[3].to_string()))
+        .collect()
+}
 
-    fn visit_define_public(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        parameters: Option<Vec<clarity_repl::analysis::ast_visitor::TypedVar<'a>>>,
-        _body: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_function_completion_with_bindings(expr, name, &parameters.unwrap_or_default());
-        true
+/// Returns the completion items relevant to the position of the cursor:
+/// - the namespaces and ids of the commands, in `action "name" "evm::`
+/// - the attributes of the enclosing action or signer block
+/// - the outputs of an action, after `action.name.`
+///
+/// `None` is returned when the cursor is not in any of these contexts.
+pub fn get_command_completion_items(
+    commands: &[CommandDocumentation],
+    actions_command_ids: &HashMap<String, String>,
+    source: &str,
+    position: &Position,
+) -> Option<Vec<CompletionItem>> {
+    let offset = position_to_offset(source, position);
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_prefix = &source[line_start..offset];
+
+    if let Some(captures) = PARTIAL_COMMAND_ID.captures(line_prefix) {
+        let construct_type = &captures[1];
+        let partial_id = &captures[2];
+        let range = Range::new(
+            Position::new(position.line, position.character - utf16_len(partial_id)),
+            *position,
+        );
+        return Some(get_command_id_completion_items(commands, construct_type, partial_id, range));
     }
 
-    fn visit_define_read_only(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        parameters: Option<Vec<clarity_repl::analysis::ast_visitor::TypedVar<'a>>>,
-        _body: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_function_completion_with_bindings(expr, name, &parameters.unwrap_or_default());
-        true
+    if let Some(captures) = PARTIAL_OUTPUT.captures(line_prefix) {
+        let command_id = actions_command_ids.get(&captures[1])?;
+        let command = CommandDocumentation::find(commands, "action", command_id)?;
+        let range = Range::new(
+            Position::new(position.line, position.character - utf16_len(&captures[2])),
+            *position,
+        );
+        let items = command
+            .outputs
+            .iter()
+            .map(|output| CompletionItem {
+                label: output.name.clone(),
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(output.typing.to_string()),
+                documentation: Some(markdown_documentation(output.documentation.clone())),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: output.name.clone(),
+                })),
+                ..Default::default()
+            })
+            .collect();
+        return Some(items);
     }
 
-    fn visit_define_private(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        parameters: Option<Vec<clarity_repl::analysis::ast_visitor::TypedVar<'a>>>,
-        _body: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_function_completion_with_bindings(expr, name, &parameters.unwrap_or_default());
-        true
+    if PARTIAL_ATTRIBUTE.is_match(line_prefix) {
+        let headers = get_enclosing_blocks_headers(&source[..line_start]);
+        // attributes are only completed at the root of the block
+        let [header] = &headers[..] else {
+            return None;
+        };
+        let captures = COMMAND_HEADER.captures(header)?;
+        let command = CommandDocumentation::find(commands, &captures[1], &captures[3])?;
+        let items = command
+            .inputs
+            .iter()
+            .filter(|input| !input.internal)
+            .map(|input| {
+                let requirement = if input.optional { "optional" } else { "required" };
+                CompletionItem {
+                    label: input.name.clone(),
+                    label_details: Some(CompletionItemLabelDetails {
+                        detail: None,
+                        description: Some(requirement.to_string()),
+                    }),
+                    kind: Some(CompletionItemKind::PROPERTY),
+                    detail: Some(format!("{} ({})", input.typing.to_string(), requirement)),
+                    documentation: Some(markdown_documentation(input.documentation.clone())),
+                    // required attributes are listed first
+                    sort_text: Some(format!(
+                        "{}{}",
+                        if input.optional { 1 } else { 0 },
+                        input.name
+                    )),
+                    insert_text: Some(format!("{} = ", input.name)),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        return Some(items);
     }
 
-    fn visit_let(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        bindings: &HashMap<&'a ClarityName, &'a SymbolicExpression>,
-        _body: &'a [SymbolicExpression],
-    ) -> bool {
-        if is_position_within_span(&self.position, &expr.span, 0) {
-            for (name, value) in bindings {
-                self.locals.push((name.to_string(), value.to_string()));
-            }
-        }
-        true
-    }
+    None
 }
 
-fn build_contract_calls_args(signature: &FunctionType) -> (Vec<String>, Vec<String>) {
-    let mut snippet_args = vec![];
-    let mut doc_args = vec![];
-    if let FunctionType::Fixed(function) = signature {
-        for (i, arg) in function.args.iter().enumerate() {
-            snippet_args.push(format!("${{{}:{}:{}}}", i + 1, arg.name, arg.signature));
-            doc_args.push(format!("{} `{}`", arg.name, arg.signature));
-        }
-    }
-    (snippet_args, doc_args)
-}
-
-pub fn get_contract_calls(analysis: &ContractAnalysis) -> Vec<CompletionItem> {
-    let mut inter_contract = vec![];
-    for (name, signature) in analysis
-        .public_function_types
-        .iter()
-        .chain(analysis.read_only_function_types.iter())
-    {
-        let (snippet_args, doc_args) = build_contract_calls_args(signature);
-        let label = format!(
-            "contract-call? .{} {}",
-            analysis.contract_identifier.name, name,
-        );
-        let documentation = MarkupContent {
-            kind: MarkupKind::Markdown,
-            value: [vec![format!("**{}**", name.to_string())], doc_args]
-                .concat()
-                .join("\n\n"),
-        };
-        let insert_text = format!(
-            "contract-call? .{} {} {}",
-            analysis.contract_identifier.name,
-            name,
-            snippet_args.join(" "),
-        );
+fn get_command_id_completion_items(
+    commands: &[CommandDocumentation],
+    construct_type: &str,
+    partial_id: &str,
+    range: Range,
+) -> Vec<CompletionItem> {
+    let commands = commands.iter().filter(|c| c.construct_type.eq(construct_type));
+
+    let Some((namespace, _)) = partial_id.split_once("::") else {
+        let namespaces = commands.map(|c| c.namespace.as_str()).collect::<BTreeSet<_>>();
+        return namespaces
+            .into_iter()
+            .map(|namespace| CompletionItem {
+                label: format!("{}::", namespace),
+                kind: Some(CompletionItemKind::MODULE),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: format!("{}::", namespace),
+                })),
+                // the ids of the namespace are suggested right away
+                command: Some(Command {
+                    title: "Suggest".into(),
+                    command: "editor.action.triggerSuggest".into(),
+                    arguments: None,
+                }),
+                ..Default::default()
+            })
+            .collect();
+    };
 
-        inter_contract.push(CompletionItem {
-            label,
-            detail: Some(analysis.contract_identifier.name.to_string()),
-            documentation: Some(Documentation::MarkupContent(documentation)),
-            kind: Some(CompletionItemKind::EVENT),
-            insert_text: Some(insert_text),
-            insert_text_format: Some(InsertTextFormat::SNIPPET),
+    commands
+        .filter(|c| c.namespace.eq(namespace))
+        .map(|command| CompletionItem {
+            label: command.matcher.clone(),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some(command.get_id()),
+            documentation: Some(markdown_documentation(command.documentation.clone())),
+            filter_text: Some(command.get_id()),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: command.get_id(),
+            })),
             ..Default::default()
-        });
-    }
-    inter_contract
+        })
+        .collect()
 }
 
-pub fn build_completion_item_list(
-    expressions: &Vec<SymbolicExpression>,
+/// Returns the documentation of the command id or of the command attribute at the position of the cursor.
+pub fn get_command_hover(
+    commands: &[CommandDocumentation],
+    source: &str,
     position: &Position,
-    active_contract_defined_data: &ContractDefinedData,
-    contract_calls: Vec<CompletionItem>,
-    should_wrap: bool,
-    include_native_placeholders: bool,
-) -> Vec<CompletionItem> {
-    if let Some((function_name, param)) = get_function_at_position(position, expressions) {
-        // - for var-*, map-*, ft-* or nft-* methods, return the corresponding data names
-        let mut completion_strings: Option<Vec<String>> = None;
-        if VAR_FUNCTIONS.contains(&function_name.to_string()) && param == Some(0) {
-            completion_strings = Some(active_contract_defined_data.vars.clone());
-        } else if MAP_FUNCTIONS.contains(&function_name.to_string()) && param == Some(0) {
-            completion_strings = Some(active_contract_defined_data.maps.clone());
-        } else if FT_FUNCTIONS.contains(&function_name.to_string()) && param == Some(0) {
-            completion_strings = Some(active_contract_defined_data.fts.clone());
-        } else if NFT_FUNCTIONS.contains(&function_name.to_string()) && param == Some(0) {
-            completion_strings = Some(active_contract_defined_data.nfts.clone());
-        }
-
-        if let Some(completion_strings) = completion_strings {
-            return completion_strings
-                .iter()
-                .map(|s| CompletionItem::new_simple(String::from(s), String::from("")))
-                .collect();
-        }
-
-        // - for iterator methods (filter, fold, map) return the list of available and valid functions
-        if ITERATOR_FUNCTIONS.contains(&function_name.to_string()) && param == Some(0) {
-            let mut completion_items: Vec<CompletionItem> = vec![];
-            completion_items.append(
-                &mut active_contract_defined_data
-                    .functions_completion_items
-                    .iter()
-                    .map(|f| CompletionItem::new_simple(f.label.clone(), String::from("")))
-                    .collect::<Vec<CompletionItem>>(),
-            );
-            completion_items.append(&mut get_iterator_cb_completion_item(
-                clarity_version,
-                &function_name.to_string(),
-            ));
-            return completion_items;
+) -> Option<Hover> {
+    let offset = position_to_offset(source, position);
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[offset..].find('\n').map_or(source.len(), |i| offset + i);
+    let line = &source[line_start..line_end];
+    let column = offset - line_start;
+
+    if let Some(captures) =
+        line.split_once('{').and_then(|(header, _)| COMMAND_HEADER.captures(header))
+    {
+        let id = captures.get(3)?;
+        if column < id.start() || column > id.end() {
+            return None;
         }
+        let command = CommandDocumentation::find(commands, &captures[1], id.as_str())?;
+        return Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("**{}**\n\n{}", command.get_id(), command.documentation),
+            }),
+            range: None,
+        });
     }
 
-    let native_keywords = match clarity_version {
-        ClarityVersion::Clarity1 => COMPLETION_ITEMS_CLARITY_1.to_vec(),
-        ClarityVersion::Clarity2 => COMPLETION_ITEMS_CLARITY_2.to_vec(),
+    let captures = ATTRIBUTE.captures(line)?;
+    let name = captures.get(2)?;
+    if column < name.start() || column > name.end() {
+        return None;
+    }
+    let headers = get_enclosing_blocks_headers(&source[..line_start]);
+    let [header] = &headers[..] else {
+        return None;
     };
-    let placeholder_pattern = Regex::new(r" \$\{\d+:[\w-]+\}").unwrap();
-
-    let mut completion_items = vec![];
-    completion_items.append(&mut active_contract_defined_data.get_contract_completion_items());
-    for mut item in [
-        native_keywords,
-        contract_calls,
-        active_contract_defined_data
-            .functions_completion_items
-            .clone(),
-    ]
-    .concat()
-    .drain(..)
-    {
-        match item.kind {
-            Some(
-                CompletionItemKind::EVENT
-                | CompletionItemKind::FUNCTION
-                | CompletionItemKind::MODULE
-                | CompletionItemKind::CLASS,
-            ) => {
-                let mut snippet = item.insert_text.take().unwrap();
-                let mut snippet_has_choices = false;
-                if item.kind == Some(CompletionItemKind::FUNCTION) {
-                    if let Some(populated_snippet) = active_contract_defined_data
-                        .populate_snippet_with_options(clarity_version, &item.label, &snippet)
-                    {
-                        snippet_has_choices = true;
-                        snippet = populated_snippet;
-                    }
-                }
+    let header_captures = COMMAND_HEADER.captures(header)?;
+    let command = CommandDocumentation::find(commands, &header_captures[1], &header_captures[3])?;
+    let input = command.find_input(name.as_str())?;
+    let requirement = if input.optional { "optional" } else { "required" };
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!(
+                "**{}** `{}` ({})\n\n{}",
+                input.name,
+                input.typing.to_string(),
+                requirement,
+                input.documentation
+            ),
+        }),
+        range: Some(Range::new(
+            Position::new(position.line, utf16_len(&line[..name.start()])),
+            Position::new(position.line, utf16_len(&line[..name.end()])),
+        )),
+    })
+}
 
-                if !include_native_placeholders {
-                    if snippet_has_choices {
-                        // for var-*, map-*, ft-* and nft-* methods
-                        // the variable name is kept but the other placeholders are removed
-                        let updated_snippet =
-                            placeholder_pattern.replace_all(&snippet, "").to_string();
-                        if updated_snippet.ne(&snippet) {
-                            snippet = updated_snippet;
-                            snippet.push_str(" $0");
-                        }
-                    } else if item.kind == Some(CompletionItemKind::FUNCTION)
-                        || item.kind == Some(CompletionItemKind::CLASS)
-                    {
-                        match item.label.as_str() {
-                            "+ (add)"
-                            | "- (subtract)"
-                            | "/ (divide)"
-                            | "* (multiply)"
-                            | "< (less than)"
-                            | "<= (less than or equal)"
-                            | "> (greater than)"
-                            | ">= (greater than or equal)" => {
-                                snippet = item.label.split_whitespace().next().unwrap().to_string()
-                            }
-                            _ => snippet.clone_from(&item.label),
+/// Returns the headers of the blocks enclosing the end of `source`, outermost first
+/// (e.g. `action "deploy" "evm::deploy_contract"`). Strings and comments are skipped.
+fn get_enclosing_blocks_headers(source: &str) -> Vec<String> {
+    let mut headers = vec![];
+    let mut header_start = 0;
+    let mut chars = source.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
                         }
-                        snippet.push_str(" $0");
+                        '"' | '\n' => break,
+                        _ => {}
                     }
                 }
-
-                item.insert_text = if should_wrap {
-                    Some(format!("({})", snippet))
-                } else {
-                    Some(snippet)
-                };
             }
-            Some(CompletionItemKind::TYPE_PARAMETER) => {
-                if should_wrap {
-                    if let "tuple" | "buff" | "string-ascii" | "string-utf8" | "optional" | "list"
-                    | "response" = item.label.as_str()
-                    {
-                        item.insert_text = Some(format!("({} $0)", item.label));
-                        item.insert_text_format = Some(InsertTextFormat::SNIPPET);
+            '#' => while chars.next_if(|(_, c)| *c != '\n').is_some() {},
+            '/' if chars.peek().map_or(false, |(_, c)| *c == '/') => {
+                while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+            }
+            '/' if chars.peek().map_or(false, |(_, c)| *c == '*') => {
+                chars.next();
+                let mut previous = ' ';
+                while let Some((_, c)) = chars.next() {
+                    if previous == '*' && c == '/' {
+                        break;
                     }
+                    previous = c;
                 }
             }
+            '{' => {
+                headers.push(source[header_start..i].trim().to_string());
+                header_start = i + 1;
+            }
+            '}' => {
+                headers.pop();
+                header_start = i + 1;
+            }
+            '\n' => header_start = i + 1,
             _ => {}
         }
-
-        completion_items.push(item);
     }
-    completion_items
+    headers
 }
 
-pub fn check_if_should_wrap(source: &str, position: &Position) -> bool {
-    if let Some(line) = source.lines().nth(position.line as usize) {
-        if position.character as usize > line.len() {
-            return false;
-        }
-
-        let mut chars = line[..position.character as usize].chars();
-        while let Some(char) = chars.next_back() {
-            match char {
-                '(' => return false,
-                char if char.is_whitespace() => return true,
-                _ => {}
-            }
-        }
-    }
-    true
+fn markdown_documentation(value: String) -> Documentation {
+    Documentation::MarkupContent(MarkupContent { kind: MarkupKind::Markdown, value })
 }
 
-pub fn build_default_native_keywords_list(version: ClarityVersion) -> Vec<CompletionItem> {
-    let clarity2_aliased_functions: Vec<NativeFunctions> = vec![
-        NativeFunctions::ElementAt,
-        NativeFunctions::IndexOf,
-        NativeFunctions::BitwiseXor,
-    ];
-
-    let command = lsp_types::Command {
-        title: "triggerParameterHints".into(),
-        command: "editor.action.triggerParameterHints".into(),
-        arguments: None,
-    };
-
-    let native_functions: Vec<CompletionItem> = NativeFunctions::ALL
-        .iter()
-        .filter_map(|func| {
-            let mut api = make_api_reference(func);
-            if api.version > version {
-                return None;
-            }
-            if clarity2_aliased_functions.contains(func) {
-                if version >= ClarityVersion::Clarity2 {
-                    return None;
-                } else if api.version == ClarityVersion::Clarity1 {
-                    // only for element-at? and index-of?
-                    api.snippet = api.snippet.replace('?', "");
-                }
-            }
-
-            Some(CompletionItem {
-                label: api.name.to_string(),
-                kind: Some(CompletionItemKind::FUNCTION),
-                detail: Some(api.name),
-                documentation: Some(Documentation::MarkupContent(MarkupContent {
-                    kind: MarkupKind::Markdown,
-                    value: api.description,
-                })),
-                insert_text: Some(api.snippet.clone()),
-                insert_text_format: Some(InsertTextFormat::SNIPPET),
-                command: Some(command.clone()),
-                ..Default::default()
-            })
-        })
-        .collect();
-
-    let define_functions: Vec<CompletionItem> = DefineFunctions::ALL
-        .iter()
-        .filter_map(|func| {
-            let api = make_define_reference(func);
-            if api.version > version {
-                return None;
-            }
-            Some(CompletionItem {
-                label: api.name.to_string(),
-                kind: Some(CompletionItemKind::CLASS),
-                detail: Some(api.name),
-                documentation: Some(Documentation::MarkupContent(MarkupContent {
-                    kind: MarkupKind::Markdown,
-                    value: api.description,
-                })),
-                insert_text: Some(api.snippet.clone()),
-                insert_text_format: Some(InsertTextFormat::SNIPPET),
-                command: Some(command.clone()),
-                ..Default::default()
-            })
-        })
-        .collect();
-
-    let native_variables: Vec<CompletionItem> = NativeVariables::ALL
-        .iter()
-        .filter_map(|var| {
-            if let Some(api) = make_keyword_reference(var) {
-                if api.version > version {
-                    return None;
-                }
-                Some(CompletionItem {
-                    label: api.name.to_string(),
-                    kind: Some(CompletionItemKind::FIELD),
-                    detail: Some(api.name.to_string()),
-                    documentation: Some(Documentation::MarkupContent(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: api.description.to_string(),
-                    })),
-                    insert_text: Some(api.snippet.to_string()),
-                    insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-                    ..Default::default()
-                })
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    let block_properties: Vec<CompletionItem> = BlockInfoProperty::ALL
-        .iter()
-        .filter_map(|var| {
-            if var.get_version() > version {
-                return None;
-            }
-            Some(CompletionItem {
-                label: var.to_string(),
-                kind: Some(CompletionItemKind::FIELD),
-                insert_text: Some(var.to_string()),
-                insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-                ..Default::default()
-            })
-        })
-        .collect();
-
-    let types = [
-        "uint",
-        "int",
-        "bool",
-        "list",
-        "tuple",
-        "buff",
-        "string-ascii",
-        "string-utf8",
-        "optional",
-        "response",
-        "principal",
-    ]
-    .iter()
-    .map(|t| CompletionItem {
-        label: t.to_string(),
-        kind: Some(CompletionItemKind::TYPE_PARAMETER),
-        insert_text: Some(t.to_string()),
-        insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-        ..Default::default()
-    })
-    .collect();
-
-    vec![
-        native_functions,
-        define_functions,
-        native_variables,
-        block_properties,
-        types,
-    ]
-    .into_iter()
-    .flatten()
-    .collect::<Vec<CompletionItem>>()
-}
-
-pub fn build_map_valid_cb_completion_items(version: ClarityVersion) -> Vec<CompletionItem> {
-    [
-        NativeFunctions::Add,
-        NativeFunctions::Subtract,
-        NativeFunctions::Multiply,
-        NativeFunctions::Divide,
-        NativeFunctions::CmpGeq,
-        NativeFunctions::CmpLeq,
-        NativeFunctions::CmpLess,
-        NativeFunctions::CmpGreater,
-        NativeFunctions::ToInt,
-        NativeFunctions::ToUInt,
-        NativeFunctions::Modulo,
-        NativeFunctions::Power,
-        NativeFunctions::Sqrti,
-        NativeFunctions::Log2,
-        NativeFunctions::BitwiseXor,
-        NativeFunctions::And,
-        NativeFunctions::Or,
-        NativeFunctions::Not,
-        NativeFunctions::BuffToIntLe,
-        NativeFunctions::BuffToUIntLe,
-        NativeFunctions::BuffToIntBe,
-        NativeFunctions::BuffToUIntBe,
-        NativeFunctions::IsStandard,
-        NativeFunctions::PrincipalDestruct,
-        NativeFunctions::PrincipalConstruct,
-        NativeFunctions::StringToInt,
-        NativeFunctions::StringToUInt,
-        NativeFunctions::IntToAscii,
-        NativeFunctions::IntToUtf8,
-        NativeFunctions::Hash160,
-        NativeFunctions::Sha256,
-        NativeFunctions::Sha512,
-        NativeFunctions::Sha512Trunc256,
-        NativeFunctions::Keccak256,
-        NativeFunctions::BitwiseAnd,
-        NativeFunctions::BitwiseOr,
-        NativeFunctions::BitwiseNot,
-        NativeFunctions::BitwiseLShift,
-        NativeFunctions::BitwiseRShift,
-        NativeFunctions::BitwiseXor2,
-    ]
-    .iter()
-    .filter_map(|func| build_iterator_cb_completion_item(func, version))
-    .collect()
-}
-
-pub fn build_filter_valid_cb_completion_items(version: ClarityVersion) -> Vec<CompletionItem> {
-    [
-        NativeFunctions::And,
-        NativeFunctions::Or,
-        NativeFunctions::Not,
-    ]
-    .iter()
-    .filter_map(|func| build_iterator_cb_completion_item(func, version))
-    .collect()
-}
-
-pub fn build_fold_valid_cb_completion_items(version: ClarityVersion) -> Vec<CompletionItem> {
-    [
-        NativeFunctions::Add,
-        NativeFunctions::Subtract,
-        NativeFunctions::Multiply,
-        NativeFunctions::Divide,
-        NativeFunctions::CmpGeq,
-        NativeFunctions::CmpLeq,
-        NativeFunctions::CmpLess,
-        NativeFunctions::CmpGreater,
-        NativeFunctions::ToInt,
-        NativeFunctions::ToUInt,
-        NativeFunctions::Modulo,
-        NativeFunctions::Power,
-        NativeFunctions::Sqrti,
-        NativeFunctions::Log2,
-        NativeFunctions::BitwiseXor,
-        NativeFunctions::And,
-        NativeFunctions::Or,
-        NativeFunctions::Not,
-        NativeFunctions::IsStandard,
-        NativeFunctions::BitwiseAnd,
-        NativeFunctions::BitwiseOr,
-        NativeFunctions::BitwiseNot,
-        NativeFunctions::BitwiseLShift,
-        NativeFunctions::BitwiseRShift,
-        NativeFunctions::BitwiseXor2,
-    ]
-    .iter()
-    .filter_map(|func| build_iterator_cb_completion_item(func, version))
-    .collect()
-}
-
-fn build_iterator_cb_completion_item(
-    func: &NativeFunctions,
-    version: ClarityVersion,
-) -> Option<CompletionItem> {
-    let api = make_api_reference(func);
-    if api.version > version {
-        return None;
-    }
-
-    let insert_text = Some(api.snippet.split_whitespace().next().unwrap().to_string());
-
-    Some(CompletionItem {
-        label: api.name.clone(),
-        kind: Some(CompletionItemKind::FUNCTION),
-        detail: Some(api.name.clone()),
-        documentation: Some(Documentation::MarkupContent(MarkupContent {
-            kind: MarkupKind::Markdown,
-            value: api.description,
-        })),
-        insert_text,
-        insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-        ..Default::default()
-    })
-}
-
-fn get_iterator_cb_completion_item(version: &ClarityVersion, func: &str) -> Vec<CompletionItem> {
-    if func.to_string().eq(&NativeFunctions::Map.to_string()) {
-        return match version {
-            ClarityVersion::Clarity1 => VALID_MAP_FUNCTIONS_CLARITY_1.to_vec(),
-            ClarityVersion::Clarity2 => VALID_MAP_FUNCTIONS_CLARITY_1.to_vec(),
-        };
-    }
-    if func.to_string().eq(&NativeFunctions::Filter.to_string()) {
-        return match version {
-            ClarityVersion::Clarity1 => VALID_FILTER_FUNCTIONS_CLARITY_1.to_vec(),
-            ClarityVersion::Clarity2 => VALID_FILTER_FUNCTIONS_CLARITY_1.to_vec(),
-        };
-    }
-    match version {
-        ClarityVersion::Clarity1 => VALID_FOLD_FUNCTIONS_CLARITY_1.to_vec(),
-        ClarityVersion::Clarity2 => VALID_FOLD_FUNCTIONS_CLARITY_1.to_vec(),
-    }
+fn utf16_len(value: &str) -> u32 {
+    value.encode_utf16().count() as u32
 }
 
 #[cfg(test)]
-mod get_contract_global_data_tests {
-    use clarity_repl::clarity::ast::build_ast_with_rules;
-    use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
-    use clarity_repl::clarity::{ClarityVersion, StacksEpochId};
-    use lsp_types::Position;
-
-    use super::ContractDefinedData;
-
-    fn get_defined_data(source: &str) -> ContractDefinedData {
-        let contract_ast = build_ast_with_rules(
-            &QualifiedContractIdentifier::transient(),
-            source,
-            &mut (),
-            ClarityVersion::Clarity2,
-            StacksEpochId::Epoch21,
-            clarity_repl::clarity::ast::ASTRules::Typical,
-        )
-        .unwrap();
-        ContractDefinedData::new(&contract_ast.expressions, &Position::default())
-    }
+mod tests {
+    use std::collections::HashMap;
 
-    #[test]
-    fn get_data_vars() {
-        let data = get_defined_data(
-            "(define-data-var counter uint u1) (define-data-var is-active bool true)",
-        );
-        assert_eq!(data.vars, ["counter", "is-active"]);
-    }
+    use lsp_types::{CompletionTextEdit, HoverContents, Position};
+    use txtx_addon_kit::types::commands::{CommandInput, CommandOutput};
+    use txtx_addon_kit::types::types::Type;
 
-    #[test]
-    fn get_map() {
-        let data = get_defined_data("(define-map names principal { name: (buff 48) })");
-        assert_eq!(data.maps, ["names"]);
-    }
+    use super::{
+        get_actions_command_ids, get_command_completion_items, get_command_hover,
+        CommandDocumentation,
+    };
 
-    #[test]
-    fn get_fts() {
-        let data = get_defined_data("(define-fungible-token clarity-coin)");
-        assert_eq!(data.fts, ["clarity-coin"]);
+    fn input(name: &str, optional: bool) -> CommandInput {
+        CommandInput {
+            name: name.into(),
+            documentation: format!("The {}", name),
+            typing: Type::string(),
+            optional,
+            tainting: true,
+            check_required: false,
+            check_performed: false,
+            sensitive: false,
+            internal: false,
+        }
     }
 
-    #[test]
-    fn get_nfts() {
-        let data = get_defined_data("(define-non-fungible-token bitcoin-nft uint)");
-        assert_eq!(data.nfts, ["bitcoin-nft"]);
+    fn get_commands() -> Vec<CommandDocumentation> {
+        vec![
+            CommandDocumentation {
+                construct_type: "action".into(),
+                namespace: "evm".into(),
+                matcher: "deploy_contract".into(),
+                documentation: "Deploys a contract".into(),
+                inputs: vec![input("contract", false), input("description", true)],
+                outputs: vec![CommandOutput {
+                    name: "contract_address".into(),
+                    documentation: "The address of the contract".into(),
+                    typing: Type::string(),
+                }],
+            },
+            CommandDocumentation {
+                construct_type: "signer".into(),
+                namespace: "evm".into(),
+                matcher: "web_wallet".into(),
+                documentation: "Signs with a browser wallet".into(),
+                inputs: vec![input("expected_address", true)],
+                outputs: vec![],
+            },
+        ]
     }
-}
 
-#[cfg(test)]
-mod get_contract_local_data_tests {
-    use clarity_repl::clarity::ast::build_ast_with_rules;
-    use clarity_repl::clarity::StacksEpochId;
-    use clarity_repl::clarity::{vm::types::QualifiedContractIdentifier, ClarityVersion};
-    use lsp_types::Position;
-
-    use super::ContractDefinedData;
-
-    fn get_defined_data(source: &str, position: &Position) -> ContractDefinedData {
-        let contract_ast = build_ast_with_rules(
-            &QualifiedContractIdentifier::transient(),
-            source,
-            &mut (),
-            ClarityVersion::Clarity2,
-            StacksEpochId::Epoch21,
-            clarity_repl::clarity::ast::ASTRules::Typical,
-        )
-        .unwrap();
-        ContractDefinedData::new(&contract_ast.expressions, position)
+    fn get_labels(source: &str, position: Position) -> Vec<String> {
+        let actions = get_actions_command_ids(source);
+        get_command_completion_items(&get_commands(), &actions, source, &position)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| match item.text_edit {
+                Some(CompletionTextEdit::Edit(edit)) => edit.new_text,
+                _ => item.label,
+            })
+            .collect()
     }
 
     #[test]
-    fn get_function_binding() {
-        let data = get_defined_data(
-            "(define-private (print-arg (arg int)) )",
-            &Position {
-                line: 1,
-                character: 38,
-            },
+    fn complete_command_ids() {
+        assert_eq!(get_labels("action \"deploy\" \"ev", Position::new(0, 19)), vec!["evm::"]);
+        assert_eq!(
+            get_labels("action \"deploy\" \"evm::", Position::new(0, 22)),
+            vec!["evm::deploy_contract"]
         );
-        assert_eq!(data.locals, vec![("arg".to_string(), "int".to_string())]);
-        let data = get_defined_data(
-            "(define-private (print-arg (arg int)) )",
-            &Position {
-                line: 1,
-                character: 40,
-            },
+        assert_eq!(
+            get_labels("signer \"deployer\" \"evm::", Position::new(0, 24)),
+            vec!["evm::web_wallet"]
         );
-        assert_eq!(data.locals, vec![]);
     }
 
     #[test]
-    fn get_let_binding() {
-        let data = get_defined_data(
-            "(let ((n u0)) )",
-            &Position {
-                line: 1,
-                character: 15,
-            },
-        );
-        assert_eq!(data.locals, vec![("n".to_string(), "u0".to_string())]);
-    }
-}
-
-#[cfg(test)]
-mod populate_snippet_with_options_tests {
-    use clarity_repl::clarity::ast::build_ast_with_rules;
-    use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
-    use clarity_repl::clarity::{ClarityVersion, StacksEpochId};
-    use lsp_types::Position;
-
-    use super::ContractDefinedData;
-
-    fn get_defined_data(source: &str) -> ContractDefinedData {
-        let contract_ast = build_ast_with_rules(
-            &QualifiedContractIdentifier::transient(),
+    fn complete_command_attributes() {
+        let source = "action \"deploy\" \"evm::deploy_contract\" {\n    co\n}";
+        let items = get_command_completion_items(
+            &get_commands(),
+            &HashMap::new(),
             source,
-            &mut (),
-            ClarityVersion::Clarity2,
-            StacksEpochId::Epoch21,
-            clarity_repl::clarity::ast::ASTRules::Typical,
+            &Position::new(1, 6),
         )
         .unwrap();
-        ContractDefinedData::new(&contract_ast.expressions, &Position::default())
-    }
-
-    #[test]
-    fn get_data_vars_snippet() {
-        let data = get_defined_data(
-            "(define-data-var counter uint u1) (define-data-var is-active bool true)",
-        );
-        let snippet = data.populate_snippet_with_options(
-            &ClarityVersion::Clarity2,
-            &"var-get".to_string(),
-            "var-get ${1:var}",
-        );
-        assert_eq!(snippet, Some("var-get ${1|counter,is-active|}".to_string()));
-    }
-
-    #[test]
-    fn get_map_snippet() {
-        let data = get_defined_data("(define-map names principal { name: (buff 48) })");
-        let snippet = data.populate_snippet_with_options(
-            &ClarityVersion::Clarity2,
-            &"map-get?".to_string(),
-            "map-get? ${1:map-name} ${2:key-tuple}",
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].label, "contract");
+        assert_eq!(
+            items[0].label_details.as_ref().unwrap().description.as_deref(),
+            Some("required")
         );
         assert_eq!(
-            snippet,
-            Some("map-get? ${1|names|} ${2:key-tuple}".to_string())
+            items[1].label_details.as_ref().unwrap().description.as_deref(),
+            Some("optional")
         );
+
+        // attributes of nested blocks are not completed
+        let source =
+            "action \"deploy\" \"evm::deploy_contract\" {\n    map {\n        co\n    }\n}";
+        assert_eq!(get_labels(source, Position::new(2, 10)), Vec::<String>::new());
     }
 
     #[test]
-    fn get_fts_snippet() {
-        let data = get_defined_data("(define-fungible-token btc u21)");
-        let snippet = data.populate_snippet_with_options(
-            &ClarityVersion::Clarity2,
-            &"ft-mint?".to_string(),
-            "ft-mint? ${1:token-name} ${2:amount} ${3:recipient}",
-        );
-        assert_eq!(
-            snippet,
-            Some("ft-mint? ${1|btc|} ${2:amount} ${3:recipient}".to_string())
-        );
+    fn complete_action_outputs() {
+        let source = "action \"deploy\" \"evm::deploy_contract\" {\n}\noutput \"address\" {\n    value = action.deploy.con\n}";
+        assert_eq!(get_labels(source, Position::new(3, 29)), vec!["contract_address"]);
     }
 
     #[test]
-    fn get_nfts_snippet() {
-        let data = get_defined_data("(define-non-fungible-token bitcoin-nft uint)");
-        let snippet = data.populate_snippet_with_options(
-            &ClarityVersion::Clarity2,
-            &"nft-mint?".to_string(),
-            "nft-mint? ${1:asset-name} ${2:asset-identifier} ${3:recipient}",
-        );
-        assert_eq!(
-            snippet,
-            Some("nft-mint? ${1|bitcoin-nft|} ${2:asset-identifier} ${3:recipient}".to_string())
-        );
+    fn hover_command_attributes() {
+        let source = "action \"deploy\" \"evm::deploy_contract\" {\n    contract = \"{ }\"\n}";
+        let hover = get_command_hover(&get_commands(), source, &Position::new(1, 6)).unwrap();
+        let HoverContents::Markup(content) = hover.contents else { panic!("expected markup") };
+        assert!(content.value.contains("The contract"));
+        assert!(content.value.contains("required"));
+
+        assert!(get_command_hover(&get_commands(), source, &Position::new(1, 17)).is_none());
     }
 }
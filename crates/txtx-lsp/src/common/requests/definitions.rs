@@ -1,812 +1,619 @@
 use std::collections::HashMap;
 
-use super::helpers::span_to_range;
+use lsp_types::{Position, Range};
+use txtx_addon_kit::hcl::expr::{Expression, TraversalOperator};
+use txtx_addon_kit::hcl::structure::Block;
+use txtx_addon_kit::hcl::Span;
+use txtx_addon_kit::helpers::fs::FileLocation;
+use txtx_addon_kit::helpers::hcl::{collect_constructs_references_from_block, RawHclContent};
+use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
+use txtx_addon_kit::types::commands::{CommandId, CommandInput};
+use txtx_addon_kit::types::embedded_runbooks::EmbeddedRunbookInstance;
+use txtx_addon_kit::types::{AuthorizationContext, ConstructDid, PackageId, RunbookId};
+use txtx_addon_kit::Addon;
+use txtx_core::runbook::flow_context::FlowContext;
+use txtx_core::runbook::{
+    Runbook, RunbookExecutionContext, RunbookSources, RunbookTopLevelInputsMap,
+    RunbookWorkspaceContext, RuntimeContext,
+};
+
+use super::helpers::{
+    get_label_span, is_position_within_range, span_to_range, unquoted_span_to_range,
+};
+
+/// A symbol that can be navigated to, referenced and renamed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    /// A construct of a runbook (`action`, `variable`, `signer`, `output`, `runbook`, ...)
+    Construct(ConstructDid),
+    /// A top level input (`input.name`), defined in the environments of the workspace manifest
+    Input(String),
+}
 
-use clarity_repl::analysis::ast_visitor::{traverse, ASTVisitor, TypedVar};
-use clarity_repl::clarity::functions::define::DefineFunctions;
-use clarity_repl::clarity::vm::types::{QualifiedContractIdentifier, StandardPrincipalData};
-use clarity_repl::clarity::{ClarityName, SymbolicExpression};
-use lsp_types::Range;
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstructDefinition {
+    /// Type of construct (e.g. `variable` in `variable.value`)
+    pub construct_type: String,
+    /// Name of construct (e.g. `value` in `variable.value`)
+    pub name: String,
+    pub package_id: PackageId,
+    /// Location of the file enclosing the construct
+    pub location: FileLocation,
+    /// Range of the block defining the construct
+    pub range: Range,
+    /// Range of the name label of the block, quotes excluded
+    pub name_range: Range,
+    /// Addon command implemented by the construct, if any (e.g. `evm::deploy_contract`)
+    pub command_id: Option<String>,
+}
 
-#[cfg(feature = "wasm")]
-#[allow(unused_imports)]
-use crate::utils::log;
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymbolReference {
+    pub symbol: Symbol,
+    pub location: FileLocation,
+    /// Range of the name of the symbol (e.g. `deploy` in `action.deploy.contract_address`)
+    pub range: Range,
+}
 
+/// A reference to a construct of an embedded runbook, such as `deploy` in
+/// `runbook.embedded.action.deploy.contract_address`.
 #[derive(Clone, Debug, PartialEq)]
-pub enum DefinitionLocation {
-    Internal(Range),
-    External(QualifiedContractIdentifier, ClarityName),
+pub struct EmbeddedReference {
+    pub location: FileLocation,
+    pub range: Range,
+    pub definition_location: FileLocation,
+    pub definition_range: Range,
 }
 
-// `global` holds all of the top-level user-defined keywords that are available in the global scope
-// `local` holds the locally user-defined keywords: function parameters, let and match bindings
-// when a user-defined keyword is used in the code, its position and definition location are stored in `tokens`
-#[derive(Clone, Debug, Default)]
+// `constructs` holds the definitions of the constructs indexed in the workspace context of a runbook
+// `references` holds every expression of the runbook sources resolving to a construct or a top level input
+// `embedded_references` holds the references going past an embedded runbook, into its own constructs
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Definitions {
-    pub tokens: HashMap<(u32, u32), DefinitionLocation>,
-    global: HashMap<ClarityName, Range>,
-    local: HashMap<u64, HashMap<ClarityName, Range>>,
-    deployer: Option<StandardPrincipalData>,
+    pub constructs: HashMap<ConstructDid, ConstructDefinition>,
+    pub references: Vec<SymbolReference>,
+    pub embedded_references: Vec<EmbeddedReference>,
 }
 
-impl<'a> Definitions {
-    pub fn new(deployer: Option<StandardPrincipalData>) -> Self {
-        Self {
-            deployer,
-            ..Default::default()
+impl Definitions {
+    /// Indexes the definitions of the constructs of a runbook and every reference to them. References are
+    /// resolved with [RunbookWorkspaceContext::try_resolve_construct_reference_in_expression], as they are at runtime.
+    pub fn build(
+        workspace_context: &RunbookWorkspaceContext,
+        execution_context: &RunbookExecutionContext,
+        runbook_sources: &RunbookSources,
+    ) -> Self {
+        let mut sources = SourcesCache::new(runbook_sources);
+        let mut definitions = Definitions::default();
+
+        for (construct_did, construct_id) in workspace_context.constructs.iter() {
+            let Some(block) = get_construct_block(construct_did, execution_context) else {
+                continue;
+            };
+            let (Some(span), Some(name_span)) =
+                (block.span(), block.labels.first().and_then(get_label_span))
+            else {
+                continue;
+            };
+            let Some(source) = sources.get(&construct_id.construct_location) else {
+                continue;
+            };
+            definitions.constructs.insert(
+                construct_did.clone(),
+                ConstructDefinition {
+                    construct_type: construct_id.construct_type.clone(),
+                    name: construct_id.construct_name.clone(),
+                    package_id: construct_id.package_id.clone(),
+                    location: construct_id.construct_location.clone(),
+                    range: span_to_range(source, &span),
+                    name_range: unquoted_span_to_range(source, &name_span),
+                    command_id: block.labels.get(1).map(|label| label.as_str().to_string()),
+                },
+            );
         }
-    }
-
-    pub fn run(&mut self, expressions: &'a [SymbolicExpression]) {
-        traverse(self, expressions);
-    }
 
-    fn set_function_parameters_scope(&mut self, expr: &SymbolicExpression) -> Option<()> {
-        let mut local_scope = HashMap::new();
-        let (_, binding_exprs) = expr.match_list()?.get(1)?.match_list()?.split_first()?;
-        for binding in binding_exprs {
-            if let Some(name) = binding
-                .match_list()
-                .and_then(|l| l.split_first())
-                .and_then(|(name, _)| name.match_atom())
-            {
-                local_scope.insert(name.to_owned(), span_to_range(&binding.span));
+        let inputs_lookup = workspace_context
+            .top_level_inputs_did_lookup
+            .iter()
+            .map(|(name, construct_did)| (construct_did, name))
+            .collect::<HashMap<_, _>>();
+
+        for (location, (package_name, raw_content)) in runbook_sources.tree.iter() {
+            let Ok(package_id) =
+                PackageId::from_file(location, &workspace_context.runbook_id, package_name)
+            else {
+                continue;
+            };
+            let Ok(blocks) = raw_content.into_blocks() else {
+                continue;
+            };
+            let source = raw_content.to_string();
+
+            let mut expressions = vec![];
+            for block in blocks.iter() {
+                collect_constructs_references_from_block::<CommandInput>(
+                    block,
+                    None,
+                    &mut expressions,
+                );
             }
-        }
-        self.local.insert(expr.id, local_scope);
-        Some(())
-    }
 
-    // helper method to retrieve definitions of global keyword used in methods such as
-    // (var-get <global-keyword>) (map-insert <global-keyword> ...) (nft-burn <global-keyword> ...)
-    fn set_definition_for_arg_at_index(
-        &mut self,
-        expr: &SymbolicExpression,
-        token: &ClarityName,
-        index: usize,
-    ) -> Option<()> {
-        let range = self.global.get(token)?;
-        let keyword = expr.match_list()?.get(index)?;
-        self.tokens.insert(
-            (keyword.span.start_line, keyword.span.start_column),
-            DefinitionLocation::Internal(*range),
-        );
-        Some(())
-    }
-}
+            for (_, expression) in expressions.iter() {
+                let Ok(Some((construct_did, components, _))) = workspace_context
+                    .try_resolve_construct_reference_in_expression(&package_id, expression)
+                else {
+                    continue;
+                };
+                let (symbol, keyword, name) = match inputs_lookup.get(&construct_did) {
+                    Some(input_name) => {
+                        (Symbol::Input(input_name.to_string()), "input", *input_name)
+                    }
+                    None => {
+                        let Some(construct_id) = workspace_context.constructs.get(&construct_did)
+                        else {
+                            continue;
+                        };
+                        (
+                            Symbol::Construct(construct_did.clone()),
+                            construct_id.construct_type.as_str(),
+                            &construct_id.construct_name,
+                        )
+                    }
+                };
 
-impl<'a> ASTVisitor<'a> for Definitions {
-    fn traverse_expr(&mut self, expr: &'a SymbolicExpression) -> bool {
-        use clarity_repl::clarity::vm::representations::SymbolicExpressionType::*;
-        match &expr.expr {
-            AtomValue(value) => self.visit_atom_value(expr, value),
-            Atom(name) => self.visit_atom(expr, name),
-            List(exprs) => {
-                let result = self.traverse_list(expr, exprs);
-                // clear local scope after traversing it
-                self.local.remove(&expr.id);
-                result
+                let traversal_components = get_traversal_components(expression);
+                let Some(index) = (1..traversal_components.len()).find(|i| {
+                    traversal_components[*i].0.eq(name)
+                        && traversal_components[i - 1].0.eq_ignore_ascii_case(keyword)
+                }) else {
+                    continue;
+                };
+                let Some(span) = &traversal_components[index].1 else {
+                    continue;
+                };
+                definitions.references.push(SymbolReference {
+                    symbol: symbol.clone(),
+                    location: location.clone(),
+                    range: span_to_range(&source, span),
+                });
+
+                // `runbook.embedded.action.deploy`: `components` holds the path remaining past `runbook.embedded`
+                let Some(embedded_runbook) =
+                    execution_context.embedded_runbooks.get(&construct_did)
+                else {
+                    continue;
+                };
+                let (Some(construct_type), Some(construct_name)) =
+                    (components.get(0), components.get(1))
+                else {
+                    continue;
+                };
+                let Some((_, Some(embedded_span))) = traversal_components.get(index + 2) else {
+                    continue;
+                };
+                let Some((definition_location, definition_range)) =
+                    get_embedded_construct_definition(
+                        embedded_runbook,
+                        construct_type,
+                        construct_name,
+                        &mut sources,
+                    )
+                else {
+                    continue;
+                };
+                definitions.embedded_references.push(EmbeddedReference {
+                    location: location.clone(),
+                    range: span_to_range(&source, embedded_span),
+                    definition_location,
+                    definition_range,
+                });
             }
-            LiteralValue(value) => self.visit_literal_value(expr, value),
-            Field(field) => self.visit_field(expr, field),
-            TraitReference(name, trait_def) => self.visit_trait_reference(expr, name, trait_def),
         }
+
+        definitions
     }
 
-    fn visit_atom(&mut self, expr: &'a SymbolicExpression, atom: &'a ClarityName) -> bool {
-        // iterate on local scopes to find if the variable is declared in one of them
-        // the order does not matter because variable shadowing is not allowed
-        for scope in self.local.values() {
-            if let Some(range) = scope.get(atom) {
-                self.tokens.insert(
-                    (expr.span.start_line, expr.span.start_column),
-                    DefinitionLocation::Internal(*range),
-                );
-                return true;
+    /// Returns the symbol referenced or defined at `position`, along with the range of its name.
+    pub fn get_symbol_at_position(
+        &self,
+        location: &FileLocation,
+        position: &Position,
+    ) -> Option<(Symbol, Range)> {
+        for reference in self.references.iter() {
+            if reference.location.eq(location)
+                && is_position_within_range(position, &reference.range)
+            {
+                return Some((reference.symbol.clone(), reference.range));
             }
         }
-
-        if let Some(range) = self.global.get(atom) {
-            self.tokens.insert(
-                (expr.span.start_line, expr.span.start_column),
-                DefinitionLocation::Internal(*range),
-            );
+        for (construct_did, definition) in self.constructs.iter() {
+            if definition.location.eq(location)
+                && is_position_within_range(position, &definition.name_range)
+            {
+                return Some((Symbol::Construct(construct_did.clone()), definition.name_range));
+            }
         }
-        true
-    }
-
-    fn visit_var_set(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _value: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, name, 1);
-        true
-    }
-
-    fn visit_var_get(&mut self, expr: &'a SymbolicExpression, name: &'a ClarityName) -> bool {
-        self.set_definition_for_arg_at_index(expr, name, 1);
-        true
+        None
     }
 
-    fn visit_map_insert(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
-        _value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, name, 1);
-        true
+    pub fn get_embedded_definition_at_position(
+        &self,
+        location: &FileLocation,
+        position: &Position,
+    ) -> Option<(FileLocation, Range)> {
+        self.embedded_references
+            .iter()
+            .find(|r| r.location.eq(location) && is_position_within_range(position, &r.range))
+            .map(|r| (r.definition_location.clone(), r.definition_range))
     }
 
-    fn visit_map_get(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, name, 1);
-        true
+    pub fn get_references(&self, symbol: &Symbol) -> Vec<(FileLocation, Range)> {
+        self.references
+            .iter()
+            .filter(|r| r.symbol.eq(symbol))
+            .map(|r| (r.location.clone(), r.range))
+            .collect()
     }
 
-    fn visit_map_set(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
-        _value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, name, 1);
-        true
+    /// Returns the construct whose name label is at `name_range` in `location`. Runbooks sharing
+    /// source files index the same blocks under different dids, this is how they are matched.
+    pub fn find_construct_defined_at(
+        &self,
+        location: &FileLocation,
+        name_range: &Range,
+    ) -> Option<(&ConstructDid, &ConstructDefinition)> {
+        self.constructs.iter().find(|(_, d)| d.location.eq(location) && d.name_range.eq(name_range))
     }
+}
 
-    fn visit_map_delete(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, name, 1);
-        true
-    }
+/// Ranges of the keys defining the top level inputs, in the environments of a workspace manifest.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputsDefinitions {
+    pub location: Option<FileLocation>,
+    pub inputs: HashMap<String, Vec<Range>>,
+}
 
-    fn visit_call_user_defined(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _args: &'a [SymbolicExpression],
-    ) -> bool {
-        if let Some(range) = self.global.get(name) {
-            self.tokens.insert(
-                (expr.span.start_line, expr.span.start_column + 1),
-                DefinitionLocation::Internal(*range),
-            );
+impl InputsDefinitions {
+    /// Scans the `environments` section of a `txtx.yml` manifest, expected to be laid out as follow:
+    /// ```yaml
+    /// environments:
+    ///   devnet:
+    ///     chain_id: 11155111
+    /// ```
+    pub fn new(location: &FileLocation, manifest_source: &str) -> Self {
+        let mut inputs: HashMap<String, Vec<Range>> = HashMap::new();
+        let mut in_environments = false;
+        let mut environment_indent = None;
+
+        for (line, content) in manifest_source.lines().enumerate() {
+            let trimmed = content.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = content.len() - trimmed.len();
+            if indent == 0 {
+                in_environments = trimmed.starts_with("environments:");
+                environment_indent = None;
+                continue;
+            }
+            if !in_environments {
+                continue;
+            }
+            match environment_indent {
+                None => {
+                    environment_indent = Some(indent);
+                    continue;
+                }
+                Some(environment_indent) if indent <= environment_indent => continue,
+                _ => {}
+            }
+            let Some((key, _)) = trimmed.split_once(':') else {
+                continue;
+            };
+            let key = key.trim_end();
+            let (key, start) = match key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+                Some(unquoted) => (unquoted, indent + 1),
+                None => (key, indent),
+            };
+            let start = Position::new(line as u32, start as u32);
+            let end =
+                Position::new(line as u32, start.character + key.encode_utf16().count() as u32);
+            inputs.entry(key.to_string()).or_default().push(Range::new(start, end));
         }
-        true
-    }
-
-    fn visit_ft_mint(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        token: &'a ClarityName,
-        _amount: &'a SymbolicExpression,
-        _recipient: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, token, 1);
-        true
-    }
-
-    fn visit_ft_burn(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        token: &'a ClarityName,
-        _amount: &'a SymbolicExpression,
-        _sender: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, token, 1);
-        true
-    }
-
-    fn visit_ft_get_balance(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        token: &'a ClarityName,
-        _owner: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, token, 1);
-        true
-    }
-
-    fn visit_ft_get_supply(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        token: &'a ClarityName,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, token, 1);
-        true
-    }
-
-    fn visit_ft_transfer(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        token: &'a ClarityName,
-        _amount: &'a SymbolicExpression,
-        _sender: &'a SymbolicExpression,
-        _recipient: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, token, 1);
-        true
-    }
-
-    fn visit_nft_burn(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        token: &'a ClarityName,
-        _identifier: &'a SymbolicExpression,
-        _sender: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, token, 1);
-        true
-    }
-
-    fn visit_nft_get_owner(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        token: &'a ClarityName,
-        _identifier: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, token, 1);
-        true
-    }
-
-    fn visit_nft_mint(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        token: &'a ClarityName,
-        _identifier: &'a SymbolicExpression,
-        _recipient: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, token, 1);
-        true
-    }
-
-    fn visit_nft_transfer(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        token: &'a ClarityName,
-        _identifier: &'a SymbolicExpression,
-        _sender: &'a SymbolicExpression,
-        _recipient: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, token, 1);
-        true
-    }
 
-    fn visit_map(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        func: &'a ClarityName,
-        _sequences: &'a [SymbolicExpression],
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, func, 1);
-        true
+        InputsDefinitions { location: Some(location.clone()), inputs }
     }
 
-    fn visit_filter(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        func: &'a ClarityName,
-        _sequence: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, func, 1);
-        true
+    pub fn get_definition(&self, name: &str) -> Option<(FileLocation, Range)> {
+        let location = self.location.as_ref()?;
+        let range = self.inputs.get(name)?.first()?;
+        Some((location.clone(), *range))
     }
 
-    fn visit_fold(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        func: &'a ClarityName,
-        _sequence: &'a SymbolicExpression,
-        _initial: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_definition_for_arg_at_index(expr, func, 1);
-        true
-    }
-
-    fn visit_static_contract_call(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        identifier: &'a QualifiedContractIdentifier,
-        function_name: &'a ClarityName,
-        _args: &'a [SymbolicExpression],
-    ) -> bool {
-        if let Some(list) = expr.match_list() {
-            if let Some(SymbolicExpression { span, .. }) = list.get(2) {
-                let identifier = if identifier.issuer == StandardPrincipalData::transient() {
-                    match &self.deployer {
-                        Some(deployer) => QualifiedContractIdentifier::parse(&format!(
-                            "{}.{}",
-                            deployer, identifier.name
-                        ))
-                        .expect("failed to set contract name"),
-                        None => identifier.to_owned(),
-                    }
-                } else {
-                    identifier.to_owned()
-                };
-
-                self.tokens.insert(
-                    (span.start_line, span.start_column),
-                    DefinitionLocation::External(identifier, function_name.to_owned()),
-                );
-            };
+    pub fn get_occurrences(&self, name: &str) -> Vec<(FileLocation, Range)> {
+        let Some(location) = &self.location else {
+            return vec![];
         };
-
-        true
-    }
-
-    fn traverse_define_private(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        parameters: Option<Vec<TypedVar<'a>>>,
-        body: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_function_parameters_scope(expr);
-        self.traverse_expr(body) && self.visit_define_private(expr, name, parameters, body)
-    }
-
-    fn visit_define_private(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _parameters: Option<Vec<clarity_repl::analysis::ast_visitor::TypedVar<'a>>>,
-        _body: &'a SymbolicExpression,
-    ) -> bool {
-        self.global.insert(name.clone(), span_to_range(&expr.span));
-        true
+        self.inputs
+            .get(name)
+            .map(|ranges| ranges.iter().map(|r| (location.clone(), *r)).collect())
+            .unwrap_or_default()
     }
+}
 
-    fn traverse_define_read_only(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        parameters: Option<Vec<TypedVar<'a>>>,
-        body: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_function_parameters_scope(expr);
-        self.traverse_expr(body) && self.visit_define_read_only(expr, name, parameters, body)
-    }
+/// Indexes the constructs of a runbook. Only the first steps of [Runbook::build_contexts_from_sources]
+/// are performed, so that a runbook failing to build can still be navigated and analyzed.
+pub async fn index_runbook_sources(
+    runbook_id: &RunbookId,
+    runbook_sources: &RunbookSources,
+    top_level_inputs_map: &RunbookTopLevelInputsMap,
+    environment_selector: &Option<String>,
+    get_addon_by_namespace: fn(&str) -> Option<Box<dyn Addon>>,
+) -> Option<FlowContext> {
+    let runbook = Runbook::new(runbook_id.clone(), None);
+    let mut runtime_context = RuntimeContext::new(
+        AuthorizationContext::empty(),
+        get_addon_by_namespace,
+        CloudServiceContext::empty(),
+    );
+    let flow_contexts = runbook
+        .initialize_flow_contexts(&runtime_context, runbook_sources, top_level_inputs_map)
+        .ok()?;
+    // constructs are indexed identically in every flow
+    let mut flow_context = flow_contexts.into_iter().next()?;
+
+    // diagnostics are ignored: the constructs that could be indexed are still navigable
+    let _ = runtime_context.register_addons_from_sources(
+        &mut flow_context.workspace_context,
+        runbook_id,
+        runbook_sources,
+        &flow_context.execution_context,
+        environment_selector,
+    );
+    let _ = flow_context
+        .workspace_context
+        .build_from_sources(
+            runbook_sources,
+            &mut runtime_context,
+            &mut flow_context.graph_context,
+            &mut flow_context.execution_context,
+            environment_selector,
+        )
+        .await;
 
-    fn visit_define_read_only(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _parameters: Option<Vec<clarity_repl::analysis::ast_visitor::TypedVar<'a>>>,
-        _body: &'a SymbolicExpression,
-    ) -> bool {
-        self.global.insert(name.clone(), span_to_range(&expr.span));
-        true
-    }
+    Some(flow_context)
+}
 
-    fn traverse_define_public(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        parameters: Option<Vec<TypedVar<'a>>>,
-        body: &'a SymbolicExpression,
-    ) -> bool {
-        self.set_function_parameters_scope(expr);
-        self.traverse_expr(body) && self.visit_define_public(expr, name, parameters, body)
-    }
+/// Contents of the files enclosing constructs: the runbook sources, then files read on demand
+/// (imported packages and embedded runbooks).
+struct SourcesCache {
+    contents: HashMap<FileLocation, Option<String>>,
+}
 
-    fn visit_define_public(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _parameters: Option<Vec<clarity_repl::analysis::ast_visitor::TypedVar<'a>>>,
-        _body: &'a SymbolicExpression,
-    ) -> bool {
-        self.global.insert(name.clone(), span_to_range(&expr.span));
-        true
+impl SourcesCache {
+    fn new(runbook_sources: &RunbookSources) -> Self {
+        let contents = runbook_sources
+            .tree
+            .iter()
+            .map(|(location, (_, raw_content))| (location.clone(), Some(raw_content.to_string())))
+            .collect();
+        SourcesCache { contents }
     }
 
-    fn visit_define_constant(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _value: &'a SymbolicExpression,
-    ) -> bool {
-        self.global.insert(name.clone(), span_to_range(&expr.span));
-        true
+    fn get(&mut self, location: &FileLocation) -> Option<&str> {
+        self.contents
+            .entry(location.clone())
+            .or_insert_with(|| location.read_content_as_utf8().ok())
+            .as_deref()
     }
+}
 
-    fn visit_define_data_var(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _data_type: &'a SymbolicExpression,
-        _initial: &'a SymbolicExpression,
-    ) -> bool {
-        self.global.insert(name.clone(), span_to_range(&expr.span));
-        true
+fn get_construct_block<'a>(
+    construct_did: &ConstructDid,
+    execution_context: &'a RunbookExecutionContext,
+) -> Option<&'a Block> {
+    if let Some(command_instance) = execution_context.commands_instances.get(construct_did) {
+        return Some(&command_instance.block);
     }
-
-    fn visit_define_map(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _key_type: &'a SymbolicExpression,
-        _value_type: &'a SymbolicExpression,
-    ) -> bool {
-        self.global.insert(name.clone(), span_to_range(&expr.span));
-        true
+    if let Some(signer_instance) = execution_context.signers_instances.get(construct_did) {
+        return Some(&signer_instance.block);
     }
-
-    fn visit_define_ft(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _supply: Option<&'a SymbolicExpression>,
-    ) -> bool {
-        self.global.insert(name.clone(), span_to_range(&expr.span));
-        true
+    if let Some(embedded_runbook) = execution_context.embedded_runbooks.get(construct_did) {
+        return Some(&embedded_runbook.block);
     }
-
-    fn visit_define_nft(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        name: &'a ClarityName,
-        _nft_type: &'a SymbolicExpression,
-    ) -> bool {
-        self.global.insert(name.clone(), span_to_range(&expr.span));
-        true
+    if let Some(addon_instance) = execution_context.addon_instances.get(construct_did) {
+        return Some(&addon_instance.block);
     }
+    None
+}
 
-    fn traverse_let(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        bindings: &HashMap<&'a ClarityName, &'a SymbolicExpression>,
-        body: &'a [SymbolicExpression],
-    ) -> bool {
-        let local_scope = || -> Option<HashMap<ClarityName, Range>> {
-            let mut result = HashMap::new();
-
-            let binding_exprs = expr.match_list()?.get(1)?.match_list()?;
-            for binding in binding_exprs {
-                if let Some(name) = binding
-                    .match_list()
-                    .and_then(|l| l.split_first())
-                    .and_then(|(name, _)| name.match_atom())
-                {
-                    result.insert(name.to_owned(), span_to_range(&binding.span));
-                }
-            }
-            Some(result)
+/// Returns the name and span of each component of a traversal (`action`, `deploy` and `contract_address`
+/// in `action.deploy.contract_address`), up to the first index operator.
+pub(crate) fn get_traversal_components(
+    expression: &Expression,
+) -> Vec<(String, Option<std::ops::Range<usize>>)> {
+    let Some(traversal) = expression.as_traversal() else {
+        return vec![];
+    };
+    let Some(root) = traversal.expr.as_variable() else {
+        return vec![];
+    };
+    let mut components = vec![(root.as_str().to_string(), traversal.expr.span())];
+    for operator in traversal.operators.iter() {
+        let TraversalOperator::GetAttr(attribute) = operator.value() else {
+            break;
         };
-        if let Some(local_scope) = local_scope() {
-            self.local.insert(expr.id, local_scope);
-        }
-
-        for binding in bindings.values() {
-            if !self.traverse_expr(binding) {
-                return false;
-            }
-        }
-
-        for expr in body {
-            if !self.traverse_expr(expr) {
-                return false;
-            }
-        }
-        self.visit_let(expr, bindings, body)
-    }
-
-    fn traverse_match_option(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        input: &'a SymbolicExpression,
-        some_name: &'a ClarityName,
-        some_branch: &'a SymbolicExpression,
-        none_branch: &'a SymbolicExpression,
-    ) -> bool {
-        self.local.insert(
-            expr.id,
-            HashMap::from([(some_name.clone(), span_to_range(&input.span))]),
-        );
-        self.traverse_expr(input)
-            && self.traverse_expr(some_branch)
-            && self.traverse_expr(none_branch)
-            && self.visit_match_option(expr, input, some_name, some_branch, none_branch)
-    }
-
-    fn traverse_match_response(
-        &mut self,
-        expr: &'a SymbolicExpression,
-        input: &'a SymbolicExpression,
-        ok_name: &'a ClarityName,
-        ok_branch: &'a SymbolicExpression,
-        err_name: &'a ClarityName,
-        err_branch: &'a SymbolicExpression,
-    ) -> bool {
-        self.local.insert(
-            expr.id,
-            HashMap::from([
-                (ok_name.clone(), span_to_range(&input.span)),
-                (err_name.clone(), span_to_range(&input.span)),
-            ]),
-        );
-        self.traverse_expr(input)
-            && self.traverse_expr(ok_branch)
-            && self.traverse_expr(err_branch)
-            && self.visit_match_response(expr, input, ok_name, ok_branch, err_name, err_branch)
+        components.push((attribute.as_str().to_string(), attribute.span()));
     }
+    components
 }
 
-pub fn get_definitions(
-    expressions: &[SymbolicExpression],
-    issuer: Option<StandardPrincipalData>,
-) -> HashMap<(u32, u32), DefinitionLocation> {
-    let mut definitions_visitor = Definitions::new(issuer);
-    definitions_visitor.run(expressions);
-    definitions_visitor.tokens
-}
-
-pub fn get_public_function_definitions(
-    expressions: &Vec<SymbolicExpression>,
-) -> HashMap<ClarityName, Range> {
-    let mut definitions = HashMap::new();
-
-    for expression in expressions {
-        if let Some((function_name, args)) = expression
-            .match_list()
-            .and_then(|l| l.split_first())
-            .and_then(|(function_name, args)| Some((function_name.match_atom()?, args)))
-        {
-            if let Some(DefineFunctions::PublicFunction | DefineFunctions::ReadOnlyFunction) =
-                DefineFunctions::lookup_by_name(function_name)
-            {
-                if let Some(function_name) = args
-                    .split_first()
-                    .and_then(|(args_list, _)| args_list.match_list()?.split_first())
-                    .and_then(|(function_name, _)| function_name.match_atom())
-                {
-                    definitions.insert(function_name.to_owned(), span_to_range(&expression.span));
-                }
-            }
-        }
-    }
-
-    definitions
+/// Locates the block defining a construct of an embedded runbook. The blocks of an embedded runbook are
+/// stored without their position in the original file, which is parsed again to retrieve it.
+fn get_embedded_construct_definition(
+    embedded_runbook: &EmbeddedRunbookInstance,
+    construct_type: &str,
+    construct_name: &str,
+    sources: &mut SourcesCache,
+) -> Option<(FileLocation, Range)> {
+    let workspace_context = &embedded_runbook.specification.static_workspace_context;
+    let construct_did =
+        workspace_context.packages.values().find_map(|package| match construct_type {
+            "action" => package
+                .commands_did_lookup
+                .get(&CommandId::Action(construct_name.to_string()).to_string()),
+            "variable" => package.variables_did_lookup.get(construct_name),
+            "output" => package.outputs_did_lookup.get(construct_name),
+            "module" => package.modules_did_lookup.get(construct_name),
+            _ => None,
+        })?;
+    let location = &workspace_context.constructs.get(construct_did)?.construct_location;
+    let source = sources.get(location)?;
+    let blocks = RawHclContent::from_string(source.to_string()).into_blocks().ok()?;
+    let name_span = blocks
+        .iter()
+        .find(|block| {
+            block.ident.value().as_str().eq(construct_type)
+                && block.labels.first().map_or(false, |label| label.as_str().eq(construct_name))
+        })
+        .and_then(|block| block.labels.first().and_then(get_label_span))?;
+    Some((location.clone(), unquoted_span_to_range(source, &name_span)))
 }
 
 #[cfg(test)]
-mod definitions_visitor_tests {
-    use std::collections::HashMap;
-
-    use clarity_repl::clarity::ast::build_ast_with_rules;
-    use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
-    use clarity_repl::clarity::vm::types::StandardPrincipalData;
-    use clarity_repl::clarity::StacksEpochId;
-    use clarity_repl::clarity::{ClarityVersion, SymbolicExpression};
+mod tests {
+    use std::path::PathBuf;
+
     use lsp_types::{Position, Range};
+    use txtx_addon_kit::helpers::fs::FileLocation;
+    use txtx_addon_kit::indexmap::IndexMap;
+    use txtx_addon_kit::types::RunbookId;
+    use txtx_addon_kit::Addon;
+    use txtx_core::runbook::{RunbookSources, RunbookTopLevelInputsMap};
 
-    use super::{DefinitionLocation, Definitions};
+    use super::{index_runbook_sources, Definitions, InputsDefinitions, Symbol};
 
-    fn get_ast(source: &str) -> Vec<SymbolicExpression> {
-        let contract_ast = build_ast_with_rules(
-            &QualifiedContractIdentifier::transient(),
-            source,
-            &mut (),
-            ClarityVersion::Clarity1,
-            StacksEpochId::Epoch21,
-            clarity_repl::clarity::ast::ASTRules::Typical,
-        )
-        .unwrap();
-        contract_ast.expressions
+    fn get_no_addons_by_namespace(_namespace: &str) -> Option<Box<dyn Addon>> {
+        None
     }
 
-    fn get_tokens(sources: &str) -> HashMap<(u32, u32), DefinitionLocation> {
-        let ast = get_ast(sources);
-        let mut definitions_visitor = Definitions::new(Some(StandardPrincipalData::transient()));
-        definitions_visitor.run(&ast);
-        definitions_visitor.tokens
+    fn new_range(start_line: u32, start_char: u32, end_line: u32, end_char: u32) -> Range {
+        Range::new(Position::new(start_line, start_char), Position::new(end_line, end_char))
     }
 
-    fn new_range(start_line: u32, start_column: u32, end_line: u32, end_column: u32) -> Range {
-        Range::new(
-            Position::new(start_line, start_column),
-            Position::new(end_line, end_column),
+    fn get_definitions(sources: Vec<(&str, &str)>) -> Definitions {
+        let mut runbook_sources = RunbookSources::new();
+        for (path, content) in sources.into_iter() {
+            runbook_sources.add_source(
+                "test".into(),
+                FileLocation::from_path(PathBuf::from(path)),
+                content.to_string(),
+            );
+        }
+        let environments = IndexMap::from([(
+            "devnet".to_string(),
+            IndexMap::from([("chain_id".to_string(), "11155111".to_string())]),
+        )]);
+        let environment = Some("devnet".to_string());
+        let inputs_map =
+            RunbookTopLevelInputsMap::from_environment_map(&environment, &environments);
+        let flow_context = txtx_addon_kit::futures::executor::block_on(index_runbook_sources(
+            &RunbookId::new(None, None, "test"),
+            &runbook_sources,
+            &inputs_map,
+            &environment,
+            get_no_addons_by_namespace,
+        ))
+        .unwrap();
+        Definitions::build(
+            &flow_context.workspace_context,
+            &flow_context.execution_context,
+            &runbook_sources,
         )
     }
 
     #[test]
-    fn find_define_private_bindings() {
-        let tokens = get_tokens("(define-private (func (arg1 int)) (ok arg1))");
-        assert_eq!(tokens.keys().len(), 1);
-        assert_eq!(tokens.keys().next(), Some(&(1, 39)));
-        assert_eq!(
-            tokens.values().next(),
-            Some(&DefinitionLocation::Internal(new_range(0, 22, 0, 32)))
-        );
-    }
-
-    #[test]
-    fn find_define_read_only_bindings() {
-        let tokens = get_tokens("(define-read-only (func (arg1 int)) (ok arg1))");
-        assert_eq!(tokens.keys().len(), 1);
-        assert_eq!(tokens.keys().next(), Some(&(1, 41)));
-        assert_eq!(
-            tokens.values().next(),
-            Some(&DefinitionLocation::Internal(new_range(0, 24, 0, 34)))
-        );
-    }
-
-    #[test]
-    fn find_define_public_bindings() {
-        let tokens = get_tokens("(define-public (func (arg1 int)) (ok arg1))");
-        assert_eq!(tokens.keys().len(), 1);
-        assert_eq!(tokens.keys().next(), Some(&(1, 38)));
-        assert_eq!(
-            tokens.values().next(),
-            Some(&DefinitionLocation::Internal(new_range(0, 21, 0, 31)))
-        );
-    }
-
-    #[test]
-    fn find_let_bindings() {
-        let tokens = get_tokens("(let ((val1 u1)) (ok val1))");
-        assert_eq!(tokens.len(), 1);
-        assert_eq!(tokens.keys().next(), Some(&(1, 22)));
-        assert_eq!(
-            tokens.values().next(),
-            Some(&DefinitionLocation::Internal(new_range(0, 6, 0, 15)))
-        );
-    }
-
-    #[test]
-    fn find_data_var_definition() {
-        let tokens = get_tokens(
-            [
-                "(define-data-var var1 int 1)",
-                "(var-get var1)",
-                "(var-set var1 2)",
-            ]
-            .join("\n")
-            .as_str(),
-        );
-
-        let expected_range = new_range(0, 0, 0, 28);
-        assert_eq!(tokens.len(), 2);
-        assert_eq!(
-            tokens.get(&(2, 10)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-        assert_eq!(
-            tokens.get(&(3, 10)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-    }
-
-    #[test]
-    fn find_map_definition() {
-        let tokens = get_tokens(
-            [
-                "(define-map owners int principal)",
-                "(map-insert owners 1 tx-sender)",
-                "(map-get? owners 1)",
-                "(map-set owners 1 tx-sender)",
-                "(map-delete owners 1)",
-            ]
-            .join("\n")
-            .as_str(),
-        );
-
-        let expected_range = new_range(0, 0, 0, 33);
-        assert_eq!(tokens.len(), 4);
-        assert_eq!(
-            tokens.get(&(2, 13)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-        assert_eq!(
-            tokens.get(&(3, 11)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-        assert_eq!(
-            tokens.get(&(4, 10)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-        assert_eq!(
-            tokens.get(&(5, 13)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-    }
-
-    #[test]
-    fn find_ft_definition() {
-        let tokens = get_tokens(
-            [
-                "(define-fungible-token ft u1)",
-                "(ft-mint? ft u1 tx-sender)",
-                "(ft-burn? ft u1 tx-sender)",
-                "(ft-get-balance ft tx-sender)",
-                "(ft-get-supply ft)",
-                "(ft-transfer? ft u1 tx-sender tx-sender)",
+    fn find_variable_definition() {
+        let definitions = get_definitions(vec![(
+            "/workspace/runbook.tx",
+            &[
+                "variable \"a\" {",
+                "    value = 1",
+                "}",
+                "variable \"b\" {",
+                "    value = variable.a + 1",
+                "}",
             ]
-            .join("\n")
-            .as_str(),
-        );
-
-        let expected_range = new_range(0, 0, 0, 29);
-        assert_eq!(tokens.len(), 5);
-        assert_eq!(
-            tokens.get(&(2, 11)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-        assert_eq!(
-            tokens.get(&(3, 11)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-        assert_eq!(
-            tokens.get(&(4, 17)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-        assert_eq!(
-            tokens.get(&(5, 16)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-        assert_eq!(
-            tokens.get(&(6, 15)),
-            Some(&DefinitionLocation::Internal(expected_range))
-        );
-    }
-
-    #[test]
-    fn find_definition_in_tuple() {
-        let tokens = get_tokens("(define-public (ok-tuple (arg1 int)) (ok { value: arg1 }))");
+            .join("\n"),
+        )]);
+        let location = FileLocation::from_path(PathBuf::from("/workspace/runbook.tx"));
 
-        assert_eq!(tokens.len(), 1);
-        assert_eq!(
-            tokens.get(&(1, 51)),
-            Some(&DefinitionLocation::Internal(new_range(0, 25, 0, 35)))
-        );
+        let (symbol, range) =
+            definitions.get_symbol_at_position(&location, &Position::new(4, 22)).unwrap();
+        assert_eq!(range, new_range(4, 21, 4, 22));
+        let Symbol::Construct(construct_did) = symbol else { panic!("expected a construct") };
+        let definition = definitions.constructs.get(&construct_did).unwrap();
+        assert_eq!(definition.name, "a");
+        assert_eq!(definition.range.start, Position::new(0, 0));
+        assert_eq!(definition.name_range, new_range(0, 10, 0, 11));
     }
 
     #[test]
-    fn find_definition_in_map() {
-        let tokens =
-            get_tokens("(define-private (double (n int)) (* n 2)) (map double (list 1 2))");
-
-        assert_eq!(tokens.len(), 2);
-        assert_eq!(
-            tokens.get(&(1, 48)),
-            Some(&DefinitionLocation::Internal(new_range(0, 0, 0, 41)))
-        );
+    fn find_references_across_files() {
+        let definitions = get_definitions(vec![
+            ("/workspace/variables.tx", "variable \"a\" {\n    value = 1\n}"),
+            (
+                "/workspace/outputs.tx",
+                "output \"b\" {\n    value = [variable.a, \"${variable.a}\"]\n}",
+            ),
+        ]);
+        let location = FileLocation::from_path(PathBuf::from("/workspace/variables.tx"));
+
+        let (symbol, _) =
+            definitions.get_symbol_at_position(&location, &Position::new(0, 10)).unwrap();
+        let references = definitions.get_references(&symbol);
+        assert_eq!(references.len(), 2);
+        assert!(references
+            .iter()
+            .all(|(location, _)| location.to_string().ends_with("outputs.tx")));
     }
 
     #[test]
-    fn find_definition_in_filter() {
-        let tokens =
-            get_tokens("(define-private (is-even (n int)) (is-eq (* (/ n 2) 2) n)) (filter is-even (list 0 1 2 3 4 5))");
+    fn find_input_references() {
+        let definitions = get_definitions(vec![(
+            "/workspace/runbook.tx",
+            "variable \"chain\" {\n    value = input.chain_id\n}",
+        )]);
 
-        assert_eq!(tokens.len(), 3);
-        assert_eq!(
-            tokens.get(&(1, 68)),
-            Some(&DefinitionLocation::Internal(new_range(0, 0, 0, 58)))
-        );
+        let references = definitions.get_references(&Symbol::Input("chain_id".into()));
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].1, new_range(1, 18, 1, 26));
     }
 
     #[test]
-    fn find_definition_in_fold() {
-        let tokens =
-            get_tokens("(define-private (sum (a int) (b int)) (+ a b)) (fold sum (list 1 2) 0)");
-
-        assert_eq!(tokens.len(), 3);
+    fn find_inputs_definitions_in_manifest() {
+        let manifest = [
+            "---",
+            "name: Workspace",
+            "environments:",
+            "  devnet:",
+            "    chain_id: 11155111",
+            "    \"rpc_api_url\": http://localhost:8545",
+            "  mainnet:",
+            "    chain_id: 1",
+            "runbooks:",
+            "- name: deploy",
+        ]
+        .join("\n");
+        let location = FileLocation::from_path(PathBuf::from("/workspace/txtx.yml"));
+        let inputs = InputsDefinitions::new(&location, &manifest);
+
+        assert_eq!(inputs.inputs.len(), 2);
         assert_eq!(
-            tokens.get(&(1, 54)),
-            Some(&DefinitionLocation::Internal(new_range(0, 0, 0, 46)))
+            inputs.get_occurrences("chain_id").into_iter().map(|(_, r)| r).collect::<Vec<_>>(),
+            vec![new_range(4, 4, 4, 12), new_range(7, 4, 7, 12)]
         );
+        assert_eq!(inputs.get_definition("rpc_api_url").unwrap().1, new_range(5, 5, 5, 16));
+        assert_eq!(inputs.get_definition("name"), None);
     }
 }
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+
+use txtx_addon_kit::hcl::expr::Expression;
+use txtx_addon_kit::hcl::structure::Block;
+use txtx_addon_kit::hcl::Span;
+use txtx_addon_kit::helpers::fs::FileLocation;
+use txtx_addon_kit::helpers::hcl::collect_constructs_references_from_block;
+use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
+use txtx_addon_kit::types::commands::{CommandInput, CommandInstanceType};
+use txtx_addon_kit::types::diagnostics::{Diagnostic, DiagnosticSpan};
+use txtx_addon_kit::types::types::Type;
+use txtx_addon_kit::types::{AuthorizationContext, ConstructDid, PackageId, RunbookId};
+use txtx_addon_kit::Addon;
+use txtx_core::runbook::flow_context::FlowContext;
+use txtx_core::runbook::{Runbook, RunbookSources, RunbookTopLevelInputsMap};
+
+use super::definitions::get_traversal_components;
+use super::helpers::{get_label_span, offset_to_position};
+
+/// Roots of the traversals referencing a construct or a top level input (`action.deploy`, `input.chain_id`, ...)
+const REFERENCES_ROOTS: [&str; 8] =
+    ["action", "signer", "variable", "output", "module", "flow", "runbook", "input"];
+
+/// Checks a runbook the way `txtx check` does, and returns the diagnostics of each of its source files.
+///
+/// The runbook is first built with [Runbook::build_contexts_from_sources], which stops at the first failing
+/// step. The constructs indexed in `flow_context` are then checked statically, so that unresolved references,
+/// missing inputs and type mismatches are reported on the expressions at fault even when the build bailed early.
+/// Every source file has an entry, so that the diagnostics of a fixed file are cleared.
+pub async fn get_runbook_diagnostics(
+    runbook_id: &RunbookId,
+    runbook_sources: &RunbookSources,
+    top_level_inputs_map: &RunbookTopLevelInputsMap,
+    environment_selector: &Option<String>,
+    manifest_location: &Option<FileLocation>,
+    flow_context: Option<&FlowContext>,
+    get_addon_by_namespace: fn(&str) -> Option<Box<dyn Addon>>,
+) -> HashMap<FileLocation, Vec<Diagnostic>> {
+    let authorization_context = match manifest_location {
+        Some(location) => AuthorizationContext::new(location.clone()),
+        None => AuthorizationContext::empty(),
+    };
+    let mut runbook = Runbook::new(runbook_id.clone(), None);
+    let build_diagnostics = runbook
+        .build_contexts_from_sources(
+            runbook_sources.clone(),
+            top_level_inputs_map.clone(),
+            authorization_context,
+            get_addon_by_namespace,
+            CloudServiceContext::empty(),
+        )
+        .await
+        .err()
+        .unwrap_or_default();
+
+    let mut static_diagnostics = vec![];
+    if let Some(flow_context) = flow_context {
+        static_diagnostics.append(&mut check_references(
+            flow_context,
+            runbook_sources,
+            environment_selector,
+        ));
+        static_diagnostics.append(&mut check_constructs_inputs(flow_context));
+    }
+
+    // the graph reports unresolved references on the whole block of a construct, the static checks are more precise
+    let build_diagnostics = build_diagnostics
+        .into_iter()
+        .filter(|diag| !static_diagnostics.iter().any(|d| is_diagnostic_within(d, diag)))
+        .collect::<Vec<_>>();
+
+    let mut locations = runbook_sources.tree.keys().cloned().collect::<Vec<_>>();
+    locations.sort_by_key(|location| location.to_string());
+    let mut diagnostics: HashMap<FileLocation, Vec<Diagnostic>> =
+        locations.iter().map(|location| (location.clone(), vec![])).collect();
+
+    for diagnostic in static_diagnostics.into_iter().chain(build_diagnostics) {
+        // diagnostics not attached to a file are reported on the first one
+        let Some(location) = diagnostic.location.clone().or(locations.first().cloned()) else {
+            continue;
+        };
+        let span = match (runbook_sources.tree.get(&location), diagnostic.span_range()) {
+            (Some((_, raw_content)), Some(span)) => {
+                Some(get_diagnostic_span(&raw_content.to_string(), &span))
+            }
+            _ => diagnostic.span.clone(),
+        };
+        diagnostics.entry(location).or_default().push(diagnostic.set_diagnostic_span(span));
+    }
+    diagnostics
+}
+
+/// Reports the references that can't be resolved: unknown constructs, and top level inputs not defined
+/// in the selected environment.
+fn check_references(
+    flow_context: &FlowContext,
+    runbook_sources: &RunbookSources,
+    environment_selector: &Option<String>,
+) -> Vec<Diagnostic> {
+    let workspace_context = &flow_context.workspace_context;
+    let mut diagnostics = vec![];
+
+    for (location, (package_name, raw_content)) in runbook_sources.tree.iter() {
+        let Ok(package_id) =
+            PackageId::from_file(location, &workspace_context.runbook_id, package_name)
+        else {
+            continue;
+        };
+        let Ok(blocks) = raw_content.into_blocks() else {
+            continue;
+        };
+
+        let mut expressions = vec![];
+        for block in blocks.iter() {
+            collect_constructs_references_from_block::<CommandInput>(block, None, &mut expressions);
+        }
+
+        for (_, expression) in expressions.iter() {
+            let message = match workspace_context
+                .try_resolve_construct_reference_in_expression(&package_id, expression)
+            {
+                Ok(Some(_)) => continue,
+                Err(message) => message,
+                Ok(None) => {
+                    let components = get_traversal_components(expression);
+                    let (Some((root, _)), Some((name, _))) = (components.get(0), components.get(1))
+                    else {
+                        continue;
+                    };
+                    let root = root.to_ascii_lowercase();
+                    if !REFERENCES_ROOTS.contains(&root.as_str()) {
+                        continue;
+                    }
+                    match (root.as_str(), environment_selector) {
+                        ("input", Some(environment)) => {
+                            format!("input '{name}' is not defined in environment '{environment}'")
+                        }
+                        ("input", None) => format!("input '{name}' is not defined"),
+                        ("flow", _) => format!("unknown flow input '{name}'"),
+                        ("runbook", _) => format!("unknown embedded runbook '{name}'"),
+                        (construct_type, _) => format!("unknown {construct_type} '{name}'"),
+                    }
+                }
+            };
+            diagnostics.push(
+                Diagnostic::error_from_string(message)
+                    .location(location)
+                    .set_span_range(expression.span()),
+            );
+        }
+    }
+    diagnostics
+}
+
+/// The inputs of a command or a signer, as declared by its specification.
+struct ConstructInputs<'a> {
+    block: &'a Block,
+    package_id: &'a PackageId,
+    namespace: &'a str,
+    inputs: Vec<&'a CommandInput>,
+    check_typing: bool,
+}
+
+/// Reports the required inputs missing from variables, outputs, actions and signers, and the literal values
+/// not matching the type of the input they're assigned to.
+///
+/// The typing check is a deliberate approximation of `txtx check`, which evaluates the inputs and lets each
+/// addon decode them: only literals are checked, against the conversions the addons commonly perform (see
+/// `is_literal_type_accepted`). It doesn't rely on the `check_instantiability` of the specifications, which
+/// most addons leave unimplemented. Some invalid values are thus only reported by `txtx check`.
+fn check_constructs_inputs(flow_context: &FlowContext) -> Vec<Diagnostic> {
+    let workspace_context = &flow_context.workspace_context;
+    let execution_context = &flow_context.execution_context;
+
+    let mut constructs: Vec<(&ConstructDid, ConstructInputs)> = vec![];
+    for (construct_did, command_instance) in execution_context.commands_instances.iter() {
+        // the value of variables and outputs is typed as a string, but can be of any type
+        let check_typing = match command_instance.typing {
+            CommandInstanceType::Action(_) => true,
+            CommandInstanceType::Variable | CommandInstanceType::Output => false,
+            _ => continue,
+        };
+        let specification = &command_instance.specification;
+        constructs.push((
+            construct_did,
+            ConstructInputs {
+                block: &command_instance.block,
+                package_id: &command_instance.package_id,
+                namespace: &command_instance.namespace,
+                inputs: specification.inputs.iter().chain(&specification.default_inputs).collect(),
+                check_typing,
+            },
+        ));
+    }
+    for (construct_did, signer_instance) in execution_context.signers_instances.iter() {
+        let specification = &signer_instance.specification;
+        constructs.push((
+            construct_did,
+            ConstructInputs {
+                block: &signer_instance.block,
+                package_id: &signer_instance.package_id,
+                namespace: &signer_instance.namespace,
+                inputs: specification.inputs.iter().chain(&specification.default_inputs).collect(),
+                check_typing: true,
+            },
+        ));
+    }
+
+    let mut diagnostics = vec![];
+    for (construct_did, construct) in constructs.into_iter() {
+        let Some(construct_id) = workspace_context.constructs.get(construct_did) else {
+            continue;
+        };
+        let addon_defaults = workspace_context
+            .get_addon_defaults(&(construct.package_id.did(), construct.namespace.to_string()));
+
+        for input in construct.inputs.iter() {
+            let Some(attribute) = construct.block.body.get_attribute(&input.name) else {
+                let is_provided = input.optional
+                    || input.internal
+                    || construct.block.body.get_blocks(&input.name).next().is_some()
+                    || addon_defaults.contains_key(&input.name);
+                if !is_provided {
+                    diagnostics.push(
+                        Diagnostic::error_from_string(format!(
+                            "missing required input '{}' in {} '{}'",
+                            input.name, construct_id.construct_type, construct_id.construct_name
+                        ))
+                        .location(&construct_id.construct_location)
+                        .set_span_range(construct.block.labels.first().and_then(get_label_span)),
+                    );
+                }
+                continue;
+            };
+            if !construct.check_typing {
+                continue;
+            }
+            let Some(literal_type) = get_literal_type(&attribute.value) else {
+                continue;
+            };
+            if !is_literal_type_accepted(&input.typing, literal_type) {
+                diagnostics.push(
+                    Diagnostic::error_from_string(format!(
+                        "input '{}' expects a value of type {}, found {}",
+                        input.name,
+                        input.typing.to_string(),
+                        literal_type
+                    ))
+                    .location(&construct_id.construct_location)
+                    .set_span_range(attribute.value.span()),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Returns the type of a literal expression, `None` if the expression has to be evaluated to be typed.
+fn get_literal_type(expression: &Expression) -> Option<&'static str> {
+    match expression {
+        Expression::Bool(_) => Some("bool"),
+        Expression::Number(_) => Some("number"),
+        Expression::String(_) => Some("string"),
+        Expression::Array(_) => Some("array"),
+        Expression::Object(_) => Some("object"),
+        _ => None,
+    }
+}
+
+/// Whether a literal can be assigned to an input of type `typing`. This is permissive: numbers and buffers are
+/// commonly written as strings (`"0x..."`, `"1000"`), and objects can be decoded from strings.
+fn is_literal_type_accepted(typing: &Type, literal_type: &str) -> bool {
+    match typing {
+        Type::Bool => literal_type.eq("bool"),
+        Type::Integer | Type::Float | Type::String => matches!(literal_type, "number" | "string"),
+        Type::Buffer => matches!(literal_type, "number" | "string" | "array"),
+        Type::Array(_) => literal_type.eq("array"),
+        Type::Object(_) => matches!(literal_type, "object" | "string"),
+        Type::Null | Type::Addon(_) | Type::Map(_) => true,
+    }
+}
+
+fn is_diagnostic_within(diagnostic: &Diagnostic, enclosing: &Diagnostic) -> bool {
+    let (Some(span), Some(enclosing_span)) = (diagnostic.span_range(), enclosing.span_range())
+    else {
+        return false;
+    };
+    diagnostic.location.eq(&enclosing.location)
+        && enclosing_span.start <= span.start
+        && span.end <= enclosing_span.end
+}
+
+// lines and starting columns are 1-based, the ending column is the character where the span ends
+fn get_diagnostic_span(source: &str, span: &std::ops::Range<usize>) -> DiagnosticSpan {
+    let start = offset_to_position(source, span.start);
+    let end = offset_to_position(source, span.end);
+    DiagnosticSpan {
+        line_start: start.line + 1,
+        line_end: end.line + 1,
+        column_start: start.character + 1,
+        column_end: end.character,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use txtx_addon_kit::helpers::fs::FileLocation;
+    use txtx_addon_kit::indexmap::IndexMap;
+    use txtx_addon_kit::types::diagnostics::Diagnostic;
+    use txtx_addon_kit::types::RunbookId;
+    use txtx_addon_kit::Addon;
+    use txtx_core::runbook::{RunbookSources, RunbookTopLevelInputsMap};
+
+    use super::get_runbook_diagnostics;
+    use crate::common::requests::definitions::index_runbook_sources;
+
+    fn get_no_addons_by_namespace(_namespace: &str) -> Option<Box<dyn Addon>> {
+        None
+    }
+
+    fn get_diagnostics(source: &str) -> Vec<Diagnostic> {
+        let location = FileLocation::from_path(PathBuf::from("/workspace/runbook.tx"));
+        let mut runbook_sources = RunbookSources::new();
+        runbook_sources.add_source("test".into(), location.clone(), source.to_string());
+        let environments = IndexMap::from([(
+            "devnet".to_string(),
+            IndexMap::from([("chain_id".to_string(), "11155111".to_string())]),
+        )]);
+        let environment = Some("devnet".to_string());
+        let inputs_map =
+            RunbookTopLevelInputsMap::from_environment_map(&environment, &environments);
+        let runbook_id = RunbookId::new(None, None, "test");
+
+        let mut diagnostics: HashMap<FileLocation, Vec<Diagnostic>> =
+            txtx_addon_kit::futures::executor::block_on(async {
+                let flow_context = index_runbook_sources(
+                    &runbook_id,
+                    &runbook_sources,
+                    &inputs_map,
+                    &environment,
+                    get_no_addons_by_namespace,
+                )
+                .await;
+                get_runbook_diagnostics(
+                    &runbook_id,
+                    &runbook_sources,
+                    &inputs_map,
+                    &environment,
+                    &None,
+                    flow_context.as_ref(),
+                    get_no_addons_by_namespace,
+                )
+                .await
+            });
+        assert_eq!(diagnostics.len(), 1);
+        diagnostics.remove(&location).unwrap()
+    }
+
+    #[test]
+    fn valid_runbook_has_no_diagnostics() {
+        let diagnostics = get_diagnostics(
+            "variable \"a\" {\n    value = input.chain_id\n}\noutput \"b\" {\n    value = variable.a\n}",
+        );
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn report_unknown_construct_on_reference() {
+        let diagnostics = get_diagnostics("output \"b\" {\n    value = variable.missing\n}");
+        let diagnostic =
+            diagnostics.iter().find(|d| d.message.eq("unknown variable 'missing'")).unwrap();
+        let span = diagnostic.span.clone().unwrap();
+        assert_eq!((span.line_start, span.column_start), (2, 13));
+        assert_eq!((span.line_end, span.column_end), (2, 28));
+    }
+
+    #[test]
+    fn report_input_undefined_in_environment() {
+        let diagnostics = get_diagnostics("variable \"a\" {\n    value = input.rpc_api_url\n}");
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.message.eq("input 'rpc_api_url' is not defined in environment 'devnet'")));
+    }
+
+    #[test]
+    fn report_missing_required_input() {
+        let diagnostics = get_diagnostics("variable \"a\" {\n    description = \"value\"\n}");
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.message.eq("missing required input 'value' in variable 'a'")));
+    }
+
+    #[test]
+    fn report_cycling_dependencies() {
+        let diagnostics = get_diagnostics(
+            "variable \"a\" {\n    value = variable.b\n}\nvariable \"b\" {\n    value = variable.a\n}",
+        );
+        assert!(diagnostics.iter().any(|d| d.message.starts_with("Cycling dependency")));
+    }
+}
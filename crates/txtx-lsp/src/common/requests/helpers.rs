@@ -1,97 +1,79 @@
-use std::cmp::Ordering;
-
-use clarity_repl::clarity::{representations::Span, ClarityName, SymbolicExpression};
 use lsp_types::{Position, Range};
+use txtx_addon_kit::hcl::structure::BlockLabel;
+use txtx_addon_kit::hcl::Span;
 
-pub fn span_to_range(span: &Span) -> Range {
-    if span == &Span::zero() {
-        return Range::default();
-    }
-
-    {
-        Range::new(
-            Position::new(span.start_line - 1, span.start_column - 1),
-            Position::new(span.end_line - 1, span.end_column),
-        )
+/// Converts a byte offset of `source` to an lsp [Position], which counts characters in utf-16 code units.
+pub fn offset_to_position(source: &str, offset: usize) -> Position {
+    let mut line = 0;
+    let mut character = 0;
+    for (i, c) in source.char_indices() {
+        if i >= offset {
+            break;
+        }
+        if c == '\n' {
+            line += 1;
+            character = 0;
+        } else {
+            character += c.len_utf16() as u32;
+        }
     }
+    Position::new(line, character)
 }
 
-// end_offset is usded to include the end position of a keyword, for go to definition in particular
-pub fn is_position_within_span(position: &Position, span: &Span, end_offset: u32) -> bool {
-    if position.line < span.start_line || position.line > span.end_line {
-        return false;
-    }
-    if position.line == span.start_line && position.character < span.start_column {
-        return false;
-    }
-    if position.line == span.end_line && position.character > span.end_column + end_offset {
-        return false;
+/// Converts an lsp [Position] to a byte offset of `source`. Positions past the end of a line are
+/// clamped to the end of the line.
+pub fn position_to_offset(source: &str, position: &Position) -> usize {
+    let mut offset = 0;
+    for (i, line) in source.split('\n').enumerate() {
+        if i < position.line as usize {
+            offset += line.len() + 1;
+            continue;
+        }
+        let mut character = 0;
+        for (j, c) in line.char_indices() {
+            if character >= position.character {
+                return offset + j;
+            }
+            character += c.len_utf16() as u32;
+        }
+        return offset + line.len();
     }
+    source.len()
+}
 
-    true
+/// Converts a span of `source`, as produced by the hcl parser, to an lsp [Range].
+pub fn span_to_range(source: &str, span: &std::ops::Range<usize>) -> Range {
+    Range::new(offset_to_position(source, span.start), offset_to_position(source, span.end))
 }
 
-pub fn get_expression_name_at_position(
-    position: &Position,
-    expressions: &Vec<SymbolicExpression>,
-) -> Option<ClarityName> {
-    for expr in expressions {
-        if is_position_within_span(position, &expr.span, 0) {
-            if let Some(function_name) = expr.match_atom() {
-                return Some(function_name.to_owned());
-            } else if let Some(expressions) = expr.match_list() {
-                return get_expression_name_at_position(position, &expressions.to_vec());
-            }
-        }
+/// Same as [span_to_range], excluding the quotes wrapping the span if any (e.g. block labels).
+pub fn unquoted_span_to_range(source: &str, span: &std::ops::Range<usize>) -> Range {
+    let is_quoted = source
+        .get(span.clone())
+        .map_or(false, |s| s.len() >= 2 && s.starts_with('"') && s.ends_with('"'));
+    match is_quoted {
+        true => span_to_range(source, &(span.start + 1..span.end - 1)),
+        false => span_to_range(source, span),
     }
-    None
 }
 
-pub fn get_function_at_position(
-    position: &Position,
-    expressions: &Vec<SymbolicExpression>,
-) -> Option<(ClarityName, Option<u32>)> {
-    for expr in expressions {
-        if is_position_within_span(position, &expr.span, 0) {
-            if let Some(expressions) = expr.match_list() {
-                return get_function_at_position(position, &expressions.to_vec());
-            }
-        }
+// the end of the range is included, so that a cursor placed right after a word still targets it
+pub fn is_position_within_range(position: &Position, range: &Range) -> bool {
+    if position.line < range.start.line || position.line > range.end.line {
+        return false;
     }
-
-    let mut position_in_parameters: i32 = -1;
-    for parameter in expressions {
-        match position.line.cmp(&parameter.span.end_line) {
-            Ordering::Equal => {
-                if position.character > parameter.span.end_column + 1 {
-                    position_in_parameters += 1
-                }
-            }
-            Ordering::Greater => position_in_parameters += 1,
-            _ => {}
-        }
+    if position.line == range.start.line && position.character < range.start.character {
+        return false;
     }
-
-    let (function_name, _) = expressions.split_first()?;
-
-    Some((
-        function_name.match_atom()?.to_owned(),
-        position_in_parameters.try_into().ok(),
-    ))
+    if position.line == range.end.line && position.character > range.end.character {
+        return false;
+    }
+    true
 }
 
-pub fn get_atom_start_at_position(
-    position: &Position,
-    expressions: &Vec<SymbolicExpression>,
-) -> Option<(u32, u32)> {
-    for expr in expressions {
-        if is_position_within_span(position, &expr.span, 1) {
-            if let Some(_function_name) = expr.match_atom() {
-                return Some((expr.span.start_line, expr.span.start_column));
-            } else if let Some(expressions) = expr.match_list() {
-                return get_atom_start_at_position(position, &expressions.to_vec());
-            }
-        }
+pub fn get_label_span(label: &BlockLabel) -> Option<std::ops::Range<usize>> {
+    match label {
+        BlockLabel::String(name) => name.span(),
+        BlockLabel::Ident(name) => name.span(),
     }
-    None
 }
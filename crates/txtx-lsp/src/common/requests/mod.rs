@@ -1 +1,5 @@
 pub mod capabilities;
+pub mod completion;
+pub mod definitions;
+pub mod diagnostics;
+pub mod helpers;
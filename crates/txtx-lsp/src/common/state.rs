@@ -1,24 +1,48 @@
 use lsp_types::{
     CompletionItem, CompletionItemKind, CompletionItemLabelDetails, DocumentSymbol, Hover,
     InsertTextFormat, InsertTextMode, MarkupContent, MarkupKind, MessageType, Position,
-    SignatureHelp,
+    PrepareRenameResponse, Range, SignatureHelp, TextEdit, Url, WorkspaceEdit,
 };
-use std::borrow::BorrowMut;
 use std::collections::{HashMap, HashSet};
 use std::vec;
 use txtx_addon_kit::helpers::fs::{FileAccessor, FileLocation};
+use txtx_addon_kit::helpers::hcl::RawHclContent;
+use txtx_addon_kit::types::commands::PreCommandSpecification;
 use txtx_addon_kit::types::diagnostics::{Diagnostic as TxtxDiagnostic, DiagnosticLevel};
 use txtx_addon_kit::types::RunbookId;
 use txtx_addon_kit::Addon;
 use txtx_addon_network_evm::EvmNetworkAddon;
 use txtx_addon_telegram::TelegramAddon;
+use txtx_core::manifest::file::read_runbook_from_location;
+use txtx_core::manifest::WorkspaceManifest;
+use txtx_core::runbook::RunbookSources;
 use txtx_core::std::StdAddon;
 
 use super::requests::capabilities::InitializationOptions;
+use super::requests::completion::{
+    get_actions_command_ids, get_command_completion_items, get_command_hover, CommandDocumentation,
+};
+use super::requests::definitions::{index_runbook_sources, Definitions, InputsDefinitions, Symbol};
+use super::requests::diagnostics::get_runbook_diagnostics;
+
+pub fn get_available_addons() -> Vec<Box<dyn Addon>> {
+    vec![
+        Box::new(StdAddon::new()),
+        Box::new(EvmNetworkAddon::new()),
+        Box::new(TelegramAddon::new()),
+    ]
+}
+
+pub fn get_addon_by_namespace(namespace: &str) -> Option<Box<dyn Addon>> {
+    get_available_addons().into_iter().find(|addon| namespace.starts_with(&addon.get_namespace()))
+}
 
 lazy_static! {
+    pub static ref COMMANDS: Vec<CommandDocumentation> =
+        CommandDocumentation::from_addons(&get_available_addons());
+
     pub static ref FUNCTIONS: Vec<CompletionItem> = {
-        let addons: Vec<Box<dyn Addon>> = vec![Box::new(StdAddon::new()), Box::new(EvmNetworkAddon::new()), Box::new(TelegramAddon::new())];
+        let addons = get_available_addons();
         let mut completion_items = vec![];
         for addon in addons.iter() {
             for func in addon.get_functions() {
@@ -111,11 +135,13 @@ lazy_static! {
     };
 
     pub static ref ACTIONS: Vec<CompletionItem> = {
-        let addons: Vec<Box<dyn Addon>> = vec![Box::new(StdAddon::new()), Box::new(EvmNetworkAddon::new()), Box::new(TelegramAddon::new())];
+        let addons = get_available_addons();
         let mut completion_items = vec![];
         for addon in addons.iter() {
             for action in addon.get_actions() {
-                let spec = action.expect_atomic_specification();
+                let PreCommandSpecification::Atomic(spec) = action else {
+                    continue;
+                };
                 completion_items.push(lsp_types::CompletionItem {
                     // The label of this completion item. By default
                     // also the text that is inserted when selecting
@@ -203,7 +229,7 @@ lazy_static! {
 
 
     pub static ref WALLETS: Vec<CompletionItem> = {
-        let addons: Vec<Box<dyn Addon>> = vec![Box::new(StdAddon::new()), Box::new(EvmNetworkAddon::new()), Box::new(TelegramAddon::new())];
+        let addons = get_available_addons();
         let mut completion_items = vec![];
         for addon in addons.iter() {
             for signer in addon.get_signers() {
@@ -369,25 +395,13 @@ impl EditorState {
     }
 
     pub fn index_workspace(&mut self, manifest_location: FileLocation, workspace: WorkspaceState) {
-        let mut base_location = manifest_location.clone();
-
-        match base_location.borrow_mut() {
-            FileLocation::FileSystem { path } => {
-                let mut parent = path.clone();
-                parent.pop();
-                parent.pop();
-            }
-            FileLocation::Url { url } => {
-                let mut segments = url.path_segments_mut().expect("could not find root location");
-                segments.pop();
-                segments.pop();
-            }
-        };
+        let base_location =
+            manifest_location.get_parent_location().unwrap_or(manifest_location.clone());
 
         for (runbook_location, _runbook_state) in workspace.runbooks.iter() {
             let relative_path = runbook_location
                 .get_relative_path_from_base(&base_location)
-                .expect("could not find relative location");
+                .unwrap_or(runbook_location.to_string());
 
             self.runbooks_lookup.insert(
                 runbook_location.clone(),
@@ -401,6 +415,20 @@ impl EditorState {
         self.workspaces.insert(manifest_location, workspace);
     }
 
+    /// Returns an empty workspace state, set up with the selected environment and the unsaved sources
+    /// of the active runbooks.
+    pub fn new_workspace_state(&self) -> WorkspaceState {
+        WorkspaceState {
+            environment: self.settings.environment.clone(),
+            unsaved_sources: self
+                .active_runbooks
+                .iter()
+                .map(|(location, runbook)| (location.clone(), runbook.source.clone()))
+                .collect(),
+            ..WorkspaceState::default()
+        }
+    }
+
     pub fn clear_workspace(&mut self, manifest_location: &FileLocation) {
         if let Some(workspace) = self.workspaces.remove(manifest_location) {
             for (runbook_location, _) in workspace.runbooks.iter() {
@@ -425,37 +453,34 @@ impl EditorState {
 
     pub fn get_completion_items_for_runbook(
         &self,
-        _runbook_location: &FileLocation,
-        _position: &Position,
+        runbook_location: &FileLocation,
+        position: &Position,
     ) -> Vec<lsp_types::CompletionItem> {
-        // let active_runbook = match self.active_runbooks.get(runbook_location) {
-        //     Some(contract) => contract,
-        //     None => return vec![],
-        // };
-
-        // let modules = self
-        //     .runbooks_lookup
-        //     .get(runbook_location)
-        //     .and_then(|d| self.workspaces.get(&d.manifest_location))
-        //     .map(|p| p.get_contract_calls_for_contract(runbook_location))
-        //     .unwrap_or_default();
-
-        // let expressions = active_runbook.expressions.as_ref();
-        // let active_contract_defined_data =
-        // ContractDefinedData::new(expressions.unwrap_or(&vec![]), position);
-
-        // build_completion_item_list(
-        //     &active_runbook.clarity_version,
-        //     expressions.unwrap_or(&vec![]),
-        //     &Position {
-        //         line: position.line + 1,
-        //         character: position.character + 1,
-        //     },
-        //     &active_contract_defined_data,
-        //     contract_calls,
-        //     should_wrap,
-        //     self.settings.completion_include_native_placeholders,
-        // )
+        if let Some(active_runbook) = self.active_runbooks.get(runbook_location) {
+            // the active file may have unsaved changes, its actions take precedence over the indexed ones
+            let mut actions_command_ids = self
+                .get_runbook_definitions(runbook_location)
+                .map(|(_, definitions)| {
+                    definitions
+                        .constructs
+                        .values()
+                        .filter(|d| d.construct_type.eq("action"))
+                        .filter_map(|d| Some((d.name.clone(), d.command_id.clone()?)))
+                        .collect::<HashMap<_, _>>()
+                })
+                .unwrap_or_default();
+            actions_command_ids.extend(get_actions_command_ids(&active_runbook.source));
+
+            if let Some(completion_items) = get_command_completion_items(
+                &COMMANDS,
+                &actions_command_ids,
+                &active_runbook.source,
+                position,
+            ) {
+                return completion_items;
+            }
+        }
+
         let functions = FUNCTIONS.clone();
         let mut actions = ACTIONS.clone();
         let mut signers = WALLETS.clone();
@@ -472,42 +497,151 @@ impl EditorState {
         vec![]
     }
 
+    fn get_runbook_definitions(
+        &self,
+        runbook_location: &FileLocation,
+    ) -> Option<(&WorkspaceState, &Definitions)> {
+        let RunbookMetadata { manifest_location, .. } =
+            self.runbooks_lookup.get(runbook_location)?;
+        let workspace = self.workspaces.get(manifest_location)?;
+        let runbook = workspace.runbooks.get(runbook_location)?;
+        let definitions = workspace.definitions.get(&runbook.runbook_id.name)?;
+        Some((workspace, definitions))
+    }
+
     pub fn get_definition_location(
         &self,
-        _runbook_location: &FileLocation,
-        _position: &Position,
+        runbook_location: &FileLocation,
+        position: &Position,
     ) -> Option<lsp_types::Location> {
-        // let runbook = self.active_runbooks.get(runbook_location)?;
-        // let _position = Position {
-        //     line: position.line + 1,
-        //     character: position.character + 1,
-        // };
-        None
+        let (workspace, definitions) = self.get_runbook_definitions(runbook_location)?;
+
+        if let Some((location, range)) =
+            definitions.get_embedded_definition_at_position(runbook_location, position)
+        {
+            return to_lsp_location(&location, range);
+        }
+
+        let (symbol, _) = definitions.get_symbol_at_position(runbook_location, position)?;
+        let (location, range) = match symbol {
+            Symbol::Input(name) => workspace.inputs.get_definition(&name)?,
+            Symbol::Construct(construct_did) => {
+                let definition = definitions.constructs.get(&construct_did)?;
+                (definition.location.clone(), definition.name_range)
+            }
+        };
+        to_lsp_location(&location, range)
+    }
+
+    pub fn get_references(
+        &self,
+        runbook_location: &FileLocation,
+        position: &Position,
+        include_declaration: bool,
+    ) -> Vec<lsp_types::Location> {
+        let Some((workspace, definitions)) = self.get_runbook_definitions(runbook_location) else {
+            return vec![];
+        };
+        let Some((symbol, _)) = definitions.get_symbol_at_position(runbook_location, position)
+        else {
+            return vec![];
+        };
+        workspace
+            .get_symbol_occurrences(definitions, &symbol, include_declaration)
+            .into_iter()
+            .filter_map(|(location, range)| to_lsp_location(&location, range))
+            .collect()
+    }
+
+    pub fn prepare_rename(
+        &self,
+        runbook_location: &FileLocation,
+        position: &Position,
+    ) -> Option<PrepareRenameResponse> {
+        let (_, definitions) = self.get_runbook_definitions(runbook_location)?;
+        let (symbol, range) = definitions.get_symbol_at_position(runbook_location, position)?;
+        let placeholder = match symbol {
+            Symbol::Input(name) => name,
+            Symbol::Construct(construct_did) => {
+                let definition = definitions.constructs.get(&construct_did)?;
+                // addons are named after their namespace, and imports after their location
+                if ["addon", "import"].contains(&definition.construct_type.as_str()) {
+                    return None;
+                }
+                definition.name.clone()
+            }
+        };
+        Some(PrepareRenameResponse::RangeWithPlaceholder { range, placeholder })
+    }
+
+    pub fn get_rename_edits(
+        &self,
+        runbook_location: &FileLocation,
+        position: &Position,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>, String> {
+        let Some((workspace, definitions)) = self.get_runbook_definitions(runbook_location) else {
+            return Ok(None);
+        };
+        let Some((symbol, _)) = definitions.get_symbol_at_position(runbook_location, position)
+        else {
+            return Ok(None);
+        };
+
+        let mut chars = new_name.chars();
+        let is_valid_identifier =
+            chars.next().map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !is_valid_identifier {
+            return Err(format!("'{}' is not a valid identifier", new_name));
+        }
+
+        match &symbol {
+            Symbol::Input(_) => {
+                if workspace.inputs.inputs.contains_key(new_name) {
+                    return Err(format!("input '{}' is already defined", new_name));
+                }
+            }
+            Symbol::Construct(construct_did) => {
+                let Some(definition) = definitions.constructs.get(construct_did) else {
+                    return Ok(None);
+                };
+                if ["addon", "import"].contains(&definition.construct_type.as_str()) {
+                    return Err(format!("{} blocks can not be renamed", definition.construct_type));
+                }
+                if definitions.constructs.values().any(|d| {
+                    d.construct_type.eq(&definition.construct_type)
+                        && d.package_id.eq(&definition.package_id)
+                        && d.name.eq(new_name)
+                }) {
+                    return Err(format!(
+                        "{} '{}' is already defined",
+                        definition.construct_type, new_name
+                    ));
+                }
+            }
+        }
+
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for (location, range) in workspace.get_symbol_occurrences(definitions, &symbol, true) {
+            let Some(lsp_location) = to_lsp_location(&location, range) else {
+                continue;
+            };
+            changes
+                .entry(lsp_location.uri)
+                .or_default()
+                .push(TextEdit { range, new_text: new_name.to_string() });
+        }
+        Ok(Some(WorkspaceEdit { changes: Some(changes), ..Default::default() }))
     }
 
     pub fn get_hover_data(
         &self,
-        _runbook_location: &FileLocation,
-        _position: &lsp_types::Position,
+        runbook_location: &FileLocation,
+        position: &lsp_types::Position,
     ) -> Option<Hover> {
-        // let runbook = self.active_runbooks.get(runbook_location)?;
-        // let position = Position {
-        //     line: position.line + 1,
-        //     character: position.character + 1,
-        // };
-        // let documentation = get_expression_documentation(
-        //     &position,
-        //     contract.clarity_version,
-        //     contract.expressions.as_ref()?,
-        // )?;
-
-        Some(Hover {
-            contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
-                kind: lsp_types::MarkupKind::Markdown,
-                value: "hover".to_string(),
-            }),
-            range: None,
-        })
+        let active_runbook = self.active_runbooks.get(runbook_location)?;
+        get_command_hover(&COMMANDS, &active_runbook.source, position)
     }
 
     pub fn get_signature_help(
@@ -602,14 +736,14 @@ impl EditorState {
     pub fn update_active_contract(
         &mut self,
         runbook_location: &FileLocation,
-        _source: &str,
+        source: &str,
         _with_definitions: bool,
     ) -> Result<(), String> {
-        let _runbook = self
+        let runbook = self
             .active_runbooks
             .get_mut(runbook_location)
             .ok_or("contract not in active_contracts")?;
-        // runbook.update_sources(source, with_definitions);
+        runbook.source = source.to_string();
         Ok(())
     }
 }
@@ -617,6 +751,13 @@ impl EditorState {
 #[derive(Clone, Default, Debug)]
 pub struct WorkspaceState {
     runbooks: HashMap<FileLocation, RunbookState>,
+    /// Definitions and references of each runbook, indexed by runbook name
+    definitions: HashMap<String, Definitions>,
+    inputs: InputsDefinitions,
+    /// Environment the runbooks are checked against, defaults to the first environment of the manifest
+    environment: Option<String>,
+    /// Contents of the files being edited, taking precedence over the ones on disk
+    unsaved_sources: HashMap<FileLocation, String>,
 }
 
 impl WorkspaceState {
@@ -624,6 +765,60 @@ impl WorkspaceState {
         WorkspaceState::default()
     }
 
+    /// Returns every occurrence of a symbol across the workspace. A file can be shared by several
+    /// runbooks (e.g. embedded runbooks), in which case its constructs are matched by location.
+    fn get_symbol_occurrences(
+        &self,
+        definitions: &Definitions,
+        symbol: &Symbol,
+        include_declaration: bool,
+    ) -> Vec<(FileLocation, Range)> {
+        let mut occurrences = vec![];
+        match symbol {
+            Symbol::Input(name) => {
+                if include_declaration {
+                    occurrences.append(&mut self.inputs.get_occurrences(name));
+                }
+                for runbook_definitions in self.definitions.values() {
+                    occurrences.append(&mut runbook_definitions.get_references(symbol));
+                }
+            }
+            Symbol::Construct(construct_did) => {
+                let Some(definition) = definitions.constructs.get(construct_did) else {
+                    return vec![];
+                };
+                if include_declaration {
+                    occurrences.push((definition.location.clone(), definition.name_range));
+                }
+                for runbook_definitions in self.definitions.values() {
+                    if let Some((construct_did, _)) = runbook_definitions
+                        .find_construct_defined_at(&definition.location, &definition.name_range)
+                    {
+                        occurrences.append(
+                            &mut runbook_definitions
+                                .get_references(&Symbol::Construct(construct_did.clone())),
+                        );
+                    }
+                    for reference in runbook_definitions.embedded_references.iter() {
+                        if reference.definition_location.eq(&definition.location)
+                            && reference.definition_range.eq(&definition.name_range)
+                        {
+                            occurrences.push((reference.location.clone(), reference.range));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut deduplicated: Vec<(FileLocation, Range)> = vec![];
+        for occurrence in occurrences.into_iter() {
+            if !deduplicated.contains(&occurrence) {
+                deduplicated.push(occurrence);
+            }
+        }
+        deduplicated
+    }
+
     // pub fn consolidate(
     //     &mut self,
     //     locations: &mut HashMap<QualifiedContractIdentifier, FileLocation>,
@@ -696,99 +891,102 @@ impl WorkspaceState {
     // }
 }
 
+fn to_lsp_location(location: &FileLocation, range: Range) -> Option<lsp_types::Location> {
+    let uri = Url::parse(&location.to_url_string().ok()?).ok()?;
+    Some(lsp_types::Location { uri, range })
+}
+
 pub async fn build_state(
-    _manifest_location: &FileLocation,
-    _workspace_state: &mut WorkspaceState,
-    _file_accessor: Option<&dyn FileAccessor>,
+    manifest_location: &FileLocation,
+    workspace_state: &mut WorkspaceState,
+    file_accessor: Option<&dyn FileAccessor>,
 ) -> Result<(), String> {
-    // let manifest = match file_accessor {
-    //     None => WorkspaceManifest::from_location(manifest_location)?,
-    //     Some(file_accessor) => {
-    //         WorkspaceManifest::from_file_accessor(manifest_location, file_accessor).await?
-    //     }
-    // };
-
-    //     let (_manifest, _runbook_name, mut runbook, runbook_state) =
-    //     load_runbook_from_manifest(&cmd.manifest_path, &cmd.runbook, &cmd.environment).await?;
-
-    // match &runbook_state {
-    //     Some(RunbookState::File(state_file_location)) => {
-    //         let ctx = RunbookSnapshotContext::new();
-    //         let old = load_runbook_execution_snapshot(state_file_location)?;
-    //         for run in runbook.running_contexts.iter_mut() {
-    //             let frontier = HashSet::new();
-    //             let _res = run
-    //                 .execution_context
-    //                 .simulate_execution(
-    //                     &runbook.runtime_context,
-    //                     &run.workspace_context,
-    //                     &runbook.supervision_context,
-    //                     &frontier,
-    //                 )
-    //                 .await;
-    //         }
-
-    // let (deployment, mut artifacts) = generate_default_deployment(
-    //     &manifest,
-    //     &StacksNetwork::Simnet,
-    //     false,
-    //     file_accessor,
-    //     Some(StacksEpochId::Epoch21),
-    // )
-    // .await?;
-
-    // let mut session = initiate_session_from_deployment(&manifest);
-    // let UpdateSessionExecutionResult { contracts, .. } = update_session_with_contracts_executions(
-    //     &mut session,
-    //     &deployment,
-    //     Some(&artifacts.asts),
-    //     false,
-    //     Some(StacksEpochId::Epoch21),
-    // );
-    // for (contract_id, mut result) in contracts.into_iter() {
-    //     let (_, runbook_location) = match deployment.contracts.get(&contract_id) {
-    //         Some(entry) => entry,
-    //         None => continue,
-    //     };
-    //     locations.insert(contract_id.clone(), runbook_location.clone());
-    //     if let Some(contract_metadata) = manifest.contracts_settings.get(runbook_location) {
-    //         clarity_versions.insert(contract_id.clone(), contract_metadata.clarity_version);
-    //     }
-
-    //     match result {
-    //         Ok(mut execution_result) => {
-    //             if let Some(entry) = artifacts.diags.get_mut(&contract_id) {
-    //                 entry.append(&mut execution_result.diagnostics);
-    //             }
-
-    //             if let EvaluationResult::Contract(contract_result) = execution_result.result {
-    //                 if let Some(ast) = artifacts.asts.get(&contract_id) {
-    //                     definitions.insert(
-    //                         contract_id.clone(),
-    //                         get_public_function_definitions(&ast.expressions),
-    //                     );
-    //                 }
-    //                 analyses.insert(contract_id.clone(), Some(contract_result.contract.analysis));
-    //             };
-    //         }
-    //         Err(ref mut diags) => {
-    //             if let Some(entry) = artifacts.diags.get_mut(&contract_id) {
-    //                 entry.append(diags);
-    //             }
-    //             continue;
-    //         }
-    //     };
-    // }
+    let (manifest, manifest_source) = match file_accessor {
+        None => (
+            WorkspaceManifest::from_location(manifest_location)?,
+            manifest_location.read_content_as_utf8()?,
+        ),
+        Some(file_accessor) => (
+            WorkspaceManifest::from_file_accessor(manifest_location, file_accessor).await?,
+            file_accessor.read_file(manifest_location.to_string()).await?,
+        ),
+    };
+    workspace_state.inputs = InputsDefinitions::new(manifest_location, &manifest_source);
+
+    let environment = workspace_state
+        .environment
+        .clone()
+        .or(manifest.environments.first().map(|(name, _)| name.clone()));
+    let top_level_inputs_map = manifest.get_runbook_inputs(&environment, &vec![], None)?;
+    let root_location = manifest_location.get_parent_location()?;
+
+    for runbook_metadata in manifest.runbooks.iter() {
+        let mut location = root_location.clone();
+        location.append_path(&runbook_metadata.location)?;
+
+        let mut runbook_sources = match file_accessor {
+            None => match read_runbook_from_location(
+                &location,
+                &runbook_metadata.description,
+                &environment,
+                Some(&runbook_metadata.name),
+            ) {
+                Ok((_, _, runbook_sources)) => runbook_sources,
+                Err(_) => continue,
+            },
+            Some(file_accessor) => {
+                let Ok(content) = file_accessor.read_file(location.to_string()).await else {
+                    continue;
+                };
+                let mut runbook_sources = RunbookSources::new();
+                runbook_sources.add_source(runbook_metadata.name.clone(), location, content);
+                runbook_sources
+            }
+        };
+        // the runbooks are checked as they are being edited
+        for (runbook_location, (_, raw_content)) in runbook_sources.tree.iter_mut() {
+            if let Some(source) = workspace_state.unsaved_sources.get(runbook_location) {
+                *raw_content = RawHclContent::from_string(source.clone());
+            }
+        }
 
-    // protocol_state.consolidate(
-    //     &mut locations,
-    //     &mut artifacts.asts,
-    //     &mut artifacts.deps,
-    //     &mut artifacts.diags,
-    //     &mut definitions,
-    //     &mut analyses,
-    //     &mut clarity_versions,
-    // );
+        let runbook_id = RunbookId::new(None, None, &runbook_metadata.name);
+        let flow_context = index_runbook_sources(
+            &runbook_id,
+            &runbook_sources,
+            &top_level_inputs_map,
+            &environment,
+            get_addon_by_namespace,
+        )
+        .await;
+        let definitions = match &flow_context {
+            Some(flow_context) => Definitions::build(
+                &flow_context.workspace_context,
+                &flow_context.execution_context,
+                &runbook_sources,
+            ),
+            None => Definitions::default(),
+        };
+        let mut diagnostics = get_runbook_diagnostics(
+            &runbook_id,
+            &runbook_sources,
+            &top_level_inputs_map,
+            &environment,
+            &Some(manifest_location.clone()),
+            flow_context.as_ref(),
+            get_addon_by_namespace,
+        )
+        .await;
+
+        for (runbook_location, _) in runbook_sources.tree.iter() {
+            let diags = diagnostics.remove(runbook_location).unwrap_or_default();
+            workspace_state.runbooks.insert(
+                runbook_location.clone(),
+                RunbookState::new(runbook_id.clone(), diags, runbook_location.clone()),
+            );
+        }
+        workspace_state.definitions.insert(runbook_metadata.name.clone(), definitions);
+    }
 
     Ok(())
 }
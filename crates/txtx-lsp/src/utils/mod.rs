@@ -29,8 +29,11 @@ pub fn txtx_diagnostic_to_lsp_type(diagnostic: &TxtxDiagnostic) -> LspDiagnostic
     let range = match &diagnostic.span {
         None => Range::default(),
         Some(span) => Range {
-            start: Position { line: span.line_start - 1, character: span.column_start - 1 },
-            end: Position { line: span.line_end - 1, character: span.column_end },
+            start: Position {
+                line: span.line_start.saturating_sub(1),
+                character: span.column_start.saturating_sub(1),
+            },
+            end: Position { line: span.line_end.saturating_sub(1), character: span.column_end },
         },
     };
     // TODO(lgalabru): add hint for contracts not found errors
@@ -1,21 +1,23 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error as StdError;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::Poll;
 use std::time::Duration;
 
 use actix_cors::Cors;
 use actix_web::dev::ServerHandle;
 use actix_web::http::header::{self};
 use actix_web::http::StatusCode;
-use actix_web::web::{self, Data, Json};
+use actix_web::web::{self, Data, Json, Path};
 use actix_web::{middleware, App, HttpRequest, HttpResponse, HttpServer};
 use actix_web::{Error, HttpResponseBuilder, Responder};
-use hiro_system_kit::green;
 use juniper_actix::{graphql_handler, subscriptions};
 use juniper_graphql_ws::ConnectionConfig;
-use serde::ser::StdError;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use tokio::sync::RwLock;
+use tokio::sync::{oneshot, RwLock};
 use txtx_addon_kit::types::cloud_interface::CloudServiceContext;
 use txtx_addon_kit::Addon;
 use txtx_addon_network_bitcoin::BitcoinNetworkAddon;
@@ -30,20 +32,17 @@ use txtx_addon_sp1::Sp1Addon;
 use txtx_addon_telegram::TelegramAddon;
 use txtx_core::kit::channel;
 use txtx_core::kit::helpers::fs::FileLocation;
-use txtx_core::kit::types::frontend::{
-    ActionItemRequest, BlockEvent, ClientType, DiscoveryResponse,
-};
-use txtx_core::kit::types::{AuthorizationContext, RunbookId};
+use txtx_core::kit::indexmap::IndexMap;
+use txtx_core::kit::types::frontend::{Block, BlockEvent, ClientType, DiscoveryResponse};
+use txtx_core::kit::types::AuthorizationContext;
 use txtx_core::kit::uuid::Uuid;
-use txtx_core::runbook::RunbookTopLevelInputsMap;
-use txtx_core::start_supervised_runbook_runloop;
+use txtx_core::manifest::file::read_runbooks_from_manifest;
+use txtx_core::manifest::{RunbookStateLocation, WorkspaceManifest};
+use txtx_core::runbook::{RunbookSnapshotContext, SynthesizedChange};
+use txtx_core::start_unsupervised_runbook_runloop;
 use txtx_core::std::StdAddon;
-use txtx_core::types::{Runbook, RunbookSources};
-use txtx_gql::Context as GqlContext;
+use txtx_core::types::Runbook;
 use txtx_gql::{new_graphql_schema, Context as GraphContext, GraphqlSchema};
-use txtx_supervisor_ui::cloud_relayer::{
-    start_relayer_event_runloop, RelayerChannelEvent, RelayerContext,
-};
 
 pub const SERVE_BINDING_PORT: &str = "18488";
 pub const SERVE_BINDING_ADDRESS: &str = "localhost";
@@ -74,21 +73,51 @@ fn get_addon_by_namespace(namespace: &str) -> Option<Box<dyn Addon>> {
     None
 }
 
+/// State shared by the request handlers: the workspace manifest the runbooks are loaded from,
+/// and the executions started since the server was started.
+pub struct ServeContext {
+    pub manifest: WorkspaceManifest,
+    pub executions: RwLock<IndexMap<Uuid, RunbookExecution>>,
+    cancellation_handles: RwLock<HashMap<Uuid, oneshot::Sender<()>>>,
+}
+
+impl ServeContext {
+    pub fn new(manifest: WorkspaceManifest) -> Self {
+        Self {
+            manifest,
+            executions: RwLock::new(IndexMap::new()),
+            cancellation_handles: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Applies the final status of an execution, which can no longer be cancelled.
+    async fn complete_execution(
+        &self,
+        execution_id: &Uuid,
+        update: impl FnOnce(&mut RunbookExecution),
+    ) {
+        if let Some(execution) = self.executions.write().await.get_mut(execution_id) {
+            update(execution);
+        }
+        self.cancellation_handles.write().await.remove(execution_id);
+    }
+}
+
 pub async fn start_server(
     network_binding: &str,
-    // ctx: &Context,
+    manifest_path: &str,
 ) -> Result<ServerHandle, Box<dyn StdError>> {
-    // info!(ctx.expect_logger(), "Starting server {}", network_binding);
-
-    // let boxed_ctx = Data::new(ctx.clone());
-
-    let gql_context: Data<RwLock<Option<GqlContext>>> = Data::new(RwLock::new(None));
+    let manifest_location = FileLocation::from_path_string(manifest_path)?;
+    let manifest = WorkspaceManifest::from_location(&manifest_location)?;
+    let serve_context = Data::new(ServeContext::new(manifest));
+    // Context of the latest execution, see `execute_runbook`
+    let gql_context: Data<RwLock<Option<GraphContext>>> = Data::new(RwLock::new(None));
 
     let server = HttpServer::new(move || {
         App::new()
+            .app_data(serve_context.clone())
             .app_data(gql_context.clone())
             .app_data(Data::new(new_graphql_schema()))
-            // .app_data(boxed_ctx.clone())
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -100,20 +129,7 @@ pub async fn start_server(
             )
             .wrap(middleware::Compress::default())
             .wrap(middleware::Logger::default())
-            .service(
-                web::scope("/api/v1")
-                    .route("/runbooks/check", web::post().to(register_runbook))
-                    .route("/runbooks/run", web::post().to(execute_runbook))
-                    .route("/runbooks/run/state", web::get().to(execute_runbook))
-                    .route("/discovery", web::get().to(discovery)),
-            )
-            .route("/ping", web::get().to(check_service_health))
-            .service(
-                web::scope("/gql/v1")
-                    .route("/graphql?<request..>", web::get().to(get_graphql))
-                    .route("/graphql", web::post().to(post_graphql))
-                    .route("/subscriptions", web::get().to(subscriptions)),
-            )
+            .configure(configure_routes)
     })
     .workers(5)
     .bind(network_binding)?
@@ -122,369 +138,311 @@ pub async fn start_server(
     let handle = server.handle();
     tokio::spawn(server);
 
-    // Declare a pool of threads
-    //
-
     Ok(handle)
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub struct RunbookRegistrationRequest {
-    hcl_source: String,
+fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/v1")
+            .route("/runbooks", web::get().to(list_runbooks))
+            .route("/runbooks/check", web::post().to(check_runbook))
+            .route("/runbooks/run", web::post().to(execute_runbook))
+            .route("/runbooks/executions", web::get().to(list_executions))
+            .route("/runbooks/{execution_id}", web::get().to(get_execution))
+            .route("/runbooks/{execution_id}/outputs", web::get().to(get_execution_outputs))
+            .route("/runbooks/{execution_id}/cancel", web::post().to(cancel_execution))
+            .route("/discovery", web::get().to(discovery)),
+    )
+    .route("/ping", web::get().to(check_service_health))
+    // Progress of the latest execution, see `execute_runbook`
+    .service(
+        web::scope("/gql/v1")
+            .route("/graphql?<request..>", web::get().to(get_graphql))
+            .route("/graphql", web::post().to(post_graphql))
+            .route("/subscriptions", web::get().to(subscriptions)),
+    );
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub struct RunbookRegistrationResponse {
-    runbook_uuid: Uuid,
+pub struct RunbookRequest {
+    /// Name of the runbook, as declared in the workspace manifest
+    runbook: String,
+    /// Environment to load from the workspace manifest
+    environment: Option<String>,
+    /// Inputs overriding the values of the environment
+    #[serde(default)]
+    inputs: IndexMap<String, JsonValue>,
+    /// Ignore the previous execution state and execute all the constructs
+    #[serde(default)]
+    force: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub struct StartRunbookExecutionRequest {
-    id: String,
+pub struct RunbookMetadataResponse {
     name: String,
-    description: String,
-    constructs: Vec<ConstructRequest>,
-    hcl_source_legacy: Option<String>,
+    description: Option<String>,
+    location: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub struct ConstructRequest {
-    construct_type: String,
-    id: String,
-    description: String,
-    value: Option<JsonValue>,
-    action_id: Option<String>,
-    namespace: Option<String>,
-    inputs: Option<BTreeMap<String, JsonValue>>,
+pub struct RunbookCheckResponse {
+    runbook: String,
+    environment: Option<String>,
+    diagnostics: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub enum RunbookExecutionStepState {
+pub struct StartRunbookExecutionResponse {
+    execution_id: Uuid,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunbookExecutionStatus {
+    Running,
     Succeeded,
     Failed,
-    Current,
-    Next,
+    Cancelled,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub struct RunbookExecutionStep {
-    state: RunbookExecutionStepState,
+pub struct RunbookExecution {
+    execution_id: Uuid,
+    runbook: String,
+    environment: Option<String>,
+    status: RunbookExecutionStatus,
+    /// Set when the previous execution state was already in sync with the runbook
+    skipped: bool,
+    diagnostics: Vec<String>,
+    #[serde(skip_serializing)]
+    outputs: Option<JsonValue>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub struct RunbookExecutionStateResponse {
-    runbook_uuid: Uuid,
-    steps: Vec<RunbookExecutionStep>,
+pub struct ErrorResponse {
+    error: String,
 }
 
-pub async fn check_service_health(
-    _req: HttpRequest,
-    // ctx: Data<Context>,
-    // graph_context: Data<GraphContext>,
-) -> actix_web::Result<HttpResponse> {
-    // info!(ctx.expect_logger(), "{} {}", req.method().as_str(), req.path());
+fn error_response(status: StatusCode, error: String) -> HttpResponse {
+    HttpResponseBuilder::new(status).json(ErrorResponse { error })
+}
 
+pub async fn check_service_health(_req: HttpRequest) -> actix_web::Result<HttpResponse> {
     Ok(HttpResponseBuilder::new(StatusCode::OK).json(true))
 }
 
-pub async fn register_runbook(
+pub async fn list_runbooks(
     _req: HttpRequest,
-    // ctx: Data<Context>,
-    _payload: Json<RunbookRegistrationRequest>,
-    // relayer_context: Data<RelayerContext>,
-    // graph_context: Data<GraphContext>,
+    serve_context: Data<ServeContext>,
 ) -> actix_web::Result<HttpResponse> {
-    // info!(ctx.expect_logger(), "{} {}", req.method().as_str(), req.path());
+    let runbooks = serve_context
+        .manifest
+        .runbooks
+        .iter()
+        .map(|r| RunbookMetadataResponse {
+            name: r.name.clone(),
+            description: r.description.clone(),
+            location: r.location.clone(),
+        })
+        .collect::<Vec<_>>();
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(runbooks))
+}
 
-    Ok(HttpResponseBuilder::new(StatusCode::OK).json(true))
+pub async fn check_runbook(
+    _req: HttpRequest,
+    payload: Json<RunbookRequest>,
+    serve_context: Data<ServeContext>,
+) -> actix_web::Result<HttpResponse> {
+    let diagnostics = match load_runbook_from_manifest(&serve_context.manifest, &payload).await {
+        Ok(_) => vec![],
+        Err(diagnostics) => diagnostics,
+    };
+    let status =
+        if diagnostics.is_empty() { StatusCode::OK } else { StatusCode::UNPROCESSABLE_ENTITY };
+    Ok(HttpResponseBuilder::new(status).json(RunbookCheckResponse {
+        runbook: payload.runbook.clone(),
+        environment: payload.environment.clone(),
+        diagnostics,
+    }))
 }
 
 pub async fn execute_runbook(
     _req: HttpRequest,
-    // ctx: Data<Context>,
-    payload: Json<StartRunbookExecutionRequest>,
-    // relayer_context: Data<RelayerContext>,
+    payload: Json<RunbookRequest>,
+    serve_context: Data<ServeContext>,
     gql_context: Data<RwLock<Option<GraphContext>>>,
 ) -> actix_web::Result<HttpResponse> {
-    // info!(ctx.expect_logger(), "{} {}", req.method().as_str(), req.path());
-
-    let mut reconstructed_source = "".to_string();
-    let mut required_addons = HashSet::new();
-    for construct in payload.constructs.iter() {
-        reconstructed_source.push_str(&construct.construct_type);
-        reconstructed_source.push_str(&format!(" \"{}\"", &construct.id));
-        if let Some(ref namespace) = construct.namespace {
-            required_addons.insert(namespace);
-        }
-        let command_id = match (&construct.namespace, &construct.action_id) {
-            (Some(namespace), Some(id)) => format!("\"{}::{}\"", namespace, id),
-            (None, Some(id)) => format!("\"{}\"", id),
-            (Some(namespace), None) => format!(" \"{}\"", namespace),
-            (None, None) => format!(""),
-        };
-        reconstructed_source.push_str(&format!(" {} {{\n", &command_id));
-        if construct.construct_type.eq("variable") || construct.construct_type.eq("output") {
-            reconstructed_source
-                .push_str(&format!("  description = \"{}\"\n", construct.description));
-            reconstructed_source.push_str(&format!("  editable = true\n"));
-
-            if let Some(ref value) = construct.value {
-                match value {
-                    JsonValue::Null => {}
-                    JsonValue::String(value) if value.starts_with("$") => {
-                        reconstructed_source.push_str(&format!("  value = {}\n", &value[1..]));
-                    }
-                    JsonValue::String(value) => {
-                        reconstructed_source.push_str(&format!("  value = \"{}\"\n", value));
-                    }
-                    JsonValue::Number(value) => {
-                        reconstructed_source.push_str(&format!("  value = {}\n", &value));
-                    }
-                    _ => unreachable!(),
-                }
-            }
-        } else if construct.construct_type.eq("action") {
-            if let Some(ref inputs) = construct.inputs {
-                for (key, value) in inputs.iter() {
-                    match value {
-                        JsonValue::Null => {}
-                        JsonValue::String(value) if value.eq("null") => {}
-                        JsonValue::String(value) if value.starts_with("$") => {
-                            reconstructed_source.push_str(&format!(
-                                "  {} = {}\n",
-                                key,
-                                &value[1..]
-                            ));
-                        }
-                        JsonValue::String(value) => {
-                            reconstructed_source.push_str(&format!("  {} = \"{}\"\n", key, value));
-                        }
-                        JsonValue::Number(value) => {
-                            reconstructed_source.push_str(&format!("  {} = {}\n", key, &value));
-                        }
-                        _ => unreachable!(),
-                    }
-                }
+    let (runbook, runbook_state_location) =
+        match load_runbook_from_manifest(&serve_context.manifest, &payload).await {
+            Ok(res) => res,
+            Err(diagnostics) => {
+                return Ok(HttpResponseBuilder::new(StatusCode::UNPROCESSABLE_ENTITY).json(
+                    RunbookCheckResponse {
+                        runbook: payload.runbook.clone(),
+                        environment: payload.environment.clone(),
+                        diagnostics,
+                    },
+                ))
             }
-        }
-        reconstructed_source.push_str(&format!("}}\n\n"));
-    }
-    for addon in required_addons.iter() {
-        reconstructed_source.push_str(&format!("addon \"{}\" {{\n", addon));
-        reconstructed_source.push_str(&format!(" chain_id = 11155111\n"));
-        reconstructed_source.push_str(&format!(
-            " rpc_api_url = \"https://sepolia.infura.io/v3/a063e95957aa4fd29319b2a53c31d481\"\n"
-        ));
-        reconstructed_source.push_str(&format!("}}\n\n"));
-
-        reconstructed_source
-            .push_str(&format!("signer \"account\" \"{}::web_wallet\" {{\n", addon));
-        reconstructed_source.push_str(&format!(" description = \"Account\"\n"));
-        reconstructed_source.push_str(&format!("}}\n\n"));
-    }
-    println!("{}", reconstructed_source);
-
-    let runbook_name = payload.name.clone();
-    let runbook_description = Some(payload.description.clone());
-    let runbook_source = reconstructed_source;
-    let dummy_location =
-        FileLocation::from_path_string("/tmp/file.tx").map_err(|e| Box::<dyn StdError>::from(e))?;
-
-    let mut runbook_sources = RunbookSources::new();
-    runbook_sources.add_source(runbook_name.clone(), dummy_location, runbook_source);
-    let runbook_id = RunbookId { org: None, workspace: None, name: runbook_name.clone() };
-    let mut runbook = Runbook::new(runbook_id, runbook_description);
+        };
 
-    let runbook_inputs = RunbookTopLevelInputsMap::new();
-    let authorization_context = AuthorizationContext::empty();
-    runbook
-        .build_contexts_from_sources(
-            runbook_sources,
-            runbook_inputs,
-            authorization_context,
-            get_addon_by_namespace,
-            CloudServiceContext::empty(),
-        )
-        .await
-        .unwrap();
+    // Concurrent executions of a runbook against the same environment are rejected, the lock
+    // being held until the end of the execution
+    let state_lock = match &runbook_state_location {
+        Some(state_file_location) => match state_file_location
+            .acquire_lock(
+                &runbook.runbook_id.name,
+                &runbook.top_level_inputs_map.current_top_level_input_name(),
+                Duration::ZERO,
+            )
+            .await
+        {
+            Ok(state_lock) => Some(state_lock),
+            Err(e) => return Ok(error_response(StatusCode::CONFLICT, e)),
+        },
+        None => None,
+    };
 
-    runbook.enable_full_execution_mode();
-    // info!(ctx.expect_logger(), "2");
-    let runbook_description = runbook.description.clone();
-    let registered_addons = runbook
-        .runtime_context
-        .addons_context
-        .registered_addons
-        .keys()
-        .map(|k| k.clone())
-        .collect::<Vec<_>>();
-    let (block_tx, block_rx) = channel::unbounded::<BlockEvent>();
+    let execution_id = Uuid::new_v4();
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    serve_context.executions.write().await.insert(
+        execution_id,
+        RunbookExecution {
+            execution_id,
+            runbook: payload.runbook.clone(),
+            environment: payload.environment.clone(),
+            status: RunbookExecutionStatus::Running,
+            skipped: false,
+            diagnostics: vec![],
+            outputs: None,
+        },
+    );
+    serve_context.cancellation_handles.write().await.insert(execution_id, cancel_tx);
+
+    // The GraphQL API only reflects the latest execution: its context is replaced by each new
+    // execution, concurrent or not. Queries and new subscriptions then target the new execution,
+    // while the subscriptions opened earlier keep receiving the events of the previous one.
+    let (progress_tx, progress_rx) = channel::unbounded::<BlockEvent>();
     let (block_broadcaster, _) = tokio::sync::broadcast::channel(5);
-    let (_action_item_updates_tx, _action_item_updates_rx) =
-        channel::unbounded::<ActionItemRequest>();
-    let (action_item_events_tx, action_item_events_rx) = tokio::sync::broadcast::channel(32);
+    let (action_item_events_tx, _) = tokio::sync::broadcast::channel(32);
     let block_store = Arc::new(RwLock::new(BTreeMap::new()));
-    let (kill_loops_tx, kill_loops_rx) = channel::bounded(1);
-    let (relayer_channel_tx, relayer_channel_rx) = channel::unbounded();
-
-    let moved_block_tx = block_tx.clone();
-    let moved_kill_loops_tx = kill_loops_tx.clone();
-    // let moved_ctx = ctx.clone();
-
-    let _ = hiro_system_kit::thread_named("Runbook Runloop").spawn(move || {
-        let runloop_future =
-            start_supervised_runbook_runloop(&mut runbook, moved_block_tx, action_item_events_rx);
-        if let Err(diags) = hiro_system_kit::nestable_block_on(runloop_future) {
-            for _diag in diags.iter() {
-                // error!(moved_ctx.expect_logger(), "Runbook execution failed: {}", diag.message);
-            }
-            // if let Err(e) = write_runbook_transient_state(&mut runbook, moved_runbook_state) {
-            //     println!("{} Failed to write transient runbook state: {}", red!("x"), e);
-            // };
-        } else {
-            // if let Err(e) = write_runbook_state(&mut runbook, moved_runbook_state) {
-            //     println!("{} Failed to write runbook state: {}", red!("x"), e);
-            // };
-        }
-        if let Err(_e) = moved_kill_loops_tx.send(true) {
-            // std::process::exit(1);
-        }
-    });
-
-    // start web ui server
     {
         let mut gql_context = gql_context.write().await;
-        *gql_context = Some(GqlContext {
-            protocol_name: runbook_name.clone(),
-            runbook_name: runbook_name.clone(),
-            registered_addons,
-            runbook_description,
+        *gql_context = Some(GraphContext {
+            protocol_name: runbook.runbook_id.name.clone(),
+            runbook_name: runbook.runbook_id.name.clone(),
+            registered_addons: runbook
+                .runtime_context
+                .addons_context
+                .registered_addons
+                .keys()
+                .map(|k| k.clone())
+                .collect(),
+            runbook_description: runbook.description.clone(),
             block_store: block_store.clone(),
             block_broadcaster: block_broadcaster.clone(),
-            action_item_events_tx: action_item_events_tx.clone(),
+            action_item_events_tx,
         });
     }
-
-    let channel_data = Arc::new(RwLock::new(None));
-    let _relayer_context = RelayerContext {
-        relayer_channel_tx: relayer_channel_tx.clone(),
-        channel_data: channel_data.clone(),
-    };
-
-    let moved_relayer_channel_tx = relayer_channel_tx.clone();
-    let moved_kill_loops_tx = kill_loops_tx.clone();
-    let moved_action_item_events_tx = action_item_events_tx.clone();
-    let _ = hiro_system_kit::thread_named("Relayer Interaction").spawn(move || {
-        let future = start_relayer_event_runloop(
-            channel_data,
-            relayer_channel_rx,
-            moved_relayer_channel_tx,
-            moved_action_item_events_tx,
-            moved_kill_loops_tx,
-        );
-        hiro_system_kit::nestable_block_on(future)
-    });
-
-    let moved_relayer_channel_tx = relayer_channel_tx.clone();
-    let _block_store_handle = tokio::spawn(async move {
-        loop {
-            if let Ok(mut block_event) = block_rx.try_recv() {
-                let mut block_store = block_store.write().await;
-                let mut do_propagate_event = true;
-                match block_event.clone() {
-                    BlockEvent::Action(new_block) => {
-                        let len = block_store.len();
-                        block_store.insert(len, new_block.clone());
-                    }
-                    BlockEvent::Clear => {
-                        *block_store = BTreeMap::new();
-                    }
-                    BlockEvent::UpdateActionItems(updates) => {
-                        // for action item updates, track if we actually changed anything before propagating the event
-                        do_propagate_event = false;
-                        let mut filtered_updates = vec![];
-                        for update in updates.iter() {
-                            for (_, block) in block_store.iter_mut() {
-                                let did_update = block.apply_action_item_updates(update.clone());
-                                if did_update {
-                                    do_propagate_event = true;
-                                    filtered_updates.push(update.clone());
-                                }
-                            }
-                        }
-                        block_event = BlockEvent::UpdateActionItems(filtered_updates);
-                    }
-                    BlockEvent::Modal(new_block) => {
-                        let len = block_store.len();
-                        block_store.insert(len, new_block.clone());
-                    }
-                    BlockEvent::ProgressBar(new_block) => {
-                        let len = block_store.len();
-                        block_store.insert(len, new_block.clone());
-                    }
-                    BlockEvent::UpdateProgressBarStatus(update) => block_store
-                        .iter_mut()
-                        .filter(|(_, b)| b.uuid == update.progress_bar_uuid)
-                        .for_each(|(_, b)| {
-                            b.update_progress_bar_status(&update.construct_did, &update.new_status)
-                        }),
-                    BlockEvent::UpdateProgressBarVisibility(update) => block_store
-                        .iter_mut()
-                        .filter(|(_, b)| b.uuid == update.progress_bar_uuid)
-                        .for_each(|(_, b)| b.visible = update.visible),
-                    BlockEvent::RunbookCompleted => {
-                        println!("\n{}", green!("Runbook complete!"));
-                        break;
-                    }
-                    BlockEvent::Error(new_block) => {
-                        let len = block_store.len();
-                        block_store.insert(len, new_block.clone());
-                    }
-                    BlockEvent::Exit => break,
-                }
-
-                if do_propagate_event {
-                    let _ = block_broadcaster.send(block_event.clone());
-                    let _ = moved_relayer_channel_tx
-                        .send(RelayerChannelEvent::ForwardEventToRelayer(block_event.clone()));
+    tokio::spawn(forward_progress_events(progress_rx, block_store, block_broadcaster));
+
+    let moved_serve_context = serve_context.clone();
+    let force_execution = payload.force;
+    let _ = hiro_system_kit::thread_named(&format!("Runbook Runloop {}", execution_id)).spawn(
+        move || {
+            let future = run_runbook_execution(
+                execution_id,
+                runbook,
+                runbook_state_location,
+                force_execution,
+                progress_tx,
+                cancel_rx,
+                moved_serve_context,
+            );
+            hiro_system_kit::nestable_block_on(future);
+            if let Some(state_lock) = state_lock {
+                if let Err(e) = state_lock.release() {
+                    println!("unable to release the lock of execution {}: {}", execution_id, e);
                 }
             }
+        },
+    );
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-            // println!("waiting for next block event");
-        }
-    });
-
-    let _ = hiro_system_kit::thread_named("Kill Runloops Thread")
-        .spawn(move || {
-            let future = async {
-                match kill_loops_rx.recv() {
-                    Ok(_) => {
-                        let _ = block_tx.send(BlockEvent::Exit);
-                        let _ = relayer_channel_tx.send(RelayerChannelEvent::Exit);
-                    }
-                    Err(_) => {}
-                };
-            };
+    Ok(HttpResponseBuilder::new(StatusCode::ACCEPTED)
+        .json(StartRunbookExecutionResponse { execution_id }))
+}
 
-            hiro_system_kit::nestable_block_on(future)
-        })
-        .unwrap();
+pub async fn list_executions(
+    _req: HttpRequest,
+    serve_context: Data<ServeContext>,
+) -> actix_web::Result<HttpResponse> {
+    let executions = serve_context.executions.read().await.values().cloned().collect::<Vec<_>>();
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(executions))
+}
 
-    // info!(ctx.expect_logger(), "Attempt to initialize execution channel");
-    Ok(HttpResponseBuilder::new(StatusCode::OK).json(true))
+pub async fn get_execution(
+    _req: HttpRequest,
+    execution_id: Path<Uuid>,
+    serve_context: Data<ServeContext>,
+) -> actix_web::Result<HttpResponse> {
+    let executions = serve_context.executions.read().await;
+    let Some(execution) = executions.get(&execution_id) else {
+        return Ok(error_response(
+            StatusCode::NOT_FOUND,
+            format!("execution {} not found", execution_id),
+        ));
+    };
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(execution))
 }
 
-async fn discovery() -> impl Responder {
-    HttpResponse::Ok()
-        .json(DiscoveryResponse { needs_credentials: false, client_type: ClientType::Operator })
+pub async fn get_execution_outputs(
+    _req: HttpRequest,
+    execution_id: Path<Uuid>,
+    serve_context: Data<ServeContext>,
+) -> actix_web::Result<HttpResponse> {
+    let executions = serve_context.executions.read().await;
+    let Some(execution) = executions.get(&execution_id) else {
+        return Ok(error_response(
+            StatusCode::NOT_FOUND,
+            format!("execution {} not found", execution_id),
+        ));
+    };
+    if execution.status != RunbookExecutionStatus::Succeeded {
+        return Ok(error_response(
+            StatusCode::CONFLICT,
+            format!("execution {} did not succeed", execution_id),
+        ));
+    }
+    Ok(HttpResponseBuilder::new(StatusCode::OK)
+        .json(execution.outputs.clone().unwrap_or(JsonValue::Object(Default::default()))))
+}
+
+pub async fn cancel_execution(
+    _req: HttpRequest,
+    execution_id: Path<Uuid>,
+    serve_context: Data<ServeContext>,
+) -> actix_web::Result<HttpResponse> {
+    if serve_context.executions.read().await.get(&execution_id).is_none() {
+        return Ok(error_response(
+            StatusCode::NOT_FOUND,
+            format!("execution {} not found", execution_id),
+        ));
+    }
+    let Some(cancel_tx) = serve_context.cancellation_handles.write().await.remove(&execution_id)
+    else {
+        return Ok(error_response(
+            StatusCode::CONFLICT,
+            format!("execution {} is not running", execution_id),
+        ));
+    };
+    let _ = cancel_tx.send(());
+    Ok(HttpResponseBuilder::new(StatusCode::ACCEPTED).json(true))
 }
 
 async fn post_graphql(
@@ -492,10 +450,8 @@ async fn post_graphql(
     payload: web::Payload,
     schema: Data<GraphqlSchema>,
     context: Data<RwLock<Option<GraphContext>>>,
-    // ctx: Data<Context>,
 ) -> Result<HttpResponse, Error> {
-    // info!(ctx.expect_logger(), "{} {}", req.method().as_str(), req.path());
-    let context = context.write().await;
+    let context = context.read().await;
     let Some(context) = context.as_ref() else {
         return Err(actix_web::error::ErrorServiceUnavailable("Service Unavailable"));
     };
@@ -507,9 +463,7 @@ async fn get_graphql(
     payload: web::Payload,
     schema: Data<GraphqlSchema>,
     context: Data<RwLock<Option<GraphContext>>>,
-    // ctx: Data<Context>,
 ) -> Result<HttpResponse, Error> {
-    // info!(ctx.expect_logger(), "{} {}", req.method().as_str(), req.path());
     let context = context.read().await;
     let Some(context) = context.as_ref() else {
         return Err(actix_web::error::ErrorServiceUnavailable("Service Unavailable"));
@@ -522,9 +476,7 @@ async fn subscriptions(
     stream: web::Payload,
     schema: Data<GraphqlSchema>,
     context: Data<RwLock<Option<GraphContext>>>,
-    // ctx: Data<Context>,
 ) -> Result<HttpResponse, Error> {
-    // info!(ctx.expect_logger(), "{} {}", req.method().as_str(), req.path());
     let context = context.read().await;
     let Some(context) = context.as_ref() else {
         return Err(actix_web::error::ErrorServiceUnavailable("Service Unavailable"));
@@ -542,3 +494,335 @@ async fn subscriptions(
     let config = config.with_keep_alive_interval(Duration::from_secs(15));
     subscriptions::ws_handler(req, stream, schema.into_inner(), config).await
 }
+
+async fn discovery() -> impl Responder {
+    HttpResponse::Ok()
+        .json(DiscoveryResponse { needs_credentials: false, client_type: ClientType::Operator })
+}
+
+/// Loads the runbook requested from the workspace manifest, with the requested environment and
+/// inputs, and builds its contexts.
+async fn load_runbook_from_manifest(
+    manifest: &WorkspaceManifest,
+    request: &RunbookRequest,
+) -> Result<(Runbook, Option<RunbookStateLocation>), Vec<String>> {
+    let inputs = request
+        .inputs
+        .iter()
+        .map(|(key, value)| match value {
+            JsonValue::String(value) => format!("{}={}", key, value),
+            value => format!("{}={}", key, value.to_string()),
+        })
+        .collect::<Vec<_>>();
+    let top_level_inputs_map =
+        manifest.get_runbook_inputs(&request.environment, &inputs, None).map_err(|e| vec![e])?;
+
+    let environment_selector =
+        request.environment.clone().or(manifest.environments.first().map(|(k, _)| k.clone()));
+
+    let mut runbooks = read_runbooks_from_manifest(
+        manifest,
+        &environment_selector,
+        Some(&vec![request.runbook.clone()]),
+    )
+    .map_err(|e| vec![e])?;
+    let Some((mut runbook, runbook_sources, _, runbook_state_location)) =
+        runbooks.swap_remove(&request.runbook)
+    else {
+        return Err(vec![format!("unable to retrieve runbook '{}' in manifest", request.runbook)]);
+    };
+
+    let authorization_context = AuthorizationContext::new(manifest.location.clone().unwrap());
+    runbook
+        .build_contexts_from_sources(
+            runbook_sources,
+            top_level_inputs_map,
+            authorization_context,
+            get_addon_by_namespace,
+            CloudServiceContext::empty(),
+        )
+        .await
+        .map_err(|diags| diags.iter().map(|d| d.to_string()).collect::<Vec<_>>())?;
+
+    Ok((runbook, runbook_state_location))
+}
+
+async fn run_runbook_execution(
+    execution_id: Uuid,
+    mut runbook: Runbook,
+    runbook_state_location: Option<RunbookStateLocation>,
+    force_execution: bool,
+    progress_tx: channel::Sender<BlockEvent>,
+    cancel_rx: oneshot::Receiver<()>,
+    serve_context: Data<ServeContext>,
+) {
+    runbook.enable_full_execution_mode();
+
+    if !force_execution {
+        match prepare_runbook_for_previous_state(&mut runbook, &runbook_state_location).await {
+            Ok(true) => {}
+            Ok(false) => {
+                serve_context
+                    .complete_execution(&execution_id, |execution| {
+                        execution.status = RunbookExecutionStatus::Succeeded;
+                        execution.skipped = true;
+                    })
+                    .await;
+                return;
+            }
+            Err(e) => {
+                serve_context
+                    .complete_execution(&execution_id, |execution| {
+                        execution.status = RunbookExecutionStatus::Failed;
+                        execution.diagnostics = vec![e];
+                    })
+                    .await;
+                return;
+            }
+        }
+    }
+
+    let res = run_until_cancelled(
+        start_unsupervised_runbook_runloop(&mut runbook, &progress_tx),
+        cancel_rx,
+    )
+    .await;
+
+    let (status, diagnostics, outputs) = match res {
+        None => {
+            let _ = runbook.mark_failed_and_write_transient_state(runbook_state_location);
+            (RunbookExecutionStatus::Cancelled, vec![], None)
+        }
+        Some(Err(diags)) => {
+            let _ = runbook.mark_failed_and_write_transient_state(runbook_state_location);
+            (RunbookExecutionStatus::Failed, diags.iter().map(|d| d.to_string()).collect(), None)
+        }
+        Some(Ok(())) => {
+            let outputs = runbook.collect_formatted_outputs().to_json();
+            match runbook.write_runbook_state(runbook_state_location) {
+                Ok(_) => (RunbookExecutionStatus::Succeeded, vec![], Some(outputs)),
+                Err(e) => (
+                    RunbookExecutionStatus::Failed,
+                    vec![format!("failed to write runbook state: {}", e)],
+                    Some(outputs),
+                ),
+            }
+        }
+    };
+
+    let _ = progress_tx.send(BlockEvent::RunbookCompleted);
+    serve_context
+        .complete_execution(&execution_id, |execution| {
+            execution.status = status;
+            execution.diagnostics = diagnostics;
+            execution.outputs = outputs;
+        })
+        .await;
+}
+
+/// Diffs the runbook against its previous execution state, and prepares the flows so that only
+/// the new and updated constructs get executed. Returns false if the runbook is in sync with
+/// its previous execution, in which case there is nothing to execute.
+async fn prepare_runbook_for_previous_state(
+    runbook: &mut Runbook,
+    runbook_state_location: &Option<RunbookStateLocation>,
+) -> Result<bool, String> {
+    let Some(state_file_location) = runbook_state_location else {
+        return Ok(true);
+    };
+    let Ok(old) = state_file_location.load_execution_snapshot(
+        true,
+        &runbook.runbook_id.name,
+        &runbook.top_level_inputs_map.current_top_level_input_name(),
+    ) else {
+        return Ok(true);
+    };
+
+    let ctx = RunbookSnapshotContext::new();
+    let execution_context_backups = runbook.backup_execution_contexts();
+    let new = runbook.simulate_and_snapshot_flows(&old).await?;
+    let consolidated_changes =
+        ctx.diff(old, new).map_err(|e| format!("failed to process snapshot: {}", e))?;
+
+    let has_changes_to_execute =
+        consolidated_changes.get_synthesized_changes().iter().any(|(c, _)| match c {
+            SynthesizedChange::Edition(_, critical) => *critical,
+            SynthesizedChange::FormerFailure(_, _) => true,
+            SynthesizedChange::Addition(_) => false,
+        });
+    if !has_changes_to_execute && consolidated_changes.new_plans_to_add.is_empty() {
+        return Ok(false);
+    }
+
+    runbook.prepare_flows_for_new_plans(
+        &consolidated_changes.new_plans_to_add,
+        execution_context_backups,
+    );
+    let _ = runbook.prepared_flows_for_updated_plans(&consolidated_changes.plans_to_update);
+    Ok(true)
+}
+
+/// Applies the progress events of an execution to the block store queried by the GraphQL API,
+/// and broadcasts them to the subscribers, until the execution completes.
+async fn forward_progress_events(
+    progress_rx: channel::Receiver<BlockEvent>,
+    block_store: Arc<RwLock<BTreeMap<usize, Block>>>,
+    block_broadcaster: tokio::sync::broadcast::Sender<BlockEvent>,
+) {
+    loop {
+        let block_event = match progress_rx.try_recv() {
+            Ok(block_event) => block_event,
+            Err(channel::TryRecvError::Empty) => {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                continue;
+            }
+            Err(channel::TryRecvError::Disconnected) => break,
+        };
+        if let BlockEvent::RunbookCompleted | BlockEvent::Exit = block_event {
+            break;
+        }
+        let mut block_store = block_store.write().await;
+        if let Some(block_event) = apply_block_event(&mut block_store, block_event) {
+            let _ = block_broadcaster.send(block_event);
+        }
+    }
+}
+
+/// Applies a progress event to the block store. Returns the event to broadcast, if the event
+/// changed the store.
+fn apply_block_event(
+    block_store: &mut BTreeMap<usize, Block>,
+    block_event: BlockEvent,
+) -> Option<BlockEvent> {
+    match block_event {
+        BlockEvent::Action(ref new_block)
+        | BlockEvent::Modal(ref new_block)
+        | BlockEvent::ProgressBar(ref new_block)
+        | BlockEvent::Error(ref new_block) => {
+            let len = block_store.len();
+            block_store.insert(len, new_block.clone());
+        }
+        BlockEvent::Clear => {
+            *block_store = BTreeMap::new();
+        }
+        BlockEvent::UpdateActionItems(updates) => {
+            let mut filtered_updates = vec![];
+            for update in updates.iter() {
+                for (_, block) in block_store.iter_mut() {
+                    if block.apply_action_item_updates(update.clone()) {
+                        filtered_updates.push(update.clone());
+                    }
+                }
+            }
+            if filtered_updates.is_empty() {
+                return None;
+            }
+            return Some(BlockEvent::UpdateActionItems(filtered_updates));
+        }
+        BlockEvent::UpdateProgressBarStatus(ref update) => {
+            block_store.iter_mut().filter(|(_, b)| b.uuid == update.progress_bar_uuid).for_each(
+                |(_, b)| b.update_progress_bar_status(&update.construct_did, &update.new_status),
+            )
+        }
+        BlockEvent::UpdateProgressBarVisibility(ref update) => block_store
+            .iter_mut()
+            .filter(|(_, b)| b.uuid == update.progress_bar_uuid)
+            .for_each(|(_, b)| b.visible = update.visible),
+        BlockEvent::RunbookCompleted | BlockEvent::Exit => {}
+    }
+    Some(block_event)
+}
+
+/// Drives `future` to completion, unless a cancellation is received first.
+async fn run_until_cancelled<F: Future>(
+    future: F,
+    mut cancel_rx: oneshot::Receiver<()>,
+) -> Option<F::Output> {
+    let mut future = std::pin::pin!(future);
+    let mut is_cancel_tx_dropped = false;
+    std::future::poll_fn(move |cx| {
+        if !is_cancel_tx_dropped {
+            match Pin::new(&mut cancel_rx).poll(cx) {
+                Poll::Ready(Ok(())) => return Poll::Ready(None),
+                Poll::Ready(Err(_)) => is_cancel_tx_dropped = true,
+                Poll::Pending => {}
+            }
+        }
+        future.as_mut().poll(cx).map(Some)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test;
+    use txtx_core::manifest::RunbookMetadata;
+
+    use super::*;
+
+    macro_rules! init_app {
+        ($manifest:expr) => {
+            test::init_service(
+                App::new()
+                    .app_data(Data::new(ServeContext::new($manifest)))
+                    .app_data(Data::new(RwLock::new(None::<GraphContext>)))
+                    .app_data(Data::new(new_graphql_schema()))
+                    .configure(configure_routes),
+            )
+            .await
+        };
+    }
+
+    #[actix_web::test]
+    async fn it_lists_the_runbooks_of_the_manifest() {
+        let mut manifest = WorkspaceManifest::new("test".to_string());
+        manifest.runbooks.push(RunbookMetadata::new("deploy", "deploy", None));
+        let app = init_app!(manifest);
+
+        let req = test::TestRequest::get().uri("/api/v1/runbooks").to_request();
+        let runbooks: Vec<RunbookMetadataResponse> = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(runbooks.len(), 1);
+        assert_eq!(runbooks[0].name, "deploy");
+    }
+
+    #[actix_web::test]
+    async fn it_rejects_unknown_executions() {
+        let app = init_app!(WorkspaceManifest::new("test".to_string()));
+        let execution_id = Uuid::new_v4();
+
+        let req = test::TestRequest::get().uri(&format!("/api/v1/runbooks/{}", execution_id));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let req =
+            test::TestRequest::post().uri(&format!("/api/v1/runbooks/{}/cancel", execution_id));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let req = test::TestRequest::get().uri("/api/v1/runbooks/executions").to_request();
+        let executions: Vec<RunbookExecution> = test::call_and_read_body_json(&app, req).await;
+        assert!(executions.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn it_serves_graphql_once_an_execution_started() {
+        let app = init_app!(WorkspaceManifest::new("test".to_string()));
+
+        let req = test::TestRequest::post()
+            .uri("/gql/v1/graphql")
+            .set_json(serde_json::json!({ "query": "{ __typename }" }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn it_drops_action_item_updates_not_matching_any_block() {
+        let mut block_store = BTreeMap::new();
+        assert!(
+            apply_block_event(&mut block_store, BlockEvent::UpdateActionItems(vec![])).is_none()
+        );
+        assert!(apply_block_event(&mut block_store, BlockEvent::Clear).is_some());
+        assert!(block_store.is_empty());
+    }
+}